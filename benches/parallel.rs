@@ -0,0 +1,33 @@
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use simple_tensor::tensor::{ParallelismConfig, Tensor, TensorPromise, materialize_all};
+
+// 200 independent graphs of a few ops over [64, 64] tensors, reading a shared weight.
+fn small_graphs() -> Vec<TensorPromise<f64>> {
+    let weight = Tensor::from_iter((0..64 * 64).map(|i| (i % 7) as f64 / 7.0), &[64, 64]);
+
+    (0..200)
+        .map(|i| {
+            let x = Tensor::from_iter((0..64 * 64).map(|j| ((i + j) % 11) as f64), &[64, 64]);
+            ((&x * &weight + 1.0).sqrt() / (&x + 2.0)).transpose()
+        })
+        .collect()
+}
+
+fn materialize_200_graphs(c: &mut Criterion) {
+    let promises = small_graphs();
+    let mut group = c.benchmark_group("materialize_all_200_graphs");
+
+    for workers in [1, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(workers),
+            &workers,
+            |bench, &n| {
+                bench.iter(|| black_box(materialize_all(&promises, ParallelismConfig::Threads(n))))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, materialize_200_graphs);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use simple_tensor::tensor::StaticTensor;
+
+fn chain_4x4(c: &mut Criterion) {
+    let a: StaticTensor<f64, 4, 4> = StaticTensor::new(std::array::from_fn(|r| {
+        std::array::from_fn(|c| (r * 4 + c) as f64 / 16.0)
+    }));
+    let b = a.transpose();
+
+    c.bench_function("static_chain_4x4", |bench| {
+        bench.iter(|| {
+            let a = black_box(a);
+            black_box(-(a + b) - a * b)
+        })
+    });
+
+    // The same chain through the lazy graph, with an elementwise product as Matmul has no
+    // kernel yet.
+    let (da, db) = (a.to_dynamic(), b.to_dynamic());
+
+    c.bench_function("dynamic_chain_4x4", |bench| {
+        bench.iter(|| black_box((-(&da + &db) - &da * &db).materialize()))
+    });
+}
+
+criterion_group!(benches, chain_4x4);
+criterion_main!(benches);
@@ -0,0 +1,459 @@
+use std::sync::Arc;
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use simple_tensor::tensor::{
+    BatchProduct, InMemoryMetrics, Layout, NumericsMode, SparseZeros, Summation, Tensor,
+    clear_metrics_sink, set_adaptive_packing, set_metrics_sink, set_sparse_zeros,
+    set_strided_batch_gemm, set_summation,
+};
+#[cfg(feature = "parallel")]
+use simple_tensor::tensor::{Dimension, ParallelismConfig, TensorPromise, materialize_all};
+
+// The `_parallel` variants split the work in row blocks computed as independent promises
+// by materialize_all(), over the global rayon pool.
+#[cfg(feature = "parallel")]
+const BLOCKS: usize = 8;
+
+// The `BLOCKS` views of consecutive rows of a contiguous tensor.
+#[cfg(feature = "parallel")]
+fn row_blocks(t: &Tensor<f64>) -> Vec<Tensor<f64>> {
+    let shape = t.shape();
+    let stride: Vec<i32> = (1..=shape.len())
+        .map(|axis| shape[axis..].iter().product::<usize>() as i32)
+        .collect();
+
+    let mut block = shape.to_vec();
+    block[0] /= BLOCKS;
+    let len: usize = block.iter().product();
+
+    (0..BLOCKS)
+        .map(|i| t.as_strided(&block, &stride, i * len).unwrap())
+        .collect()
+}
+
+#[cfg(feature = "parallel")]
+fn materialize_blocks(promises: &[TensorPromise<f64>]) -> Vec<Tensor<f64>> {
+    materialize_all(promises, ParallelismConfig::Rayon)
+        .into_iter()
+        .map(Result::unwrap)
+        .collect()
+}
+
+fn scalar_add(c: &mut Criterion) {
+    let t = Tensor::from_scalar(1.0, &[1_000_000]);
+
+    c.bench_function("scalar_add_1m", |b| {
+        b.iter(|| black_box((&t + 2.0).materialize()))
+    });
+
+    #[cfg(feature = "parallel")]
+    {
+        let blocks = row_blocks(&t);
+        c.bench_function("scalar_add_1m_parallel", |b| {
+            b.iter(|| {
+                let promises: Vec<_> = blocks.iter().map(|block| block + 2.0).collect();
+                black_box(materialize_blocks(&promises))
+            })
+        });
+    }
+}
+
+fn tensor_add(c: &mut Criterion) {
+    let lhs = Tensor::from_scalar(1.0, &[1024, 1024]);
+    let rhs = Tensor::from_scalar(2.0, &[1024, 1024]);
+
+    c.bench_function("tensor_add_1024x1024", |b| {
+        b.iter(|| black_box((&lhs + &rhs).materialize()))
+    });
+
+    #[cfg(feature = "parallel")]
+    {
+        let blocks: Vec<_> = row_blocks(&lhs).into_iter().zip(row_blocks(&rhs)).collect();
+        c.bench_function("tensor_add_1024x1024_parallel", |b| {
+            b.iter(|| {
+                let promises: Vec<_> = blocks.iter().map(|(lhs, rhs)| lhs + rhs).collect();
+                black_box(materialize_blocks(&promises))
+            })
+        });
+    }
+}
+
+// Two matrices multiplied by bmm(), which gives their [m, n] product.
+fn matmul(c: &mut Criterion) {
+    let lhs = Tensor::from_iter((0..256 * 256).map(|i| (i % 7) as f64), &[256, 256]);
+    let rhs = Tensor::from_iter((0..256 * 256).map(|i| (i % 5) as f64), &[256, 256]);
+
+    c.bench_function("matmul_256x256", |b| {
+        b.iter(|| black_box(lhs.bmm(&rhs).unwrap().materialize()))
+    });
+
+    // Each block of rows of `lhs` gives the same rows of the product.
+    #[cfg(feature = "parallel")]
+    {
+        let blocks = row_blocks(&lhs);
+        c.bench_function("matmul_256x256_parallel", |b| {
+            b.iter(|| {
+                let promises: Vec<_> = blocks
+                    .iter()
+                    .map(|block| block.bmm(&rhs).unwrap())
+                    .collect();
+                black_box(materialize_blocks(&promises))
+            })
+        });
+    }
+}
+
+fn slice_iter(c: &mut Criterion) {
+    let t = Tensor::from_iter((0..1_000_000).map(|i| i as f64), &[100, 100, 100]);
+    let view = t.transpose_axes(&[2, 0, 1]).unwrap().materialize();
+
+    c.bench_function("non_contiguous_iter_100x100x100", |b| {
+        b.iter(|| black_box(view.iter().sum::<f64>()))
+    });
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        c.bench_function("non_contiguous_iter_100x100x100_parallel", |b| {
+            b.iter(|| black_box(view.par_iter().sum::<f64>()))
+        });
+    }
+}
+
+// fold() walks the rows directly, the iterators step through a counter for every element.
+fn fold_vs_iter(c: &mut Criterion) {
+    let t = Tensor::from_iter((0..10_000_000).map(|i| i as f64), &[100, 100, 1000]);
+    let view = t.transpose_axes(&[1, 0, 2]).unwrap().materialize();
+
+    c.bench_function("strided_iter_sum_10m", |b| {
+        b.iter(|| black_box(view.iter().sum::<f64>()))
+    });
+    c.bench_function("strided_fold_sum_10m", |b| {
+        b.iter(|| black_box(view.fold(0.0, |acc, el| acc + el)))
+    });
+}
+
+fn graph_chain(c: &mut Criterion) {
+    let t = Tensor::from_scalar(1.0, &[256, 256]);
+
+    let mut promise = t.as_promise();
+    for _ in 0..10 {
+        promise = &promise + &t;
+    }
+
+    c.bench_function("graph_chain_10_nodes", |b| {
+        b.iter(|| black_box(promise.clone().materialize()))
+    });
+
+    #[cfg(feature = "parallel")]
+    {
+        let promises: Vec<_> = (row_blocks(&t).iter())
+            .map(|block| (0..10).fold(block.as_promise(), |acc, _| &acc + block))
+            .collect();
+        c.bench_function("graph_chain_10_nodes_parallel", |b| {
+            b.iter(|| black_box(materialize_blocks(&promises)))
+        });
+    }
+}
+
+// Decreasing input is the worst case of the rolling max deque. The time should not
+// grow with the window, unlike reducing every window one by one.
+fn rolling_max(c: &mut Criterion) {
+    let t = Tensor::from_iter((0..1_000_000).rev().map(|i| i as f64), &[1_000_000]);
+
+    for window in [8, 512] {
+        c.bench_function(&format!("rolling_max_1m_window_{}", window), |b| {
+            b.iter(|| black_box(t.rolling_max(window, 0).unwrap().materialize()))
+        });
+    }
+}
+
+// Short chains of ops that do not fuse, where sorting the graph is a noticeable part
+// of the time for small tensors.
+fn linear_chain(c: &mut Criterion) {
+    for len in [1_000, 100_000] {
+        let t = Tensor::from_iter((0..len).map(|i| i as f64), &[len / 10, 10]);
+
+        for ops in [1, 3, 6] {
+            let mut promise = t.isnan();
+            for i in 1..ops {
+                promise = match i % 3 {
+                    0 => promise.isnan(),
+                    1 => promise.min_scalar(0.5),
+                    _ => promise.transpose(),
+                };
+            }
+
+            c.bench_function(&format!("linear_chain_{}_ops_{}", ops, len), |b| {
+                b.iter(|| black_box(promise.clone().materialize()))
+            });
+        }
+    }
+}
+
+// Pairwise summation should stay close to the plain loop, Kahan about twice as slow.
+fn summation(c: &mut Criterion) {
+    let values: Vec<f64> = (0..10_000_000).map(|i| (i % 1000) as f64 * 0.1).collect();
+    let t = Tensor::from_vec(values.clone(), &[10_000_000]);
+
+    c.bench_function("naive_sum_10m", |b| {
+        b.iter(|| black_box(values.iter().fold(0.0, |acc, x| acc + x)))
+    });
+    c.bench_function("pairwise_sum_10m", |b| b.iter(|| black_box(t.sum_scalar())));
+
+    set_summation(Summation::Kahan);
+    c.bench_function("kahan_sum_10m", |b| b.iter(|| black_box(t.sum_scalar())));
+    set_summation(Summation::Pairwise);
+}
+
+// A closure boxed and called for every element against the same closure called per chunk.
+fn apply_closures(c: &mut Criterion) {
+    let t = Tensor::from_iter((0..1_000_000).map(|i| i as f64), &[1_000_000]);
+    let boxed: Arc<dyn Fn(f64) -> f64 + Send + Sync> = Arc::new(|x| x * 0.5 + 1.0);
+
+    let per_element = t.apply(move |input, output| {
+        for (out, &x) in output.iter_mut().zip(input) {
+            *out = black_box(&boxed)(x);
+        }
+    });
+    let chunked = t.apply_scalar(|x| x * 0.5 + 1.0);
+
+    c.bench_function("apply_boxed_per_element_1m", |b| {
+        b.iter(|| black_box(per_element.clone().materialize()))
+    });
+    c.bench_function("apply_chunked_1m", |b| {
+        b.iter(|| black_box(chunked.clone().materialize()))
+    });
+}
+
+// The same batch of GEMMs as a single strided-batch call and as a call per matrix.
+fn batch_matmul(c: &mut Criterion) {
+    let lhs = Tensor::from_iter((0..64 * 64 * 64).map(|i| (i % 7) as f64), &[64, 64, 64]);
+    let rhs = Tensor::from_iter((0..64 * 64 * 64).map(|i| (i % 5) as f64), &[64, 64, 64]);
+    let promise = lhs.bmm(&rhs).unwrap();
+
+    for strided in [true, false] {
+        let name = if strided { "strided" } else { "looped" };
+
+        set_strided_batch_gemm(strided);
+        c.bench_function(&format!("bmm_{}_64x64x64x64", name), |b| {
+            b.iter(|| black_box(promise.clone().materialize()))
+        });
+    }
+
+    set_strided_batch_gemm(true);
+}
+
+// Materializing with no sink should cost the same as before metrics existed.
+fn metrics_overhead(c: &mut Criterion) {
+    let t = Tensor::from_scalar(1.0, &[64]);
+    let promise = (0..64).fold(&t * 1.0, |acc, _| acc + &t);
+
+    c.bench_function("metrics_no_sink_64_ops", |b| {
+        b.iter(|| black_box(promise.clone().materialize()))
+    });
+
+    set_metrics_sink(Arc::new(InMemoryMetrics::new()));
+    c.bench_function("metrics_in_memory_64_ops", |b| {
+        b.iter(|| black_box(promise.clone().materialize()))
+    });
+    clear_metrics_sink();
+}
+
+// A thousand scalar ops appended in a loop, computed as a single affine pass.
+fn scalar_chain(c: &mut Criterion) {
+    let t = Tensor::from_scalar(1.0, &[100_000]);
+    let promise = (0..1000).fold(&t * 1.0, |acc, i| acc * 0.999 + (i % 3) as f64);
+
+    c.bench_function("scalar_chain_1000_ops", |b| {
+        b.iter(|| black_box(promise.clone().materialize()))
+    });
+}
+
+// A graph of 64 small ops computed over new inputs: built again for each of them, or
+// compiled once and called.
+fn compiled_graph(c: &mut Criterion) {
+    let build = |x: &Tensor<f64>, y: &Tensor<f64>| (0..32).fold(x * y, |acc, _| (&acc * x) + y);
+    let (x, y) = (
+        Tensor::from_scalar(1.0, &[16]),
+        Tensor::from_scalar(0.5, &[16]),
+    );
+    let compiled = build(&x, &y).compile(&[&x, &y]);
+
+    let mut group = c.benchmark_group("compiled_graph_64_ops");
+    group.bench_function("rebuilt", |b| {
+        b.iter(|| black_box(build(&x, &y).materialize()))
+    });
+    group.bench_function("compiled", |b| {
+        b.iter(|| black_box(compiled.call(&[&x, &y]).unwrap()))
+    });
+    group.finish();
+}
+
+// `a / sqrt(b)` as a division by the square root, and rewritten into `a * rsqrt(b)`
+// outside of HighAccuracy.
+fn div_by_sqrt(c: &mut Criterion) {
+    let a = Tensor::from_scalar(3.0, &[10_000_000]);
+    let b = Tensor::from_scalar(2.0, &[10_000_000]);
+
+    let mut group = c.benchmark_group("div_by_sqrt_10m");
+    for (name, mode) in [
+        ("unfused", NumericsMode::HighAccuracy),
+        ("fused", NumericsMode::LowAccuracy),
+    ] {
+        let promise = (&a / b.sqrt()).with_numerics(mode);
+        group.bench_function(name, |bench| {
+            bench.iter(|| black_box(promise.clone().materialize()))
+        });
+    }
+    group.finish();
+}
+
+// A million rank-3 layouts built and cloned, next to cloning the three boxed slices a
+// layout used to hold.
+fn layouts(c: &mut Criterion) {
+    let layout = Layout::from_shape(&[8, 16, 32], 0);
+    let boxed = (
+        Box::<[usize]>::from(layout.shape()),
+        Box::<[i32]>::from(layout.stride()),
+        Box::<[i32]>::from(layout.adj_stride()),
+    );
+
+    let mut group = c.benchmark_group("layouts_1m_rank_3");
+    group.bench_function("from_shape", |b| {
+        b.iter(|| {
+            for i in 0..1_000_000 {
+                black_box(Layout::from_shape(&[8, 16, black_box(i)], 0));
+            }
+        })
+    });
+    group.bench_function("clone", |b| {
+        b.iter(|| {
+            for _ in 0..1_000_000 {
+                black_box(black_box(&layout).clone());
+            }
+        })
+    });
+    group.bench_function("boxed_clone", |b| {
+        b.iter(|| {
+            for _ in 0..1_000_000 {
+                black_box(black_box(&boxed).clone());
+            }
+        })
+    });
+    group.finish();
+}
+
+// A mask with 1% of ones times a dense tensor, densely, then computing only the
+// positions of the ones, keeping IEEE zeros or absorbing them.
+fn sparse_mul(c: &mut Criterion) {
+    let len = 1_000_000;
+    let mask = Tensor::from_iter((0..len).map(|i| (i % 100 == 0) as u8 as f64), &[len]);
+    let hinted = mask.with_density_hint(0.01).unwrap();
+    let x = Tensor::from_iter((0..len).map(|i| i as f64), &[len]);
+
+    let mut group = c.benchmark_group("sparse_mul_1m_99pct_zeros");
+    group.bench_function("dense", |b| {
+        b.iter(|| black_box((&mask * &x).materialize()))
+    });
+    for (name, zeros) in [
+        ("ieee", SparseZeros::Ieee),
+        ("absorbing", SparseZeros::Absorbing),
+    ] {
+        set_sparse_zeros(zeros);
+        group.bench_function(name, |b| b.iter(|| black_box((&hinted * &x).materialize())));
+    }
+    set_sparse_zeros(SparseZeros::Ieee);
+    group.finish();
+}
+
+fn adaptive_packing(c: &mut Criterion) {
+    let (rows, cols) = (1000, 1000);
+    let matrix = Tensor::from_iter((0..rows * cols).map(|i| i as f64), &[rows, cols]);
+    let x = Tensor::from_iter((0..rows * cols).map(|i| i as f64 * 0.5), &[rows, cols]);
+    let vector = Tensor::from_iter((0..rows).map(|i| i as f64), &[rows]);
+    let one = Tensor::from_vec(vec![2.0], &[1]);
+
+    let broadcast = one.as_strided(&[rows, cols], &[0, 0], 0).unwrap();
+    let column = matrix.as_strided(&[rows], &[cols as i32], 3).unwrap();
+    let transposed = matrix
+        .as_strided(&[rows, cols], &[1, cols as i32], 0)
+        .unwrap();
+
+    let mut group = c.benchmark_group("adaptive_packing_1m");
+    for (name, enabled) in [("packed", false), ("adaptive", true)] {
+        set_adaptive_packing(enabled);
+        group.bench_function(format!("broadcast_{}", name), |b| {
+            b.iter(|| black_box((&x + &broadcast).materialize()))
+        });
+        group.bench_function(format!("column_{}", name), |b| {
+            b.iter(|| black_box((&vector + &column).materialize()))
+        });
+        group.bench_function(format!("transposed_{}", name), |b| {
+            b.iter(|| black_box((&x + &transposed).materialize()))
+        });
+    }
+    set_adaptive_packing(true);
+    group.finish();
+}
+
+// A transposed 4096x4096 matrix compacted on its own, and read as either operand of an
+// addition, all of which go through the cache-blocked transpose copy.
+fn transposed_copy(c: &mut Criterion) {
+    let n = 4096;
+    let matrix = Tensor::from_iter((0..n * n).map(|i| i as f64), &[n, n]);
+    let transposed = matrix.as_strided(&[n, n], &[1, n as i32], 0).unwrap();
+
+    let mut group = c.benchmark_group("transposed_copy_4096x4096");
+    group.sample_size(20);
+    group.bench_function("as_contiguous", |b| {
+        b.iter(|| black_box(transposed.as_contiguous().materialize()))
+    });
+    group.bench_function("lhs_operand", |b| {
+        b.iter(|| black_box((&transposed - &matrix).materialize()))
+    });
+    group.bench_function("rhs_operand", |b| {
+        b.iter(|| black_box((&matrix - &transposed).materialize()))
+    });
+    group.finish();
+}
+
+// Lanes normalized along the contiguous last axis and along the strided first one.
+fn normalize(c: &mut Criterion) {
+    let t = Tensor::from_iter((0..1_000_000).map(|i| (i % 97) as f64), &[1000, 1000]);
+
+    let mut group = c.benchmark_group("normalize_1000x1000");
+    for axis in [1, 0] {
+        let promise = t.normalize(axis, 1e-5).unwrap();
+        group.bench_function(format!("axis_{}", axis), |b| {
+            b.iter(|| black_box(promise.clone().materialize()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    scalar_add,
+    tensor_add,
+    matmul,
+    slice_iter,
+    fold_vs_iter,
+    graph_chain,
+    rolling_max,
+    linear_chain,
+    summation,
+    apply_closures,
+    batch_matmul,
+    metrics_overhead,
+    scalar_chain,
+    compiled_graph,
+    div_by_sqrt,
+    layouts,
+    sparse_mul,
+    adaptive_packing,
+    normalize,
+    transposed_copy
+);
+criterion_main!(benches);
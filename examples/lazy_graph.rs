@@ -0,0 +1,28 @@
+use simple_tensor::arange;
+use simple_tensor::tensor::Tensor;
+
+fn main() {
+    // Leaves hold data, everything built on top of them is a promise
+    let x = arange![12];
+    let bias = Tensor::from_scalar(0.5, &[12]);
+
+    // Nothing runs until materialize() is called
+    let shifted = (&x + &bias).cache();
+    let doubled = &shifted * 2.0;
+    let squared = &shifted * &shifted;
+
+    println!("doubled: {}", doubled.materialize());
+    println!(
+        "cached after the first materialization: {}",
+        shifted.is_cached()
+    );
+    println!("squared: {}", squared.materialize());
+
+    // Chains of scalar ops are fused into a single pass over the data
+    let mut p = x.as_promise();
+    for i in 0..20 {
+        p = p + i as f64;
+    }
+
+    println!("fused: {}", (p * 2.0).materialize());
+}
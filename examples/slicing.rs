@@ -0,0 +1,21 @@
+use simple_tensor::tensor::Dimension;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::{s, srange};
+
+fn main() -> Result<(), OpError> {
+    let t = srange![12, &[3, 4]];
+    println!("tensor:\n{}", t);
+
+    // Slices are views over the same buffer until they are materialized
+    let block = t.slice(s![0..2, 1..3])?.materialize();
+    println!("rows 0..2, columns 1..3 {:?}:\n{}", block.shape(), block);
+
+    // Negative bounds count from the end of the axis
+    let last_column = t.slice(s![.., -1..])?.materialize();
+    println!("last column {:?}:\n{}", last_column.shape(), last_column);
+
+    let tail_rows = t.slice(s![1.., ..])?.materialize();
+    println!("rows 1.. {:?}:\n{}", tail_rows.shape(), tail_rows);
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use simple_tensor::arange;
+use simple_tensor::tensor::Dimension;
+use simple_tensor::tensor::errors::OpError;
+
+fn main() -> Result<(), OpError> {
+    let t = arange![12];
+
+    // view() reinterprets the shape without copying
+    let matrix = t.view(&[3, 4])?.materialize();
+    println!("view as [3, 4]:\n{}", matrix);
+
+    let transposed = matrix.transpose().materialize();
+    println!("transposed {:?}:\n{}", transposed.shape(), transposed);
+
+    // Transposing twice gives back the original matrix
+    let round_trip = transposed.transpose().materialize();
+    println!(
+        "transpose round trip matches: {}",
+        round_trip.iter().eq(matrix.iter())
+    );
+
+    // And so does viewing the matrix back as a vector
+    let flat = matrix.view(&[12])?.materialize();
+    println!("view round trip matches: {}", flat.iter().eq(t.iter()));
+
+    let cube = t
+        .view(&[2, 3, 2])?
+        .transpose_axes(&[2, 0, 1])?
+        .materialize();
+    println!("axes permuted to {:?}:\n{}", cube.shape(), cube);
+
+    Ok(())
+}
@@ -0,0 +1,4 @@
+pub mod tensor;
+
+// The arange! and srange! macros expand to $crate::arange::...
+pub use tensor::arange;
@@ -0,0 +1,211 @@
+#![allow(improper_ctypes, non_snake_case)]
+
+use std::ffi::c_int;
+
+use crate::tensor::backend::{
+    CBLAS_ROW_MAJOR, Gemm, GemmBackend, ReductionBackend, UnaryMathBackend, VectorMathBackend,
+    cblas_transpose, pure,
+};
+use crate::tensor::complex::Complex;
+
+// Only the standard CBLAS symbols are used here, so any provider works.
+// Accelerate ships with macOS and is linked automatically; on other targets
+// the final binary has to link one (e.g. through `openblas-src` or `-l openblas`).
+// The Complex<f64> bindings rely on it having the same layout as C's double complex.
+#[cfg_attr(target_os = "macos", link(name = "Accelerate", kind = "framework"))]
+unsafe extern "C" {
+    fn cblas_dscal(N: c_int, alpha: f64, X: *mut f64, incX: c_int);
+    fn cblas_zscal(N: c_int, alpha: *const Complex<f64>, X: *mut Complex<f64>, incX: c_int);
+    fn cblas_daxpy(N: c_int, alpha: f64, X: *const f64, incX: c_int, Y: *mut f64, incY: c_int);
+    fn cblas_zaxpy(
+        N: c_int,
+        alpha: *const Complex<f64>,
+        X: *const Complex<f64>,
+        incX: c_int,
+        Y: *mut Complex<f64>,
+        incY: c_int,
+    );
+    fn cblas_idamax(N: c_int, X: *const f64, incX: c_int) -> usize;
+    fn cblas_dgemm(
+        Layout: c_int,
+        TransA: c_int,
+        TransB: c_int,
+        M: c_int,
+        N: c_int,
+        K: c_int,
+        alpha: f64,
+        A: *const f64,
+        lda: c_int,
+        B: *const f64,
+        ldb: c_int,
+        beta: f64,
+        C: *mut f64,
+        ldc: c_int,
+    );
+}
+
+pub(crate) struct CblasBackend;
+
+// CBLAS has no elementwise vector ops, so those fall back to the pure kernels.
+macro_rules! impl_cblas_backend {
+    ($ty: ty, $scal: ident, $axpy: ident, $by_ref: tt) => {
+        impl VectorMathBackend<$ty> for CblasBackend {
+            #[inline]
+            fn scal(alpha: $ty, x: &mut [$ty]) {
+                unsafe { $scal(x.len() as c_int, impl_cblas_backend!(@alpha $by_ref alpha), x.as_mut_ptr(), 1) }
+            }
+
+            #[inline]
+            fn axpy(alpha: $ty, x: &[$ty], y: &mut [$ty]) {
+                debug_assert_eq!(x.len(), y.len());
+
+                unsafe {
+                    $axpy(
+                        x.len() as c_int,
+                        impl_cblas_backend!(@alpha $by_ref alpha),
+                        x.as_ptr(),
+                        1,
+                        y.as_mut_ptr(),
+                        1,
+                    )
+                }
+            }
+
+            #[inline]
+            fn axpy_strided(alpha: $ty, x: &[$ty], incx: usize, y: &mut [$ty]) {
+                debug_assert!(y.is_empty() || x.len() > (y.len() - 1) * incx);
+
+                unsafe {
+                    $axpy(
+                        y.len() as c_int,
+                        impl_cblas_backend!(@alpha $by_ref alpha),
+                        x.as_ptr(),
+                        incx as c_int,
+                        y.as_mut_ptr(),
+                        1,
+                    )
+                }
+            }
+
+            #[inline]
+            fn add(acc: &mut [$ty], rhs: &[$ty]) {
+                pure::add(acc, rhs)
+            }
+
+            #[inline]
+            fn sub(acc: &mut [$ty], rhs: &[$ty]) {
+                pure::sub(acc, rhs)
+            }
+
+            #[inline]
+            fn mul(acc: &mut [$ty], rhs: &[$ty]) {
+                pure::mul(acc, rhs)
+            }
+
+            #[inline]
+            fn div(acc: &mut [$ty], rhs: &[$ty]) {
+                pure::div(acc, rhs)
+            }
+        }
+    };
+
+    (@alpha by_value $alpha: ident) => {
+        $alpha
+    };
+
+    (@alpha by_ref $alpha: ident) => {
+        &$alpha
+    };
+}
+
+impl_cblas_backend!(f64, cblas_dscal, cblas_daxpy, by_value);
+impl_cblas_backend!(Complex<f64>, cblas_zscal, cblas_zaxpy, by_ref);
+
+impl UnaryMathBackend for CblasBackend {
+    #[inline]
+    fn sqrt(x: &mut [f64]) {
+        pure::sqrt(x)
+    }
+
+    #[inline]
+    fn inv(x: &mut [f64]) {
+        pure::inv(x)
+    }
+
+    #[inline]
+    fn inv_sqrt(x: &mut [f64]) {
+        pure::inv_sqrt(x)
+    }
+
+    #[inline]
+    fn exp(x: &mut [f64]) {
+        pure::exp(x)
+    }
+
+    #[inline]
+    fn ln(x: &mut [f64]) {
+        pure::ln(x)
+    }
+
+    #[inline]
+    fn expm1(x: &mut [f64]) {
+        pure::expm1(x)
+    }
+
+    #[inline]
+    fn log1p(x: &mut [f64]) {
+        pure::log1p(x)
+    }
+
+    #[inline]
+    fn log2(x: &mut [f64]) {
+        pure::log2(x)
+    }
+
+    #[inline]
+    fn log10(x: &mut [f64]) {
+        pure::log10(x)
+    }
+
+    #[inline]
+    fn exp2(x: &mut [f64]) {
+        pure::exp2(x)
+    }
+}
+
+// CBLAS_INDEX is a size_t holding a 0-based position.
+impl ReductionBackend for CblasBackend {
+    #[inline]
+    fn iamax(x: &[f64]) -> usize {
+        if x.is_empty() {
+            return 0;
+        }
+
+        unsafe { cblas_idamax(x.len() as c_int, x.as_ptr(), 1) }
+    }
+}
+
+// Batches are not part of CBLAS, so they are a loop of cblas_dgemm.
+impl GemmBackend for CblasBackend {
+    #[inline]
+    fn gemm(gemm: &Gemm, a: &[f64], b: &[f64], c: &mut [f64]) {
+        unsafe {
+            cblas_dgemm(
+                CBLAS_ROW_MAJOR,
+                cblas_transpose(gemm.a.trans),
+                cblas_transpose(gemm.b.trans),
+                gemm.m as c_int,
+                gemm.n as c_int,
+                gemm.k as c_int,
+                1.0,
+                a.as_ptr(),
+                gemm.a.ld as c_int,
+                b.as_ptr(),
+                gemm.b.ld as c_int,
+                0.0,
+                c.as_mut_ptr(),
+                gemm.n as c_int,
+            )
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use lapacke::Layout;
+
+use crate::tensor::backend::{Backend, LinAlgBackend};
+
+// MKL ships LAPACKE. With `cblas` the provider has to export it too, like OpenBLAS does.
+// The matrices are column-major, LAPACK's own layout, so LAPACKE does not transpose them.
+impl LinAlgBackend for Backend {
+    #[inline]
+    fn getrf(m: usize, n: usize, a: &mut [f64], ipiv: &mut [i32]) -> i32 {
+        debug_assert_eq!(a.len(), m * n);
+
+        unsafe { lapacke::dgetrf(Layout::ColumnMajor, m as i32, n as i32, a, m as i32, ipiv) }
+    }
+
+    #[inline]
+    fn geqrf(m: usize, n: usize, a: &mut [f64], tau: &mut [f64]) -> i32 {
+        debug_assert_eq!(a.len(), m * n);
+
+        unsafe { lapacke::dgeqrf(Layout::ColumnMajor, m as i32, n as i32, a, m as i32, tau) }
+    }
+
+    #[inline]
+    fn orgqr(m: usize, n: usize, a: &mut [f64], tau: &[f64]) -> i32 {
+        debug_assert!(a.len() >= m * n && n <= m);
+
+        unsafe {
+            lapacke::dorgqr(
+                Layout::ColumnMajor,
+                m as i32,
+                n as i32,
+                n as i32,
+                a,
+                m as i32,
+                tau,
+            )
+        }
+    }
+
+    #[inline]
+    fn gecon(one: bool, n: usize, lu: &[f64], anorm: f64, rcond: &mut f64) -> i32 {
+        let norm = if one { b'1' } else { b'I' };
+
+        unsafe {
+            lapacke::dgecon(
+                Layout::ColumnMajor,
+                norm,
+                n as i32,
+                lu,
+                n as i32,
+                anorm,
+                rcond,
+            )
+        }
+    }
+}
@@ -0,0 +1,286 @@
+#![allow(improper_ctypes, non_snake_case)]
+
+use std::ffi::c_int;
+
+use crate::tensor::backend::{
+    CBLAS_ROW_MAJOR, Gemm, GemmBackend, ReductionBackend, UnaryMathBackend, VectorMathBackend,
+    cblas_transpose,
+};
+use crate::tensor::complex::Complex;
+use crate::tensor::numerics::numerics_mode;
+
+// The Complex<f64> bindings rely on it having the same layout as MKL_Complex16.
+unsafe extern "C" {
+    fn cblas_dscal(N: c_int, alpha: f64, X: *mut f64, incX: c_int);
+    fn cblas_zscal(N: c_int, alpha: *const Complex<f64>, X: *mut Complex<f64>, incX: c_int);
+    fn cblas_daxpy(N: c_int, alpha: f64, X: *const f64, incX: c_int, Y: *mut f64, incY: c_int);
+    fn cblas_zaxpy(
+        N: c_int,
+        alpha: *const Complex<f64>,
+        X: *const Complex<f64>,
+        incX: c_int,
+        Y: *mut Complex<f64>,
+        incY: c_int,
+    );
+    fn cblas_idamax(N: c_int, X: *const f64, incX: c_int) -> usize;
+    fn cblas_dgemm(
+        Layout: c_int,
+        TransA: c_int,
+        TransB: c_int,
+        M: c_int,
+        N: c_int,
+        K: c_int,
+        alpha: f64,
+        A: *const f64,
+        lda: c_int,
+        B: *const f64,
+        ldb: c_int,
+        beta: f64,
+        C: *mut f64,
+        ldc: c_int,
+    );
+    fn cblas_dgemm_batch_strided(
+        Layout: c_int,
+        TransA: c_int,
+        TransB: c_int,
+        M: c_int,
+        N: c_int,
+        K: c_int,
+        alpha: f64,
+        A: *const f64,
+        lda: c_int,
+        stridea: c_int,
+        B: *const f64,
+        ldb: c_int,
+        strideb: c_int,
+        beta: f64,
+        C: *mut f64,
+        ldc: c_int,
+        stridec: c_int,
+        batch_size: c_int,
+    );
+
+    fn vmdAdd(n: c_int, a: *const f64, b: *const f64, r: *mut f64, mode: i64);
+    fn vmdSub(n: c_int, a: *const f64, b: *const f64, r: *mut f64, mode: i64);
+    fn vmdMul(n: c_int, a: *const f64, b: *const f64, r: *mut f64, mode: i64);
+    fn vmdDiv(n: c_int, a: *const f64, b: *const f64, r: *mut f64, mode: i64);
+    fn vmdSqrt(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdInv(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdInvSqrt(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdExp(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdLn(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdExpm1(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdLog1p(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdLog2(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdLog10(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+    fn vmdExp2(n: c_int, a: *const f64, r: *mut f64, mode: i64);
+
+    fn vmzAdd(
+        n: c_int,
+        a: *const Complex<f64>,
+        b: *const Complex<f64>,
+        r: *mut Complex<f64>,
+        mode: i64,
+    );
+    fn vmzSub(
+        n: c_int,
+        a: *const Complex<f64>,
+        b: *const Complex<f64>,
+        r: *mut Complex<f64>,
+        mode: i64,
+    );
+    fn vmzMul(
+        n: c_int,
+        a: *const Complex<f64>,
+        b: *const Complex<f64>,
+        r: *mut Complex<f64>,
+        mode: i64,
+    );
+    fn vmzDiv(
+        n: c_int,
+        a: *const Complex<f64>,
+        b: *const Complex<f64>,
+        r: *mut Complex<f64>,
+        mode: i64,
+    );
+}
+
+pub(crate) struct MklBackend;
+
+// VML allows the output to alias the first operand, which is what makes the in place ops work.
+// The accuracy mode is passed on every call through the vm* variants, instead of vmlSetMode,
+// so that the mode of MKL's threads is never changed from under another caller.
+macro_rules! impl_mkl_backend {
+    ($ty: ty, $scal: ident, $axpy: ident, $add: ident, $sub: ident, $mul: ident, $div: ident, $by_ref: tt) => {
+        impl VectorMathBackend<$ty> for MklBackend {
+            #[inline]
+            fn scal(alpha: $ty, x: &mut [$ty]) {
+                unsafe { $scal(x.len() as c_int, impl_mkl_backend!(@alpha $by_ref alpha), x.as_mut_ptr(), 1) }
+            }
+
+            #[inline]
+            fn axpy(alpha: $ty, x: &[$ty], y: &mut [$ty]) {
+                debug_assert_eq!(x.len(), y.len());
+
+                unsafe {
+                    $axpy(
+                        x.len() as c_int,
+                        impl_mkl_backend!(@alpha $by_ref alpha),
+                        x.as_ptr(),
+                        1,
+                        y.as_mut_ptr(),
+                        1,
+                    )
+                }
+            }
+
+            #[inline]
+            fn axpy_strided(alpha: $ty, x: &[$ty], incx: usize, y: &mut [$ty]) {
+                debug_assert!(y.is_empty() || x.len() > (y.len() - 1) * incx);
+
+                unsafe {
+                    $axpy(
+                        y.len() as c_int,
+                        impl_mkl_backend!(@alpha $by_ref alpha),
+                        x.as_ptr(),
+                        incx as c_int,
+                        y.as_mut_ptr(),
+                        1,
+                    )
+                }
+            }
+
+            impl_mkl_backend!(@binary $ty, add, $add);
+            impl_mkl_backend!(@binary $ty, sub, $sub);
+            impl_mkl_backend!(@binary $ty, mul, $mul);
+            impl_mkl_backend!(@binary $ty, div, $div);
+        }
+    };
+
+    (@alpha by_value $alpha: ident) => {
+        $alpha
+    };
+
+    (@alpha by_ref $alpha: ident) => {
+        &$alpha
+    };
+
+    (@binary $ty: ty, $name: ident, $vml: ident) => {
+        #[inline]
+        fn $name(acc: &mut [$ty], rhs: &[$ty]) {
+            debug_assert_eq!(acc.len(), rhs.len());
+
+            let acc_ptr = acc.as_mut_ptr();
+            let mode = numerics_mode().vml_mode();
+            unsafe { $vml(acc.len() as c_int, acc_ptr, rhs.as_ptr(), acc_ptr, mode) }
+        }
+    };
+}
+
+impl_mkl_backend!(
+    f64,
+    cblas_dscal,
+    cblas_daxpy,
+    vmdAdd,
+    vmdSub,
+    vmdMul,
+    vmdDiv,
+    by_value
+);
+impl_mkl_backend!(
+    Complex<f64>,
+    cblas_zscal,
+    cblas_zaxpy,
+    vmzAdd,
+    vmzSub,
+    vmzMul,
+    vmzDiv,
+    by_ref
+);
+
+macro_rules! impl_mkl_unary {
+    ($name: ident, $vml: ident) => {
+        #[inline]
+        fn $name(x: &mut [f64]) {
+            let ptr = x.as_mut_ptr();
+            let mode = numerics_mode().vml_mode();
+            unsafe { $vml(x.len() as c_int, ptr, ptr, mode) }
+        }
+    };
+}
+
+impl UnaryMathBackend for MklBackend {
+    impl_mkl_unary!(sqrt, vmdSqrt);
+    impl_mkl_unary!(inv, vmdInv);
+    impl_mkl_unary!(inv_sqrt, vmdInvSqrt);
+    impl_mkl_unary!(exp, vmdExp);
+    impl_mkl_unary!(ln, vmdLn);
+    impl_mkl_unary!(expm1, vmdExpm1);
+    impl_mkl_unary!(log1p, vmdLog1p);
+    impl_mkl_unary!(log2, vmdLog2);
+    impl_mkl_unary!(log10, vmdLog10);
+    impl_mkl_unary!(exp2, vmdExp2);
+}
+
+// CBLAS_INDEX is a size_t holding a 0-based position.
+impl ReductionBackend for MklBackend {
+    #[inline]
+    fn iamax(x: &[f64]) -> usize {
+        if x.is_empty() {
+            return 0;
+        }
+
+        unsafe { cblas_idamax(x.len() as c_int, x.as_ptr(), 1) }
+    }
+}
+
+// The whole batch is a single call, which MKL spreads over its threads.
+impl GemmBackend for MklBackend {
+    #[inline]
+    fn gemm(gemm: &Gemm, a: &[f64], b: &[f64], c: &mut [f64]) {
+        unsafe {
+            cblas_dgemm(
+                CBLAS_ROW_MAJOR,
+                cblas_transpose(gemm.a.trans),
+                cblas_transpose(gemm.b.trans),
+                gemm.m as c_int,
+                gemm.n as c_int,
+                gemm.k as c_int,
+                1.0,
+                a.as_ptr(),
+                gemm.a.ld as c_int,
+                b.as_ptr(),
+                gemm.b.ld as c_int,
+                0.0,
+                c.as_mut_ptr(),
+                gemm.n as c_int,
+            )
+        }
+    }
+
+    #[inline]
+    fn gemm_batch_strided(gemm: &Gemm, a: &[f64], b: &[f64], c: &mut [f64]) {
+        unsafe {
+            cblas_dgemm_batch_strided(
+                CBLAS_ROW_MAJOR,
+                cblas_transpose(gemm.a.trans),
+                cblas_transpose(gemm.b.trans),
+                gemm.m as c_int,
+                gemm.n as c_int,
+                gemm.k as c_int,
+                1.0,
+                a.as_ptr(),
+                gemm.a.ld as c_int,
+                gemm.a.stride as c_int,
+                b.as_ptr(),
+                gemm.b.ld as c_int,
+                gemm.b.stride as c_int,
+                0.0,
+                c.as_mut_ptr(),
+                gemm.n as c_int,
+                (gemm.m * gemm.n) as c_int,
+                gemm.batch as c_int,
+            )
+        }
+    }
+}
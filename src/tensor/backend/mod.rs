@@ -0,0 +1,180 @@
+// The vector math backend is picked at compile time by exactly one of the
+// `mkl`, `cblas` or `pure` features, so every call below is statically dispatched.
+//
+// Agreement between backends, which tests/backends.rs checks:
+// - add, sub, mul, scal and axpy on f64 are plain IEEE operations everywhere, but
+//   BLAS providers may use FMA in axpy, so results can differ by 1 ulp. With an alpha of
+//   1 or -1 the product is exact, so axpy rounds like add or sub either way.
+// - div on f64 is correctly rounded in `pure` and `cblas`. MKL's VML runs in the
+//   accuracy mode given by numerics_mode(), high accuracy (below 1 ulp) by default.
+//   The other backends ignore the mode.
+// - sqrt and inv on f64 are correctly rounded in `pure` and `cblas`, and inv_sqrt divides
+//   1 by the rounded square root, so it is within 1 ulp. MKL computes all of them in the
+//   accuracy mode, like div.
+// - exp, ln, expm1, log1p, log2, log10 and exp2 on f64 are the standard library ones in
+//   `pure` and `cblas`, and VML's in MKL, in the accuracy mode like div. In the default
+//   mode both stay below 1 ulp, so they may differ by 1 ulp.
+// - Complex<f64> mul and div may differ by a few ulps, as MKL rescales the operands
+//   to avoid overflow while the pure kernels use the textbook formulas.
+// - gemm adds the products of each dot product in an order of the provider's choosing,
+//   and the pure kernel in order, so results can differ by a few ulps.
+// - getrf picks the same pivots everywhere but LAPACK updates the trailing matrix by
+//   blocks, so the factors can differ by a few ulps. geqrf and orgqr likewise. gecon is an
+//   estimate in LAPACK while the pure kernel inverts the matrix, so it is exact.
+
+#[cfg(not(any(feature = "mkl", feature = "cblas", feature = "pure")))]
+compile_error!(
+    "no vector math backend selected: enable exactly one of the `mkl`, `cblas` or `pure` features"
+);
+
+#[cfg(any(
+    all(feature = "mkl", feature = "cblas"),
+    all(feature = "mkl", feature = "pure"),
+    all(feature = "cblas", feature = "pure"),
+))]
+compile_error!(
+    "the `mkl`, `cblas` and `pure` features are mutually exclusive: enable only one of them \
+     (use `--no-default-features` to drop the default `mkl` backend)"
+);
+
+#[cfg(any(feature = "mkl", feature = "cblas"))]
+use std::ffi::c_int;
+
+#[cfg(feature = "cblas")]
+mod cblas;
+#[cfg(any(feature = "mkl", feature = "cblas"))]
+mod lapack;
+#[cfg(feature = "mkl")]
+mod mkl;
+#[cfg(any(feature = "cblas", feature = "pure"))]
+mod pure;
+
+#[cfg(feature = "cblas")]
+pub(crate) use cblas::CblasBackend as Backend;
+#[cfg(feature = "mkl")]
+pub(crate) use mkl::MklBackend as Backend;
+#[cfg(all(feature = "pure", not(any(feature = "mkl", feature = "cblas"))))]
+pub(crate) use pure::PureBackend as Backend;
+
+/// Slice kernels every backend has to provide for the element types it supports.
+/// The binary ops work in place, computing `acc[i] = acc[i] op rhs[i]`.
+pub(crate) trait VectorMathBackend<T> {
+    /// `x = alpha * x`
+    fn scal(alpha: T, x: &mut [T]);
+    /// `y = alpha * x + y`
+    fn axpy(alpha: T, x: &[T], y: &mut [T]);
+    /// `y = alpha * x + y`, reading the elements of `x` `incx` apart from its first one,
+    /// like the `incX` of BLAS.
+    fn axpy_strided(alpha: T, x: &[T], incx: usize, y: &mut [T]);
+
+    fn add(acc: &mut [T], rhs: &[T]);
+    fn sub(acc: &mut [T], rhs: &[T]);
+    fn mul(acc: &mut [T], rhs: &[T]);
+    fn div(acc: &mut [T], rhs: &[T]);
+}
+
+/// Reductions of real slices, which BLAS computes without materializing any intermediate.
+/// Sums are not among them, they all go through ops/kernels.rs to round the same way
+/// on every backend.
+pub(crate) trait ReductionBackend {
+    /// Position of the first element with the largest absolute value, 0 for an empty slice.
+    /// How NaNs are treated depends on the provider.
+    fn iamax(x: &[f64]) -> usize;
+}
+
+/// Elementwise functions of real slices, computed in place. Like the IEEE operations,
+/// they never fail: 1 / 0 is infinite and the square root of a negative number is NaN.
+pub(crate) trait UnaryMathBackend {
+    /// `x = sqrt(x)`
+    fn sqrt(x: &mut [f64]);
+    /// `x = 1 / x`
+    fn inv(x: &mut [f64]);
+    /// `x = 1 / sqrt(x)`
+    fn inv_sqrt(x: &mut [f64]);
+    /// `x = e^x`
+    fn exp(x: &mut [f64]);
+    /// `x = ln(x)`
+    fn ln(x: &mut [f64]);
+    /// `x = e^x - 1`, without the cancellation of the subtraction for x near 0.
+    fn expm1(x: &mut [f64]);
+    /// `x = ln(1 + x)`, without the rounding of the addition for x near 0.
+    fn log1p(x: &mut [f64]);
+    /// `x = log2(x)`
+    fn log2(x: &mut [f64]);
+    /// `x = log10(x)`
+    fn log10(x: &mut [f64]);
+    /// `x = 2^x`
+    fn exp2(x: &mut [f64]);
+}
+
+/// One operand of a GEMM, a row-major matrix whose rows are `ld` elements apart, read
+/// transposed if `trans`. The matrices of a batch are `stride` elements apart, a stride
+/// of 0 using the same matrix for every product.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct GemmOperand {
+    pub(crate) trans: bool,
+    pub(crate) ld: usize,
+    pub(crate) stride: usize,
+}
+
+/// `batch` products of an [m, k] matrix of `a` by a [k, n] matrix of `b`,
+/// written back to back into `c`, [m, n] each.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Gemm {
+    pub(crate) m: usize,
+    pub(crate) n: usize,
+    pub(crate) k: usize,
+    pub(crate) a: GemmOperand,
+    pub(crate) b: GemmOperand,
+    pub(crate) batch: usize,
+}
+
+/// Matrix products of f64, m, n and k being at least 1.
+pub(crate) trait GemmBackend {
+    /// The first product of `gemm`, ignoring the batch.
+    fn gemm(gemm: &Gemm, a: &[f64], b: &[f64], c: &mut [f64]);
+
+    /// Every product of `gemm`, in a single call when the provider has one.
+    fn gemm_batch_strided(gemm: &Gemm, a: &[f64], b: &[f64], c: &mut [f64]) {
+        let len = gemm.m * gemm.n;
+
+        for (i, c) in c.chunks_exact_mut(len).take(gemm.batch).enumerate() {
+            let a = &a[i * gemm.a.stride..];
+            let b = &b[i * gemm.b.stride..];
+
+            Self::gemm(gemm, a, b, c);
+        }
+    }
+}
+
+/// Dense factorizations of f64 matrices, stored column-major like in LAPACK with their
+/// columns `m` elements apart. They return the info code of LAPACK: 0 on success, below 0
+/// for an invalid argument and above 0 for a singular matrix.
+pub(crate) trait LinAlgBackend {
+    /// LU with partial pivoting of an [m, n] matrix, in place: L under the diagonal, its own
+    /// diagonal being ones, and U on and above it. Row i was swapped with row `ipiv[i] - 1`,
+    /// from the first row on. Above 0, the info code is the first zero of the diagonal of U,
+    /// counting from 1, and the factorization is still complete.
+    fn getrf(m: usize, n: usize, a: &mut [f64], ipiv: &mut [i32]) -> i32;
+
+    /// QR of an [m, n] matrix, in place: R on and above the diagonal, and under it the
+    /// Householder reflectors which with the min(m, n) scalars of `tau` give Q.
+    fn geqrf(m: usize, n: usize, a: &mut [f64], tau: &mut [f64]) -> i32;
+
+    /// The first `n` columns of Q, from the first `n` columns of the output of geqrf,
+    /// in place. `n` is at most `m`.
+    fn orgqr(m: usize, n: usize, a: &mut [f64], tau: &[f64]) -> i32;
+
+    /// Reciprocal of the condition number of an [n, n] matrix in the 1-norm, or in the
+    /// infinity norm if not `one`, from its norm `anorm` and the output `lu` of getrf.
+    fn gecon(one: bool, n: usize, lu: &[f64], anorm: f64, rcond: &mut f64) -> i32;
+}
+
+// The values of the CBLAS_LAYOUT and CBLAS_TRANSPOSE enums.
+#[cfg(any(feature = "mkl", feature = "cblas"))]
+const CBLAS_ROW_MAJOR: c_int = 101;
+
+#[cfg(any(feature = "mkl", feature = "cblas"))]
+fn cblas_transpose(trans: bool) -> c_int {
+    if trans { 112 } else { 111 }
+}
@@ -0,0 +1,402 @@
+#[cfg(feature = "pure")]
+use crate::tensor::backend::{
+    Gemm, GemmBackend, LinAlgBackend, ReductionBackend, UnaryMathBackend, VectorMathBackend,
+};
+use crate::tensor::definitions::NumberLike;
+
+// Plain loops over slices. They have no bounds checks in the hot path,
+// so the compiler is free to vectorize them for the target.
+
+#[cfg(feature = "pure")]
+#[inline]
+pub(super) fn scal<T: NumberLike>(alpha: T, x: &mut [T]) {
+    for el in x.iter_mut() {
+        *el = alpha * *el;
+    }
+}
+
+#[cfg(feature = "pure")]
+#[inline]
+pub(super) fn axpy<T: NumberLike>(alpha: T, x: &[T], y: &mut [T]) {
+    debug_assert_eq!(x.len(), y.len());
+
+    for (acc, &el) in y.iter_mut().zip(x.iter()) {
+        *acc = alpha * el + *acc;
+    }
+}
+
+#[cfg(feature = "pure")]
+#[inline]
+pub(super) fn axpy_strided<T: NumberLike>(alpha: T, x: &[T], incx: usize, y: &mut [T]) {
+    debug_assert!(y.is_empty() || x.len() > (y.len() - 1) * incx);
+
+    for (acc, &el) in y.iter_mut().zip(x.iter().step_by(incx)) {
+        *acc = alpha * el + *acc;
+    }
+}
+
+// Like the reference BLAS, NaNs never compare greater, unless they come first.
+#[cfg(feature = "pure")]
+#[inline]
+pub(super) fn iamax(x: &[f64]) -> usize {
+    let mut best = 0;
+
+    for (idx, el) in x.iter().enumerate().skip(1) {
+        if el.abs() > x[best].abs() {
+            best = idx;
+        }
+    }
+
+    best
+}
+
+// Row by row, accumulating the rows of `b` scaled by the elements of the row of `a`,
+// so the rows of a `b` that is not transposed are read in order.
+#[cfg(feature = "pure")]
+pub(super) fn gemm(gemm: &Gemm, a: &[f64], b: &[f64], c: &mut [f64]) {
+    let (lda, ldb) = (gemm.a.ld, gemm.b.ld);
+    let a_at = |i: usize, p: usize| {
+        if gemm.a.trans {
+            a[p * lda + i]
+        } else {
+            a[i * lda + p]
+        }
+    };
+
+    for (i, row) in c.chunks_exact_mut(gemm.n).take(gemm.m).enumerate() {
+        row.fill(0.0);
+
+        for p in 0..gemm.k {
+            let scale = a_at(i, p);
+
+            if gemm.b.trans {
+                for (j, out) in row.iter_mut().enumerate() {
+                    *out += scale * b[j * ldb + p];
+                }
+            } else {
+                axpy(scale, &b[p * ldb..p * ldb + gemm.n], row);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "pure")]
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
+
+// A column at a time like LAPACK's dgetf2. The columns are contiguous, so the pivot is
+// the iamax of the part of the column from the diagonal down.
+#[cfg(feature = "pure")]
+pub(super) fn getrf(m: usize, n: usize, a: &mut [f64], ipiv: &mut [i32]) -> i32 {
+    let mut info = 0;
+
+    for j in 0..m.min(n) {
+        let p = j + iamax(&a[j * m + j..(j + 1) * m]);
+        ipiv[j] = p as i32 + 1;
+
+        if a[j * m + p] != 0.0 {
+            if p != j {
+                for col in 0..n {
+                    a.swap(col * m + j, col * m + p);
+                }
+            }
+
+            let pivot = a[j * m + j];
+            for el in a[j * m + j + 1..(j + 1) * m].iter_mut() {
+                *el /= pivot;
+            }
+        } else if info == 0 {
+            info = j as i32 + 1;
+        }
+
+        let (left, right) = a.split_at_mut((j + 1) * m);
+        let l = &left[j * m + j + 1..];
+
+        for col in right.chunks_exact_mut(m) {
+            let scale = col[j];
+            axpy(-scale, l, &mut col[j + 1..]);
+        }
+    }
+
+    info
+}
+
+// Householder reflections like LAPACK's dgeqr2, with the reflector of dlarfg: the column
+// from the diagonal down becomes [beta, 0, ..] by I - tau v v^T, v starting with a 1.
+#[cfg(feature = "pure")]
+pub(super) fn geqrf(m: usize, n: usize, a: &mut [f64], tau: &mut [f64]) -> i32 {
+    for j in 0..m.min(n) {
+        let column = &mut a[j * m + j..(j + 1) * m];
+        let alpha = column[0];
+        let norm = column[1..]
+            .iter()
+            .fold(0.0, |norm: f64, &el| norm.hypot(el));
+
+        if norm == 0.0 {
+            tau[j] = 0.0;
+            continue;
+        }
+
+        let beta = -alpha.hypot(norm).copysign(alpha);
+        tau[j] = (beta - alpha) / beta;
+        scal(1.0 / (alpha - beta), &mut column[1..]);
+        column[0] = beta;
+
+        let (left, right) = a.split_at_mut((j + 1) * m);
+        let v = &left[j * m + j + 1..];
+
+        for col in right.chunks_exact_mut(m) {
+            let col = &mut col[j..];
+            let w = tau[j] * (col[0] + dot(v, &col[1..]));
+            col[0] -= w;
+            axpy(-w, v, &mut col[1..]);
+        }
+    }
+
+    0
+}
+
+// Like LAPACK's dorg2r, applying the reflectors from the last one to the first to the
+// columns of the identity, each column of Q taking the place of its reflector.
+#[cfg(feature = "pure")]
+pub(super) fn orgqr(m: usize, n: usize, a: &mut [f64], tau: &[f64]) -> i32 {
+    for i in (0..n).rev() {
+        a[i * m + i] = 1.0;
+
+        let (left, right) = a.split_at_mut((i + 1) * m);
+        let v = &left[i * m + i..];
+
+        for col in right.chunks_exact_mut(m).take(n - i - 1) {
+            let col = &mut col[i..];
+            let w = tau[i] * dot(v, col);
+            axpy(-w, v, col);
+        }
+
+        let column = &mut a[i * m..(i + 1) * m];
+        scal(-tau[i], &mut column[i + 1..]);
+        column[i] = 1.0 - tau[i];
+        column[..i].fill(0.0);
+    }
+
+    0
+}
+
+// The exact norm of the inverse of L U, a column at a time. The row swaps only reorder the
+// columns of the inverse of the matrix, which changes neither norm.
+#[cfg(feature = "pure")]
+pub(super) fn gecon(one: bool, n: usize, lu: &[f64], anorm: f64, rcond: &mut f64) -> i32 {
+    let at = |i: usize, j: usize| lu[j * n + i];
+    let mut sums = vec![0.0; n];
+    let mut x = vec![0.0; n];
+
+    for c in 0..n {
+        x.fill(0.0);
+        x[c] = 1.0;
+
+        for i in c + 1..n {
+            x[i] -= (c..i).map(|j| at(i, j) * x[j]).sum::<f64>();
+        }
+
+        for i in (0..n).rev() {
+            x[i] -= (i + 1..n).map(|j| at(i, j) * x[j]).sum::<f64>();
+            x[i] /= at(i, i);
+        }
+
+        if one {
+            sums[c] = x.iter().map(|el| el.abs()).sum();
+        } else {
+            sums.iter_mut()
+                .zip(&x)
+                .for_each(|(sum, el)| *sum += el.abs());
+        }
+    }
+
+    let inv_norm = (sums.into_iter()).fold(
+        0.0,
+        |max: f64, sum| {
+            if sum > max || sum.is_nan() { sum } else { max }
+        },
+    );
+
+    *rcond = if anorm == 0.0 {
+        0.0
+    } else {
+        1.0 / inv_norm / anorm
+    };
+
+    if rcond.is_nan() { 1 } else { 0 }
+}
+
+macro_rules! impl_binary_kernel {
+    ($name: ident, $op: tt) => {
+        #[inline]
+        pub(super) fn $name<T: NumberLike>(acc: &mut [T], rhs: &[T]) {
+            debug_assert_eq!(acc.len(), rhs.len());
+
+            for (acc, &el) in acc.iter_mut().zip(rhs.iter()) {
+                *acc = *acc $op el;
+            }
+        }
+    };
+}
+
+macro_rules! impl_unary_kernel {
+    ($name: ident, $f: expr) => {
+        #[inline]
+        pub(super) fn $name(x: &mut [f64]) {
+            for el in x.iter_mut() {
+                *el = $f(*el);
+            }
+        }
+    };
+}
+
+impl_binary_kernel!(add, +);
+impl_binary_kernel!(sub, -);
+impl_binary_kernel!(mul, *);
+impl_binary_kernel!(div, /);
+
+impl_unary_kernel!(sqrt, f64::sqrt);
+impl_unary_kernel!(inv, |el: f64| 1.0 / el);
+impl_unary_kernel!(inv_sqrt, |el: f64| 1.0 / el.sqrt());
+impl_unary_kernel!(exp, f64::exp);
+impl_unary_kernel!(ln, f64::ln);
+impl_unary_kernel!(expm1, f64::exp_m1);
+impl_unary_kernel!(log1p, f64::ln_1p);
+impl_unary_kernel!(log2, f64::log2);
+impl_unary_kernel!(log10, f64::log10);
+impl_unary_kernel!(exp2, f64::exp2);
+
+#[cfg(feature = "pure")]
+pub(crate) struct PureBackend;
+
+#[cfg(feature = "pure")]
+impl<T: NumberLike> VectorMathBackend<T> for PureBackend {
+    #[inline]
+    fn scal(alpha: T, x: &mut [T]) {
+        scal(alpha, x)
+    }
+
+    #[inline]
+    fn axpy(alpha: T, x: &[T], y: &mut [T]) {
+        axpy(alpha, x, y)
+    }
+
+    #[inline]
+    fn axpy_strided(alpha: T, x: &[T], incx: usize, y: &mut [T]) {
+        axpy_strided(alpha, x, incx, y)
+    }
+
+    #[inline]
+    fn add(acc: &mut [T], rhs: &[T]) {
+        add(acc, rhs)
+    }
+
+    #[inline]
+    fn sub(acc: &mut [T], rhs: &[T]) {
+        sub(acc, rhs)
+    }
+
+    #[inline]
+    fn mul(acc: &mut [T], rhs: &[T]) {
+        mul(acc, rhs)
+    }
+
+    #[inline]
+    fn div(acc: &mut [T], rhs: &[T]) {
+        div(acc, rhs)
+    }
+}
+
+#[cfg(feature = "pure")]
+impl UnaryMathBackend for PureBackend {
+    #[inline]
+    fn sqrt(x: &mut [f64]) {
+        sqrt(x)
+    }
+
+    #[inline]
+    fn inv(x: &mut [f64]) {
+        inv(x)
+    }
+
+    #[inline]
+    fn inv_sqrt(x: &mut [f64]) {
+        inv_sqrt(x)
+    }
+
+    #[inline]
+    fn exp(x: &mut [f64]) {
+        exp(x)
+    }
+
+    #[inline]
+    fn ln(x: &mut [f64]) {
+        ln(x)
+    }
+
+    #[inline]
+    fn expm1(x: &mut [f64]) {
+        expm1(x)
+    }
+
+    #[inline]
+    fn log1p(x: &mut [f64]) {
+        log1p(x)
+    }
+
+    #[inline]
+    fn log2(x: &mut [f64]) {
+        log2(x)
+    }
+
+    #[inline]
+    fn log10(x: &mut [f64]) {
+        log10(x)
+    }
+
+    #[inline]
+    fn exp2(x: &mut [f64]) {
+        exp2(x)
+    }
+}
+
+#[cfg(feature = "pure")]
+impl ReductionBackend for PureBackend {
+    #[inline]
+    fn iamax(x: &[f64]) -> usize {
+        iamax(x)
+    }
+}
+
+#[cfg(feature = "pure")]
+impl GemmBackend for PureBackend {
+    #[inline]
+    fn gemm(gemm: &Gemm, a: &[f64], b: &[f64], c: &mut [f64]) {
+        self::gemm(gemm, a, b, c)
+    }
+}
+
+#[cfg(feature = "pure")]
+impl LinAlgBackend for PureBackend {
+    #[inline]
+    fn getrf(m: usize, n: usize, a: &mut [f64], ipiv: &mut [i32]) -> i32 {
+        getrf(m, n, a, ipiv)
+    }
+
+    #[inline]
+    fn geqrf(m: usize, n: usize, a: &mut [f64], tau: &mut [f64]) -> i32 {
+        geqrf(m, n, a, tau)
+    }
+
+    #[inline]
+    fn orgqr(m: usize, n: usize, a: &mut [f64], tau: &[f64]) -> i32 {
+        orgqr(m, n, a, tau)
+    }
+
+    #[inline]
+    fn gecon(one: bool, n: usize, lu: &[f64], anorm: f64, rcond: &mut f64) -> i32 {
+        gecon(one, n, lu, anorm, rcond)
+    }
+}
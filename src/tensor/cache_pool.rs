@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+
+/// A stored result the pool can drop.
+pub(crate) trait Evict: Send + Sync {
+    fn evict(&self);
+}
+
+struct PoolEntry {
+    bytes: usize,
+    last_used: u64,
+    slot: Weak<dyn Evict>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    budget: usize,
+    used: usize,
+    // Incremented on every read, so the entry with the smallest value is the least recent.
+    clock: u64,
+    hits: u64,
+    misses: u64,
+    entries: HashMap<usize, PoolEntry>,
+}
+
+impl PoolState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn remove(&mut self, id: usize) -> Option<Weak<dyn Evict>> {
+        let entry = self.entries.remove(&id)?;
+        self.used -= entry.bytes;
+
+        Some(entry.slot)
+    }
+
+    // Forgets the entries of caches that were dropped, their results are already freed.
+    fn prune(&mut self) {
+        let dead: Vec<usize> = (self.entries.iter())
+            .filter(|(_, entry)| entry.slot.strong_count() == 0)
+            .map(|(&id, _)| id)
+            .collect();
+
+        for id in dead {
+            self.remove(id);
+        }
+    }
+}
+
+/// A byte budget shared by the caches created with TensorPromise::cache_in().
+///
+/// Every filled cache of the pool counts the size of its result. Once the total goes over
+/// the budget, the results read least recently are dropped, and the next materialization
+/// that reads an evicted cache computes it again. A result bigger than the whole budget
+/// is never kept, so a budget of 0 disables caching without changing any value.
+///
+/// Cloning a pool gives another handle to the same budget, and the pool can be shared
+/// between threads materializing its caches.
+#[derive(Clone, Default)]
+pub struct CachePool {
+    state: Arc<Mutex<PoolState>>,
+}
+
+impl CachePool {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PoolState {
+                budget: budget_bytes,
+                ..Default::default()
+            })),
+        }
+    }
+
+    // The state is consistent between statements, so a panic elsewhere does not poison it.
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn budget(&self) -> usize {
+        self.lock().budget
+    }
+
+    /// Bytes of the results currently kept.
+    pub fn used_bytes(&self) -> usize {
+        let mut state = self.lock();
+        state.prune();
+
+        state.used
+    }
+
+    /// Reads of a filled cache.
+    pub fn hits(&self) -> u64 {
+        self.lock().hits
+    }
+
+    /// Reads of a cache that had to be computed, the first one included.
+    pub fn misses(&self) -> u64 {
+        self.lock().misses
+    }
+
+    /// Fraction of the reads that found the cache filled, 0.0 before any read.
+    pub fn hit_rate(&self) -> f64 {
+        let state = self.lock();
+        let reads = state.hits + state.misses;
+
+        if reads == 0 {
+            0.0
+        } else {
+            state.hits as f64 / reads as f64
+        }
+    }
+
+    /// Drops every kept result. The hit and miss counts are not reset.
+    pub fn clear(&self) {
+        let slots: Vec<Weak<dyn Evict>> = {
+            let mut state = self.lock();
+            state.used = 0;
+            state.entries.drain().map(|(_, entry)| entry.slot).collect()
+        };
+
+        evict_all(slots);
+    }
+
+    // Records a read of the filled cache `id`.
+    pub(crate) fn hit(&self, id: usize) {
+        let mut state = self.lock();
+        state.hits += 1;
+
+        let now = state.tick();
+        if let Some(entry) = state.entries.get_mut(&id) {
+            entry.last_used = now;
+        }
+    }
+
+    // Records that the cache `id` was computed and now holds `bytes`, evicting the least
+    // recently read results until the pool fits its budget again.
+    pub(crate) fn insert(&self, id: usize, bytes: usize, slot: Weak<dyn Evict>) {
+        // The slots are evicted once the lock is released, as evicting waits for any
+        // thread reading them, which may be waiting for the pool in turn.
+        let victims = {
+            let mut state = self.lock();
+            state.misses += 1;
+            state.prune();
+
+            if bytes > state.budget {
+                vec![slot]
+            } else {
+                // Filled again by another thread before reading it, same result.
+                state.remove(id);
+                let now = state.tick();
+
+                state.used += bytes;
+                state.entries.insert(
+                    id,
+                    PoolEntry {
+                        bytes,
+                        last_used: now,
+                        slot,
+                    },
+                );
+
+                let mut victims = Vec::new();
+                while state.used > state.budget {
+                    let oldest = (state.entries.iter())
+                        .min_by_key(|(_, entry)| entry.last_used)
+                        .map(|(&id, _)| id);
+
+                    match oldest.and_then(|id| state.remove(id)) {
+                        Some(slot) => victims.push(slot),
+                        None => break,
+                    }
+                }
+
+                victims
+            }
+        };
+
+        evict_all(victims);
+    }
+}
+
+fn evict_all(slots: Vec<Weak<dyn Evict>>) {
+    for slot in slots {
+        if let Some(slot) = slot.upgrade() {
+            slot.evict();
+        }
+    }
+}
+
+impl std::fmt::Debug for CachePool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+
+        f.debug_struct("CachePool")
+            .field("budget", &state.budget)
+            .field("used", &state.used)
+            .field("entries", &state.entries.len())
+            .field("hits", &state.hits)
+            .field("misses", &state.misses)
+            .finish()
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::CallError;
+use crate::tensor::graph::{self, NodeKind, get_id};
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::metrics;
+use crate::tensor::numerics::{self, NumericsMode};
+use crate::tensor::ops::def_op::OpKind;
+use crate::tensor::ops::{AxisNameList, ComputeWrapperSpec, cpu_compute};
+use crate::tensor::profile;
+use crate::tensor::promise::TensorPromise;
+use crate::tensor::storage::{Storage, TensorData};
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+/// The graph of a promise prepared once to be computed many times over other tensors,
+/// like a function of the tensors it was compiled with, see TensorPromise::compile().
+///
+/// The graph is sorted and its nodes numbered when it is compiled, so a call only binds
+/// the arguments and computes the nodes in order. The tensors the graph reads that are not
+/// inputs are captured as they are at compile time: writing to them afterwards does not
+/// change the results, as writes to a shared buffer copy it first, see Tensor::make_unique().
+///
+/// Caches of the graph get a result of their own, empty at compile time and shared by
+/// every call: the first call computes them, the others read that result even if the
+/// arguments changed. It can be called from several threads at once.
+pub struct CompiledGraph<T: NumberLike> {
+    steps: Box<[Step<T>]>,
+    inputs: Box<[Input]>,
+    names: Option<AxisNameList>,
+    numerics: Option<NumericsMode>,
+}
+
+// A node of the compiled graph, reading the results of the steps before it.
+enum Step<T: NumberLike> {
+    // The argument at this position of call().
+    Argument(usize),
+    Constant(TensorData<T>),
+    // Boxed, as it is much larger than the others.
+    Op(Box<OpStep<T>>),
+}
+
+struct OpStep<T: NumberLike> {
+    // Id of the node it was compiled from, for the profiler.
+    id: usize,
+    op: OpKind<T>,
+    layout: Layout,
+    inputs: Box<[usize]>,
+    cache: Option<Mutex<Option<TensorData<T>>>>,
+}
+
+impl<T: NumberLike> OpStep<T> {
+    fn cached(&self) -> Option<MutexGuard<'_, Option<TensorData<T>>>> {
+        let slot = self.cache.as_ref()?;
+
+        Some(slot.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn is_filled(&self) -> bool {
+        self.cached().is_some_and(|slot| slot.is_some())
+    }
+}
+
+// The layout the graph reads an input with, and the size of the buffer it needs.
+struct Input {
+    layout: Layout,
+    buffer_len: usize,
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> TensorPromise<T> {
+    /// Prepares the graph of this promise to be computed over other tensors in place of
+    /// `inputs`, given in the same order to CompiledGraph::call(). A tensor listed twice
+    /// is bound to its first position, and one the graph does not read is only checked
+    /// for its shape.
+    pub fn compile(&self, inputs: &[&Tensor<T>]) -> CompiledGraph<T> {
+        let mut positions: HashMap<usize, usize> = HashMap::new();
+        for (position, input) in inputs.iter().enumerate() {
+            positions.entry(input.graph.id).or_insert(position);
+        }
+
+        let root = NodeKind::Node(self.graph.clone());
+        let mut steps: Vec<Step<T>> = Vec::new();
+        let mut index: HashMap<usize, usize> = HashMap::new();
+        let mut visited: HashSet<usize> = HashSet::new();
+        let mut stack: Vec<(&NodeKind<T>, bool)> = vec![(&root, false)];
+
+        // Every input is walked, the ones of filled caches included, as the caches of
+        // the compiled graph start empty.
+        while let Some((node, exiting)) = stack.pop() {
+            if !exiting {
+                if visited.insert(get_id(node)) {
+                    stack.push((node, true));
+                    stack.extend(node_inputs(node).iter().rev().map(|input| (input, false)));
+                }
+                continue;
+            }
+
+            let inputs: Box<[usize]> = (node_inputs(node).iter())
+                .map(|input| index[&get_id(input)])
+                .collect();
+
+            let step = match node {
+                NodeKind::Edge(edge) => match positions.get(&edge.id) {
+                    Some(&position) => Step::Argument(position),
+                    None => Step::Constant(edge.peek().mark_as_not_reusable()),
+                },
+                NodeKind::Node(node) => Step::Op(Box::new(OpStep {
+                    id: node.id,
+                    op: node.op.clone(),
+                    layout: node.layout.clone(),
+                    inputs,
+                    cache: None,
+                })),
+                NodeKind::Cache(cache) => {
+                    let node = cache.get_node();
+
+                    Step::Op(Box::new(OpStep {
+                        id: node.id,
+                        op: node.op.clone(),
+                        layout: node.layout.clone(),
+                        inputs,
+                        cache: Some(Mutex::new(None)),
+                    }))
+                }
+            };
+
+            index.insert(get_id(node), steps.len());
+            steps.push(step);
+        }
+
+        let inputs = (inputs.iter())
+            .map(|input| Input {
+                layout: input.layout().clone(),
+                buffer_len: input.data().buffer_len(),
+            })
+            .collect();
+
+        CompiledGraph {
+            steps: steps.into(),
+            inputs,
+            names: self.graph.names.clone(),
+            numerics: self.graph.numerics,
+        }
+    }
+}
+
+fn node_inputs<T: Copy>(node: &NodeKind<T>) -> &[NodeKind<T>] {
+    match node {
+        NodeKind::Edge(_) => &[],
+        NodeKind::Node(node) => &node.inputs,
+        NodeKind::Cache(cache) => &cache.get_node().inputs,
+    }
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> CompiledGraph<T> {
+    /// Number of arguments call() takes.
+    pub fn arity(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Shape of the results of call().
+    pub fn shape(&self) -> &[usize] {
+        match self.steps.last() {
+            Some(Step::Op(step)) => step.layout.shape(),
+            _ => unreachable!("the output of a promise is an op"),
+        }
+    }
+
+    /// Computes the graph with `args` in place of the inputs it was compiled with.
+    /// Fails with WrongArity if there are not as many arguments as inputs, with
+    /// ArgumentShape if one does not have the shape of its input, and with Op if an
+    /// assertion of the graph does not hold, see TensorPromise::try_materialize().
+    ///
+    /// Arguments laid out like their input are read in place, the others are copied
+    /// into that layout first.
+    pub fn call(&self, args: &[&Tensor<T>]) -> Result<Tensor<T>, CallError> {
+        if args.len() != self.inputs.len() {
+            return Err(CallError::WrongArity(self.inputs.len(), args.len()));
+        }
+
+        let bound = (args.iter().enumerate())
+            .map(|(position, arg)| self.bind(position, arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let data = graph::checked(|| numerics::scoped(self.numerics, || self.run(&bound)))?;
+
+        Ok(Tensor::from_data_named(data, self.names.clone()))
+    }
+
+    fn bind(&self, position: usize, arg: &Tensor<T>) -> Result<TensorData<T>, CallError> {
+        let Input { layout, buffer_len } = &self.inputs[position];
+
+        if arg.shape() != layout.shape() {
+            return Err(CallError::ArgumentShape(
+                position,
+                layout.shape().into(),
+                arg.shape().into(),
+            ));
+        }
+
+        // Views were compiled for the strides of the input, so they must be the same.
+        let data = arg.data();
+        if data.layout().stride() == layout.stride() && data.layout().offset() == layout.offset() {
+            return Ok(data.clone().mark_as_not_reusable());
+        }
+
+        let buffer = Storage::from_vec(vec![T::default(); *buffer_len]);
+        let mut copy = TensorData::new(buffer, layout.clone());
+        copy.write_iter_as_layout(layout, data.copied_iter());
+
+        Ok(copy)
+    }
+
+    // Computes the steps in order, skipping the ones only read by filled caches.
+    fn run(&self, args: &[TensorData<T>]) -> TensorData<T> {
+        let output = self.steps.len() - 1;
+        let filled: Vec<bool> = (self.steps.iter())
+            .map(|step| matches!(step, Step::Op(step) if step.is_filled()))
+            .collect();
+
+        // Reads left for each result, counted from the output down as the readers of
+        // a step are after it. The steps nothing reads are not computed.
+        let mut remaining = vec![0usize; self.steps.len()];
+        remaining[output] = 1;
+        for at in (0..self.steps.len()).rev() {
+            if let Step::Op(step) = &self.steps[at]
+                && remaining[at] > 0
+                && !filled[at]
+            {
+                for &input in &step.inputs {
+                    remaining[input] += 1;
+                }
+            }
+        }
+
+        let mut results: Vec<Option<TensorData<T>>> = vec![None; self.steps.len()];
+
+        for (at, step) in self.steps.iter().enumerate() {
+            if remaining[at] == 0 {
+                continue;
+            }
+
+            results[at] = Some(match step {
+                Step::Argument(position) => args[*position].clone(),
+                Step::Constant(data) => data.clone(),
+                Step::Op(step) if filled[at] => {
+                    metrics::record_cache_read(true);
+                    let slot = step.cached().unwrap();
+
+                    slot.clone().unwrap().mark_as_not_reusable()
+                }
+                Step::Op(step) => {
+                    let inputs = (step.inputs.iter().enumerate())
+                        .map(|(nth, &input)| {
+                            let data = take(&mut results, &mut remaining, input);
+
+                            // Read twice by the op, which holds a clone from the first read.
+                            if step.inputs[..nth].contains(&input) {
+                                data.mark_as_not_reusable()
+                            } else {
+                                data
+                            }
+                        })
+                        .collect();
+                    let result = profile::timed(step.id, step.op.as_str(), || {
+                        cpu_compute(&step.op, &step.layout, inputs)
+                    });
+
+                    match step.cached() {
+                        Some(mut slot) => {
+                            metrics::record_cache_read(false);
+                            slot.get_or_insert_with(|| result.clone());
+
+                            result.mark_as_not_reusable()
+                        }
+                        None => result,
+                    }
+                }
+            });
+        }
+
+        take(&mut results, &mut remaining, output).mark_as_not_reusable()
+    }
+}
+
+// The result at `at`, moved out on its last read so the op reading it can reuse it.
+fn take<T: Copy>(
+    results: &mut [Option<TensorData<T>>],
+    remaining: &mut [usize],
+    at: usize,
+) -> TensorData<T> {
+    remaining[at] -= 1;
+
+    match remaining[at] {
+        0 => results[at].take().unwrap(),
+        _ => (results[at].clone().unwrap()).mark_as_not_reusable(),
+    }
+}
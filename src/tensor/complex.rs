@@ -0,0 +1,149 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::OpError;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+/// A complex number. It has the same memory layout as the MKL complex types,
+/// so buffers of it can be handed directly to the vz* routines.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    #[inline]
+    pub fn new(re: T, im: T) -> Self {
+        Self { re, im }
+    }
+}
+
+impl<T: NumberLike> Complex<T> {
+    #[inline]
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+}
+
+impl Complex<f64> {
+    #[inline]
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    #[inline]
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+}
+
+impl<T: NumberLike> Add for Complex<T> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl<T: NumberLike> Sub for Complex<T> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl<T: NumberLike> Mul for Complex<T> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl<T: NumberLike> Div for Complex<T> {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: Self) -> Self::Output {
+        let denominator = rhs.re * rhs.re + rhs.im * rhs.im;
+
+        Self::new(
+            (self.re * rhs.re + self.im * rhs.im) / denominator,
+            (self.im * rhs.re - self.re * rhs.im) / denominator,
+        )
+    }
+}
+
+impl<T: NumberLike> Neg for Complex<T> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self::new(-self.re, -self.im)
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Complex<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let im = self.im.to_string();
+
+        if im.starts_with('-') {
+            write!(f, "{}{}i", self.re, im)
+        } else {
+            write!(f, "{}+{}i", self.re, im)
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+// These are eager as the output type is not the same as the input type,
+// which the graph does not support.
+impl Tensor<Complex<f64>> {
+    /// Builds a complex tensor out of its real and imaginary parts, which must have
+    /// the same shape.
+    pub fn from_parts_complex(re: &Tensor<f64>, im: &Tensor<f64>) -> Result<Self, OpError> {
+        if re.shape() != im.shape() {
+            return Err(OpError::NotSameShape(re.shape().into(), im.shape().into()));
+        }
+
+        Ok(Tensor::from_iter(
+            re.iter()
+                .zip(im.iter())
+                .map(|(&re, &im)| Complex::new(re, im)),
+            re.shape(),
+        ))
+    }
+
+    pub fn real(&self) -> Tensor<f64> {
+        Tensor::from_iter(self.iter().map(|c| c.re), self.shape())
+    }
+
+    pub fn imag(&self) -> Tensor<f64> {
+        Tensor::from_iter(self.iter().map(|c| c.im), self.shape())
+    }
+
+    /// Element-wise `atan2(im, re)`.
+    pub fn angle(&self) -> Tensor<f64> {
+        Tensor::from_iter(self.iter().map(|c| c.arg()), self.shape())
+    }
+
+    /// Element-wise magnitude.
+    pub fn abs_complex(&self) -> Tensor<f64> {
+        Tensor::from_iter(self.iter().map(|c| c.abs()), self.shape())
+    }
+
+    pub fn conj(&self) -> Tensor<Complex<f64>> {
+        Tensor::from_iter(self.iter().map(|c| c.conj()), self.shape())
+    }
+}
@@ -0,0 +1,109 @@
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::OpError;
+use crate::tensor::graph::NodeKind;
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::mem_formats::slice::SliceKind;
+use crate::tensor::ops::ComputeWrapperSpec;
+use crate::tensor::ops::def_op::OpKind;
+use crate::tensor::promise::TensorPromise;
+use crate::tensor::storage::TensorData;
+use crate::tensor::traits::Dimension;
+
+/// Assembles a tensor from promises placed into windows of it, like writing blocks into
+/// a bigger result, as a single node computed when the composite is materialized.
+/// Every element must be written by exactly one placement, unless the composite has
+/// a fill value for the ones no placement writes, see with_fill().
+///
+/// The placed promises are computed like any other input and then copied into their window.
+pub struct CompositeBuilder<T: NumberLike> {
+    shape: Box<[usize]>,
+    windows: Vec<Layout>,
+    promises: Vec<TensorPromise<T>>,
+    fill: Option<T>,
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> CompositeBuilder<T> {
+    pub fn new(shape: &[usize]) -> Self {
+        Self {
+            shape: shape.into(),
+            windows: Vec::new(),
+            promises: Vec::new(),
+            fill: None,
+        }
+    }
+
+    /// The value of the elements no placement writes, which are an error at build() otherwise.
+    pub fn with_fill(mut self, fill: T) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// Writes `promise` into the slice `range` of the output, see Tensor::slice().
+    /// Fails with PlacementShape if the promise does not have the shape of the slice.
+    pub fn place(&mut self, range: &[SliceKind], promise: TensorPromise<T>) -> Result<(), OpError> {
+        let window = Layout::from_shape(&self.shape, 0).slice(range)?;
+
+        if window.shape() != promise.shape() {
+            return Err(OpError::PlacementShape(
+                self.windows.len(),
+                window.shape().into(),
+                promise.shape().into(),
+            ));
+        }
+
+        self.windows.push(window);
+        self.promises.push(promise);
+
+        Ok(())
+    }
+
+    /// The promise of the composite. Fails with OverlappingPlacements if two placements
+    /// write the same element, and with UncoveredElements if some element is written by
+    /// none and there is no fill value.
+    pub fn build(self) -> Result<TensorPromise<T>, OpError> {
+        self.check_coverage()?;
+
+        let inputs = (self.promises.iter())
+            .map(|promise| NodeKind::Node(promise.graph.clone()))
+            .collect();
+
+        TensorPromise::new(
+            OpKind::Composite {
+                shape: self.shape,
+                windows: self.windows.into(),
+                fill: self.fill.unwrap_or_default(),
+            },
+            inputs,
+        )
+    }
+
+    // Marks every element with the placement writing it, the first one written twice
+    // being an overlap.
+    fn check_coverage(&self) -> Result<(), OpError> {
+        const UNCOVERED: usize = usize::MAX;
+
+        let len = self.shape.iter().product();
+        let mut owners = TensorData::from_vec(vec![UNCOVERED; len], &self.shape, 0);
+
+        for (index, window) in self.windows.iter().enumerate() {
+            // The window is a slice of a layout of the same shape as the owners.
+            let owner = unsafe { owners.iter_as_layout(window) }.find(|&&owner| owner != UNCOVERED);
+
+            if let Some(&owner) = owner {
+                return Err(OpError::OverlappingPlacements(owner, index));
+            }
+
+            owners.write_iter_as_layout(window, std::iter::repeat(index));
+        }
+
+        if self.fill.is_none() {
+            let uncovered = owners.iter().filter(|&&owner| owner == UNCOVERED).count();
+
+            if uncovered > 0 {
+                return Err(OpError::UncoveredElements(uncovered));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,47 @@
+/// Slices for Tensor::slice(), one [`SliceKind`](crate::tensor::SliceKind) per entry.
+/// An entry is either a range, which may take a step after a `;`, an index, which removes
+/// its axis, or `NewAxis`, which adds one of length 1:
+/// `s![1, ..]` is the second row of a matrix, `s![.., 0..10;2]` every other of its first
+/// ten columns, and `s![.., NewAxis, ..]` turns a `[3, 4]` matrix into `[3, 1, 4]`.
+///
+/// Negative bounds and indexes count from the end of their axis. Unlike in Python, bounds
+/// are not clamped: those outside of `-len..=len`, indexes outside of `-len..len` and empty
+/// ranges make the slice fail.
 #[macro_export]
 macro_rules! s {
-    ($($range: expr),*) => {
-        &[$($crate::tensor::SliceRange::from($range)),*]
+    (@kind $range: expr) => {{
+        #[allow(unused_imports)]
+        use $crate::tensor::NewAxis;
+
+        $crate::tensor::SliceKind::from($range)
+    }};
+    (@kind $range: expr; $step: expr) => {
+        $crate::tensor::SliceKind::Range($crate::ss!(@range $range; $step))
+    };
+    ($($range: expr $(; $step: expr)?),*) => {
+        &[$($crate::s!(@kind $range $(; $step)?)),*]
+    };
+}
+
+/// Ranges for Tensor::step_slice(), each of them taking an optional step after a `;` like in
+/// [`s!`], as in `ss![0..10;2, 9..0;-1, ..]`. Indexes and new axes are only taken by [`s!`].
+#[macro_export]
+macro_rules! ss {
+    (@range $range: expr) => {
+        $crate::tensor::StepSliceRange::from($crate::tensor::SliceRange::from($range))
+    };
+    (@range $range: expr; $step: expr) => {{
+        // Reversed bounds such as `9..0;-1` are meant to be walked backwards.
+        #[allow(clippy::reversed_empty_ranges)]
+        let range = $range;
+
+        $crate::tensor::StepSliceRange::new(
+            range,
+            ::core::num::NonZeroI32::new($step).expect("the step of a slice can't be zero"),
+        )
+    }};
+    ($($range: expr $(; $step: expr)?),*) => {
+        &[$($crate::ss!(@range $range $(; $step)?)),*]
     };
 }
 
@@ -19,6 +59,74 @@ macro_rules! ones {
     };
 }
 
+/// A 1-D tensor of f64 from its elements, each cast with `as f64`: `vector![1, 2.5]`.
+#[macro_export]
+macro_rules! vector {
+    ($($el: expr),* $(,)?) => {{
+        let v: Vec<f64> = vec![$($el as f64),*];
+        let size = v.len();
+
+        $crate::tensor::Tensor::from_vec(v, &[size])
+    }};
+}
+
+/// A 2-D tensor of f64 from its rows, each element cast with `as f64`: `matrix![[1, 2], [3, 4]]`
+/// is `[2, 2]`, with 1 and 2 on its first row.
+///
+/// Rows of different lengths do not compile:
+///
+/// ```compile_fail
+/// use simple_tensor::matrix;
+///
+/// let ragged = matrix![[1, 2], [3]];
+/// ```
+#[macro_export]
+macro_rules! matrix {
+    // Every row is compared element by element against the first one,
+    // so a ragged matrix is rejected when the macro expands.
+    (@check $first: tt $($rest: tt)*) => {
+        $($crate::matrix!(@same_len $first $rest);)*
+    };
+
+    (@same_len [$a: expr $(, $ra: expr)*] [$b: expr $(, $rb: expr)*]) => {
+        $crate::matrix!(@same_len [$($ra),*] [$($rb),*])
+    };
+
+    (@same_len [] []) => {};
+
+    (@same_len [$($a: expr),+] []) => {
+        compile_error!("all the rows of matrix! must have the same length")
+    };
+
+    (@same_len [] [$($b: expr),+]) => {
+        compile_error!("all the rows of matrix! must have the same length")
+    };
+
+    ($([$($el: expr),* $(,)?]),+ $(,)?) => {{
+        $crate::matrix!(@check $([$($el),*])+);
+
+        let rows: &[&[f64]] = &[$(&[$($el as f64),*]),+];
+
+        $crate::tensor::Tensor::from_vec(rows.concat(), &[rows.len(), rows[0].len()])
+    }};
+}
+
+/// A tensor of f64, or of the type given after the text, read from nested lists like
+/// `parse_tensor!("[[1, 2], [3, 4]]")` or `parse_tensor!("[1, -2]", i64)`, for fixtures.
+/// Panics with the error of Tensor::from_str_data() if the text is not a valid tensor.
+#[macro_export]
+macro_rules! parse_tensor {
+    ($text: expr) => {
+        $crate::parse_tensor!($text, f64)
+    };
+    ($text: expr, $dtype: ty) => {
+        match $crate::tensor::Tensor::<$dtype>::from_str_data($text) {
+            Ok(tensor) => tensor,
+            Err(err) => panic!("parse_tensor!: {}", err),
+        }
+    };
+}
+
 pub mod arange {
     use crate::tensor::Tensor;
 
@@ -17,6 +17,7 @@ pub trait NumberLike:
     + Neg<Output = Self>
     + Default
     + Debug
+    + PartialEq
 {
 }
 
@@ -29,5 +30,6 @@ impl<T> NumberLike for T where
         + Neg<Output = T>
         + Default
         + Debug
+        + PartialEq
 {
 }
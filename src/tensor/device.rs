@@ -0,0 +1,33 @@
+use std::fmt;
+
+/// Where the elements of a tensor live, and so which backend computes the ops reading them.
+/// Only the CPU has a backend for now, the other devices are carried through graphs so
+/// placement can be validated before one exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Device {
+    #[default]
+    Cpu,
+    // Ordinal of the GPU.
+    Gpu(usize),
+}
+
+impl Device {
+    #[inline]
+    pub fn is_cpu(&self) -> bool {
+        matches!(self, Device::Cpu)
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Device::Cpu => write!(f, "cpu"),
+            Device::Gpu(ordinal) => write!(f, "gpu:{}", ordinal),
+        }
+    }
+}
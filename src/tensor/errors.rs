@@ -1,15 +1,186 @@
+use std::borrow::Cow;
+
+use crate::tensor::device::Device;
+use crate::tensor::shape_check::ShapeMismatch;
+
+/// Where an op failed to be built: its name, the shapes of all of its inputs,
+/// which of them was rejected, and the chain of ops that produced that input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpContext {
+    op: &'static str,
+    input: usize,
+    shapes: Box<[Box<[usize]>]>,
+    breadcrumb: Box<[Box<str>]>,
+}
+
+impl OpContext {
+    pub(crate) fn new(
+        op: &'static str,
+        input: usize,
+        shapes: Box<[Box<[usize]>]>,
+        breadcrumb: Box<[Box<str>]>,
+    ) -> Self {
+        Self {
+            op,
+            input,
+            shapes,
+            breadcrumb,
+        }
+    }
+
+    /// Name of the op that failed, like "Add".
+    pub fn op(&self) -> &'static str {
+        self.op
+    }
+
+    /// Position of the rejected input.
+    pub fn input(&self) -> usize {
+        self.input
+    }
+
+    /// Shapes of every input of the op, in order.
+    pub fn shapes(&self) -> &[Box<[usize]>] {
+        &self.shapes
+    }
+
+    /// The failing op followed by the ops that produced its rejected input, from the
+    /// closest to the farthest. Labeled nodes read like "Mul(label=attention_scores)".
+    pub fn breadcrumb(&self) -> &[Box<str>] {
+        &self.breadcrumb
+    }
+}
+
+/// An invariant of the graph that did not hold while it was materialized,
+/// see TensorPromise::assert_finite() and the other assertions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssertionFailure {
+    label: Box<str>,
+    condition: Box<str>,
+    index: Option<usize>,
+    value: Box<str>,
+}
+
+impl AssertionFailure {
+    pub(crate) fn new(label: &str, condition: String, index: Option<usize>, value: String) -> Self {
+        Self {
+            label: label.into(),
+            condition: condition.into(),
+            index,
+            value: value.into(),
+        }
+    }
+
+    /// The label the assertion was given.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// What was expected, like "every element to be finite".
+    pub fn condition(&self) -> &str {
+        &self.condition
+    }
+
+    /// Row-major position of the first element breaking the invariant,
+    /// None if it is the shape that does not match.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// That element as printed by Debug, like "NaN", or the shape, like "[4, 64]".
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum OpError {
     InvalidViewShape,
     NonContiguousView,
+    // Length of an axis and the length of the groups it was split into.
+    IndivisibleAxis(usize, usize),
     InvalidSliceShape(usize, usize),
+    // Axis, the negative bound counting back past its start, and the length of the axis.
+    NegativeBoundOutOfRange(usize, i64, usize),
     OutOfBoundSlice,
     OutOfBoundAxes,
     CannotMatmul(usize, usize),
     CannotBroadcast,
     NotEnoughAxes(usize, usize),
+    // Rank an op expects and the bigger rank of its input.
+    RankMismatch(usize, usize),
     NotSameShape(Box<[usize]>, Box<[usize]>),
     NotSameBatch(usize, usize),
+    RepeatedAxis(usize),
+    NonPositiveEpsilon,
+    EmptyAxis(usize),
+    InvalidPadding(usize),
+    SharedBuffer,
+    ReadOnlyBuffer,
+    InvalidWindow(usize, usize),
+    InvalidDiffOrder(usize, usize),
+    UnknownAxisName(Box<str>, Box<[Box<str>]>),
+    RepeatedAxisName(Box<str>),
+    NotSameNamedShape(Box<[(Box<str>, usize)]>, Box<[(Box<str>, usize)]>),
+    AxisNamesMismatch,
+    NonContiguousBuffer,
+    // Lowest and highest positions a strided layout reads, and the length of the buffer.
+    StridedOutOfBounds(i64, i64, usize),
+    AliasedElements,
+    IndexOutOfBounds(usize, usize),
+    NotStreamable(Box<[(usize, &'static str)]>),
+    ZeroChunkSize,
+    NothingToConcat,
+    // Index of the placement of a CompositeBuilder, the shape of its window and the one
+    // of its promise.
+    PlacementShape(usize, Box<[usize]>, Box<[usize]>),
+    // Indexes of two placements writing the same element.
+    OverlappingPlacements(usize, usize),
+    // Number of elements of a composite without fill value that no placement writes.
+    UncoveredElements(usize),
+    InvalidQuantile(f64),
+    NoQuantiles,
+    InvalidProbability(f64),
+    InvalidDensity(f32),
+    // Number of elements the shape holds and the number of elements given for it.
+    ElementCount(usize, usize),
+    // Number of parameters given to an optimizer and the number of gradients, or of state
+    // buffers it kept from its first step.
+    ParamCount(usize, usize),
+    // Id of a cache a GraphRewriter was not allowed to replace.
+    ProtectedCache(usize),
+    // The depth of the graph and the limit set with set_max_graph_depth().
+    GraphTooDeep(usize, usize),
+    // Id of a node that is its own input, directly or not.
+    CyclicGraph(usize),
+    // Device of the first input of an op and of the first input placed elsewhere.
+    MixedDevices(Device, Device),
+    // Device of a tensor and the one it was asked to move to, which has no backend yet.
+    DeviceNotSupported(Device, Device),
+    // Index of the promise given to materialize_all() whose graph panicked, and the message
+    // of the panic.
+    PromisePanicked(usize, Box<str>),
+    // The inputs of an op do not fit together, with the op and its inputs attached.
+    InOp(Box<OpError>, Box<OpContext>),
+    AssertionFailed(Box<AssertionFailure>),
+}
+
+impl OpError {
+    /// The error without the context added by the ops it went through.
+    pub fn cause(&self) -> &OpError {
+        match self {
+            OpError::InOp(cause, _) => cause.cause(),
+            _ => self,
+        }
+    }
+
+    /// The op and inputs that caused this error, if they are known.
+    pub fn context(&self) -> Option<&OpContext> {
+        match self {
+            OpError::InOp(_, context) => Some(context),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for OpError {
@@ -23,6 +194,11 @@ impl std::fmt::Display for OpError {
                 f,
                 "the view is non-contiguous. you probably want a reshape instead"
             ),
+            OpError::IndivisibleAxis(len, k) => write!(
+                f,
+                "cannot split an axis of length {} into groups of {}",
+                len, k
+            ),
             OpError::OutOfBoundSlice => write!(
                 f,
                 "you cannot reference a slice that access out of bounds memory"
@@ -32,6 +208,11 @@ impl std::fmt::Display for OpError {
                 "the slice shape is bigger than the original tensor it is slicing. expected {} found {}",
                 expected, got
             ),
+            OpError::NegativeBoundOutOfRange(axis, bound, len) => write!(
+                f,
+                "the bound {} of axis {} is before the start of an axis of length {}. negative bounds go from -{} to -1",
+                bound, axis, len, len
+            ),
             OpError::OutOfBoundAxes => {
                 write!(f, "cannot reference out of bounds axes")
             }
@@ -52,6 +233,13 @@ impl std::fmt::Display for OpError {
                     expected, got
                 )
             }
+            OpError::RankMismatch(expected, got) => {
+                write!(
+                    f,
+                    "there are too many axes for this operation. expected {} found {}",
+                    expected, got
+                )
+            }
             OpError::NotSameShape(expected, got) => {
                 write!(f, "expected {:?}, but got {:?}", *expected, *got)
             }
@@ -62,8 +250,602 @@ impl std::fmt::Display for OpError {
                     expected, got
                 )
             }
+            OpError::RepeatedAxis(axis) => {
+                write!(f, "the axis {} was referenced more than once", axis)
+            }
+            OpError::NonPositiveEpsilon => {
+                write!(f, "epsilon must be strictly positive")
+            }
+            OpError::EmptyAxis(axis) => {
+                write!(f, "the axis {} has no elements", axis)
+            }
+            OpError::InvalidPadding(axis) => {
+                write!(
+                    f,
+                    "the padding of the axis {} is not valid for this mode",
+                    axis
+                )
+            }
+            OpError::SharedBuffer => {
+                write!(
+                    f,
+                    "cannot write into a tensor whose buffer is shared with other tensors or promises"
+                )
+            }
+            OpError::ReadOnlyBuffer => {
+                write!(
+                    f,
+                    "cannot write into a read-only tensor, like one mapped from a file. use make_unique() to copy it first"
+                )
+            }
+            OpError::InvalidWindow(window, len) => {
+                write!(
+                    f,
+                    "a window of {} does not fit in an axis of length {}",
+                    window, len
+                )
+            }
+            OpError::InvalidDiffOrder(n, len) => {
+                write!(
+                    f,
+                    "a difference of order {} needs between 1 and {} for an axis of length {}",
+                    n,
+                    len.saturating_sub(1),
+                    len
+                )
+            }
+            OpError::UnknownAxisName(name, names) if names.is_empty() => {
+                write!(f, "there is no axis named {}, the axes have no names", name)
+            }
+            OpError::UnknownAxisName(name, names) => {
+                write!(
+                    f,
+                    "there is no axis named {}. the axes are [{}]",
+                    name,
+                    names.join(", ")
+                )
+            }
+            OpError::RepeatedAxisName(name) => {
+                write!(f, "the axis name {} was used more than once", name)
+            }
+            OpError::NotSameNamedShape(expected, got) => {
+                let fmt_axes = |axes: &[(Box<str>, usize)]| {
+                    let axes: Vec<String> = axes
+                        .iter()
+                        .map(|(name, len)| format!("{}={}", name, len))
+                        .collect();
+
+                    axes.join(", ")
+                };
+
+                write!(
+                    f,
+                    "expected [{}], but got [{}]",
+                    fmt_axes(expected),
+                    fmt_axes(got)
+                )
+            }
+            OpError::AxisNamesMismatch => {
+                write!(f, "the axis names do not match the names of the type")
+            }
+            OpError::NonContiguousBuffer => {
+                write!(
+                    f,
+                    "only contiguous tensors expose their buffer. use as_contiguous() first"
+                )
+            }
+            OpError::StridedOutOfBounds(lowest, highest, len) => {
+                write!(
+                    f,
+                    "the strides read the elements {} to {}, outside of a buffer of {} elements",
+                    lowest, highest, len
+                )
+            }
+            OpError::AliasedElements => {
+                write!(
+                    f,
+                    "several elements of this tensor are the same in memory, so writing one changes the others. use the _aliased variant to write anyway"
+                )
+            }
+            OpError::NotStreamable(nodes) => {
+                let nodes: Vec<String> = nodes
+                    .iter()
+                    .map(|(id, op)| format!("{} (node {})", op, id))
+                    .collect();
+
+                write!(
+                    f,
+                    "the graph cannot be streamed, these ops are not elementwise: {}",
+                    nodes.join(", ")
+                )
+            }
+            OpError::ZeroChunkSize => {
+                write!(f, "the chunk size must be at least 1")
+            }
+            OpError::NothingToConcat => {
+                write!(f, "cannot concatenate an empty list of tensors")
+            }
+            OpError::PlacementShape(index, expected, got) => {
+                write!(
+                    f,
+                    "placement {} covers a window of shape {:?}, but its promise has shape {:?}",
+                    index, *expected, *got
+                )
+            }
+            OpError::OverlappingPlacements(first, second) => {
+                write!(f, "placements {} and {} overlap", first, second)
+            }
+            OpError::UncoveredElements(count) => {
+                write!(
+                    f,
+                    "{} elements are not covered by any placement. give the composite a fill value to allow it",
+                    count
+                )
+            }
+            OpError::InvalidQuantile(q) => {
+                write!(f, "quantiles must be between 0 and 1, found {}", q)
+            }
+            OpError::NoQuantiles => write!(f, "at least one quantile must be requested"),
+            OpError::InvalidProbability(p) => {
+                write!(f, "the probability must be within [0, 1), found {}", p)
+            }
+            OpError::InvalidDensity(density) => {
+                write!(f, "the density must be within [0, 1], found {}", density)
+            }
+            OpError::ElementCount(expected, found) => write!(
+                f,
+                "the shape holds {} elements, but {} were given",
+                expected, found
+            ),
+            OpError::ParamCount(params, found) => write!(
+                f,
+                "expected one tensor per parameter, {} parameters but {} tensors",
+                params, found
+            ),
+            OpError::ProtectedCache(id) => write!(
+                f,
+                "node #{} is a cache, replacing it requires GraphRewriter::replace_caches(true)",
+                id
+            ),
+            OpError::GraphTooDeep(depth, max) => {
+                write!(
+                    f,
+                    "the graph is {} ops deep, more than the limit of {}",
+                    depth, max
+                )
+            }
+            OpError::CyclicGraph(id) => {
+                write!(f, "the graph has a cycle going through node #{}", id)
+            }
+            OpError::MixedDevices(first, other) => write!(
+                f,
+                "the inputs are placed on different devices, {} and {}. move them to the same one with to_device()",
+                first, other
+            ),
+            OpError::DeviceNotSupported(from, to) => write!(
+                f,
+                "cannot move a tensor from {} to {}, only the cpu has a backend for now",
+                from, to
+            ),
+            OpError::PromisePanicked(index, message) => {
+                write!(f, "materializing promise {} panicked: {}", index, message)
+            }
+            OpError::AssertionFailed(failure) => {
+                write!(
+                    f,
+                    "assertion {} failed: expected {}, found {}",
+                    failure.label, failure.condition, failure.value
+                )?;
+
+                match failure.index {
+                    Some(index) => write!(f, " at index {}", index),
+                    None => Ok(()),
+                }
+            }
+            OpError::InOp(cause, context) => {
+                let shapes: Vec<String> = context
+                    .shapes
+                    .iter()
+                    .map(|shape| format!("{:?}", shape))
+                    .collect();
+
+                write!(
+                    f,
+                    "{}\n  in {} with inputs of shapes {}, rejecting input {}\n  while computing {}",
+                    cause,
+                    context.op,
+                    shapes.join(" and "),
+                    context.input,
+                    context.breadcrumb.join(" ← ")
+                )
+            }
+            OpError::IndexOutOfBounds(index, len) => {
+                write!(
+                    f,
+                    "the index {} is out of bounds for an axis of length {}",
+                    index, len
+                )
+            }
         }
     }
 }
 
 impl std::error::Error for OpError {}
+
+/// Why a CompiledGraph could not be called with the given arguments, see CompiledGraph::call().
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CallError {
+    // Number of inputs the graph was compiled with and number of arguments given.
+    WrongArity(usize, usize),
+    // Position of the argument, the shape of the input it stands for and its own shape.
+    ArgumentShape(usize, Box<[usize]>, Box<[usize]>),
+    // The graph was computed but one of its assertions did not hold.
+    Op(OpError),
+}
+
+impl From<OpError> for CallError {
+    fn from(err: OpError) -> Self {
+        CallError::Op(err)
+    }
+}
+
+impl std::fmt::Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallError::WrongArity(expected, found) => write!(
+                f,
+                "the graph was compiled with {} inputs, called with {} arguments",
+                expected, found
+            ),
+            CallError::ArgumentShape(position, expected, found) => write!(
+                f,
+                "argument {} should have the shape {:?} of its input, found {:?}",
+                position, expected, found
+            ),
+            CallError::Op(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CallError::Op(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Why a file could not be opened as a tensor, see TensorData::open_npy_mmap() and load_npz().
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IoError {
+    Io(std::io::Error),
+    // The file does not start with the NPY magic string.
+    NotNpy,
+    // Major and minor version of a format that is not supported.
+    UnsupportedVersion(u8, u8),
+    InvalidHeader(Box<str>),
+    // The dtype the element type reads and the one of the file, like "<f8" and "<f4".
+    DtypeMismatch(Box<str>, Box<str>),
+    FortranOrder,
+    // Offset of the first element in the file and the alignment the element type needs.
+    Misaligned(usize, usize),
+    // Size the file should have for its header and the one it has.
+    Truncated(u64, u64),
+    // A dtype no element type reads, like "<U5" or ">f8" on a little-endian machine.
+    UnsupportedDtype(Box<str>),
+    // The zip file of an NPZ archive is not valid.
+    InvalidArchive(Box<str>),
+    // Name given twice to the tensors of an NPZ archive.
+    DuplicateMember(Box<str>),
+    // The member of an NPZ archive that could not be read, and why.
+    InMember(Box<str>, Box<IoError>),
+}
+
+impl From<std::io::Error> for IoError {
+    fn from(err: std::io::Error) -> Self {
+        IoError::Io(err)
+    }
+}
+
+impl std::fmt::Display for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IoError::Io(err) => write!(f, "{}", err),
+            IoError::NotNpy => write!(f, "the file is not in the NPY format"),
+            IoError::UnsupportedVersion(major, minor) => {
+                write!(
+                    f,
+                    "version {}.{} of the NPY format is not supported",
+                    major, minor
+                )
+            }
+            IoError::InvalidHeader(reason) => write!(f, "invalid NPY header: {}", reason),
+            IoError::DtypeMismatch(expected, found) => {
+                write!(
+                    f,
+                    "expected elements of dtype {}, found {}",
+                    expected, found
+                )
+            }
+            IoError::FortranOrder => write!(
+                f,
+                "arrays in Fortran order are not supported. save them in C order instead"
+            ),
+            IoError::Misaligned(offset, align) => write!(
+                f,
+                "the elements start at byte {}, which is not a multiple of their alignment of {}",
+                offset, align
+            ),
+            IoError::Truncated(expected, found) => write!(
+                f,
+                "the file should be {} bytes long for its header, found {}",
+                expected, found
+            ),
+            IoError::UnsupportedDtype(dtype) => {
+                write!(f, "no element type reads the dtype {}", dtype)
+            }
+            IoError::InvalidArchive(reason) => write!(f, "invalid NPZ archive: {}", reason),
+            IoError::DuplicateMember(name) => {
+                write!(f, "the name {} is given to several tensors", name)
+            }
+            IoError::InMember(name, err) => write!(f, "in the member {}: {}", name, err),
+        }
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IoError::Io(err) => Some(err),
+            IoError::InMember(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Why a string could not be read as a tensor, see TensorData::from_str(). Every variant
+/// starts with the byte offset of the input it rejects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    // What was found, like "']'" or "the end of the input", and what was expected instead.
+    Unexpected(usize, Box<str>, &'static str),
+    // A number the element type does not read, and the element type, like "i64".
+    InvalidNumber(usize, Box<str>, &'static str),
+    // Index of a list, the length of the lists before it at the same depth and its own.
+    RaggedLength(usize, Box<[usize]>, usize, usize),
+    // Index of a number where the elements before it were lists, or the other way around.
+    RaggedDepth(usize, Box<[usize]>),
+}
+
+impl ParseError {
+    /// Byte offset of the part of the input that was rejected.
+    pub fn offset(&self) -> usize {
+        match self {
+            ParseError::Unexpected(offset, ..)
+            | ParseError::InvalidNumber(offset, ..)
+            | ParseError::RaggedLength(offset, ..)
+            | ParseError::RaggedDepth(offset, ..) => *offset,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Unexpected(offset, found, expected) => {
+                write!(
+                    f,
+                    "expected {} at byte {}, found {}",
+                    expected, offset, found
+                )
+            }
+            ParseError::InvalidNumber(offset, number, dtype) => {
+                write!(f, "{} at byte {} is not a valid {}", number, offset, dtype)
+            }
+            ParseError::RaggedLength(offset, index, expected, found) => write!(
+                f,
+                "the list {:?} at byte {} has {} elements, but the lists before it have {}",
+                index, offset, found, expected
+            ),
+            ParseError::RaggedDepth(offset, index) => write!(
+                f,
+                "the element {:?} at byte {} is not nested like the elements before it",
+                index, offset
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why a matrix could not be factorized, see Tensor::lu(), qr(), det() and cond().
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LinAlgError {
+    // Shape of a tensor that is not a matrix with at least one element.
+    NotAMatrix(Box<[usize]>),
+    // Rows and columns of a matrix that has to be square.
+    NotSquare(usize, usize),
+    // The info code of LAPACK: the diagonal of U is exactly zero at this position,
+    // counting from 1.
+    Singular(i32),
+    // The LAPACK routine, like "dgetrf", and the info code it failed with.
+    Lapack(&'static str, i32),
+}
+
+impl std::fmt::Display for LinAlgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinAlgError::NotAMatrix(shape) => write!(
+                f,
+                "expected a matrix with at least one element, found the shape {:?}",
+                shape
+            ),
+            LinAlgError::NotSquare(rows, cols) => {
+                write!(f, "expected a square matrix, found {} x {}", rows, cols)
+            }
+            LinAlgError::Singular(info) => write!(
+                f,
+                "the matrix is singular: U has a zero at position {} of its diagonal",
+                info
+            ),
+            LinAlgError::Lapack(routine, info) => {
+                write!(f, "{} failed with the info code {}", routine, info)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinAlgError {}
+
+/// Any error of the crate, for the code that does not need to tell them apart.
+///
+/// The fallible functions of the crate fail with the error of their domain, like OpError
+/// or IoError, and every one of them converts into this one with `?`. The variants wrap
+/// them as they are, so Display and source() are the ones of the wrapped error, and
+/// ErrorContext::context() adds what was being done on top.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    // A shape that does not match a ShapePattern.
+    Shape(ShapeMismatch),
+    // An op that cannot be built from its inputs.
+    Op(OpError),
+    // A graph that was built but could not be computed: too deep, cyclic, panicking or
+    // with an assertion that did not hold.
+    Compute(OpError),
+    Io(IoError),
+    Parse(ParseError),
+    Call(CallError),
+    LinAlg(LinAlgError),
+    // Something asked of a device without a backend.
+    NotSupported(OpError),
+    // What was being done, and the error it failed with.
+    Context(Cow<'static, str>, Box<Error>),
+}
+
+impl Error {
+    /// The error without the context added with ErrorContext.
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::Context(_, err) => err.cause(),
+            _ => self,
+        }
+    }
+
+    /// The OpError this error wraps, if any.
+    pub fn op_error(&self) -> Option<&OpError> {
+        match self.cause() {
+            Error::Op(err) | Error::Compute(err) | Error::NotSupported(err) => Some(err),
+            Error::Call(CallError::Op(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Shape(err) => write!(f, "{}", err),
+            Error::Op(err) | Error::Compute(err) | Error::NotSupported(err) => write!(f, "{}", err),
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Parse(err) => write!(f, "{}", err),
+            Error::Call(err) => write!(f, "{}", err),
+            Error::LinAlg(err) => write!(f, "{}", err),
+            Error::Context(message, _) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Shape(err) => err.source(),
+            Error::Op(err) | Error::Compute(err) | Error::NotSupported(err) => err.source(),
+            Error::Io(err) => err.source(),
+            Error::Parse(err) => err.source(),
+            Error::Call(err) => err.source(),
+            Error::LinAlg(err) => err.source(),
+            Error::Context(_, err) => Some(&**err),
+        }
+    }
+}
+
+impl From<OpError> for Error {
+    fn from(err: OpError) -> Self {
+        match err.cause() {
+            OpError::GraphTooDeep(..)
+            | OpError::CyclicGraph(_)
+            | OpError::PromisePanicked(..)
+            | OpError::AssertionFailed(_) => Error::Compute(err),
+            OpError::DeviceNotSupported(..) => Error::NotSupported(err),
+            _ => Error::Op(err),
+        }
+    }
+}
+
+impl From<ShapeMismatch> for Error {
+    fn from(err: ShapeMismatch) -> Self {
+        Error::Shape(err)
+    }
+}
+
+impl From<IoError> for Error {
+    fn from(err: IoError) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(IoError::Io(err))
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<CallError> for Error {
+    fn from(err: CallError) -> Self {
+        Error::Call(err)
+    }
+}
+
+impl From<LinAlgError> for Error {
+    fn from(err: LinAlgError) -> Self {
+        Error::LinAlg(err)
+    }
+}
+
+/// Adds what was being done to the error of a Result, like
+/// `load_npz(path).context("loading the checkpoint")?`.
+pub trait ErrorContext<T> {
+    /// Wraps the error with `message`. A string literal is kept as is, nothing is allocated
+    /// unless the result is an error.
+    fn context(self, message: impl Into<Cow<'static, str>>) -> Result<T, Error>;
+
+    /// Same as context(), with a message that is only built if the result is an error.
+    fn with_context<M>(self, message: impl FnOnce() -> M) -> Result<T, Error>
+    where
+        M: Into<Cow<'static, str>>;
+}
+
+impl<T, E: Into<Error>> ErrorContext<T> for Result<T, E> {
+    fn context(self, message: impl Into<Cow<'static, str>>) -> Result<T, Error> {
+        self.map_err(|err| Error::Context(message.into(), Box::new(err.into())))
+    }
+
+    fn with_context<M>(self, message: impl FnOnce() -> M) -> Result<T, Error>
+    where
+        M: Into<Cow<'static, str>>,
+    {
+        self.map_err(|err| Error::Context(message().into(), Box::new(err.into())))
+    }
+}
@@ -0,0 +1,135 @@
+use crate::tensor::Tensor;
+use crate::tensor::errors::OpError;
+use crate::tensor::ops::kernels;
+use crate::tensor::traits::Dimension;
+
+// Geometry of small vectors, the last axis of a tensor, with the leading axes being a batch.
+// These are eager operations, writing dense tensors.
+
+fn check_vectors(t: &Tensor<f64>) -> Result<usize, OpError> {
+    match t.shape().last() {
+        Some(&len) => Ok(len),
+        None => Err(OpError::NotEnoughAxes(1, 0)),
+    }
+}
+
+fn check_same_shape(lhs: &Tensor<f64>, rhs: &Tensor<f64>) -> Result<usize, OpError> {
+    if lhs.shape() != rhs.shape() {
+        return Err(OpError::NotSameShape(
+            lhs.shape().into(),
+            rhs.shape().into(),
+        ));
+    }
+
+    check_vectors(lhs)
+}
+
+fn check_3d(len: usize) -> Result<(), OpError> {
+    if len != 3 {
+        return Err(OpError::NotSameShape([3].into(), [len].into()));
+    }
+
+    Ok(())
+}
+
+// Calls `f` with every pair of vectors at the same position of the batch, and the vector
+// of the output to write, returning the output of the shape of the inputs.
+fn map_vector_pairs(
+    lhs: &Tensor<f64>,
+    rhs: &Tensor<f64>,
+    len: usize,
+    mut f: impl FnMut(&[f64], &[f64], &mut [f64]),
+) -> Tensor<f64> {
+    let lhs_values: Vec<f64> = lhs.iter().copied().collect();
+    let rhs_values: Vec<f64> = rhs.iter().copied().collect();
+    let mut out = vec![0.0; lhs_values.len()];
+
+    if len > 0 {
+        let pairs = lhs_values
+            .chunks_exact(len)
+            .zip(rhs_values.chunks_exact(len));
+
+        for ((a, b), out) in pairs.zip(out.chunks_exact_mut(len)) {
+            f(a, b, out);
+        }
+    }
+
+    Tensor::from_vec(out, lhs.shape())
+}
+
+fn norm(x: &[f64]) -> f64 {
+    kernels::dot(x, x).sqrt()
+}
+
+/// Cross product of vectors of length 3, computed along the last axis.
+/// Both tensors must have the same shape, ending with 3.
+pub fn cross(lhs: &Tensor<f64>, rhs: &Tensor<f64>) -> Result<Tensor<f64>, OpError> {
+    check_3d(check_same_shape(lhs, rhs)?)?;
+
+    Ok(map_vector_pairs(lhs, rhs, 3, |a, b, out| {
+        out[0] = a[1] * b[2] - a[2] * b[1];
+        out[1] = a[2] * b[0] - a[0] * b[2];
+        out[2] = a[0] * b[1] - a[1] * b[0];
+    }))
+}
+
+/// Angle between two 1-D vectors of the same length, in radians between 0 and pi.
+/// It is `2 atan2(|a|b| - |b|a|, |a|b| + |b|a|)`, which keeps its precision for nearly
+/// parallel and nearly opposite vectors, unlike the arccosine of the normalized dot product.
+/// The angle with a zero vector is 0.
+pub fn angle_between(lhs: &Tensor<f64>, rhs: &Tensor<f64>) -> Result<f64, OpError> {
+    for t in [lhs, rhs] {
+        if t.shape().len() != 1 {
+            return Err(OpError::NotEnoughAxes(1, t.shape().len()));
+        }
+    }
+
+    check_same_shape(lhs, rhs)?;
+
+    let a: Vec<f64> = lhs.iter().copied().collect();
+    let b: Vec<f64> = rhs.iter().copied().collect();
+    let (norm_a, norm_b) = (norm(&a), norm(&b));
+
+    let scaled = |sign: f64| -> Vec<f64> {
+        (a.iter().zip(&b))
+            .map(|(a, b)| a * norm_b + sign * b * norm_a)
+            .collect()
+    };
+
+    Ok(2.0 * norm(&scaled(-1.0)).atan2(norm(&scaled(1.0))))
+}
+
+/// Every vector along the last axis divided by its Euclidean norm. Vectors whose norm is
+/// `eps` or less have no direction and are written as zeros rather than failing the
+/// whole batch. `eps` must be strictly positive.
+pub fn normalize(t: &Tensor<f64>, eps: f64) -> Result<Tensor<f64>, OpError> {
+    if eps.is_nan() || eps <= 0.0 {
+        return Err(OpError::NonPositiveEpsilon);
+    }
+
+    let len = check_vectors(t)?;
+
+    Ok(map_vector_pairs(t, t, len, |a, _, out| {
+        let norm = norm(a);
+
+        if norm > eps {
+            out.iter_mut().zip(a).for_each(|(out, a)| *out = a / norm);
+        }
+    }))
+}
+
+/// Projection of every vector of `t` onto the one of `onto` at the same position of the
+/// batch, along the last axis. Both tensors must have the same shape. Projecting onto
+/// a zero vector gives zeros.
+pub fn project_onto(t: &Tensor<f64>, onto: &Tensor<f64>) -> Result<Tensor<f64>, OpError> {
+    let len = check_same_shape(t, onto)?;
+
+    Ok(map_vector_pairs(t, onto, len, |a, b, out| {
+        let squared_norm = kernels::dot(b, b);
+
+        if squared_norm > 0.0 {
+            let scale = kernels::dot(a, b) / squared_norm;
+            out.iter_mut().zip(b).for_each(|(out, b)| *out = scale * b);
+        }
+    }))
+}
@@ -1,20 +1,200 @@
+//! The graph of ops behind promises, and how it is computed.
+//!
+//! # Order of computation
+//!
+//! materialize() computes the nodes feeding a promise depth-first, in post-order: the inputs
+//! of every op in the order it lists them, each node the first time it is reached, and the
+//! promise last. materialize_many() walks its promises in the order they are given, the same
+//! way. The order only depends on how the graph is built, never on the ids of its nodes or
+//! the order of a map, so a graph built twice is computed in the same order.
+//! materialize_min_memory() first goes into the inputs needing the most memory, see
+//! Schedule::MinMemory, ties keeping the order of the op, which is just as deterministic.
+//!
+//! Whatever the order, every node is computed once and after all of its inputs. Its result is
+//! kept until its last read, which takes it so the op may reuse its buffer, while the reads
+//! before get a clone that cannot be reused. An op reading a result twice, like `x + x`,
+//! never reuses it, and neither do the nodes reading one of the promises materialized, whose
+//! results are handed to the caller.
+//!
+//! Tensors and filled caches are read in place, except the tensors given up with
+//! Tensor::consume(), which the op reading them takes like a result. A cache that was empty when the graph was
+//! sorted is computed from its inputs, even if another thread filled it meanwhile, and keeps
+//! the first result stored, which materialize_all() avoids by filling the caches its promises
+//! share first. Caches in a pool are computed on their own when reached. Casts and square
+//! roots fused into the op reading them are not computed, the op reads their inputs instead,
+//! see set_cast_fusion() and TensorPromise::rsqrt().
+//!
+//! With the `execution_trace` feature, TraceRecorder records the nodes in the order they are
+//! computed and how they read their inputs, which `tests/execution_order.rs` checks against
+//! these guarantees on random graphs.
+
 use std::boxed::Box;
-use std::cell::OnceCell;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
 
+use crate::tensor::cache_pool::{CachePool, Evict};
 use crate::tensor::definitions::NumberLike;
-use crate::tensor::errors::OpError;
+use crate::tensor::device::Device;
+use crate::tensor::errors::{AssertionFailure, OpContext, OpError};
+use crate::tensor::mem_formats::interner::intern_if_enabled;
 use crate::tensor::mem_formats::layout::Layout;
-use crate::tensor::ops::def_op::OpKind;
+use crate::tensor::metrics;
+use crate::tensor::numerics::{self, NumericsMode};
+use crate::tensor::ops::def_op::{OpKind, OpKindScalar, UnaryKind};
 use crate::tensor::ops::fusion::try_fuse;
-use crate::tensor::ops::{ComputeWrapperSpec, compute_layout, cpu_compute};
+use crate::tensor::ops::impl_names::name_shape_error;
+use crate::tensor::ops::{
+    AxisNameList, ComputeWrapperSpec, compute_layout, compute_names, cpu_compute,
+    cpu_compute_with_cast,
+};
+use crate::tensor::profile;
+use crate::tensor::progress::GraphProgress;
 use crate::tensor::storage::TensorData;
+use crate::tensor::trace::{self, InputRead};
 use crate::tensor::traits::Promising;
 
 static NEXT_ID: AtomicUsize = const { AtomicUsize::new(0) };
+static MAX_GRAPH_DEPTH: AtomicUsize = const { AtomicUsize::new(usize::MAX) };
+
+/// Sets the deepest graph materialize() accepts, counted in ops from the tensors it reads
+/// to the materialized promise, see TensorPromise::depth(). Deeper graphs fail with
+/// OpError::GraphTooDeep before anything is computed. Defaults to no limit.
+///
+/// Materializing and dropping graphs never recurses, whatever their depth, but other passes
+/// like fold_constants() or materialize_streamed() do, and a limit keeps them off the stack.
+pub fn set_max_graph_depth(max_depth: usize) {
+    MAX_GRAPH_DEPTH.store(max_depth, Ordering::Relaxed);
+}
+
+pub fn max_graph_depth() -> usize {
+    MAX_GRAPH_DEPTH.load(Ordering::Relaxed)
+}
+
+static CAST_FUSION: AtomicBool = const { AtomicBool::new(true) };
+
+/// Whether a binary op that is the only reader of a cast, like `x.cast::<f64>() * &w`,
+/// converts the elements of `x` as it reads them instead of computing the cast into a
+/// tensor of its own. The results are the same either way. Enabled by default, disabling
+/// it helps telling whether a wrong result comes from the fused kernels.
+pub fn set_cast_fusion(enabled: bool) {
+    CAST_FUSION.store(enabled, Ordering::Relaxed);
+}
+
+pub fn cast_fusion() -> bool {
+    CAST_FUSION.load(Ordering::Relaxed)
+}
+
+static GRAPH_ASSERTIONS: AtomicBool = const { AtomicBool::new(true) };
+
+thread_local! {
+    // First assertion that failed in the materialization running on this thread.
+    static ASSERTION_FAILURE: RefCell<Option<AssertionFailure>> = const { RefCell::new(None) };
+}
+
+/// Whether the assertions of a graph, like TensorPromise::assert_finite(), are checked
+/// when it is materialized. Disabled, they pass their input through without reading it,
+/// and the ops built on top of them fuse as if they were not there.
+/// Enabled by default.
+pub fn set_graph_assertions(enabled: bool) {
+    GRAPH_ASSERTIONS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn graph_assertions() -> bool {
+    GRAPH_ASSERTIONS.load(Ordering::Relaxed)
+}
+
+// Records a failed assertion, only the first one of a materialization is kept.
+pub(crate) fn fail_assertion(failure: AssertionFailure) {
+    ASSERTION_FAILURE.with_borrow_mut(|first| {
+        first.get_or_insert(failure);
+    });
+}
+
+// Whether an assertion failed so far in the materialization running on this thread.
+fn assertion_failed() -> bool {
+    ASSERTION_FAILURE.with_borrow(Option::is_some)
+}
+
+// Runs the materialization `f`, failing with OpError::AssertionFailed if an assertion
+// of its graph did not hold. The graph is still computed whole, the result is dropped.
+// It is also counted by the sink of set_metrics_sink(), if any.
+pub(crate) fn checked<R>(f: impl FnOnce() -> R) -> Result<R, OpError> {
+    let outer = ASSERTION_FAILURE.take();
+    let result = metrics::recorded(f);
+
+    match ASSERTION_FAILURE.replace(outer) {
+        Some(failure) => Err(OpError::AssertionFailed(Box::new(failure))),
+        None => Ok(result),
+    }
+}
+
+// Fails if the graph of depth `depth` goes over the limit of set_max_graph_depth().
+pub(crate) fn check_depth(depth: usize) -> Result<(), OpError> {
+    let max = max_graph_depth();
+
+    if depth > max {
+        return Err(OpError::GraphTooDeep(depth, max));
+    }
+
+    Ok(())
+}
+
+static EXP_LOG_FUSION: AtomicBool = const { AtomicBool::new(false) };
+
+/// Whether `x.exp() - 1.0` is computed as `x.expm1()` and `(x + 1.0).ln()` as `x.log1p()`
+/// when the exponential or the sum is read by nothing else in the graph. The rewritten
+/// ops keep the bits the subtraction cancels and the addition rounds off for x near 0,
+/// so the results change, for the better. Disabled by default, so that a graph computes
+/// the ops it was built with.
+pub fn set_exp_log_fusion(enabled: bool) {
+    EXP_LOG_FUSION.store(enabled, Ordering::Relaxed);
+}
+
+pub fn exp_log_fusion() -> bool {
+    EXP_LOG_FUSION.load(Ordering::Relaxed)
+}
+
+/// Identity of a tensor or a promise in the graph, cheap to copy and usable as a map key.
+/// Every edge and node gets a distinct id, so ids of tensors and promises never collide.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TensorId(pub(crate) usize);
+
+/// Order in which the nodes of a graph are computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Schedule {
+    /// Depth-first, the inputs of every op in the order it lists them.
+    #[default]
+    Topological,
+    /// Depth-first, starting with the inputs that need the most memory to compute,
+    /// so fewer intermediate results are alive at once.
+    MinMemory,
+}
+
+/// What computing a graph costs, measured without computing it.
+/// See TensorPromise::graph_stats().
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphStats {
+    nodes: usize,
+    peak_bytes: usize,
+}
+
+impl GraphStats {
+    /// Nodes of the graph, tensors and filled caches included.
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Most bytes of results, the output included, alive at once. Every op is assumed
+    /// to allocate its result, although some write it over an input that is no longer
+    /// needed, so the actual peak may be lower.
+    pub fn peak_bytes(&self) -> usize {
+        self.peak_bytes
+    }
+}
 
 //////////////////////////////////////////////////////////////////////////////////
 
@@ -25,6 +205,18 @@ pub enum NodeKind<T: Copy> {
     Node(Arc<TensorGraphNode<T>>),
 }
 
+impl<T: Copy> NodeKind<T> {
+    /// Device the result of this node is placed on.
+    #[inline]
+    pub fn device(&self) -> Device {
+        match self {
+            NodeKind::Edge(edge) => edge.get().device(),
+            NodeKind::Node(node) => node.device,
+            NodeKind::Cache(cache) => cache.node.device,
+        }
+    }
+}
+
 //////////////////////////////////////////////////////////////////////////////////
 
 #[inline]
@@ -36,6 +228,17 @@ pub fn get_id<T: Copy>(node: &NodeKind<T>) -> usize {
     }
 }
 
+pub fn get_inputs_names<T: NumberLike>(inputs: &[NodeKind<T>]) -> Box<[Option<&AxisNameList>]> {
+    inputs
+        .iter()
+        .map(|node| match &node {
+            NodeKind::Edge(edge) => edge.names(),
+            NodeKind::Node(node) => node.names.as_ref(),
+            NodeKind::Cache(cache) => cache.get_node().names.as_ref(),
+        })
+        .collect()
+}
+
 pub fn get_inputs_layout<T: NumberLike>(inputs: &[NodeKind<T>]) -> Box<[&Layout]> {
     inputs
         .iter()
@@ -53,17 +256,26 @@ fn get_inputs_tensor_data<T: Copy>(
     reference_counter: &mut HashMap<usize, usize>,
 ) -> Vec<TensorData<T>> {
     let mut inputs_data: Vec<TensorData<T>> = Vec::with_capacity(inputs.len());
-    for n in inputs.iter() {
+    for (at, n) in inputs.iter().enumerate() {
         let id = get_id(n);
 
         // If this panics, you fucked up the topological sort, congrats!
         let tensor_data = if let Some(count) = reference_counter.get_mut(&id) {
             if *count == 1 {
                 *count = 0;
+                trace::read(id, InputRead::Consumed);
 
-                computation_cache.remove(&id).unwrap()
+                // An op reading the same result twice, like `x + x`, holds a clone of it
+                // from its first read, so its buffer cannot be reused.
+                let data = computation_cache.remove(&id).unwrap();
+                if inputs[..at].iter().any(|input| get_id(input) == id) {
+                    data.mark_as_not_reusable()
+                } else {
+                    data
+                }
             } else {
                 *count -= 1;
+                trace::read(id, InputRead::Cloned);
                 computation_cache
                     .get(&id)
                     .unwrap()
@@ -81,11 +293,78 @@ fn get_inputs_tensor_data<T: Copy>(
 
     inputs_data
 }
+// Longest chain of ops listed in the breadcrumb of an error.
+const MAX_BREADCRUMB: usize = 16;
+
+// Adds the op and the shapes of its inputs to the errors caused by inputs that do not
+// fit together, along with the ops that produced the rejected input, which is the
+// first one that does not match the shape of input 0.
+// Both the fallible constructors and the panicking operators go through here.
+pub(crate) fn op_error<T: NumberLike>(
+    err: OpError,
+    op: &OpKind<T>,
+    inputs: &[NodeKind<T>],
+) -> OpError {
+    let layouts = get_inputs_layout(inputs);
+    let err = name_shape_error(err, &layouts, &get_inputs_names(inputs));
+
+    if !matches!(
+        err,
+        OpError::NotSameShape(_, _)
+            | OpError::NotSameNamedShape(_, _)
+            | OpError::CannotMatmul(_, _)
+            | OpError::NotSameBatch(_, _)
+            | OpError::MixedDevices(_, _)
+    ) {
+        return err;
+    }
+
+    let shapes: Box<[Box<[usize]>]> = layouts.iter().map(|layout| layout.shape().into()).collect();
+    let input = if let OpError::MixedDevices(first, _) = err {
+        inputs.iter().position(|input| input.device() != first)
+    } else {
+        shapes.iter().position(|shape| *shape != shapes[0])
+    };
+    let input = input.unwrap_or(shapes.len().saturating_sub(1));
+
+    let crumb = |op: &OpKind<T>, label: Option<&str>| -> Box<str> {
+        match label {
+            Some(label) => format!("{}(label={})", op.as_str(), label).into(),
+            None => op.as_str().into(),
+        }
+    };
+
+    let mut breadcrumb = vec![crumb(op, None)];
+    let mut next = inputs.get(input);
+
+    while let Some(node) = next
+        && breadcrumb.len() < MAX_BREADCRUMB
+    {
+        let node = match node {
+            NodeKind::Edge(_) => break,
+            NodeKind::Node(node) => &**node,
+            NodeKind::Cache(cache) => cache.get_node(),
+        };
+
+        breadcrumb.push(crumb(&node.op, node.label.as_deref()));
+        next = node.inputs.first();
+    }
+
+    let context = OpContext::new(op.as_str(), input, shapes, breadcrumb.into());
+
+    OpError::InOp(Box::new(err), Box::new(context))
+}
+
 //////////////////////////////////////////////////////////////////////////////////
 
 pub struct TensorGraphEdge<T: Copy> {
     pub(crate) id: usize,
     data: TensorData<T>,
+    is_constant: bool,
+    names: Option<AxisNameList>,
+    // The data of a tensor given up with Tensor::consume(), until the first op reading it
+    // takes it. `data` then only has its layout and device, without elements.
+    consumable: Option<Mutex<Option<TensorData<T>>>>,
 }
 
 impl<T: Copy> TensorGraphEdge<T> {
@@ -93,12 +372,84 @@ impl<T: Copy> TensorGraphEdge<T> {
         Self {
             id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             data,
+            is_constant: false,
+            names: None,
+            consumable: None,
+        }
+    }
+
+    // A constant edge promises that its data will never change, which allows
+    // the subgraphs depending only on constants to be folded.
+    pub fn constant(data: TensorData<T>) -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            data,
+            is_constant: true,
+            names: None,
+            consumable: None,
         }
     }
 
+    // An edge handing its data to the first op reading it, which may overwrite it.
+    pub(crate) fn consumable(data: TensorData<T>) -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            data: data.without_elements(),
+            is_constant: false,
+            names: None,
+            consumable: Some(Mutex::new(Some(data))),
+        }
+    }
+
+    // The data an op reads. The one of a consumable edge is taken, and may be overwritten
+    // unless another tensor still shares its buffer, which fails in debug builds since
+    // consume() promised otherwise.
+    pub(crate) fn read(&self) -> TensorData<T> {
+        let Some(slot) = &self.consumable else {
+            return self.data.clone().mark_as_not_reusable();
+        };
+
+        let data = (slot.lock().unwrap_or_else(PoisonError::into_inner).take())
+            .expect("a consumed tensor is only read by the first computation of its op");
+        let aliased = data.storage.is_shared();
+        debug_assert!(
+            !aliased,
+            "the buffer of a consumed tensor is shared with another tensor, which would see it overwritten"
+        );
+
+        if aliased {
+            data.mark_as_not_reusable()
+        } else {
+            data.mark_as_reusable()
+        }
+    }
+
+    // The data without taking it, for the readers that never write to their inputs.
+    pub(crate) fn peek(&self) -> TensorData<T> {
+        match &self.consumable {
+            None => self.data.clone(),
+            Some(slot) => (slot.lock().unwrap_or_else(PoisonError::into_inner).clone())
+                .expect("a consumed tensor is only read by the first computation of its op"),
+        }
+    }
+
+    pub fn with_names(mut self, names: Option<AxisNameList>) -> Self {
+        self.names = names;
+        self
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.is_constant
+    }
+
+    /// The data of the tensor, only its layout and device for a consumable one.
     pub fn get(&self) -> &TensorData<T> {
         &self.data
     }
+
+    pub(crate) fn get_mut(&mut self) -> &mut TensorData<T> {
+        &mut self.data
+    }
 }
 
 impl<T: Copy> Promising for TensorGraphEdge<T> {
@@ -106,13 +457,33 @@ impl<T: Copy> Promising for TensorGraphEdge<T> {
 
     #[inline]
     fn compute(&self) -> TensorData<T> {
-        self.data.clone()
+        self.read()
     }
 
     #[inline]
     fn layout(&self) -> &Layout {
         self.data.layout()
     }
+
+    #[inline]
+    fn device(&self) -> Device {
+        self.data.device()
+    }
+
+    #[inline]
+    fn names(&self) -> Option<&AxisNameList> {
+        self.names.as_ref()
+    }
+
+    #[inline]
+    fn depth(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn validate_acyclic(&self) -> Result<(), OpError> {
+        Ok(())
+    }
 }
 
 impl<T: Copy> Debug for TensorGraphEdge<T> {
@@ -129,6 +500,62 @@ pub struct TensorGraphNode<T: Copy> {
     pub(crate) op: OpKind<T>,
     pub(crate) inputs: Box<[NodeKind<T>]>,
     pub(crate) layout: Layout,
+    pub(crate) names: Option<AxisNameList>,
+    // Label of the progress bar shown while this node computes its graph
+    pub(crate) progress: Option<Arc<str>>,
+    // Name given by the user, shown in the errors of the ops built on top of this node
+    pub(crate) label: Option<Box<str>>,
+    // Numerics mode the graph is computed with when this node is the one materialized
+    pub(crate) numerics: Option<NumericsMode>,
+    // Every node down to the tensor this graph reads has a single input and no cache,
+    // so it is computed by following the inputs, without sorting the graph.
+    pub(crate) is_linear_chain: bool,
+    // Ops on the longest path from a tensor to this node, this one included.
+    pub(crate) depth: usize,
+    // Shared by all of its inputs, see device_over().
+    pub(crate) device: Device,
+}
+
+fn is_linear_chain<T: Copy>(inputs: &[NodeKind<T>]) -> bool {
+    match inputs {
+        [NodeKind::Edge(_)] => true,
+        [NodeKind::Node(node)] => node.is_linear_chain,
+        _ => false,
+    }
+}
+
+// Depth of a node reading `inputs`, tensors being at depth 0.
+fn depth_over<T: Copy>(inputs: &[NodeKind<T>]) -> usize {
+    let deepest = (inputs.iter())
+        .map(|input| match input {
+            NodeKind::Edge(_) => 0,
+            NodeKind::Node(node) => node.depth,
+            NodeKind::Cache(cache) => cache.node.depth,
+        })
+        .max();
+
+    deepest.unwrap_or(0) + 1
+}
+
+// Device of a node running `op` over `inputs`, which must all be placed on the same one.
+// A cast reads the tensor it converts instead of an input, and the other ops without
+// inputs, like the random ones, create their result on the cpu.
+pub(crate) fn device_over<T: Copy>(
+    op: &OpKind<T>,
+    inputs: &[NodeKind<T>],
+) -> Result<Device, OpError> {
+    let Some(first) = inputs.first() else {
+        return Ok(match op {
+            OpKind::Cast(source) => source.device(),
+            _ => Device::Cpu,
+        });
+    };
+
+    let device = first.device();
+    match inputs.iter().find(|input| input.device() != device) {
+        Some(other) => Err(OpError::MixedDevices(device, other.device())),
+        None => Ok(device),
+    }
 }
 
 impl<T: NumberLike> TensorGraphNode<T> {
@@ -136,33 +563,130 @@ impl<T: NumberLike> TensorGraphNode<T> {
         let fused = try_fuse(op, inputs);
 
         let layouts = get_inputs_layout(&fused.inputs);
-        let layout = compute_layout(&fused.op, &layouts);
+        let names = get_inputs_names(&fused.inputs);
+        let layout = compute_layout(&fused.op, &layouts).map(intern_if_enabled);
 
         if let Err(err) = layout {
-            return Err(err);
+            return Err(op_error(err, &fused.op, &fused.inputs));
         }
 
+        let device = match device_over(&fused.op, &fused.inputs) {
+            Ok(device) => device,
+            Err(err) => return Err(op_error(err, &fused.op, &fused.inputs)),
+        };
+
         let unchecked_layout = unsafe { layout.unwrap_unchecked() };
+        let names = compute_names(&fused.op, &names, &unchecked_layout);
 
         Ok(Self {
             id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            is_linear_chain: is_linear_chain(&fused.inputs),
+            depth: depth_over(&fused.inputs),
             op: fused.op,
             inputs: fused.inputs,
             layout: unchecked_layout,
+            names,
+            progress: None,
+            label: None,
+            numerics: None,
+            device,
         })
     }
 
+    // The layout is trusted, but the placement is still checked, panicking on inputs of
+    // different devices. Callers reading more than one input check device_over() first.
     pub fn with_layout(op: OpKind<T>, inputs: Box<[NodeKind<T>]>, layout: Layout) -> Self {
         let fused = try_fuse(op, inputs);
+        let layout = intern_if_enabled(layout);
+        let names = compute_names(&fused.op, &get_inputs_names(&fused.inputs), &layout);
+        let device = device_over(&fused.op, &fused.inputs)
+            .unwrap_or_else(|err| panic!("{}", op_error(err, &fused.op, &fused.inputs)));
 
         Self {
             id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            is_linear_chain: is_linear_chain(&fused.inputs),
+            depth: depth_over(&fused.inputs),
             op: fused.op,
             inputs: fused.inputs,
             layout,
+            names,
+            progress: None,
+            label: None,
+            numerics: None,
+            device,
+        }
+    }
+
+    /// Names the axes of the result of this node, replacing the ones propagated from its inputs.
+    pub fn with_names(mut self, names: Option<AxisNameList>) -> Self {
+        self.names = names;
+        self
+    }
+
+    // Copy of this node, with a new id, reading from other inputs.
+    // The inputs must have the same layouts as the original ones.
+    pub(crate) fn with_inputs(&self, inputs: Box<[NodeKind<T>]>) -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            is_linear_chain: is_linear_chain(&inputs),
+            depth: depth_over(&inputs),
+            op: self.op.clone(),
+            inputs,
+            layout: self.layout.clone(),
+            names: self.names.clone(),
+            progress: self.progress.clone(),
+            label: self.label.clone(),
+            numerics: self.numerics,
+            device: self.device,
         }
     }
 
+    // Copy of this node reading other inputs, with a new id and its layout computed again
+    // from them, which may fail unlike with_inputs().
+    pub(crate) fn rebuilt(&self, inputs: Box<[NodeKind<T>]>) -> Result<Self, OpError> {
+        let mut node = Self::new(self.op.clone(), inputs)?;
+        node.progress = self.progress.clone();
+        node.label = self.label.clone();
+        node.numerics = self.numerics;
+
+        Ok(node)
+    }
+
+    /// Names this node in the errors of the ops built on top of it.
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Computes the graph of this node in `mode` when this node is the one materialized,
+    /// instead of the mode set by set_numerics_mode(). See NumericsMode.
+    pub fn with_numerics(mut self, mode: NumericsMode) -> Self {
+        self.numerics = Some(mode);
+        self
+    }
+
+    /// Shows a progress bar labeled `label` every time this node is computed,
+    /// advancing once per evaluated node of the graph.
+    /// The bar is only drawn with the `indicatif` feature, see set_progress_output().
+    #[cfg(feature = "indicatif")]
+    pub fn with_progress(mut self, label: &str) -> Self {
+        self.progress = Some(label.into());
+        self
+    }
+
+    /// Does nothing, progress bars require the `indicatif` feature.
+    #[cfg(not(feature = "indicatif"))]
+    #[inline]
+    pub fn with_progress(self, _label: &str) -> Self {
+        self
+    }
+
+    /// Ops on the longest path from a tensor to this node, this one included.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
     // Performs a DFS topological sort on the current DAG that this leaf (sink) is part of.
     //  It should be iterated from left to right.
     // NOTE: This node is not added to the returning vec.
@@ -171,171 +695,1332 @@ impl<T: NumberLike> TensorGraphNode<T> {
     //  the cache will not be used. That will not be fixed as it would require
     //  invalidating some elements in the sorted.
     //  It's the user responsibility to use the cached node correctly.
-    // TODO: Maybe make an iterator so that we don't need to allocate a Vec
-    // still, even for big graphs, it should still be ok.
-    fn topological_sort(&self) -> (Vec<&NodeKind<T>>, HashMap<usize, usize>) {
-        let mut sorted: Vec<&NodeKind<T>> = Vec::with_capacity(64);
-        let mut reference_counter: HashMap<usize, usize> = HashMap::new();
-
-        let mut stack: Vec<(&NodeKind<T>, bool)> = Vec::new();
-
-        stack.extend(self.inputs.iter().map(|i| (i, false)));
-
-        while let Some((node, exiting)) = stack.pop() {
-            let id = get_id(node);
-
-            if exiting {
-                sorted.push(node);
-                continue;
-            }
-
-            if let Some(count) = reference_counter.get_mut(&id) {
-                *count += 1;
-                continue;
-            } else {
-                reference_counter.insert(id, 1);
-            }
+    fn topological_sort(&self) -> (Vec<&NodeKind<T>>, HashMap<usize, usize>, HashSet<usize>) {
+        topological_sort(&self.inputs)
+    }
+}
 
-            stack.push((node, true));
+// Dropping a node drops its inputs, which would recurse once per op of a long chain.
+// The inputs only this node holds are unlinked into a work list instead, and dropped there
+// once their own inputs were taken out.
+impl<T: Copy> Drop for TensorGraphNode<T> {
+    fn drop(&mut self) {
+        let mut pending: Vec<NodeKind<T>> = std::mem::take(&mut self.inputs).into_vec();
 
-            match node {
+        while let Some(input) = pending.pop() {
+            match input {
                 NodeKind::Edge(_) => {}
-                NodeKind::Node(n) => stack.extend(n.inputs.iter().rev().map(|i| (i, false))),
+                NodeKind::Node(node) => {
+                    if let Some(mut node) = Arc::into_inner(node) {
+                        pending.extend(std::mem::take(&mut node.inputs));
+                    }
+                }
                 NodeKind::Cache(cache) => {
-                    if !cache.is_cache_filled() {
-                        stack.extend(cache.get_node().inputs.iter().rev().map(|i| (i, false)))
+                    if let Some(mut cache) = Arc::into_inner(cache) {
+                        pending.extend(std::mem::take(&mut cache.node.inputs));
                     }
                 }
             }
         }
-
-        (sorted, reference_counter)
     }
 }
 
-impl<T: NumberLike + ComputeWrapperSpec> Promising for TensorGraphNode<T> {
-    type Output = T;
+// Fails with the id of a node on a cycle of the graph feeding `inputs`, as read by a node
+// with the id `root`. Every input is walked, the ones behind filled caches included.
+//
+// No cycle can be built through the public API: the inputs of a node are given when it is
+// built, so they are nodes that already exist, and they are never replaced afterwards as
+// nodes are immutable once behind their Arc. This check guards the code building graphs
+// inside the crate, like fold_constants(), against breaking that.
+pub(crate) fn validate_acyclic<T: Copy>(
+    root: usize,
+    inputs: &[NodeKind<T>],
+) -> Result<(), OpError> {
+    // Nodes in the path from the root to the one visited, and nodes fully walked
+    let mut in_path: HashSet<usize> = HashSet::from([root]);
+    let mut done: HashSet<usize> = HashSet::new();
 
-    fn compute(&self) -> TensorData<T> {
-        let (sorted_dag, mut reference_counter) = self.topological_sort();
-        let mut computation_cache: HashMap<usize, TensorData<T>> = HashMap::new();
+    let mut stack: Vec<(&NodeKind<T>, bool)> = inputs.iter().map(|i| (i, false)).collect();
 
-        for node in sorted_dag.into_iter() {
-            match node {
-                NodeKind::Edge(edge) => {
-                    computation_cache.insert(edge.id, edge.compute().mark_as_not_reusable());
-                }
-                NodeKind::Node(node) => {
-                    let inputs: Vec<TensorData<T>> = get_inputs_tensor_data(
-                        &node.inputs,
-                        &mut computation_cache,
-                        &mut reference_counter,
-                    );
+    while let Some((node, exiting)) = stack.pop() {
+        let id = get_id(node);
 
-                    let result = cpu_compute(&node.op, node.layout(), inputs);
-                    computation_cache.insert(node.id, result);
-                }
-                NodeKind::Cache(cache) => {
-                    let tensor_data = if cache.is_cache_filled() {
-                        unsafe { cache.cache.get().unwrap_unchecked().clone() }
-                            .mark_as_not_reusable()
-                    } else {
-                        let inputs: Vec<TensorData<T>> = get_inputs_tensor_data(
-                            &cache.node.inputs,
-                            &mut computation_cache,
-                            &mut reference_counter,
-                        );
-
-                        let result = cpu_compute(&cache.node.op, cache.layout(), inputs);
-                        let _ = cache.cache.set(result.clone());
-                        result.mark_as_not_reusable()
-                    };
-
-                    computation_cache.insert(cache.node.id, tensor_data);
-                }
-            }
+        if exiting {
+            in_path.remove(&id);
+            done.insert(id);
+            continue;
         }
 
-        let inputs: Vec<TensorData<T>> =
-            get_inputs_tensor_data(&self.inputs, &mut computation_cache, &mut reference_counter);
+        if in_path.contains(&id) {
+            return Err(OpError::CyclicGraph(id));
+        }
+        if done.contains(&id) {
+            continue;
+        }
 
-        cpu_compute(&self.op, self.layout(), inputs).mark_as_not_reusable()
-    }
+        let inputs: &[NodeKind<T>] = match node {
+            NodeKind::Edge(_) => &[],
+            NodeKind::Node(node) => &node.inputs,
+            NodeKind::Cache(cache) => &cache.node.inputs,
+        };
 
-    #[inline]
-    fn layout(&self) -> &Layout {
-        &self.layout
+        in_path.insert(id);
+        stack.push((node, true));
+        stack.extend(inputs.iter().map(|i| (i, false)));
     }
-}
 
-impl<T: Copy + Debug> Debug for TensorGraphNode<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "TensorGraphNode {{ id: {:?}, op: {:?},  inputs: [...] }}",
-            self.id, self.op
-        )
-    }
+    Ok(())
 }
 
-//////////////////////////////////////////////////////////////////////////////////
+// Performs a DFS topological sort on the DAG feeding all of the `roots`, roots included.
+//  Every use of a node counts as a reference, including being one of the roots,
+//  so a node is only freed once all of the roots have been read.
+//  The roots are walked in order, like the inputs of a node.
+// Also returns the caches that were empty, whose inputs are sorted before them, see
+// sorted_inputs().
+// TODO: Maybe make an iterator so that we don't need to allocate a Vec
+// still, even for big graphs, it should still be ok.
+fn topological_sort<T: Copy>(
+    roots: &[NodeKind<T>],
+) -> (Vec<&NodeKind<T>>, HashMap<usize, usize>, HashSet<usize>) {
+    let mut sorted: Vec<&NodeKind<T>> = Vec::with_capacity(64);
+    let mut reference_counter: HashMap<usize, usize> = HashMap::new();
+    let mut empty_caches: HashSet<usize> = HashSet::new();
 
-pub struct TensorGraphCacheNode<T: Copy> {
-    node: TensorGraphNode<T>,
-    cache: OnceLock<TensorData<T>>,
-}
+    let mut stack: Vec<(&NodeKind<T>, bool)> = Vec::new();
 
-impl<T: Copy> TensorGraphCacheNode<T> {
-    pub fn from_node(node: TensorGraphNode<T>) -> Self {
-        Self {
-            node,
-            cache: OnceLock::new(),
+    stack.extend(roots.iter().rev().map(|i| (i, false)));
+
+    while let Some((node, exiting)) = stack.pop() {
+        let id = get_id(node);
+
+        if exiting {
+            sorted.push(node);
+            continue;
         }
+
+        if let Some(count) = reference_counter.get_mut(&id) {
+            *count += 1;
+            continue;
+        } else {
+            reference_counter.insert(id, 1);
+        }
+
+        stack.push((node, true));
+
+        let inputs = computed_inputs(node);
+        if matches!(node, NodeKind::Cache(_)) && !inputs.is_empty() {
+            empty_caches.insert(id);
+        }
+
+        stack.extend(inputs.iter().rev().map(|i| (i, false)));
     }
 
-    pub fn get_node(&self) -> &TensorGraphNode<T> {
-        &self.node
+    (sorted, reference_counter, empty_caches)
+}
+
+// The inputs read to compute the node. Tensors and filled caches read none.
+// Caches in a pool read none either, as their result may be evicted by the time the sorted
+// graph reaches them. They are computed on their own when needed.
+fn computed_inputs<T: Copy>(node: &NodeKind<T>) -> &[NodeKind<T>] {
+    match node {
+        NodeKind::Edge(_) => &[],
+        NodeKind::Cache(cache) if cache.is_cache_filled() || cache.is_pooled() => &[],
+        NodeKind::Cache(cache) => &cache.node.inputs,
+        NodeKind::Node(node) => &node.inputs,
     }
+}
 
-    pub fn is_cache_filled(&self) -> bool {
-        self.cache.get().is_some()
+// The inputs a sorted DAG computes the node from. Same as computed_inputs(), except for
+// caches filled since the DAG was sorted, by another thread or by a pooled cache over them:
+// the inputs of the caches that were empty are still computed, see topological_sort().
+fn sorted_inputs<'a, T: Copy>(
+    node: &'a NodeKind<T>,
+    empty_caches: &HashSet<usize>,
+) -> &'a [NodeKind<T>] {
+    match node {
+        NodeKind::Cache(cache) if empty_caches.contains(&cache.node.id) => &cache.node.inputs,
+        NodeKind::Cache(_) => &[],
+        _ => computed_inputs(node),
     }
 }
 
-impl<T: NumberLike> TensorGraphCacheNode<T> {
-    pub fn new(op: OpKind<T>, inputs: Box<[NodeKind<T>]>) -> Result<Self, OpError> {
-        let node = TensorGraphNode::new(op, inputs);
+// Bytes allocated for the result of the node. Tensors and filled caches are read in place.
+fn result_bytes<T: Copy>(node: &NodeKind<T>) -> usize {
+    match node {
+        NodeKind::Edge(_) => 0,
+        NodeKind::Cache(cache) if cache.is_cache_filled() => 0,
+        NodeKind::Cache(cache) => cache.node.layout.len() * std::mem::size_of::<T>(),
+        NodeKind::Node(node) => node.layout.len() * std::mem::size_of::<T>(),
+    }
+}
 
-        match node {
-            Ok(node) => Ok(Self {
-                node: node,
-                cache: OnceLock::new(),
-            }),
-            Err(err) => Err(err),
+// Reorders a sorted DAG, Sethi-Ullman style: the result of an input stays alive while the
+// next ones are computed, so the inputs that need the most memory, beyond their own result,
+// go first. The need of a node is the most bytes alive while computing its subgraph,
+// which is exact for trees and an estimate once subgraphs are shared.
+fn schedule_min_memory<'a, T: Copy>(
+    roots: &'a [NodeKind<T>],
+    sorted: &[&'a NodeKind<T>],
+    empty_caches: &HashSet<usize>,
+) -> Vec<&'a NodeKind<T>> {
+    let mut need: HashMap<usize, usize> = HashMap::with_capacity(sorted.len());
+
+    // Inputs are always sorted before the nodes reading them.
+    for &node in sorted {
+        let mut alive = 0;
+        let mut peak = 0;
+
+        for input in by_need(sorted_inputs(node, empty_caches), &need) {
+            peak = peak.max(alive + need[&get_id(input)]);
+            alive += result_bytes(input);
         }
+
+        need.insert(get_id(node), peak.max(alive + result_bytes(node)));
     }
 
-    pub fn with_layout(op: OpKind<T>, inputs: Box<[NodeKind<T>]>, layout: Layout) -> Self {
-        Self {
-            node: TensorGraphNode::with_layout(op, inputs, layout),
-            cache: OnceLock::new(),
+    let mut scheduled: Vec<&NodeKind<T>> = Vec::with_capacity(sorted.len());
+    let mut visited: HashSet<usize> = HashSet::with_capacity(sorted.len());
+    let mut stack: Vec<(&NodeKind<T>, bool)> = Vec::new();
+
+    stack.extend(by_need(roots, &need).into_iter().rev().map(|i| (i, false)));
+
+    while let Some((node, exiting)) = stack.pop() {
+        if exiting {
+            scheduled.push(node);
+            continue;
+        }
+
+        if !visited.insert(get_id(node)) {
+            continue;
         }
+
+        stack.push((node, true));
+        stack.extend(
+            by_need(sorted_inputs(node, empty_caches), &need)
+                .into_iter()
+                .rev()
+                .map(|i| (i, false)),
+        );
     }
+
+    scheduled
 }
 
-impl<T: NumberLike + ComputeWrapperSpec> Promising for TensorGraphCacheNode<T> {
-    type Output = T;
+// The inputs from the one needing the most memory beyond its result to the one needing the least.
+// Ties keep the order of the op.
+fn by_need<'a, T: Copy>(
+    inputs: &'a [NodeKind<T>],
+    need: &HashMap<usize, usize>,
+) -> Vec<&'a NodeKind<T>> {
+    let mut inputs: Vec<&NodeKind<T>> = inputs.iter().collect();
+    inputs.sort_by_key(|input| Reverse(need[&get_id(input)].saturating_sub(result_bytes(input))));
 
-    fn compute(&self) -> TensorData<T> {
-        // TODO: Once the cuda async is implemented, it would be ideal to change this to an async
-        // OnceCell from tokio or some other library
-        self.cache.get_or_init(|| self.node.compute()).clone()
+    inputs
+}
+
+// Most bytes alive at once when computing the nodes in `order`, freeing every result
+// after its last use like compute_sorted() does. The roots are never freed.
+fn simulate_peak<T: Copy>(
+    order: &[&NodeKind<T>],
+    reference_counter: &HashMap<usize, usize>,
+    empty_caches: &HashSet<usize>,
+) -> usize {
+    let mut remaining = reference_counter.clone();
+    let mut alive = 0;
+    let mut peak = 0;
+
+    for node in order {
+        alive += result_bytes(node);
+        peak = peak.max(alive);
+
+        for input in sorted_inputs(node, empty_caches) {
+            let count = remaining.get_mut(&get_id(input)).unwrap();
+            *count -= 1;
+
+            if *count == 0 {
+                alive -= result_bytes(input);
+            }
+        }
     }
 
-    #[inline]
-    fn layout(&self) -> &Layout {
+    peak
+}
+
+// The order the nodes feeding `roots` are computed in, roots included,
+// along with the rest of what topological_sort() returns.
+fn schedule<T: Copy>(
+    roots: &[NodeKind<T>],
+    schedule: Schedule,
+) -> (Vec<&NodeKind<T>>, HashMap<usize, usize>, HashSet<usize>) {
+    let (sorted, reference_counter, empty_caches) = topological_sort(roots);
+
+    let order = match schedule {
+        Schedule::Topological => sorted,
+        Schedule::MinMemory => schedule_min_memory(roots, &sorted, &empty_caches),
+    };
+
+    (order, reference_counter, empty_caches)
+}
+
+pub(crate) fn graph_stats<T: Copy>(root: &NodeKind<T>, schedule: Schedule) -> GraphStats {
+    let (order, reference_counter, empty_caches) =
+        self::schedule(std::slice::from_ref(root), schedule);
+
+    GraphStats {
+        nodes: order.len(),
+        peak_bytes: simulate_peak(&order, &reference_counter, &empty_caches),
+    }
+}
+
+// The empty caches, outside of a pool, that the graphs of more than one of the `roots`
+// would compute, grouped in waves: the subgraph of a cache only holds shared caches of
+// earlier waves. Filling the waves in order, each cache on its own, computes every node
+// under a shared cache once, see materialize_all().
+#[cfg(feature = "parallel")]
+pub(crate) fn shared_cache_waves<T: Copy>(
+    roots: &[NodeKind<T>],
+) -> Vec<Vec<Arc<TensorGraphCacheNode<T>>>> {
+    let mut readers: HashMap<usize, usize> = HashMap::new();
+
+    for root in roots {
+        let (_, _, empty_caches) = topological_sort(std::slice::from_ref(root));
+
+        for id in empty_caches {
+            *readers.entry(id).or_default() += 1;
+        }
+    }
+
+    // Latest wave of the shared caches in the subgraph of each node, 0 if there is none.
+    let (sorted, _, _) = topological_sort(roots);
+    let mut below: HashMap<usize, usize> = HashMap::with_capacity(sorted.len());
+    let mut waves: Vec<Vec<Arc<TensorGraphCacheNode<T>>>> = Vec::new();
+
+    for node in sorted {
+        let inputs = computed_inputs(node).iter();
+        let mut wave = inputs.map(|input| below[&get_id(input)]).max().unwrap_or(0);
+
+        if let NodeKind::Cache(cache) = node
+            && readers.get(&cache.node.id).is_some_and(|&count| count > 1)
+        {
+            wave += 1;
+            if waves.len() < wave {
+                waves.push(Vec::new());
+            }
+            waves[wave - 1].push(cache.clone());
+        }
+
+        below.insert(get_id(node), wave);
+    }
+
+    waves
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> TensorGraphNode<T> {
+    // Replaces every subgraph whose leaves are all constant edges by a constant edge
+    // holding its result. Results bigger than `max_bytes` are not folded, but their
+    // own subgraphs may still be.
+    // Returns a copy of this node reading from the folded inputs and whether
+    // all of its leaves are constant and its op foldable, see OpKind::is_foldable().
+    pub(crate) fn fold_constants(&self, max_bytes: usize) -> (Self, bool) {
+        let mut folded: HashMap<usize, (NodeKind<T>, bool)> = HashMap::new();
+        let (inputs, is_constant) = fold_inputs(&self.inputs, max_bytes, &mut folded);
+
+        (
+            self.with_inputs(inputs),
+            is_constant && self.op.is_foldable(),
+        )
+    }
+}
+
+fn fold_inputs<T: NumberLike + ComputeWrapperSpec>(
+    inputs: &[NodeKind<T>],
+    max_bytes: usize,
+    folded: &mut HashMap<usize, (NodeKind<T>, bool)>,
+) -> (Box<[NodeKind<T>]>, bool) {
+    let mut all_constant = true;
+
+    let new_inputs = inputs
+        .iter()
+        .map(|input| {
+            let (node, is_constant) = fold_node(input, max_bytes, folded);
+            all_constant &= is_constant;
+
+            node
+        })
+        .collect();
+
+    (new_inputs, all_constant)
+}
+
+fn fold_node<T: NumberLike + ComputeWrapperSpec>(
+    node: &NodeKind<T>,
+    max_bytes: usize,
+    folded: &mut HashMap<usize, (NodeKind<T>, bool)>,
+) -> (NodeKind<T>, bool) {
+    let id = get_id(node);
+
+    // Shared subgraphs must stay shared after folding
+    if let Some(result) = folded.get(&id) {
+        return result.clone();
+    }
+
+    let fits = |layout: &Layout| layout.len() * std::mem::size_of::<T>() <= max_bytes;
+
+    let result = match node {
+        NodeKind::Edge(edge) => (node.clone(), edge.is_constant()),
+        NodeKind::Node(n) => {
+            let (inputs, is_constant) = fold_inputs(&n.inputs, max_bytes, folded);
+            let new_node = n.with_inputs(inputs);
+            let is_constant = is_constant && n.op.is_foldable();
+
+            if is_constant && fits(&n.layout) {
+                let data = new_node.compute();
+
+                (
+                    NodeKind::Edge(Arc::new(TensorGraphEdge::constant(data))),
+                    true,
+                )
+            } else {
+                (NodeKind::Node(Arc::new(new_node)), is_constant)
+            }
+        }
+        NodeKind::Cache(cache) => {
+            let (inputs, is_constant) = fold_inputs(&cache.node.inputs, max_bytes, folded);
+
+            if is_constant && fits(&cache.node.layout) {
+                let data = match cache.cached() {
+                    Some(data) => data,
+                    None => cache.node.with_inputs(inputs).compute(),
+                };
+
+                (
+                    NodeKind::Edge(Arc::new(TensorGraphEdge::constant(data))),
+                    true,
+                )
+            } else if cache.is_cache_filled() {
+                // The cache is already computed, so its inputs will never be read
+                (node.clone(), is_constant)
+            } else {
+                let new_cache = cache.with_node(cache.node.with_inputs(inputs));
+
+                (NodeKind::Cache(Arc::new(new_cache)), is_constant)
+            }
+        }
+    };
+
+    folded.insert(id, result.clone());
+
+    result
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> Promising for TensorGraphNode<T> {
+    type Output = T;
+
+    fn compute(&self) -> TensorData<T> {
+        self.compute_with(None)
+    }
+
+    // The last op writes straight into the buffer of `out`, see compute_root().
+    fn compute_into(&self, out: &mut TensorData<T>) {
+        self.compute_with(Some(out));
+    }
+
+    #[inline]
+    fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    #[inline]
+    fn device(&self) -> Device {
+        self.device
+    }
+
+    #[inline]
+    fn names(&self) -> Option<&AxisNameList> {
+        self.names.as_ref()
+    }
+
+    #[inline]
+    fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn validate_acyclic(&self) -> Result<(), OpError> {
+        validate_acyclic(self.id, &self.inputs)
+    }
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> TensorGraphNode<T> {
+    // Computes this node, into `out` if given, see compute_root().
+    fn compute_with(&self, out: Option<&mut TensorData<T>>) -> TensorData<T> {
+        numerics::scoped(self.numerics, || {
+            if self.is_linear_chain {
+                let result = self.compute_chain(out);
+
+                #[cfg(feature = "check_fast_paths")]
+                if self.chain_can_run_twice() {
+                    check_same_result(&result, &unobserved(|| self.compute_sorted(None)));
+                }
+
+                return result;
+            }
+
+            self.compute_sorted(out)
+        })
+    }
+
+    // Whether this linear chain can be computed a second time, which it cannot if the
+    // closure of an Apply op may have side effects, or if it reads a consumed tensor.
+    #[cfg(feature = "check_fast_paths")]
+    fn chain_can_run_twice(&self) -> bool {
+        let mut node = self;
+
+        loop {
+            if let OpKind::Apply(_, _) = node.op {
+                return false;
+            }
+
+            match &node.inputs[0] {
+                NodeKind::Node(input) => node = input,
+                NodeKind::Edge(edge) => return edge.consumable.is_none(),
+                _ => return true,
+            }
+        }
+    }
+
+    // Computes a linear chain from the tensor it reads up to this node, each result
+    // being the only input of the next op, so no map of results is needed.
+    fn compute_chain(&self, out: Option<&mut TensorData<T>>) -> TensorData<T> {
+        let mut chain: Vec<&TensorGraphNode<T>> = Vec::new();
+        let mut node = self;
+
+        let edge = loop {
+            match &node.inputs[0] {
+                NodeKind::Edge(edge) => break edge,
+                NodeKind::Node(input) => {
+                    chain.push(input);
+                    node = input;
+                }
+                NodeKind::Cache(_) => unreachable!("linear chains have no cache nodes"),
+            }
+        };
+
+        // The chain, the tensor and this node
+        let progress = GraphProgress::new(self.progress.as_deref(), chain.len() + 2);
+
+        let mut data = edge.read();
+        trace::computed(edge.id, "Tensor");
+        progress.inc();
+
+        // Each node is the only reader of the one before, so the ops exp_log_rewrite()
+        // computes from the input of their own input only need the fusion enabled.
+        let fusion = exp_log_fusion();
+        let mut rewritten: Option<OpKind<T>> = None;
+
+        let mut input = edge.id;
+        for (idx, node) in chain.iter().enumerate().rev() {
+            progress.start(&node.op);
+
+            let reader = if idx == 0 { self } else { chain[idx - 1] };
+            if fusion && let Some(kind) = exp_log_rewrite(&node.op, &reader.op) {
+                rewritten = Some(OpKind::Unary(kind));
+                metrics::record_fusions(1);
+                progress.inc();
+                continue;
+            }
+
+            let op = rewritten.take();
+            let op = op.as_ref().unwrap_or(&node.op);
+            trace::read(input, InputRead::Consumed);
+            data = profile::timed(node.id, node.op.as_str(), || {
+                cpu_compute(op, node.layout(), vec![data])
+            });
+            trace::computed(node.id, node.op.as_str());
+            input = node.id;
+
+            progress.inc();
+        }
+
+        progress.start(&self.op);
+        let op = rewritten.take();
+        let op = op.as_ref().unwrap_or(&self.op);
+        trace::read(input, InputRead::Consumed);
+        let result = profile::timed(self.id, self.op.as_str(), || {
+            compute_root(op, self.layout(), vec![data], out)
+        });
+        trace::computed(self.id, self.op.as_str());
+
+        progress.inc();
+        progress.finish();
+
+        result
+    }
+
+    fn compute_sorted(&self, out: Option<&mut TensorData<T>>) -> TensorData<T> {
+        let (sorted_dag, mut reference_counter, empty_caches) = self.topological_sort();
+        let mut computation_cache: HashMap<usize, TensorData<T>> = HashMap::new();
+
+        let fused = fused_inputs(&sorted_dag, Some(self), &reference_counter);
+
+        // This node is not in the sorted DAG, so it is counted separately
+        let progress = GraphProgress::new(self.progress.as_deref(), sorted_dag.len() + 1);
+
+        compute_sorted(
+            sorted_dag,
+            &mut computation_cache,
+            &mut reference_counter,
+            &progress,
+            &fused,
+            &empty_caches,
+        );
+
+        progress.start(&self.op);
+        let result = compute_node(
+            self,
+            &mut computation_cache,
+            &mut reference_counter,
+            &fused,
+            out,
+        )
+        .mark_as_not_reusable();
+
+        progress.inc();
+        progress.finish();
+
+        result
+    }
+}
+
+// Runs the cross-check `f` of a linear chain with no effect seen from outside: what it
+// computes is neither traced, counted, recorded nor profiled, and shows no progress.
+#[cfg(feature = "check_fast_paths")]
+fn unobserved<R>(f: impl FnOnce() -> R) -> R {
+    use crate::tensor::ops::uncounted;
+    use crate::tensor::progress;
+
+    trace::untraced(|| {
+        uncounted(|| metrics::unrecorded(|| profile::unprofiled(|| progress::hidden(f))))
+    })
+}
+
+// Both paths of a linear chain must give the same elements, NaNs included.
+#[cfg(feature = "check_fast_paths")]
+fn check_same_result<T: NumberLike>(chain: &TensorData<T>, sorted: &TensorData<T>) {
+    assert_eq!(chain.layout().shape(), sorted.layout().shape());
+
+    for (a, b) in chain.copied_iter().zip(sorted.copied_iter()) {
+        assert_eq!(
+            format!("{:?}", a),
+            format!("{:?}", b),
+            "the linear chain and the sorted graph disagree"
+        );
+    }
+}
+
+// The constant a scalar op adds to its input, if it only adds one.
+fn added_constant<T: NumberLike>(op: &OpKind<T>) -> Option<T> {
+    match op {
+        OpKind::ScalarOp(OpKindScalar::Sum(c)) => Some(*c),
+        OpKind::ScalarOp(OpKindScalar::Sub(c)) => Some(-*c),
+        _ => None,
+    }
+}
+
+// 1 is the only nonzero value equal to its square.
+fn is_one<T: NumberLike>(c: T) -> bool {
+    c != T::default() && c * c == c
+}
+
+// The op computing `reader` straight from the input of `op`, if `reader` is `exp(x) - 1`
+// or `ln(x + 1)`: expm1(x) or log1p(x). The caller checks set_exp_log_fusion() and that
+// nothing else reads `op`.
+fn exp_log_rewrite<T: NumberLike>(op: &OpKind<T>, reader: &OpKind<T>) -> Option<UnaryKind> {
+    match (op, reader) {
+        (OpKind::Unary(UnaryKind::Exp), _)
+            if added_constant(reader).is_some_and(|c| is_one(-c)) =>
+        {
+            Some(UnaryKind::Expm1)
+        }
+        (_, OpKind::Unary(UnaryKind::Ln)) if added_constant(op).is_some_and(is_one) => {
+            Some(UnaryKind::Log1p)
+        }
+        _ => None,
+    }
+}
+
+// The exponential or the sum `node` reads, if nothing else does and exp_log_rewrite()
+// computes `node` from its input.
+fn exp_log_read_by<'a, T: NumberLike>(
+    node: &'a TensorGraphNode<T>,
+    reference_counter: &HashMap<usize, usize>,
+) -> Option<&'a TensorGraphNode<T>> {
+    match &*node.inputs {
+        [NodeKind::Node(input)]
+            if exp_log_fusion()
+                && reference_counter.get(&input.id) == Some(&1)
+                && exp_log_rewrite(&input.op, &node.op).is_some() =>
+        {
+            Some(input)
+        }
+        _ => None,
+    }
+}
+
+// The input of a binary op that is a cast read by nothing else, with its position.
+fn cast_read_by<'a, T: Copy>(
+    node: &'a TensorGraphNode<T>,
+    reference_counter: &HashMap<usize, usize>,
+) -> Option<(usize, &'a TensorGraphNode<T>)> {
+    if !matches!(
+        node.op,
+        OpKind::Add | OpKind::Sub | OpKind::Mul | OpKind::Div
+    ) {
+        return None;
+    }
+
+    node.inputs
+        .iter()
+        .enumerate()
+        .find_map(|(i, input)| match input {
+            NodeKind::Node(cast)
+                if matches!(cast.op, OpKind::Cast(_))
+                    && reference_counter.get(&cast.id) == Some(&1) =>
+            {
+                Some((i, &**cast))
+            }
+            _ => None,
+        })
+}
+
+// The square root a division by it reads, if nothing else does and the numerics mode lets
+// `a / sqrt(b)` be computed as `a * rsqrt(b)`, which rounds differently. See rsqrt().
+fn sqrt_read_by<'a, T: Copy>(
+    node: &'a TensorGraphNode<T>,
+    reference_counter: &HashMap<usize, usize>,
+) -> Option<&'a TensorGraphNode<T>> {
+    if !matches!(node.op, OpKind::Div) || numerics::numerics_mode() == NumericsMode::HighAccuracy {
+        return None;
+    }
+
+    match &node.inputs[1] {
+        NodeKind::Node(sqrt)
+            if matches!(sqrt.op, OpKind::Unary(UnaryKind::Sqrt))
+                && reference_counter.get(&sqrt.id) == Some(&1) =>
+        {
+            Some(sqrt)
+        }
+        _ => None,
+    }
+}
+
+// Ids of the inputs that are not computed on their own, as the op reading them fuses them:
+// casts converted by the binary op reading them, see set_cast_fusion(), square roots
+// divided by, see sqrt_read_by(), and the exponentials and sums of exp_log_read_by().
+// An op fuses one input at most. `root` is the node computed after the sorted DAG, if any.
+fn fused_inputs<T: NumberLike>(
+    sorted_dag: &[&NodeKind<T>],
+    root: Option<&TensorGraphNode<T>>,
+    reference_counter: &HashMap<usize, usize>,
+) -> HashSet<usize> {
+    let cast_fusion = cast_fusion();
+
+    let nodes = sorted_dag.iter().filter_map(|node| match node {
+        NodeKind::Node(node) => Some(&**node),
+        _ => None,
+    });
+
+    let fused: HashSet<usize> = (nodes.chain(root))
+        .filter_map(|node| {
+            let cast = cast_read_by(node, reference_counter).filter(|_| cast_fusion);
+
+            (cast.map(|(_, cast)| cast))
+                .or_else(|| sqrt_read_by(node, reference_counter))
+                .or_else(|| exp_log_read_by(node, reference_counter))
+                .map(|input| input.id)
+        })
+        .collect();
+    metrics::record_fusions(fused.len());
+
+    fused
+}
+
+// Computes `node` from the results of its inputs, reading the source of a fused cast input
+// in place of its result, or the input of a fused square root, exponential or sum, see
+// fused_inputs(). Given `out`, the result is also written into it, see compute_root().
+fn compute_node<T: NumberLike + ComputeWrapperSpec>(
+    node: &TensorGraphNode<T>,
+    computation_cache: &mut HashMap<usize, TensorData<T>>,
+    reference_counter: &mut HashMap<usize, usize>,
+    fused: &HashSet<usize>,
+    out: Option<&mut TensorData<T>>,
+) -> TensorData<T> {
+    // The fused ops are computed on their own, and copied into `out`.
+    let into = |result: TensorData<T>, out: Option<&mut TensorData<T>>| match out {
+        Some(out) => write_into(result, out),
+        None => result,
+    };
+
+    let fused_at = (node.inputs.iter()).position(|input| fused.contains(&get_id(input)));
+
+    if let Some(fused_at) = fused_at {
+        let NodeKind::Node(input) = &node.inputs[fused_at] else {
+            unreachable!("only nodes are fused")
+        };
+
+        if let Some(kind) = exp_log_rewrite(&input.op, &node.op) {
+            let x = get_inputs_tensor_data(&input.inputs, computation_cache, reference_counter);
+            reference_counter.insert(input.id, 0);
+
+            let result = profile::timed(node.id, node.op.as_str(), || {
+                cpu_compute(&OpKind::Unary(kind), node.layout(), x)
+            });
+            trace::computed(node.id, node.op.as_str());
+
+            return into(result, out);
+        }
+
+        let other = &node.inputs[1 - fused_at..2 - fused_at];
+        let other = get_inputs_tensor_data(other, computation_cache, reference_counter).remove(0);
+        reference_counter.insert(input.id, 0);
+
+        let result = match &input.op {
+            OpKind::Cast(source) => profile::timed(node.id, node.op.as_str(), || {
+                cpu_compute_with_cast(&node.op, other, source, fused_at)
+            }),
+            // `other / sqrt(x)` as `other * rsqrt(x)`.
+            OpKind::Unary(UnaryKind::Sqrt) => {
+                let radicand =
+                    get_inputs_tensor_data(&input.inputs, computation_cache, reference_counter);
+
+                profile::timed(node.id, node.op.as_str(), || {
+                    let rsqrt = OpKind::Unary(UnaryKind::Rsqrt);
+                    let rsqrt = cpu_compute(&rsqrt, input.layout(), radicand);
+
+                    cpu_compute(&OpKind::Mul, node.layout(), vec![other, rsqrt])
+                })
+            }
+            _ => unreachable!("only casts and square roots are fused"),
+        };
+        trace::computed(node.id, node.op.as_str());
+
+        return into(result, out);
+    }
+
+    let inputs: Vec<TensorData<T>> =
+        get_inputs_tensor_data(&node.inputs, computation_cache, reference_counter);
+
+    let result = profile::timed(node.id, node.op.as_str(), || match out {
+        Some(out) => compute_root(&node.op, node.layout(), inputs, Some(out)),
+        None => cpu_compute(&node.op, node.layout(), inputs),
+    });
+    trace::computed(node.id, node.op.as_str());
+
+    result
+}
+
+// Index of the input whose buffer the kernel of `op` overwrites with the result, when it
+// can reuse it: the only input of the elementwise unary ops, the first operand of the binary
+// ones and the operand of a DynScalarOp that is not the scalar. It must have as many
+// elements as the result.
+fn overwritten_input<T: Copy>(
+    op: &OpKind<T>,
+    inputs: &[TensorData<T>],
+    layout: &Layout,
+) -> Option<usize> {
+    let idx = match op {
+        OpKind::ScalarOp(_)
+        | OpKind::FusedScalar(_)
+        | OpKind::Unary(_)
+        | OpKind::Neg
+        | OpKind::Add
+        | OpKind::Sub
+        | OpKind::Mul
+        | OpKind::Div => 0,
+        OpKind::DynScalarOp(_, scalar_idx, _) => 1 - scalar_idx,
+        _ => return None,
+    };
+
+    (inputs[idx].layout().len() == layout.len()).then_some(idx)
+}
+
+// Computes the last op of a graph. Given `out`, the op writes its result into the buffer
+// of `out`: the input it would overwrite is copied there first and handed to it as
+// reusable, so no buffer is allocated for the result. The other ops are computed into a
+// buffer of their own and copied into `out`. The result returned shares the buffer of `out`.
+// Once an assertion of the graph failed, `out` is left untouched.
+fn compute_root<T: NumberLike + ComputeWrapperSpec>(
+    op: &OpKind<T>,
+    layout: &Layout,
+    mut inputs: Vec<TensorData<T>>,
+    out: Option<&mut TensorData<T>>,
+) -> TensorData<T> {
+    let Some(out) = out else {
+        return cpu_compute(op, layout, inputs).mark_as_not_reusable();
+    };
+
+    let buffer = overwritten_input(op, &inputs, layout)
+        .filter(|_| !assertion_failed())
+        .and_then(|idx| Some((idx, out.take_vec()?)));
+
+    match buffer {
+        Some((idx, mut buffer)) => {
+            for (slot, value) in buffer.iter_mut().zip(inputs[idx].copied_iter()) {
+                *slot = value;
+            }
+
+            let shape = inputs[idx].layout().shape().to_vec();
+            inputs[idx] = TensorData::from_vec(buffer, &shape, 0).mark_as_reusable();
+
+            *out = cpu_compute(op, layout, inputs).mark_as_not_reusable();
+            out.clone()
+        }
+        None => write_into(cpu_compute(op, layout, inputs), out),
+    }
+}
+
+// Copies `result` into `out`, unless an assertion of the graph failed, which leaves `out`
+// untouched, see checked().
+fn write_into<T: NumberLike>(result: TensorData<T>, out: &mut TensorData<T>) -> TensorData<T> {
+    if assertion_failed() {
+        return result.mark_as_not_reusable();
+    }
+
+    out.write_from(&result);
+    out.clone()
+}
+
+// Computes the nodes of a sorted DAG in order, leaving in `computation_cache`
+// the results that are still referenced by someone. The inputs in `fused` are skipped,
+// they are read by the op computed after them. `empty_caches` are the caches computed
+// from their inputs, see topological_sort().
+fn compute_sorted<T: NumberLike + ComputeWrapperSpec>(
+    sorted_dag: Vec<&NodeKind<T>>,
+    computation_cache: &mut HashMap<usize, TensorData<T>>,
+    reference_counter: &mut HashMap<usize, usize>,
+    progress: &GraphProgress,
+    fused: &HashSet<usize>,
+    empty_caches: &HashSet<usize>,
+) {
+    for node in sorted_dag.into_iter() {
+        match node {
+            NodeKind::Edge(edge) => {
+                computation_cache.insert(edge.id, edge.read());
+                trace::computed(edge.id, "Tensor");
+            }
+            NodeKind::Node(node) if fused.contains(&node.id) => {}
+            NodeKind::Node(node) => {
+                progress.start(&node.op);
+
+                let result = compute_node(node, computation_cache, reference_counter, fused, None);
+                computation_cache.insert(node.id, result);
+            }
+            NodeKind::Cache(cache) => {
+                progress.start(&cache.node.op);
+
+                let tensor_data = if cache.is_pooled() {
+                    // Computed on its own, see computed_inputs(), which is not traced.
+                    trace::untraced(|| cache.compute()).mark_as_not_reusable()
+                } else if !empty_caches.contains(&cache.node.id) {
+                    let data = cache.cached().expect("only pooled caches are emptied");
+                    data.mark_as_not_reusable()
+                } else {
+                    // Its inputs were computed for it, so they are read even if it was
+                    // filled since the DAG was sorted, and the first result stored is kept.
+                    let inputs: Vec<TensorData<T>> = get_inputs_tensor_data(
+                        &cache.node.inputs,
+                        computation_cache,
+                        reference_counter,
+                    );
+
+                    let result = cache.fill_with(|| {
+                        profile::timed(cache.node.id, cache.node.op.as_str(), || {
+                            cpu_compute(&cache.node.op, cache.layout(), inputs)
+                        })
+                    });
+                    result.mark_as_not_reusable()
+                };
+                trace::computed(cache.node.id, cache.node.op.as_str());
+
+                computation_cache.insert(cache.node.id, tensor_data);
+            }
+        }
+
+        progress.inc();
+    }
+}
+
+// Numerics mode `node` is computed with when materialized, if it has one of its own.
+pub(crate) fn numerics_of<T: Copy>(node: &NodeKind<T>) -> Option<NumericsMode> {
+    match node {
+        NodeKind::Node(node) => node.numerics,
+        NodeKind::Cache(cache) => cache.node.numerics,
+        NodeKind::Edge(_) => None,
+    }
+}
+
+// Computes several sinks in a single pass over the union of their DAGs,
+// so the nodes they share are only computed once. The results are in the order of `sinks`.
+pub(crate) fn compute_many<T: NumberLike + ComputeWrapperSpec>(
+    sinks: &[NodeKind<T>],
+    schedule: Schedule,
+) -> Vec<TensorData<T>> {
+    let (sorted_dag, mut reference_counter, empty_caches) = self::schedule(sinks, schedule);
+    let mut computation_cache: HashMap<usize, TensorData<T>> = HashMap::new();
+
+    // The bar of the first sink with one tracks the whole batch
+    let label = sinks.iter().find_map(|sink| match sink {
+        NodeKind::Node(node) => node.progress.as_deref(),
+        NodeKind::Cache(cache) => cache.node.progress.as_deref(),
+        NodeKind::Edge(_) => None,
+    });
+    let progress = GraphProgress::new(label, sorted_dag.len());
+
+    // As is the mode of the first sink with one
+    let mode = sinks.iter().find_map(numerics_of);
+    numerics::scoped(mode, || {
+        let fused = fused_inputs(&sorted_dag, None, &reference_counter);
+
+        compute_sorted(
+            sorted_dag,
+            &mut computation_cache,
+            &mut reference_counter,
+            &progress,
+            &fused,
+            &empty_caches,
+        )
+    });
+    progress.finish();
+
+    let outputs = get_inputs_tensor_data(sinks, &mut computation_cache, &mut reference_counter);
+    trace::outputs_read();
+
+    (outputs.into_iter())
+        .map(|data| data.mark_as_not_reusable())
+        .collect()
+}
+
+// One line per node of the DAG feeding `root`, in the order they would be computed,
+// with the ids of its inputs listed below it:
+//   #4 Add [2, 3]
+//   ├── #2
+//   └── #3
+// A filled cache is marked and its inputs are not shown, as they are never computed again.
+pub(crate) fn graph_summary<T: NumberLike>(root: &NodeKind<T>) -> String {
+    let (sorted, _, _) = topological_sort(std::slice::from_ref(root));
+    let mut summary = String::new();
+
+    for node in sorted {
+        let (op, inputs, layout, cached) = match node {
+            NodeKind::Edge(edge) => {
+                let shape = edge.get().layout().shape();
+                summary.push_str(&format!("#{} Tensor {:?}\n", edge.id, shape));
+                continue;
+            }
+            NodeKind::Node(node) => (&node.op, &node.inputs[..], &node.layout, false),
+            NodeKind::Cache(cache) => {
+                let node = cache.get_node();
+                let filled = cache.is_cache_filled();
+                let inputs: &[NodeKind<T>] = if filled { &[] } else { &node.inputs };
+
+                (&node.op, inputs, &node.layout, filled)
+            }
+        };
+
+        summary.push_str(&format!("#{} {:?} {:?}", get_id(node), op, layout.shape()));
+        if cached {
+            summary.push_str(" [cached]");
+        }
+        summary.push('\n');
+
+        for (i, input) in inputs.iter().enumerate() {
+            let branch = if i + 1 == inputs.len() {
+                "└──"
+            } else {
+                "├──"
+            };
+            summary.push_str(&format!("{} #{}\n", branch, get_id(input)));
+        }
+    }
+
+    summary
+}
+
+// "Node {id}: {op:?} (layout: {shape:?})" for every node at most `max_depth` inputs away
+// from `root`, the root being at depth 0, in the order they would be computed.
+// Tensors are listed with `Tensor` as their op. The inputs of filled caches are not walked.
+pub(crate) fn trace_back<T: NumberLike>(root: &NodeKind<T>, max_depth: usize) -> Vec<String> {
+    let mut depths: HashMap<usize, usize> = HashMap::new();
+    let mut queue = VecDeque::from([(root, 0)]);
+
+    while let Some((node, depth)) = queue.pop_front() {
+        if depths.contains_key(&get_id(node)) {
+            continue;
+        }
+        depths.insert(get_id(node), depth);
+
+        if depth < max_depth {
+            queue.extend(computed_inputs(node).iter().map(|input| (input, depth + 1)));
+        }
+    }
+
+    let (sorted, _, _) = topological_sort(std::slice::from_ref(root));
+
+    (sorted.into_iter())
+        .filter(|node| depths.contains_key(&get_id(node)))
+        .map(|node| {
+            let id = get_id(node);
+            match node {
+                NodeKind::Edge(edge) => {
+                    let shape = edge.get().layout().shape();
+                    format!("Node {}: Tensor (layout: {:?})", id, shape)
+                }
+                NodeKind::Node(node) => {
+                    format!(
+                        "Node {}: {:?} (layout: {:?})",
+                        id,
+                        node.op,
+                        node.layout.shape()
+                    )
+                }
+                NodeKind::Cache(cache) => {
+                    let node = cache.get_node();
+                    format!(
+                        "Node {}: {:?} (layout: {:?})",
+                        id,
+                        node.op,
+                        node.layout.shape()
+                    )
+                }
+            }
+        })
+        .collect()
+}
+
+// Computes the DAG feeding `root` one node at a time, in the order of materialize(), and
+// returns the id of the first one whose result has a NaN, tensors included.
+// Gives up after computing `max_nodes` nodes.
+pub(crate) fn first_nan_node(root: &NodeKind<f64>, max_nodes: usize) -> Option<usize> {
+    let (sorted_dag, mut reference_counter, empty_caches) =
+        topological_sort(std::slice::from_ref(root));
+    let mut computation_cache: HashMap<usize, TensorData<f64>> = HashMap::new();
+    let progress = GraphProgress::new(None, sorted_dag.len());
+
+    // Every result is looked at, so no cast is fused.
+    let fused = HashSet::new();
+
+    for node in sorted_dag.into_iter().take(max_nodes) {
+        let id = get_id(node);
+        compute_sorted(
+            vec![node],
+            &mut computation_cache,
+            &mut reference_counter,
+            &progress,
+            &fused,
+            &empty_caches,
+        );
+
+        if (computation_cache.get(&id)).is_some_and(|data| data.copied_iter().any(f64::is_nan)) {
+            return Some(id);
+        }
+    }
+
+    None
+}
+
+impl<T: Copy + Debug> Debug for TensorGraphNode<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "TensorGraphNode {{ id: {:?}, op: {:?},  inputs: [...] }}",
+            self.id, self.op
+        )
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+// The result of a cache node. It is only ever emptied by the pool of the cache.
+struct CacheSlot<T: Copy> {
+    data: Mutex<Option<TensorData<T>>>,
+}
+
+impl<T: Copy> CacheSlot<T> {
+    fn empty() -> Arc<Self> {
+        Arc::new(Self {
+            data: Mutex::new(None),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<TensorData<T>>> {
+        self.data.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Copy + Send + Sync> Evict for CacheSlot<T> {
+    fn evict(&self) {
+        self.lock().take();
+    }
+}
+
+// The pool of a cache, with the function registering its slot. The function is built
+// where T is known to be 'static, which the pool needs to hold the slot.
+struct CachePoolHandle<T: Copy> {
+    pool: CachePool,
+    downgrade: fn(&Arc<CacheSlot<T>>) -> Weak<dyn Evict>,
+}
+
+impl<T: Copy> Clone for CachePoolHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            downgrade: self.downgrade,
+        }
+    }
+}
+
+pub struct TensorGraphCacheNode<T: Copy> {
+    node: TensorGraphNode<T>,
+    cache: Arc<CacheSlot<T>>,
+    pool: Option<CachePoolHandle<T>>,
+}
+
+impl<T: Copy> TensorGraphCacheNode<T> {
+    pub fn from_node(node: TensorGraphNode<T>) -> Self {
+        Self {
+            node,
+            cache: CacheSlot::empty(),
+            pool: None,
+        }
+    }
+
+    /// A cache whose result counts towards the budget of `pool`, see CachePool.
+    pub fn from_node_in(node: TensorGraphNode<T>, pool: &CachePool) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        Self {
+            node,
+            cache: CacheSlot::empty(),
+            pool: Some(CachePoolHandle {
+                pool: pool.clone(),
+                downgrade: |slot| Arc::downgrade(slot) as Weak<dyn Evict>,
+            }),
+        }
+    }
+
+    pub fn get_node(&self) -> &TensorGraphNode<T> {
+        &self.node
+    }
+
+    pub fn is_cache_filled(&self) -> bool {
+        self.cache.lock().is_some()
+    }
+
+    pub(crate) fn is_pooled(&self) -> bool {
+        self.pool.is_some()
+    }
+
+    // The stored result, counted as a read by the pool.
+    pub(crate) fn cached(&self) -> Option<TensorData<T>> {
+        let data = self.cache.lock().clone()?;
+        self.record_hit();
+
+        Some(data)
+    }
+
+    // The stored result, or the one of `compute` if there is none yet, which is stored.
+    // The slot stays locked while computing, so concurrent reads wait for the result
+    // instead of computing it again. The pool is only called once it is unlocked, as
+    // storing a result may evict older results of the pool, this one included.
+    fn fill_with(&self, compute: impl FnOnce() -> TensorData<T>) -> TensorData<T> {
+        let mut slot = self.cache.lock();
+
+        if let Some(data) = slot.clone() {
+            drop(slot);
+            self.record_hit();
+
+            return data;
+        }
+
+        let data = compute();
+        *slot = Some(data.clone());
+        drop(slot);
+        self.record_fill();
+
+        data
+    }
+
+    fn record_hit(&self) {
+        metrics::record_cache_read(true);
+
+        if let Some(handle) = &self.pool {
+            handle.pool.hit(self.node.id);
+        }
+    }
+
+    fn record_fill(&self) {
+        metrics::record_cache_read(false);
+
+        if let Some(handle) = &self.pool {
+            let bytes = self.node.layout.len() * std::mem::size_of::<T>();
+            handle
+                .pool
+                .insert(self.node.id, bytes, (handle.downgrade)(&self.cache));
+        }
+    }
+
+    // Same cache over another node, in the same pool but with a slot of its own.
+    pub(crate) fn with_node(&self, node: TensorGraphNode<T>) -> Self {
+        Self {
+            node,
+            cache: CacheSlot::empty(),
+            pool: self.pool.clone(),
+        }
+    }
+}
+
+impl<T: NumberLike> TensorGraphCacheNode<T> {
+    pub fn new(op: OpKind<T>, inputs: Box<[NodeKind<T>]>) -> Result<Self, OpError> {
+        TensorGraphNode::new(op, inputs).map(Self::from_node)
+    }
+
+    pub fn with_layout(op: OpKind<T>, inputs: Box<[NodeKind<T>]>, layout: Layout) -> Self {
+        Self::from_node(TensorGraphNode::with_layout(op, inputs, layout))
+    }
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> Promising for TensorGraphCacheNode<T> {
+    type Output = T;
+
+    fn compute(&self) -> TensorData<T> {
+        // TODO: Once the cuda async is implemented, it would be ideal to change this to an async
+        // OnceCell from tokio or some other library
+        self.fill_with(|| self.node.compute())
+    }
+    #[inline]
+    fn layout(&self) -> &Layout {
         &self.get_node().layout
     }
+
+    #[inline]
+    fn device(&self) -> Device {
+        self.node.device
+    }
+
+    #[inline]
+    fn names(&self) -> Option<&AxisNameList> {
+        self.get_node().names.as_ref()
+    }
+
+    #[inline]
+    fn depth(&self) -> usize {
+        self.node.depth
+    }
+
+    fn validate_acyclic(&self) -> Result<(), OpError> {
+        validate_acyclic(self.node.id, &self.node.inputs)
+    }
 }
 
 impl<T: Copy + Debug> Debug for TensorGraphCacheNode<T> {
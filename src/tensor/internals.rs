@@ -1,5 +1,9 @@
-pub(super) fn calculate_dim_stride(shape: &[usize]) -> Box<[i32]> {
-    let mut v: Box<[i32]> = vec![1; shape.len()].into_boxed_slice();
+use std::cmp::Ordering;
+
+use crate::tensor::mem_formats::dims::DimVec;
+
+pub(super) fn calculate_dim_stride(shape: &[usize]) -> DimVec<i32> {
+    let mut v = DimVec::filled(1, shape.len());
 
     for i in (0..shape.len().saturating_sub(1)).rev() {
         v[i] = (shape[i + 1] as i32) * v[i + 1];
@@ -8,8 +12,8 @@ pub(super) fn calculate_dim_stride(shape: &[usize]) -> Box<[i32]> {
     v
 }
 
-pub(super) fn calculate_adjacent_dim_stride(stride: &[i32], slice_shape: &[usize]) -> Box<[i32]> {
-    let mut v: Box<[i32]> = stride.into();
+pub(super) fn calculate_adjacent_dim_stride(stride: &[i32], slice_shape: &[usize]) -> DimVec<i32> {
+    let mut v = DimVec::from(stride);
 
     let mut accum: i32 = 0;
     for i in (0..stride.len() - 1).rev() {
@@ -19,3 +23,130 @@ pub(super) fn calculate_adjacent_dim_stride(stride: &[i32], slice_shape: &[usize
 
     v
 }
+
+// Lowest and highest positions read by a layout of `shape` and `stride` starting at `offset`,
+// None if it has no elements. Every view of a buffer is checked against these, see
+// Layout::as_strided().
+pub(super) fn strided_extent(shape: &[usize], stride: &[i32], offset: i64) -> Option<(i64, i64)> {
+    if shape.contains(&0) {
+        return None;
+    }
+
+    let (mut lowest, mut highest) = (offset, offset);
+
+    for (&len, &stride) in shape.iter().zip(stride) {
+        let reach = (len as i64 - 1) * stride as i64;
+
+        if reach < 0 {
+            lowest += reach;
+        } else {
+            highest += reach;
+        }
+    }
+
+    Some((lowest, highest))
+}
+
+// Whether two indexes of a layout of `shape` and `stride` may read the same position.
+// Conservative: the axes sorted by stride must each step over everything the smaller
+// ones reach, which holds for any layout made by slicing, flipping or permuting.
+pub(super) fn may_alias(shape: &[usize], stride: &[i32]) -> bool {
+    if shape.contains(&0) {
+        return false;
+    }
+
+    let mut axes: Vec<(u64, u64)> = (shape.iter().zip(stride))
+        .filter(|&(&len, _)| len > 1)
+        .map(|(&len, &stride)| (stride.unsigned_abs() as u64, len as u64))
+        .collect();
+
+    axes.sort_unstable();
+
+    let mut reach = 0;
+    for (stride, len) in axes {
+        if stride <= reach {
+            return true;
+        }
+
+        reach += stride * (len - 1);
+    }
+
+    false
+}
+
+// Stride of the single axis the axes of `shape` and `stride` merge into, if they do, which
+// is when every axis steps over exactly the whole of the next one. Axes of length 1 are
+// never stepped along, so their stride does not matter, and without elements any stride
+// will do. Flipped axes merge as well, into an axis of negative stride.
+pub(super) fn merged_stride(shape: &[usize], stride: &[i32]) -> Option<i32> {
+    if shape.contains(&0) {
+        return Some(1);
+    }
+
+    let mut axes = (shape.iter().zip(stride))
+        .filter(|&(&len, _)| len > 1)
+        .rev();
+
+    let Some((&len, &merged)) = axes.next() else {
+        return Some(1);
+    };
+
+    let mut next = len as i64 * merged as i64;
+
+    for (&len, &stride) in axes {
+        if stride as i64 != next {
+            return None;
+        }
+
+        next *= len as i64;
+    }
+
+    Some(merged)
+}
+
+// Total order over a PartialOrd type where the values that are not comparable
+// with themselves (NaNs) are all equal and greater than everything else.
+pub(super) fn total_order<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    match a.partial_cmp(b) {
+        Some(ordering) => ordering,
+        None => {
+            let a_nan = a.partial_cmp(a).is_none();
+            let b_nan = b.partial_cmp(b).is_none();
+
+            a_nan.cmp(&b_nan)
+        }
+    }
+}
+
+pub(super) const TRANSPOSE_BLOCK_SIZE: usize = 32;
+
+// Writes the dense `rows x cols` matrix whose element (r, c) is `src[c * src_row_stride + r]`,
+// which is how a transposed matrix is laid out in memory.
+// Goes through the matrix in square blocks so that both the reads and the writes
+// stay in cache, instead of striding across the whole source for every element.
+pub(super) fn transpose_copy_2d<T: Copy>(
+    src: &[T],
+    dst: &mut [T],
+    rows: usize,
+    cols: usize,
+    src_row_stride: usize,
+) {
+    debug_assert!(dst.len() >= rows * cols);
+    debug_assert!(cols == 0 || src.len() >= (cols - 1) * src_row_stride + rows);
+
+    for row_block in (0..rows).step_by(TRANSPOSE_BLOCK_SIZE) {
+        let row_end = (row_block + TRANSPOSE_BLOCK_SIZE).min(rows);
+
+        for col_block in (0..cols).step_by(TRANSPOSE_BLOCK_SIZE) {
+            let col_end = (col_block + TRANSPOSE_BLOCK_SIZE).min(cols);
+
+            for c in col_block..col_end {
+                let src_row = &src[c * src_row_stride..];
+
+                for r in row_block..row_end {
+                    dst[r * cols + c] = src_row[r];
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,17 @@
 use std::iter::FusedIterator;
-use std::sync::Arc;
 
 use crate::debug_assert_positive;
 use crate::tensor::mem_formats::layout::Layout;
 use crate::tensor::traits::StreamingIterator;
 
 pub struct ContiguousIter<'a, T: Copy> {
-    data: &'a Arc<Vec<T>>,
+    data: &'a [T],
     offset: usize,
     left_over: usize,
 }
 
 impl<'a, T: Copy> ContiguousIter<'a, T> {
-    pub fn new(data: &'a Arc<Vec<T>>, offset: usize, len: usize) -> Self {
+    pub fn new(data: &'a [T], offset: usize, len: usize) -> Self {
         Self {
             data,
             offset,
@@ -90,13 +89,13 @@ impl<'a, T: Copy> FusedIterator for ContiguousIter<'a, T> {}
 ///////////////////////////////////////////////////////////////
 
 pub struct CopiedContiguousIter<'a, T: Copy> {
-    data: &'a Arc<Vec<T>>,
+    data: &'a [T],
     offset: usize,
     left_over: usize,
 }
 
 impl<'a, T: Copy> CopiedContiguousIter<'a, T> {
-    pub fn new(data: &'a Arc<Vec<T>>, offset: usize, len: usize) -> Self {
+    pub fn new(data: &'a [T], offset: usize, len: usize) -> Self {
         Self {
             data,
             offset,
@@ -132,7 +131,7 @@ impl<'a, T: Copy> FusedIterator for CopiedContiguousIter<'a, T> {}
 ///////////////////////////////////////////////////////////////
 
 pub struct SliceIter<'a, T: Copy> {
-    data: &'a Arc<Vec<T>>,
+    data: &'a [T],
     pos: isize,
     counter: Box<[usize]>,
     layout: &'a Layout,
@@ -140,7 +139,7 @@ pub struct SliceIter<'a, T: Copy> {
 }
 
 impl<'a, T: Copy> SliceIter<'a, T> {
-    pub fn new(data: &'a Arc<Vec<T>>, data_len: usize, layout: &'a Layout) -> Self {
+    pub fn new(data: &'a [T], data_len: usize, layout: &'a Layout) -> Self {
         let counter = vec![0; layout.shape().len()].into_boxed_slice();
 
         Self {
@@ -265,7 +264,7 @@ impl<'a, T: Copy> FusedIterator for SliceIter<'a, T> {}
 ///////////////////////////////////////////////////////////////
 
 pub struct CopiedSliceIter<'a, T: Copy> {
-    data: &'a Arc<Vec<T>>,
+    data: &'a [T],
     pos: isize,
     counter: Box<[usize]>,
     layout: &'a Layout,
@@ -273,7 +272,7 @@ pub struct CopiedSliceIter<'a, T: Copy> {
 }
 
 impl<'a, T: Copy> CopiedSliceIter<'a, T> {
-    pub fn new(data: &'a Arc<Vec<T>>, data_len: usize, layout: &'a Layout) -> Self {
+    pub fn new(data: &'a [T], data_len: usize, layout: &'a Layout) -> Self {
         let counter = vec![0; layout.shape().len()].into_boxed_slice();
 
         Self {
@@ -337,7 +336,7 @@ pub enum StepInfo<T: Copy> {
 }
 
 pub struct InformedSliceIter<'a, T: Copy> {
-    buffer: &'a Arc<Vec<T>>,
+    buffer: &'a [T],
     layout: &'a Layout,
     next_state: StepInfo<T>,
     pos: i64,
@@ -345,7 +344,7 @@ pub struct InformedSliceIter<'a, T: Copy> {
 }
 
 impl<'a, T: Copy> InformedSliceIter<'a, T> {
-    pub fn new(data: &'a Arc<Vec<T>>, layout: &'a Layout) -> Self {
+    pub fn new(data: &'a [T], layout: &'a Layout) -> Self {
         let len = layout.shape().len();
 
         Self {
@@ -429,6 +428,14 @@ impl<'a, T: Copy> ExactSizeIterator for InformedSliceIter<'a, T> {}
 
 impl<'a, T: Copy> FusedIterator for InformedSliceIter<'a, T> {}
 
+/////////////////////////////////////////////////////////////
+
+// The elements of TensorData::par_iter(): split over the buffer if it is contiguous,
+// or over a copy gathered in row-major order otherwise.
+#[cfg(feature = "parallel")]
+pub type ParIter<'a, T> =
+    rayon::iter::Either<rayon::iter::Copied<rayon::slice::Iter<'a, T>>, rayon::vec::IntoIter<T>>;
+
 /////////////////////////////////////////////////////////////
 pub struct PackedBuffer<'a, T: Copy> {
     pub packing_buffer: &'a [T],
@@ -494,3 +501,51 @@ where
 }
 
 /////////////////////////////////////////////////////////////
+
+// Folds over the rows of N layouts of the same shape, the rows being their lanes along the
+// last axis, in row-major order. `f` gets the position in the buffer of the first element
+// of the row in each layout. It recurses once per axis instead of keeping a counter, so
+// nothing is allocated.
+pub(crate) fn fold_rows<B, F, const N: usize>(
+    shape: &[usize],
+    strides: [&[i32]; N],
+    offsets: [usize; N],
+    init: B,
+    mut f: F,
+) -> B
+where
+    F: FnMut(B, [usize; N]) -> B,
+{
+    fn walk<B, F, const N: usize>(
+        shape: &[usize],
+        strides: [&[i32]; N],
+        positions: [isize; N],
+        acc: B,
+        f: &mut F,
+    ) -> B
+    where
+        F: FnMut(B, [usize; N]) -> B,
+    {
+        match shape {
+            [] | [_] => f(acc, positions.map(|pos| pos as usize)),
+            [len, rest @ ..] => (0..*len).fold(acc, |acc, i| {
+                let positions =
+                    std::array::from_fn(|k| positions[k] + i as isize * strides[k][0] as isize);
+
+                walk(rest, strides.map(|stride| &stride[1..]), positions, acc, f)
+            }),
+        }
+    }
+
+    if shape.contains(&0) {
+        return init;
+    }
+
+    walk(
+        shape,
+        strides,
+        offsets.map(|offset| offset as isize),
+        init,
+        &mut f,
+    )
+}
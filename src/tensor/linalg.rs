@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+
+use crate::tensor::Tensor;
+use crate::tensor::backend::{Backend, LinAlgBackend};
+use crate::tensor::errors::LinAlgError;
+use crate::tensor::traits::Dimension;
+
+// Dense factorizations of matrices. They are eager methods rather than ops of the graph,
+// as they have several outputs and the pivots are not a tensor. The matrix is copied
+// column-major, the layout of LAPACK, and the factors are written back row-major.
+
+/// The norm of a condition number, see Tensor::cond().
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixNorm {
+    /// Largest sum of the absolute values of a column.
+    One,
+    /// Largest sum of the absolute values of a row.
+    Inf,
+}
+
+/// L, U and the pivots of Tensor::lu().
+pub type LuFactors = (Tensor<f64>, Tensor<f64>, Vec<i32>);
+
+fn check_matrix(t: &Tensor<f64>) -> Result<(usize, usize), LinAlgError> {
+    match *t.shape() {
+        [m, n] if m > 0 && n > 0 => Ok((m, n)),
+        _ => Err(LinAlgError::NotAMatrix(t.shape().into())),
+    }
+}
+
+fn check_square(t: &Tensor<f64>) -> Result<usize, LinAlgError> {
+    match check_matrix(t)? {
+        (m, n) if m == n => Ok(n),
+        (m, n) => Err(LinAlgError::NotSquare(m, n)),
+    }
+}
+
+fn check_info(routine: &'static str, info: i32) -> Result<(), LinAlgError> {
+    match info {
+        0 => Ok(()),
+        _ => Err(LinAlgError::Lapack(routine, info)),
+    }
+}
+
+fn column_major(t: &Tensor<f64>, m: usize, n: usize) -> Vec<f64> {
+    let mut a = vec![0.0; m * n];
+
+    for (idx, &el) in t.iter().enumerate() {
+        a[(idx % n) * m + idx / n] = el;
+    }
+
+    a
+}
+
+// The [rows, cols] matrix whose element at (i, j) is `f(i, j)`.
+fn from_fn(rows: usize, cols: usize, f: impl Fn(usize, usize) -> f64) -> Tensor<f64> {
+    let values = (0..rows * cols).map(|idx| f(idx / cols, idx % cols));
+
+    Tensor::from_iter(values, &[rows, cols])
+}
+
+// The pivots and the info code of getrf, factorizing `a` in place.
+fn getrf(a: &mut [f64], m: usize, n: usize) -> (Vec<i32>, i32) {
+    let mut ipiv = vec![0; m.min(n)];
+    let info = Backend::getrf(m, n, a, &mut ipiv);
+
+    (ipiv, info)
+}
+
+// Largest sum of the absolute values of a column of the [n, n] matrix `a` for the 1-norm,
+// of a row for the infinity norm. NaN if any element is.
+fn matrix_norm(a: &[f64], n: usize, norm: MatrixNorm) -> f64 {
+    let at = |i: usize, j: usize| a[j * n + i].abs();
+    let sum = |outer: usize| -> f64 {
+        (0..n)
+            .map(|inner| match norm {
+                MatrixNorm::One => at(inner, outer),
+                MatrixNorm::Inf => at(outer, inner),
+            })
+            .sum()
+    };
+
+    (0..n).map(sum).fold(
+        0.0,
+        |max, sum| {
+            if sum > max || sum.is_nan() { sum } else { max }
+        },
+    )
+}
+
+impl Tensor<f64> {
+    /// LU factorization with partial pivoting of an [m, n] matrix, as the [m, k] lower
+    /// triangular L with ones on its diagonal, the [k, n] upper triangular U and the
+    /// pivots, k being the smallest of m and n. Swapping the rows `i` and `pivots[i]` of
+    /// the matrix, in order from the first one, gives L U. The pivots count from 0, unlike
+    /// the ones of LAPACK.
+    ///
+    /// Fails with Singular if U has a zero on its diagonal.
+    pub fn lu(&self) -> Result<LuFactors, LinAlgError> {
+        let (m, n) = check_matrix(self)?;
+        let k = m.min(n);
+        let mut a = column_major(self, m, n);
+
+        let (ipiv, info) = getrf(&mut a, m, n);
+        match info {
+            0 => {}
+            info if info > 0 => return Err(LinAlgError::Singular(info)),
+            info => return Err(LinAlgError::Lapack("dgetrf", info)),
+        }
+
+        let l = from_fn(m, k, |i, j| match i.cmp(&j) {
+            Ordering::Greater => a[j * m + i],
+            Ordering::Equal => 1.0,
+            Ordering::Less => 0.0,
+        });
+        let u = from_fn(k, n, |i, j| if i <= j { a[j * m + i] } else { 0.0 });
+        let pivots = ipiv.into_iter().map(|p| p - 1).collect();
+
+        Ok((l, u, pivots))
+    }
+
+    /// Reduced QR factorization of an [m, n] matrix, as the [m, k] Q with orthonormal
+    /// columns and the [k, n] upper triangular R, k being the smallest of m and n.
+    /// The diagonal of R can be negative.
+    pub fn qr(&self) -> Result<(Tensor<f64>, Tensor<f64>), LinAlgError> {
+        let (m, n) = check_matrix(self)?;
+        let k = m.min(n);
+        let mut a = column_major(self, m, n);
+        let mut tau = vec![0.0; k];
+
+        check_info("dgeqrf", Backend::geqrf(m, n, &mut a, &mut tau))?;
+        let r = from_fn(k, n, |i, j| if i <= j { a[j * m + i] } else { 0.0 });
+
+        // The first k columns are the first m * k elements.
+        a.truncate(m * k);
+        check_info("dorgqr", Backend::orgqr(m, k, &mut a, &tau))?;
+        let q = from_fn(m, k, |i, j| a[j * m + i]);
+
+        Ok((q, r))
+    }
+
+    /// Determinant of a square matrix, the product of the diagonal of U from lu(), negated
+    /// for an odd number of row swaps. It is 0.0 for a singular matrix rather than an
+    /// error, and can overflow to an infinity for a large matrix.
+    pub fn det(&self) -> Result<f64, LinAlgError> {
+        let n = check_square(self)?;
+        let mut a = column_major(self, n, n);
+
+        let (ipiv, info) = getrf(&mut a, n, n);
+        match info {
+            0 => {}
+            info if info > 0 => return Ok(0.0),
+            info => return Err(LinAlgError::Lapack("dgetrf", info)),
+        }
+
+        let swaps = (ipiv.iter().enumerate())
+            .filter(|&(i, &p)| p != i as i32 + 1)
+            .count();
+        let det: f64 = (0..n).map(|i| a[i * n + i]).product();
+
+        Ok(if swaps % 2 == 1 { -det } else { det })
+    }
+
+    /// Condition number of a square matrix in `norm`, the norm of the matrix times the one
+    /// of its inverse, from the factorization of lu(). It is infinite for a singular matrix.
+    ///
+    /// LAPACK (dgecon) estimates the norm of the inverse from below, usually exactly and
+    /// rarely off by more than a factor of 3. The `pure` backend inverts the matrix instead.
+    pub fn cond(&self, norm: MatrixNorm) -> Result<f64, LinAlgError> {
+        let n = check_square(self)?;
+        let mut a = column_major(self, n, n);
+
+        let anorm = matrix_norm(&a, n, norm);
+
+        let (_, info) = getrf(&mut a, n, n);
+        match info {
+            0 => {}
+            info if info > 0 => return Ok(f64::INFINITY),
+            info => return Err(LinAlgError::Lapack("dgetrf", info)),
+        }
+
+        let mut rcond = 0.0;
+        let one = norm == MatrixNorm::One;
+        check_info("dgecon", Backend::gecon(one, n, &a, anorm, &mut rcond))?;
+
+        Ok(1.0 / rcond)
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+
+/// Ranks up to this are stored inline, without allocating.
+pub(crate) const INLINE_DIMS: usize = 6;
+
+/// The shape or strides of a layout: inline up to INLINE_DIMS axes, boxed above that.
+/// Cloning a layout of a common rank is then a copy instead of three allocations.
+#[derive(Clone)]
+pub(crate) enum DimVec<T> {
+    Inline { len: u32, dims: [T; INLINE_DIMS] },
+    Spilled(Box<[T]>),
+}
+
+impl<T: Copy + Default> DimVec<T> {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self::Inline {
+            len: 0,
+            dims: [T::default(); INLINE_DIMS],
+        }
+    }
+
+    /// `len` axes of `value`.
+    #[inline]
+    pub(crate) fn filled(value: T, len: usize) -> Self {
+        if len > INLINE_DIMS {
+            return Self::Spilled(vec![value; len].into_boxed_slice());
+        }
+
+        let mut dims = [T::default(); INLINE_DIMS];
+        dims[..len].fill(value);
+
+        Self::Inline {
+            len: len as u32,
+            dims,
+        }
+    }
+
+    /// Appends an axis. Past INLINE_DIMS axes every push reallocates, which is fine for
+    /// the few layouts of such a rank.
+    #[inline]
+    pub(crate) fn push(&mut self, value: T) {
+        match self {
+            Self::Inline { len, dims } if (*len as usize) < INLINE_DIMS => {
+                dims[*len as usize] = value;
+                *len += 1;
+            }
+            _ => {
+                let mut spilled = self.to_vec();
+                spilled.push(value);
+                *self = Self::Spilled(spilled.into_boxed_slice());
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn extend_from_slice(&mut self, values: &[T]) {
+        for &value in values {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Copy + Default> Default for DimVec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for DimVec<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        match self {
+            Self::Inline { len, dims } => &dims[..*len as usize],
+            Self::Spilled(spilled) => spilled,
+        }
+    }
+}
+
+impl<T> DerefMut for DimVec<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            Self::Inline { len, dims } => &mut dims[..*len as usize],
+            Self::Spilled(spilled) => spilled,
+        }
+    }
+}
+
+impl<T: Copy + Default> From<&[T]> for DimVec<T> {
+    #[inline]
+    fn from(values: &[T]) -> Self {
+        if values.len() > INLINE_DIMS {
+            return Self::Spilled(values.into());
+        }
+
+        let mut dims = [T::default(); INLINE_DIMS];
+        dims[..values.len()].copy_from_slice(values);
+
+        Self::Inline {
+            len: values.len() as u32,
+            dims,
+        }
+    }
+}
+
+impl<T: Copy + Default, const N: usize> From<[T; N]> for DimVec<T> {
+    #[inline]
+    fn from(values: [T; N]) -> Self {
+        Self::from(&values[..])
+    }
+}
+
+// Keeps an already boxed rank boxed, rather than copying it inline.
+impl<T: Copy + Default> From<Box<[T]>> for DimVec<T> {
+    #[inline]
+    fn from(values: Box<[T]>) -> Self {
+        if values.len() > INLINE_DIMS {
+            return Self::Spilled(values);
+        }
+
+        Self::from(&values[..])
+    }
+}
+
+impl<T: Copy + Default> FromIterator<T> for DimVec<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut dims = Self::new();
+        for value in iter {
+            dims.push(value);
+        }
+
+        dims
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DimVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for DimVec<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for DimVec<T> {}
+
+// Like the slice, so an inline and a spilled DimVec of the same axes hash the same.
+impl<T: Hash> Hash for DimVec<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
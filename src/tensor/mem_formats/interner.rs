@@ -0,0 +1,142 @@
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use rustc_hash::FxBuildHasher;
+
+use crate::tensor::mem_formats::layout::{Layout, LayoutInner};
+
+// Layouts falling in different shards are interned by different threads at the same time.
+const SHARDS: usize = 16;
+
+type Shard = HashSet<Arc<LayoutInner>, FxBuildHasher>;
+
+static INTERNING: AtomicBool = AtomicBool::new(false);
+static INTERNER: LayoutInterner = LayoutInterner::new();
+
+thread_local! {
+    // Whether the innermost InterningScope of this thread interns, if there is one.
+    static SCOPED: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Makes every graph node built from now on share its layout with the nodes of an equal
+/// one, through the global LayoutInterner. Off by default.
+///
+/// Layouts of up to INLINE_DIMS axes are already copied without allocating, so interning
+/// them only saves the copies and makes comparing them cheaper. Above that, every node
+/// otherwise allocates its own shape and strides. Results are the same either way.
+pub fn set_layout_interning(enabled: bool) {
+    INTERNING.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether the nodes built by the current thread intern their layouts: the setting of the
+/// innermost InterningScope, or the global one.
+pub fn layout_interning() -> bool {
+    SCOPED
+        .get()
+        .unwrap_or_else(|| INTERNING.load(Ordering::Relaxed))
+}
+
+/// The set of interned layouts, one shared copy of every distinct layout. It is split in
+/// shards by the hash of the layouts, each behind its own lock, so threads building graphs
+/// at the same time seldom wait for each other.
+///
+/// The interner keeps every layout it interned until it is cleared. Interned layouts stay
+/// valid after that, they are only no longer shared with the ones interned after.
+pub struct LayoutInterner {
+    shards: [Mutex<Shard>; SHARDS],
+}
+
+impl LayoutInterner {
+    const fn new() -> Self {
+        Self {
+            shards: [const { Mutex::new(HashSet::with_hasher(FxBuildHasher)) }; SHARDS],
+        }
+    }
+
+    /// The interner the graph nodes use.
+    pub fn global() -> &'static LayoutInterner {
+        &INTERNER
+    }
+
+    /// The interned layout equal to `layout`, interning a copy of it if there is none.
+    pub fn intern(&self, layout: &Layout) -> Layout {
+        let inner: &LayoutInner = layout;
+        let mut shard = self.shard(inner);
+
+        if let Some(interned) = shard.get(inner) {
+            return Layout::from_interned(interned.clone());
+        }
+
+        let interned = Arc::new(inner.clone());
+        shard.insert(interned.clone());
+
+        Layout::from_interned(interned)
+    }
+
+    /// Number of distinct layouts interned since the last clear().
+    pub fn len(&self) -> usize {
+        (0..SHARDS).map(|idx| self.lock(idx).len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets every interned layout, freeing the ones no layout uses anymore.
+    pub fn clear(&self) {
+        for idx in 0..SHARDS {
+            self.lock(idx).clear();
+        }
+    }
+
+    fn shard(&self, inner: &LayoutInner) -> MutexGuard<'_, Shard> {
+        self.lock(FxBuildHasher.hash_one(inner) as usize % SHARDS)
+    }
+
+    // A shard is valid whatever panicked while it was locked, it only ever gains or loses
+    // whole layouts.
+    fn lock(&self, idx: usize) -> MutexGuard<'_, Shard> {
+        self.shards[idx]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Interns the layouts of the nodes built by the current thread until it is dropped,
+/// whatever set_layout_interning() says. Dropping the outermost scope of the thread clears
+/// the global interner, so the layouts of one graph after another do not pile up in a
+/// long-running process.
+pub struct InterningScope {
+    previous: Option<bool>,
+}
+
+impl InterningScope {
+    pub fn enter() -> Self {
+        Self {
+            previous: SCOPED.replace(Some(true)),
+        }
+    }
+}
+
+impl Drop for InterningScope {
+    fn drop(&mut self) {
+        SCOPED.set(self.previous);
+
+        if self.previous.is_none() {
+            INTERNER.clear();
+        }
+    }
+}
+
+/// `layout`, interned if the current thread interns layouts.
+#[inline]
+pub(crate) fn intern_if_enabled(layout: Layout) -> Layout {
+    if layout_interning() {
+        INTERNER.intern(&layout)
+    } else {
+        layout
+    }
+}
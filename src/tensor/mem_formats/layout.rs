@@ -1,21 +1,118 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
 use crate::tensor::{
     errors::OpError,
-    internals::{calculate_adjacent_dim_stride, calculate_dim_stride},
-    mem_formats::slice::{SliceInfo, SliceRange},
+    internals::{
+        calculate_adjacent_dim_stride, calculate_dim_stride, may_alias, merged_stride,
+        strided_extent,
+    },
+    mem_formats::dims::DimVec,
+    mem_formats::slice::{SliceInfo, SliceKind, StepSliceRange},
 };
 
 use crate::cfg_debug_only;
 
-#[derive(Clone, Debug)]
-pub struct Layout {
-    pub(crate) shape: Box<[usize]>,
-    pub(crate) stride: Box<[i32]>,
-    pub(crate) adj_stride: Box<[i32]>,
+/// The fields of a layout, shared by every layout interned from equal ones.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LayoutInner {
+    pub(crate) shape: DimVec<usize>,
+    pub(crate) stride: DimVec<i32>,
+    pub(crate) adj_stride: DimVec<i32>,
     pub(crate) offset: usize,
     pub(crate) len: usize,
 }
 
+/// Shape, strides and offset of the elements of a tensor in its buffer.
+///
+/// A layout holds its fields inline, or shares them with the equal layouts interned by
+/// the LayoutInterner, see set_layout_interning(). Both behave the same, and two
+/// interned layouts compare equal by their pointers.
+#[derive(Clone)]
+pub struct Layout(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Inline(LayoutInner),
+    Interned(Arc<LayoutInner>),
+}
+
+impl Deref for Layout {
+    type Target = LayoutInner;
+
+    #[inline]
+    fn deref(&self) -> &LayoutInner {
+        match &self.0 {
+            Repr::Inline(inner) => inner,
+            Repr::Interned(inner) => inner,
+        }
+    }
+}
+
+// Changing an interned layout copies its fields out, the shared ones never change.
+impl DerefMut for Layout {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut LayoutInner {
+        if let Repr::Interned(inner) = &self.0 {
+            self.0 = Repr::Inline(LayoutInner::clone(inner));
+        }
+
+        match &mut self.0 {
+            Repr::Inline(inner) => inner,
+            Repr::Interned(_) => unreachable!(),
+        }
+    }
+}
+
+impl From<LayoutInner> for Layout {
+    #[inline]
+    fn from(inner: LayoutInner) -> Self {
+        Self(Repr::Inline(inner))
+    }
+}
+
+impl PartialEq for Layout {
+    fn eq(&self, other: &Self) -> bool {
+        Self::ptr_eq(self, other) || **self == **other
+    }
+}
+
+impl Eq for Layout {}
+
+impl std::fmt::Debug for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Layout")
+            .field("shape", &self.shape)
+            .field("stride", &self.stride)
+            .field("adj_stride", &self.adj_stride)
+            .field("offset", &self.offset)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
 impl Layout {
+    #[inline]
+    pub(crate) fn from_interned(inner: Arc<LayoutInner>) -> Self {
+        Self(Repr::Interned(inner))
+    }
+
+    /// Whether the fields of this layout are shared through the LayoutInterner.
+    #[inline]
+    pub fn is_interned(&self) -> bool {
+        matches!(self.0, Repr::Interned(_))
+    }
+
+    /// Whether both layouts are interned and share their fields. Equal layouts interned
+    /// by the same interner since its last clear() always do.
+    #[inline]
+    pub fn ptr_eq(lhs: &Layout, rhs: &Layout) -> bool {
+        match (&lhs.0, &rhs.0) {
+            (Repr::Interned(lhs), Repr::Interned(rhs)) => Arc::ptr_eq(lhs, rhs),
+            _ => false,
+        }
+    }
+
     pub fn new(
         shape: Box<[usize]>,
         stride: Box<[i32]>,
@@ -23,37 +120,90 @@ impl Layout {
         offset: usize,
         len: usize,
     ) -> Self {
-        Self {
-            shape,
-            stride,
-            adj_stride,
+        LayoutInner {
+            shape: shape.into(),
+            stride: stride.into(),
+            adj_stride: adj_stride.into(),
             offset,
             len,
         }
+        .into()
     }
 
     pub fn from_shape(shape: &[usize], offset: usize) -> Self {
         let len: usize = shape.iter().product();
 
-        Self {
+        LayoutInner {
             shape: shape.into(),
             stride: calculate_dim_stride(shape),
-            adj_stride: vec![1; shape.len()].into_boxed_slice(),
+            adj_stride: DimVec::filled(1, shape.len()),
             offset,
             len,
         }
+        .into()
     }
 
     pub fn from_slice(shape: &[usize], stride: &[i32], offset: usize) -> Self {
+        Self::from_dims(shape.into(), stride.into(), offset)
+    }
+
+    fn from_dims(shape: DimVec<usize>, stride: DimVec<i32>, offset: usize) -> Self {
         let len: usize = shape.iter().product();
+        let adj_stride = calculate_adjacent_dim_stride(&stride, &shape);
 
-        Self {
-            shape: shape.into(),
-            stride: stride.into(),
-            adj_stride: calculate_adjacent_dim_stride(stride, shape),
+        LayoutInner {
+            shape,
+            stride,
+            adj_stride,
             offset,
             len,
         }
+        .into()
+    }
+
+    /// Layout of `shape` reading the element at `offset + Σ index[i] * stride[i]` of a buffer
+    /// of `buffer_len` elements, like numpy's as_strided. Fails with StridedOutOfBounds if any
+    /// of those positions is outside of the buffer, so the layout can never read past it.
+    pub fn as_strided(
+        shape: &[usize],
+        stride: &[i32],
+        offset: i64,
+        buffer_len: usize,
+    ) -> Result<Self, OpError> {
+        if shape.len() != stride.len() {
+            return Err(OpError::NotEnoughAxes(shape.len(), stride.len()));
+        }
+
+        let offset = match strided_extent(shape, stride, offset) {
+            Some((lowest, highest)) if lowest < 0 || highest >= buffer_len as i64 => {
+                return Err(OpError::StridedOutOfBounds(lowest, highest, buffer_len));
+            }
+            Some(_) => offset as usize,
+            // Nothing is read, any offset within the buffer will do.
+            None => offset.clamp(0, buffer_len as i64) as usize,
+        };
+
+        if shape.is_empty() {
+            return Ok(Self::from_shape(shape, offset));
+        }
+
+        Ok(Self::from_slice(shape, stride, offset))
+    }
+
+    // Number of elements a buffer needs to hold every position this layout reads.
+    fn reach(&self) -> usize {
+        match strided_extent(&self.shape, &self.stride, self.offset as i64) {
+            Some((_, highest)) => highest as usize + 1,
+            None => self.offset,
+        }
+    }
+
+    /// Whether two indexes of this layout may read the same element, like the rows of
+    /// a broadcast made with as_strided(). Layouts made by slicing, flipping, permuting or
+    /// reshaping never do.
+    #[inline]
+    pub fn may_alias(&self) -> bool {
+        may_alias(&self.shape, &self.stride)
     }
 
     pub fn view(&self, shape: &[usize]) -> Result<Self, OpError> {
@@ -71,9 +221,81 @@ impl Layout {
         Ok(Layout::from_shape(shape, self.offset))
     }
 
-    pub fn slice(&self, range: &[SliceRange]) -> Result<Self, OpError> {
-        let info = SliceInfo::from_range(self, range);
+    /// Splits the last axis into groups of `k` elements, as a new last axis of length `k`:
+    /// `[..., n]` becomes `[..., n / k, k]`. Fails with IndivisibleAxis if `k` does not
+    /// divide `n`, and with NonContiguousView if the last axis does not have a stride of 1,
+    /// as the elements of a group must be next to each other in the buffer.
+    pub fn group_last_axis(&self, k: usize) -> Result<Self, OpError> {
+        let Some(last) = self.shape.len().checked_sub(1) else {
+            return Err(OpError::NotEnoughAxes(1, 0));
+        };
+
+        let len = self.shape[last];
+
+        if k == 0 || !len.is_multiple_of(k) {
+            return Err(OpError::IndivisibleAxis(len, k));
+        }
+
+        if len > 1 && self.stride[last] != 1 {
+            return Err(OpError::NonContiguousView);
+        }
+
+        let mut shape = DimVec::from(&self.shape[..last]);
+        shape.extend_from_slice(&[len / k, k]);
+        let mut stride = DimVec::from(&self.stride[..last]);
+        stride.extend_from_slice(&[k as i32, 1]);
+
+        Ok(Self::from_dims(shape, stride, self.offset))
+    }
+
+    /// Merges the last `n` axes into one, the inverse of group_last_axis(). Fails with
+    /// NonContiguousView unless each of them steps over exactly the whole of the next one,
+    /// which the axes of a slice or a flip may still do, see merged_stride().
+    pub fn flatten_last_axes(&self, n: usize) -> Result<Self, OpError> {
+        let rank = self.shape.len();
+
+        if n == 0 || n > rank {
+            return Err(OpError::NotEnoughAxes(n.max(1), rank));
+        }
+
+        let first = rank - n;
+        let merged = merged_stride(&self.shape[first..], &self.stride[first..])
+            .ok_or(OpError::NonContiguousView)?;
+
+        let len = self.shape[first..].iter().product();
+        let mut shape = DimVec::from(&self.shape[..first]);
+        shape.push(len);
+        let mut stride = DimVec::from(&self.stride[..first]);
+        stride.push(merged);
 
+        Ok(Self::from_dims(shape, stride, self.offset))
+    }
+
+    /// The whole layout as a single axis of stride 1. Fails with NonContiguousView
+    /// unless its elements are all next to each other in the buffer, in order.
+    pub fn flatten(&self) -> Result<Self, OpError> {
+        if self.shape.is_empty() {
+            return Ok(Self::from_shape(&[1], self.offset));
+        }
+
+        let flat = self.flatten_last_axes(self.shape.len())?;
+
+        if flat.len > 1 && flat.stride[0] != 1 {
+            return Err(OpError::NonContiguousView);
+        }
+
+        Ok(Self::from_shape(&flat.shape, flat.offset))
+    }
+
+    pub fn slice(&self, range: &[SliceKind]) -> Result<Self, OpError> {
+        Self::from_slice_info(SliceInfo::from_range(self, range))
+    }
+
+    pub fn step_slice(&self, range: &[StepSliceRange]) -> Result<Self, OpError> {
+        Self::from_slice_info(SliceInfo::from_step_range(self, range))
+    }
+
+    fn from_slice_info(info: Result<SliceInfo, OpError>) -> Result<Self, OpError> {
         cfg_debug_only!(if let Err(err) = info {
             return Err(err);
         });
@@ -82,13 +304,14 @@ impl Layout {
 
         let len: usize = unwrapped_info.shape.iter().product();
 
-        Ok(Self {
+        Ok(LayoutInner {
             shape: unwrapped_info.shape,
-            stride: self.stride.clone(),
+            stride: unwrapped_info.stride,
             adj_stride: unwrapped_info.adj_stride,
             offset: unwrapped_info.offset,
             len,
-        })
+        }
+        .into())
     }
 
     pub fn transpose(&self) -> Self {
@@ -107,15 +330,16 @@ impl Layout {
             shape[i] = temp;
         }
 
-        let adj_stride: Box<[i32]> = calculate_adjacent_dim_stride(&stride, &shape);
+        let adj_stride = calculate_adjacent_dim_stride(&stride, &shape);
 
-        Self {
+        LayoutInner {
             shape,
             stride,
             adj_stride,
             offset: self.offset,
             len: self.len,
         }
+        .into()
     }
 
     pub fn transpose_axes(&self, axes: &[usize]) -> Result<Self, OpError> {
@@ -123,8 +347,8 @@ impl Layout {
             return Err(OpError::NotEnoughAxes(self.stride.len(), axes.len()));
         });
 
-        let mut stride: Vec<i32> = Vec::with_capacity(self.stride.len());
-        let mut shape: Vec<usize> = Vec::with_capacity(self.stride.len());
+        let mut stride: DimVec<i32> = DimVec::new();
+        let mut shape: DimVec<usize> = DimVec::new();
 
         for &axis in axes.iter() {
             cfg_debug_only!(if axis >= self.stride.len() {
@@ -137,13 +361,71 @@ impl Layout {
 
         let adj_stride = calculate_adjacent_dim_stride(&stride, &shape);
 
-        Ok(Self {
-            shape: shape.into_boxed_slice(),
-            stride: stride.into_boxed_slice(),
+        Ok(LayoutInner {
+            shape,
+            stride,
             adj_stride,
             offset: self.offset,
             len: self.len,
-        })
+        }
+        .into())
+    }
+
+    // Reverses the element order along each of the axes by negating their stride
+    // and moving the offset to the last element of that axis.
+    pub fn flip(&self, axes: &[usize]) -> Result<Self, OpError> {
+        let mut stride = self.stride.clone();
+        let mut offset = self.offset as i64;
+
+        for (i, &axis) in axes.iter().enumerate() {
+            if axis >= self.shape.len() {
+                return Err(OpError::OutOfBoundAxes);
+            }
+
+            if axes[..i].contains(&axis) {
+                return Err(OpError::RepeatedAxis(axis));
+            }
+
+            offset += (self.shape[axis] as i64 - 1).max(0) * stride[axis] as i64;
+            stride[axis] = -stride[axis];
+        }
+
+        Self::as_strided(&self.shape, &stride, offset, self.reach())
+    }
+
+    // The `k`-th diagonal of a matrix as a 1-D layout, stepping through both axes at once.
+    // Positive `k` are above the main diagonal, negative ones below it.
+    // A diagonal outside of the matrix is empty.
+    pub fn diagonal(&self, k: i32) -> Result<Self, OpError> {
+        if self.shape.len() != 2 {
+            return Err(OpError::NotEnoughAxes(2, self.shape.len()));
+        }
+
+        let (rows, cols) = (self.shape[0], self.shape[1]);
+        let shift = k.unsigned_abs() as usize;
+
+        let (len, offset) = if k >= 0 {
+            let len = rows.min(cols.saturating_sub(shift));
+            (
+                len,
+                self.offset as i64 + shift as i64 * self.stride[1] as i64,
+            )
+        } else {
+            let len = rows.saturating_sub(shift).min(cols);
+            (
+                len,
+                self.offset as i64 + shift as i64 * self.stride[0] as i64,
+            )
+        };
+
+        let offset = if len == 0 { self.offset as i64 } else { offset };
+
+        Self::as_strided(
+            &[len],
+            &[self.stride[0] + self.stride[1]],
+            offset,
+            self.reach(),
+        )
     }
 
     // pub fn broadcast_to_shape(&self, shape: &[usize]) -> Result<Self, OpError> {
@@ -221,12 +503,33 @@ impl Layout {
             return false;
         }
 
-        self.adj_stride[axis] == 1
+        // Every axis from `axis` on must step to the next element, a step slice of the last
+        // axis can still wrap around onto the next row with a step of 1.
+        self.adj_stride[axis..].iter().all(|&adj| adj == 1)
+    }
+
+    /// The distance in the buffer between every element and the next in row-major order,
+    /// if it is the same for all of them, like for a column of a row-major matrix.
+    /// Some(1) for a contiguous layout, None for one with less than two elements.
+    pub fn single_stride(&self) -> Option<i32> {
+        let mut steps = (self.shape.iter().zip(self.adj_stride.iter()))
+            .filter(|&(&len, _)| len > 1)
+            .map(|(_, &step)| step);
+        let first = steps.next()?;
+
+        steps.all(|step| step == first).then_some(first)
+    }
+
+    /// Whether every element is the same element of the buffer, like for the broadcast
+    /// of a single element made with as_strided().
+    #[inline]
+    pub fn is_constant_broadcast(&self) -> bool {
+        self.single_stride() == Some(0)
     }
 
     #[inline]
     pub fn is_transposed(&self) -> bool {
-        for &adj_stride in &self.adj_stride {
+        for &adj_stride in self.adj_stride.iter() {
             if adj_stride < 0 {
                 return true;
             }
@@ -268,6 +571,11 @@ impl Layout {
     pub fn len(&self) -> usize {
         self.len
     }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 }
 
 impl std::fmt::Display for Layout {
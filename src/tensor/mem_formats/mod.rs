@@ -1,2 +1,4 @@
+pub(crate) mod dims;
+pub mod interner;
 pub mod layout;
 pub mod slice;
@@ -1,3 +1,4 @@
+use std::num::NonZeroI32;
 use std::ops::{Range, RangeFrom, RangeFull, RangeTo};
 
 use crate::tensor::mem_formats::layout::Layout;
@@ -5,7 +6,11 @@ use crate::tensor::mem_formats::layout::Layout;
 use crate::cfg_debug_only;
 use crate::tensor::errors::OpError;
 use crate::tensor::internals::calculate_adjacent_dim_stride;
+use crate::tensor::mem_formats::dims::DimVec;
 
+// A bound of a range. ReverseIndex holds how far before the end a negative bound is,
+// so -1 is ReverseIndex(1).
+#[derive(Clone, Copy, Debug)]
 enum SliceBounds {
     Beginning,
     Index(usize),
@@ -13,6 +18,19 @@ enum SliceBounds {
     End,
 }
 
+impl SliceBounds {
+    // unsigned_abs() rather than a negation, which overflows for i32::MIN.
+    #[inline]
+    fn from_i32(bound: i32) -> Self {
+        if bound >= 0 {
+            Self::Index(bound as usize)
+        } else {
+            Self::ReverseIndex(bound.unsigned_abs() as usize)
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct SliceRange {
     start: SliceBounds,
     end: SliceBounds,
@@ -21,16 +39,9 @@ pub struct SliceRange {
 impl From<RangeFrom<i32>> for SliceRange {
     #[inline]
     fn from(value: RangeFrom<i32>) -> Self {
-        if value.start >= 0 {
-            Self {
-                start: SliceBounds::Index(value.start as usize),
-                end: SliceBounds::End,
-            }
-        } else {
-            Self {
-                start: SliceBounds::ReverseIndex((-value.start) as usize),
-                end: SliceBounds::End,
-            }
+        Self {
+            start: SliceBounds::from_i32(value.start),
+            end: SliceBounds::End,
         }
     }
 }
@@ -38,16 +49,9 @@ impl From<RangeFrom<i32>> for SliceRange {
 impl From<RangeTo<i32>> for SliceRange {
     #[inline]
     fn from(value: RangeTo<i32>) -> Self {
-        if value.end >= 0 {
-            Self {
-                start: SliceBounds::Beginning,
-                end: SliceBounds::Index(value.end as usize),
-            }
-        } else {
-            Self {
-                start: SliceBounds::Beginning,
-                end: SliceBounds::ReverseIndex((-value.end) as usize),
-            }
+        Self {
+            start: SliceBounds::Beginning,
+            end: SliceBounds::from_i32(value.end),
         }
     }
 }
@@ -65,19 +69,153 @@ impl From<RangeFull> for SliceRange {
 impl From<Range<i32>> for SliceRange {
     #[inline]
     fn from(value: Range<i32>) -> Self {
-        let start = if value.start >= 0 {
-            SliceBounds::Index(value.start as usize)
-        } else {
-            SliceBounds::ReverseIndex((-value.start) as usize)
-        };
+        Self {
+            start: SliceBounds::from_i32(value.start),
+            end: SliceBounds::from_i32(value.end),
+        }
+    }
+}
 
-        let end = if value.end >= 0 {
-            SliceBounds::Index(value.end as usize)
-        } else {
-            SliceBounds::ReverseIndex((-value.end) as usize)
-        };
+/// A range with a step, for the bounded ranges written as `start..end;step` in [`ss!`](crate::ss).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepRange<Idx> {
+    pub start: Idx,
+    pub end: Idx,
+    pub step: Idx,
+}
+
+/// A [`SliceRange`] that takes every `step` element of the range.
+///
+/// The range always selects the same positions of the axis, whatever the sign of
+/// the step: a negative step walks them from the last one backwards. So `9..0;-1`
+/// and `0..9;-1` both reverse an axis of 9 elements, and `..;-2` takes every other
+/// element starting from the last one.
+#[derive(Debug)]
+pub struct StepSliceRange {
+    start: SliceBounds,
+    end: SliceBounds,
+    step: NonZeroI32,
+}
+
+impl StepSliceRange {
+    #[inline]
+    pub fn new(range: impl Into<SliceRange>, step: NonZeroI32) -> Self {
+        let range = range.into();
 
-        Self { start, end }
+        Self {
+            start: range.start,
+            end: range.end,
+            step,
+        }
+    }
+}
+
+impl From<SliceRange> for StepSliceRange {
+    #[inline]
+    fn from(value: SliceRange) -> Self {
+        Self::new(value, NonZeroI32::new(1).unwrap())
+    }
+}
+
+impl From<StepRange<i32>> for StepSliceRange {
+    /// # Panics
+    ///
+    /// If the step is zero.
+    #[inline]
+    fn from(value: StepRange<i32>) -> Self {
+        let step = NonZeroI32::new(value.step).expect("the step of a slice can't be zero");
+
+        Self::new(value.start..value.end, step)
+    }
+}
+
+/// Inserts an axis of length 1 when written in [`s!`](crate::s), like `numpy.newaxis`.
+/// It does not take an axis of the tensor, so a slice may have more entries than axes
+/// as long as the extra ones are new axes.
+///
+/// The new axis is given the stride a contiguous tensor would have there, which keeps a
+/// contiguous view contiguous. Until elementwise ops broadcast, tile() the result to
+/// write `a[:, None] * b[None, :]`:
+///
+/// ```
+/// use simple_tensor::s;
+/// use simple_tensor::tensor::{Dimension, Tensor};
+///
+/// let a = Tensor::from_vec(vec![1.0, 2.0], &[2]);
+/// let b = Tensor::from_vec(vec![1.0, 10.0, 100.0], &[3]);
+///
+/// let column = a.slice(s![.., NewAxis]).unwrap().tile(&[1, 3]);
+/// let row = b.slice(s![NewAxis, ..]).unwrap().tile(&[2, 1]);
+/// let outer = (&column * &row).materialize();
+///
+/// assert_eq!(outer.shape(), [2, 3]);
+/// assert!(outer.iter().copied().eq([1.0, 10.0, 100.0, 2.0, 20.0, 200.0]));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NewAxis;
+
+/// One entry of [`s!`](crate::s). Every entry but NewAxis takes the next axis of the tensor,
+/// and the axes left after the last one are kept whole.
+#[derive(Debug)]
+pub enum SliceKind {
+    /// Keeps the axis, restricted to the range.
+    Range(StepSliceRange),
+    /// Keeps the element at the index and removes the axis. Negative indexes count from
+    /// the end, so -1 is the last element.
+    Index(i32),
+    /// Adds an axis of length 1, without taking one of the tensor.
+    NewAxis,
+}
+
+macro_rules! slice_kind_from_range {
+    ($($range: ty),*) => {
+        $(
+            impl From<$range> for SliceKind {
+                #[inline]
+                fn from(value: $range) -> Self {
+                    Self::Range(StepSliceRange::from(SliceRange::from(value)))
+                }
+            }
+        )*
+    };
+}
+
+slice_kind_from_range!(
+    Range<i32>,
+    RangeFrom<i32>,
+    RangeTo<i32>,
+    RangeFull,
+    SliceRange
+);
+
+impl From<StepSliceRange> for SliceKind {
+    #[inline]
+    fn from(value: StepSliceRange) -> Self {
+        Self::Range(value)
+    }
+}
+
+impl From<StepRange<i32>> for SliceKind {
+    /// # Panics
+    ///
+    /// If the step is zero.
+    #[inline]
+    fn from(value: StepRange<i32>) -> Self {
+        Self::Range(StepSliceRange::from(value))
+    }
+}
+
+impl From<i32> for SliceKind {
+    #[inline]
+    fn from(value: i32) -> Self {
+        Self::Index(value)
+    }
+}
+
+impl From<NewAxis> for SliceKind {
+    #[inline]
+    fn from(_: NewAxis) -> Self {
+        Self::NewAxis
     }
 }
 
@@ -86,65 +224,206 @@ impl From<Range<i32>> for SliceRange {
 #[derive(Debug)]
 pub struct SliceInfo {
     pub(crate) offset: usize,
-    pub(crate) shape: Box<[usize]>,
-    pub(crate) adj_stride: Box<[i32]>,
+    pub(crate) shape: DimVec<usize>,
+    pub(crate) stride: DimVec<i32>,
+    pub(crate) adj_stride: DimVec<i32>,
 }
 
-impl SliceInfo {
-    pub(crate) fn from_range(layout: &Layout, range: &[SliceRange]) -> Result<Self, OpError> {
-        debug_assert!(layout.shape().len() >= range.len());
+// Position of `bound` in an axis of `dim_size` elements. Bounds outside of the axis are
+// clamped to it, so the layout stays within the buffer even with the checks compiled out.
+#[inline]
+fn resolve_bound(bound: SliceBounds, dim_size: usize) -> usize {
+    match bound {
+        SliceBounds::Beginning => 0,
+        SliceBounds::Index(i) => i.min(dim_size),
+        SliceBounds::ReverseIndex(i) => dim_size.saturating_sub(i),
+        SliceBounds::End => dim_size,
+    }
+}
 
-        let mut offset: i64 = layout.offset() as i64;
-        let mut new_shape: Vec<usize> = layout.shape().into();
+// A bound must be within the axis, from -dim_size to dim_size: past the end fails with
+// InvalidSliceShape and before the start with NegativeBoundOutOfRange, rather than being
+// clamped like Python does.
+#[inline]
+fn check_bound(axis: usize, bound: SliceBounds, dim_size: usize) -> Result<(), OpError> {
+    match bound {
+        SliceBounds::Index(i) if i > dim_size => Err(OpError::InvalidSliceShape(dim_size, i)),
+        SliceBounds::ReverseIndex(i) if i > dim_size => Err(OpError::NegativeBoundOutOfRange(
+            axis,
+            -(i as i64),
+            dim_size,
+        )),
+        _ => Ok(()),
+    }
+}
+
+// A point index must be an element of the axis, from -dim_size to dim_size - 1.
+#[inline]
+fn check_index(axis: usize, index: i32, dim_size: usize) -> Result<(), OpError> {
+    match SliceBounds::from_i32(index) {
+        SliceBounds::Index(i) if i >= dim_size => Err(OpError::InvalidSliceShape(dim_size, i + 1)),
+        SliceBounds::ReverseIndex(i) if i > dim_size => Err(OpError::NegativeBoundOutOfRange(
+            axis,
+            index as i64,
+            dim_size,
+        )),
+        _ => Ok(()),
+    }
+}
 
-        for (dim, r) in range.iter().enumerate() {
-            let start = match r.start {
-                SliceBounds::Beginning => 0,
-                SliceBounds::Index(i) => {
-                    offset += (i as i64) * layout.stride()[dim] as i64;
+// The single element at `index` as a range of bounds.
+#[inline]
+fn index_bounds(index: i32) -> (SliceBounds, SliceBounds) {
+    match SliceBounds::from_i32(index) {
+        SliceBounds::Index(index) => (SliceBounds::Index(index), SliceBounds::Index(index + 1)),
+        SliceBounds::ReverseIndex(1) => (SliceBounds::ReverseIndex(1), SliceBounds::End),
+        SliceBounds::ReverseIndex(index) => (
+            SliceBounds::ReverseIndex(index),
+            SliceBounds::ReverseIndex(index - 1),
+        ),
+        _ => unreachable!(),
+    }
+}
 
-                    i
+impl SliceInfo {
+    // Indexes are sliced like ranges of one element, and their axes are removed afterwards.
+    // New axes are given the stride a contiguous tensor would have there, so they do not
+    // make a contiguous view look strided.
+    pub(crate) fn from_range(layout: &Layout, range: &[SliceKind]) -> Result<Self, OpError> {
+        let bounds: Vec<(SliceBounds, SliceBounds, i32)> = (range.iter())
+            .filter_map(|kind| match kind {
+                SliceKind::Range(r) => Some((r.start, r.end, r.step.get())),
+                SliceKind::Index(index) => {
+                    let (start, end) = index_bounds(*index);
+                    Some((start, end, 1))
                 }
-                SliceBounds::ReverseIndex(i) => {
-                    let true_index = layout.shape()[dim] - i;
-                    offset += true_index as i64 * layout.stride()[dim] as i64;
+                SliceKind::NewAxis => None,
+            })
+            .collect();
 
-                    true_index
+        cfg_debug_only!({
+            if bounds.len() > layout.shape().len() {
+                return Err(OpError::NotEnoughAxes(layout.shape().len(), bounds.len()));
+            }
+
+            // Checked here, so the error names the index rather than the bounds around it.
+            let taken = range
+                .iter()
+                .filter(|kind| !matches!(kind, SliceKind::NewAxis));
+            for (axis, kind) in taken.enumerate() {
+                if let SliceKind::Index(index) = kind {
+                    check_index(axis, *index, layout.shape()[axis])?;
                 }
-                _ => unreachable!("a new variation of SliceBounds was implemented"),
-            };
+            }
+        });
+
+        let sliced = Self::from_bounds(layout, bounds.into_iter())?;
+
+        let mut shape: DimVec<usize> = DimVec::new();
+        let mut stride: DimVec<i32> = DimVec::new();
+        let mut new_axes: Vec<usize> = Vec::new();
+        let mut axis = 0;
 
-            let end = match r.end {
-                SliceBounds::End => layout.shape()[dim],
-                SliceBounds::Index(i) => i,
-                SliceBounds::ReverseIndex(i) => {
-                    let true_index = layout.shape()[dim] - i;
-                    true_index
+        for kind in range {
+            match kind {
+                SliceKind::Range(_) => {
+                    shape.push(sliced.shape[axis]);
+                    stride.push(sliced.stride[axis]);
+                    axis += 1;
                 }
-                _ => unreachable!("a new variation of SliceBounds was implemented"),
+                SliceKind::Index(_) => axis += 1,
+                // Filled in below, once the axes after it are known.
+                SliceKind::NewAxis => {
+                    new_axes.push(shape.len());
+                    shape.push(1);
+                    stride.push(1);
+                }
+            }
+        }
+
+        shape.extend_from_slice(&sliced.shape[axis..]);
+        stride.extend_from_slice(&sliced.stride[axis..]);
+
+        for &dim in new_axes.iter().rev() {
+            if let Some(&len) = shape.get(dim + 1) {
+                stride[dim] = stride[dim + 1] * len as i32;
+            }
+        }
+
+        // Indexing every axis leaves a single element.
+        if shape.is_empty() {
+            shape.push(1);
+            stride.push(1);
+        }
+
+        let adj_stride = calculate_adjacent_dim_stride(&stride, &shape);
+
+        Ok(Self {
+            offset: sliced.offset,
+            shape,
+            stride,
+            adj_stride,
+        })
+    }
+
+    pub(crate) fn from_step_range(
+        layout: &Layout,
+        range: &[StepSliceRange],
+    ) -> Result<Self, OpError> {
+        Self::from_bounds(layout, range.iter().map(|r| (r.start, r.end, r.step.get())))
+    }
+
+    fn from_bounds(
+        layout: &Layout,
+        range: impl ExactSizeIterator<Item = (SliceBounds, SliceBounds, i32)>,
+    ) -> Result<Self, OpError> {
+        debug_assert!(layout.shape().len() >= range.len());
+
+        let mut offset: i64 = layout.offset() as i64;
+        let mut new_shape = layout.shape.clone();
+        let mut new_stride = layout.stride.clone();
+
+        for (dim, (start, end, step)) in range.enumerate() {
+            let dim_size = layout.shape()[dim];
+
+            cfg_debug_only!({
+                check_bound(dim, start, dim_size)?;
+                check_bound(dim, end, dim_size)?;
+            });
+
+            let start = resolve_bound(start, dim_size);
+            let end = resolve_bound(end, dim_size);
+
+            // A negative step walks the same positions backwards, so the bounds
+            // may come in either order.
+            let (start, end) = if step < 0 && start > end {
+                (end, start)
+            } else {
+                (start, end)
             };
 
             cfg_debug_only!(if end <= start {
                 return Err(OpError::OutOfBoundSlice);
             });
 
-            new_shape[dim] = end - start;
-        }
-
-        cfg_debug_only!({
-            let len: usize = new_shape.iter().product();
-            let len = len as usize;
+            // An empty range, only left with the checks compiled out, starts at its start.
+            let first = if step > 0 || end <= start {
+                start
+            } else {
+                end - 1
+            };
+            offset += first as i64 * layout.stride()[dim] as i64;
 
-            if len + (offset as usize) > layout.len() {
-                return Err(OpError::InvalidSliceShape(layout.len(), len));
-            }
-        });
+            new_shape[dim] = (end.saturating_sub(start)).div_ceil(step.unsigned_abs() as usize);
+            new_stride[dim] *= step;
+        }
 
-        let adj_stride = calculate_adjacent_dim_stride(layout.stride(), &new_shape);
+        let adj_stride = calculate_adjacent_dim_stride(&new_stride, &new_shape);
 
         Ok(Self {
             offset: offset as usize,
-            shape: new_shape.into_boxed_slice(),
+            shape: new_shape,
+            stride: new_stride,
             adj_stride,
         })
     }
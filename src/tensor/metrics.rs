@@ -0,0 +1,264 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+/// What a counter of a MetricsSink counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MetricKey {
+    /// Nodes computed with the op of the given name, like "Add", see OpKind::as_str().
+    OpsComputed(&'static str),
+    /// Elements of the results of the computed nodes.
+    ElementsProcessed,
+    /// Reads of a cached promise that found its result already computed.
+    CacheHits,
+    /// Reads of a cached promise that had to compute its result.
+    CacheMisses,
+    /// Calls to materialize() and its variants, a materialize_many() being a single one.
+    Materializations,
+    /// Ops merged with their inputs, when the graph is built or, for the casts of
+    /// set_cast_fusion(), the divisions by a square root of rsqrt() and the rewrites of
+    /// set_exp_log_fusion(), when it is materialized.
+    FusionRewrites,
+    /// Add, Sub, Mul and Div nodes that read their second operand the given way.
+    BinaryRoutes(BinaryRoute),
+}
+
+/// How a binary op read its second operand, see set_adaptive_packing().
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BinaryRoute {
+    /// Straight from its buffer, as it is contiguous.
+    Contiguous,
+    /// As a scalar op, every element being the same one, like a broadcast made with
+    /// as_strided().
+    Scalar,
+    /// With a strided BLAS axpy, its elements being the same distance apart, like
+    /// a column of a row-major matrix.
+    Strided,
+    /// Gathered into a contiguous buffer chunk by chunk, or whole for a transposed matrix.
+    Packed,
+}
+
+/// Receives the counters of the materializations, see set_metrics_sink().
+///
+/// The counts of a materialization are kept by the thread running it and handed to the sink
+/// once it is done, with a single call per key, so the sink is never called from the
+/// loop computing the nodes. It can be called from several threads at once.
+pub trait MetricsSink: Send + Sync {
+    fn incr(&self, key: MetricKey, by: u64);
+}
+
+static METRICS_ENABLED: AtomicBool = AtomicBool::new(false);
+static METRICS_SINK: RwLock<Option<Arc<dyn MetricsSink>>> = RwLock::new(None);
+
+/// Sends the counters of every materialization from now on to `sink`, replacing the
+/// previous one. Without a sink, which is the default, nothing is counted.
+pub fn set_metrics_sink(sink: Arc<dyn MetricsSink>) {
+    *METRICS_SINK.write().unwrap_or_else(PoisonError::into_inner) = Some(sink);
+    METRICS_ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Removes the sink of set_metrics_sink(), the counts not flushed yet are dropped.
+pub fn clear_metrics_sink() {
+    METRICS_ENABLED.store(false, Ordering::Relaxed);
+    *METRICS_SINK.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+// In the order of PendingMetrics::binary_routes.
+const ROUTES: [BinaryRoute; 4] = [
+    BinaryRoute::Contiguous,
+    BinaryRoute::Scalar,
+    BinaryRoute::Strided,
+    BinaryRoute::Packed,
+];
+
+// Counts of the current thread not handed to the sink yet.
+struct PendingMetrics {
+    ops: Vec<(&'static str, u64)>,
+    elements: u64,
+    cache_hits: u64,
+    cache_misses: u64,
+    materializations: u64,
+    fusion_rewrites: u64,
+    binary_routes: [u64; 4],
+}
+
+impl PendingMetrics {
+    const fn new() -> Self {
+        Self {
+            ops: Vec::new(),
+            elements: 0,
+            cache_hits: 0,
+            cache_misses: 0,
+            materializations: 0,
+            fusion_rewrites: 0,
+            binary_routes: [0; 4],
+        }
+    }
+
+    fn counts(&self) -> impl Iterator<Item = (MetricKey, u64)> + '_ {
+        let ops = (self.ops.iter()).map(|&(op, count)| (MetricKey::OpsComputed(op), count));
+        let routes = (ROUTES.iter().zip(self.binary_routes))
+            .map(|(&route, count)| (MetricKey::BinaryRoutes(route), count));
+
+        ops.chain([
+            (MetricKey::ElementsProcessed, self.elements),
+            (MetricKey::CacheHits, self.cache_hits),
+            (MetricKey::CacheMisses, self.cache_misses),
+            (MetricKey::Materializations, self.materializations),
+            (MetricKey::FusionRewrites, self.fusion_rewrites),
+        ])
+        .chain(routes)
+        .filter(|&(_, count)| count > 0)
+    }
+}
+
+thread_local! {
+    // Whether a materialization counted by a sink is running on this thread.
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+    static PENDING: RefCell<PendingMetrics> = const { RefCell::new(PendingMetrics::new()) };
+}
+
+// Ends the outermost counted materialization, even if it panics.
+struct Recording;
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        RECORDING.set(false);
+        flush();
+    }
+}
+
+// Runs the materialization `f`, counting it if there is a sink. The sink is only looked up
+// once, here, and a materialization nested in another one is counted by the outer one.
+pub(crate) fn recorded<R>(f: impl FnOnce() -> R) -> R {
+    if RECORDING.get() || !METRICS_ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+
+    RECORDING.set(true);
+    let _recording = Recording;
+    PENDING.with_borrow_mut(|pending| pending.materializations += 1);
+
+    f()
+}
+
+// Runs `f` without counting anything it computes, not even the fusions it makes.
+#[cfg(feature = "check_fast_paths")]
+pub(crate) fn unrecorded<R>(f: impl FnOnce() -> R) -> R {
+    let recording = RECORDING.replace(false);
+    let pending = PENDING.replace(PendingMetrics::new());
+    let result = f();
+    PENDING.set(pending);
+    RECORDING.set(recording);
+
+    result
+}
+
+fn flush() {
+    let pending = PENDING.replace(PendingMetrics::new());
+    let sink = METRICS_SINK
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+
+    if let Some(sink) = sink {
+        for (key, count) in pending.counts() {
+            sink.incr(key, count);
+        }
+    }
+}
+
+// A node computed with `op`, its result having `elements` elements.
+#[inline]
+pub(crate) fn record_op(op: &'static str, elements: usize) {
+    if !RECORDING.get() {
+        return;
+    }
+
+    PENDING.with_borrow_mut(|pending| {
+        pending.elements += elements as u64;
+
+        match pending.ops.iter_mut().find(|(name, _)| *name == op) {
+            Some((_, count)) => *count += 1,
+            None => pending.ops.push((op, 1)),
+        }
+    });
+}
+
+#[inline]
+pub(crate) fn record_binary_route(route: BinaryRoute) {
+    if !RECORDING.get() {
+        return;
+    }
+
+    PENDING.with_borrow_mut(|pending| pending.binary_routes[route as usize] += 1);
+}
+
+#[inline]
+pub(crate) fn record_cache_read(hit: bool) {
+    if !RECORDING.get() {
+        return;
+    }
+
+    PENDING.with_borrow_mut(|pending| match hit {
+        true => pending.cache_hits += 1,
+        false => pending.cache_misses += 1,
+    });
+}
+
+// Fusions happen as graphs are built, so they are counted whenever there is a sink
+// and handed to it with the next materialization of the thread.
+#[inline]
+pub(crate) fn record_fusions(count: usize) {
+    if count == 0 || !METRICS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    PENDING.with_borrow_mut(|pending| pending.fusion_rewrites += count as u64);
+}
+
+/// A MetricsSink adding up the counters in memory, for tests and for services that
+/// export them on their own.
+#[derive(Debug, Default)]
+pub struct InMemoryMetrics {
+    counts: Mutex<HashMap<MetricKey, u64>>,
+}
+
+impl InMemoryMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every counter incremented so far with its total.
+    pub fn snapshot(&self) -> HashMap<MetricKey, u64> {
+        self.counts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Total of the counter `key`, 0 if it was never incremented.
+    pub fn get(&self, key: MetricKey) -> u64 {
+        (self.counts.lock().unwrap_or_else(PoisonError::into_inner))
+            .get(&key)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Sets every counter back to 0.
+    pub fn reset(&self) {
+        self.counts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl MetricsSink for InMemoryMetrics {
+    fn incr(&self, key: MetricKey, by: u64) {
+        *(self.counts.lock().unwrap_or_else(PoisonError::into_inner))
+            .entry(key)
+            .or_insert(0) += by;
+    }
+}
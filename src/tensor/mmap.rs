@@ -0,0 +1,412 @@
+// Read-only tensors over memory-mapped NPY files, behind the `mmap` feature.
+//
+// The map is the buffer of the tensor: nothing is read until an element is, so files much
+// larger than the memory can be sliced, iterated or streamed through the graph, and the
+// pages read are left to the OS to evict.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::impl_display;
+use crate::tensor::complex::Complex;
+use crate::tensor::errors::{IoError, OpError};
+use crate::tensor::iter::{CopiedSliceIter, InformedSliceIter, SliceIter};
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::mem_formats::slice::{SliceKind, StepSliceRange};
+use crate::tensor::storage::{MappedBuffer, Storage, TensorData};
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+// The elements of the files written start at a multiple of this, like the ones of NumPy.
+const HEADER_ALIGN: usize = 64;
+
+/// An element type with a fixed NPY dtype, which can be read straight from the bytes of a file.
+///
+/// # Safety
+/// Every bit pattern of the size of the type must be a valid value of it, as the elements
+/// are never checked.
+pub unsafe trait NpyElement: Copy {
+    /// The dtype without its byte order, like "f8".
+    const DTYPE: &'static str;
+}
+
+unsafe impl NpyElement for f64 {
+    const DTYPE: &'static str = "f8";
+}
+
+unsafe impl NpyElement for f32 {
+    const DTYPE: &'static str = "f4";
+}
+
+unsafe impl NpyElement for i64 {
+    const DTYPE: &'static str = "i8";
+}
+
+unsafe impl NpyElement for i32 {
+    const DTYPE: &'static str = "i4";
+}
+
+unsafe impl NpyElement for u8 {
+    const DTYPE: &'static str = "u1";
+}
+
+unsafe impl NpyElement for Complex<f64> {
+    const DTYPE: &'static str = "c16";
+}
+
+#[cfg(target_endian = "little")]
+const NATIVE_ORDER: char = '<';
+#[cfg(target_endian = "big")]
+const NATIVE_ORDER: char = '>';
+
+/// A tensor whose elements are the ones of a memory-mapped NPY file, see
+/// TensorData::open_npy_mmap(). Its slices stay mapped, and the file is unmapped once
+/// it, its slices and every tensor or promise built from them are dropped.
+///
+/// It cannot be written: use Tensor::from_mapped() to read it in the graph, where
+/// the mutation APIs return ReadOnlyBuffer.
+#[derive(Clone, Debug)]
+pub struct MappedTensor<T: Copy> {
+    data: TensorData<T>,
+}
+
+impl<T: Copy> MappedTensor<T> {
+    #[inline]
+    pub fn data(&self) -> &TensorData<T> {
+        &self.data
+    }
+
+    #[inline]
+    pub fn iter(&self) -> SliceIter<'_, T> {
+        self.data.iter()
+    }
+
+    #[inline]
+    pub fn copied_iter(&self) -> CopiedSliceIter<'_, T> {
+        self.data.copied_iter()
+    }
+
+    #[inline]
+    pub fn informed_iter(&self) -> InformedSliceIter<'_, T> {
+        self.data.informed_iter()
+    }
+
+    /// Same as TensorData::fold().
+    #[inline]
+    pub fn fold<B>(&self, init: B, f: impl FnMut(B, T) -> B) -> B {
+        self.data.fold(init, f)
+    }
+
+    /// Same as TensorData::for_each().
+    #[inline]
+    pub fn for_each(&self, f: impl FnMut(T)) {
+        self.data.for_each(f)
+    }
+
+    /// A view of `range` of this tensor over the same map, see [`s!`](crate::s).
+    pub fn slice(&self, range: &[SliceKind]) -> Result<Self, OpError> {
+        self.as_layout(self.layout().slice(range))
+    }
+
+    /// Same as slice(), with a step per axis, see [`ss!`](crate::ss).
+    pub fn step_slice(&self, range: &[StepSliceRange]) -> Result<Self, OpError> {
+        self.as_layout(self.layout().step_slice(range))
+    }
+
+    /// A view with the axes in reverse order over the same map.
+    pub fn transpose(&self) -> Self {
+        Self {
+            data: self.data.as_layout(self.layout().transpose()),
+        }
+    }
+
+    fn as_layout(&self, layout: Result<Layout, OpError>) -> Result<Self, OpError> {
+        Ok(Self {
+            data: self.data.as_layout(layout?),
+        })
+    }
+}
+
+impl<T: Copy> Dimension for MappedTensor<T> {
+    #[inline]
+    fn layout(&self) -> &Layout {
+        self.data.layout()
+    }
+}
+
+impl_display!(MappedTensor<T>);
+
+impl<T: NpyElement> TensorData<T> {
+    /// Maps the NPY file at `path` without reading its elements. The dtype of the file must
+    /// be the one of T in the byte order of this machine, and the array in C order.
+    ///
+    /// The elements are read from the file as they are accessed, so it must not be modified
+    /// or truncated while the map is alive: doing so is undefined behavior, as for any map.
+    /// Files whose elements do not start at a multiple of the alignment of T are rejected,
+    /// which the files written by NumPy always do.
+    pub fn open_npy_mmap(path: impl AsRef<Path>) -> Result<MappedTensor<T>, IoError> {
+        // The file can be closed once mapped, the map keeps the pages reachable.
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+
+        if (file_len as usize) < MAGIC.len() + 2 {
+            return Err(IoError::NotNpy);
+        }
+
+        let map = unsafe { Mmap::map(&file)? };
+        drop(file);
+
+        let header = Header::parse(&map)?;
+
+        if header.shape.is_empty() {
+            return Err(invalid("arrays without axes are not supported".into()));
+        }
+
+        if !header.reads_as::<T>() {
+            let expected = format!("{}{}", NATIVE_ORDER, T::DTYPE);
+            return Err(IoError::DtypeMismatch(expected.into(), header.descr.into()));
+        }
+
+        if header.fortran_order {
+            return Err(IoError::FortranOrder);
+        }
+
+        let len = header.len(size_of::<T>())?;
+        let expected_len = (header.start + len * size_of::<T>()) as u64;
+        if file_len < expected_len {
+            return Err(IoError::Truncated(expected_len, file_len));
+        }
+
+        if header.start % align_of::<T>() != 0 {
+            return Err(IoError::Misaligned(header.start, align_of::<T>()));
+        }
+
+        let buffer = unsafe { MappedBuffer::new(map, header.start, len) };
+        let data = TensorData::new(
+            Storage::from_mapped(buffer),
+            Layout::from_shape(&header.shape, 0),
+        );
+
+        Ok(MappedTensor { data })
+    }
+}
+
+impl<T: Copy> Tensor<T> {
+    /// A leaf of the graph reading the elements of `mapped` from the map when it is
+    /// materialized, without copying them first. With stream(), elementwise graphs over
+    /// it run a chunk at a time, whatever the size of the file.
+    ///
+    /// The tensor is read-only, see is_read_only().
+    #[inline]
+    pub fn from_mapped(mapped: &MappedTensor<T>) -> Self {
+        Self::from_data(mapped.data.clone())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The header of an NPY file, a Python dict literal like
+// {'descr': '<f8', 'fortran_order': False, 'shape': (3, 4), }
+pub(crate) struct Header {
+    pub(crate) descr: String,
+    pub(crate) fortran_order: bool,
+    pub(crate) shape: Vec<usize>,
+    // Position of the first element in the file.
+    pub(crate) start: usize,
+}
+
+impl Header {
+    // Reads the header at the start of `reader`, which is left at the first element.
+    pub(crate) fn read(reader: &mut impl Read) -> Result<Self, IoError> {
+        let mut bytes = vec![0; MAGIC.len() + 2];
+        read_prefix(reader, &mut bytes)?;
+
+        let len_size = match (bytes.starts_with(MAGIC), bytes[6]) {
+            (false, _) => return Err(IoError::NotNpy),
+            (true, 1) => 2,
+            (true, 2 | 3) => 4,
+            (true, major) => return Err(IoError::UnsupportedVersion(major, bytes[7])),
+        };
+
+        bytes.resize(bytes.len() + len_size, 0);
+        read_prefix(reader, &mut bytes[MAGIC.len() + 2..])?;
+
+        let header_len = (bytes[MAGIC.len() + 2..].iter().rev())
+            .fold(0u64, |len, &byte| (len << 8) | byte as u64);
+
+        // Read as it comes rather than allocated upfront, the length may be corrupted.
+        reader.take(header_len).read_to_end(&mut bytes)?;
+
+        Self::parse(&bytes)
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, IoError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(IoError::NotNpy);
+        }
+
+        let (major, minor) = (bytes[6], bytes[7]);
+        let (header_start, header_len) = match major {
+            1 if bytes.len() >= 10 => (10, u16::from_le_bytes([bytes[8], bytes[9]]) as usize),
+            2 | 3 if bytes.len() >= 12 => {
+                let len = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+                (12, len as usize)
+            }
+            1..=3 => return Err(IoError::NotNpy),
+            _ => return Err(IoError::UnsupportedVersion(major, minor)),
+        };
+
+        let start = header_start + header_len;
+        let dict = bytes
+            .get(header_start..start)
+            .ok_or_else(|| invalid("the header is longer than the file".into()))?;
+        let dict = std::str::from_utf8(dict)
+            .map_err(|_| invalid("the header is not valid text".into()))?;
+
+        let descr = value_of(dict, "descr")?;
+        if descr.starts_with('[') {
+            let fields = &descr[..descr.find(']').map_or(descr.len(), |end| end + 1)];
+            return Err(IoError::UnsupportedDtype(fields.into()));
+        }
+        let descr = unquote(descr).ok_or_else(|| invalid(format!("descr {}", descr)))?;
+
+        let fortran_order = match value_of(dict, "fortran_order")? {
+            value if value.starts_with("True") => true,
+            value if value.starts_with("False") => false,
+            value => return Err(invalid(format!("fortran_order {}", value))),
+        };
+
+        let shape = value_of(dict, "shape")?;
+        let shape = parse_shape(shape).ok_or_else(|| invalid(format!("shape {}", shape)))?;
+
+        if descr.len() < 2 {
+            return Err(invalid(format!("descr '{}'", descr)));
+        }
+
+        Ok(Self {
+            descr: descr.into(),
+            fortran_order,
+            shape,
+            start,
+        })
+    }
+
+    // Whether the elements are the ones of T, in the byte order of this machine.
+    pub(crate) fn reads_as<T: NpyElement>(&self) -> bool {
+        let (order, dtype) = self.descr.split_at(1);
+
+        // Single bytes have no order, "|u1".
+        let order_matches = order == "=" || order.starts_with(NATIVE_ORDER) || size_of::<T>() == 1;
+
+        order_matches && dtype == T::DTYPE
+    }
+
+    // Number of elements of the array, checking that its elements of `element_size` bytes
+    // can be addressed.
+    pub(crate) fn len(&self, element_size: usize) -> Result<usize, IoError> {
+        let len = (self.shape.iter())
+            .try_fold(1usize, |acc, &dim| acc.checked_mul(dim))
+            .filter(|len| len.checked_mul(element_size).is_some())
+            .ok_or_else(|| invalid(format!("the shape {:?} is too large", self.shape)))?;
+
+        // Strides are 32 bits wide, the first axis is the only one that does not need one.
+        let largest_stride = self.shape.iter().skip(1).product::<usize>();
+        if largest_stride > i32::MAX as usize {
+            return Err(invalid(format!(
+                "the strides of the shape {:?} do not fit in 32 bits",
+                self.shape
+            )));
+        }
+
+        Ok(len)
+    }
+
+    // The header of an array of T of the given shape in C order, padded so the elements
+    // start at a multiple of HEADER_ALIGN.
+    pub(crate) fn write<T: NpyElement>(shape: &[usize]) -> Vec<u8> {
+        let order = if size_of::<T>() == 1 {
+            '|'
+        } else {
+            NATIVE_ORDER
+        };
+        let dims: String = shape.iter().map(|dim| format!("{},", dim)).collect();
+        // "(3,)" for a single axis, "(3, 4)" for more.
+        let dims = match shape.len() {
+            1 => dims,
+            _ => dims.trim_end_matches(',').replace(',', ", "),
+        };
+        let mut dict = format!(
+            "{{'descr': '{}{}', 'fortran_order': False, 'shape': ({}), }}",
+            order,
+            T::DTYPE,
+            dims
+        );
+
+        let (version, len_size) = match dict.len() < u16::MAX as usize - HEADER_ALIGN {
+            true => (1, 2),
+            false => (2, 4),
+        };
+        let prefix = MAGIC.len() + 2 + len_size;
+        let padded = (prefix + dict.len() + 1).next_multiple_of(HEADER_ALIGN) - prefix;
+        dict.extend(std::iter::repeat_n(' ', padded - dict.len() - 1));
+        dict.push('\n');
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([version, 0]);
+        bytes.extend(&(dict.len() as u32).to_le_bytes()[..len_size]);
+        bytes.extend(dict.as_bytes());
+
+        bytes
+    }
+}
+
+// Fills `bytes` from `reader`, which is not an NPY file if it ends first.
+fn read_prefix(reader: &mut impl Read, bytes: &mut [u8]) -> Result<(), IoError> {
+    reader.read_exact(bytes).map_err(|err| match err.kind() {
+        std::io::ErrorKind::UnexpectedEof => IoError::NotNpy,
+        _ => err.into(),
+    })
+}
+
+fn invalid(reason: String) -> IoError {
+    IoError::InvalidHeader(reason.into())
+}
+
+// Everything after the colon following `key`, trimmed at the start.
+fn value_of<'a>(dict: &'a str, key: &str) -> Result<&'a str, IoError> {
+    [format!("'{}'", key), format!("\"{}\"", key)]
+        .iter()
+        .find_map(|quoted| {
+            dict.find(quoted.as_str())
+                .map(|at| &dict[at + quoted.len()..])
+        })
+        .and_then(|rest| rest.trim_start().strip_prefix(':'))
+        .map(str::trim_start)
+        .ok_or_else(|| invalid(format!("the key {} is missing", key)))
+}
+
+// The text between the quotes starting `value`.
+fn unquote(value: &str) -> Option<&str> {
+    let quote = value.chars().next().filter(|&c| c == '\'' || c == '"')?;
+    let rest = &value[1..];
+
+    rest.find(quote).map(|end| &rest[..end])
+}
+
+// The dimensions of a tuple like "(3, 4)" or "(3,)" starting `value`.
+fn parse_shape(value: &str) -> Option<Vec<usize>> {
+    let inner = value.strip_prefix('(')?;
+    let inner = &inner[..inner.find(')')?];
+
+    inner
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.trim_end_matches('L').parse().ok())
+        .collect()
+}
@@ -1,6 +1,6 @@
-extern crate cblas;
+#[cfg(feature = "mkl")]
 extern crate intel_mkl_src;
-extern crate intel_mkl_sys;
+#[cfg(any(feature = "mkl", feature = "cblas"))]
 extern crate lapacke;
 
 pub const PACKING_BUFFER_SIZE: usize = 128;
@@ -8,27 +8,113 @@ pub const PACKING_BUFFER_SIZE: usize = 128;
 #[macro_use]
 mod convenience;
 
+mod backend;
+mod cache_pool;
+mod compiled;
+pub mod complex;
+mod composite;
 mod definitions;
+mod device;
 pub mod errors;
 mod impl_generics;
 mod internals;
 mod iter;
+mod linalg;
 mod macros;
 mod mem_formats;
-mod mkl_extension;
+mod metrics;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "mmap")]
+mod npz;
+mod numerics;
+mod padding;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod parse;
+mod pretty;
+mod profile;
+mod progress;
+mod rewrite;
+#[cfg(feature = "serde")]
+mod serialization;
+mod sparse;
+#[cfg(feature = "static_tensor")]
+mod static_tensor;
 mod storage;
+mod stream;
+mod trace;
 mod traits;
 
+pub mod geometry;
 pub mod graph;
+pub mod named;
+pub mod nn;
 pub mod ops;
+pub mod optim;
 pub mod promise;
+pub mod shape_check;
 // pub mod slice;
 pub mod tensor;
+pub mod testing;
+pub use cache_pool::CachePool;
+pub use compiled::CompiledGraph;
+pub use composite::CompositeBuilder;
 pub use convenience::*;
+pub use device::Device;
+pub use errors::{Error, ErrorContext, LinAlgError};
+pub use graph::{
+    GraphStats, Schedule, TensorId, cast_fusion, exp_log_fusion, graph_assertions, max_graph_depth,
+    set_cast_fusion, set_exp_log_fusion, set_graph_assertions, set_max_graph_depth,
+};
 // pub use iter::StepInfo;
 // pub use traits::Dimension;
 
-pub use mem_formats::slice::SliceRange;
-pub use promise::{CachedTensorPromise, TensorPromise};
-pub use tensor::Tensor;
+pub use linalg::{LuFactors, MatrixNorm};
+pub use mem_formats::interner::{
+    InterningScope, LayoutInterner, layout_interning, set_layout_interning,
+};
+pub use mem_formats::layout::Layout;
+pub use mem_formats::slice::{NewAxis, SliceKind, SliceRange, StepRange, StepSliceRange};
+pub use metrics::{
+    BinaryRoute, InMemoryMetrics, MetricKey, MetricsSink, clear_metrics_sink, set_metrics_sink,
+};
+#[cfg(feature = "mmap")]
+pub use mmap::{MappedTensor, NpyElement};
+#[cfg(feature = "mmap")]
+pub use npz::{NpzCompression, NpzTensor, NpzWriter, SkippedMember, load_npz, save_npz};
+pub use numerics::{
+    DEFAULT_PAIRWISE_BLOCK, DEFAULT_SPARSE_THRESHOLD, FlushDenormals, NumericsMode, NumericsScope,
+    SparseZeros, Summation, denormals_are_flushed, flush_denormals, numerics_mode, pairwise_block,
+    set_flush_denormals, set_numerics_mode, set_pairwise_block, set_sparse_threshold,
+    set_sparse_zeros, set_summation, sparse_threshold, sparse_zeros, summation,
+};
+pub use ops::def_op::{NanPolicy, QuantileMethod};
+pub use ops::impl_op::{
+    BatchProduct, ElementwiseMinMax, InnerProduct, OuterProduct, TryArithmetic,
+};
+pub use ops::{
+    ApplyOptions, DEFAULT_MAX_FUSED_SCALAR_OPS, adaptive_packing, max_fused_scalar_ops,
+    set_adaptive_packing, set_max_fused_scalar_ops, set_strided_batch_gemm, strided_batch_gemm,
+};
+pub use padding::{PaddingMode, pad_sequence};
+#[cfg(feature = "parallel")]
+pub use parallel::{ParallelismConfig, materialize_all};
+pub use pretty::FLAT_ELEMENT_BUDGET;
+pub use profile::{ProfileRecord, flush_profile};
+#[cfg(feature = "indicatif")]
+pub use progress::last_progress;
+pub use progress::{ProgressOutput, set_progress_output};
+pub use promise::{
+    CachedTensorPromise, TensorPromise, detect_nan_in_graph, materialize_many, try_materialize_many,
+};
+pub use rewrite::{GraphRewriter, Replacement, RewriteNodeView};
+#[cfg(feature = "serde")]
+pub use serialization::TENSOR_FORMAT_VERSION;
+pub use sparse::{SparseTensor, sparse_add};
+#[cfg(feature = "static_tensor")]
+pub use static_tensor::StaticTensor;
+pub use storage::{ExternalDeleter, TensorData};
+pub use tensor::{ConsumableTensor, Tensor};
+pub use trace::{InputRead, TraceRecord, TraceRecorder};
 pub use traits::Dimension;
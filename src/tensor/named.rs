@@ -0,0 +1,284 @@
+//! Tensors whose axes carry names in their type, so mixing up axes is a compile error.
+//!
+//! Every name is stored in the type as a hash, which keeps two tensors built with the same
+//! names, in the same order, of the same type:
+//!
+//! ```
+//! use simple_tensor::named_tensor;
+//! use simple_tensor::tensor::Tensor;
+//!
+//! let a = named_tensor!(Tensor::from_scalar(1.0, &[2, 3]), ["batch", "channel"]).unwrap();
+//! let b = named_tensor!(Tensor::from_scalar(2.0, &[2, 3]), ["batch", "channel"]).unwrap();
+//!
+//! let sum = (&a + &b).materialize();
+//! assert_eq!(sum.names(), &["batch", "channel"]);
+//! ```
+//!
+//! Tensors with different names cannot be combined:
+//!
+//! ```compile_fail,E0369
+//! use simple_tensor::named_tensor;
+//! use simple_tensor::tensor::Tensor;
+//!
+//! let a = named_tensor!(Tensor::from_scalar(1.0, &[2, 3]), ["batch", "channel"]).unwrap();
+//! let b = named_tensor!(Tensor::from_scalar(2.0, &[2, 3]), ["channel", "batch"]).unwrap();
+//!
+//! let sum = &a + &b;
+//! ```
+
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::OpError;
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::promise::RawTensorPromise;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::{Dimension, Promising};
+
+/// FNV-1a hash of an axis name, used to encode it in a type.
+#[doc(hidden)]
+pub const fn axis_hash(name: &str) -> u64 {
+    let bytes = name.as_bytes();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+
+    hash
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Type-level list of axis names, built with the axes! macro.
+pub trait AxisNames: sealed::Sealed {
+    const LEN: usize;
+
+    fn hash_at(idx: usize) -> Option<u64>;
+}
+
+pub struct AxisNil;
+
+pub struct AxisCons<const HASH: u64, Tail>(PhantomData<Tail>);
+
+impl sealed::Sealed for AxisNil {}
+
+impl<const HASH: u64, Tail: AxisNames> sealed::Sealed for AxisCons<HASH, Tail> {}
+
+impl AxisNames for AxisNil {
+    const LEN: usize = 0;
+
+    fn hash_at(_: usize) -> Option<u64> {
+        None
+    }
+}
+
+impl<const HASH: u64, Tail: AxisNames> AxisNames for AxisCons<HASH, Tail> {
+    const LEN: usize = Tail::LEN + 1;
+
+    fn hash_at(idx: usize) -> Option<u64> {
+        match idx {
+            0 => Some(HASH),
+            _ => Tail::hash_at(idx - 1),
+        }
+    }
+}
+
+/// The type of a list of axis names, e.g. `axes!["batch", "channel"]`.
+#[macro_export]
+macro_rules! axes {
+    [] => {
+        $crate::tensor::named::AxisNil
+    };
+
+    [$head: literal $(, $tail: literal)* $(,)?] => {
+        $crate::tensor::named::AxisCons<
+            { $crate::tensor::named::axis_hash($head) },
+            $crate::axes![$($tail),*]
+        >
+    };
+}
+
+/// Wraps a tensor or a promise, naming each of its axes.
+/// Returns an error if the number of names does not match the number of axes.
+#[macro_export]
+macro_rules! named_tensor {
+    ($tensor: expr, [$($name: literal),* $(,)?]) => {
+        $crate::tensor::named::Named::<_, { [$($name),*].len() }, $crate::axes![$($name),*]>::new(
+            $tensor,
+            [$($name),*],
+        )
+    };
+}
+
+/// A tensor or a promise with named axes. The names only live in the type and in a small
+/// array next to the wrapped value, the storage is the same as the one of `D`.
+/// Binary ops are only implemented between values with the same names in the same order.
+pub struct Named<D, const N: usize, Names: AxisNames> {
+    inner: D,
+    names: [&'static str; N],
+    _names: PhantomData<Names>,
+}
+
+impl<D: Dimension, const N: usize, Names: AxisNames> Named<D, N, Names> {
+    /// Names the axes of `inner`. Prefer the named_tensor! macro, which builds the `Names` type.
+    /// `names` must match the names encoded in `Names`.
+    pub fn new(inner: D, names: [&'static str; N]) -> Result<Self, OpError> {
+        const { assert!(N == Names::LEN, "N must be the number of names in Names") };
+
+        if inner.shape().len() != N {
+            return Err(OpError::NotEnoughAxes(N, inner.shape().len()));
+        }
+
+        let matches_type = names
+            .iter()
+            .enumerate()
+            .all(|(idx, name)| Names::hash_at(idx) == Some(axis_hash(name)));
+
+        if !matches_type {
+            return Err(OpError::AxisNamesMismatch);
+        }
+
+        Ok(Self {
+            inner,
+            names,
+            _names: PhantomData,
+        })
+    }
+
+    /// Renames the axis `old` to `new`. The result must be annotated with its new names,
+    /// e.g. `named.rename::<axes!["batch", "features"]>("channel", "features")`.
+    pub fn rename<NewNames: AxisNames>(
+        self,
+        old: &str,
+        new: &'static str,
+    ) -> Result<Named<D, N, NewNames>, OpError> {
+        let Some(idx) = self.axis(old) else {
+            let names = self.names.iter().map(|&name| name.into()).collect();
+
+            return Err(OpError::UnknownAxisName(old.into(), names));
+        };
+
+        let mut names = self.names;
+        names[idx] = new;
+
+        Named::new(self.inner, names)
+    }
+}
+
+impl<D, const N: usize, Names: AxisNames> Named<D, N, Names> {
+    #[inline]
+    pub fn names(&self) -> &[&'static str; N] {
+        &self.names
+    }
+
+    /// Index of the axis called `name`.
+    pub fn axis(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|&axis| axis == name)
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+
+    // The names are already known to match, only the wrapped value changes.
+    fn with_inner<E>(&self, inner: E) -> Named<E, N, Names> {
+        Named {
+            inner,
+            names: self.names,
+            _names: PhantomData,
+        }
+    }
+}
+
+impl<P, const N: usize, Names: AxisNames> Named<RawTensorPromise<P>, N, Names>
+where
+    P: Promising<Output: NumberLike>,
+{
+    pub fn materialize(self) -> Named<Tensor<P::Output>, N, Names> {
+        let names = self.names;
+
+        Named {
+            inner: self.inner.materialize(),
+            names,
+            _names: PhantomData,
+        }
+    }
+}
+
+// Names is only a marker, so these are implemented by hand to not require it to be Clone or Debug.
+impl<D: Clone, const N: usize, Names: AxisNames> Clone for Named<D, N, Names> {
+    fn clone(&self) -> Self {
+        self.with_inner(self.inner.clone())
+    }
+}
+
+impl<D: std::fmt::Debug, const N: usize, Names: AxisNames> std::fmt::Debug for Named<D, N, Names> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Named")
+            .field("inner", &self.inner)
+            .field("names", &self.names)
+            .finish()
+    }
+}
+
+impl<D: Dimension, const N: usize, Names: AxisNames> Dimension for Named<D, N, Names> {
+    #[inline]
+    fn layout(&self) -> &Layout {
+        self.inner.layout()
+    }
+}
+
+macro_rules! impl_named_binop {
+    ($trait:ident, $method:ident) => {
+        impl<D1, D2, const N: usize, Names> $trait<Named<D2, N, Names>> for Named<D1, N, Names>
+        where
+            D1: $trait<D2>,
+            Names: AxisNames,
+        {
+            type Output = Named<D1::Output, N, Names>;
+
+            #[inline]
+            fn $method(self, rhs: Named<D2, N, Names>) -> Self::Output {
+                let names = self.names;
+
+                Named {
+                    inner: self.inner.$method(rhs.inner),
+                    names,
+                    _names: PhantomData,
+                }
+            }
+        }
+
+        impl<'a, 'b, D1, D2, const N: usize, Names> $trait<&'b Named<D2, N, Names>>
+            for &'a Named<D1, N, Names>
+        where
+            &'a D1: $trait<&'b D2>,
+            Names: AxisNames,
+        {
+            type Output = Named<<&'a D1 as $trait<&'b D2>>::Output, N, Names>;
+
+            #[inline]
+            fn $method(self, rhs: &'b Named<D2, N, Names>) -> Self::Output {
+                self.with_inner(self.inner.$method(&rhs.inner))
+            }
+        }
+    };
+}
+
+impl_named_binop!(Add, add);
+impl_named_binop!(Sub, sub);
+impl_named_binop!(Mul, mul);
+impl_named_binop!(Div, div);
@@ -0,0 +1,173 @@
+use crate::tensor::errors::OpError;
+use crate::tensor::graph::NodeKind;
+use crate::tensor::ops::def_op::OpKind;
+use crate::tensor::traits::Dimension;
+use crate::tensor::{Tensor, TensorPromise};
+
+pub use crate::tensor::ops::def_op::Reduction;
+
+/// Mean squared error between `predictions` and `targets`, i.e. `mean((predictions - targets)^2)`
+/// when using `Reduction::Mean`.
+/// Both tensors must have the same shape.
+pub fn mse_loss(
+    predictions: &Tensor<f64>,
+    targets: &Tensor<f64>,
+    reduction: Reduction,
+) -> Result<TensorPromise<f64>, OpError> {
+    TensorPromise::new(
+        OpKind::MSELoss(reduction),
+        [
+            NodeKind::Edge(predictions.graph.clone()),
+            NodeKind::Edge(targets.graph.clone()),
+        ]
+        .into(),
+    )
+}
+
+/// Mean absolute error between `predictions` and `targets`, i.e. `mean(|predictions - targets|)`
+/// when using `Reduction::Mean`.
+/// Both tensors must have the same shape.
+pub fn mae_loss(
+    predictions: &Tensor<f64>,
+    targets: &Tensor<f64>,
+    reduction: Reduction,
+) -> Result<TensorPromise<f64>, OpError> {
+    TensorPromise::new(
+        OpKind::MAELoss(reduction),
+        [
+            NodeKind::Edge(predictions.graph.clone()),
+            NodeKind::Edge(targets.graph.clone()),
+        ]
+        .into(),
+    )
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterpMode {
+    Nearest,
+    Linear,
+}
+
+// For every output position, returns the two input positions it reads from
+// and the weight of the second one, which is 0 if only the first one is read.
+// Uses the half-pixel convention, so the corners are not aligned.
+fn interp_weights(in_len: usize, out_len: usize, mode: InterpMode) -> Vec<(usize, usize, f64)> {
+    let scale = in_len as f64 / out_len as f64;
+
+    (0..out_len)
+        .map(|i| match mode {
+            InterpMode::Nearest => {
+                let src = ((i as f64 * scale) as usize).min(in_len - 1);
+
+                (src, src, 0.0)
+            }
+            InterpMode::Linear => {
+                let src = ((i as f64 + 0.5) * scale - 0.5).max(0.0);
+                let i0 = (src as usize).min(in_len - 1);
+                let i1 = (i0 + 1).min(in_len - 1);
+                let w = if i0 == i1 { 0.0 } else { src - i0 as f64 };
+
+                (i0, i1, w)
+            }
+        })
+        .collect()
+}
+
+// Blends `a` and `b`, or copies `a` if `b` has no weight, so an infinite `b` is not
+// multiplied by 0 into a NaN.
+#[inline]
+fn lerp(a: f64, b: f64, w: f64) -> f64 {
+    if w == 0.0 { a } else { a * (1.0 - w) + b * w }
+}
+
+fn check_rank(x: &Tensor<f64>, rank: usize) -> Result<(), OpError> {
+    let found = x.shape().len();
+
+    if found < rank {
+        return Err(OpError::NotEnoughAxes(rank, found));
+    }
+
+    if found > rank {
+        return Err(OpError::RankMismatch(rank, found));
+    }
+
+    Ok(())
+}
+
+/// Resizes the last axis of a `[N, C, W]` tensor to `[N, C, size]`.
+/// Tensors of another rank fail with NotEnoughAxes or RankMismatch.
+/// This is an eager operation.
+pub fn interpolate_1d(
+    x: &Tensor<f64>,
+    size: usize,
+    mode: InterpMode,
+) -> Result<Tensor<f64>, OpError> {
+    check_rank(x, 3)?;
+
+    let width = x.shape()[2];
+    let rows = x.shape()[0] * x.shape()[1];
+
+    if width == 0 || size == 0 {
+        return Err(OpError::EmptyAxis(2));
+    }
+
+    let input: Vec<f64> = x.iter().copied().collect();
+    let weights = interp_weights(width, size, mode);
+
+    let mut out: Vec<f64> = Vec::with_capacity(rows * size);
+
+    for row in input.chunks_exact(width) {
+        out.extend(weights.iter().map(|&(i0, i1, w)| lerp(row[i0], row[i1], w)));
+    }
+
+    Ok(Tensor::from_vec(out, &[x.shape()[0], x.shape()[1], size]))
+}
+
+/// Resizes the last two axes of a `[N, C, H, W]` tensor to `[N, C, size.0, size.1]`.
+/// Tensors of another rank fail with NotEnoughAxes or RankMismatch.
+/// This is an eager operation.
+pub fn interpolate_2d(
+    x: &Tensor<f64>,
+    size: (usize, usize),
+    mode: InterpMode,
+) -> Result<Tensor<f64>, OpError> {
+    check_rank(x, 4)?;
+
+    let (height, width) = (x.shape()[2], x.shape()[3]);
+    let planes = x.shape()[0] * x.shape()[1];
+
+    if height == 0 || size.0 == 0 {
+        return Err(OpError::EmptyAxis(2));
+    }
+
+    if width == 0 || size.1 == 0 {
+        return Err(OpError::EmptyAxis(3));
+    }
+
+    let input: Vec<f64> = x.iter().copied().collect();
+    let row_weights = interp_weights(height, size.0, mode);
+    let col_weights = interp_weights(width, size.1, mode);
+
+    let mut out: Vec<f64> = Vec::with_capacity(planes * size.0 * size.1);
+
+    for plane in input.chunks_exact(height * width) {
+        for &(r0, r1, wr) in row_weights.iter() {
+            let top = &plane[r0 * width..(r0 + 1) * width];
+            let bottom = &plane[r1 * width..(r1 + 1) * width];
+
+            out.extend(col_weights.iter().map(|&(c0, c1, wc)| {
+                let t = lerp(top[c0], top[c1], wc);
+                let b = lerp(bottom[c0], bottom[c1], wc);
+
+                lerp(t, b, wr)
+            }));
+        }
+    }
+
+    Ok(Tensor::from_vec(
+        out,
+        &[x.shape()[0], x.shape()[1], size.0, size.1],
+    ))
+}
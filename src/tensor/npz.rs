@@ -0,0 +1,341 @@
+// NPZ archives, the zip files of NPY members NumPy writes with savez(), behind the `mmap`
+// feature.
+//
+// Members are written and read a chunk at a time, so neither saving nor loading ever holds
+// more than the tensors themselves and a small buffer, whatever the size of the archive.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::tensor::complex::Complex;
+use crate::tensor::errors::IoError;
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::mmap::{Header, NpyElement};
+use crate::tensor::storage::TensorData;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+// NumPy names the member of a tensor after it, with this suffix.
+const SUFFIX: &str = ".npy";
+
+// Size of the chunks the elements are written in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How the members of an NPZ archive are stored, see NpzWriter::with_compression().
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NpzCompression {
+    /// As they are, like numpy.savez().
+    #[default]
+    Stored,
+    /// Deflated, like numpy.savez_compressed().
+    Deflated,
+}
+
+/// A tensor read from an NPZ archive, of any of the element types of NpyElement.
+#[derive(Clone, Debug)]
+pub enum NpzTensor {
+    F64(Tensor<f64>),
+    F32(Tensor<f32>),
+    I64(Tensor<i64>),
+    I32(Tensor<i32>),
+    U8(Tensor<u8>),
+    C64(Tensor<Complex<f64>>),
+}
+
+impl NpzTensor {
+    /// The dtype of the member without its byte order, like "f8", see NpyElement::DTYPE.
+    pub fn dtype(&self) -> &'static str {
+        match self {
+            NpzTensor::F64(_) => f64::DTYPE,
+            NpzTensor::F32(_) => f32::DTYPE,
+            NpzTensor::I64(_) => i64::DTYPE,
+            NpzTensor::I32(_) => i32::DTYPE,
+            NpzTensor::U8(_) => u8::DTYPE,
+            NpzTensor::C64(_) => Complex::<f64>::DTYPE,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<&Tensor<f64>> {
+        match self {
+            NpzTensor::F64(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<&Tensor<f32>> {
+        match self {
+            NpzTensor::F32(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<&Tensor<i64>> {
+        match self {
+            NpzTensor::I64(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<&Tensor<i32>> {
+        match self {
+            NpzTensor::I32(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn as_u8(&self) -> Option<&Tensor<u8>> {
+        match self {
+            NpzTensor::U8(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn as_c64(&self) -> Option<&Tensor<Complex<f64>>> {
+        match self {
+            NpzTensor::C64(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+impl Dimension for NpzTensor {
+    fn layout(&self) -> &Layout {
+        match self {
+            NpzTensor::F64(t) => t.layout(),
+            NpzTensor::F32(t) => t.layout(),
+            NpzTensor::I64(t) => t.layout(),
+            NpzTensor::I32(t) => t.layout(),
+            NpzTensor::U8(t) => t.layout(),
+            NpzTensor::C64(t) => t.layout(),
+        }
+    }
+}
+
+/// A member of an archive load_npz() did not read, and why, like UnsupportedDtype for
+/// the dtypes that are not one of NpyElement.
+#[derive(Debug)]
+pub struct SkippedMember {
+    pub name: String,
+    pub reason: IoError,
+}
+
+/// Writes an NPZ archive a tensor at a time, for tensors of different element types or
+/// compressed members. save_npz() writes the ones of a single type.
+///
+/// Each tensor is the member of its name followed by ".npy", which NumPy strips when it
+/// loads the archive, so the names round-trip whatever they end with.
+pub struct NpzWriter {
+    zip: ZipWriter<File>,
+    compression: NpzCompression,
+    names: HashSet<String>,
+}
+
+impl NpzWriter {
+    /// Creates the archive at `path`, replacing the file there.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, IoError> {
+        Ok(Self {
+            zip: ZipWriter::new(File::create(path)?),
+            compression: NpzCompression::default(),
+            names: HashSet::new(),
+        })
+    }
+
+    /// How the tensors added from now on are stored, Stored by default.
+    pub fn with_compression(mut self, compression: NpzCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Writes `tensor` in C order as the member `name`. Fails with DuplicateMember if
+    /// a tensor of the same name was already added.
+    pub fn add<T: NpyElement>(&mut self, name: &str, tensor: &Tensor<T>) -> Result<(), IoError> {
+        if !self.names.insert(name.into()) {
+            return Err(IoError::DuplicateMember(name.into()));
+        }
+
+        let header = Header::write::<T>(tensor.shape());
+        let size = (header.len() + tensor.len() * size_of::<T>()) as u64;
+        let options = SimpleFileOptions::default()
+            .compression_method(match self.compression {
+                NpzCompression::Stored => CompressionMethod::Stored,
+                NpzCompression::Deflated => CompressionMethod::Deflated,
+            })
+            .large_file(size >= u32::MAX as u64);
+
+        (self.zip)
+            .start_file(format!("{}{}", name, SUFFIX), options)
+            .map_err(from_zip)?;
+        self.zip.write_all(&header)?;
+
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+        for value in tensor.iter() {
+            // NpyElement types have no padding, every byte of them is initialized.
+            let bytes = unsafe {
+                std::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>())
+            };
+            chunk.extend_from_slice(bytes);
+
+            if chunk.len() + size_of::<T>() > CHUNK_SIZE {
+                self.zip.write_all(&chunk)?;
+                chunk.clear();
+            }
+        }
+        self.zip.write_all(&chunk)?;
+
+        Ok(())
+    }
+
+    /// Writes the directory of the archive. Dropping the writer writes it too, but
+    /// ignores the errors.
+    pub fn finish(self) -> Result<(), IoError> {
+        self.zip.finish().map_err(from_zip)?.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Writes `entries` to an NPZ archive at `path` that numpy.load() reads, each tensor with
+/// its name, uncompressed. Fails with DuplicateMember before creating the file if a name
+/// is given twice. See NpzWriter for compressed archives or tensors of several types.
+pub fn save_npz<T: NpyElement>(
+    path: impl AsRef<Path>,
+    entries: &[(&str, &Tensor<T>)],
+) -> Result<(), IoError> {
+    let mut names = HashSet::new();
+    if let Some((name, _)) = entries.iter().find(|(name, _)| !names.insert(*name)) {
+        return Err(IoError::DuplicateMember((*name).into()));
+    }
+
+    let mut writer = NpzWriter::create(path)?;
+    for (name, tensor) in entries {
+        writer.add(name, tensor)?;
+    }
+
+    writer.finish()
+}
+
+/// Reads every tensor of the NPZ archive at `path`, by the name NumPy gives it, along with
+/// the members it skipped: the ones that are not NPY files, the arrays without axes, and the
+/// ones of a dtype that is not one of NpyElement or not in the byte order of this machine.
+///
+/// The members are read one at a time straight into their tensors. Arrays in Fortran order
+/// are read as transposed views, and the other errors fail the whole load with InMember,
+/// naming the member.
+pub fn load_npz(
+    path: impl AsRef<Path>,
+) -> Result<(HashMap<String, NpzTensor>, Vec<SkippedMember>), IoError> {
+    let file = BufReader::new(File::open(path)?);
+    let mut archive = ZipArchive::new(file).map_err(from_zip)?;
+
+    let mut tensors = HashMap::new();
+    let mut skipped = Vec::new();
+
+    for index in 0..archive.len() {
+        let mut member = archive.by_index(index).map_err(from_zip)?;
+        let name = member.name().to_string();
+
+        let Some(key) = name.strip_suffix(SUFFIX) else {
+            skipped.push(SkippedMember {
+                name,
+                reason: IoError::NotNpy,
+            });
+            continue;
+        };
+
+        let size = member.size();
+        match read_member(&mut member, size) {
+            Ok(Member::Read(tensor)) => {
+                tensors.insert(key.to_string(), tensor);
+            }
+            Ok(Member::Skipped(reason)) => skipped.push(SkippedMember { name, reason }),
+            Err(err) => return Err(IoError::InMember(name.into(), Box::new(err))),
+        }
+    }
+
+    Ok((tensors, skipped))
+}
+
+// A valid NPY member that is read or skipped.
+enum Member {
+    Read(NpzTensor),
+    Skipped(IoError),
+}
+
+// The tensor of the NPY member `reader`, of `size` bytes.
+fn read_member(reader: &mut impl Read, size: u64) -> Result<Member, IoError> {
+    let header = Header::read(reader)?;
+
+    // Like the scalars NumPy saves, which tensors cannot hold.
+    if header.shape.is_empty() {
+        let reason = "arrays without axes are not supported";
+        return Ok(Member::Skipped(IoError::InvalidHeader(reason.into())));
+    }
+
+    let tensor = if header.reads_as::<f64>() {
+        NpzTensor::F64(read_elements(reader, &header, size)?)
+    } else if header.reads_as::<f32>() {
+        NpzTensor::F32(read_elements(reader, &header, size)?)
+    } else if header.reads_as::<i64>() {
+        NpzTensor::I64(read_elements(reader, &header, size)?)
+    } else if header.reads_as::<i32>() {
+        NpzTensor::I32(read_elements(reader, &header, size)?)
+    } else if header.reads_as::<u8>() {
+        NpzTensor::U8(read_elements(reader, &header, size)?)
+    } else if header.reads_as::<Complex<f64>>() {
+        NpzTensor::C64(read_elements(reader, &header, size)?)
+    } else {
+        let reason = IoError::UnsupportedDtype(header.descr.into());
+        return Ok(Member::Skipped(reason));
+    };
+
+    // The checksum of the member is only checked once it is read to its end.
+    io::copy(reader, &mut io::sink())?;
+
+    Ok(Member::Read(tensor))
+}
+
+fn read_elements<T: NpyElement>(
+    reader: &mut impl Read,
+    header: &Header,
+    size: u64,
+) -> Result<Tensor<T>, IoError> {
+    let len = header.len(size_of::<T>())?;
+
+    // Checked before allocating, the shape may not be the one of the data.
+    let expected_size = (header.start + len * size_of::<T>()) as u64;
+    if size < expected_size {
+        return Err(IoError::Truncated(expected_size, size));
+    }
+
+    let mut elements: Vec<T> = Vec::with_capacity(len);
+    // Every bit pattern is a valid T, so the elements can be zeroed and then read over.
+    let bytes = unsafe {
+        std::ptr::write_bytes(elements.as_mut_ptr(), 0, len);
+        elements.set_len(len);
+        std::slice::from_raw_parts_mut(elements.as_mut_ptr() as *mut u8, len * size_of::<T>())
+    };
+    reader.read_exact(bytes)?;
+
+    if !header.fortran_order {
+        return Ok(Tensor::from_vec(elements, &header.shape));
+    }
+
+    // The axes are stored in reverse order.
+    let reversed: Vec<usize> = header.shape.iter().rev().copied().collect();
+    let data = TensorData::from_vec(elements, &reversed, 0);
+
+    Ok(Tensor::from_data(data.as_layout(data.layout().transpose())))
+}
+
+fn from_zip(err: ZipError) -> IoError {
+    match err {
+        ZipError::Io(err) => IoError::Io(err),
+        err => IoError::InvalidArchive(err.to_string().into()),
+    }
+}
@@ -0,0 +1,295 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicUsize, Ordering};
+
+/// How accurate the elementwise kernels are, trading the last bits of the results for
+/// speed. They are the accuracy modes of MKL's VML, and only the `mkl` backend uses them:
+/// the other backends always compute the IEEE results and ignore the mode.
+///
+/// Per VML's documentation, HighAccuracy stays below 1 ulp, LowAccuracy below 4 ulps,
+/// and EnhancedPerformance keeps about half of the bits of the mantissa.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NumericsMode {
+    #[default]
+    HighAccuracy,
+    LowAccuracy,
+    EnhancedPerformance,
+}
+
+impl NumericsMode {
+    #[inline]
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => NumericsMode::LowAccuracy,
+            2 => NumericsMode::EnhancedPerformance,
+            _ => NumericsMode::HighAccuracy,
+        }
+    }
+
+    #[inline]
+    fn as_u8(self) -> u8 {
+        match self {
+            NumericsMode::HighAccuracy => 0,
+            NumericsMode::LowAccuracy => 1,
+            NumericsMode::EnhancedPerformance => 2,
+        }
+    }
+
+    // VML_HA, VML_LA and VML_EP, as passed to the vm* variants of the VML functions.
+    #[cfg(feature = "mkl")]
+    #[inline]
+    pub(crate) fn vml_mode(self) -> i64 {
+        match self {
+            NumericsMode::HighAccuracy => 0x2,
+            NumericsMode::LowAccuracy => 0x1,
+            NumericsMode::EnhancedPerformance => 0x3,
+        }
+    }
+}
+
+static NUMERICS_MODE: AtomicU8 = AtomicU8::new(0);
+static FLUSH_DENORMALS: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // Mode of the innermost NumericsScope of this thread, if any.
+    static SCOPED_MODE: Cell<Option<NumericsMode>> = const { Cell::new(None) };
+}
+
+/// Sets the mode of every thread, used by the promises materialized without one of their
+/// own, see TensorPromise::with_numerics(). HighAccuracy by default.
+pub fn set_numerics_mode(mode: NumericsMode) {
+    NUMERICS_MODE.store(mode.as_u8(), Ordering::Relaxed);
+}
+
+/// The mode the kernels of the current thread run with: the one of the innermost
+/// NumericsScope, like the one of a promise being materialized, or the global one.
+pub fn numerics_mode() -> NumericsMode {
+    SCOPED_MODE
+        .get()
+        .unwrap_or_else(|| NumericsMode::from_u8(NUMERICS_MODE.load(Ordering::Relaxed)))
+}
+
+/// Overrides the numerics mode of the current thread until it is dropped, restoring the
+/// previous one even if it is dropped by a panic. Promises with a mode of their own
+/// enter one while they are materialized.
+///
+/// VML is given the mode on every call, through the vm* variants of its functions, so
+/// the thread-wide mode of MKL (vmlSetMode) is never changed and a scope costs nothing.
+pub struct NumericsScope {
+    previous: Option<NumericsMode>,
+}
+
+impl NumericsScope {
+    pub fn enter(mode: NumericsMode) -> Self {
+        Self {
+            previous: SCOPED_MODE.replace(Some(mode)),
+        }
+    }
+}
+
+impl Drop for NumericsScope {
+    fn drop(&mut self) {
+        SCOPED_MODE.set(self.previous);
+    }
+}
+
+/// How the reductions add up their elements, see the documentation of the summation
+/// in `ops/kernels.rs` for the error of each one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Summation {
+    /// Blocks of set_pairwise_block() elements are added in order, and the sums of the
+    /// blocks are added in pairs, like numpy.sum.
+    #[default]
+    Pairwise,
+    /// One element at a time, carrying the rounding error of every addition along, which
+    /// keeps sums of large cancelling terms exact. About twice as slow.
+    Kahan,
+}
+
+/// Elements added in order before the pairwise summation adds the blocks in pairs.
+pub const DEFAULT_PAIRWISE_BLOCK: usize = 128;
+
+static SUMMATION: AtomicU8 = AtomicU8::new(0);
+static PAIRWISE_BLOCK: AtomicUsize = AtomicUsize::new(DEFAULT_PAIRWISE_BLOCK);
+
+/// Sets how every reduction adds up its elements, Summation::Pairwise by default.
+pub fn set_summation(summation: Summation) {
+    SUMMATION.store(summation as u8, Ordering::Relaxed);
+}
+
+pub fn summation() -> Summation {
+    match SUMMATION.load(Ordering::Relaxed) {
+        1 => Summation::Kahan,
+        _ => Summation::Pairwise,
+    }
+}
+
+/// Sets the length of the blocks of the pairwise summation, DEFAULT_PAIRWISE_BLOCK by
+/// default. Sums are the same bit for bit as long as it does not change. 0 is taken as 1.
+pub fn set_pairwise_block(len: usize) {
+    PAIRWISE_BLOCK.store(len.max(1), Ordering::Relaxed);
+}
+
+pub fn pairwise_block() -> usize {
+    PAIRWISE_BLOCK.load(Ordering::Relaxed)
+}
+
+/// What a Mul over an operand hinted to be mostly zeros, see Tensor::with_density_hint(),
+/// makes of its zeros. Either way the nonzero elements are found first, and only their
+/// positions are multiplied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SparseZeros {
+    /// Zero times inf or NaN is NaN, as in IEEE 754, and zeros keep the sign of the
+    /// product, so the result is the one of the dense product bit for bit. The other
+    /// operand is read whole first, and the product is computed densely unless all of
+    /// its elements are finite and non-negative, zero times those being +0.0.
+    #[default]
+    Ieee,
+    /// A zero makes a zero whatever it multiplies, so only the positions of the nonzero
+    /// elements are read from the other operand. Unlike IEEE 754, zero times inf or NaN
+    /// is then 0 rather than NaN, and every zero is positive.
+    Absorbing,
+}
+
+/// Density hint below which a Mul only computes the nonzero elements of its operand.
+pub const DEFAULT_SPARSE_THRESHOLD: f32 = 0.1;
+
+static SPARSE_ZEROS: AtomicU8 = AtomicU8::new(0);
+static SPARSE_THRESHOLD: AtomicU32 = AtomicU32::new(DEFAULT_SPARSE_THRESHOLD.to_bits());
+
+/// Sets what every sparse Mul makes of its zeros, SparseZeros::Ieee by default.
+pub fn set_sparse_zeros(zeros: SparseZeros) {
+    SPARSE_ZEROS.store(zeros as u8, Ordering::Relaxed);
+}
+
+pub fn sparse_zeros() -> SparseZeros {
+    match SPARSE_ZEROS.load(Ordering::Relaxed) {
+        1 => SparseZeros::Absorbing,
+        _ => SparseZeros::Ieee,
+    }
+}
+
+/// Sets the density hint below which a Mul only computes the nonzero elements of its
+/// operand, DEFAULT_SPARSE_THRESHOLD by default. 0 computes every Mul densely.
+pub fn set_sparse_threshold(density: f32) {
+    SPARSE_THRESHOLD.store(density.to_bits(), Ordering::Relaxed);
+}
+
+pub fn sparse_threshold() -> f32 {
+    f32::from_bits(SPARSE_THRESHOLD.load(Ordering::Relaxed))
+}
+
+// Runs `f` in the scope of `mode`, or of the current mode if None.
+#[inline]
+pub(crate) fn scoped<R>(mode: Option<NumericsMode>, f: impl FnOnce() -> R) -> R {
+    let _scope = mode.map(NumericsScope::enter);
+    f()
+}
+
+/// Makes every kernel flush the subnormal numbers it reads and produces to zero, which
+/// avoids the slow path most CPUs take on them. Off by default.
+/// Only x86_64 and aarch64 are supported, elsewhere it does nothing.
+pub fn set_flush_denormals(flush: bool) {
+    FLUSH_DENORMALS.store(flush, Ordering::Relaxed);
+}
+
+/// Whether the kernels flush subnormal numbers to zero, see set_flush_denormals().
+pub fn flush_denormals() -> bool {
+    FLUSH_DENORMALS.load(Ordering::Relaxed)
+}
+
+/// Whether subnormal numbers are currently flushed to zero on this thread, as read from
+/// the floating point control register. Always false on unsupported architectures.
+pub fn denormals_are_flushed() -> bool {
+    fp_control::read() & fp_control::FLUSH_BITS == fp_control::FLUSH_BITS
+}
+
+/// Flushes subnormal numbers to zero on the current thread until it is dropped,
+/// restoring the floating point control register even if it is dropped by a panic.
+/// Kernels are computed under one when set_flush_denormals(true) is set, at the cost
+/// of writing the register twice per kernel.
+pub struct FlushDenormals {
+    previous: fp_control::Register,
+}
+
+impl FlushDenormals {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let previous = fp_control::read();
+        fp_control::write(previous | fp_control::FLUSH_BITS);
+
+        Self { previous }
+    }
+}
+
+impl Drop for FlushDenormals {
+    fn drop(&mut self) {
+        fp_control::write(self.previous);
+    }
+}
+
+// The guard kernels are computed under, if denormals are to be flushed.
+#[inline]
+pub(crate) fn kernel_guard() -> Option<FlushDenormals> {
+    flush_denormals().then(FlushDenormals::new)
+}
+
+// The register holding the flush to zero bits. The compiler assumes the default floating
+// point environment, so it is only changed around the kernels and always restored.
+#[cfg(target_arch = "x86_64")]
+mod fp_control {
+    use std::arch::asm;
+
+    pub(super) type Register = u32;
+
+    // MXCSR: flush to zero (FTZ) for the results, denormals are zero (DAZ) for the operands.
+    pub(super) const FLUSH_BITS: Register = (1 << 15) | (1 << 6);
+
+    #[inline]
+    pub(super) fn read() -> Register {
+        let mut csr: Register = 0;
+        unsafe { asm!("stmxcsr [{}]", in(reg) &mut csr, options(nostack, preserves_flags)) };
+        csr
+    }
+
+    #[inline]
+    pub(super) fn write(csr: Register) {
+        unsafe { asm!("ldmxcsr [{}]", in(reg) &csr, options(nostack, preserves_flags, readonly)) };
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod fp_control {
+    use std::arch::asm;
+
+    pub(super) type Register = u64;
+
+    // FPCR.FZ flushes both the operands and the results.
+    pub(super) const FLUSH_BITS: Register = 1 << 24;
+
+    #[inline]
+    pub(super) fn read() -> Register {
+        let fpcr: Register;
+        unsafe { asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack, preserves_flags)) };
+        fpcr
+    }
+
+    #[inline]
+    pub(super) fn write(fpcr: Register) {
+        unsafe { asm!("msr fpcr, {}", in(reg) fpcr, options(nomem, nostack, preserves_flags)) };
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod fp_control {
+    pub(super) type Register = u8;
+
+    pub(super) const FLUSH_BITS: Register = 1;
+
+    #[inline]
+    pub(super) fn read() -> Register {
+        0
+    }
+
+    #[inline]
+    pub(super) fn write(_: Register) {}
+}
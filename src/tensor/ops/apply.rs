@@ -0,0 +1,73 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::tensor::PACKING_BUFFER_SIZE;
+
+/// Options of an Apply op, see TensorPromise::apply_with().
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ApplyOptions {
+    /// Whether the closure always writes the same elements for the same inputs and has no
+    /// side effects, so fold_constants() may call it ahead of time over constant inputs.
+    pub pure: bool,
+}
+
+type ChunkFn<T> = dyn Fn(&[T], &mut [T]) + Send + Sync;
+
+/// The closure an Apply op calls, see TensorPromise::apply().
+///
+/// It is called with the input elements and the slice to write their results into, both
+/// of the same length, at most PACKING_BUFFER_SIZE elements at a time.
+#[derive(Clone)]
+pub struct ApplyFn<T> {
+    f: Arc<ChunkFn<T>>,
+    // Type of the closure given by the user, shown instead of the closure itself.
+    name: &'static str,
+}
+
+impl<T: Copy + 'static> ApplyFn<T> {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: Fn(&[T], &mut [T]) + Send + Sync + 'static,
+    {
+        Self {
+            f: Arc::new(f),
+            name: std::any::type_name::<F>(),
+        }
+    }
+
+    // Adapts a closure over single elements, which is inlined within the loop over
+    // the chunk so there is still a single dynamic call per chunk.
+    pub(crate) fn from_scalar<F>(f: F) -> Self
+    where
+        F: Fn(T) -> T + Send + Sync + 'static,
+    {
+        Self {
+            f: Arc::new(move |input: &[T], output: &mut [T]| {
+                for (out, &el) in output.iter_mut().zip(input) {
+                    *out = f(el);
+                }
+            }),
+            name: std::any::type_name::<F>(),
+        }
+    }
+}
+
+impl<T: Copy + Default> ApplyFn<T> {
+    // Overwrites every element of `elements` with its result, a chunk at a time.
+    pub(crate) fn apply_in_place(&self, elements: &mut [T]) {
+        let mut packed = [T::default(); PACKING_BUFFER_SIZE];
+
+        for chunk in elements.chunks_mut(PACKING_BUFFER_SIZE) {
+            let input = &mut packed[..chunk.len()];
+            input.copy_from_slice(chunk);
+
+            (self.f)(input, chunk);
+        }
+    }
+}
+
+impl<T> Debug for ApplyFn<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ApplyFn({})", self.name)
+    }
+}
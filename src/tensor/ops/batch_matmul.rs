@@ -0,0 +1,150 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::tensor::backend::{Backend, Gemm, GemmBackend, GemmOperand};
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::storage::TensorData;
+use crate::tensor::traits::Dimension;
+
+static STRIDED_BATCH_GEMM: AtomicBool = AtomicBool::new(true);
+
+/// Whether the batch matrix multiplies whose operands hold their matrices back to back
+/// are computed in a single strided-batch GEMM, see BatchProduct::bmm(). Disabled, every
+/// matrix of the batch is a GEMM of its own, like with any other layout.
+/// Enabled by default.
+pub fn set_strided_batch_gemm(enabled: bool) {
+    STRIDED_BATCH_GEMM.store(enabled, Ordering::Relaxed);
+}
+
+pub fn strided_batch_gemm() -> bool {
+    STRIDED_BATCH_GEMM.load(Ordering::Relaxed)
+}
+
+// An operand of shape [rows, cols] or [batch, rows, cols] as GEMM reads it.
+struct Operand {
+    data: TensorData<f64>,
+    gemm: GemmOperand,
+    // Distance between its matrices, 0 if it has a single one to broadcast.
+    batch_stride: isize,
+}
+
+impl Operand {
+    // Reads the matrices in place when either of their axes is contiguous, a transposed
+    // matrix being a transposed GEMM operand, and compacts them otherwise.
+    fn new(data: TensorData<f64>) -> Self {
+        if let Some(operand) = Self::in_place(&data) {
+            return Self { data, ..operand };
+        }
+
+        let data = data.as_contiguous();
+        let operand = Self::in_place(&data).expect("contiguous matrices are GEMM operands");
+
+        Self { data, ..operand }
+    }
+
+    fn in_place(data: &TensorData<f64>) -> Option<Self> {
+        let (shape, stride) = (data.shape(), data.stride());
+        let rank = shape.len();
+        let (rows, cols) = (shape[rank - 2], shape[rank - 1]);
+        let (row_stride, col_stride) = (stride[rank - 2] as isize, stride[rank - 1] as isize);
+
+        // The stride of an axis of length 1 is never followed, so it can be anything.
+        let steps_by = |len: usize, stride: isize, step: isize| len == 1 || stride == step;
+        let at_least =
+            |len: usize, stride: isize, min: usize| len == 1 || stride >= min.max(1) as isize;
+
+        let gemm = if steps_by(cols, col_stride, 1) && at_least(rows, row_stride, cols) {
+            GemmOperand {
+                trans: false,
+                ld: if rows == 1 { cols } else { row_stride as usize },
+                stride: 0,
+            }
+        } else if steps_by(rows, row_stride, 1) && at_least(cols, col_stride, rows) {
+            GemmOperand {
+                trans: true,
+                ld: if cols == 1 { rows } else { col_stride as usize },
+                stride: 0,
+            }
+        } else {
+            return None;
+        };
+
+        let batch_stride = if rank == 3 && shape[0] > 1 {
+            stride[0] as isize
+        } else {
+            0
+        };
+
+        Some(Self {
+            data: data.clone(),
+            gemm: GemmOperand {
+                ld: gemm.ld.max(1),
+                ..gemm
+            },
+            batch_stride,
+        })
+    }
+
+    // Whether the matrices are packed back to back, or a single one is broadcast,
+    // as the strided-batch GEMM expects them.
+    fn is_packed(&self) -> bool {
+        let shape = self.data.shape();
+        let (rows, cols) = (shape[shape.len() - 2], shape[shape.len() - 1]);
+        let row_len = if self.gemm.trans { rows } else { cols };
+
+        self.batch_stride == 0
+            || (self.batch_stride == (rows * cols) as isize && self.gemm.ld == row_len.max(1))
+    }
+
+    // The elements from the first one of the matrix at `index` of the batch on.
+    fn matrix(&self, index: usize) -> &[f64] {
+        let start = self.data.offset() as isize + index as isize * self.batch_stride;
+
+        &self.data.storage.as_slice()[start as usize..]
+    }
+}
+
+// Multiplies the matrices of the lhs by the ones of the rhs, a single matrix on either
+// side being used for the whole batch. The batch is a single strided-batch GEMM when both
+// operands are packed, see Operand::is_packed(), and a GEMM per matrix otherwise.
+pub(crate) fn cpu_compute_batch_matmul_f64(
+    output_layout: &Layout,
+    mut inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let shape = output_layout.shape();
+    let rank = shape.len();
+    let (m, n) = (shape[rank - 2], shape[rank - 1]);
+    let batch = if rank == 3 { shape[0] } else { 1 };
+
+    let rhs = inputs.pop().unwrap();
+    let lhs = inputs.pop().unwrap();
+    let k = lhs.shape()[lhs.shape().len() - 1];
+
+    let mut out = vec![0.0; output_layout.len()];
+
+    if out.is_empty() || k == 0 {
+        return TensorData::from_vec(out, shape, 0).mark_as_reusable();
+    }
+
+    let (lhs, rhs) = (Operand::new(lhs), Operand::new(rhs));
+    let mut gemm = Gemm {
+        m,
+        n,
+        k,
+        a: lhs.gemm,
+        b: rhs.gemm,
+        batch,
+    };
+
+    if strided_batch_gemm() && lhs.is_packed() && rhs.is_packed() {
+        gemm.a.stride = lhs.batch_stride as usize;
+        gemm.b.stride = rhs.batch_stride as usize;
+
+        Backend::gemm_batch_strided(&gemm, lhs.matrix(0), rhs.matrix(0), &mut out);
+    } else {
+        for (i, c) in out.chunks_exact_mut(m * n).enumerate() {
+            Backend::gemm(&gemm, lhs.matrix(i), rhs.matrix(i), c);
+        }
+    }
+
+    TensorData::from_vec(out, shape, 0).mark_as_reusable()
+}
@@ -0,0 +1,105 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::tensor::PACKING_BUFFER_SIZE;
+use crate::tensor::device::Device;
+use crate::tensor::iter::ChunkedSliceIter;
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::storage::TensorData;
+use crate::tensor::traits::{Dimension, StreamingIterator};
+
+// Reads the elements of a tensor of another type as T, in row-major order.
+trait ConvertingRead<T>: Send + Sync {
+    fn layout(&self) -> &Layout;
+
+    fn device(&self) -> Device;
+
+    fn source_type(&self) -> &'static str;
+
+    // Calls `f` with the converted elements, up to PACKING_BUFFER_SIZE at a time,
+    // and the row-major position of the first one.
+    fn for_each_packed(&self, f: &mut dyn FnMut(&[T], usize));
+}
+
+struct Converting<U: Copy>(TensorData<U>);
+
+// Converts within the packing buffer, so no converted copy of the whole tensor is made.
+fn pack<T: Copy + Default>(iter: impl Iterator<Item = T>, f: &mut dyn FnMut(&[T], usize)) {
+    let mut packed: ChunkedSliceIter<_, T, PACKING_BUFFER_SIZE> = ChunkedSliceIter::new(iter);
+
+    while let Some(chunk) = packed.next() {
+        f(chunk.packing_buffer, chunk.absolute_buffer_position);
+    }
+}
+
+impl<T, U> ConvertingRead<T> for Converting<U>
+where
+    T: Copy + Default + From<U>,
+    U: Copy + Send + Sync,
+{
+    #[inline]
+    fn layout(&self) -> &Layout {
+        self.0.layout()
+    }
+
+    #[inline]
+    fn device(&self) -> Device {
+        self.0.device()
+    }
+
+    #[inline]
+    fn source_type(&self) -> &'static str {
+        std::any::type_name::<U>()
+    }
+
+    fn for_each_packed(&self, f: &mut dyn FnMut(&[T], usize)) {
+        let data = &self.0;
+
+        if data.is_contiguous() {
+            let start = data.offset();
+            let elements = &data.storage.as_slice()[start..start + data.len()];
+
+            pack(elements.iter().map(|&el| T::from(el)), f);
+        } else {
+            pack(data.copied_iter().map(T::from), f);
+        }
+    }
+}
+
+/// The tensor a Cast op reads, of another element type than the graph, see Tensor::cast().
+#[derive(Clone)]
+pub struct CastSource<T>(Arc<dyn ConvertingRead<T>>);
+
+impl<T: Copy + Default> CastSource<T> {
+    pub(crate) fn new<U>(data: TensorData<U>) -> Self
+    where
+        T: From<U> + 'static,
+        U: Copy + Send + Sync + 'static,
+    {
+        Self(Arc::new(Converting(data)))
+    }
+
+    // See ConvertingRead::for_each_packed().
+    #[inline]
+    pub(crate) fn for_each_packed(&self, mut f: impl FnMut(&[T], usize)) {
+        self.0.for_each_packed(&mut f)
+    }
+}
+
+impl<T> CastSource<T> {
+    #[inline]
+    pub fn layout(&self) -> &Layout {
+        self.0.layout()
+    }
+
+    #[inline]
+    pub fn device(&self) -> Device {
+        self.0.device()
+    }
+}
+
+impl<T> Debug for CastSource<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CastSource({})", self.0.source_type())
+    }
+}
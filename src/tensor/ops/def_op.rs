@@ -1,4 +1,7 @@
 use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::ops::apply::{ApplyFn, ApplyOptions};
+use crate::tensor::ops::cast::CastSource;
+use crate::tensor::shape_check::ShapePattern;
 
 // TODO: Design some way to fuse arbitrary combinations of ops
 // without handling it at the runtime, because it would be annoying.
@@ -9,6 +12,131 @@ pub enum OpKindScalar<T: Copy> {
     Sub(T),
     Mul(T),
     Div(T),
+    /// `x * scale + shift`, what a chain of the ops above collapses into, see
+    /// set_max_fused_scalar_ops().
+    Affine(T, T),
+}
+
+// Marker of the scalar op a DynScalarOp performs. The scalar itself is only known
+// once its input is computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalarKind {
+    Sum,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    None,
+    Sum,
+    Mean,
+}
+
+// Boolean reductions, any nonzero element counts as true.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReduceKind {
+    Any,
+    All,
+}
+
+// Reductions over a sliding window along one axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RollKind {
+    Sum,
+    Mean,
+    Max,
+}
+
+// Elementwise minimum and maximum. -0.0 is smaller than 0.0 in all of them.
+// The plain ones return NaN if any operand is NaN, the Num ones ignore a NaN
+// operand, like fmin and fmax, and only return NaN if both are NaN.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinMaxKind {
+    Minimum,
+    Maximum,
+    MinimumNum,
+    MaximumNum,
+}
+
+/// How a quantile falling between two elements of the sorted lane is computed,
+/// like the `method` of numpy.quantile. `i` and `j` are the elements around it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuantileMethod {
+    /// `i + (j - i) * fraction`, where fraction is how far the quantile is from `i`.
+    #[default]
+    Linear,
+    Lower,
+    Higher,
+    /// The closest of `i` and `j`, the one at an even position on a tie.
+    Nearest,
+    Midpoint,
+}
+
+/// What quantiles do with the NaNs of a lane.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Any NaN makes every quantile of the lane NaN.
+    #[default]
+    Propagate,
+    /// NaNs are left out, only a lane without any other element produces NaN.
+    Omit,
+}
+
+// What a Random op draws for every element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RandomKind {
+    // Zeroes the element with the given probability, scales it by 1 / (1 - p) otherwise.
+    Dropout(f64),
+    // Within [0, 1), ignoring the value of the element.
+    Uniform,
+    // Standard normal, ignoring the value of the element.
+    Normal,
+}
+
+// The invariant an Assert op checks over its input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssertKind<T> {
+    Finite,
+    // Within [lo, hi], NaN never is.
+    InRange(T, T),
+    Shape(ShapePattern),
+}
+
+impl<T: std::fmt::Debug> AssertKind<T> {
+    // What is expected of the input, as in "expected every element to be finite".
+    pub(crate) fn condition(&self) -> String {
+        match self {
+            AssertKind::Finite => "every element to be finite".to_string(),
+            AssertKind::InRange(lo, hi) => {
+                format!("every element to be within [{:?}, {:?}]", lo, hi)
+            }
+            AssertKind::Shape(pattern) => format!("the shape to match {}", pattern),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryKind {
+    IsNan,
+    IsInf,
+    Sqrt,
+    // 1 / x
+    Recip,
+    // 1 / sqrt(x)
+    Rsqrt,
+    Exp,
+    // Natural logarithm.
+    Ln,
+    // exp(x) - 1, exact for tiny x.
+    Expm1,
+    // ln(1 + x), exact for tiny x.
+    Log1p,
+    Log2,
+    Log10,
+    // 2^x
+    Exp2,
 }
 
 #[derive(Clone, Debug)]
@@ -16,16 +144,82 @@ pub enum OpKind<T: Copy> {
     NoOp,
     ScalarOp(OpKindScalar<T>),
     FusedScalar(Box<[OpKindScalar<T>]>),
+    // Binary op where the input at the given index has a single element and is used as
+    // a scalar. The static scalar ops fused after it are applied in order.
+    DynScalarOp(ScalarKind, usize, Box<[OpKindScalar<T>]>),
     View(Layout),
     Slice(Layout),
     Transpose,
-    TransposeAxes(Layout),
+    TransposeAxes(Layout, Box<[usize]>),
+    Flip(Box<[usize]>),
+    // Repeats the whole tensor along each axis, like numpy.tile.
+    Tile(Box<[usize]>),
+    // Repeats every element of the axis the given number of times, like numpy.repeat.
+    Repeat(usize /* axis */, usize /* repeats */),
+    // Joins the inputs along the axis, the other axes of every input must match.
+    Concat {
+        axis: usize,
+        n_inputs: usize,
+    },
+    // Writes every input into its window of an output of the given shape, the elements
+    // outside every window being `fill`. See CompositeBuilder.
+    Composite {
+        shape: Box<[usize]>,
+        windows: Box<[Layout]>,
+        fill: T,
+    },
     Matmul,
+    // Outer product of two 1-D inputs, of shape [len_lhs, len_rhs].
+    Outer,
+    // Contracts the last axis of both inputs, like numpy.inner. The shape is the leading
+    // axes of the lhs followed by the leading axes of the rhs, or [1] for two vectors.
+    Inner,
+    // Products of the matrices of two [m, k] or [batch, m, k] inputs, of shape [batch, m, n],
+    // or [m, n] if neither has a batch. A single matrix, or a batch of 1, is used for the
+    // whole batch of the other input.
+    BatchMatMul,
     AsContiguous,
     Add,
     Sub,
     Mul,
     Div,
+    MSELoss(Reduction),
+    MAELoss(Reduction),
+    Normalize(usize, T),
+    // Reduces the given axis away, producing 0 or 1 in the element type.
+    Reduce(ReduceKind, usize),
+    Unary(UnaryKind),
+    Neg,
+    // Slides a window along the axis, which shrinks to len - window + 1.
+    Rolling(RollKind, usize /* window */, usize /* axis */),
+    // n-th discrete difference along the axis, which shrinks to len - n.
+    Diff(usize /* n */, usize /* axis */),
+    // Trapezoidal integral along the axis, removing it. The samples are either
+    // `dx` apart or at the positions given by a second, 1-D input.
+    Trapz(T /* dx */, usize /* axis */),
+    TrapzX(usize /* axis */),
+    MinMax(MinMaxKind),
+    MinMaxScalar(MinMaxKind, T),
+    // Quantiles of every lane along the axis, removing it, or of the whole tensor.
+    // Asking for more than one adds a leading axis with one entry per quantile.
+    Quantile(
+        Box<[f64]>,
+        Option<usize>, /* axis */
+        QuantileMethod,
+        NanPolicy,
+    ),
+    // The draw of the element at row-major position `i` only depends on the seed and on
+    // `counter + i`, the counter being nonzero for the chunks of a streamed graph.
+    Random(RandomKind, u64 /* seed */, usize /* counter */),
+    // Reads a tensor of another element type, converting every element. It has no inputs,
+    // and a binary op that is its only reader converts the elements as it reads them.
+    Cast(CastSource<T>),
+    // Passes its input through untouched, reporting it under the label if it breaks the
+    // invariant. Skipped when the assertions are disabled, see set_graph_assertions().
+    Assert(AssertKind<T>, Box<str> /* label */),
+    // Calls a closure of the user over chunks of the input. Nothing fuses with it, and it
+    // is only folded if the options say it is pure.
+    Apply(ApplyFn<T>, ApplyOptions),
 }
 
 impl<T: Copy> OpKind<T> {
@@ -34,16 +228,126 @@ impl<T: Copy> OpKind<T> {
             OpKind::NoOp => "NoOp",
             OpKind::ScalarOp(_) => "ScalarOp",
             OpKind::FusedScalar(_) => "FusedScalar",
+            OpKind::DynScalarOp(_, _, _) => "DynScalarOp",
             OpKind::View(_) => "View",
             OpKind::Slice(_) => "Slice",
             OpKind::Transpose => "Transpose",
-            OpKind::TransposeAxes(_) => "TransposeAxes",
+            OpKind::TransposeAxes(_, _) => "TransposeAxes",
+            OpKind::Flip(_) => "Flip",
+            OpKind::Tile(_) => "Tile",
+            OpKind::Repeat(_, _) => "Repeat",
+            OpKind::Concat { .. } => "Concat",
+            OpKind::Composite { .. } => "Composite",
             OpKind::Matmul => "Matmul",
+            OpKind::Outer => "Outer",
+            OpKind::Inner => "Inner",
+            OpKind::BatchMatMul => "BatchMatMul",
             OpKind::AsContiguous => "AsContiguous",
             OpKind::Add => "Add",
             OpKind::Sub => "Sub",
             OpKind::Mul => "Mul",
             OpKind::Div => "Div",
+            OpKind::MSELoss(_) => "MSELoss",
+            OpKind::MAELoss(_) => "MAELoss",
+            OpKind::Normalize(_, _) => "Normalize",
+            OpKind::Reduce(ReduceKind::Any, _) => "Any",
+            OpKind::Reduce(ReduceKind::All, _) => "All",
+            OpKind::Unary(UnaryKind::IsNan) => "IsNan",
+            OpKind::Unary(UnaryKind::IsInf) => "IsInf",
+            OpKind::Unary(UnaryKind::Sqrt) => "Sqrt",
+            OpKind::Unary(UnaryKind::Recip) => "Recip",
+            OpKind::Unary(UnaryKind::Rsqrt) => "Rsqrt",
+            OpKind::Unary(UnaryKind::Exp) => "Exp",
+            OpKind::Unary(UnaryKind::Ln) => "Ln",
+            OpKind::Unary(UnaryKind::Expm1) => "Expm1",
+            OpKind::Unary(UnaryKind::Log1p) => "Log1p",
+            OpKind::Unary(UnaryKind::Log2) => "Log2",
+            OpKind::Unary(UnaryKind::Log10) => "Log10",
+            OpKind::Unary(UnaryKind::Exp2) => "Exp2",
+            OpKind::Neg => "Neg",
+            OpKind::Rolling(RollKind::Sum, _, _) => "RollingSum",
+            OpKind::Rolling(RollKind::Mean, _, _) => "RollingMean",
+            OpKind::Rolling(RollKind::Max, _, _) => "RollingMax",
+            OpKind::Diff(_, _) => "Diff",
+            OpKind::Trapz(_, _) | OpKind::TrapzX(_) => "Trapz",
+            OpKind::MinMax(MinMaxKind::Minimum) => "Minimum",
+            OpKind::MinMax(MinMaxKind::Maximum) => "Maximum",
+            OpKind::MinMax(MinMaxKind::MinimumNum) => "MinimumNum",
+            OpKind::MinMax(MinMaxKind::MaximumNum) => "MaximumNum",
+            OpKind::MinMaxScalar(MinMaxKind::Minimum, _) => "MinimumScalar",
+            OpKind::MinMaxScalar(MinMaxKind::Maximum, _) => "MaximumScalar",
+            OpKind::MinMaxScalar(MinMaxKind::MinimumNum, _) => "MinimumNumScalar",
+            OpKind::MinMaxScalar(MinMaxKind::MaximumNum, _) => "MaximumNumScalar",
+            OpKind::Quantile(_, _, _, _) => "Quantile",
+            OpKind::Random(RandomKind::Dropout(_), _, _) => "Dropout",
+            OpKind::Random(RandomKind::Uniform, _, _) => "RandLike",
+            OpKind::Random(RandomKind::Normal, _, _) => "RandnLike",
+            OpKind::Cast(_) => "Cast",
+            OpKind::Assert(_, _) => "Assert",
+            OpKind::Apply(_, _) => "Apply",
+        }
+    }
+
+    /// Whether every output element only depends on the input elements at the same
+    /// position in row-major order, so the op can be computed over any range of them.
+    /// Views qualify since reshaping keeps that order, the scalar input of a
+    /// DynScalarOp is the only exception to the rule.
+    pub fn is_elementwise(&self) -> bool {
+        match self {
+            OpKind::NoOp
+            | OpKind::ScalarOp(_)
+            | OpKind::FusedScalar(_)
+            | OpKind::DynScalarOp(_, _, _)
+            | OpKind::View(_)
+            | OpKind::AsContiguous
+            | OpKind::Add
+            | OpKind::Sub
+            | OpKind::Mul
+            | OpKind::Div
+            | OpKind::MSELoss(Reduction::None)
+            | OpKind::MAELoss(Reduction::None)
+            | OpKind::Unary(_)
+            | OpKind::Neg
+            | OpKind::Random(_, _, _)
+            | OpKind::MinMax(_)
+            | OpKind::MinMaxScalar(_, _)
+            | OpKind::Apply(_, _) => true,
+            OpKind::Slice(_)
+            | OpKind::Transpose
+            | OpKind::TransposeAxes(_, _)
+            | OpKind::Flip(_)
+            | OpKind::Tile(_)
+            | OpKind::Repeat(_, _)
+            | OpKind::Concat { .. }
+            | OpKind::Composite { .. }
+            | OpKind::Matmul
+            | OpKind::Outer
+            | OpKind::Inner
+            | OpKind::BatchMatMul
+            | OpKind::MSELoss(_)
+            | OpKind::MAELoss(_)
+            | OpKind::Normalize(_, _)
+            | OpKind::Reduce(_, _)
+            | OpKind::Rolling(_, _, _)
+            | OpKind::Diff(_, _)
+            | OpKind::Trapz(_, _)
+            | OpKind::TrapzX(_)
+            | OpKind::Quantile(_, _, _, _)
+            // Its source is read whole, as it is not an input of the graph.
+            | OpKind::Cast(_)
+            // The first element breaking the invariant is found over the whole input.
+            | OpKind::Assert(_, _) => false,
+        }
+    }
+
+    // Whether constant inputs make a constant result, which fold_constants() may compute
+    // ahead of time. A Cast reads a tensor that is not one of its inputs, an Assert
+    // is checked when the graph is materialized and an Apply may have side effects.
+    pub(crate) fn is_foldable(&self) -> bool {
+        match self {
+            OpKind::Cast(_) | OpKind::Assert(_, _) => false,
+            OpKind::Apply(_, options) => options.pure,
+            _ => true,
         }
     }
 }
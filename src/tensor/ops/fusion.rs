@@ -2,7 +2,12 @@ use std::ops::{Add, Div, Mul, Neg, Sub};
 use std::str::Matches;
 
 use crate::tensor::definitions::NumberLike;
-use crate::tensor::graph::NodeKind;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::tensor::graph::{NodeKind, TensorGraphNode, get_id, graph_assertions};
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::metrics;
 use crate::tensor::ops::def_op::{OpKind, OpKindScalar};
 
 ///////////////////////////////////////////
@@ -15,6 +20,20 @@ pub(crate) struct Fusion<T: Copy> {
 
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn try_fuse<T: NumberLike>(op: OpKind<T>, inputs: Box<[NodeKind<T>]>) -> Fusion<T> {
+    // Disabled assertions are read through, so they do not keep the ops around them apart.
+    let inputs = if graph_assertions() {
+        inputs
+    } else {
+        inputs.iter().map(skip_asserts).collect()
+    };
+
+    if let OpKind::Concat { axis, .. } = op
+        && let Some(fusion) = fuse_adjacent_views(axis, &inputs)
+    {
+        metrics::record_fusions(1);
+        return fusion;
+    }
+
     let mut current_fusion: Fusion<T> = Fusion {
         op,
         inputs: inputs.clone(),
@@ -33,6 +52,7 @@ pub fn try_fuse<T: NumberLike>(op: OpKind<T>, inputs: Box<[NodeKind<T>]>) -> Fus
                 );
 
                 if let Some(f) = fused {
+                    metrics::record_fusions(1);
                     current_fusion = f;
                 }
             }
@@ -48,6 +68,7 @@ pub fn try_fuse<T: NumberLike>(op: OpKind<T>, inputs: Box<[NodeKind<T>]>) -> Fus
                 );
 
                 if let Some(f) = fused {
+                    metrics::record_fusions(1);
                     current_fusion = f;
                 }
             }
@@ -57,136 +78,249 @@ pub fn try_fuse<T: NumberLike>(op: OpKind<T>, inputs: Box<[NodeKind<T>]>) -> Fus
     current_fusion
 }
 
-fn fuse_sum_scalar<T: NumberLike>(
-    op1: &OpKindScalar<T>, // Parent
-    inputs1: &[NodeKind<T>],
-    op2: &OpKindScalar<T>, // Child
-) -> Fusion<T> {
-    let s1: T = match op1 {
-        OpKindScalar::Sum(scalar) => *scalar,
-        OpKindScalar::Sub(scalar) => -*scalar,
-        _ => unreachable!("no other op should appear here"),
-    };
+// The first input down from `node` that is not an assertion.
+fn skip_asserts<T: Copy>(node: &NodeKind<T>) -> NodeKind<T> {
+    let mut node = node;
+
+    while let NodeKind::Node(assert) = node
+        && let OpKind::Assert(_, _) = assert.op
+    {
+        node = &assert.inputs[0];
+    }
+
+    node.clone()
+}
 
-    let s2: T = match op2 {
-        OpKindScalar::Sum(scalar) => *scalar,
-        OpKindScalar::Sub(scalar) => -*scalar,
-        _ => unreachable!("no other op should appear here"),
+/// Longest list of scalar ops fused into a single node by default,
+/// see set_max_fused_scalar_ops().
+pub const DEFAULT_MAX_FUSED_SCALAR_OPS: usize = 16;
+
+static MAX_FUSED_SCALAR_OPS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_FUSED_SCALAR_OPS);
+
+/// Sets the longest list of scalar ops a single node applies one after the other.
+/// Chains of `+`, `-`, `*` and `/` by scalars collapse into a single `x * scale + shift`
+/// as they are built, so only the ones that cannot, like integer divisions that are not
+/// exact, keep a list. Past the limit, the next scalar op is a node of its own reading
+/// the list, so a loop appending ops never builds a single unbounded one.
+/// Defaults to DEFAULT_MAX_FUSED_SCALAR_OPS, and is at least 1.
+pub fn set_max_fused_scalar_ops(max: usize) {
+    MAX_FUSED_SCALAR_OPS.store(max.max(1), Ordering::Relaxed);
+}
+
+pub fn max_fused_scalar_ops() -> usize {
+    MAX_FUSED_SCALAR_OPS.load(Ordering::Relaxed)
+}
+
+// The 1 of the type of `divisor`, if it is a nonzero finite value.
+#[allow(clippy::eq_op)]
+fn one_from<T: NumberLike>(divisor: T) -> Option<T> {
+    if divisor == T::default() {
+        return None;
+    }
+
+    let one = divisor / divisor;
+
+    // Infinities and NaNs give a NaN
+    (one * divisor == divisor).then_some(one)
+}
+
+// Whether dividing `value` by `divisor` inside a collapsed chain gives what the chain
+// itself would. Types whose division truncates, like integers, need the division to be
+// exact. The ones whose division does not, like floats, always pass, the collapsed chain
+// only rounding differently, by a few ulps.
+fn divides_exactly<T: NumberLike>(value: T, divisor: T) -> bool {
+    let Some(one) = one_from(divisor) else {
+        return false;
     };
 
-    Fusion {
-        op: OpKind::ScalarOp(OpKindScalar::Sum(s1 + s2)),
-        inputs: inputs1.into(),
+    let truncates = one / (one + one) == T::default();
+
+    !truncates || (value / divisor) * divisor == value
+}
+
+// A scalar op as `x * scale + shift`, a missing scale being 1.
+type AffineParts<T> = (Option<T>, T);
+
+fn affine_parts<T: NumberLike>(op: &OpKindScalar<T>) -> Option<AffineParts<T>> {
+    match *op {
+        OpKindScalar::Sum(shift) => Some((None, shift)),
+        OpKindScalar::Sub(shift) => Some((None, -shift)),
+        OpKindScalar::Mul(scale) => Some((Some(scale), T::default())),
+        OpKindScalar::Div(divisor) => {
+            let one = one_from(divisor)?;
+            divides_exactly(one, divisor).then(|| (Some(one / divisor), T::default()))
+        }
+        OpKindScalar::Affine(scale, shift) => Some((Some(scale), shift)),
+    }
+}
+
+// The canonical op of `x * scale + shift`: a Sum without a scale or with a scale of 1,
+// a Mul without a shift, and an Affine otherwise.
+fn canonical_affine<T: NumberLike>((scale, shift): AffineParts<T>) -> OpKindScalar<T> {
+    let zero = T::default();
+
+    match scale {
+        Some(scale) if one_from(scale) == Some(scale) => OpKindScalar::Sum(shift),
+        None => OpKindScalar::Sum(shift),
+        Some(scale) if shift == zero => OpKindScalar::Mul(scale),
+        Some(scale) => OpKindScalar::Affine(scale, shift),
     }
 }
 
-fn fuse_mul_scalar<T: NumberLike>(
+// The single op computing `op1` then `op2`, if there is one.
+fn compose_scalars<T: NumberLike>(
     op1: &OpKindScalar<T>, // Parent
-    inputs1: &[NodeKind<T>],
     op2: &OpKindScalar<T>, // Child
-) -> Fusion<T> {
-    match op1 {
-        OpKindScalar::Mul(s1) => match op2 {
-            OpKindScalar::Mul(s2) => Fusion {
-                op: OpKind::ScalarOp(OpKindScalar::Mul(*s1 * *s2)),
-                inputs: inputs1.into(),
-            },
-            OpKindScalar::Div(s2) => Fusion {
-                op: OpKind::ScalarOp(OpKindScalar::Mul(*s1 / *s2)),
-                inputs: inputs1.into(),
-            },
-            _ => unreachable!("no other op should appear here"),
-        },
-        OpKindScalar::Div(s1) => match op2 {
-            OpKindScalar::Mul(s2) => Fusion {
-                op: OpKind::ScalarOp(OpKindScalar::Mul(*s2 / *s1)),
-                inputs: inputs1.into(),
-            },
-            OpKindScalar::Div(s2) => Fusion {
-                op: OpKind::ScalarOp(OpKindScalar::Div(*s1 * *s2)),
-                inputs: inputs1.into(),
-            },
-            _ => unreachable!("no other op should appear here"),
-        },
-        _ => unreachable!("no other op should appear here"),
+) -> Option<OpKindScalar<T>> {
+    match (op1, op2) {
+        // Kept as divisions, which also holds for truncating ones
+        (OpKindScalar::Div(d1), OpKindScalar::Div(d2)) => {
+            return Some(OpKindScalar::Div(*d1 * *d2));
+        }
+        (OpKindScalar::Mul(s), OpKindScalar::Div(d)) if divides_exactly(*s, *d) => {
+            return Some(OpKindScalar::Mul(*s / *d));
+        }
+        _ => {}
     }
+
+    let (scale, shift) = affine_parts(op1)?;
+
+    let parts = match *op2 {
+        OpKindScalar::Sum(c) => (scale, shift + c),
+        OpKindScalar::Sub(c) => (scale, shift - c),
+        OpKindScalar::Mul(c) => (Some(scale.map_or(c, |s| s * c)), shift * c),
+        OpKindScalar::Div(d) => {
+            let scale = match scale {
+                Some(scale) => scale,
+                None => one_from(d)?,
+            };
+
+            if !divides_exactly(scale, d) || !divides_exactly(shift, d) {
+                return None;
+            }
+
+            (Some(scale / d), shift / d)
+        }
+        OpKindScalar::Affine(t, c) => (Some(scale.map_or(t, |s| s * t)), shift * t + c),
+    };
+
+    Some(canonical_affine(parts))
 }
 
-#[inline]
-fn fuse_scalars_into_combination<T: NumberLike>(
+fn fuse_scalars<T: NumberLike>(
     op1: &OpKindScalar<T>,
     inputs1: &[NodeKind<T>],
     op2: &OpKindScalar<T>,
 ) -> Fusion<T> {
-    let ops = Box::new([op1.clone(), op2.clone()]);
+    let op = match compose_scalars(op1, op2) {
+        Some(op) => OpKind::ScalarOp(op),
+        None => OpKind::FusedScalar(Box::new([op1.clone(), op2.clone()])),
+    };
+
     Fusion {
-        op: OpKind::FusedScalar(ops),
+        op,
         inputs: inputs1.into(),
     }
 }
 
-fn fuse_scalars<T: NumberLike>(
-    op1: &OpKindScalar<T>,
-    inputs1: &[NodeKind<T>],
+// Appends `op2` to the list `ops`, collapsing the tail of the list as long as its last
+// two ops compose into one. None if the list would go over max_fused_scalar_ops().
+fn push_scalar<T: NumberLike>(
+    ops: &[OpKindScalar<T>],
     op2: &OpKindScalar<T>,
-) -> Fusion<T> {
-    match op1 {
-        OpKindScalar::Sum(_) => match op2 {
-            OpKindScalar::Sum(_) => fuse_sum_scalar(op1, inputs1, op2),
-            OpKindScalar::Sub(_) => fuse_sum_scalar(op1, inputs1, op2),
-            _ => fuse_scalars_into_combination(op1, inputs1, op2),
-        },
-        OpKindScalar::Sub(_) => match op2 {
-            OpKindScalar::Sum(_) => fuse_sum_scalar(op1, inputs1, op2),
-            OpKindScalar::Sub(_) => fuse_sum_scalar(op1, inputs1, op2),
-            _ => fuse_scalars_into_combination(op1, inputs1, op2),
-        },
-        OpKindScalar::Mul(_) => match op2 {
-            OpKindScalar::Mul(_) => fuse_mul_scalar(op1, inputs1, op2),
-            OpKindScalar::Div(_) => fuse_mul_scalar(op1, inputs1, op2),
-            _ => fuse_scalars_into_combination(op1, inputs1, op2),
-        },
-        OpKindScalar::Div(_) => match op2 {
-            OpKindScalar::Mul(_) => fuse_mul_scalar(op1, inputs1, op2),
-            OpKindScalar::Div(_) => fuse_mul_scalar(op1, inputs1, op2),
-            _ => fuse_scalars_into_combination(op1, inputs1, op2),
-        },
+) -> Option<Box<[OpKindScalar<T>]>> {
+    let mut ops = ops.to_vec();
+    ops.push(op2.clone());
+
+    while ops.len() >= 2 {
+        let last = ops.len() - 1;
+
+        let Some(op) = compose_scalars(&ops[last - 1], &ops[last]) else {
+            break;
+        };
+
+        ops.truncate(last - 1);
+        ops.push(op);
     }
+
+    (ops.len() <= max_fused_scalar_ops()).then(|| ops.into_boxed_slice())
 }
 
 fn fuse_scalar_combination<T: NumberLike>(
     ops: &[OpKindScalar<T>],
     inputs1: &[NodeKind<T>],
     op2: &OpKindScalar<T>,
-) -> Fusion<T> {
-    let tail = &ops[ops.len() - 1];
-    let fused = fuse_scalars(tail, inputs1, op2);
+) -> Option<Fusion<T>> {
+    let ops = push_scalar(ops, op2)?;
+
+    let op = match &*ops {
+        [op] => OpKind::ScalarOp(op.clone()),
+        _ => OpKind::FusedScalar(ops),
+    };
 
-    let op = fused.op;
-    let inputs = fused.inputs;
+    Some(Fusion {
+        op,
+        inputs: inputs1.into(),
+    })
+}
+
+// A concatenation of views or slices of the same tensor that sit next to each
+// other in its buffer along `axis` is a single slice of that tensor.
+fn fuse_adjacent_views<T: NumberLike>(axis: usize, inputs: &[NodeKind<T>]) -> Option<Fusion<T>> {
+    let mut base: Option<&NodeKind<T>> = None;
+    let mut layouts: Vec<&Layout> = Vec::with_capacity(inputs.len());
 
-    let new_ops = match op {
-        OpKind::FusedScalar(_) => {
-            let mut vec: Vec<OpKindScalar<T>> = Vec::with_capacity(ops.len() + 1);
-            vec.extend(ops[..ops.len() - 1].iter().cloned());
-            vec.push(op2.clone());
+    for input in inputs {
+        let NodeKind::Node(node) = input else {
+            return None;
+        };
 
-            vec.into_boxed_slice()
+        let (OpKind::View(layout) | OpKind::Slice(layout)) = &node.op else {
+            return None;
+        };
+
+        let edge @ NodeKind::Edge(_) = &node.inputs[0] else {
+            return None;
+        };
+
+        if base.is_some_and(|base| get_id(base) != get_id(edge)) {
+            return None;
         }
-        OpKind::ScalarOp(scalar_op) => {
-            let mut vec: Vec<OpKindScalar<T>> = Vec::with_capacity(ops.len());
-            vec.extend(ops[..ops.len() - 1].iter().cloned());
-            vec.push(scalar_op.clone());
 
-            vec.into_boxed_slice()
+        base = Some(edge);
+        layouts.push(layout);
+    }
+
+    let first = layouts.first()?;
+
+    if axis >= first.shape().len() {
+        return None;
+    }
+
+    let mut shape = first.shape().to_vec();
+    let mut next_offset = first.offset() as i64;
+
+    for layout in &layouts {
+        let adjacent = layout.stride() == first.stride()
+            && layout.shape().len() == shape.len()
+            && (layout.shape().iter().zip(first.shape()))
+                .enumerate()
+                .all(|(dim, (a, b))| dim == axis || a == b)
+            && layout.offset() as i64 == next_offset;
+
+        if !adjacent {
+            return None;
         }
-        _ => unreachable!("no other op should appear here"),
-    };
 
-    Fusion {
-        op: OpKind::FusedScalar(new_ops),
-        inputs,
+        next_offset += layout.shape()[axis] as i64 * layout.stride()[axis] as i64;
     }
+
+    shape[axis] = layouts.iter().map(|layout| layout.shape()[axis]).sum();
+
+    Some(Fusion {
+        op: OpKind::Slice(Layout::from_slice(&shape, first.stride(), first.offset())),
+        inputs: Box::new([base?.clone()]),
+    })
 }
 
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
@@ -206,7 +340,58 @@ where
             _ => None,
         },
         OpKind::FusedScalar(ops) => match op2 {
-            OpKind::ScalarOp(s2) => Some(fuse_scalar_combination(ops, inputs1, s2)),
+            OpKind::ScalarOp(s2) => fuse_scalar_combination(ops, inputs1, s2),
+            _ => None,
+        },
+        OpKind::DynScalarOp(kind, scalar_idx, tail) => match op2 {
+            OpKind::ScalarOp(s2) => {
+                let tail = push_scalar(tail, s2)?;
+
+                Some(Fusion {
+                    op: OpKind::DynScalarOp(*kind, *scalar_idx, tail),
+                    inputs: inputs1.into(),
+                })
+            }
+            _ => None,
+        },
+        // Scalar ops are moved below the repetition, so they run on the smaller input.
+        OpKind::Tile(_) | OpKind::Repeat(_, _) => match op2 {
+            OpKind::ScalarOp(_) => {
+                let scalar =
+                    unsafe { TensorGraphNode::new(op2.clone(), inputs1.into()).unwrap_unchecked() };
+
+                Some(Fusion {
+                    op: op1.clone(),
+                    inputs: Box::new([NodeKind::Node(Arc::new(scalar))]),
+                })
+            }
+            _ => None,
+        },
+        // A transpose undoes the one below it.
+        OpKind::Transpose => match op2 {
+            OpKind::Transpose => Some(Fusion {
+                op: OpKind::NoOp,
+                inputs: inputs1.into(),
+            }),
+            _ => None,
+        },
+        // Two permutations of the axes make a single one, which the layout of the child
+        // already describes, or none at all if they cancel out.
+        OpKind::TransposeAxes(_, axes1) => match op2 {
+            OpKind::TransposeAxes(layout, axes2) => {
+                let axes: Box<[usize]> = axes2.iter().map(|&axis| axes1[axis]).collect();
+
+                let op = if axes.iter().enumerate().all(|(i, &axis)| i == axis) {
+                    OpKind::NoOp
+                } else {
+                    OpKind::TransposeAxes(layout.clone(), axes)
+                };
+
+                Some(Fusion {
+                    op,
+                    inputs: inputs1.into(),
+                })
+            }
             _ => None,
         },
         OpKind::View(_) => match op2 {
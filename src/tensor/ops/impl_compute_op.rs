@@ -1,90 +1,231 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::branch_fast_iter;
+use crate::tensor::backend::{Backend, UnaryMathBackend, VectorMathBackend};
+use crate::tensor::complex::Complex;
 use crate::tensor::definitions::{ChunkedIter, NumberLike};
+use crate::tensor::device::Device;
+use crate::tensor::errors::AssertionFailure;
+use crate::tensor::graph;
 use crate::tensor::mem_formats::layout::Layout;
-use crate::tensor::ops::def_op::{OpKind, OpKindScalar};
-use crate::tensor::ops::reusable::{get_reusable_or_alloc, unordered_get_reusable_or_alloc_n};
+use crate::tensor::metrics::{self, BinaryRoute};
+use crate::tensor::numerics::{self, SparseZeros};
+use crate::tensor::ops::apply::ApplyFn;
+use crate::tensor::ops::batch_matmul::cpu_compute_batch_matmul_f64;
+use crate::tensor::ops::cast::CastSource;
+use crate::tensor::ops::def_op::{
+    AssertKind, MinMaxKind, NanPolicy, OpKind, OpKindScalar, QuantileMethod, RandomKind,
+    ReduceKind, Reduction, RollKind, ScalarKind, UnaryKind,
+};
+use crate::tensor::ops::kernels;
+use crate::tensor::ops::reusable::get_reusable_or_alloc;
 use crate::tensor::storage::{Storage, TensorData};
 use crate::tensor::traits::{Dimension, StreamingIterator};
-use cblas_sys::cblas_dgemm;
-use intel_mkl_sys::{vdAdd, vdDiv, vdMul, vdSub};
 
-// TODO: Add BLAS support for scalar ops using vdAddl and the like
-fn compute_scalar_op<T: NumberLike>(op: &OpKindScalar<T>, mut input: Vec<T>) -> Vec<T> {
+// TODO: Add BLAS support for the remaining scalar ops using vdAddl and the like
+fn compute_scalar_op<T: NumberLike>(op: &OpKindScalar<T>, input: &mut [T])
+where
+    Backend: VectorMathBackend<T>,
+{
     match op {
         OpKindScalar::Sum(scalar) => {
             for el in input.iter_mut() {
                 *el = *el + *scalar;
             }
-            input
         }
         OpKindScalar::Sub(scalar) => {
             for el in input.iter_mut() {
                 *el = *el - *scalar;
             }
-            input
         }
-        OpKindScalar::Mul(scalar) => {
+        OpKindScalar::Mul(scalar) => Backend::scal(*scalar, input),
+        OpKindScalar::Div(scalar) => {
             for el in input.iter_mut() {
-                *el = *el * *scalar;
+                *el = *el / *scalar;
             }
-            input
         }
-        OpKindScalar::Div(scalar) => {
+        // A single pass however long the chain it replaces
+        OpKindScalar::Affine(scale, shift) => {
             for el in input.iter_mut() {
-                *el = *el / *scalar;
+                *el = *el * *scale + *shift;
             }
+        }
+    }
+}
+
+static ADAPTIVE_PACKING: AtomicBool = AtomicBool::new(true);
+
+/// Whether Add, Sub and Mul read a second operand that repeats a single element as a scalar,
+/// and Add and Sub one whose elements are the same distance apart, like a column of
+/// a row-major matrix, with a strided BLAS axpy, instead of packing it into contiguous
+/// chunks first. Both round exactly like the packed path, see BinaryRoute for the
+/// counters. Enabled by default.
+pub fn set_adaptive_packing(enabled: bool) {
+    ADAPTIVE_PACKING.store(enabled, Ordering::Relaxed);
+}
+
+pub fn adaptive_packing() -> bool {
+    ADAPTIVE_PACKING.load(Ordering::Relaxed)
+}
+
+// A binary elementwise op. `operation` overwrites its first operand, and the other kernels
+// compute the same op reading the second operand without packing it, when its layout
+// allows. They round exactly like `operation`, or are None.
+struct BinaryKernels<T: Copy> {
+    operation: fn(&mut [T], &[T]),
+    commutative: bool,
+    // The op with the element a constant broadcast repeats, as a scalar op.
+    repeated: Option<fn(T) -> OpKindScalar<T>>,
+    // `alpha` of the axpy adding a strided operand.
+    strided: Option<T>,
+}
 
-            input
+impl<T: Copy> BinaryKernels<T> {
+    fn packed(operation: fn(&mut [T], &[T]), commutative: bool) -> Self {
+        Self {
+            operation,
+            commutative,
+            repeated: None,
+            strided: None,
         }
     }
 }
 
-fn compute_elementwise_tensor_tensor<T: Copy + Default>(
+// How `kernels` read a second operand of layout `rhs`.
+fn binary_route<T: Copy>(rhs: &Layout, kernels: &BinaryKernels<T>) -> BinaryRoute {
+    if rhs.is_contiguous() {
+        return BinaryRoute::Contiguous;
+    }
+
+    if !adaptive_packing() {
+        return BinaryRoute::Packed;
+    }
+
+    match rhs.single_stride() {
+        Some(0) if kernels.repeated.is_some() => BinaryRoute::Scalar,
+        Some(step) if step > 1 && kernels.strided.is_some() => BinaryRoute::Strided,
+        _ => BinaryRoute::Packed,
+    }
+}
+
+// The result is written over the lhs, or over the rhs of a commutative op
+// when only the rhs can be reused.
+fn compute_elementwise_tensor_tensor<T: NumberLike>(
     mut inputs: Vec<TensorData<T>>,
-    operation: unsafe extern "C" fn(i32, *const T, *const T, *mut T),
-) -> TensorData<T> {
+    kernels: BinaryKernels<T>,
+) -> TensorData<T>
+where
+    Backend: VectorMathBackend<T>,
+{
+    let is_reusable = |data: &TensorData<T>| data.reusable && data.is_contiguous();
+    if kernels.commutative && !is_reusable(&inputs[0]) && is_reusable(&inputs[1]) {
+        inputs.swap(0, 1);
+    }
+
     // TODO: This is a mess. It would be ideal if we can design the operations without having to
     // think about reusability and then plug it on some magic and it starts reusing tensors.
-    let mut output_data = unordered_get_reusable_or_alloc_n(&mut inputs, 0);
-
-    // Non-contiguous path
-    if !inputs[0].is_contiguous() {
-        // TODO: There's no need to pack the input. Maybe we should
-        // allocate a full buffer and then operate directly
-        let mut packed_iter: ChunkedIter<'_, T> = inputs[0].packed_iter();
-
-        while let Some(chunk) = packed_iter.next() {
-            let buffer_size: usize = chunk.packing_buffer.len();
-
-            unsafe {
-                operation(
-                    buffer_size as i32,
-                    output_data
-                        .v
-                        .as_ptr()
-                        .add(chunk.absolute_buffer_position + output_data.offset),
-                    chunk.packing_buffer.as_ptr(),
-                    output_data
-                        .v
-                        .as_mut_ptr()
-                        .add(chunk.absolute_buffer_position + output_data.offset),
-                )
-            }
-        }
-    // Contiguous path
-    } else {
-        let lhs_buffer = &inputs[0].storage.buffer;
-
-        unsafe {
-            operation(
-                (output_data.v.len() - output_data.offset) as i32,
-                output_data.v.as_ptr().add(output_data.offset),
-                lhs_buffer.as_ptr(),
-                output_data.v.as_mut_ptr().add(output_data.offset),
-            )
+    let lhs = inputs.remove(0);
+    let mut output_data = get_reusable_or_alloc(lhs);
+    let rhs = inputs.pop().unwrap();
+    let out = &mut output_data.v[output_data.offset..];
+
+    let route = binary_route(rhs.layout(), &kernels);
+    metrics::record_binary_route(route);
+
+    match route {
+        BinaryRoute::Contiguous => {
+            let rhs = rhs.contiguous_slice().expect("the operand is contiguous");
+            (kernels.operation)(out, &rhs[..out.len()]);
+        }
+        BinaryRoute::Scalar => {
+            let repeated = kernels.repeated.expect("the route has a kernel");
+            compute_scalar_op(&repeated(rhs.storage.as_slice()[rhs.offset()]), out);
+        }
+        BinaryRoute::Strided => {
+            let alpha = kernels.strided.expect("the route has a kernel");
+            let step = rhs
+                .layout()
+                .single_stride()
+                .expect("the operand is strided");
+            Backend::axpy_strided(
+                alpha,
+                &rhs.storage.as_slice()[rhs.offset()..],
+                step as usize,
+                out,
+            );
+        }
+        // Packing a transposed operand one element at a time is mostly cache misses,
+        // so it is compacted a strip of rows at a time with the blocked copy instead.
+        BinaryRoute::Packed if rhs.is_transposed_matrix() => {
+            rhs.for_each_transposed_strip(|start, strip| {
+                (kernels.operation)(&mut out[start..start + strip.len()], strip);
+            });
+        }
+        BinaryRoute::Packed => {
+            // TODO: There's no need to pack the input. Maybe we should
+            // allocate a full buffer and then operate directly
+            let mut packed_iter: ChunkedIter<'_, T> = rhs.packed_iter();
+
+            while let Some(chunk) = packed_iter.next() {
+                let start = chunk.absolute_buffer_position;
+                let end = start + chunk.packing_buffer.len();
+
+                (kernels.operation)(&mut out[start..end], chunk.packing_buffer);
+            }
         }
     }
 
-    TensorData::from_vec(output_data.v, inputs[0].shape(), output_data.offset).mark_as_reusable()
+    TensorData::from_vec(output_data.v, rhs.shape(), output_data.offset).mark_as_reusable()
+}
+
+// The elements of the source of a Cast, converted into a buffer of their own.
+fn cpu_compute_cast<T: Copy + Default>(source: &CastSource<T>) -> TensorData<T> {
+    let shape = source.layout().shape();
+    let mut v = vec![T::default(); source.layout().len()];
+
+    source.for_each_packed(|chunk, pos| v[pos..pos + chunk.len()].copy_from_slice(chunk));
+
+    TensorData::from_vec(v, shape, 0).mark_as_reusable()
+}
+
+// A binary op reading the cast at `cast_at` and `other`. The converted elements go straight
+// from the packing buffer into the result, which is written over `other` when possible.
+// Only a cast lhs of an op that is not commutative is converted whole, being the result.
+fn cpu_compute_binary_with_cast<T: NumberLike>(
+    op: &OpKind<T>,
+    other: TensorData<T>,
+    source: &CastSource<T>,
+    cast_at: usize,
+) -> TensorData<T>
+where
+    Backend: VectorMathBackend<T>,
+{
+    let operation: fn(&mut [T], &[T]) = match op {
+        OpKind::Add => Backend::add,
+        OpKind::Sub => Backend::sub,
+        OpKind::Mul => Backend::mul,
+        OpKind::Div => Backend::div,
+        _ => unreachable!("only binary ops read a fused cast"),
+    };
+    let commutative = matches!(op, OpKind::Add | OpKind::Mul);
+
+    if cast_at == 0 && !commutative {
+        return compute_elementwise_tensor_tensor(
+            vec![cpu_compute_cast(source), other],
+            BinaryKernels::packed(operation, false),
+        );
+    }
+
+    let shape: Box<[usize]> = other.shape().into();
+    let mut output_data = get_reusable_or_alloc(other);
+    let out = &mut output_data.v[output_data.offset..];
+
+    source.for_each_packed(|chunk, pos| operation(&mut out[pos..pos + chunk.len()], chunk));
+
+    TensorData::from_vec(output_data.v, &shape, output_data.offset).mark_as_reusable()
 }
 
 // TODO: Add custom kernel for non-contiguous tensors.
@@ -101,9 +242,7 @@ fn cpu_compute_matmul_f64(
     let a_stride_len = raw_a.stride().len();
     let b_stride_len = raw_b.stride().len();
 
-    let mut transa = cblas::Transpose::None;
     let mut is_a_trans = false;
-    let mut transb = cblas::Transpose::None;
     let mut is_b_trans = false;
 
     // Check whether the tensor is transposed between the last 2 axis
@@ -112,7 +251,6 @@ fn cpu_compute_matmul_f64(
         && raw_a.stride()[a_stride_len - 2] == 1
         && raw_a.stride()[a_stride_len - 1] as usize == raw_a.shape()[a_stride_len - 1]
     {
-        transa = cblas::Transpose::Ordinary;
         is_a_trans = true;
     }
 
@@ -120,7 +258,6 @@ fn cpu_compute_matmul_f64(
         && raw_b.stride()[b_stride_len - 2] == 1
         && raw_b.stride()[b_stride_len - 1] as usize == raw_b.shape()[b_stride_len - 1]
     {
-        transb = cblas::Transpose::Ordinary;
         is_b_trans = true;
     }
 
@@ -139,101 +276,1363 @@ fn cpu_compute_matmul_f64(
     TensorData::new(storage, output_layout.clone())
 }
 
-fn cpu_compute_elementwise_f64(
-    op: &OpKind<f64>,
+fn cpu_compute_elementwise<T: NumberLike>(
+    op: &OpKind<T>,
     output_layout: &Layout,
-    mut inputs: Vec<TensorData<f64>>,
-) -> TensorData<f64> {
+    mut inputs: Vec<TensorData<T>>,
+) -> TensorData<T>
+where
+    Backend: VectorMathBackend<T>,
+{
     let buffer = get_reusable_or_alloc(inputs.pop().unwrap());
+    let (mut v, offset) = (buffer.v, buffer.offset);
 
     match op {
-        OpKind::ScalarOp(op) => TensorData::from_vec(
-            compute_scalar_op(op, buffer.v),
-            output_layout.shape(),
-            buffer.offset,
-        ),
+        OpKind::ScalarOp(op) => compute_scalar_op(op, &mut v[offset..]),
         OpKind::FusedScalar(ops) => {
-            let offset = buffer.offset;
-            let mut buffer = buffer.v;
-
             for op in ops {
-                buffer = compute_scalar_op(op, buffer);
+                compute_scalar_op(op, &mut v[offset..]);
             }
-
-            TensorData::from_vec(buffer, output_layout.shape(), offset)
         }
         _ => unreachable!("no other op should appear here"),
     }
-    .mark_as_reusable()
+
+    TensorData::from_vec(v, output_layout.shape(), offset).mark_as_reusable()
 }
 
-#[cfg_attr(
-    feature = "tracing",
-    tracing::instrument(
-        level = "debug",
-        skip(inputs, output_layout),
-        fields(op = op.as_str(), out_len = output_layout.len())
-    )
-)]
-fn cpu_compute_op_f64(
+// Computes the whole loss in a single pass over both inputs, so the
+// intermediate (predictions - targets) tensor is never allocated.
+fn cpu_compute_loss_f64(
     op: &OpKind<f64>,
     output_layout: &Layout,
-    mut inputs: Vec<TensorData<f64>>,
+    inputs: Vec<TensorData<f64>>,
 ) -> TensorData<f64> {
-    match op {
-        OpKind::ScalarOp(_) | OpKind::FusedScalar(_) => {
-            cpu_compute_elementwise_f64(op, output_layout, inputs)
+    let (reduction, loss): (&Reduction, fn(f64) -> f64) = match op {
+        OpKind::MSELoss(reduction) => (reduction, |diff| diff * diff),
+        OpKind::MAELoss(reduction) => (reduction, f64::abs),
+        _ => unreachable!("no other op should appear here"),
+    };
+
+    let predictions = &inputs[0];
+    let targets = &inputs[1];
+
+    let errors = predictions
+        .copied_iter()
+        .zip(targets.copied_iter())
+        .map(|(p, t)| loss(p - t));
+
+    match reduction {
+        Reduction::None => TensorData::from_iter(errors, output_layout.shape()),
+        Reduction::Sum => {
+            TensorData::from_vec(vec![kernels::sum_iter(errors)], output_layout.shape(), 0)
         }
-        OpKind::Slice(new_layout)
-        | OpKind::View(new_layout)
-        | OpKind::TransposeAxes(new_layout) => inputs[0].as_layout(new_layout.clone()),
-        OpKind::AsContiguous => {
-            if inputs[0].is_contiguous() {
-                inputs[0].clone()
-            } else {
-                TensorData::from_iter(inputs[0].copied_iter(), inputs[0].shape()).mark_as_reusable()
+        Reduction::Mean => {
+            let total = kernels::sum_iter(errors);
+
+            TensorData::from_vec(
+                vec![total / predictions.len() as f64],
+                output_layout.shape(),
+                0,
+            )
+        }
+    }
+    .mark_as_reusable()
+}
+
+// Every lane along `axis` stops being read at the first element that decides it.
+// An empty lane is false for Any and true for All.
+fn cpu_compute_reduce_f64(
+    kind: ReduceKind,
+    axis: usize,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let input = &inputs[0];
+    let buffer = &input.storage.buffer;
+    let shape = input.shape();
+    let stride = input.stride();
+
+    let axis_len = shape[axis];
+    let axis_stride = stride[axis] as isize;
+
+    // Any looks for a nonzero element and All for a zero one.
+    let witness_is_nonzero = kind == ReduceKind::Any;
+
+    let mut out = vec![0.0; output_layout.len()];
+
+    for (lane, slot) in out.iter_mut().enumerate() {
+        let mut remaining = lane;
+        let mut pos = input.offset() as isize;
+
+        for dim in (0..shape.len()).rev() {
+            if dim == axis {
+                continue;
             }
+
+            pos += (remaining % shape[dim]) as isize * stride[dim] as isize;
+            remaining /= shape[dim];
         }
-        OpKind::Transpose => {
-            let layout = inputs[0].layout();
-            inputs[0].as_layout(layout.transpose())
+
+        let found = (0..axis_len).any(|k| {
+            (buffer[(pos + k as isize * axis_stride) as usize] != 0.0) == witness_is_nonzero
+        });
+
+        *slot = if found == witness_is_nonzero {
+            1.0
+        } else {
+            0.0
+        };
+    }
+
+    TensorData::from_vec(out, output_layout.shape(), 0).mark_as_reusable()
+}
+
+fn cpu_compute_unary_f64(
+    kind: UnaryKind,
+    output_layout: &Layout,
+    mut inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let mut buffer = get_reusable_or_alloc(inputs.pop().unwrap());
+    let len = output_layout.len();
+
+    // A reused buffer may start before the view, whose elements are the only ones computed.
+    let elements = &mut buffer.v[buffer.offset..];
+
+    // The masks know how many of their elements are set.
+    let density = match kind {
+        UnaryKind::IsNan => Some(indicate(elements, f64::is_nan) as f32 / len as f32),
+        UnaryKind::IsInf => Some(indicate(elements, f64::is_infinite) as f32 / len as f32),
+        UnaryKind::Sqrt => {
+            Backend::sqrt(elements);
+            None
         }
-        OpKind::Add => compute_elementwise_tensor_tensor(inputs, vdAdd),
-        OpKind::Sub => compute_elementwise_tensor_tensor(inputs, vdSub),
-        OpKind::Mul => compute_elementwise_tensor_tensor(inputs, vdMul),
-        OpKind::Div => compute_elementwise_tensor_tensor(inputs, vdDiv),
-        OpKind::NoOp => unsafe { inputs.pop().unwrap_unchecked() },
-        _ => todo!("not implemented"),
+        UnaryKind::Recip => {
+            Backend::inv(elements);
+            None
+        }
+        UnaryKind::Rsqrt => {
+            Backend::inv_sqrt(elements);
+            None
+        }
+        UnaryKind::Exp => {
+            Backend::exp(elements);
+            None
+        }
+        UnaryKind::Ln => {
+            Backend::ln(elements);
+            None
+        }
+        UnaryKind::Expm1 => {
+            Backend::expm1(elements);
+            None
+        }
+        UnaryKind::Log1p => {
+            Backend::log1p(elements);
+            None
+        }
+        UnaryKind::Log2 => {
+            Backend::log2(elements);
+            None
+        }
+        UnaryKind::Log10 => {
+            Backend::log10(elements);
+            None
+        }
+        UnaryKind::Exp2 => {
+            Backend::exp2(elements);
+            None
+        }
+    };
+
+    TensorData::from_vec(buffer.v, output_layout.shape(), buffer.offset)
+        .mark_as_reusable()
+        .with_density_hint(density)
+}
+
+// Replaces every element by 1 if it passes `predicate`, 0 otherwise, and counts the ones.
+fn indicate(buffer: &mut [f64], predicate: fn(f64) -> bool) -> usize {
+    let mut ones = 0;
+    for el in buffer.iter_mut() {
+        let passes = predicate(*el);
+        ones += passes as usize;
+        *el = if passes { 1.0 } else { 0.0 };
     }
+
+    ones
 }
 
-pub trait ComputeWrapperSpec
-where
-    Self: Copy,
-{
-    fn compute_for_type(
-        op: &OpKind<Self>,
-        output_layout: &Layout,
-        inputs: Vec<TensorData<Self>>,
-    ) -> TensorData<Self>;
+// `negate` flips the sign of every element of the slice in place.
+fn cpu_compute_neg<T: NumberLike>(
+    output_layout: &Layout,
+    mut inputs: Vec<TensorData<T>>,
+    negate: fn(&mut [T]),
+) -> TensorData<T> {
+    let mut buffer = get_reusable_or_alloc(inputs.pop().unwrap());
+    negate(&mut buffer.v[buffer.offset..]);
+
+    TensorData::from_vec(buffer.v, output_layout.shape(), buffer.offset).mark_as_reusable()
 }
 
-impl ComputeWrapperSpec for f64 {
-    #[inline]
-    fn compute_for_type(
-        op: &OpKind<f64>,
-        output_layout: &Layout,
-        inputs: Vec<TensorData<f64>>,
-    ) -> TensorData<f64> {
-        cpu_compute_op_f64(op, output_layout, inputs)
+// The results of `f` over the input, written over it when it can be reused.
+fn cpu_compute_apply<T: NumberLike>(
+    f: &ApplyFn<T>,
+    output_layout: &Layout,
+    mut inputs: Vec<TensorData<T>>,
+) -> TensorData<T> {
+    let mut buffer = get_reusable_or_alloc(inputs.pop().unwrap());
+    f.apply_in_place(&mut buffer.v[buffer.offset..]);
+
+    TensorData::from_vec(buffer.v, output_layout.shape(), buffer.offset).mark_as_reusable()
+}
+
+// Hands the input back as it is, the result shares its buffer. Unless the assertions are
+// disabled, the first element for which `breaks` holds, or a shape not matching the
+// pattern, is recorded to fail the materialization, see graph::checked().
+fn cpu_compute_assert<T: NumberLike>(
+    kind: &AssertKind<T>,
+    label: &str,
+    mut inputs: Vec<TensorData<T>>,
+    breaks: impl Fn(T) -> bool,
+) -> TensorData<T> {
+    let input = inputs.pop().unwrap();
+
+    if !graph::graph_assertions() {
+        return input;
+    }
+
+    let failure = match kind {
+        AssertKind::Shape(pattern) => (pattern.matches_shape(input.shape()).is_err())
+            .then(|| (None, format!("{:?}", input.shape()))),
+        AssertKind::Finite | AssertKind::InRange(_, _) => (input.copied_iter().enumerate())
+            .find(|(_, el)| breaks(*el))
+            .map(|(i, el)| (Some(i), format!("{:?}", el))),
+    };
+
+    if let Some((index, value)) = failure {
+        graph::fail_assertion(AssertionFailure::new(label, kind.condition(), index, value));
+    }
+
+    input
+}
+
+// Whether `el` breaks the invariant, NaN is never within a range.
+fn breaks_assert_f64(kind: &AssertKind<f64>, el: f64) -> bool {
+    match kind {
+        AssertKind::Finite => !el.is_finite(),
+        AssertKind::InRange(lo, hi) => !(*lo <= el && el <= *hi),
+        AssertKind::Shape(_) => false,
+    }
+}
+
+// Counter-based generator: the draw of element `i` is a hash of the seed and of `i`, so it
+// does not depend on which elements are computed together, nor in which order. The hash
+// is the finalizer of splitmix64, over the counter scaled by its increment.
+fn random_bits(seed: u64, i: u64) -> u64 {
+    fn mix(mut z: u64) -> u64 {
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
     }
+
+    mix(mix(seed).wrapping_add(i.wrapping_mul(0x9e3779b97f4a7c15)))
 }
 
+// Within [0, 1), from the top 53 bits.
 #[inline]
-pub fn cpu_compute<T: ComputeWrapperSpec>(
-    op: &OpKind<T>,
+fn random_uniform(seed: u64, i: u64) -> f64 {
+    (random_bits(seed, i) >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+// Box-Muller over the draws 2i and 2i + 1, the first one taken within (0, 1].
+#[inline]
+fn random_normal(seed: u64, i: u64) -> f64 {
+    let radius = (-2.0 * (1.0 - random_uniform(seed, 2 * i)).ln()).sqrt();
+    radius * (std::f64::consts::TAU * random_uniform(seed, 2 * i + 1)).cos()
+}
+
+fn cpu_compute_random_f64(
+    kind: RandomKind,
+    seed: u64,
+    counter: usize,
     output_layout: &Layout,
-    inputs: Vec<TensorData<T>>,
-) -> TensorData<T> {
-    T::compute_for_type(op, output_layout, inputs)
+    mut inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let first = counter as u64;
+
+    let draw = match kind {
+        RandomKind::Dropout(p) => {
+            let mut buffer = get_reusable_or_alloc(inputs.pop().unwrap());
+            let scale = 1.0 / (1.0 - p);
+            let mut nonzeros = 0;
+
+            for (i, el) in buffer.v[buffer.offset..].iter_mut().enumerate() {
+                *el = if random_uniform(seed, first + i as u64) < p {
+                    0.0
+                } else {
+                    *el * scale
+                };
+                nonzeros += (*el != 0.0) as usize;
+            }
+
+            let density = nonzeros as f32 / output_layout.len() as f32;
+
+            return TensorData::from_vec(buffer.v, output_layout.shape(), buffer.offset)
+                .mark_as_reusable()
+                .with_density_hint(Some(density));
+        }
+        RandomKind::Uniform => random_uniform,
+        RandomKind::Normal => random_normal,
+    };
+
+    // Only the layout of the input is used, so it is not copied
+    let v = (0..output_layout.len() as u64)
+        .map(|i| draw(seed, first + i))
+        .collect();
+
+    TensorData::from_vec(v, output_layout.shape(), 0).mark_as_reusable()
+}
+
+// Mul, computing only the nonzero elements when an operand is hinted to be mostly zeros.
+// The result is at most as dense as its sparsest operand.
+fn cpu_compute_mul_f64(inputs: Vec<TensorData<f64>>) -> TensorData<f64> {
+    let density = (inputs.iter())
+        .filter_map(TensorData::density_hint)
+        .reduce(f32::min);
+
+    let result = match sparse_mul_f64(&inputs, density) {
+        Some(result) => result,
+        None => compute_elementwise_tensor_tensor(
+            inputs,
+            BinaryKernels {
+                repeated: Some(OpKindScalar::Mul),
+                ..BinaryKernels::packed(Backend::mul, true)
+            },
+        ),
+    };
+
+    result.with_density_hint(density)
+}
+
+// Bits of an f64 at or above which it is negative, inf or NaN.
+const INF_BITS: u64 = 0x7ff0_0000_0000_0000;
+const SIGN_BIT: u64 = 1 << 63;
+
+// Product of the operand hinted to be `density` dense with the other, multiplying only
+// its nonzero elements, see SparseZeros. None if the hint is not below sparse_threshold(),
+// if an operand is not contiguous, or if the other operand is not finite and non-negative
+// with SparseZeros::Ieee.
+fn sparse_mul_f64(inputs: &[TensorData<f64>], density: Option<f32>) -> Option<TensorData<f64>> {
+    if density? >= numerics::sparse_threshold() {
+        return None;
+    }
+
+    let sparse_at = (inputs.iter()).position(|input| input.density_hint() == density)?;
+    let sparse = inputs[sparse_at].contiguous_slice()?;
+    let dense = inputs[1 - sparse_at].contiguous_slice()?;
+
+    // +0.0 times a finite non-negative element is +0.0, the zeros of the result for any
+    // other element have to be computed. The chunks are checked without branches.
+    let zero_bits = match numerics::sparse_zeros() {
+        SparseZeros::Ieee => {
+            let plain = |chunk: &[f64]| {
+                (chunk.iter()).fold(true, |plain, el| plain & (el.to_bits() < INF_BITS))
+            };
+            if !dense.chunks(256).all(plain) {
+                return None;
+            }
+
+            // -0.0 is multiplied along with the nonzero elements, for its sign.
+            u64::MAX
+        }
+        SparseZeros::Absorbing => !SIGN_BIT,
+    };
+
+    let mut out = vec![0.0; dense.len()];
+    for (i, &el) in sparse.iter().enumerate() {
+        if el.to_bits() & zero_bits != 0 {
+            out[i] = el * dense[i];
+        }
+    }
+
+    Some(TensorData::from_vec(out, inputs[0].shape(), 0).mark_as_reusable())
+}
+
+// vdFmin and vdFmax do not order the signed zeros, and ignore NaNs like fmin
+// and fmax, so every variant is implemented explicitly.
+fn min_max_f64(kind: MinMaxKind) -> fn(f64, f64) -> f64 {
+    fn minimum(a: f64, b: f64) -> f64 {
+        if a.is_nan() || b.is_nan() {
+            return if a.is_nan() { a } else { b };
+        }
+
+        // Also picks -0.0 out of -0.0 and 0.0, which compare equal.
+        if a < b || (a == b && a.is_sign_negative()) {
+            a
+        } else {
+            b
+        }
+    }
+
+    fn maximum(a: f64, b: f64) -> f64 {
+        if a.is_nan() || b.is_nan() {
+            return if a.is_nan() { a } else { b };
+        }
+
+        if a > b || (a == b && a.is_sign_positive()) {
+            a
+        } else {
+            b
+        }
+    }
+
+    fn minimum_num(a: f64, b: f64) -> f64 {
+        match (a.is_nan(), b.is_nan()) {
+            (true, _) => b,
+            (_, true) => a,
+            _ => minimum(a, b),
+        }
+    }
+
+    fn maximum_num(a: f64, b: f64) -> f64 {
+        match (a.is_nan(), b.is_nan()) {
+            (true, _) => b,
+            (_, true) => a,
+            _ => maximum(a, b),
+        }
+    }
+
+    match kind {
+        MinMaxKind::Minimum => minimum,
+        MinMaxKind::Maximum => maximum,
+        MinMaxKind::MinimumNum => minimum_num,
+        MinMaxKind::MaximumNum => maximum_num,
+    }
+}
+
+fn cpu_compute_min_max_f64(
+    kind: MinMaxKind,
+    output_layout: &Layout,
+    mut inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let rhs = inputs.pop().unwrap();
+    let mut buffer = get_reusable_or_alloc(inputs.pop().unwrap());
+    let select = min_max_f64(kind);
+
+    let out = &mut buffer.v[buffer.offset..buffer.offset + output_layout.len()];
+
+    branch_fast_iter!(rhs.copied_fast_iter() => iter, {
+        for (el, other) in out.iter_mut().zip(iter) {
+            *el = select(*el, other);
+        }
+    });
+
+    TensorData::from_vec(buffer.v, output_layout.shape(), buffer.offset).mark_as_reusable()
+}
+
+fn cpu_compute_min_max_scalar_f64(
+    kind: MinMaxKind,
+    scalar: f64,
+    output_layout: &Layout,
+    mut inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let mut buffer = get_reusable_or_alloc(inputs.pop().unwrap());
+    let select = min_max_f64(kind);
+
+    for el in buffer.v[buffer.offset..].iter_mut() {
+        *el = select(*el, scalar);
+    }
+
+    TensorData::from_vec(buffer.v, output_layout.shape(), buffer.offset).mark_as_reusable()
+}
+
+// Normalizes every lane along `axis` in two passes: the first one computes the mean
+// and the (biased) variance with Welford's algorithm, the second writes the dense output.
+fn cpu_compute_normalize_f64(
+    axis: usize,
+    eps: f64,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let input = &inputs[0];
+    let buffer = &input.storage.buffer;
+    let shape = input.shape();
+    let stride = input.stride();
+
+    let mut out = vec![0.0; output_layout.len()];
+
+    let axis_len = shape[axis];
+    let axis_stride = stride[axis] as isize;
+    let inner: usize = shape[axis + 1..].iter().product();
+
+    if axis_len == 0 {
+        return TensorData::from_vec(out, output_layout.shape(), 0).mark_as_reusable();
+    }
+
+    for lane in 0..output_layout.len() / axis_len {
+        let mut remaining = lane;
+        let mut pos = input.offset() as isize;
+
+        for dim in (0..shape.len()).rev() {
+            if dim == axis {
+                continue;
+            }
+
+            pos += (remaining % shape[dim]) as isize * stride[dim] as isize;
+            remaining /= shape[dim];
+        }
+
+        let mut mean = 0.0;
+        let mut m2 = 0.0;
+
+        for k in 0..axis_len {
+            let x = buffer[(pos + k as isize * axis_stride) as usize];
+            let delta = x - mean;
+
+            mean += delta / (k + 1) as f64;
+            m2 += delta * (x - mean);
+        }
+
+        let inv_std = 1.0 / (m2 / axis_len as f64 + eps).sqrt();
+        let out_base = (lane / inner) * axis_len * inner + lane % inner;
+
+        for k in 0..axis_len {
+            let x = buffer[(pos + k as isize * axis_stride) as usize];
+            out[out_base + k * inner] = (x - mean) * inv_std;
+        }
+    }
+
+    TensorData::from_vec(out, output_layout.shape(), 0).mark_as_reusable()
+}
+
+// Sum of a sliding window. Non-finite values are counted instead of added,
+// so an infinity leaving the window does not turn the following sums into NaN.
+#[derive(Default)]
+struct WindowSum {
+    finite: f64,
+    nan: usize,
+    pos_inf: usize,
+    neg_inf: usize,
+}
+
+impl WindowSum {
+    fn push(&mut self, x: f64) {
+        match x {
+            _ if x.is_nan() => self.nan += 1,
+            f64::INFINITY => self.pos_inf += 1,
+            f64::NEG_INFINITY => self.neg_inf += 1,
+            _ => self.finite += x,
+        }
+    }
+
+    fn pop(&mut self, x: f64) {
+        match x {
+            _ if x.is_nan() => self.nan -= 1,
+            f64::INFINITY => self.pos_inf -= 1,
+            f64::NEG_INFINITY => self.neg_inf -= 1,
+            _ => self.finite -= x,
+        }
+    }
+
+    fn value(&self) -> f64 {
+        match (self.nan, self.pos_inf, self.neg_inf) {
+            (0, 0, 0) => self.finite,
+            (0, _, 0) => f64::INFINITY,
+            (0, 0, _) => f64::NEG_INFINITY,
+            _ => f64::NAN,
+        }
+    }
+}
+
+fn rolling_sum_lane(lane: &[f64], window: usize, results: &mut Vec<f64>) {
+    let mut sum = WindowSum::default();
+
+    for (i, &x) in lane.iter().enumerate() {
+        sum.push(x);
+
+        if i >= window {
+            sum.pop(lane[i - window]);
+        }
+
+        if i + 1 >= window {
+            results.push(sum.value());
+        }
+    }
+}
+
+// Keeps the indices of a decreasing run of values in a deque, so the front is
+// always the maximum of the window and every element is pushed and popped once.
+fn rolling_max_lane(lane: &[f64], window: usize, results: &mut Vec<f64>) {
+    let mut candidates: VecDeque<usize> = VecDeque::with_capacity(window);
+    let mut last_nan = None;
+
+    for (i, &x) in lane.iter().enumerate() {
+        if x.is_nan() {
+            last_nan = Some(i);
+        } else {
+            while candidates.back().is_some_and(|&j| lane[j] <= x) {
+                candidates.pop_back();
+            }
+
+            candidates.push_back(i);
+        }
+
+        if i + 1 < window {
+            continue;
+        }
+
+        let start = i + 1 - window;
+
+        while candidates.front().is_some_and(|&j| j < start) {
+            candidates.pop_front();
+        }
+
+        // A window made only of NaN leaves no candidates, but then last_nan is inside it.
+        match last_nan {
+            Some(j) if j >= start => results.push(f64::NAN),
+            _ => results.push(lane[candidates[0]]),
+        }
+    }
+}
+
+// Every lane along `axis` is read once into a dense buffer and reduced in O(len),
+// regardless of the window size. A NaN in the window makes the output NaN.
+fn cpu_compute_rolling_f64(
+    kind: RollKind,
+    window: usize,
+    axis: usize,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let out_axis_len = output_layout.shape()[axis];
+
+    cpu_compute_lanes_f64(
+        &inputs[0],
+        axis,
+        out_axis_len,
+        output_layout,
+        |lane, results| match kind {
+            RollKind::Sum => rolling_sum_lane(lane, window, results),
+            RollKind::Mean => {
+                rolling_sum_lane(lane, window, results);
+
+                for el in results.iter_mut() {
+                    *el /= window as f64;
+                }
+            }
+            RollKind::Max => rolling_max_lane(lane, window, results),
+        },
+    )
+}
+
+// Every row of the output is the rhs scaled by an element of the lhs. It is the rank-1
+// update of a zero matrix done by ger, except that the products are not added to zero,
+// so -0.0 is kept.
+fn cpu_compute_outer<T: NumberLike>(
+    output_layout: &Layout,
+    inputs: Vec<TensorData<T>>,
+) -> TensorData<T>
+where
+    Backend: VectorMathBackend<T>,
+{
+    let rhs = inputs[1].elements();
+    let mut out = Vec::with_capacity(output_layout.len());
+
+    for scale in inputs[0].copied_iter() {
+        let start = out.len();
+        out.extend_from_slice(&rhs);
+        Backend::scal(scale, &mut out[start..]);
+    }
+
+    TensorData::from_vec(out, output_layout.shape(), 0).mark_as_reusable()
+}
+
+// Dot product of every lane along the last axis of the lhs with every lane of the rhs.
+fn cpu_compute_inner_f64(output_layout: &Layout, inputs: Vec<TensorData<f64>>) -> TensorData<f64> {
+    let shape = inputs[0].shape();
+    let len = shape[shape.len() - 1];
+
+    // Packed once if strided, so every lane is a contiguous slice.
+    let lhs = inputs[0].elements();
+    let rhs = inputs[1].elements();
+
+    let mut out = Vec::with_capacity(output_layout.len());
+
+    if len == 0 {
+        out.resize(output_layout.len(), 0.0);
+    } else {
+        for lhs_lane in lhs.chunks_exact(len) {
+            for rhs_lane in rhs.chunks_exact(len) {
+                out.push(kernels::dot(lhs_lane, rhs_lane));
+            }
+        }
+    }
+
+    TensorData::from_vec(out, output_layout.shape(), 0).mark_as_reusable()
+}
+
+fn cpu_compute_diff_f64(
+    n: usize,
+    axis: usize,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let out_axis_len = output_layout.shape()[axis];
+
+    cpu_compute_lanes_f64(
+        &inputs[0],
+        axis,
+        out_axis_len,
+        output_layout,
+        |lane, results| {
+            results.extend_from_slice(lane);
+
+            // Every pass is done in place, shortening the lane by one.
+            for pass in 1..=n {
+                for i in 0..lane.len() - pass {
+                    results[i] = results[i + 1] - results[i];
+                }
+            }
+
+            results.truncate(out_axis_len);
+        },
+    )
+}
+
+// `positions` holds the sample positions, otherwise they are `dx` apart.
+fn cpu_compute_trapz_f64(
+    dx: f64,
+    axis: usize,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let positions = inputs.get(1).map(|x| x.elements());
+
+    cpu_compute_lanes_f64(&inputs[0], axis, 1, output_layout, |lane, results| {
+        let area = match &positions {
+            Some(x) => kernels::sum_iter(
+                (lane.windows(2).zip(x.windows(2)))
+                    .map(|(y, x)| (y[0] + y[1]) / 2.0 * (x[1] - x[0])),
+            ),
+            None => kernels::sum_iter(lane.windows(2).map(|y| (y[0] + y[1]) / 2.0 * dx)),
+        };
+
+        results.push(area);
+    })
+}
+
+// Every lane is gathered into a scratch buffer, which is partially sorted once or twice
+// per quantile with select_nth_unstable instead of being fully sorted.
+fn cpu_compute_quantile_f64(
+    qs: &[f64],
+    axis: Option<usize>,
+    method: QuantileMethod,
+    nan_policy: NanPolicy,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    let input = &inputs[0];
+    let buffer = &input.storage.buffer;
+    let shape = input.shape();
+    let stride = input.stride();
+
+    let mut out = vec![0.0; output_layout.len()];
+    // The quantiles of a lane are `n_lanes` apart in the output, as they lead the shape.
+    let n_lanes = out.len() / qs.len();
+
+    let mut lane: Vec<f64> = Vec::with_capacity(axis.map_or(input.len(), |axis| shape[axis]));
+
+    for lane_idx in 0..n_lanes {
+        lane.clear();
+
+        match axis {
+            None => lane.extend(input.copied_iter()),
+            Some(axis) => {
+                let mut remaining = lane_idx;
+                let mut pos = input.offset() as isize;
+
+                for dim in (0..shape.len()).rev() {
+                    if dim == axis {
+                        continue;
+                    }
+
+                    pos += (remaining % shape[dim]) as isize * stride[dim] as isize;
+                    remaining /= shape[dim];
+                }
+
+                let axis_stride = stride[axis] as isize;
+                lane.extend(
+                    (0..shape[axis]).map(|k| buffer[(pos + k as isize * axis_stride) as usize]),
+                );
+            }
+        }
+
+        let has_nan = match nan_policy {
+            NanPolicy::Propagate => lane.iter().any(|x| x.is_nan()),
+            NanPolicy::Omit => {
+                lane.retain(|x| !x.is_nan());
+                false
+            }
+        };
+
+        for (k, &q) in qs.iter().enumerate() {
+            out[k * n_lanes + lane_idx] = if has_nan || lane.is_empty() {
+                f64::NAN
+            } else {
+                quantile_of(&mut lane, q, method)
+            };
+        }
+    }
+
+    TensorData::from_vec(out, output_layout.shape(), 0).mark_as_reusable()
+}
+
+// The `q` quantile of `values`, which are reordered. They must not be empty nor have NaNs.
+fn quantile_of(values: &mut [f64], q: f64, method: QuantileMethod) -> f64 {
+    let position = (values.len() - 1) as f64 * q;
+    let (lower, higher) = (position.floor() as usize, position.ceil() as usize);
+
+    let mut nth = |n: usize| *values.select_nth_unstable_by(n, f64::total_cmp).1;
+
+    match method {
+        QuantileMethod::Lower => nth(lower),
+        QuantileMethod::Higher => nth(higher),
+        QuantileMethod::Nearest => nth(position.round_ties_even() as usize),
+        QuantileMethod::Midpoint | QuantileMethod::Linear => {
+            let below = nth(lower);
+            let above = if higher == lower { below } else { nth(higher) };
+
+            if method == QuantileMethod::Midpoint {
+                return (below + above) / 2.0;
+            }
+
+            // Interpolated from the closest end, like numpy, so the ends are exact.
+            let fraction = position - lower as f64;
+            if fraction < 0.5 {
+                below + (above - below) * fraction
+            } else {
+                above - (above - below) * (1.0 - fraction)
+            }
+        }
+    }
+}
+
+// Calls `f` with every lane of the input along `axis`, and writes the `out_axis_len`
+// results it pushes to the same lane of the output. An output lane of length 1
+// may also have its axis removed from the output shape.
+fn cpu_compute_lanes_f64(
+    input: &TensorData<f64>,
+    axis: usize,
+    out_axis_len: usize,
+    output_layout: &Layout,
+    mut f: impl FnMut(&[f64], &mut Vec<f64>),
+) -> TensorData<f64> {
+    let buffer = &input.storage.buffer;
+    let shape = input.shape();
+    let stride = input.stride();
+
+    let mut out = vec![0.0; output_layout.len()];
+
+    let axis_len = shape[axis];
+    let axis_stride = stride[axis] as isize;
+    let inner: usize = shape[axis + 1..].iter().product();
+
+    if out.is_empty() {
+        return TensorData::from_vec(out, output_layout.shape(), 0).mark_as_reusable();
+    }
+
+    let mut lane = Vec::with_capacity(axis_len);
+    let mut results = Vec::with_capacity(out_axis_len);
+
+    for lane_idx in 0..out.len() / out_axis_len {
+        let mut remaining = lane_idx;
+        let mut pos = input.offset() as isize;
+
+        for dim in (0..shape.len()).rev() {
+            if dim == axis {
+                continue;
+            }
+
+            pos += (remaining % shape[dim]) as isize * stride[dim] as isize;
+            remaining /= shape[dim];
+        }
+
+        lane.clear();
+        lane.extend((0..axis_len).map(|k| buffer[(pos + k as isize * axis_stride) as usize]));
+
+        results.clear();
+        f(&lane, &mut results);
+
+        let out_base = (lane_idx / inner) * out_axis_len * inner + lane_idx % inner;
+
+        for (k, value) in results.iter().enumerate() {
+            out[out_base + k * inner] = *value;
+        }
+    }
+
+    TensorData::from_vec(out, output_layout.shape(), 0).mark_as_reusable()
+}
+
+fn cpu_compute_dyn_scalar<T: NumberLike>(
+    kind: ScalarKind,
+    scalar_idx: usize,
+    tail: &[OpKindScalar<T>],
+    output_layout: &Layout,
+    mut inputs: Vec<TensorData<T>>,
+) -> TensorData<T>
+where
+    Backend: VectorMathBackend<T>,
+{
+    let scalar_data = inputs.remove(scalar_idx);
+    let scalar = unsafe { scalar_data.copied_iter().next().unwrap_unchecked() };
+
+    let buffer = get_reusable_or_alloc(inputs.pop().unwrap());
+    let offset = buffer.offset;
+    let mut buffer = buffer.v;
+    let elements = &mut buffer[offset..];
+
+    // The scalar is on the right, so it behaves exactly like a ScalarOp
+    if scalar_idx == 1 {
+        let op = match kind {
+            ScalarKind::Sum => OpKindScalar::Sum(scalar),
+            ScalarKind::Sub => OpKindScalar::Sub(scalar),
+            ScalarKind::Mul => OpKindScalar::Mul(scalar),
+            ScalarKind::Div => OpKindScalar::Div(scalar),
+        };
+
+        compute_scalar_op(&op, elements);
+    } else {
+        match kind {
+            ScalarKind::Sum => compute_scalar_op(&OpKindScalar::Sum(scalar), elements),
+            ScalarKind::Mul => compute_scalar_op(&OpKindScalar::Mul(scalar), elements),
+            ScalarKind::Sub => {
+                for el in elements.iter_mut() {
+                    *el = scalar - *el;
+                }
+            }
+            ScalarKind::Div => {
+                for el in elements.iter_mut() {
+                    *el = scalar / *el;
+                }
+            }
+        }
+    }
+
+    for op in tail {
+        compute_scalar_op(op, elements);
+    }
+
+    TensorData::from_vec(buffer, output_layout.shape(), offset).mark_as_reusable()
+}
+
+// Expands one axis at a time, starting from the last one, so every step
+// only copies contiguous blocks of the previous one.
+fn cpu_compute_tile<T: Copy + Default>(
+    source: Cow<'_, [T]>,
+    shape: &[usize],
+    repeats: &[usize],
+    output_layout: &Layout,
+) -> TensorData<T> {
+    let ndim = output_layout.shape().len();
+
+    let mut current_shape = vec![1; ndim];
+    current_shape[ndim - shape.len()..].copy_from_slice(shape);
+
+    let mut current = source;
+
+    for axis in (0..ndim).rev() {
+        let reps = (axis + repeats.len())
+            .checked_sub(ndim)
+            .map_or(1, |d| repeats[d]);
+
+        if reps == 1 {
+            continue;
+        }
+
+        let block: usize = current_shape[axis..].iter().product();
+        let mut next = Vec::with_capacity(current.len() * reps);
+
+        for chunk in current.chunks(block.max(1)) {
+            for _ in 0..reps {
+                next.extend_from_slice(chunk);
+            }
+        }
+
+        current_shape[axis] *= reps;
+        current = Cow::Owned(next);
+    }
+
+    TensorData::from_vec(current.into_owned(), output_layout.shape(), 0).mark_as_reusable()
+}
+
+// Ops that only change how the buffer is read. They do not depend on the element type.
+fn cpu_compute_layout_op<T: Copy + Default>(
+    op: &OpKind<T>,
+    output_layout: &Layout,
+    mut inputs: Vec<TensorData<T>>,
+) -> TensorData<T> {
+    match op {
+        OpKind::Slice(new_layout)
+        | OpKind::View(new_layout)
+        | OpKind::TransposeAxes(new_layout, _) => inputs[0].as_layout(new_layout.clone()),
+        OpKind::AsContiguous => {
+            if inputs[0].is_contiguous() {
+                inputs[0].clone()
+            } else {
+                inputs[0].as_contiguous().mark_as_reusable()
+            }
+        }
+        OpKind::Transpose => {
+            let layout = inputs[0].layout();
+            inputs[0].as_layout(layout.transpose())
+        }
+        OpKind::Flip(axes) => {
+            let flipped = unsafe { inputs[0].layout().flip(axes).unwrap_unchecked() };
+            let view = inputs[0].as_layout(flipped);
+
+            TensorData::from_iter(view.copied_iter(), output_layout.shape()).mark_as_reusable()
+        }
+        OpKind::Tile(repeats) => cpu_compute_tile(
+            inputs[0].elements(),
+            inputs[0].shape(),
+            repeats,
+            output_layout,
+        ),
+        OpKind::Repeat(axis, repeats) => {
+            let shape = inputs[0].shape();
+            let inner: usize = shape[axis + 1..].iter().product();
+
+            let mut buffer = Vec::with_capacity(output_layout.len());
+            let source = inputs[0].elements();
+
+            for chunk in source.chunks(inner.max(1)) {
+                for _ in 0..*repeats {
+                    buffer.extend_from_slice(chunk);
+                }
+            }
+
+            TensorData::from_vec(buffer, output_layout.shape(), 0).mark_as_reusable()
+        }
+        OpKind::Concat { axis, .. } => {
+            let shape = output_layout.shape();
+            let outer: usize = shape[..*axis].iter().product();
+            let inner: usize = shape[axis + 1..].iter().product();
+
+            // Every input contributes a block of its own axis length per outer index.
+            let mut sources: Vec<_> = inputs
+                .iter()
+                .map(|input| (input.copied_iter(), input.shape()[*axis] * inner))
+                .collect();
+
+            let mut buffer = Vec::with_capacity(output_layout.len());
+
+            for _ in 0..outer {
+                for (iter, block) in sources.iter_mut() {
+                    buffer.extend(iter.by_ref().take(*block));
+                }
+            }
+
+            TensorData::from_vec(buffer, shape, 0).mark_as_reusable()
+        }
+        OpKind::Composite { windows, fill, .. } => {
+            let buffer = vec![*fill; output_layout.len()];
+            let mut output = TensorData::from_vec(buffer, output_layout.shape(), 0);
+
+            for (window, input) in windows.iter().zip(&inputs) {
+                output.write_iter_as_layout(window, input.copied_iter());
+            }
+
+            output.mark_as_reusable()
+        }
+        OpKind::NoOp => unsafe { inputs.pop().unwrap_unchecked() },
+        _ => unreachable!("no other op should appear here"),
+    }
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip(inputs, output_layout),
+        fields(op = op.as_str(), out_len = output_layout.len())
+    )
+)]
+fn cpu_compute_op_f64(
+    op: &OpKind<f64>,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<f64>>,
+) -> TensorData<f64> {
+    match op {
+        OpKind::ScalarOp(_) | OpKind::FusedScalar(_) => {
+            cpu_compute_elementwise(op, output_layout, inputs)
+        }
+        OpKind::DynScalarOp(kind, scalar_idx, tail) => {
+            cpu_compute_dyn_scalar(*kind, *scalar_idx, tail, output_layout, inputs)
+        }
+        OpKind::Slice(_)
+        | OpKind::View(_)
+        | OpKind::TransposeAxes(_, _)
+        | OpKind::AsContiguous
+        | OpKind::Transpose
+        | OpKind::Flip(_)
+        | OpKind::Tile(_)
+        | OpKind::Repeat(_, _)
+        | OpKind::Concat { .. }
+        | OpKind::Composite { .. }
+        | OpKind::NoOp => cpu_compute_layout_op(op, output_layout, inputs),
+        OpKind::Add => compute_elementwise_tensor_tensor(
+            inputs,
+            BinaryKernels {
+                repeated: Some(OpKindScalar::Sum),
+                strided: Some(1.0),
+                ..BinaryKernels::packed(Backend::add, true)
+            },
+        ),
+        OpKind::Sub => compute_elementwise_tensor_tensor(
+            inputs,
+            BinaryKernels {
+                repeated: Some(OpKindScalar::Sub),
+                strided: Some(-1.0),
+                ..BinaryKernels::packed(Backend::sub, false)
+            },
+        ),
+        OpKind::Mul => cpu_compute_mul_f64(inputs),
+        // VML may round a division differently from the scalar kernel.
+        OpKind::Div => {
+            compute_elementwise_tensor_tensor(inputs, BinaryKernels::packed(Backend::div, false))
+        }
+        OpKind::Outer => cpu_compute_outer(output_layout, inputs),
+        OpKind::Inner => cpu_compute_inner_f64(output_layout, inputs),
+        OpKind::BatchMatMul => cpu_compute_batch_matmul_f64(output_layout, inputs),
+        OpKind::MSELoss(_) | OpKind::MAELoss(_) => cpu_compute_loss_f64(op, output_layout, inputs),
+        OpKind::Normalize(axis, eps) => {
+            cpu_compute_normalize_f64(*axis, *eps, output_layout, inputs)
+        }
+        OpKind::Reduce(kind, axis) => cpu_compute_reduce_f64(*kind, *axis, output_layout, inputs),
+        OpKind::Unary(kind) => cpu_compute_unary_f64(*kind, output_layout, inputs),
+        OpKind::Neg => cpu_compute_neg(output_layout, inputs, |v| Backend::scal(-1.0, v)),
+        OpKind::Rolling(kind, window, axis) => {
+            cpu_compute_rolling_f64(*kind, *window, *axis, output_layout, inputs)
+        }
+        OpKind::Diff(n, axis) => cpu_compute_diff_f64(*n, *axis, output_layout, inputs),
+        OpKind::Trapz(dx, axis) => cpu_compute_trapz_f64(*dx, *axis, output_layout, inputs),
+        OpKind::TrapzX(axis) => cpu_compute_trapz_f64(0.0, *axis, output_layout, inputs),
+        OpKind::MinMax(kind) => cpu_compute_min_max_f64(*kind, output_layout, inputs),
+        OpKind::MinMaxScalar(kind, scalar) => {
+            cpu_compute_min_max_scalar_f64(*kind, *scalar, output_layout, inputs)
+        }
+        OpKind::Quantile(qs, axis, method, nan_policy) => {
+            cpu_compute_quantile_f64(qs, *axis, *method, *nan_policy, output_layout, inputs)
+        }
+        OpKind::Random(kind, seed, counter) => {
+            cpu_compute_random_f64(*kind, *seed, *counter, output_layout, inputs)
+        }
+        OpKind::Cast(source) => cpu_compute_cast(source),
+        OpKind::Assert(kind, label) => {
+            cpu_compute_assert(kind, label, inputs, |el| breaks_assert_f64(kind, el))
+        }
+        OpKind::Apply(f, _) => cpu_compute_apply(f, output_layout, inputs),
+        _ => todo!("not implemented"),
+    }
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip(inputs, output_layout),
+        fields(op = op.as_str(), out_len = output_layout.len())
+    )
+)]
+fn cpu_compute_op_c64(
+    op: &OpKind<Complex<f64>>,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<Complex<f64>>>,
+) -> TensorData<Complex<f64>> {
+    match op {
+        OpKind::ScalarOp(_) | OpKind::FusedScalar(_) => {
+            cpu_compute_elementwise(op, output_layout, inputs)
+        }
+        OpKind::DynScalarOp(kind, scalar_idx, tail) => {
+            cpu_compute_dyn_scalar(*kind, *scalar_idx, tail, output_layout, inputs)
+        }
+        OpKind::Slice(_)
+        | OpKind::View(_)
+        | OpKind::TransposeAxes(_, _)
+        | OpKind::AsContiguous
+        | OpKind::Transpose
+        | OpKind::Flip(_)
+        | OpKind::Tile(_)
+        | OpKind::Repeat(_, _)
+        | OpKind::Concat { .. }
+        | OpKind::Composite { .. }
+        | OpKind::NoOp => cpu_compute_layout_op(op, output_layout, inputs),
+        // A complex axpy multiplies by alpha, which turns infinite parts into NaNs, and
+        // the products of VML are rescaled, so only Add and Sub read a broadcast unpacked.
+        OpKind::Add => compute_elementwise_tensor_tensor(
+            inputs,
+            BinaryKernels {
+                repeated: Some(OpKindScalar::Sum),
+                ..BinaryKernels::packed(Backend::add, true)
+            },
+        ),
+        OpKind::Sub => compute_elementwise_tensor_tensor(
+            inputs,
+            BinaryKernels {
+                repeated: Some(OpKindScalar::Sub),
+                ..BinaryKernels::packed(Backend::sub, false)
+            },
+        ),
+        OpKind::Mul => {
+            compute_elementwise_tensor_tensor(inputs, BinaryKernels::packed(Backend::mul, true))
+        }
+        OpKind::Div => {
+            compute_elementwise_tensor_tensor(inputs, BinaryKernels::packed(Backend::div, false))
+        }
+        OpKind::Outer => cpu_compute_outer(output_layout, inputs),
+        OpKind::Neg => cpu_compute_neg(output_layout, inputs, |v| {
+            v.iter_mut().for_each(|el| *el = -*el)
+        }),
+        OpKind::Cast(source) => cpu_compute_cast(source),
+        // Complex numbers have no order, so only finiteness and shapes are asserted.
+        OpKind::Assert(kind @ (AssertKind::Finite | AssertKind::Shape(_)), label) => {
+            cpu_compute_assert(kind, label, inputs, |el| {
+                !(el.re.is_finite() && el.im.is_finite())
+            })
+        }
+        OpKind::Apply(f, _) => cpu_compute_apply(f, output_layout, inputs),
+        _ => todo!("not implemented"),
+    }
+}
+
+pub trait ComputeWrapperSpec
+where
+    Self: Copy,
+{
+    fn compute_for_type(
+        op: &OpKind<Self>,
+        output_layout: &Layout,
+        inputs: Vec<TensorData<Self>>,
+    ) -> TensorData<Self>;
+
+    fn compute_with_cast_for_type(
+        op: &OpKind<Self>,
+        other: TensorData<Self>,
+        source: &CastSource<Self>,
+        cast_at: usize,
+    ) -> TensorData<Self>;
+}
+
+impl ComputeWrapperSpec for f64 {
+    #[inline]
+    fn compute_for_type(
+        op: &OpKind<f64>,
+        output_layout: &Layout,
+        inputs: Vec<TensorData<f64>>,
+    ) -> TensorData<f64> {
+        cpu_compute_op_f64(op, output_layout, inputs)
+    }
+
+    #[inline]
+    fn compute_with_cast_for_type(
+        op: &OpKind<f64>,
+        other: TensorData<f64>,
+        source: &CastSource<f64>,
+        cast_at: usize,
+    ) -> TensorData<f64> {
+        cpu_compute_binary_with_cast(op, other, source, cast_at)
+    }
+}
+
+impl ComputeWrapperSpec for Complex<f64> {
+    #[inline]
+    fn compute_for_type(
+        op: &OpKind<Complex<f64>>,
+        output_layout: &Layout,
+        inputs: Vec<TensorData<Complex<f64>>>,
+    ) -> TensorData<Complex<f64>> {
+        cpu_compute_op_c64(op, output_layout, inputs)
+    }
+
+    #[inline]
+    fn compute_with_cast_for_type(
+        op: &OpKind<Complex<f64>>,
+        other: TensorData<Complex<f64>>,
+        source: &CastSource<Complex<f64>>,
+        cast_at: usize,
+    ) -> TensorData<Complex<f64>> {
+        cpu_compute_binary_with_cast(op, other, source, cast_at)
+    }
+}
+
+thread_local! {
+    static COMPUTED_OPS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Number of ops computed on the current thread so far. Comparing it before and after
+/// a materialization tells how many nodes of the graph were actually evaluated.
+pub fn computed_op_count() -> usize {
+    COMPUTED_OPS.get()
+}
+
+// Runs `f` without counting the ops it computes in computed_op_count().
+#[cfg(feature = "check_fast_paths")]
+pub(crate) fn uncounted<R>(f: impl FnOnce() -> R) -> R {
+    let count = COMPUTED_OPS.get();
+    let result = f();
+    COMPUTED_OPS.set(count);
+
+    result
+}
+
+// The kernels only read memory of the cpu. Graphs over other devices are built and
+// validated, but reaching here with one of them is a missing backend.
+#[inline]
+fn assert_on_cpu<T: Copy>(op: &OpKind<T>, device: Device) {
+    assert!(
+        device.is_cpu(),
+        "{} read data placed on {}, but only the cpu has a backend",
+        op.as_str(),
+        device
+    );
+}
+
+#[inline]
+pub fn cpu_compute<T: ComputeWrapperSpec>(
+    op: &OpKind<T>,
+    output_layout: &Layout,
+    inputs: Vec<TensorData<T>>,
+) -> TensorData<T> {
+    for input in &inputs {
+        assert_on_cpu(op, input.device());
+    }
+    if let OpKind::Cast(source) = op {
+        assert_on_cpu(op, source.device());
+    }
+
+    COMPUTED_OPS.set(COMPUTED_OPS.get() + 1);
+    metrics::record_op(op.as_str(), output_layout.len());
+
+    let _denormals = numerics::kernel_guard();
+    T::compute_for_type(op, output_layout, inputs)
+}
+
+/// Computes the binary op `op` reading `other` and, at the position `cast_at`, the source
+/// of a Cast converted as it is read, without computing the Cast itself.
+#[inline]
+pub fn cpu_compute_with_cast<T: ComputeWrapperSpec>(
+    op: &OpKind<T>,
+    other: TensorData<T>,
+    source: &CastSource<T>,
+    cast_at: usize,
+) -> TensorData<T> {
+    assert_on_cpu(op, other.device());
+    assert_on_cpu(op, source.device());
+
+    COMPUTED_OPS.set(COMPUTED_OPS.get() + 1);
+
+    let _denormals = numerics::kernel_guard();
+    let result = T::compute_with_cast_for_type(op, other, source, cast_at);
+    metrics::record_op(op.as_str(), result.layout().len());
+
+    result
 }
@@ -1,16 +1,72 @@
 use crate::tensor::errors::OpError;
 use crate::tensor::mem_formats::layout::Layout;
-use crate::tensor::ops::def_op::OpKind;
+use crate::tensor::ops::def_op::{OpKind, RandomKind, Reduction};
 
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn compute_layout<T: Copy>(op: &OpKind<T>, inputs: &[&Layout]) -> Result<Layout, OpError> {
     match op {
-        OpKind::ScalarOp(_) | OpKind::FusedScalar(_) | OpKind::NoOp => Ok(inputs[0].clone()),
+        OpKind::ScalarOp(_) | OpKind::FusedScalar(_) | OpKind::NoOp | OpKind::Assert(_, _) => {
+            Ok(inputs[0].clone())
+        }
         OpKind::View(new_layout)
         | OpKind::Slice(new_layout)
-        | OpKind::TransposeAxes(new_layout) => Ok(new_layout.clone()),
+        | OpKind::TransposeAxes(new_layout, _) => Ok(new_layout.clone()),
         OpKind::AsContiguous => Ok(Layout::from_shape(inputs[0].shape(), 0)),
+        OpKind::Cast(source) => Ok(Layout::from_shape(source.layout().shape(), 0)),
+        OpKind::DynScalarOp(_, scalar_idx, _) => {
+            if inputs[*scalar_idx].len() != 1 {
+                return Err(OpError::CannotBroadcast);
+            }
+
+            // Broadcasting a scalar with more axes adds leading axes of length 1.
+            let other = inputs[1 - scalar_idx].shape();
+            let added = inputs[*scalar_idx]
+                .shape()
+                .len()
+                .saturating_sub(other.len());
+            let shape: Vec<usize> = std::iter::repeat_n(1, added)
+                .chain(other.iter().copied())
+                .collect();
+
+            Ok(Layout::from_shape(&shape, 0))
+        }
         OpKind::Transpose => Ok(inputs[0].transpose()),
+        OpKind::Flip(axes) => {
+            // The flip itself is only validated here, the output is always dense.
+            let flipped = inputs[0].flip(axes)?;
+
+            Ok(Layout::from_shape(flipped.shape(), 0))
+        }
+        OpKind::Tile(repeats) => {
+            let shape = inputs[0].shape();
+            let ndim = shape.len().max(repeats.len());
+
+            // The shorter of the two is padded with leading ones.
+            let tiled: Vec<usize> = (0..ndim)
+                .map(|dim| {
+                    let len = (dim + shape.len())
+                        .checked_sub(ndim)
+                        .map_or(1, |d| shape[d]);
+                    let reps = (dim + repeats.len())
+                        .checked_sub(ndim)
+                        .map_or(1, |d| repeats[d]);
+
+                    len * reps
+                })
+                .collect();
+
+            Ok(Layout::from_shape(&tiled, 0))
+        }
+        OpKind::Repeat(axis, repeats) => {
+            if *axis >= inputs[0].shape().len() {
+                return Err(OpError::OutOfBoundAxes);
+            }
+
+            let mut shape = inputs[0].shape().to_vec();
+            shape[*axis] *= repeats;
+
+            Ok(Layout::from_shape(&shape, 0))
+        }
         OpKind::Matmul => {
             // Assumes that the tensor is ALREADY BROADCASTED!
             let a_shape = inputs[0].shape_as_3d();
@@ -29,7 +85,66 @@ pub fn compute_layout<T: Copy>(op: &OpKind<T>, inputs: &[&Layout]) -> Result<Lay
                 0,
             ))
         }
-        OpKind::Add | OpKind::Sub | OpKind::Mul | OpKind::Div => {
+        OpKind::Outer => {
+            for input in inputs {
+                if input.shape().len() != 1 {
+                    return Err(OpError::NotEnoughAxes(1, input.shape().len()));
+                }
+            }
+
+            Ok(Layout::from_shape(
+                &[inputs[0].shape()[0], inputs[1].shape()[0]],
+                0,
+            ))
+        }
+        OpKind::Inner => {
+            let (lhs, rhs) = (inputs[0].shape(), inputs[1].shape());
+            let (lhs_len, rhs_len) = (lhs[lhs.len() - 1], rhs[rhs.len() - 1]);
+
+            if lhs_len != rhs_len {
+                return Err(OpError::NotSameShape([lhs_len].into(), [rhs_len].into()));
+            }
+
+            let shape: Vec<usize> = (lhs[..lhs.len() - 1].iter())
+                .chain(&rhs[..rhs.len() - 1])
+                .copied()
+                .collect();
+
+            if shape.is_empty() {
+                Ok(Layout::from_shape(&[1], 0))
+            } else {
+                Ok(Layout::from_shape(&shape, 0))
+            }
+        }
+        OpKind::BatchMatMul => {
+            for input in inputs {
+                let rank = input.shape().len();
+
+                if !(2..=3).contains(&rank) {
+                    return Err(OpError::NotEnoughAxes(rank.clamp(2, 3), rank));
+                }
+            }
+
+            let (lhs, rhs) = (inputs[0].shape(), inputs[1].shape());
+            let batch = |shape: &[usize]| (shape.len() == 3).then(|| shape[0]);
+            let (m, k) = (lhs[lhs.len() - 2], lhs[lhs.len() - 1]);
+            let (k_rhs, n) = (rhs[rhs.len() - 2], rhs[rhs.len() - 1]);
+
+            if k != k_rhs {
+                return Err(OpError::CannotMatmul(k, k_rhs));
+            }
+
+            let batch = match (batch(lhs), batch(rhs)) {
+                (None, None) => return Ok(Layout::from_shape(&[m, n], 0)),
+                (Some(batch), None) | (None, Some(batch)) => batch,
+                (Some(lhs), Some(rhs)) if lhs == rhs || rhs == 1 => lhs,
+                (Some(1), Some(rhs)) => rhs,
+                (Some(lhs), Some(rhs)) => return Err(OpError::NotSameBatch(lhs, rhs)),
+            };
+
+            Ok(Layout::from_shape(&[batch, m, n], 0))
+        }
+        OpKind::Add | OpKind::Sub | OpKind::Mul | OpKind::Div | OpKind::MinMax(_) => {
             if inputs[0].shape() == inputs[1].shape() {
                 Ok(inputs[0].clone())
             } else {
@@ -39,6 +154,173 @@ pub fn compute_layout<T: Copy>(op: &OpKind<T>, inputs: &[&Layout]) -> Result<Lay
                 ))
             }
         }
-        _ => todo!("not implemented"),
+        OpKind::MSELoss(reduction) | OpKind::MAELoss(reduction) => {
+            if inputs[0].shape() != inputs[1].shape() {
+                return Err(OpError::NotSameShape(
+                    inputs[0].shape().into(),
+                    inputs[1].shape().into(),
+                ));
+            }
+
+            match reduction {
+                Reduction::None => Ok(Layout::from_shape(inputs[0].shape(), 0)),
+                Reduction::Sum | Reduction::Mean => Ok(Layout::from_shape(&[1], 0)),
+            }
+        }
+        OpKind::Normalize(axis, _) => {
+            if *axis >= inputs[0].shape().len() {
+                return Err(OpError::OutOfBoundAxes);
+            }
+
+            Ok(Layout::from_shape(inputs[0].shape(), 0))
+        }
+        OpKind::Reduce(_, axis) | OpKind::Trapz(_, axis) | OpKind::TrapzX(axis) => {
+            let shape = inputs[0].shape();
+
+            if *axis >= shape.len() {
+                return Err(OpError::OutOfBoundAxes);
+            }
+
+            if let OpKind::TrapzX(_) = op
+                && inputs[1].shape() != [shape[*axis]]
+            {
+                return Err(OpError::NotSameShape(
+                    [shape[*axis]].into(),
+                    inputs[1].shape().into(),
+                ));
+            }
+
+            let reduced: Vec<usize> = shape
+                .iter()
+                .enumerate()
+                .filter(|(dim, _)| dim != axis)
+                .map(|(_, &len)| len)
+                .collect();
+
+            // Reducing a 1-D tensor still produces a tensor with a single element.
+            if reduced.is_empty() {
+                Ok(Layout::from_shape(&[1], 0))
+            } else {
+                Ok(Layout::from_shape(&reduced, 0))
+            }
+        }
+        OpKind::Quantile(q, axis, _, _) => {
+            if q.is_empty() {
+                return Err(OpError::NoQuantiles);
+            }
+
+            if let Some(&bad) = q.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+                return Err(OpError::InvalidQuantile(bad));
+            }
+
+            let shape = inputs[0].shape();
+            let mut reduced: Vec<usize> = Vec::with_capacity(shape.len());
+
+            if q.len() > 1 {
+                reduced.push(q.len());
+            }
+
+            if let Some(axis) = axis {
+                if *axis >= shape.len() {
+                    return Err(OpError::OutOfBoundAxes);
+                }
+
+                reduced.extend(
+                    (shape.iter().enumerate())
+                        .filter(|(dim, _)| dim != axis)
+                        .map(|(_, &len)| len),
+                );
+            }
+
+            if reduced.is_empty() {
+                Ok(Layout::from_shape(&[1], 0))
+            } else {
+                Ok(Layout::from_shape(&reduced, 0))
+            }
+        }
+        OpKind::Random(RandomKind::Dropout(p), _, _) if !(0.0..1.0).contains(p) => {
+            Err(OpError::InvalidProbability(*p))
+        }
+        OpKind::Unary(_)
+        | OpKind::Neg
+        | OpKind::Random(_, _, _)
+        | OpKind::MinMaxScalar(_, _)
+        | OpKind::Apply(_, _) => Ok(Layout::from_shape(inputs[0].shape(), 0)),
+        OpKind::Rolling(_, window, axis) => {
+            let mut shape = inputs[0].shape().to_vec();
+
+            if *axis >= shape.len() {
+                return Err(OpError::OutOfBoundAxes);
+            }
+
+            if *window == 0 || *window > shape[*axis] {
+                return Err(OpError::InvalidWindow(*window, shape[*axis]));
+            }
+
+            shape[*axis] -= window - 1;
+
+            Ok(Layout::from_shape(&shape, 0))
+        }
+        OpKind::Diff(n, axis) => {
+            let mut shape = inputs[0].shape().to_vec();
+
+            if *axis >= shape.len() {
+                return Err(OpError::OutOfBoundAxes);
+            }
+
+            if *n == 0 || *n >= shape[*axis] {
+                return Err(OpError::InvalidDiffOrder(*n, shape[*axis]));
+            }
+
+            shape[*axis] -= n;
+
+            Ok(Layout::from_shape(&shape, 0))
+        }
+        OpKind::Composite { shape, windows, .. } => {
+            debug_assert_eq!(windows.len(), inputs.len());
+
+            for (index, (window, input)) in windows.iter().zip(inputs).enumerate() {
+                if window.shape() != input.shape() {
+                    return Err(OpError::PlacementShape(
+                        index,
+                        window.shape().into(),
+                        input.shape().into(),
+                    ));
+                }
+            }
+
+            Ok(Layout::from_shape(shape, 0))
+        }
+        OpKind::Concat { axis, n_inputs } => {
+            debug_assert_eq!(*n_inputs, inputs.len());
+
+            let Some(first) = inputs.first() else {
+                return Err(OpError::NothingToConcat);
+            };
+
+            if *axis >= first.shape().len() {
+                return Err(OpError::OutOfBoundAxes);
+            }
+
+            let mut shape = first.shape().to_vec();
+
+            for input in &inputs[1..] {
+                let matches = input.shape().len() == shape.len()
+                    && (input.shape().iter().zip(first.shape()))
+                        .enumerate()
+                        .all(|(dim, (a, b))| dim == *axis || a == b);
+
+                if !matches {
+                    return Err(OpError::NotSameShape(
+                        first.shape().into(),
+                        input.shape().into(),
+                    ));
+                }
+
+                shape[*axis] += input.shape()[*axis];
+            }
+
+            Ok(Layout::from_shape(&shape, 0))
+        }
     }
 }
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use crate::tensor::errors::OpError;
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::ops::def_op::{OpKind, Reduction};
+
+/// Names of the axes of a tensor, in order.
+pub type AxisNameList = Arc<[Box<str>]>;
+
+// Elementwise ops keep the names of the lhs, or of the rhs if the lhs has none.
+// Ops that reshape the tensor, like views, drop them.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+pub fn compute_names<T: Copy>(
+    op: &OpKind<T>,
+    inputs: &[Option<&AxisNameList>],
+    output: &Layout,
+) -> Option<AxisNameList> {
+    let first = inputs.first().copied().flatten();
+
+    match op {
+        OpKind::ScalarOp(_)
+        | OpKind::FusedScalar(_)
+        | OpKind::NoOp
+        | OpKind::Assert(_, _)
+        | OpKind::AsContiguous
+        | OpKind::Flip(_)
+        | OpKind::Repeat(_, _)
+        | OpKind::Concat { .. }
+        | OpKind::Normalize(_, _)
+        | OpKind::Unary(_)
+        | OpKind::Neg
+        | OpKind::Apply(_, _)
+        | OpKind::Random(_, _, _)
+        | OpKind::MinMaxScalar(_, _)
+        | OpKind::Rolling(_, _, _)
+        | OpKind::Diff(_, _) => first.cloned(),
+        OpKind::Add
+        | OpKind::Sub
+        | OpKind::Mul
+        | OpKind::Div
+        | OpKind::MinMax(_)
+        | OpKind::MSELoss(Reduction::None)
+        | OpKind::MAELoss(Reduction::None) => first.or(inputs[1]).cloned(),
+        // The leading axes added by a scalar with more axes have no name.
+        OpKind::DynScalarOp(_, scalar_idx, _) => inputs[1 - scalar_idx]
+            .filter(|names| names.len() == output.shape().len())
+            .cloned(),
+        OpKind::Transpose => first.map(|names| names.iter().rev().cloned().collect()),
+        OpKind::TransposeAxes(_, axes) => {
+            first.map(|names| axes.iter().map(|&axis| names[axis].clone()).collect())
+        }
+        OpKind::Reduce(_, axis) | OpKind::Trapz(_, axis) | OpKind::TrapzX(axis) => {
+            first.filter(|names| names.len() > 1).map(|names| {
+                names
+                    .iter()
+                    .enumerate()
+                    .filter(|(dim, _)| dim != axis)
+                    .map(|(_, name)| name.clone())
+                    .collect()
+            })
+        }
+        // The leading axis of several quantiles has no name.
+        OpKind::Quantile(q, Some(axis), _, _) if q.len() == 1 => {
+            first.filter(|names| names.len() > 1).map(|names| {
+                names
+                    .iter()
+                    .enumerate()
+                    .filter(|(dim, _)| dim != axis)
+                    .map(|(_, name)| name.clone())
+                    .collect()
+            })
+        }
+        // Tiling with more repeats than axes adds unnamed leading axes, and slicing
+        // may remove or add axes.
+        OpKind::Tile(_) | OpKind::Slice(_) => first
+            .filter(|names| names.len() == output.shape().len())
+            .cloned(),
+        OpKind::View(_)
+        | OpKind::Matmul
+        | OpKind::Outer
+        | OpKind::Inner
+        | OpKind::BatchMatMul
+        | OpKind::Composite { .. }
+        | OpKind::MSELoss(_)
+        | OpKind::MAELoss(_)
+        | OpKind::Quantile(_, _, _, _)
+        | OpKind::Cast(_) => None,
+    }
+}
+
+// Index of the axis called `name`, or an error listing the existing names.
+pub(crate) fn resolve_axis(names: Option<&AxisNameList>, name: &str) -> Result<usize, OpError> {
+    let position = names.and_then(|names| names.iter().position(|axis| &**axis == name));
+
+    position.ok_or_else(|| {
+        OpError::UnknownAxisName(
+            name.into(),
+            names.map(|names| names.to_vec()).unwrap_or_default().into(),
+        )
+    })
+}
+
+// Checks that there is one distinct name per axis.
+pub(crate) fn validate_names(names: &[&str], ndim: usize) -> Result<AxisNameList, OpError> {
+    if names.len() != ndim {
+        return Err(OpError::NotEnoughAxes(ndim, names.len()));
+    }
+
+    for (i, name) in names.iter().enumerate() {
+        if names[..i].contains(name) {
+            return Err(OpError::RepeatedAxisName((*name).into()));
+        }
+    }
+
+    Ok(names.iter().map(|&name| name.into()).collect())
+}
+
+// Adds the axis names of the inputs to a shape mismatch between them,
+// so it reads "expected [batch=32], but got [batch=64]".
+pub(crate) fn name_shape_error(
+    err: OpError,
+    inputs: &[&Layout],
+    names: &[Option<&AxisNameList>],
+) -> OpError {
+    let [lhs, rhs] = names else {
+        return err;
+    };
+    let (lhs, rhs) = (lhs.or(*rhs), rhs.or(*lhs));
+
+    match err {
+        OpError::NotSameShape(_, _) if lhs.is_some() => {
+            let label = |layout: &Layout, names: Option<&AxisNameList>| {
+                layout
+                    .shape()
+                    .iter()
+                    .enumerate()
+                    .map(|(axis, &len)| {
+                        let name = names
+                            .and_then(|names| names.get(axis))
+                            .map_or("_", |name| &**name);
+
+                        (name.into(), len)
+                    })
+                    .collect()
+            };
+
+            OpError::NotSameNamedShape(label(inputs[0], lhs), label(inputs[1], rhs))
+        }
+        _ => err,
+    }
+}
@@ -1,16 +1,22 @@
-use std::ops::{Add, Div, Mul, Sub};
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 use crate::cfg_debug_only;
 use crate::tensor::definitions::NumberLike;
 use crate::tensor::errors::OpError;
-use crate::tensor::graph::NodeKind;
+use crate::tensor::graph::{NodeKind, device_over, op_error};
 use crate::tensor::mem_formats::layout::Layout;
-use crate::tensor::mem_formats::slice::SliceRange;
-use crate::tensor::ops::ComputeWrapperSpec;
+use crate::tensor::mem_formats::slice::{SliceKind, StepSliceRange};
 use crate::tensor::ops::compute_layout;
-use crate::tensor::ops::def_op::{OpKind, OpKindScalar};
+use crate::tensor::ops::def_op::{
+    AssertKind, MinMaxKind, NanPolicy, OpKind, OpKindScalar, QuantileMethod, RandomKind,
+    ReduceKind, RollKind, ScalarKind, UnaryKind,
+};
+use crate::tensor::ops::impl_names::{AxisNameList, resolve_axis};
+use crate::tensor::ops::{ApplyFn, ApplyOptions, ComputeWrapperSpec};
+use crate::tensor::shape_check::ShapePattern;
 use crate::tensor::traits::Promising;
-use crate::tensor::{CachedTensorPromise, Tensor, TensorPromise};
+use crate::tensor::{CachedTensorPromise, ConsumableTensor, Tensor, TensorPromise};
 
 //////////////////////////////////////////////////////////////
 
@@ -19,6 +25,33 @@ trait ComputationDef {
 
     fn create_node(&self) -> NodeKind<Self::Output>;
     fn layout(&self) -> &Layout;
+    fn names(&self) -> Option<&AxisNameList>;
+}
+
+// Lets the methods taking `self` by value, like the ones of ConsumableTensor, and the ones
+// taking it by reference share their bodies.
+impl<D: ComputationDef> ComputationDef for &D {
+    type Output = D::Output;
+
+    fn create_node(&self) -> NodeKind<Self::Output> {
+        (**self).create_node()
+    }
+
+    fn layout(&self) -> &Layout {
+        (**self).layout()
+    }
+
+    fn names(&self) -> Option<&AxisNameList> {
+        (**self).names()
+    }
+}
+
+// Positions of the named axes, in the given order.
+fn resolve_axes<D: ComputationDef>(source: &D, names: &[&str]) -> Result<Box<[usize]>, OpError> {
+    names
+        .iter()
+        .map(|name| resolve_axis(source.names(), name))
+        .collect()
 }
 
 //////////////////////////////////////////////////////////////
@@ -46,13 +79,34 @@ where
     ))
 }
 
-fn slice_impl<D>(source: &D, range: &[SliceRange]) -> Result<TensorPromise<D::Output>, OpError>
+fn slice_impl<D>(source: &D, range: &[SliceKind]) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    slice_with_layout(source, source.layout().slice(range))
+}
+
+fn step_slice_impl<D>(
+    source: &D,
+    range: &[StepSliceRange],
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    slice_with_layout(source, source.layout().step_slice(range))
+}
+
+fn slice_with_layout<D>(
+    source: &D,
+    layout: Result<Layout, OpError>,
+) -> Result<TensorPromise<D::Output>, OpError>
 where
     D: ComputationDef,
     D::Output: NumberLike,
 {
     let input = Box::new([source.create_node()]);
-    let layout = source.layout().slice(range);
 
     cfg_debug_only!({
         if let Err(err) = layout {
@@ -69,6 +123,26 @@ where
     ))
 }
 
+// A View reading the input through `layout`, computed from the layout of the input,
+// which does not need it to be contiguous.
+fn view_with_layout<D>(
+    source: &D,
+    layout: Result<Layout, OpError>,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+    let layout = layout?;
+
+    Ok(TensorPromise::with_layout(
+        OpKind::View(layout.clone()),
+        input,
+        layout,
+    ))
+}
+
 fn transpose_impl<D>(source: &D) -> TensorPromise<D::Output>
 where
     D: ComputationDef,
@@ -96,12 +170,66 @@ where
     let layout = unsafe { layout.unwrap_unchecked() };
 
     Ok(TensorPromise::with_layout(
-        OpKind::TransposeAxes(layout.clone()),
+        OpKind::TransposeAxes(layout.clone(), axes.into()),
         input,
         layout,
     ))
 }
 
+fn swap_axes_impl<D>(
+    source: &D,
+    axis1: usize,
+    axis2: usize,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let rank = source.layout().shape().len();
+    if axis1 >= rank || axis2 >= rank {
+        return Err(OpError::OutOfBoundAxes);
+    }
+
+    let mut axes: Vec<usize> = (0..rank).collect();
+    axes.swap(axis1, axis2);
+
+    transpose_axes_impl(source, &axes)
+}
+
+fn flip_impl<D>(source: &D, axes: &[usize]) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Flip(axes.into()), input)
+}
+
+fn tile_impl<D>(source: &D, repeats: &[usize]) -> TensorPromise<D::Output>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    unsafe { TensorPromise::new(OpKind::Tile(repeats.into()), input).unwrap_unchecked() }
+}
+
+fn repeat_impl<D>(
+    source: &D,
+    repeats: usize,
+    axis: usize,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Repeat(axis, repeats), input)
+}
+
 fn as_contiguous_impl<D>(source: &D) -> TensorPromise<D::Output>
 where
     D: ComputationDef,
@@ -112,6 +240,240 @@ where
     unsafe { TensorPromise::new(OpKind::AsContiguous, input).unwrap_unchecked() }
 }
 
+fn normalize_impl<D>(
+    source: &D,
+    axis: usize,
+    eps: D::Output,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike + PartialOrd,
+{
+    // Compared with partial_cmp so a NaN eps, which is not greater than zero, is rejected.
+    if eps.partial_cmp(&D::Output::default()) != Some(Ordering::Greater) {
+        return Err(OpError::NonPositiveEpsilon);
+    }
+
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Normalize(axis, eps), input)
+}
+
+fn reduce_impl<D>(
+    source: &D,
+    kind: ReduceKind,
+    axis: usize,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Reduce(kind, axis), input)
+}
+
+fn rolling_impl<D>(
+    source: &D,
+    kind: RollKind,
+    window: usize,
+    axis: usize,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Rolling(kind, window, axis), input)
+}
+
+fn quantile_impl<D>(
+    source: &D,
+    qs: &[f64],
+    axis: Option<usize>,
+    method: QuantileMethod,
+    nan_policy: NanPolicy,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Quantile(qs.into(), axis, method, nan_policy), input)
+}
+
+fn diff_impl<D>(source: &D, n: usize, axis: usize) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Diff(n, axis), input)
+}
+
+fn trapz_impl<D>(
+    source: &D,
+    dx: D::Output,
+    axis: usize,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Trapz(dx, axis), input)
+}
+
+fn trapz_x_impl<D>(
+    source: &D,
+    x: &Tensor<D::Output>,
+    axis: usize,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike + ComputeWrapperSpec,
+{
+    let inputs = Box::new([source.create_node(), x.create_node()]);
+
+    TensorPromise::new(OpKind::TrapzX(axis), inputs)
+}
+
+fn outer_impl<D1, D2>(lhs: &D1, rhs: &D2) -> Result<TensorPromise<D1::Output>, OpError>
+where
+    D1: ComputationDef,
+    D2: ComputationDef<Output = D1::Output>,
+    D1::Output: NumberLike,
+{
+    TensorPromise::new(OpKind::Outer, [lhs.create_node(), rhs.create_node()].into())
+}
+
+fn inner_impl<D1, D2>(lhs: &D1, rhs: &D2) -> Result<TensorPromise<D1::Output>, OpError>
+where
+    D1: ComputationDef,
+    D2: ComputationDef<Output = D1::Output>,
+    D1::Output: NumberLike,
+{
+    TensorPromise::new(OpKind::Inner, [lhs.create_node(), rhs.create_node()].into())
+}
+
+fn bmm_impl<D1, D2>(lhs: &D1, rhs: &D2) -> Result<TensorPromise<D1::Output>, OpError>
+where
+    D1: ComputationDef,
+    D2: ComputationDef<Output = D1::Output>,
+    D1::Output: NumberLike,
+{
+    TensorPromise::new(
+        OpKind::BatchMatMul,
+        [lhs.create_node(), rhs.create_node()].into(),
+    )
+}
+
+fn unary_impl<D>(source: &D, kind: UnaryKind) -> TensorPromise<D::Output>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    unsafe { TensorPromise::new(OpKind::Unary(kind), input).unwrap_unchecked() }
+}
+
+// `scalar / x` as the reciprocal of x, scaled by the scalar unless it is 1.
+fn rdiv_scalar_impl<D>(scalar: f64, source: &D) -> TensorPromise<f64>
+where
+    D: ComputationDef<Output = f64>,
+{
+    let recip = unary_impl(source, UnaryKind::Recip);
+
+    if scalar == 1.0 {
+        recip
+    } else {
+        mul_scalar_impl(&recip, scalar)
+    }
+}
+
+fn neg_impl<D>(source: &D) -> TensorPromise<D::Output>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    unsafe { TensorPromise::new(OpKind::Neg, input).unwrap_unchecked() }
+}
+
+fn random_impl<D>(
+    source: &D,
+    kind: RandomKind,
+    seed: u64,
+) -> Result<TensorPromise<D::Output>, OpError>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    TensorPromise::new(OpKind::Random(kind, seed, 0), input)
+}
+
+fn assert_impl<D>(source: &D, kind: AssertKind<D::Output>, label: &str) -> TensorPromise<D::Output>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    unsafe { TensorPromise::new(OpKind::Assert(kind, label.into()), input).unwrap_unchecked() }
+}
+
+fn apply_impl<D>(
+    source: &D,
+    f: ApplyFn<D::Output>,
+    options: ApplyOptions,
+) -> TensorPromise<D::Output>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    unsafe { TensorPromise::new(OpKind::Apply(f, options), input).unwrap_unchecked() }
+}
+
+fn min_max_impl<D1, D2>(
+    lhs: &D1,
+    rhs: &D2,
+    kind: MinMaxKind,
+) -> Result<TensorPromise<D1::Output>, OpError>
+where
+    D1: ComputationDef,
+    D2: ComputationDef<Output = D1::Output>,
+    D1::Output: NumberLike,
+{
+    TensorPromise::new(
+        OpKind::MinMax(kind),
+        [lhs.create_node(), rhs.create_node()].into(),
+    )
+}
+
+fn min_max_scalar_impl<D>(
+    source: &D,
+    kind: MinMaxKind,
+    scalar: D::Output,
+) -> TensorPromise<D::Output>
+where
+    D: ComputationDef,
+    D::Output: NumberLike,
+{
+    let input = Box::new([source.create_node()]);
+
+    unsafe { TensorPromise::new(OpKind::MinMaxScalar(kind, scalar), input).unwrap_unchecked() }
+}
+
 //////////////////////////////////////////////////////////////
 
 fn add_scalar_impl<D>(lhs: &D, rhs: D::Output) -> TensorPromise<D::Output>
@@ -172,80 +534,104 @@ where
 
 //////////////////////////////////////////////////////////////
 
-fn add_tensor_impl<D1, D2>(lhs: &D1, rhs: &D2) -> TensorPromise<D1::Output>
+// When one of the operands has a single element and the shapes differ, the op is lowered
+// into a scalar op that reads the scalar at compute time, so it never needs to be broadcasted.
+// The right operand is preferred as the scalar when both have a single element.
+fn dyn_scalar_impl<D1, D2>(
+    lhs: &D1,
+    rhs: &D2,
+    kind: ScalarKind,
+) -> Option<TensorPromise<D1::Output>>
 where
     D1: ComputationDef,
     D2: ComputationDef<Output = D1::Output>,
     D1::Output: Copy + ComputeWrapperSpec,
 {
-    let layout = compute_layout(&OpKind::<D1::Output>::Add, &[lhs.layout(), rhs.layout()]);
-
-    if let Err(err) = layout {
-        panic!("{}", err);
+    if lhs.layout().shape() == rhs.layout().shape() {
+        return None;
     }
 
-    TensorPromise::with_layout(
-        OpKind::Add,
+    let scalar_idx = if rhs.layout().len() == 1 {
+        1
+    } else if lhs.layout().len() == 1 {
+        0
+    } else {
+        return None;
+    };
+
+    let promise = TensorPromise::new(
+        OpKind::DynScalarOp(kind, scalar_idx, Box::new([])),
         [lhs.create_node(), rhs.create_node()].into(),
-        unsafe { layout.unwrap_unchecked() },
-    )
+    );
+
+    Some(unsafe { promise.unwrap_unchecked() })
 }
 
-fn sub_tensor_impl<D1, D2>(lhs: &D1, rhs: &D2) -> TensorPromise<D1::Output>
+// Elementwise op between two tensors or promises of the same shape.
+fn binary_tensor_impl<D1, D2>(
+    lhs: &D1,
+    rhs: &D2,
+    op: OpKind<D1::Output>,
+    scalar_kind: ScalarKind,
+) -> Result<TensorPromise<D1::Output>, OpError>
 where
     D1: ComputationDef,
     D2: ComputationDef<Output = D1::Output>,
-    D1::Output: Copy + ComputeWrapperSpec,
+    D1::Output: NumberLike + ComputeWrapperSpec,
 {
-    let layout = compute_layout(&OpKind::<D1::Output>::Sub, &[lhs.layout(), rhs.layout()]);
-
-    if let Err(err) = layout {
-        panic!("{}", err);
+    if let Some(promise) = dyn_scalar_impl(lhs, rhs, scalar_kind) {
+        return Ok(promise);
     }
 
-    TensorPromise::with_layout(
-        OpKind::Sub,
-        [lhs.create_node(), rhs.create_node()].into(),
-        unsafe { layout.unwrap_unchecked() },
-    )
+    let inputs: Box<[NodeKind<D1::Output>]> = [lhs.create_node(), rhs.create_node()].into();
+
+    let checked = compute_layout(&op, &[lhs.layout(), rhs.layout()])
+        .and_then(|layout| device_over(&op, &inputs).map(|_| layout));
+
+    match checked {
+        Ok(layout) => Ok(TensorPromise::with_layout(op, inputs, layout)),
+        Err(err) => Err(op_error(err, &op, &inputs)),
+    }
 }
 
-fn mul_tensor_impl<D1, D2>(lhs: &D1, rhs: &D2) -> TensorPromise<D1::Output>
+fn add_tensor_impl<D1, D2>(lhs: &D1, rhs: &D2) -> TensorPromise<D1::Output>
 where
     D1: ComputationDef,
     D2: ComputationDef<Output = D1::Output>,
-    D1::Output: Copy + ComputeWrapperSpec,
+    D1::Output: NumberLike + ComputeWrapperSpec,
 {
-    let layout = compute_layout(&OpKind::<D1::Output>::Mul, &[lhs.layout(), rhs.layout()]);
+    binary_tensor_impl(lhs, rhs, OpKind::Add, ScalarKind::Sum)
+        .unwrap_or_else(|err| panic!("{}", err))
+}
 
-    if let Err(err) = layout {
-        panic!("{}", err);
-    }
+fn sub_tensor_impl<D1, D2>(lhs: &D1, rhs: &D2) -> TensorPromise<D1::Output>
+where
+    D1: ComputationDef,
+    D2: ComputationDef<Output = D1::Output>,
+    D1::Output: NumberLike + ComputeWrapperSpec,
+{
+    binary_tensor_impl(lhs, rhs, OpKind::Sub, ScalarKind::Sub)
+        .unwrap_or_else(|err| panic!("{}", err))
+}
 
-    TensorPromise::with_layout(
-        OpKind::Mul,
-        [lhs.create_node(), rhs.create_node()].into(),
-        unsafe { layout.unwrap_unchecked() },
-    )
+fn mul_tensor_impl<D1, D2>(lhs: &D1, rhs: &D2) -> TensorPromise<D1::Output>
+where
+    D1: ComputationDef,
+    D2: ComputationDef<Output = D1::Output>,
+    D1::Output: NumberLike + ComputeWrapperSpec,
+{
+    binary_tensor_impl(lhs, rhs, OpKind::Mul, ScalarKind::Mul)
+        .unwrap_or_else(|err| panic!("{}", err))
 }
 
 fn div_tensor_impl<D1, D2>(lhs: &D1, rhs: &D2) -> TensorPromise<D1::Output>
 where
     D1: ComputationDef,
     D2: ComputationDef<Output = D1::Output>,
-    D1::Output: Copy + ComputeWrapperSpec,
+    D1::Output: NumberLike + ComputeWrapperSpec,
 {
-    let layout = compute_layout(&OpKind::<D1::Output>::Div, &[lhs.layout(), rhs.layout()]);
-
-    if let Err(err) = layout {
-        panic!("{}", err);
-    }
-
-    TensorPromise::with_layout(
-        OpKind::Div,
-        [lhs.create_node(), rhs.create_node()].into(),
-        unsafe { layout.unwrap_unchecked() },
-    )
+    binary_tensor_impl(lhs, rhs, OpKind::Div, ScalarKind::Div)
+        .unwrap_or_else(|err| panic!("{}", err))
 }
 
 //////////////////////////////////////////////////////////////
@@ -265,6 +651,10 @@ macro_rules! impl_computation_def {
             fn layout(&self) -> &Layout {
                 self.graph.layout()
             }
+
+            fn names(&self) -> Option<&AxisNameList> {
+                self.graph.names()
+            }
         }
     };
 }
@@ -273,13 +663,35 @@ macro_rules! impl_computation_def {
 
 macro_rules! impl_view {
     ($ty:ident) => {
+        impl_view!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
         impl<T> $ty<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             #[inline]
-            pub fn view(&self, shape: &[usize]) -> Result<TensorPromise<T>, OpError> {
-                view_impl(self, shape)
+            pub fn view(self: $recv, shape: &[usize]) -> Result<TensorPromise<T>, OpError> {
+                view_impl(&self, shape)
+            }
+
+            /// Splits the last axis into groups of `k` elements, of shape `[..., n / k, k]`,
+            /// see Layout::group_last_axis().
+            #[inline]
+            pub fn group_last_axis(self: $recv, k: usize) -> Result<TensorPromise<T>, OpError> {
+                view_with_layout(&self, self.layout().group_last_axis(k))
+            }
+
+            /// Merges the last `n` axes into one, see Layout::flatten_last_axes().
+            #[inline]
+            pub fn flatten_last_axes(self: $recv, n: usize) -> Result<TensorPromise<T>, OpError> {
+                view_with_layout(&self, self.layout().flatten_last_axes(n))
+            }
+
+            /// Every element as a single axis, see Layout::flatten().
+            #[inline]
+            pub fn flatten(self: $recv) -> Result<TensorPromise<T>, OpError> {
+                view_with_layout(&self, self.layout().flatten())
             }
         }
     };
@@ -287,27 +699,43 @@ macro_rules! impl_view {
 
 macro_rules! impl_slice {
     ($ty:ident) => {
+        impl_slice!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
         impl<T> $ty<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
+            /// Slices with the entries of the [`s!`](crate::s) macro, see [`SliceKind`].
             #[inline]
-            pub fn slice(&self, shape: &[SliceRange]) -> Result<TensorPromise<T>, OpError> {
-                slice_impl(self, shape)
+            pub fn slice(self: $recv, shape: &[SliceKind]) -> Result<TensorPromise<T>, OpError> {
+                slice_impl(&self, shape)
+            }
+
+            /// Slices with a step per axis, see [`StepSliceRange`] and the [`ss!`](crate::ss) macro.
+            #[inline]
+            pub fn step_slice(
+                self: $recv,
+                shape: &[StepSliceRange],
+            ) -> Result<TensorPromise<T>, OpError> {
+                step_slice_impl(&self, shape)
             }
         }
     };
 }
 
 macro_rules! impl_transpose {
-    ($ty: ident) => {
+    ($ty:ident) => {
+        impl_transpose!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
         impl<T> $ty<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             #[inline]
-            pub fn transpose(&self) -> TensorPromise<T> {
-                transpose_impl(self)
+            pub fn transpose(self: $recv) -> TensorPromise<T> {
+                transpose_impl(&self)
             }
         }
     };
@@ -315,142 +743,725 @@ macro_rules! impl_transpose {
 
 macro_rules! impl_transpose_axes {
     ($ty:ident) => {
+        impl_transpose_axes!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
         impl<T> $ty<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             #[inline]
-            pub fn transpose_axes(&self, axes: &[usize]) -> Result<TensorPromise<T>, OpError> {
-                transpose_axes_impl(self, axes)
+            pub fn transpose_axes(
+                self: $recv,
+                axes: &[usize],
+            ) -> Result<TensorPromise<T>, OpError> {
+                transpose_axes_impl(&self, axes)
+            }
+
+            /// Swaps two axes, leaving the others in place. Like transpose_axes(), it is a view.
+            pub fn swap_axes(
+                self: $recv,
+                axis1: usize,
+                axis2: usize,
+            ) -> Result<TensorPromise<T>, OpError> {
+                swap_axes_impl(&self, axis1, axis2)
+            }
+
+            /// Same as transpose_axes(), with the new order of the axes given by their names.
+            pub fn permute_named(self: $recv, names: &[&str]) -> Result<TensorPromise<T>, OpError> {
+                transpose_axes_impl(&self, &resolve_axes(&self, names)?)
             }
         }
     };
 }
 
-macro_rules! impl_as_contiguous {
-    ($ty: ident) => {
+macro_rules! impl_flip {
+    ($ty:ident) => {
+        impl_flip!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
         impl<T> $ty<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             #[inline]
-            pub fn as_contiguous(&self) -> TensorPromise<T> {
-                as_contiguous_impl(self)
+            pub fn flip(self: $recv, axes: &[usize]) -> Result<TensorPromise<T>, OpError> {
+                flip_impl(&self, axes)
             }
         }
     };
 }
 
-macro_rules! impl_reshape_like {
+macro_rules! impl_tile {
     ($ty:ident) => {
-        impl_view!($ty);
-        impl_slice!($ty);
-        impl_transpose!($ty);
-        impl_transpose_axes!($ty);
-        impl_as_contiguous!($ty);
+        impl_tile!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        impl<T> $ty<T>
+        where
+            T: NumberLike + ComputeWrapperSpec,
+        {
+            /// Repeats the whole tensor `repeats[i]` times along the axis `i`, like numpy.tile.
+            /// If `repeats` and the shape have different lengths, the shorter one
+            /// is padded with leading ones.
+            #[inline]
+            pub fn tile(self: $recv, repeats: &[usize]) -> TensorPromise<T> {
+                tile_impl(&self, repeats)
+            }
+
+            /// Repeats every element `repeats` times along `axis`, like numpy.repeat.
+            #[inline]
+            pub fn repeat(
+                self: $recv,
+                repeats: usize,
+                axis: usize,
+            ) -> Result<TensorPromise<T>, OpError> {
+                repeat_impl(&self, repeats, axis)
+            }
+        }
     };
 }
-//////////////////////////////////////////////////////////////
 
-macro_rules! impl_add_scalar {
+macro_rules! impl_as_contiguous {
     ($ty:ident) => {
-        impl<T> Add<T> for &$ty<T>
+        impl_as_contiguous!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        impl<T> $ty<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
-            type Output = TensorPromise<T>;
+            #[inline]
+            pub fn as_contiguous(self: $recv) -> TensorPromise<T> {
+                as_contiguous_impl(&self)
+            }
+        }
+    };
+}
 
+macro_rules! impl_normalize {
+    ($ty:ident) => {
+        impl_normalize!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        impl<T> $ty<T>
+        where
+            T: NumberLike + ComputeWrapperSpec + PartialOrd,
+        {
+            /// Computes `(x - mean) / sqrt(var + eps)` for every lane along `axis`
+            /// in a single op, without materializing the mean or the variance.
+            /// The variance is the biased one, so lanes of length 1 produce zeros.
             #[inline]
-            fn add(self, rhs: T) -> Self::Output {
-                add_scalar_impl(self, rhs)
+            pub fn normalize(
+                self: $recv,
+                axis: usize,
+                eps: T,
+            ) -> Result<TensorPromise<T>, OpError> {
+                normalize_impl(&self, axis, eps)
+            }
+
+            #[inline]
+            pub fn normalize_named(
+                self: $recv,
+                name: &str,
+                eps: T,
+            ) -> Result<TensorPromise<T>, OpError> {
+                normalize_impl(&self, self.axis_index(name)?, eps)
+            }
+        }
+    };
+}
+
+macro_rules! impl_reduce {
+    ($ty:ident) => {
+        impl_reduce!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        // Only the real kernel compares with 0, see cpu_compute_reduce_f64().
+        impl $ty<f64> {
+            /// 1 where any element along `axis` is nonzero, 0 otherwise.
+            /// The axis is removed from the shape and an empty lane produces 0.
+            #[inline]
+            pub fn any(self: $recv, axis: usize) -> Result<TensorPromise<f64>, OpError> {
+                reduce_impl(&self, ReduceKind::Any, axis)
+            }
+
+            /// 1 where every element along `axis` is nonzero, 0 otherwise.
+            /// The axis is removed from the shape and an empty lane produces 1.
+            #[inline]
+            pub fn all(self: $recv, axis: usize) -> Result<TensorPromise<f64>, OpError> {
+                reduce_impl(&self, ReduceKind::All, axis)
+            }
+
+            #[inline]
+            pub fn any_named(self: $recv, name: &str) -> Result<TensorPromise<f64>, OpError> {
+                reduce_impl(&self, ReduceKind::Any, self.axis_index(name)?)
+            }
+
+            #[inline]
+            pub fn all_named(self: $recv, name: &str) -> Result<TensorPromise<f64>, OpError> {
+                reduce_impl(&self, ReduceKind::All, self.axis_index(name)?)
+            }
+        }
+    };
+}
+
+// Windows always fit in the axis, there is no min_periods-like padding of the edges.
+macro_rules! impl_rolling {
+    ($ty:ident) => {
+        impl_rolling!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        impl<T> $ty<T>
+        where
+            T: NumberLike + ComputeWrapperSpec + PartialOrd,
+        {
+            /// Sum of every `window` consecutive elements along `axis`,
+            /// which shrinks to `len - window + 1`. A NaN in the window produces NaN.
+            #[inline]
+            pub fn rolling_sum(
+                self: $recv,
+                window: usize,
+                axis: usize,
+            ) -> Result<TensorPromise<T>, OpError> {
+                rolling_impl(&self, RollKind::Sum, window, axis)
+            }
+
+            /// Mean of every `window` consecutive elements along `axis`,
+            /// which shrinks to `len - window + 1`. A NaN in the window produces NaN.
+            #[inline]
+            pub fn rolling_mean(
+                self: $recv,
+                window: usize,
+                axis: usize,
+            ) -> Result<TensorPromise<T>, OpError> {
+                rolling_impl(&self, RollKind::Mean, window, axis)
+            }
+
+            /// Maximum of every `window` consecutive elements along `axis`,
+            /// which shrinks to `len - window + 1`. A NaN in the window produces NaN.
+            #[inline]
+            pub fn rolling_max(
+                self: $recv,
+                window: usize,
+                axis: usize,
+            ) -> Result<TensorPromise<T>, OpError> {
+                rolling_impl(&self, RollKind::Max, window, axis)
             }
         }
+    };
+}
 
-        impl<T> Add<T> for $ty<T>
+// Quantiles are computed like numpy.quantile, with the same defaults.
+macro_rules! impl_quantile {
+    ($ty:ident) => {
+        impl_quantile!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        impl<T> $ty<T>
+        where
+            T: NumberLike + ComputeWrapperSpec + PartialOrd,
+        {
+            /// The `qs` quantiles of every lane along `axis`, which is removed, or of the
+            /// whole tensor if `axis` is None. Quantiles falling between two elements are
+            /// interpolated linearly, and a lane with a NaN produces NaN, see quantile_with().
+            ///
+            /// Asking for more than one quantile adds a leading axis of length `qs.len()`,
+            /// so `out[k]` holds the `qs[k]` quantiles. Every quantile must be within [0, 1].
+            #[inline]
+            pub fn quantile(
+                self: $recv,
+                qs: &[f64],
+                axis: Option<usize>,
+            ) -> Result<TensorPromise<T>, OpError> {
+                quantile_impl(
+                    &self,
+                    qs,
+                    axis,
+                    QuantileMethod::Linear,
+                    NanPolicy::Propagate,
+                )
+            }
+
+            /// Same as quantile(), choosing how quantiles between two elements are computed
+            /// and what to do with NaNs.
+            #[inline]
+            pub fn quantile_with(
+                self: $recv,
+                qs: &[f64],
+                axis: Option<usize>,
+                method: QuantileMethod,
+                nan_policy: NanPolicy,
+            ) -> Result<TensorPromise<T>, OpError> {
+                quantile_impl(&self, qs, axis, method, nan_policy)
+            }
+
+            /// Same as quantile(), with the quantiles given as percentages within [0, 100].
+            #[inline]
+            pub fn percentile(
+                self: $recv,
+                ps: &[f64],
+                axis: Option<usize>,
+            ) -> Result<TensorPromise<T>, OpError> {
+                let qs: Vec<f64> = ps.iter().map(|p| p / 100.0).collect();
+                quantile_impl(
+                    &self,
+                    &qs,
+                    axis,
+                    QuantileMethod::Linear,
+                    NanPolicy::Propagate,
+                )
+            }
+
+            /// The 0.5 quantile, see quantile().
+            #[inline]
+            pub fn median(self: $recv, axis: Option<usize>) -> Result<TensorPromise<T>, OpError> {
+                self.quantile(&[0.5], axis)
+            }
+        }
+    };
+}
+
+macro_rules! impl_diff_trapz {
+    ($ty:ident) => {
+        impl_diff_trapz!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        // The lane kernels only exist for real elements.
+        impl $ty<f64> {
+            /// The `n`-th discrete difference along `axis`, `out[i] = x[i + 1] - x[i]`
+            /// applied `n` times. The axis shrinks to `len - n`, so `n` must be at least 1
+            /// and smaller than its length.
+            #[inline]
+            pub fn diff(self: $recv, n: usize, axis: usize) -> Result<TensorPromise<f64>, OpError> {
+                diff_impl(&self, n, axis)
+            }
+
+            /// Integral along `axis` with the trapezoidal rule, for samples `dx` apart.
+            /// The axis is removed.
+            #[inline]
+            pub fn trapz(self: $recv, dx: f64, axis: usize) -> Result<TensorPromise<f64>, OpError> {
+                trapz_impl(&self, dx, axis)
+            }
+
+            /// Integral along `axis` with the trapezoidal rule, for samples at the
+            /// positions in `x`, which must be 1-D and as long as the axis.
+            /// The axis is removed.
+            #[inline]
+            pub fn trapz_x(
+                self: $recv,
+                x: &Tensor<f64>,
+                axis: usize,
+            ) -> Result<TensorPromise<f64>, OpError> {
+                trapz_x_impl(&self, x, axis)
+            }
+        }
+    };
+}
+
+macro_rules! impl_unary {
+    ($ty:ident) => {
+        impl_unary!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        // The kernels only exist for real elements.
+        impl $ty<f64> {
+            /// 1 where the element is NaN, 0 otherwise.
+            #[inline]
+            pub fn isnan(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::IsNan)
+            }
+
+            /// 1 where the element is positive or negative infinity, 0 otherwise.
+            #[inline]
+            pub fn isinf(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::IsInf)
+            }
+
+            /// Square root of every element, NaN for the negative ones.
+            #[inline]
+            pub fn sqrt(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Sqrt)
+            }
+
+            /// `1 / x` for every element. Like the IEEE division, 1 / 0 is infinite.
+            #[inline]
+            pub fn recip(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Recip)
+            }
+
+            /// `1 / sqrt(x)` for every element, within 1 ulp of the exact result, or of
+            /// the accuracy of the numerics mode with the `mkl` backend. Infinite for 0
+            /// and NaN for the negative elements.
+            ///
+            /// Dividing by a square root read by nothing else computes this instead when
+            /// the numerics mode is not HighAccuracy, as `a * rsqrt(b)` rounds differently
+            /// than `a / sqrt(b)`, see set_numerics_mode().
+            #[inline]
+            pub fn rsqrt(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Rsqrt)
+            }
+        }
+    };
+}
+
+// `scalar / tensor`, computed as the reciprocal of the tensor times the scalar, which
+// rounds twice unless the scalar is 1.
+macro_rules! impl_rdiv_scalar {
+    ($ty:ident) => {
+        impl_rdiv_scalar!(&$ty<f64>);
+        impl_rdiv_scalar!($ty<f64>);
+    };
+    ($rhs:ty) => {
+        impl Div<$rhs> for f64 {
+            type Output = TensorPromise<f64>;
+
+            #[inline]
+            fn div(self, rhs: $rhs) -> Self::Output {
+                rdiv_scalar_impl(self, &rhs)
+            }
+        }
+    };
+}
+
+macro_rules! impl_exp_log {
+    ($ty:ident) => {
+        impl_exp_log!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        // The VML functions only exist for real elements.
+        impl $ty<f64> {
+            /// `e^x` for every element.
+            #[inline]
+            pub fn exp(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Exp)
+            }
+
+            /// Natural logarithm of every element, -inf for 0 and NaN for the negative ones.
+            #[inline]
+            pub fn ln(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Ln)
+            }
+
+            /// `e^x - 1` for every element, accurate where `x.exp() - 1.0` cancels to 0
+            /// or to a few bits, for x near 0. See set_exp_log_fusion() to have the latter
+            /// computed as this.
+            #[inline]
+            pub fn expm1(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Expm1)
+            }
+
+            /// `ln(1 + x)` for every element, accurate where `(x + 1.0).ln()` loses the bits
+            /// of x rounded off by the addition, for x near 0. -inf for -1 and NaN below.
+            /// See set_exp_log_fusion() to have the latter computed as this.
+            #[inline]
+            pub fn log1p(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Log1p)
+            }
+
+            /// Base 2 logarithm of every element, exact for the powers of 2.
+            #[inline]
+            pub fn log2(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Log2)
+            }
+
+            /// Base 10 logarithm of every element.
+            #[inline]
+            pub fn log10(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Log10)
+            }
+
+            /// `2^x` for every element, exact for the integers in range.
+            #[inline]
+            pub fn exp2(self: $recv) -> TensorPromise<f64> {
+                unary_impl(&self, UnaryKind::Exp2)
+            }
+        }
+    };
+}
+
+macro_rules! impl_neg {
+    ($ty:ident) => {
+        impl_neg!(&$ty<T>);
+        impl_neg!($ty<T>);
+    };
+    ($self_ty:ty) => {
+        impl<T> Neg for $self_ty
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             type Output = TensorPromise<T>;
 
             #[inline]
-            fn add(self, rhs: T) -> Self::Output {
-                (&self).add(rhs)
+            fn neg(self) -> Self::Output {
+                neg_impl(&self)
             }
         }
     };
 }
 
-macro_rules! impl_sub_scalar {
+// Every element is drawn from the seed and its row-major position alone, so a promise
+// draws the same values on every materialization, however its graph is computed, and the
+// draw of a cached promise is the one it keeps.
+macro_rules! impl_random {
+    ($ty:ident) => {
+        impl_random!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        // The generator only draws real elements.
+        impl $ty<f64> {
+            /// Zeroes every element with probability `p` and scales the others by
+            /// 1 / (1 - p), keeping the expected value of each one. `p` must be within
+            /// [0, 1), and 0 leaves the tensor as it is.
+            #[inline]
+            pub fn dropout(self: $recv, p: f64, seed: u64) -> Result<TensorPromise<f64>, OpError> {
+                random_impl(&self, RandomKind::Dropout(p), seed)
+            }
+
+            /// A tensor of the same shape, uniformly distributed within [0, 1).
+            /// Only the shape is used, although the graph of a promise is still computed.
+            #[inline]
+            pub fn rand_like(self: $recv, seed: u64) -> TensorPromise<f64> {
+                unsafe { random_impl(&self, RandomKind::Uniform, seed).unwrap_unchecked() }
+            }
+
+            /// Same as rand_like(), drawn from the standard normal distribution.
+            #[inline]
+            pub fn randn_like(self: $recv, seed: u64) -> TensorPromise<f64> {
+                unsafe { random_impl(&self, RandomKind::Normal, seed).unwrap_unchecked() }
+            }
+        }
+    };
+}
+
+// The assertions are checked when the graph is materialized, which then fails with
+// OpError::AssertionFailed naming the label, and skipped if set_graph_assertions(false).
+macro_rules! impl_assert {
     ($ty:ident) => {
-        impl<T> Sub<T> for &$ty<T>
+        impl_assert!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        impl<T> $ty<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
-            type Output = TensorPromise<T>;
+            /// The same elements, asserting that none of them is NaN or infinite.
+            #[inline]
+            pub fn assert_finite(self: $recv, label: &str) -> TensorPromise<T> {
+                assert_impl(&self, AssertKind::Finite, label)
+            }
 
+            /// The same elements, asserting that all of them are within `[lo, hi]`.
             #[inline]
-            fn sub(self, rhs: T) -> Self::Output {
-                sub_scalar_impl(self, rhs)
+            pub fn assert_in_range(self: $recv, lo: T, hi: T, label: &str) -> TensorPromise<T>
+            where
+                T: PartialOrd,
+            {
+                assert_impl(&self, AssertKind::InRange(lo, hi), label)
+            }
+
+            /// The same elements, asserting that the shape matches `pattern`.
+            #[inline]
+            pub fn assert_shape(
+                self: $recv,
+                pattern: &ShapePattern,
+                label: &str,
+            ) -> TensorPromise<T> {
+                assert_impl(&self, AssertKind::Shape(pattern.clone()), label)
             }
         }
+    };
+}
 
-        impl<T> Sub<T> for $ty<T>
+// The closures are called when the graph is materialized, over chunks of at most
+// PACKING_BUFFER_SIZE elements in row-major order, and may be called from any thread.
+macro_rules! impl_apply {
+    ($ty:ident) => {
+        impl_apply!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        impl<T> $ty<T>
+        where
+            T: NumberLike + ComputeWrapperSpec + 'static,
+        {
+            /// A tensor of the same shape whose elements are written by `f`, which is given
+            /// a chunk of the input elements and the slice of the same length to write
+            /// their results into. Nothing is fused across it, and it is never folded,
+            /// see apply_with().
+            #[inline]
+            pub fn apply<F>(self: $recv, f: F) -> TensorPromise<T>
+            where
+                F: Fn(&[T], &mut [T]) + Send + Sync + 'static,
+            {
+                apply_impl(&self, ApplyFn::new(f), ApplyOptions::default())
+            }
+
+            /// Same as apply(), with options like whether `f` is pure.
+            #[inline]
+            pub fn apply_with<F>(self: $recv, f: F, options: ApplyOptions) -> TensorPromise<T>
+            where
+                F: Fn(&[T], &mut [T]) + Send + Sync + 'static,
+            {
+                apply_impl(&self, ApplyFn::new(f), options)
+            }
+
+            /// Same as apply(), calling `f` on every element. It is still called from
+            /// a loop over each chunk, not through the graph for every element.
+            #[inline]
+            pub fn apply_scalar<F>(self: $recv, f: F) -> TensorPromise<T>
+            where
+                F: Fn(T) -> T + Send + Sync + 'static,
+            {
+                apply_impl(&self, ApplyFn::from_scalar(f), ApplyOptions::default())
+            }
+
+            /// Same as apply_scalar(), with options like whether `f` is pure.
+            #[inline]
+            pub fn apply_scalar_with<F>(
+                self: $recv,
+                f: F,
+                options: ApplyOptions,
+            ) -> TensorPromise<T>
+            where
+                F: Fn(T) -> T + Send + Sync + 'static,
+            {
+                apply_impl(&self, ApplyFn::from_scalar(f), options)
+            }
+        }
+    };
+}
+
+macro_rules! impl_axis_names {
+    ($ty:ident) => {
+        impl<T> $ty<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
-            type Output = TensorPromise<T>;
+            /// Names of the axes, if they were set with Tensor::with_axis_names()
+            /// and survived the ops since then.
+            #[inline]
+            pub fn names(&self) -> Option<&[Box<str>]> {
+                self.graph.names().map(|names| &**names)
+            }
 
+            /// Index of the axis called `name`.
             #[inline]
-            fn sub(self, rhs: T) -> Self::Output {
-                (&self).sub(rhs)
+            pub fn axis_index(&self, name: &str) -> Result<usize, OpError> {
+                resolve_axis(self.graph.names(), name)
             }
         }
     };
 }
 
-macro_rules! impl_mul_scalar {
+macro_rules! impl_min_max_scalar {
+    ($ty:ident) => {
+        impl_min_max_scalar!($ty, &Self);
+    };
+    ($ty:ident, $recv:ty) => {
+        // Complex numbers are not ordered, only the real kernel exists.
+        impl $ty<f64> {
+            /// Elementwise minimum with `scalar`, NaN where the element or the scalar is NaN.
+            #[inline]
+            pub fn min_scalar(self: $recv, scalar: f64) -> TensorPromise<f64> {
+                min_max_scalar_impl(&self, MinMaxKind::Minimum, scalar)
+            }
+
+            /// Elementwise maximum with `scalar`, NaN where the element or the scalar is NaN.
+            #[inline]
+            pub fn max_scalar(self: $recv, scalar: f64) -> TensorPromise<f64> {
+                min_max_scalar_impl(&self, MinMaxKind::Maximum, scalar)
+            }
+
+            /// Limits every element to `[lo, hi]`, computed as `min(max(x, lo), hi)`,
+            /// so NaNs are kept and `hi` wins if it is smaller than `lo`.
+            #[inline]
+            pub fn clamp(self: $recv, lo: f64, hi: f64) -> TensorPromise<f64> {
+                self.max_scalar(lo).min_scalar(hi)
+            }
+        }
+    };
+}
+
+macro_rules! impl_reshape_like {
+    ($ty:ident $(, $recv:ty)?) => {
+        impl_view!($ty $(, $recv)?);
+        impl_slice!($ty $(, $recv)?);
+        impl_transpose!($ty $(, $recv)?);
+        impl_transpose_axes!($ty $(, $recv)?);
+        impl_flip!($ty $(, $recv)?);
+        impl_tile!($ty $(, $recv)?);
+        impl_as_contiguous!($ty $(, $recv)?);
+    };
+}
+//////////////////////////////////////////////////////////////
+
+macro_rules! impl_add_scalar {
     ($ty:ident) => {
-        impl<T> Mul<T> for &$ty<T>
+        impl_add_scalar!(&$ty<T>);
+        impl_add_scalar!($ty<T>);
+    };
+    ($self_ty:ty) => {
+        impl<T> Add<T> for $self_ty
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             type Output = TensorPromise<T>;
 
             #[inline]
-            fn mul(self, rhs: T) -> Self::Output {
-                mul_scalar_impl(self, rhs)
+            fn add(self, rhs: T) -> Self::Output {
+                add_scalar_impl(&self, rhs)
             }
         }
+    };
+}
 
-        impl<T> Mul<T> for $ty<T>
+macro_rules! impl_sub_scalar {
+    ($ty:ident) => {
+        impl_sub_scalar!(&$ty<T>);
+        impl_sub_scalar!($ty<T>);
+    };
+    ($self_ty:ty) => {
+        impl<T> Sub<T> for $self_ty
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             type Output = TensorPromise<T>;
 
             #[inline]
-            fn mul(self, rhs: T) -> Self::Output {
-                (&self).mul(rhs)
+            fn sub(self, rhs: T) -> Self::Output {
+                sub_scalar_impl(&self, rhs)
             }
         }
     };
 }
 
-macro_rules! impl_div_scalar {
+macro_rules! impl_mul_scalar {
     ($ty:ident) => {
-        impl<T> Div<T> for &$ty<T>
+        impl_mul_scalar!(&$ty<T>);
+        impl_mul_scalar!($ty<T>);
+    };
+    ($self_ty:ty) => {
+        impl<T> Mul<T> for $self_ty
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             type Output = TensorPromise<T>;
 
             #[inline]
-            fn div(self, rhs: T) -> Self::Output {
-                div_scalar_impl(self, rhs)
+            fn mul(self, rhs: T) -> Self::Output {
+                mul_scalar_impl(&self, rhs)
             }
         }
+    };
+}
 
-        impl<T> Div<T> for $ty<T>
+macro_rules! impl_div_scalar {
+    ($ty:ident) => {
+        impl_div_scalar!(&$ty<T>);
+        impl_div_scalar!($ty<T>);
+    };
+    ($self_ty:ty) => {
+        impl<T> Div<T> for $self_ty
         where
             T: NumberLike + ComputeWrapperSpec,
         {
@@ -458,18 +1469,18 @@ macro_rules! impl_div_scalar {
 
             #[inline]
             fn div(self, rhs: T) -> Self::Output {
-                (&self).div(rhs)
+                div_scalar_impl(&self, rhs)
             }
         }
     };
 }
 
 macro_rules! impl_op_scalar {
-    ($ty:ident) => {
-        impl_add_scalar!($ty);
-        impl_sub_scalar!($ty);
-        impl_div_scalar!($ty);
-        impl_mul_scalar!($ty);
+    ($($self_ty:tt)+) => {
+        impl_add_scalar!($($self_ty)+);
+        impl_sub_scalar!($($self_ty)+);
+        impl_div_scalar!($($self_ty)+);
+        impl_mul_scalar!($($self_ty)+);
     };
 }
 
@@ -477,51 +1488,185 @@ macro_rules! impl_op_scalar {
 
 macro_rules! impl_tensor_binop {
     ($trait:ident, $method:ident, $impl_fn:ident, $lhs:ident, $rhs:ident) => {
-        impl<T> $trait<&$rhs<T>> for &$lhs<T>
+        impl_tensor_binop!($trait, $method, $impl_fn, &$lhs<T>, &$rhs<T>);
+        impl_tensor_binop!($trait, $method, $impl_fn, &$lhs<T>, $rhs<T>);
+        impl_tensor_binop!($trait, $method, $impl_fn, $lhs<T>, &$rhs<T>);
+        impl_tensor_binop!($trait, $method, $impl_fn, $lhs<T>, $rhs<T>);
+    };
+    ($trait:ident, $method:ident, $impl_fn:ident, $lhs:ty, $rhs:ty) => {
+        impl<T> $trait<$rhs> for $lhs
+        where
+            T: NumberLike + ComputeWrapperSpec,
+        {
+            type Output = TensorPromise<T>;
+
+            #[inline]
+            fn $method(self, rhs: $rhs) -> Self::Output {
+                $impl_fn(&self, &rhs)
+            }
+        }
+    };
+}
+
+/// Elementwise minimum and maximum between tensors or promises of the same shape.
+/// -0.0 is treated as smaller than 0.0 by every method. They only differ in their NaNs:
+/// minimum() and maximum() return NaN if either element is NaN, while minimum_num()
+/// and maximum_num() return the other element, like fmin and fmax, so NaNs used as
+/// missing values are skipped. Only real elements are ordered, so it is implemented for f64.
+pub trait ElementwiseMinMax<Rhs> {
+    type Output;
+
+    fn minimum(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+    fn maximum(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+    fn minimum_num(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+    fn maximum_num(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+}
+
+macro_rules! impl_min_max {
+    ($lhs:ident, $rhs:ident) => {
+        impl ElementwiseMinMax<$rhs<f64>> for $lhs<f64> {
+            type Output = TensorPromise<f64>;
+
+            #[inline]
+            fn minimum(&self, rhs: &$rhs<f64>) -> Result<TensorPromise<f64>, OpError> {
+                min_max_impl(self, rhs, MinMaxKind::Minimum)
+            }
+
+            #[inline]
+            fn maximum(&self, rhs: &$rhs<f64>) -> Result<TensorPromise<f64>, OpError> {
+                min_max_impl(self, rhs, MinMaxKind::Maximum)
+            }
+
+            #[inline]
+            fn minimum_num(&self, rhs: &$rhs<f64>) -> Result<TensorPromise<f64>, OpError> {
+                min_max_impl(self, rhs, MinMaxKind::MinimumNum)
+            }
+
+            #[inline]
+            fn maximum_num(&self, rhs: &$rhs<f64>) -> Result<TensorPromise<f64>, OpError> {
+                min_max_impl(self, rhs, MinMaxKind::MaximumNum)
+            }
+        }
+    };
+}
+
+/// The arithmetic operators between tensors or promises, returning an error instead
+/// of panicking when the shapes do not match. The error carries the op and the shapes
+/// of its inputs, see OpError::context().
+pub trait TryArithmetic<Rhs> {
+    type Output;
+
+    fn try_add(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+    fn try_sub(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+    fn try_mul(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+    fn try_div(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+}
+
+/// Outer product of two 1-D tensors or promises, `out[i][j] = self[i] * rhs[j]`.
+pub trait OuterProduct<Rhs> {
+    type Output;
+
+    /// Fails with NotEnoughAxes if either input is not 1-D.
+    fn outer(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+}
+
+macro_rules! impl_outer {
+    ($lhs:ident, $rhs:ident) => {
+        impl<T> OuterProduct<$rhs<T>> for $lhs<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             type Output = TensorPromise<T>;
 
             #[inline]
-            fn $method(self, rhs: &$rhs<T>) -> Self::Output {
-                $impl_fn(self, rhs)
+            fn outer(&self, rhs: &$rhs<T>) -> Result<TensorPromise<T>, OpError> {
+                outer_impl(self, rhs)
             }
         }
+    };
+}
 
-        impl<T> $trait<$rhs<T>> for &$lhs<T>
+/// Inner product over the last axis of two tensors or promises, like numpy.inner.
+/// Two vectors give their dot product, as a tensor of shape [1]. Otherwise the output
+/// has the leading axes of `self` followed by the leading axes of `rhs`.
+pub trait InnerProduct<Rhs> {
+    type Output;
+
+    /// Fails with NotSameShape if the last axes have different lengths.
+    fn inner(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+}
+
+macro_rules! impl_inner {
+    ($lhs:ident, $rhs:ident) => {
+        impl<T> InnerProduct<$rhs<T>> for $lhs<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             type Output = TensorPromise<T>;
 
             #[inline]
-            fn $method(self, rhs: $rhs<T>) -> Self::Output {
-                $impl_fn(self, &rhs)
+            fn inner(&self, rhs: &$rhs<T>) -> Result<TensorPromise<T>, OpError> {
+                inner_impl(self, rhs)
             }
         }
+    };
+}
+
+/// Batch matrix multiply, `[batch, m, k] x [batch, k, n] -> [batch, m, n]`. Either side
+/// may be a single [m, k] or [k, n] matrix, or a batch of 1, multiplied with every matrix
+/// of the other side. Two matrices give their [m, n] product.
+///
+/// Transposed matrices, like the ones of transpose_axes(&[0, 2, 1]), are read in place.
+pub trait BatchProduct<Rhs> {
+    type Output;
+
+    /// Fails with CannotMatmul if the inner lengths differ, with NotSameBatch if the batches
+    /// differ and neither is 1, and with NotEnoughAxes if an input is not 2-D or 3-D.
+    fn bmm(&self, rhs: &Rhs) -> Result<Self::Output, OpError>;
+}
 
-        impl<T> $trait<&$rhs<T>> for $lhs<T>
+macro_rules! impl_bmm {
+    ($lhs:ident, $rhs:ident) => {
+        impl<T> BatchProduct<$rhs<T>> for $lhs<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             type Output = TensorPromise<T>;
 
             #[inline]
-            fn $method(self, rhs: &$rhs<T>) -> Self::Output {
-                $impl_fn(&self, rhs)
+            fn bmm(&self, rhs: &$rhs<T>) -> Result<TensorPromise<T>, OpError> {
+                bmm_impl(self, rhs)
             }
         }
+    };
+}
 
-        impl<T> $trait<$rhs<T>> for $lhs<T>
+macro_rules! impl_try_arithmetic {
+    ($lhs:ident, $rhs:ident) => {
+        impl<T> TryArithmetic<$rhs<T>> for $lhs<T>
         where
             T: NumberLike + ComputeWrapperSpec,
         {
             type Output = TensorPromise<T>;
 
             #[inline]
-            fn $method(self, rhs: $rhs<T>) -> Self::Output {
-                $impl_fn(&self, &rhs)
+            fn try_add(&self, rhs: &$rhs<T>) -> Result<TensorPromise<T>, OpError> {
+                binary_tensor_impl(self, rhs, OpKind::Add, ScalarKind::Sum)
+            }
+
+            #[inline]
+            fn try_sub(&self, rhs: &$rhs<T>) -> Result<TensorPromise<T>, OpError> {
+                binary_tensor_impl(self, rhs, OpKind::Sub, ScalarKind::Sub)
+            }
+
+            #[inline]
+            fn try_mul(&self, rhs: &$rhs<T>) -> Result<TensorPromise<T>, OpError> {
+                binary_tensor_impl(self, rhs, OpKind::Mul, ScalarKind::Mul)
+            }
+
+            #[inline]
+            fn try_div(&self, rhs: &$rhs<T>) -> Result<TensorPromise<T>, OpError> {
+                binary_tensor_impl(self, rhs, OpKind::Div, ScalarKind::Div)
             }
         }
     };
@@ -533,6 +1678,22 @@ macro_rules! impl_tensor_ops {
         impl_tensor_binop!(Sub, sub, sub_tensor_impl, $lhs, $rhs);
         impl_tensor_binop!(Mul, mul, mul_tensor_impl, $lhs, $rhs);
         impl_tensor_binop!(Div, div, div_tensor_impl, $lhs, $rhs);
+        impl_min_max!($lhs, $rhs);
+        impl_outer!($lhs, $rhs);
+        impl_inner!($lhs, $rhs);
+        impl_bmm!($lhs, $rhs);
+        impl_try_arithmetic!($lhs, $rhs);
+    };
+}
+
+// The operators taking a consumable tensor, only by value so that a single op reads it,
+// see Tensor::consume(). The traits above borrow their operands and are left out.
+macro_rules! impl_consumed_ops {
+    ($lhs:ty, $rhs:ty) => {
+        impl_tensor_binop!(Add, add, add_tensor_impl, $lhs, $rhs);
+        impl_tensor_binop!(Sub, sub, sub_tensor_impl, $lhs, $rhs);
+        impl_tensor_binop!(Mul, mul, mul_tensor_impl, $lhs, $rhs);
+        impl_tensor_binop!(Div, div, div_tensor_impl, $lhs, $rhs);
     };
 }
 
@@ -541,14 +1702,87 @@ macro_rules! impl_tensor_ops {
 impl_computation_def!(Tensor, Edge);
 impl_computation_def!(TensorPromise, Node);
 impl_computation_def!(CachedTensorPromise, Cache);
+impl_computation_def!(ConsumableTensor, Edge);
+
+impl_axis_names!(Tensor);
+impl_axis_names!(TensorPromise);
+impl_axis_names!(CachedTensorPromise);
+impl_axis_names!(ConsumableTensor);
 
 impl_reshape_like!(Tensor);
 impl_reshape_like!(TensorPromise);
 impl_reshape_like!(CachedTensorPromise);
+impl_reshape_like!(ConsumableTensor, Self);
+
+impl_normalize!(Tensor);
+impl_normalize!(TensorPromise);
+impl_normalize!(CachedTensorPromise);
+impl_normalize!(ConsumableTensor, Self);
+
+impl_reduce!(Tensor);
+impl_reduce!(TensorPromise);
+impl_reduce!(CachedTensorPromise);
+impl_reduce!(ConsumableTensor, Self);
+
+impl_rolling!(Tensor);
+impl_rolling!(TensorPromise);
+impl_rolling!(CachedTensorPromise);
+impl_rolling!(ConsumableTensor, Self);
+
+impl_quantile!(Tensor);
+impl_quantile!(TensorPromise);
+impl_quantile!(CachedTensorPromise);
+impl_quantile!(ConsumableTensor, Self);
+
+impl_diff_trapz!(Tensor);
+impl_diff_trapz!(TensorPromise);
+impl_diff_trapz!(CachedTensorPromise);
+impl_diff_trapz!(ConsumableTensor, Self);
+
+impl_neg!(Tensor);
+impl_neg!(TensorPromise);
+impl_neg!(CachedTensorPromise);
+impl_neg!(ConsumableTensor<T>);
+
+impl_rdiv_scalar!(Tensor);
+impl_rdiv_scalar!(TensorPromise);
+impl_rdiv_scalar!(CachedTensorPromise);
+impl_rdiv_scalar!(ConsumableTensor<f64>);
+
+impl_random!(Tensor);
+impl_random!(TensorPromise);
+impl_random!(CachedTensorPromise);
+impl_random!(ConsumableTensor, Self);
+
+impl_apply!(Tensor);
+impl_apply!(TensorPromise);
+impl_apply!(CachedTensorPromise);
+impl_apply!(ConsumableTensor, Self);
+
+impl_assert!(Tensor);
+impl_assert!(TensorPromise);
+impl_assert!(CachedTensorPromise);
+impl_assert!(ConsumableTensor, Self);
+
+impl_unary!(Tensor);
+impl_unary!(TensorPromise);
+impl_unary!(CachedTensorPromise);
+impl_unary!(ConsumableTensor, Self);
+
+impl_exp_log!(Tensor);
+impl_exp_log!(TensorPromise);
+impl_exp_log!(CachedTensorPromise);
+impl_exp_log!(ConsumableTensor, Self);
+
+impl_min_max_scalar!(Tensor);
+impl_min_max_scalar!(TensorPromise);
+impl_min_max_scalar!(CachedTensorPromise);
+impl_min_max_scalar!(ConsumableTensor, Self);
 
 impl_op_scalar!(Tensor);
 impl_op_scalar!(TensorPromise);
 impl_op_scalar!(CachedTensorPromise);
+impl_op_scalar!(ConsumableTensor<T>);
 
 impl_tensor_ops!(Tensor, Tensor);
 impl_tensor_ops!(Tensor, TensorPromise);
@@ -561,3 +1795,18 @@ impl_tensor_ops!(TensorPromise, CachedTensorPromise);
 impl_tensor_ops!(CachedTensorPromise, Tensor);
 impl_tensor_ops!(CachedTensorPromise, TensorPromise);
 impl_tensor_ops!(CachedTensorPromise, CachedTensorPromise);
+
+impl_consumed_ops!(ConsumableTensor<T>, Tensor<T>);
+impl_consumed_ops!(ConsumableTensor<T>, &Tensor<T>);
+impl_consumed_ops!(ConsumableTensor<T>, TensorPromise<T>);
+impl_consumed_ops!(ConsumableTensor<T>, &TensorPromise<T>);
+impl_consumed_ops!(ConsumableTensor<T>, CachedTensorPromise<T>);
+impl_consumed_ops!(ConsumableTensor<T>, &CachedTensorPromise<T>);
+impl_consumed_ops!(ConsumableTensor<T>, ConsumableTensor<T>);
+
+impl_consumed_ops!(Tensor<T>, ConsumableTensor<T>);
+impl_consumed_ops!(&Tensor<T>, ConsumableTensor<T>);
+impl_consumed_ops!(TensorPromise<T>, ConsumableTensor<T>);
+impl_consumed_ops!(&TensorPromise<T>, ConsumableTensor<T>);
+impl_consumed_ops!(CachedTensorPromise<T>, ConsumableTensor<T>);
+impl_consumed_ops!(&CachedTensorPromise<T>, ConsumableTensor<T>);
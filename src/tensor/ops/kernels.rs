@@ -0,0 +1,141 @@
+//! The summation every reduction goes through, so they all round the same way.
+//!
+//! Adding `n` elements one after the other has an error growing with `n`: the bound is
+//! about `n * ε * Σ|x|`, with ε = 2^-53, and large terms cancelling each other leave
+//! nothing but that error. The two modes of set_summation() do better:
+//!
+//! - Pairwise adds blocks of `b` elements in order, see set_pairwise_block(), and then the
+//!   sums of the blocks two by two, like a balanced tree. The bound drops to about
+//!   `(b + log2(n / b)) * ε * Σ|x|`, for the cost of a few additions per block.
+//!   The blocks only depend on the position of the elements in row-major order, so a sum
+//!   is the same bit for bit whatever the layout or the chunks it is read in.
+//! - Kahan carries the rounding error of every addition to the next one, in the variant
+//!   of Neumaier, which also holds when the next element is larger than the sum so far.
+//!   The bound is about `2 * ε * |Σx| + n * ε² * Σ|x|`, independent of `n` in practice.
+//!
+//! Neither one helps a sum whose result is below `ε * Σ|x|` of the actual one, except
+//! Kahan when the terms cancel exactly.
+
+use crate::tensor::numerics::{self, Summation};
+
+// Sums the elements it is given, in the mode set when it was created. `levels[i]` holds
+// the sum of 2^i blocks whenever bit i of `blocks` is set, like a binary counter, so every
+// block is added to the one next to it, then every pair to the pair next to it, and so on.
+pub(crate) struct Summer {
+    summation: Summation,
+    block: usize,
+    // Sum of the current block and its length, or the running sum of Kahan.
+    partial: f64,
+    in_block: usize,
+    // Rounding error carried along by Kahan.
+    compensation: f64,
+    levels: [f64; usize::BITS as usize],
+    blocks: usize,
+}
+
+impl Summer {
+    pub(crate) fn new() -> Self {
+        Self {
+            summation: numerics::summation(),
+            block: numerics::pairwise_block(),
+            partial: 0.0,
+            in_block: 0,
+            compensation: 0.0,
+            levels: [0.0; usize::BITS as usize],
+            blocks: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn add(&mut self, x: f64) {
+        match self.summation {
+            Summation::Pairwise => {
+                self.partial += x;
+                self.in_block += 1;
+
+                if self.in_block == self.block {
+                    self.push_block();
+                }
+            }
+            Summation::Kahan => self.add_compensated(x),
+        }
+    }
+
+    pub(crate) fn add_slice(&mut self, mut xs: &[f64]) {
+        if self.summation == Summation::Kahan {
+            xs.iter().for_each(|&x| self.add_compensated(x));
+            return;
+        }
+
+        // The rest of the current block, then whole blocks.
+        while !xs.is_empty() {
+            let (block, rest) = xs.split_at((self.block - self.in_block).min(xs.len()));
+
+            self.partial = block.iter().fold(self.partial, |acc, &x| acc + x);
+            self.in_block += block.len();
+
+            if self.in_block == self.block {
+                self.push_block();
+            }
+
+            xs = rest;
+        }
+    }
+
+    pub(crate) fn finish(self) -> f64 {
+        match self.summation {
+            Summation::Pairwise => (0..self.levels.len())
+                .filter(|level| self.blocks & (1 << level) != 0)
+                .fold(self.partial, |acc, level| self.levels[level] + acc),
+            // An infinity or a NaN leaves a NaN in the compensation.
+            Summation::Kahan if !self.partial.is_finite() => self.partial,
+            Summation::Kahan => self.partial + self.compensation,
+        }
+    }
+
+    fn push_block(&mut self) {
+        let mut sum = self.partial;
+        let mut level = 0;
+
+        while self.blocks & (1 << level) != 0 {
+            sum += self.levels[level];
+            level += 1;
+        }
+
+        self.levels[level] = sum;
+        self.blocks += 1;
+        self.partial = 0.0;
+        self.in_block = 0;
+    }
+
+    #[inline]
+    fn add_compensated(&mut self, x: f64) {
+        let sum = self.partial + x;
+
+        self.compensation += if self.partial.abs() >= x.abs() {
+            (self.partial - sum) + x
+        } else {
+            (x - sum) + self.partial
+        };
+        self.partial = sum;
+    }
+}
+
+impl Extend<f64> for Summer {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|x| self.add(x));
+    }
+}
+
+/// Sum of the elements, see the module documentation.
+pub(crate) fn sum_iter(xs: impl IntoIterator<Item = f64>) -> f64 {
+    let mut summer = Summer::new();
+    summer.extend(xs);
+
+    summer.finish()
+}
+
+/// Sum of the products of the elements at the same position.
+pub(crate) fn dot(x: &[f64], y: &[f64]) -> f64 {
+    sum_iter(x.iter().zip(y).map(|(a, b)| a * b))
+}
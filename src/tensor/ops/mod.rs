@@ -1,10 +1,23 @@
+mod apply;
+pub(crate) mod batch_matmul;
+mod cast;
 pub mod def_op;
 pub mod fusion;
 pub mod impl_compute_op;
 mod impl_layout;
+pub mod impl_names;
 pub mod impl_op;
+pub(crate) mod kernels;
 mod reusable;
 
+pub use apply::{ApplyFn, ApplyOptions};
+pub use batch_matmul::{set_strided_batch_gemm, strided_batch_gemm};
+pub use cast::CastSource;
+pub use fusion::{DEFAULT_MAX_FUSED_SCALAR_OPS, max_fused_scalar_ops, set_max_fused_scalar_ops};
 pub use impl_compute_op::ComputeWrapperSpec;
-pub use impl_compute_op::cpu_compute;
+#[cfg(feature = "check_fast_paths")]
+pub(crate) use impl_compute_op::uncounted;
+pub use impl_compute_op::{adaptive_packing, set_adaptive_packing};
+pub use impl_compute_op::{computed_op_count, cpu_compute, cpu_compute_with_cast};
 pub use impl_layout::compute_layout;
+pub use impl_names::{AxisNameList, compute_names};
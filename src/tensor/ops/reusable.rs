@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::tensor::Dimension;
-use crate::tensor::storage::TensorData;
+use crate::tensor::storage::{Buffer, TensorData};
 use crate::{branch_fast_iter, cfg_tracing, cfg_tracing_in_scope};
 use tracing::{Level, event, span};
 
@@ -15,16 +15,35 @@ fn strip_tensor<T: Copy + Default>(tensor: TensorData<T>) -> ReusableVec<T> {
     let len = tensor.len();
     let offset = tensor.offset();
 
-    if let Ok(mut v) = Arc::try_unwrap(tensor.storage.buffer) {
-        v.resize(len + offset, T::default());
-        ReusableVec { v, offset }
-    } else {
-        unreachable!("a reusable tensor should not be shared")
+    match Arc::try_unwrap(tensor.storage.buffer) {
+        Ok(Buffer::Owned(mut v)) => {
+            v.resize(len + offset, T::default());
+            ReusableVec { v, offset }
+        }
+        // External memory cannot become a Vec, so the elements of the view are copied.
+        Ok(external) => ReusableVec {
+            v: external[offset..offset + len].to_vec(),
+            offset: 0,
+        },
+        // An op that read it before kept a view of its buffer, so the elements are copied.
+        Err(buffer) => ReusableVec {
+            v: buffer[offset..offset + len].to_vec(),
+            offset: 0,
+        },
     }
 }
 
 #[inline]
 pub fn alloc_cont_tensor<T: Copy + Default>(tensor: &TensorData<T>) -> ReusableVec<T> {
+    // The new buffer is a dense copy either way, the blocked one avoids the cache misses
+    // of gathering a transposed operand one element at a time.
+    if tensor.is_transposed_matrix() {
+        return ReusableVec {
+            v: tensor.transposed_copy(),
+            offset: 0,
+        };
+    }
+
     branch_fast_iter!(tensor.copied_fast_iter() => iter, {
         let v = Vec::from_iter(iter);
 
@@ -45,29 +64,3 @@ pub fn get_reusable_or_alloc<T: Copy + Default>(tensor: TensorData<T>) -> Reusab
         }
     )
 }
-
-#[inline]
-fn unordered_remove_tensor<T: Copy>(tensors: &mut Vec<TensorData<T>>, n: usize) -> TensorData<T> {
-    let temp = tensors[n].clone();
-    tensors[n] = tensors.pop().unwrap();
-
-    temp
-}
-
-pub fn unordered_get_reusable_or_alloc_n<T: Copy + Default>(
-    tensors: &mut Vec<TensorData<T>>,
-    n: usize,
-) -> ReusableVec<T> {
-    for i in 0..tensors.len() {
-        if tensors[i].reusable {
-            let removed = unordered_remove_tensor(tensors, i);
-
-            return strip_tensor(removed);
-        }
-    }
-
-    let v = alloc_cont_tensor(&tensors[n]);
-    let _ = unordered_remove_tensor(tensors, n);
-
-    v
-}
@@ -0,0 +1,208 @@
+use crate::tensor::backend::{Backend, VectorMathBackend};
+use crate::tensor::errors::OpError;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+// NOTE: Tensors are immutable once materialized, so the optimizers compute the update
+// in a single pass over the parameters and swap the old tensor for the new one.
+// Promises built on top of the old parameter will keep seeing the old values.
+
+fn check_params(params: &[&mut Tensor<f64>], grads: &[&Tensor<f64>]) -> Result<(), OpError> {
+    if params.len() != grads.len() {
+        return Err(OpError::ParamCount(params.len(), grads.len()));
+    }
+
+    for (param, grad) in params.iter().zip(grads.iter()) {
+        if param.shape() != grad.shape() {
+            return Err(OpError::NotSameShape(
+                param.shape().into(),
+                grad.shape().into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// The buffers kept from the first step must still line up with the parameters.
+fn check_buffers(params: &[&mut Tensor<f64>], buffers: &[Tensor<f64>]) -> Result<(), OpError> {
+    if params.len() != buffers.len() {
+        return Err(OpError::ParamCount(params.len(), buffers.len()));
+    }
+
+    for (param, buffer) in params.iter().zip(buffers.iter()) {
+        if param.shape() != buffer.shape() {
+            return Err(OpError::NotSameShape(
+                buffer.shape().into(),
+                param.shape().into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn zeros_like(params: &[&mut Tensor<f64>]) -> Vec<Tensor<f64>> {
+    params
+        .iter()
+        .map(|param| Tensor::from_scalar(0.0, param.shape()))
+        .collect()
+}
+
+// First and second moments of every parameter, in the order of the parameters.
+type Moments = (Vec<Tensor<f64>>, Vec<Tensor<f64>>);
+
+//////////////////////////////////////////////////////////////////////////////////
+
+/// Stochastic gradient descent with optional momentum and L2 weight decay.
+pub struct Sgd {
+    pub lr: f64,
+    pub momentum: f64,
+    pub weight_decay: f64,
+    velocity: Option<Vec<Tensor<f64>>>,
+}
+
+impl Sgd {
+    pub fn new(lr: f64, momentum: f64, weight_decay: f64) -> Self {
+        Self {
+            lr,
+            momentum,
+            weight_decay,
+            velocity: None,
+        }
+    }
+
+    /// Applies one update to every parameter using its respective gradient.
+    /// The velocity buffers are allocated on the first call, so the same
+    /// parameters must be given in the same order on every call, otherwise
+    /// it fails with ParamCount or NotSameShape.
+    pub fn step(
+        &mut self,
+        params: &mut [&mut Tensor<f64>],
+        grads: &[&Tensor<f64>],
+    ) -> Result<(), OpError> {
+        check_params(params, grads)?;
+
+        if self.momentum == 0.0 {
+            for (param, grad) in params.iter_mut().zip(grads.iter()) {
+                let updated = param
+                    .iter()
+                    .zip(grad.iter())
+                    .map(|(&p, &g)| p - self.lr * (g + self.weight_decay * p));
+
+                **param = Tensor::from_iter(updated, param.shape());
+            }
+
+            return Ok(());
+        }
+
+        let velocity = self.velocity.get_or_insert_with(|| zeros_like(params));
+        check_buffers(params, velocity)?;
+
+        for ((param, grad), v) in params.iter_mut().zip(grads.iter()).zip(velocity.iter_mut()) {
+            let mut new_v: Vec<f64> = Vec::with_capacity(param.len());
+
+            for ((&p, &g), &v) in param.iter().zip(grad.iter()).zip(v.iter()) {
+                new_v.push(self.momentum * v + g + self.weight_decay * p);
+            }
+
+            let mut new_p: Vec<f64> = param.iter().copied().collect();
+            Backend::axpy(-self.lr, &new_v, &mut new_p);
+
+            *v = Tensor::from_vec(new_v, param.shape());
+            **param = Tensor::from_vec(new_p, param.shape());
+        }
+
+        Ok(())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+/// Adam optimizer with bias correction and L2 weight decay.
+pub struct Adam {
+    pub lr: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub eps: f64,
+    pub weight_decay: f64,
+    step: usize,
+    moments: Option<Moments>,
+}
+
+impl Adam {
+    /// Creates an Adam optimizer with the usual defaults:
+    /// beta1 = 0.9, beta2 = 0.999, eps = 1e-8 and no weight decay.
+    pub fn new(lr: f64) -> Self {
+        Self::with_params(lr, 0.9, 0.999, 1e-8, 0.0)
+    }
+
+    pub fn with_params(lr: f64, beta1: f64, beta2: f64, eps: f64, weight_decay: f64) -> Self {
+        Self {
+            lr,
+            beta1,
+            beta2,
+            eps,
+            weight_decay,
+            step: 0,
+            moments: None,
+        }
+    }
+
+    /// Number of times step() was called successfully.
+    pub fn steps(&self) -> usize {
+        self.step
+    }
+
+    /// Applies one update to every parameter using its respective gradient.
+    /// The moment buffers are allocated on the first call, so the same
+    /// parameters must be given in the same order on every call, otherwise
+    /// it fails with ParamCount or NotSameShape.
+    pub fn step(
+        &mut self,
+        params: &mut [&mut Tensor<f64>],
+        grads: &[&Tensor<f64>],
+    ) -> Result<(), OpError> {
+        check_params(params, grads)?;
+
+        let (first, second) = self
+            .moments
+            .get_or_insert_with(|| (zeros_like(params), zeros_like(params)));
+        check_buffers(params, first)?;
+
+        self.step += 1;
+
+        let bias1 = 1.0 - self.beta1.powi(self.step as i32);
+        let bias2 = 1.0 - self.beta2.powi(self.step as i32);
+
+        for (((param, grad), m), v) in params
+            .iter_mut()
+            .zip(grads.iter())
+            .zip(first.iter_mut())
+            .zip(second.iter_mut())
+        {
+            let mut new_m: Vec<f64> = Vec::with_capacity(param.len());
+            let mut new_v: Vec<f64> = Vec::with_capacity(param.len());
+            let mut new_p: Vec<f64> = Vec::with_capacity(param.len());
+
+            for (((&p, &g), &m), &v) in param.iter().zip(grad.iter()).zip(m.iter()).zip(v.iter()) {
+                let g = g + self.weight_decay * p;
+                let m = self.beta1 * m + (1.0 - self.beta1) * g;
+                let v = self.beta2 * v + (1.0 - self.beta2) * g * g;
+
+                let m_hat = m / bias1;
+                let v_hat = v / bias2;
+
+                new_m.push(m);
+                new_v.push(v);
+                new_p.push(p - self.lr * m_hat / (v_hat.sqrt() + self.eps));
+            }
+
+            *m = Tensor::from_vec(new_m, param.shape());
+            *v = Tensor::from_vec(new_v, param.shape());
+            **param = Tensor::from_vec(new_p, param.shape());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+use crate::tensor::errors::OpError;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+#[derive(Clone, Copy, Debug)]
+pub enum PaddingMode<T: Copy> {
+    /// Fills the padding with the given value.
+    Constant(T),
+    /// Repeats the first and last elements of the axis.
+    Edge,
+    /// Mirrors the axis around its first and last elements, without repeating them.
+    /// The padding of each side must be smaller than the axis.
+    Reflect,
+    /// Mirrors the axis around its edges, repeating the first and last elements.
+    /// The padding of each side must not be longer than the axis.
+    Symmetric,
+}
+
+// Maps every position of the padded axis to a position of the original axis.
+// None means that the position must be filled with the constant.
+fn pad_axis_indices<T: Copy>(
+    axis: usize,
+    len: usize,
+    before: usize,
+    after: usize,
+    mode: &PaddingMode<T>,
+) -> Result<Vec<Option<usize>>, OpError> {
+    let padded = before > 0 || after > 0;
+
+    match mode {
+        PaddingMode::Constant(_) => {}
+        PaddingMode::Edge => {
+            if padded && len == 0 {
+                return Err(OpError::InvalidPadding(axis));
+            }
+        }
+        PaddingMode::Reflect => {
+            if padded && (before >= len || after >= len) {
+                return Err(OpError::InvalidPadding(axis));
+            }
+        }
+        PaddingMode::Symmetric => {
+            if before > len || after > len {
+                return Err(OpError::InvalidPadding(axis));
+            }
+        }
+    }
+
+    let indices = (0..before + len + after)
+        .map(|i| {
+            let src = i as isize - before as isize;
+            let last = len as isize - 1;
+
+            if (0..=last).contains(&src) {
+                return Some(src as usize);
+            }
+
+            match mode {
+                PaddingMode::Constant(_) => None,
+                PaddingMode::Edge => Some(src.clamp(0, last) as usize),
+                PaddingMode::Reflect => {
+                    if src < 0 {
+                        Some((-src) as usize)
+                    } else {
+                        Some((2 * last - src) as usize)
+                    }
+                }
+                PaddingMode::Symmetric => {
+                    if src < 0 {
+                        Some((-src - 1) as usize)
+                    } else {
+                        Some((2 * last + 1 - src) as usize)
+                    }
+                }
+            }
+        })
+        .collect();
+
+    Ok(indices)
+}
+
+impl<T: Copy> Tensor<T> {
+    /// Pads every axis with `padding[axis].0` elements before it and `padding[axis].1` after it.
+    /// This is an eager operation that always allocates a new tensor.
+    pub fn pad(&self, padding: &[(usize, usize)], mode: PaddingMode<T>) -> Result<Self, OpError> {
+        let shape = self.shape();
+
+        if padding.len() != shape.len() {
+            return Err(OpError::NotEnoughAxes(shape.len(), padding.len()));
+        }
+
+        let mut indices: Vec<Vec<Option<usize>>> = Vec::with_capacity(shape.len());
+
+        for (axis, &(before, after)) in padding.iter().enumerate() {
+            indices.push(pad_axis_indices(axis, shape[axis], before, after, &mode)?);
+        }
+
+        let new_shape: Vec<usize> = indices.iter().map(|i| i.len()).collect();
+        let len: usize = new_shape.iter().product();
+
+        let data = self.graph.get();
+        let buffer = &data.storage.buffer;
+        let stride = self.stride();
+
+        let fill = match mode {
+            PaddingMode::Constant(value) => Some(value),
+            _ => None,
+        };
+
+        let mut out: Vec<T> = Vec::with_capacity(len);
+        let mut counter = vec![0; new_shape.len()];
+
+        for _ in 0..len {
+            let mut pos = self.offset() as isize;
+            let mut inside = true;
+
+            for (axis, &i) in counter.iter().enumerate() {
+                match indices[axis][i] {
+                    Some(src) => pos += src as isize * stride[axis] as isize,
+                    None => {
+                        inside = false;
+                        break;
+                    }
+                }
+            }
+
+            if inside {
+                out.push(buffer[pos as usize]);
+            } else {
+                out.push(unsafe { fill.unwrap_unchecked() });
+            }
+
+            for axis in (0..counter.len()).rev() {
+                counter[axis] += 1;
+
+                if counter[axis] < new_shape[axis] {
+                    break;
+                }
+
+                counter[axis] = 0;
+            }
+        }
+
+        Ok(Tensor::from_vec(out, &new_shape))
+    }
+
+    /// Pads both sides of every axis with `pad` elements equal to `value`.
+    pub fn pad_constant(&self, pad: usize, value: T) -> Self {
+        let padding = vec![(pad, pad); self.shape().len()];
+
+        unsafe {
+            self.pad(&padding, PaddingMode::Constant(value))
+                .unwrap_unchecked()
+        }
+    }
+
+    /// Same as pad(), but also rejects the padding of an axis whose two sides together are
+    /// longer than it, so every padded element mirrors or repeats a distinct part of the axis.
+    pub fn pad_symmetric(
+        &self,
+        padding: &[(usize, usize)],
+        mode: PaddingMode<T>,
+    ) -> Result<Self, OpError> {
+        for (axis, (&(before, after), &len)) in padding.iter().zip(self.shape()).enumerate() {
+            if before + after > len {
+                return Err(OpError::InvalidPadding(axis));
+            }
+        }
+
+        self.pad(padding, mode)
+    }
+
+    /// Pads the end of every axis with `value` until the shape is `target`.
+    /// No axis can be longer than its target.
+    pub fn pad_to_shape(&self, target: &[usize], value: T) -> Result<Self, OpError> {
+        let shape = self.shape();
+
+        if target.len() != shape.len() {
+            return Err(OpError::NotEnoughAxes(shape.len(), target.len()));
+        }
+
+        let mut padding = Vec::with_capacity(shape.len());
+
+        for (axis, (&len, &target_len)) in shape.iter().zip(target).enumerate() {
+            if target_len < len {
+                return Err(OpError::InvalidPadding(axis));
+            }
+
+            padding.push((0, target_len - len));
+        }
+
+        self.pad(&padding, PaddingMode::Constant(value))
+    }
+
+    /// Pads the end of `axis` so that its length becomes a multiple of `multiple`.
+    pub fn pad_to_multiple(
+        &self,
+        axis: usize,
+        multiple: usize,
+        mode: PaddingMode<T>,
+    ) -> Result<Self, OpError> {
+        if axis >= self.shape().len() {
+            return Err(OpError::OutOfBoundAxes);
+        }
+
+        if multiple == 0 {
+            return Err(OpError::InvalidPadding(axis));
+        }
+
+        let mut padding = vec![(0, 0); self.shape().len()];
+        padding[axis].1 = (multiple - self.shape()[axis] % multiple) % multiple;
+
+        self.pad(&padding, mode)
+    }
+}
+
+/// Stacks variable-length 1-D tensors into a `[max_len, batch]` tensor,
+/// filling the end of the shorter ones with `padding_value`.
+pub fn pad_sequence<T: Copy>(
+    tensors: &[Tensor<T>],
+    padding_value: T,
+) -> Result<Tensor<T>, OpError> {
+    for tensor in tensors {
+        if tensor.shape().len() != 1 {
+            return Err(OpError::NotEnoughAxes(1, tensor.shape().len()));
+        }
+    }
+
+    let batch = tensors.len();
+    let max_len = tensors.iter().map(|t| t.shape()[0]).max().unwrap_or(0);
+
+    let mut out: Vec<T> = vec![padding_value; max_len * batch];
+
+    for (b, tensor) in tensors.iter().enumerate() {
+        for (t, value) in tensor.iter().enumerate() {
+            out[t * batch + b] = *value;
+        }
+    }
+
+    Ok(Tensor::from_vec(out, &[max_len, batch]))
+}
@@ -0,0 +1,107 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::OpError;
+use crate::tensor::graph::{self, NodeKind};
+use crate::tensor::numerics::{NumericsScope, numerics_mode};
+use crate::tensor::ops::ComputeWrapperSpec;
+use crate::tensor::promise::TensorPromise;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Promising;
+
+/// Threads materialize_all() runs its promises on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ParallelismConfig {
+    /// One promise after the other, on the calling thread.
+    CurrentThread,
+    /// Tasks of the rayon pool the caller runs in: the global one, or the one of the
+    /// enclosing ThreadPool::install(), so an application with a pool of its own is not
+    /// oversubscribed.
+    #[default]
+    Rayon,
+    /// A pool of `n` threads created for the call, rayon's default count for 0.
+    Threads(usize),
+}
+
+/// Materializes independent promises as tasks of `pool`, idle threads stealing the
+/// promises not started yet, and returns their results in the same order.
+///
+/// The caches reached by several of the promises are filled first, each subgraph under
+/// them being computed once, however many threads read it. Every promise is then
+/// materialized on its own, reading the filled caches in place, so unlike materialize_many()
+/// the nodes they share without a cache are computed by each of them.
+///
+/// A promise failing like try_materialize() returns its error, and one whose graph panics
+/// returns OpError::PromisePanicked with its index, the other results being unaffected.
+/// The threads compute with the numerics mode of the caller, see numerics_mode().
+pub fn materialize_all<T>(
+    promises: &[TensorPromise<T>],
+    pool: ParallelismConfig,
+) -> Vec<Result<Tensor<T>, OpError>>
+where
+    T: NumberLike + ComputeWrapperSpec + Send + Sync,
+{
+    let mode = numerics_mode();
+
+    let run = || {
+        let roots: Box<[NodeKind<T>]> = (promises.iter())
+            .map(|promise| NodeKind::Node(promise.graph.clone()))
+            .collect();
+
+        // A cache that panics is left empty, the promises reading it report the panic.
+        for wave in graph::shared_cache_waves(&roots) {
+            tasks(pool, &wave, |_, cache| {
+                let _scope = NumericsScope::enter(mode);
+                let _ =
+                    panic::catch_unwind(AssertUnwindSafe(|| graph::checked(|| cache.compute())));
+            });
+        }
+
+        tasks(pool, promises, |index, promise| {
+            let _scope = NumericsScope::enter(mode);
+
+            panic::catch_unwind(AssertUnwindSafe(|| promise.clone().try_materialize()))
+                .unwrap_or_else(|payload| {
+                    Err(OpError::PromisePanicked(index, panic_message(&*payload)))
+                })
+        })
+    };
+
+    match pool {
+        ParallelismConfig::Threads(threads) => rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("the threads of materialize_all() could not be created")
+            .install(run),
+        _ => run(),
+    }
+}
+
+// `f` over every item and its index, in parallel unless `pool` is CurrentThread.
+fn tasks<I: Sync, R: Send>(
+    pool: ParallelismConfig,
+    items: &[I],
+    f: impl Fn(usize, &I) -> R + Sync + Send,
+) -> Vec<R> {
+    match pool {
+        ParallelismConfig::CurrentThread => (items.iter().enumerate())
+            .map(|(index, item)| f(index, item))
+            .collect(),
+        _ => (items.par_iter().enumerate())
+            .map(|(index, item)| f(index, item))
+            .collect(),
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> Box<str> {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => (*message).into(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.as_str().into(),
+            None => "the panic has no message".into(),
+        },
+    }
+}
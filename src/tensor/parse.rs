@@ -0,0 +1,145 @@
+// Reads tensors written as nested lists, like `[[1, 2.5], [3e-7, 4]]`, the syntax of
+// to_parseable_string() and of the elements shown by to_string_flat().
+//
+// The parser keeps a stack of the lists it is in instead of recursing, so the depth of the
+// input is only bounded by memory, and every malformed input is an error, never a panic.
+
+use std::str::FromStr;
+
+use crate::tensor::errors::ParseError;
+
+struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn unexpected(&self, expected: &'static str) -> ParseError {
+        let found = match self.peek() {
+            Some(c) => format!("{:?}", c).into(),
+            None => "the end of the input".into(),
+        };
+
+        ParseError::Unexpected(self.pos, found, expected)
+    }
+}
+
+// A list is open for every entry, with its offset and the number of elements read in it,
+// which is also the position of the element being read.
+type OpenLists = Vec<(usize, usize)>;
+
+fn index(open: &OpenLists) -> Box<[usize]> {
+    open.iter().map(|&(_, read)| read).collect()
+}
+
+// Closes the innermost list, which must be as long as the lists before it at its depth.
+fn close(open: &mut OpenLists, lens: &mut Vec<Option<usize>>) -> Result<(), ParseError> {
+    let (start, len) = open.pop().expect("a list is open");
+    let depth = open.len();
+
+    if lens.len() <= depth {
+        lens.resize(depth + 1, None);
+    }
+
+    match lens[depth] {
+        None => lens[depth] = Some(len),
+        Some(expected) if expected != len => {
+            return Err(ParseError::RaggedLength(start, index(open), expected, len));
+        }
+        Some(_) => {}
+    }
+
+    Ok(())
+}
+
+/// The elements of `input` in row-major order, and the shape given by their nesting.
+/// A number outside of any list is a tensor without axes.
+pub(crate) fn parse_nested<T: FromStr>(input: &str) -> Result<(Vec<T>, Vec<usize>), ParseError> {
+    let mut cursor = Cursor { input, pos: 0 };
+    let mut elements = Vec::new();
+    let mut open = OpenLists::new();
+    // Length of the lists at every depth, and depth of the numbers, once one of them is read.
+    let mut lens: Vec<Option<usize>> = Vec::new();
+    let mut numbers_depth: Option<usize> = None;
+
+    loop {
+        cursor.skip_whitespace();
+        let start = cursor.pos;
+        let depth = open.len();
+
+        if cursor.peek() == Some('[') {
+            if numbers_depth == Some(depth) {
+                return Err(ParseError::RaggedDepth(start, index(&open)));
+            }
+
+            open.push((start, 0));
+            cursor.pos += 1;
+            cursor.skip_whitespace();
+            if cursor.peek() != Some(']') {
+                continue;
+            }
+
+            cursor.pos += 1;
+            close(&mut open, &mut lens)?;
+        } else {
+            let rest = &input[start..];
+            let len = rest
+                .find(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']'))
+                .unwrap_or(rest.len());
+            if len == 0 {
+                return Err(cursor.unexpected("a number or '['"));
+            }
+
+            let lists_at_depth = lens.get(depth).is_some_and(Option::is_some);
+            if lists_at_depth || numbers_depth.is_some_and(|numbers| numbers != depth) {
+                return Err(ParseError::RaggedDepth(start, index(&open)));
+            }
+
+            let number = &rest[..len];
+            let element = number.parse().map_err(|_| {
+                ParseError::InvalidNumber(start, number.into(), std::any::type_name::<T>())
+            })?;
+
+            elements.push(element);
+            numbers_depth = Some(depth);
+            cursor.pos += len;
+        }
+
+        // The element is read, what follows it either starts the next one or closes lists.
+        loop {
+            cursor.skip_whitespace();
+
+            let Some(innermost) = open.last_mut() else {
+                if cursor.pos < input.len() {
+                    return Err(cursor.unexpected("the end of the input"));
+                }
+
+                // Every list is closed, so every depth has a length.
+                return Ok((elements, lens.into_iter().flatten().collect()));
+            };
+
+            match cursor.peek() {
+                Some(',') => {
+                    innermost.1 += 1;
+                    cursor.pos += 1;
+                    break;
+                }
+                Some(']') => {
+                    innermost.1 += 1;
+                    cursor.pos += 1;
+                    close(&mut open, &mut lens)?;
+                }
+                _ => return Err(cursor.unexpected("',' or ']'")),
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+// Renderings of tensors bounded by the number of elements shown, for logs and tracing spans.
+//
+// The elements shown are copied out of the tensor before any of them is formatted, so a
+// slow Display never delays anything else reading or writing the buffer. Only those
+// elements are read and formatted: the size of the string does not depend on the size
+// of the tensor.
+
+use std::fmt::{Debug, Display, Write};
+
+use crate::tensor::ops::AxisNameList;
+use crate::tensor::storage::TensorData;
+use crate::tensor::traits::Dimension;
+
+/// Number of elements to_string_flat() writes before eliding the rest.
+pub const FLAT_ELEMENT_BUDGET: usize = 32;
+
+// Writes a shape as [2,3], without spaces.
+fn write_shape(out: &mut String, shape: &[usize]) {
+    out.push('[');
+
+    for (i, dim) in shape.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "{}", dim);
+    }
+
+    out.push(']');
+}
+
+// The name of T without its module paths, like Complex<f64>.
+fn dtype_name<T>() -> String {
+    let full = std::any::type_name::<T>();
+
+    full.split_inclusive(['<', '>', ',', ' ', '(', ')', '[', ']', ';', '&'])
+        .map(|part| part.rsplit_once("::").map_or(part, |(_, name)| name))
+        .collect()
+}
+
+fn repeat(out: &mut String, c: char, n: usize) {
+    out.extend(std::iter::repeat_n(c, n));
+}
+
+// The first `max_elements` elements in logical order.
+fn first_elements<T: Copy>(data: &TensorData<T>, max_elements: usize) -> Vec<T> {
+    match data.shape() {
+        // The iterators walk one axis at least.
+        [] => {
+            let scalar = data.storage.as_slice()[data.offset()];
+            std::iter::repeat_n(scalar, max_elements.min(1)).collect()
+        }
+        _ => data.copied_iter().take(max_elements).collect(),
+    }
+}
+
+// Writes `shown`, the first elements of a tensor of the given shape, in one pair of brackets
+// per axis, followed by `...` if there are more of them.
+fn write_nested<T: Copy>(
+    out: &mut String,
+    shape: &[usize],
+    shown: &[T],
+    elided: bool,
+    write_element: impl Fn(&mut String, T),
+) {
+    let ndim = shape.len();
+    let mut index = vec![0; ndim];
+    // Brackets opened before the next element, the ones closed after the last one.
+    let mut opening = ndim;
+    let mut closing = 0;
+
+    for (i, el) in shown.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        repeat(out, '[', opening);
+        write_element(out, *el);
+
+        // Moves to the next index like an odometer, closing the axes that wrap around.
+        closing = 0;
+        for dim in (0..ndim).rev() {
+            index[dim] += 1;
+            if index[dim] < shape[dim] {
+                break;
+            }
+            index[dim] = 0;
+            closing += 1;
+        }
+
+        repeat(out, ']', closing);
+        opening = closing;
+    }
+
+    if elided {
+        out.push_str(", ...");
+        repeat(out, ']', ndim - closing);
+    }
+}
+
+pub(crate) fn flat<T: Copy + Display>(data: &TensorData<T>, max_elements: usize) -> String {
+    let shape = data.shape();
+    let shown = first_elements(data, max_elements);
+    let elided = shown.len() < data.len();
+
+    let mut out = String::new();
+    let ndim = shape.len();
+
+    if data.len() == 0 {
+        out.push_str("[]");
+    } else if shown.is_empty() {
+        repeat(&mut out, '[', ndim.min(1));
+        out.push_str("...");
+        repeat(&mut out, ']', ndim.min(1));
+    } else {
+        write_nested(&mut out, shape, &shown, elided, |out, el| {
+            let _ = write!(out, "{}", el);
+        });
+    }
+
+    out.push_str(" shape=");
+    write_shape(&mut out, shape);
+
+    out
+}
+
+// Every element in the shortest form Debug reads back exactly. The shape of an empty
+// tensor stops at its first empty axis, which has no elements to nest the next ones in.
+pub(crate) fn parseable<T: Copy + Debug>(data: &TensorData<T>) -> String {
+    let shape = data.shape();
+    let mut out = String::new();
+
+    if data.len() == 0 {
+        let depth = shape.iter().take_while(|&&dim| dim > 0).count();
+
+        let mut nested = String::from("[]");
+        for &dim in shape[..depth].iter().rev() {
+            nested = format!("[{}]", vec![nested; dim].join(", "));
+        }
+        out.push_str(&nested);
+    } else {
+        let elements = first_elements(data, usize::MAX);
+        write_nested(&mut out, shape, &elements, false, |out, el| {
+            let _ = write!(out, "{:?}", el);
+        });
+    }
+
+    out
+}
+
+pub(crate) fn preview<T: Copy + Display>(
+    data: &TensorData<T>,
+    names: Option<&AxisNameList>,
+    max_rows: usize,
+    max_cols: usize,
+) -> String {
+    let shape = data.shape();
+    let stride = data.stride();
+    let ndim = shape.len();
+
+    // A vector is shown as a single row, and only the first matrix of a batch of them.
+    let (rows, row_stride) = match ndim {
+        0 | 1 => (1, 0),
+        _ => (shape[ndim - 2], stride[ndim - 2] as isize),
+    };
+    let (cols, col_stride) = match ndim {
+        0 => (1, 0),
+        _ => (shape[ndim - 1], stride[ndim - 1] as isize),
+    };
+    let (shown_rows, shown_cols) = (rows.min(max_rows), cols.min(max_cols));
+
+    let corner: Vec<T> = {
+        let buffer = data.storage.as_slice();
+        let offset = data.offset() as isize;
+
+        (0..shown_rows as isize)
+            .flat_map(|row| (0..shown_cols as isize).map(move |col| (row, col)))
+            .map(|(row, col)| buffer[(offset + row * row_stride + col * col_stride) as usize])
+            .collect()
+    };
+
+    let cells: Vec<String> = corner.iter().map(|el| el.to_string()).collect();
+    let width = cells.iter().map(String::len).max().unwrap_or(0);
+
+    let mut out = String::from("shape=");
+    write_shape(&mut out, shape);
+
+    if let Some(names) = names {
+        let _ = write!(out, " names=[{}]", names.join(","));
+    }
+    let _ = write!(out, " dtype={}", dtype_name::<T>());
+
+    if ndim > 2 {
+        let _ = write!(out, " view=[{},:,:]", vec!["0"; ndim - 2].join(","));
+    }
+    out.push('\n');
+
+    if ndim == 0 {
+        out.push_str(cells.first().map_or("...", String::as_str));
+        return out;
+    }
+
+    let mut lines: Vec<String> = (0..shown_rows)
+        .map(|row| {
+            let mut line = String::from("[");
+            for (i, cell) in cells[row * shown_cols..][..shown_cols].iter().enumerate() {
+                if i > 0 {
+                    line.push_str(", ");
+                }
+                let _ = write!(line, "{:>width$}", cell, width = width);
+            }
+            if shown_cols < cols {
+                line.push_str(if shown_cols > 0 { ", ..." } else { "..." });
+            }
+            line.push(']');
+            line
+        })
+        .collect();
+
+    if shown_rows < rows {
+        lines.push("...".to_string());
+    }
+
+    if ndim == 1 {
+        out.push_str(&lines.concat());
+    } else {
+        out.push('[');
+        out.push_str(&lines.join(",\n "));
+        out.push(']');
+    }
+
+    out
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use crate::tensor::graph::TensorId;
+use crate::tensor::numerics::NumericsMode;
+#[cfg(feature = "profiling")]
+use crate::tensor::numerics::numerics_mode;
+
+#[cfg(feature = "profiling")]
+use std::cell::RefCell;
+#[cfg(feature = "profiling")]
+use std::time::Instant;
+
+/// Time spent computing a node of a graph, see flush_profile().
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProfileRecord {
+    id: TensorId,
+    op: &'static str,
+    duration: Duration,
+    numerics: NumericsMode,
+}
+
+impl ProfileRecord {
+    /// The node that was computed, the same as the id() of its promise.
+    pub fn id(&self) -> TensorId {
+        self.id
+    }
+
+    /// Name of the op of the node, like "Add" or "RollingSum".
+    pub fn op(&self) -> &'static str {
+        self.op
+    }
+
+    /// Wall-clock time of the kernel, without reading the inputs or scheduling the graph.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Numerics mode the kernel was computed with.
+    pub fn numerics(&self) -> NumericsMode {
+        self.numerics
+    }
+}
+
+#[cfg(feature = "profiling")]
+thread_local! {
+    // Node id, op name, nanoseconds and mode of every node computed on this thread.
+    static PROFILER_DATA: RefCell<Vec<(usize, &'static str, u64, NumericsMode)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Takes the records of every node computed on the current thread since the last call,
+/// in the order they were computed.
+/// Nodes are only timed with the `profiling` feature, otherwise this is always empty.
+pub fn flush_profile() -> Vec<ProfileRecord> {
+    #[cfg(feature = "profiling")]
+    {
+        PROFILER_DATA
+            .take()
+            .into_iter()
+            .map(|(id, op, nanos, numerics)| ProfileRecord {
+                id: TensorId(id),
+                op,
+                duration: Duration::from_nanos(nanos),
+                numerics,
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    Vec::new()
+}
+
+// Runs the kernel of the node `id` and records how long it took.
+#[cfg(feature = "profiling")]
+#[inline]
+pub(crate) fn timed<R>(id: usize, op: &'static str, compute: impl FnOnce() -> R) -> R {
+    let start = Instant::now();
+    let result = compute();
+    let nanos = start.elapsed().as_nanos() as u64;
+
+    let mode = numerics_mode();
+    PROFILER_DATA.with_borrow_mut(|data| data.push((id, op, nanos, mode)));
+
+    result
+}
+
+#[cfg(not(feature = "profiling"))]
+#[inline(always)]
+pub(crate) fn timed<R>(_id: usize, _op: &'static str, compute: impl FnOnce() -> R) -> R {
+    compute()
+}
+
+// Runs `f` without keeping the records of the nodes it computes.
+#[cfg(all(feature = "profiling", feature = "check_fast_paths"))]
+pub(crate) fn unprofiled<R>(f: impl FnOnce() -> R) -> R {
+    let len = PROFILER_DATA.with_borrow(Vec::len);
+    let result = f();
+    PROFILER_DATA.with_borrow_mut(|data| data.truncate(len));
+
+    result
+}
+
+#[cfg(all(not(feature = "profiling"), feature = "check_fast_paths"))]
+#[inline(always)]
+pub(crate) fn unprofiled<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
@@ -0,0 +1,138 @@
+#[cfg(feature = "indicatif")]
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(feature = "indicatif")]
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// Where the progress bars of graphs built with `with_progress` are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressOutput {
+    Stderr,
+    Stdout,
+    None,
+}
+
+static PROGRESS_OUTPUT: AtomicU8 = const { AtomicU8::new(0) };
+
+/// Sets the output of every progress bar created from now on. Defaults to stderr.
+/// It does nothing unless the `indicatif` feature is enabled.
+pub fn set_progress_output(output: ProgressOutput) {
+    let value = match output {
+        ProgressOutput::Stderr => 0,
+        ProgressOutput::Stdout => 1,
+        ProgressOutput::None => 2,
+    };
+
+    PROGRESS_OUTPUT.store(value, Ordering::Relaxed);
+}
+
+#[cfg(feature = "indicatif")]
+fn progress_output() -> ProgressOutput {
+    match PROGRESS_OUTPUT.load(Ordering::Relaxed) {
+        0 => ProgressOutput::Stderr,
+        1 => ProgressOutput::Stdout,
+        _ => ProgressOutput::None,
+    }
+}
+
+#[cfg(feature = "indicatif")]
+thread_local! {
+    // Position and length of the last bar finished on this thread.
+    static LAST_PROGRESS: Cell<Option<(u64, u64)>> = const { Cell::new(None) };
+    // Whether the graphs computed on this thread show no bar, see hidden().
+    static HIDDEN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The position and the length of the last progress bar finished on this thread, once
+/// its graph was computed, whatever its output. The position is the length when every
+/// node of the graph was evaluated.
+#[cfg(feature = "indicatif")]
+pub fn last_progress() -> Option<(u64, u64)> {
+    LAST_PROGRESS.get()
+}
+
+// Runs `f` without showing or finishing any progress bar.
+#[cfg(all(feature = "indicatif", feature = "check_fast_paths"))]
+pub(crate) fn hidden<R>(f: impl FnOnce() -> R) -> R {
+    let hidden = HIDDEN.replace(true);
+    let result = f();
+    HIDDEN.set(hidden);
+
+    result
+}
+
+#[cfg(all(not(feature = "indicatif"), feature = "check_fast_paths"))]
+#[inline(always)]
+pub(crate) fn hidden<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+// Progress of a single graph evaluation. Every method is a no-op
+// when there is no label or the `indicatif` feature is disabled.
+pub(crate) struct GraphProgress {
+    #[cfg(feature = "indicatif")]
+    bar: Option<ProgressBar>,
+}
+
+impl GraphProgress {
+    #[cfg(feature = "indicatif")]
+    pub(crate) fn new(label: Option<&str>, total_nodes: usize) -> Self {
+        let label = label.filter(|_| !HIDDEN.get());
+        let bar = label.map(|label| {
+            let target = match progress_output() {
+                ProgressOutput::Stderr => ProgressDrawTarget::stderr(),
+                ProgressOutput::Stdout => ProgressDrawTarget::stdout(),
+                ProgressOutput::None => ProgressDrawTarget::hidden(),
+            };
+
+            let bar = ProgressBar::with_draw_target(Some(total_nodes as u64), target);
+            bar.set_style(
+                ProgressStyle::with_template(
+                    "{prefix} [{elapsed_precise}] {wide_bar} {pos}/{len} {msg}",
+                )
+                .expect("the progress template is valid"),
+            );
+            bar.set_prefix(label.to_string());
+
+            bar
+        });
+
+        Self { bar }
+    }
+
+    #[cfg(not(feature = "indicatif"))]
+    #[inline]
+    pub(crate) fn new(_label: Option<&str>, _total_nodes: usize) -> Self {
+        Self {}
+    }
+
+    // Shows the op about to be evaluated.
+    #[inline]
+    pub(crate) fn start<O: Debug>(&self, _op: &O) {
+        #[cfg(feature = "indicatif")]
+        if let Some(bar) = &self.bar {
+            bar.set_message(format!("{:?}", _op));
+        }
+    }
+
+    #[inline]
+    pub(crate) fn inc(&self) {
+        #[cfg(feature = "indicatif")]
+        if let Some(bar) = &self.bar {
+            bar.inc(1);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn finish(self) {
+        #[cfg(feature = "indicatif")]
+        if let Some(bar) = self.bar {
+            bar.finish();
+            LAST_PROGRESS.set(Some((bar.position(), bar.length().unwrap_or(0))));
+        }
+    }
+}
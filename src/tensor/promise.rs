@@ -1,14 +1,25 @@
 use std::marker::PhantomData;
 use std::sync::Arc;
 
+use crate::tensor::cache_pool::CachePool;
 use crate::tensor::definitions::NumberLike;
+use crate::tensor::device::Device;
 use crate::tensor::errors::OpError;
-use crate::tensor::graph::{NodeKind, TensorGraphCacheNode, TensorGraphNode};
+use crate::tensor::graph::{
+    self, GraphStats, NodeKind, Schedule, TensorGraphCacheNode, TensorGraphEdge, TensorGraphNode,
+    TensorId,
+};
 use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::numerics::NumericsMode;
+use crate::tensor::ops::ComputeWrapperSpec;
 use crate::tensor::ops::def_op::OpKind;
+use crate::tensor::stream;
 use crate::tensor::tensor::Tensor;
 use crate::tensor::traits::{Dimension, Promising};
 
+/// Results bigger than this are not folded by fold_constants().
+pub const DEFAULT_FOLD_LIMIT_BYTES: usize = 64 * 1024 * 1024;
+
 pub type TensorPromise<T> = RawTensorPromise<TensorGraphNode<T>>;
 pub type CachedTensorPromise<T> = RawTensorPromise<TensorGraphCacheNode<T>>;
 
@@ -34,12 +45,191 @@ impl<T: NumberLike> TensorPromise<T> {
         }
     }
 
+    /// Shows a progress bar labeled `label` while this promise is materialized,
+    /// see TensorGraphNode::with_progress(). A no-op without the `indicatif` feature.
+    pub fn with_progress(self, label: &str) -> Self {
+        let node = Arc::unwrap_or_clone(self.graph);
+
+        Self {
+            graph: Arc::new(node.with_progress(label)),
+        }
+    }
+
+    /// Computes the graph of this promise in `mode` when it is materialized, overriding
+    /// set_numerics_mode() for it alone. See NumericsMode.
+    pub fn with_numerics(self, mode: NumericsMode) -> Self {
+        let node = Arc::unwrap_or_clone(self.graph);
+
+        Self {
+            graph: Arc::new(node.with_numerics(mode)),
+        }
+    }
+
+    /// Joins the promises along `axis` once materialized. Every other axis must have
+    /// the same length in all of them.
+    pub fn concat(promises: &[TensorPromise<T>], axis: usize) -> Result<Self, OpError> {
+        let inputs = promises
+            .iter()
+            .map(|promise| NodeKind::Node(promise.graph.clone()))
+            .collect();
+
+        Self::new(
+            OpKind::Concat {
+                axis,
+                n_inputs: promises.len(),
+            },
+            inputs,
+        )
+    }
+
+    /// Names this promise in the errors of the ops built on top of it,
+    /// like "while computing Add ← Mul(label=attention_scores)".
+    pub fn label(self, label: &str) -> Self {
+        let node = Arc::unwrap_or_clone(self.graph);
+
+        Self {
+            graph: Arc::new(node.with_label(label)),
+        }
+    }
+
+    /// Evaluates once every subgraph that only depends on constant tensors, see Tensor::constant(),
+    /// and replaces it by a constant tensor holding its result.
+    /// Regular tensors are assumed to be mutable and are never folded.
+    /// Results bigger than DEFAULT_FOLD_LIMIT_BYTES are kept as they are.
+    pub fn fold_constants(&self) -> Self
+    where
+        T: ComputeWrapperSpec,
+    {
+        self.fold_constants_with_limit(DEFAULT_FOLD_LIMIT_BYTES)
+    }
+
+    /// Same as fold_constants(), but does not fold results bigger than `max_bytes`.
+    pub fn fold_constants_with_limit(&self, max_bytes: usize) -> Self
+    where
+        T: ComputeWrapperSpec,
+    {
+        let (node, is_constant) = self.graph.fold_constants(max_bytes);
+
+        if is_constant && node.layout.len() * std::mem::size_of::<T>() <= max_bytes {
+            let data = node.compute();
+            let edge = TensorGraphEdge::constant(data);
+
+            return unsafe {
+                Self::new(OpKind::NoOp, [NodeKind::Edge(Arc::new(edge))].into()).unwrap_unchecked()
+            };
+        }
+
+        Self {
+            graph: Arc::new(node),
+        }
+    }
+
+    /// Identity of the node of this promise, see Tensor::id().
+    #[inline]
+    pub fn id(&self) -> TensorId {
+        TensorId(self.graph.id)
+    }
+
+    /// Whether the graph is a single tensor followed by ops of a single input, without caches.
+    /// Those graphs are materialized by following the inputs, skipping the sort of the graph.
+    #[inline]
+    pub fn is_linear_chain(&self) -> bool {
+        self.graph.is_linear_chain
+    }
+
+    /// Computes this promise `chunk_elems` elements at a time, in row-major order, and hands
+    /// every chunk to `sink` with the position of its first element. No intermediate is
+    /// bigger than a chunk, so the output never has to fit in memory.
+    /// Only graphs of elementwise ops can be streamed, see OpKind::is_elementwise(),
+    /// although the tensors they read may be slices, transposes or flips of other tensors.
+    /// Anything else returns an error listing the offending nodes.
+    pub fn materialize_streamed(
+        &self,
+        chunk_elems: usize,
+        sink: impl FnMut(&[T], usize),
+    ) -> Result<(), OpError>
+    where
+        T: ComputeWrapperSpec,
+    {
+        stream::materialize_streamed(
+            &NodeKind::Node(self.graph.clone()),
+            self.len(),
+            chunk_elems,
+            sink,
+        )
+    }
+
+    /// Same as materialize(), computing the graph in the order of Schedule::MinMemory,
+    /// which keeps fewer intermediate results alive at once in wide graphs.
+    pub fn materialize_min_memory(self) -> Tensor<T>
+    where
+        T: ComputeWrapperSpec,
+    {
+        self.try_materialize_min_memory()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as materialize_min_memory(), failing with OpError::AssertionFailed if one of
+    /// the assertions of the graph does not hold.
+    pub fn try_materialize_min_memory(self) -> Result<Tensor<T>, OpError>
+    where
+        T: ComputeWrapperSpec,
+    {
+        let root = NodeKind::Node(self.graph.clone());
+        let data = graph::checked(|| graph::compute_many(&[root], Schedule::MinMemory))?.remove(0);
+
+        Ok(Tensor::from_data_named(data, self.graph.names().cloned()))
+    }
+
+    /// Number of nodes and peak memory of materializing this promise in the given order,
+    /// found by walking the graph without computing it.
+    /// materialize() follows Schedule::Topological.
+    pub fn graph_stats(&self, schedule: Schedule) -> GraphStats {
+        graph::graph_stats(&NodeKind::Node(self.graph.clone()), schedule)
+    }
+
+    /// The pending ops of this promise, one node per line in the order they would be computed,
+    /// each with its id, op, output shape and the ids of its inputs.
+    pub fn graph_summary(&self) -> String {
+        graph::graph_summary(&NodeKind::Node(self.graph.clone()))
+    }
+
+    /// Prints graph_summary() to stdout.
+    pub fn print_graph(&self) {
+        print!("{}", self.graph_summary());
+    }
+
+    /// The nodes at most `max_depth` inputs away from this promise, itself included at depth 0,
+    /// as "Node {id}: {op:?} (layout: {shape:?})" in the order they would be computed.
+    /// Tensors are listed with `Tensor` as their op.
+    pub fn trace_back(&self, max_depth: usize) -> Vec<String> {
+        graph::trace_back(&NodeKind::Node(self.graph.clone()), max_depth)
+    }
+
     pub fn cache(self) -> CachedTensorPromise<T> {
         unsafe {
             CachedTensorPromise::new(OpKind::NoOp, [NodeKind::Node(self.graph)].into())
                 .unwrap_unchecked()
         }
     }
+
+    /// Same as cache(), but the result counts towards the budget of `pool`, which may drop it
+    /// to make room for others. An evicted cache is computed again the next time it is read.
+    ///
+    /// Caches in a pool are always computed on their own, so materialize_many() computes
+    /// the nodes they share with the rest of the graph twice, and graph_stats() leaves
+    /// their subgraph out.
+    pub fn cache_in(&self, pool: &CachePool) -> CachedTensorPromise<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let node = unsafe {
+            TensorGraphNode::new(OpKind::NoOp, [NodeKind::Node(self.graph.clone())].into())
+                .unwrap_unchecked()
+        };
+
+        CachedTensorPromise::from_node(TensorGraphCacheNode::from_node_in(node, pool))
+    }
 }
 
 impl<T: NumberLike> CachedTensorPromise<T> {
@@ -65,14 +255,221 @@ impl<T: NumberLike> CachedTensorPromise<T> {
             graph: Arc::new(node),
         }
     }
+
+    /// Identity of the node of this promise, see Tensor::id().
+    #[inline]
+    pub fn id(&self) -> TensorId {
+        TensorId(self.graph.get_node().id)
+    }
+
+    /// Same as TensorPromise::materialize_streamed(), a filled cache is read like a tensor.
+    pub fn materialize_streamed(
+        &self,
+        chunk_elems: usize,
+        sink: impl FnMut(&[T], usize),
+    ) -> Result<(), OpError>
+    where
+        T: ComputeWrapperSpec,
+    {
+        stream::materialize_streamed(
+            &NodeKind::Cache(self.graph.clone()),
+            self.len(),
+            chunk_elems,
+            sink,
+        )
+    }
+
+    /// Same as TensorPromise::materialize_min_memory(), filling the cache.
+    pub fn materialize_min_memory(self) -> Tensor<T>
+    where
+        T: ComputeWrapperSpec,
+    {
+        self.try_materialize_min_memory()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as materialize_min_memory(), failing with OpError::AssertionFailed if one of
+    /// the assertions of the graph does not hold.
+    pub fn try_materialize_min_memory(self) -> Result<Tensor<T>, OpError>
+    where
+        T: ComputeWrapperSpec,
+    {
+        let root = NodeKind::Cache(self.graph.clone());
+        let data = graph::checked(|| graph::compute_many(&[root], Schedule::MinMemory))?.remove(0);
+
+        Ok(Tensor::from_data_named(data, self.graph.names().cloned()))
+    }
+
+    /// Same as TensorPromise::graph_stats(), a filled cache is read in place.
+    pub fn graph_stats(&self, schedule: Schedule) -> GraphStats {
+        graph::graph_stats(&NodeKind::Cache(self.graph.clone()), schedule)
+    }
+
+    /// Same as TensorPromise::graph_summary(), filled caches are marked with `[cached]`.
+    pub fn graph_summary(&self) -> String {
+        graph::graph_summary(&NodeKind::Cache(self.graph.clone()))
+    }
+
+    /// Prints graph_summary() to stdout.
+    pub fn print_graph(&self) {
+        print!("{}", self.graph_summary());
+    }
+
+    /// Whether the result was already computed by a previous materialization.
+    pub fn is_cached(&self) -> bool {
+        self.graph.is_cache_filled()
+    }
 }
 
 impl<P: Promising<Output: NumberLike>> RawTensorPromise<P> {
+    /// Computes the promise. Panics if its graph is deeper than set_max_graph_depth() allows
+    /// or if one of its assertions does not hold, see try_materialize().
     pub fn materialize(self) -> Tensor<P::Output> {
-        let data = self.graph.compute();
+        self.try_materialize()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as materialize(), failing with OpError::GraphTooDeep instead of computing a graph
+    /// deeper than set_max_graph_depth() allows, and with OpError::AssertionFailed if one of
+    /// its assertions does not hold, see TensorPromise::assert_finite(). With the
+    /// `validate_graphs` feature, the graph is also checked with validate_acyclic() first.
+    pub fn try_materialize(self) -> Result<Tensor<P::Output>, OpError> {
+        graph::check_depth(self.depth())?;
+
+        #[cfg(feature = "validate_graphs")]
+        self.validate_acyclic()?;
+
+        let names = self.graph.names().cloned();
+        let data = graph::checked(|| self.graph.compute())?;
 
-        Tensor::from_data(data)
+        Ok(Tensor::from_data_named(data, names))
     }
+
+    /// Ops on the longest path from a tensor read by this promise to the promise itself,
+    /// 1 for an op over tensors. Kept up to date as the graph is built, so reading it is free.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.graph.depth()
+    }
+
+    /// Device the result is placed on, the one shared by every input of the last op.
+    #[inline]
+    pub fn device(&self) -> Device {
+        self.graph.device()
+    }
+
+    /// Fails with OpError::CyclicGraph if a node of the graph is its own input. Graphs built
+    /// through the public API are always acyclic, so this is a debug check of the crate itself.
+    pub fn validate_acyclic(&self) -> Result<(), OpError> {
+        self.graph.validate_acyclic()
+    }
+
+    /// Computes this promise and writes the result into the existing buffer of `dest`,
+    /// so a pre-allocated output can be reused across runs. When nothing else reads `dest`,
+    /// elementwise ops write their result straight into it, without a buffer of their own.
+    /// `dest` must have the same shape as this promise, and is left untouched if one of the
+    /// assertions of the graph does not hold. If it shares its buffer with
+    /// anything else, like clones or promises built on top of it, it gets a buffer of its
+    /// own first, see Tensor::make_unique(), and the others keep the old elements.
+    /// Read-only tensors, like the ones over a mapped file, return ReadOnlyBuffer.
+    /// Fails with AliasedElements if several elements of `dest` are the same in memory,
+    /// like with Tensor::assign_from_iter().
+    pub fn materialize_into(self, dest: &mut Tensor<P::Output>) -> Result<(), OpError> {
+        if dest.is_read_only() {
+            return Err(OpError::ReadOnlyBuffer);
+        }
+
+        if self.shape() != dest.shape() {
+            return Err(OpError::NotSameShape(
+                dest.shape().into(),
+                self.shape().into(),
+            ));
+        }
+
+        // Nothing else reads dest, so the result is written straight into its buffer.
+        if let Some(dest_data) = dest.unique_data_mut() {
+            if dest_data.layout().may_alias() {
+                return Err(OpError::AliasedElements);
+            }
+
+            return graph::checked(|| self.graph.compute_into(dest_data));
+        }
+
+        // Computed first, as the promise may read dest itself
+        let data = graph::checked(|| self.graph.compute())?;
+        drop(self);
+        dest.make_unique();
+
+        let Some(dest_data) = dest.unique_data_mut() else {
+            return Err(OpError::SharedBuffer);
+        };
+
+        // Only checked once unique, as a copy made by make_unique() never aliases.
+        if dest_data.layout().may_alias() {
+            return Err(OpError::AliasedElements);
+        }
+
+        dest_data.write_from(&data);
+
+        Ok(())
+    }
+
+    /// Materializes the promise and checks whether any element is nonzero,
+    /// see [`Tensor::any_scalar`].
+    pub fn any_scalar(self) -> bool
+    where
+        P::Output: PartialEq,
+    {
+        self.materialize().any_scalar()
+    }
+
+    /// Materializes the promise and checks whether every element is nonzero,
+    /// see [`Tensor::all_scalar`].
+    pub fn all_scalar(self) -> bool
+    where
+        P::Output: PartialEq,
+    {
+        self.materialize().all_scalar()
+    }
+}
+
+/// Materializes several promises in a single pass over their graphs and returns
+/// the results in the same order. Nodes shared by the promises, or promises used
+/// by one another, are computed once without having to cache them.
+/// Panics if an assertion of any of the graphs does not hold, see try_materialize_many().
+pub fn materialize_many<T>(promises: &[&TensorPromise<T>]) -> Vec<Tensor<T>>
+where
+    T: NumberLike + ComputeWrapperSpec,
+{
+    try_materialize_many(promises).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Same as materialize_many(), failing with OpError::AssertionFailed if an assertion of
+/// any of the graphs does not hold.
+pub fn try_materialize_many<T>(promises: &[&TensorPromise<T>]) -> Result<Vec<Tensor<T>>, OpError>
+where
+    T: NumberLike + ComputeWrapperSpec,
+{
+    let sinks: Vec<NodeKind<T>> = promises
+        .iter()
+        .map(|promise| NodeKind::Node(promise.graph.clone()))
+        .collect();
+
+    let results = graph::checked(|| graph::compute_many(&sinks, Schedule::Topological))?;
+
+    Ok(results
+        .into_iter()
+        .zip(promises)
+        .map(|(data, promise)| Tensor::from_data_named(data, promise.graph.names().cloned()))
+        .collect())
+}
+
+/// Computes the graph of `promise` one node at a time, in the order of materialize(),
+/// and returns the id of the first node whose result has a NaN, see trace_back().
+/// A tensor holding a NaN is found as well. Returns None if there is no NaN within
+/// the first `max_nodes` nodes computed.
+pub fn detect_nan_in_graph(promise: TensorPromise<f64>, max_nodes: usize) -> Option<TensorId> {
+    graph::first_nan_node(&NodeKind::Node(promise.graph), max_nodes).map(TensorId)
 }
 
 impl<P: Promising> Dimension for RawTensorPromise<P> {
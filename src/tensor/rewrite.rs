@@ -0,0 +1,253 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::OpError;
+use crate::tensor::graph::{NodeKind, TensorGraphNode, TensorId, get_id};
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::ops::def_op::OpKind;
+use crate::tensor::promise::{CachedTensorPromise, TensorPromise};
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+/// A node of the graph being rewritten, as seen by the rule of a GraphRewriter.
+/// Its inputs are the ones left by the rewrite of the nodes below it.
+pub struct RewriteNodeView<'a, T: Copy> {
+    node: &'a NodeKind<T>,
+}
+
+impl<'a, T: Copy> RewriteNodeView<'a, T> {
+    #[inline]
+    pub fn id(&self) -> TensorId {
+        TensorId(get_id(self.node))
+    }
+
+    /// The op of the node, None for tensors.
+    pub fn op(&self) -> Option<&'a OpKind<T>> {
+        match self.node {
+            NodeKind::Edge(_) => None,
+            NodeKind::Node(node) => Some(&node.op),
+            NodeKind::Cache(cache) => Some(&cache.get_node().op),
+        }
+    }
+
+    #[inline]
+    pub fn is_tensor(&self) -> bool {
+        matches!(self.node, NodeKind::Edge(_))
+    }
+
+    #[inline]
+    pub fn is_cache(&self) -> bool {
+        matches!(self.node, NodeKind::Cache(_))
+    }
+
+    pub fn inputs(&self) -> Vec<RewriteNodeView<'a, T>> {
+        node_inputs(self.node)
+            .iter()
+            .map(|node| RewriteNodeView { node })
+            .collect()
+    }
+
+    /// The node itself, to be spliced somewhere else with Replacement::Node.
+    #[inline]
+    pub fn node(&self) -> NodeKind<T> {
+        self.node.clone()
+    }
+}
+
+impl<T: Copy> Dimension for RewriteNodeView<'_, T> {
+    fn layout(&self) -> &Layout {
+        match self.node {
+            NodeKind::Edge(edge) => edge.get().layout(),
+            NodeKind::Node(node) => &node.layout,
+            NodeKind::Cache(cache) => &cache.get_node().layout,
+        }
+    }
+}
+
+/// What a rule of a GraphRewriter puts in place of a node. It must have the same shape.
+pub enum Replacement<T: Copy> {
+    /// A new node computing the op over the inputs of the replaced node at the given
+    /// positions, which may be repeated or left out.
+    Op(OpKind<T>, Vec<usize>),
+    /// An existing node, spliced in as it is. Tensors and promises convert into one with
+    /// NodeKind::from().
+    Node(NodeKind<T>),
+}
+
+impl<T: Copy> From<&Tensor<T>> for NodeKind<T> {
+    fn from(tensor: &Tensor<T>) -> Self {
+        NodeKind::Edge(tensor.graph.clone())
+    }
+}
+
+impl<T: Copy> From<&TensorPromise<T>> for NodeKind<T> {
+    fn from(promise: &TensorPromise<T>) -> Self {
+        NodeKind::Node(promise.graph.clone())
+    }
+}
+
+impl<T: Copy> From<&CachedTensorPromise<T>> for NodeKind<T> {
+    fn from(promise: &CachedTensorPromise<T>) -> Self {
+        NodeKind::Cache(promise.graph.clone())
+    }
+}
+
+type Rule<'a, T> = dyn FnMut(&RewriteNodeView<T>) -> Option<Replacement<T>> + 'a;
+
+/// Rewrites the graph of a promise by calling a rule on every node, from the tensors
+/// up to the promise, and putting the Replacement it returns in place of the node.
+///
+/// Only the nodes reading a replaced node, directly or not, are built again, so the
+/// subgraphs left untouched are shared with the original graph, which is never modified.
+/// Every rebuilt node has its layout computed again, and a replacement that does not fit
+/// fails with the error of the op instead of panicking.
+///
+/// Caches are shown to the rule like any other node, but replacing one fails with
+/// OpError::ProtectedCache unless replace_caches(true) is set, as it drops what it holds.
+/// A cache rebuilt because something below it was replaced starts empty.
+pub struct GraphRewriter<'a, T: Copy> {
+    rule: Box<Rule<'a, T>>,
+    replace_caches: bool,
+}
+
+impl<'a, T: NumberLike> GraphRewriter<'a, T> {
+    pub fn new(rule: impl FnMut(&RewriteNodeView<T>) -> Option<Replacement<T>> + 'a) -> Self {
+        Self {
+            rule: Box::new(rule),
+            replace_caches: false,
+        }
+    }
+
+    /// Lets the rule replace caches.
+    pub fn replace_caches(mut self, replace_caches: bool) -> Self {
+        self.replace_caches = replace_caches;
+        self
+    }
+
+    /// The rewritten graph of `promise`. The rule is called once per node, even for nodes
+    /// read by several others. A promise whose graph is not replaced anywhere is returned
+    /// as it is, the same node.
+    pub fn rewrite(&mut self, promise: &TensorPromise<T>) -> Result<TensorPromise<T>, OpError> {
+        let root = NodeKind::Node(promise.graph.clone());
+        // The new version of every node that changed, by the id of the original one
+        let mut rewritten: HashMap<usize, NodeKind<T>> = HashMap::new();
+
+        for node in post_order(&root) {
+            let original_inputs = node_inputs(node);
+            let inputs: Box<[NodeKind<T>]> = (original_inputs.iter())
+                .map(|input| rewritten.get(&get_id(input)).unwrap_or(input).clone())
+                .collect();
+
+            let changed =
+                (original_inputs.iter()).any(|input| rewritten.contains_key(&get_id(input)));
+
+            let current = if changed {
+                rebuild(node, inputs)?
+            } else {
+                node.clone()
+            };
+
+            let replacement = (self.rule)(&RewriteNodeView { node: &current });
+
+            let current = match replacement {
+                None => current,
+                Some(replacement) => {
+                    if matches!(current, NodeKind::Cache(_)) && !self.replace_caches {
+                        return Err(OpError::ProtectedCache(get_id(node)));
+                    }
+
+                    replace(&current, replacement)?
+                }
+            };
+
+            if changed || get_id(&current) != get_id(node) {
+                rewritten.insert(get_id(node), current);
+            }
+        }
+
+        let graph = match rewritten.remove(&get_id(&root)) {
+            None => promise.graph.clone(),
+            Some(NodeKind::Node(node)) => node,
+            // Promises are nodes, tensors and caches are read through a NoOp
+            Some(other) => Arc::new(TensorGraphNode::new(OpKind::NoOp, [other].into())?),
+        };
+
+        Ok(TensorPromise { graph })
+    }
+}
+
+// Every input of the node, those behind filled caches included.
+fn node_inputs<T: Copy>(node: &NodeKind<T>) -> &[NodeKind<T>] {
+    match node {
+        NodeKind::Edge(_) => &[],
+        NodeKind::Node(node) => &node.inputs,
+        NodeKind::Cache(cache) => &cache.get_node().inputs,
+    }
+}
+
+// The nodes of the graph feeding `root`, each one after all of its inputs, root included.
+fn post_order<T: Copy>(root: &NodeKind<T>) -> Vec<&NodeKind<T>> {
+    let mut sorted = Vec::new();
+    let mut seen: HashSet<usize> = HashSet::new();
+    let mut stack: Vec<(&NodeKind<T>, bool)> = vec![(root, false)];
+
+    while let Some((node, exiting)) = stack.pop() {
+        if exiting {
+            sorted.push(node);
+            continue;
+        }
+
+        if !seen.insert(get_id(node)) {
+            continue;
+        }
+
+        stack.push((node, true));
+        stack.extend(node_inputs(node).iter().rev().map(|input| (input, false)));
+    }
+
+    sorted
+}
+
+// The node reading the rewritten `inputs` instead of its own.
+fn rebuild<T: NumberLike>(
+    node: &NodeKind<T>,
+    inputs: Box<[NodeKind<T>]>,
+) -> Result<NodeKind<T>, OpError> {
+    Ok(match node {
+        NodeKind::Edge(_) => node.clone(),
+        NodeKind::Node(node) => NodeKind::Node(Arc::new(node.rebuilt(inputs)?)),
+        NodeKind::Cache(cache) => {
+            let rebuilt = cache.get_node().rebuilt(inputs)?;
+            NodeKind::Cache(Arc::new(cache.with_node(rebuilt)))
+        }
+    })
+}
+
+fn replace<T: NumberLike>(
+    node: &NodeKind<T>,
+    replacement: Replacement<T>,
+) -> Result<NodeKind<T>, OpError> {
+    let view = RewriteNodeView { node };
+
+    let new = match replacement {
+        Replacement::Node(new) => new,
+        Replacement::Op(op, positions) => {
+            let inputs = node_inputs(node);
+            let inputs: Box<[NodeKind<T>]> = (positions.iter())
+                .map(|&i| {
+                    (inputs.get(i).cloned()).ok_or(OpError::IndexOutOfBounds(i, inputs.len()))
+                })
+                .collect::<Result<_, _>>()?;
+
+            NodeKind::Node(Arc::new(TensorGraphNode::new(op, inputs)?))
+        }
+    };
+
+    let new_shape = RewriteNodeView { node: &new }.shape().to_vec();
+    if new_shape != view.shape() {
+        return Err(OpError::NotSameShape(view.shape().into(), new_shape.into()));
+    }
+
+    Ok(new)
+}
@@ -0,0 +1,199 @@
+// Serialize and Deserialize for the tensors and layouts, behind the `serde` feature.
+//
+// Tensors are written as a struct named "TensorData":
+//   { format: u32, shape: [usize], device: Device, data: [T] }
+// with the elements in logical order, so any view is written dense and read back contiguous.
+// Memory shared between tensors is not preserved, every tensor read owns its buffer.
+// Promises are not serializable, materialize them first.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+use crate::tensor::device::Device;
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::storage::TensorData;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+/// Version of the format tensors are serialized in. Readers reject any newer version,
+/// and ignore the fields they do not know in the self-describing formats like JSON.
+/// Format 2 added the device of the tensor, read as the cpu from tensors of format 1.
+pub const TENSOR_FORMAT_VERSION: u32 = 2;
+
+// The elements of a tensor in logical order, written without collecting them first.
+struct Elements<'a, T: Copy>(&'a TensorData<T>);
+
+impl<T: Copy + Serialize> Serialize for Elements<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        self.0.fold(Ok(()), |result, el| {
+            result.and_then(|()| seq.serialize_element(&el))
+        })?;
+        seq.end()
+    }
+}
+
+impl<T: Copy + Serialize> Serialize for TensorData<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TensorData", 4)?;
+        state.serialize_field("format", &TENSOR_FORMAT_VERSION)?;
+        state.serialize_field("shape", self.shape())?;
+        state.serialize_field("device", &self.device())?;
+        state.serialize_field("data", &Elements(self))?;
+        state.end()
+    }
+}
+
+const TENSOR_FIELDS: &[&str] = &["format", "shape", "device", "data"];
+
+#[derive(serde::Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum TensorField {
+    Format,
+    Shape,
+    Device,
+    Data,
+    #[serde(other)]
+    Unknown,
+}
+
+struct TensorDataVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Copy + Deserialize<'de>> Visitor<'de> for TensorDataVisitor<T> {
+    type Value = TensorData<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a tensor")
+    }
+
+    // The compact formats like bincode write the fields in order and without names.
+    // Tensors of format 1 have no device, so it is only read from the newer ones.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let format: u32 = next_field(&mut seq, 0)?;
+        check_format(format)?;
+
+        let shape: Vec<usize> = next_field(&mut seq, 1)?;
+        let device = match format {
+            1 => Device::Cpu,
+            _ => next_field(&mut seq, 2)?,
+        };
+        let data: Vec<T> = next_field(&mut seq, 3)?;
+
+        tensor_data(shape, device, data)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut format: Option<u32> = None;
+        let mut shape: Option<Vec<usize>> = None;
+        let mut device: Option<Device> = None;
+        let mut data: Option<Vec<T>> = None;
+
+        while let Some(field) = map.next_key()? {
+            match field {
+                TensorField::Format => format = Some(map.next_value()?),
+                TensorField::Shape => shape = Some(map.next_value()?),
+                TensorField::Device => device = Some(map.next_value()?),
+                TensorField::Data => data = Some(map.next_value()?),
+                TensorField::Unknown => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        check_format(format.ok_or_else(|| de::Error::missing_field("format"))?)?;
+        let shape = shape.ok_or_else(|| de::Error::missing_field("shape"))?;
+        let data = data.ok_or_else(|| de::Error::missing_field("data"))?;
+
+        tensor_data(shape, device.unwrap_or_default(), data)
+    }
+}
+
+fn next_field<'de, A: SeqAccess<'de>, F: Deserialize<'de>>(
+    seq: &mut A,
+    index: usize,
+) -> Result<F, A::Error> {
+    seq.next_element()?
+        .ok_or_else(|| de::Error::invalid_length(index, &"the fields of a tensor"))
+}
+
+fn check_format<E: de::Error>(format: u32) -> Result<(), E> {
+    if format > TENSOR_FORMAT_VERSION {
+        return Err(E::custom(format_args!(
+            "tensor format {} is newer than the supported {}",
+            format, TENSOR_FORMAT_VERSION
+        )));
+    }
+
+    Ok(())
+}
+
+fn tensor_data<T: Copy, E: de::Error>(
+    shape: Vec<usize>,
+    device: Device,
+    data: Vec<T>,
+) -> Result<TensorData<T>, E> {
+    let len: usize = shape.iter().product();
+    if data.len() != len {
+        return Err(E::invalid_length(
+            data.len(),
+            &format!("{} elements for the shape {:?}", len, shape).as_str(),
+        ));
+    }
+
+    Ok(TensorData::from_vec(data, &shape, 0).with_device(device))
+}
+
+impl<'de, T: Copy + Deserialize<'de>> Deserialize<'de> for TensorData<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("TensorData", TENSOR_FIELDS, TensorDataVisitor(PhantomData))
+    }
+}
+
+/// Writes the data of the tensor, see TensorData. Axis names are not kept.
+impl<T: Copy + Serialize> Serialize for Tensor<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data().serialize(serializer)
+    }
+}
+
+impl<'de, T: Copy + Deserialize<'de>> Deserialize<'de> for Tensor<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TensorData::deserialize(deserializer).map(Tensor::from_data)
+    }
+}
+
+impl Serialize for Layout {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Layout", 3)?;
+        state.serialize_field("shape", self.shape())?;
+        state.serialize_field("stride", self.stride())?;
+        state.serialize_field("offset", &self.offset)?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "Layout")]
+struct LayoutRepr {
+    shape: Vec<usize>,
+    stride: Vec<i32>,
+    offset: usize,
+}
+
+impl<'de> Deserialize<'de> for Layout {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = LayoutRepr::deserialize(deserializer)?;
+
+        if repr.stride.len() != repr.shape.len() {
+            return Err(de::Error::invalid_length(
+                repr.stride.len(),
+                &"one stride per axis",
+            ));
+        }
+
+        Ok(Layout::from_slice(&repr.shape, &repr.stride, repr.offset))
+    }
+}
@@ -0,0 +1,302 @@
+//! Checks of the shape of tensors whose dimensions are only partly known.
+//!
+//! A pattern lists what each axis must be: any length (`_`), an exact one (`128`), or a
+//! name that binds the length for later use (`h`). A single `..` stands for any number
+//! of axes, so `[.., 3]` only checks the last one. A name used twice must match
+//! the same length both times.
+//!
+//! ```
+//! use simple_tensor::expect_shape;
+//! use simple_tensor::tensor::Tensor;
+//!
+//! let t = Tensor::from_scalar(0.0, &[4, 128, 8]);
+//!
+//! expect_shape!(t, [_, 128, heads]);
+//! assert_eq!(heads, 8);
+//!
+//! expect_shape!(t, [batch, ..]);
+//! assert_eq!(batch * heads, 32);
+//! ```
+//!
+//! The macro panics on a mismatch, ShapePattern returns the error instead:
+//!
+//! ```
+//! use simple_tensor::tensor::Tensor;
+//! use simple_tensor::tensor::shape_check::ShapePattern;
+//!
+//! let t = Tensor::from_scalar(0.0, &[4, 64, 8]);
+//! let err = ShapePattern::new().any().exact(128).bind("h").matches(&t).unwrap_err();
+//!
+//! assert_eq!(
+//!     err.to_string(),
+//!     "shape [4, 64, 8] does not match [_, 128, h]\n          ^^ expected 128 at axis 1"
+//! );
+//! ```
+
+use std::fmt::Display;
+use std::ops::Index;
+
+use crate::tensor::traits::Dimension;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DimPattern {
+    Any,
+    Exact(usize),
+    Bind(Box<str>),
+    // Any number of axes, at most once per pattern.
+    Rest,
+}
+
+/// What the shape of a tensor must look like, built one axis at a time.
+/// See the module documentation and the expect_shape! macro.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShapePattern {
+    dims: Vec<DimPattern>,
+}
+
+impl ShapePattern {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An axis of any length.
+    pub fn any(mut self) -> Self {
+        self.dims.push(DimPattern::Any);
+        self
+    }
+
+    /// An axis of exactly `len` elements.
+    pub fn exact(mut self, len: usize) -> Self {
+        self.dims.push(DimPattern::Exact(len));
+        self
+    }
+
+    /// An axis of any length, bound to `name` in the returned Bindings.
+    /// Every axis bound to the same name must have the same length.
+    pub fn bind(mut self, name: &str) -> Self {
+        self.dims.push(DimPattern::Bind(name.into()));
+        self
+    }
+
+    /// Any number of axes, including none.
+    ///
+    /// # Panics
+    /// If the pattern already has one.
+    pub fn rest(mut self) -> Self {
+        assert!(
+            !self.dims.contains(&DimPattern::Rest),
+            "a shape pattern can only have one `..`"
+        );
+
+        self.dims.push(DimPattern::Rest);
+        self
+    }
+
+    /// Checks the shape of `tensor`, returning the lengths of the bound axes.
+    pub fn matches(&self, tensor: &dyn Dimension) -> Result<Bindings, ShapeMismatch> {
+        self.matches_shape(tensor.shape())
+    }
+
+    /// Same as matches(), for a shape that is not attached to a tensor.
+    pub fn matches_shape(&self, shape: &[usize]) -> Result<Bindings, ShapeMismatch> {
+        let rest = self.dims.iter().position(|dim| *dim == DimPattern::Rest);
+        let fixed = self.dims.len() - rest.is_some() as usize;
+
+        let rank_matches = match rest {
+            Some(_) => shape.len() >= fixed,
+            None => shape.len() == fixed,
+        };
+
+        if !rank_matches {
+            let at_least = if rest.is_some() { "at least " } else { "" };
+            let reason = format!("expected {}{} axes, found {}", at_least, fixed, shape.len());
+
+            return Err(self.mismatch(shape, None, reason));
+        }
+
+        // The axes before the `..` are matched from the front, the ones after it from the back.
+        let axis_of = |i: usize| match rest {
+            Some(rest) if i > rest => shape.len() - (self.dims.len() - i),
+            _ => i,
+        };
+
+        let mut bindings = Bindings::default();
+
+        for (i, dim) in self.dims.iter().enumerate() {
+            let axis = axis_of(i);
+
+            match dim {
+                DimPattern::Any | DimPattern::Rest => {}
+                DimPattern::Exact(len) if shape[axis] != *len => {
+                    let reason = format!("expected {} at axis {}", len, axis);
+                    return Err(self.mismatch(shape, Some(axis), reason));
+                }
+                DimPattern::Exact(_) => {}
+                DimPattern::Bind(name) => match bindings.get(name) {
+                    Some(len) if shape[axis] != len => {
+                        let reason = format!("expected {} = {} at axis {}", name, len, axis);
+                        return Err(self.mismatch(shape, Some(axis), reason));
+                    }
+                    Some(_) => {}
+                    None => bindings.dims.push((name.clone(), shape[axis])),
+                },
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    fn mismatch(&self, shape: &[usize], axis: Option<usize>, reason: String) -> ShapeMismatch {
+        ShapeMismatch {
+            shape: shape.into(),
+            pattern: self.clone(),
+            axis,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for ShapePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dims: Vec<String> = self
+            .dims
+            .iter()
+            .map(|dim| match dim {
+                DimPattern::Any => "_".to_string(),
+                DimPattern::Exact(len) => len.to_string(),
+                DimPattern::Bind(name) => name.to_string(),
+                DimPattern::Rest => "..".to_string(),
+            })
+            .collect();
+
+        write!(f, "[{}]", dims.join(", "))
+    }
+}
+
+/// Lengths of the axes bound by a ShapePattern, by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bindings {
+    dims: Vec<(Box<str>, usize)>,
+}
+
+impl Bindings {
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.dims
+            .iter()
+            .find(|(bound, _)| **bound == *name)
+            .map(|(_, len)| *len)
+    }
+
+    /// The names and their lengths, in the order they appear in the pattern.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.dims.iter().map(|(name, len)| (&**name, *len))
+    }
+}
+
+impl Index<&str> for Bindings {
+    type Output = usize;
+
+    fn index(&self, name: &str) -> &usize {
+        match self.dims.iter().find(|(bound, _)| **bound == *name) {
+            Some((_, len)) => len,
+            None => panic!("no axis is bound to {}", name),
+        }
+    }
+}
+
+/// A shape that does not match a ShapePattern. Displayed with a caret under the first
+/// axis that does not match, if the number of axes is right.
+#[derive(Clone, Debug)]
+pub struct ShapeMismatch {
+    shape: Box<[usize]>,
+    pattern: ShapePattern,
+    axis: Option<usize>,
+    reason: Box<str>,
+}
+
+impl ShapeMismatch {
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn pattern(&self) -> &ShapePattern {
+        &self.pattern
+    }
+
+    /// The first axis that does not match, None if the number of axes is wrong.
+    pub fn axis(&self) -> Option<usize> {
+        self.axis
+    }
+}
+
+impl Display for ShapeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dims: Vec<String> = self.shape.iter().map(|len| len.to_string()).collect();
+        let prefix = "shape [";
+
+        writeln!(
+            f,
+            "{}{}] does not match {}",
+            prefix,
+            dims.join(", "),
+            self.pattern
+        )?;
+
+        match self.axis {
+            Some(axis) => {
+                let column = prefix.len() + dims[..axis].iter().map(|d| d.len() + 2).sum::<usize>();
+
+                write!(
+                    f,
+                    "{}{} {}",
+                    " ".repeat(column),
+                    "^".repeat(dims[axis].len()),
+                    self.reason
+                )
+            }
+            None => write!(f, "{}", self.reason),
+        }
+    }
+}
+
+impl std::error::Error for ShapeMismatch {}
+
+/// Asserts the shape of a tensor against a pattern like `[_, 128, h]` and binds every
+/// named axis to a local variable, see the shape_check module.
+///
+/// # Panics
+/// If the shape does not match, with the message of the ShapeMismatch.
+#[macro_export]
+macro_rules! expect_shape {
+    ($tensor: expr, [$($dims: tt)*]) => {
+        $crate::expect_shape!(
+            @munch $tensor; $crate::tensor::shape_check::ShapePattern::new(); []; $($dims)*
+        );
+    };
+
+    (@munch $tensor: expr; $pattern: expr; [$($bound: ident)*];) => {
+        let _bindings = match $pattern.matches(&$tensor) {
+            Ok(bindings) => bindings,
+            Err(err) => panic!("{}", err),
+        };
+        $(let $bound = _bindings[stringify!($bound)];)*
+    };
+
+    (@munch $tensor: expr; $pattern: expr; [$($bound: ident)*]; _ $(, $($rest: tt)*)?) => {
+        $crate::expect_shape!(@munch $tensor; $pattern.any(); [$($bound)*]; $($($rest)*)?)
+    };
+
+    (@munch $tensor: expr; $pattern: expr; [$($bound: ident)*]; .. $(, $($rest: tt)*)?) => {
+        $crate::expect_shape!(@munch $tensor; $pattern.rest(); [$($bound)*]; $($($rest)*)?)
+    };
+
+    (@munch $tensor: expr; $pattern: expr; [$($bound: ident)*]; $len: literal $(, $($rest: tt)*)?) => {
+        $crate::expect_shape!(@munch $tensor; $pattern.exact($len); [$($bound)*]; $($($rest)*)?)
+    };
+
+    (@munch $tensor: expr; $pattern: expr; [$($bound: ident)*]; $name: ident $(, $($rest: tt)*)?) => {
+        $crate::expect_shape!(
+            @munch $tensor; $pattern.bind(stringify!($name)); [$($bound)* $name]; $($($rest)*)?
+        )
+    };
+}
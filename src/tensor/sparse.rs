@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::OpError;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+/// Sparse tensor in the coordinate (COO) format. The column `j` of `indices`, of shape
+/// `[ndim, nnz]`, holds the position of `values[j]` in the dense tensor.
+/// Repeated positions are allowed and their values are summed when densified.
+pub struct SparseTensor<T: Copy> {
+    indices: Tensor<usize>,
+    values: Tensor<T>,
+    dense_shape: Box<[usize]>,
+}
+
+// Row-major position of every entry in the dense buffer.
+fn linear_indices<T: Copy>(sparse: &SparseTensor<T>) -> Vec<usize> {
+    let nnz = sparse.nnz();
+    let mut linear = vec![0; nnz];
+
+    for (i, &index) in sparse.indices.iter().enumerate() {
+        let dim = i / nnz;
+        linear[i % nnz] = linear[i % nnz] * sparse.dense_shape[dim] + index;
+    }
+
+    linear
+}
+
+// Inverse of linear_indices(), builds the [ndim, nnz] indices of the given positions.
+fn coo_indices(linear: &[usize], shape: &[usize]) -> Tensor<usize> {
+    let nnz = linear.len();
+    let mut indices = vec![0; shape.len() * nnz];
+
+    for (j, &position) in linear.iter().enumerate() {
+        let mut remaining = position;
+
+        for dim in (0..shape.len()).rev() {
+            indices[dim * nnz + j] = remaining % shape[dim];
+            remaining /= shape[dim];
+        }
+    }
+
+    Tensor::from_vec(indices, &[shape.len(), nnz])
+}
+
+impl<T: Copy> SparseTensor<T> {
+    /// `indices` must have the shape `[dense_shape.len(), nnz]` and `values` the shape `[nnz]`.
+    pub fn new(
+        indices: Tensor<usize>,
+        values: Tensor<T>,
+        dense_shape: &[usize],
+    ) -> Result<Self, OpError> {
+        if values.shape().len() != 1 {
+            return Err(OpError::NotEnoughAxes(1, values.shape().len()));
+        }
+
+        let nnz = values.shape()[0];
+
+        if indices.shape() != [dense_shape.len(), nnz] {
+            return Err(OpError::NotSameShape(
+                [dense_shape.len(), nnz].into(),
+                indices.shape().into(),
+            ));
+        }
+
+        for (i, &index) in indices.iter().enumerate() {
+            let len = dense_shape[i / nnz];
+
+            if index >= len {
+                return Err(OpError::IndexOutOfBounds(index, len));
+            }
+        }
+
+        Ok(Self {
+            indices,
+            values,
+            dense_shape: dense_shape.into(),
+        })
+    }
+
+    #[inline]
+    pub fn indices(&self) -> &Tensor<usize> {
+        &self.indices
+    }
+
+    #[inline]
+    pub fn values(&self) -> &Tensor<T> {
+        &self.values
+    }
+
+    #[inline]
+    pub fn dense_shape(&self) -> &[usize] {
+        &self.dense_shape
+    }
+
+    /// Number of stored entries.
+    #[inline]
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl<T: NumberLike> SparseTensor<T> {
+    /// Keeps the elements of `dense` greater than `threshold` in absolute value.
+    /// The entries are ordered as in a row-major walk of the dense tensor.
+    pub fn from_dense(dense: &Tensor<T>, threshold: T) -> Self
+    where
+        T: PartialOrd,
+    {
+        let shape = dense.shape();
+
+        let (linear, values): (Vec<usize>, Vec<T>) = dense
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, value)| value > threshold || value < -threshold)
+            .unzip();
+
+        let nnz = values.len();
+
+        Self {
+            indices: coo_indices(&linear, shape),
+            values: Tensor::from_vec(values, &[nnz]),
+            dense_shape: shape.into(),
+        }
+    }
+
+    /// Allocates a zeroed tensor of the dense shape and writes every entry into it.
+    pub fn to_dense(&self) -> Tensor<T> {
+        let len: usize = self.dense_shape.iter().product();
+        let mut out = vec![T::default(); len];
+
+        for (linear, &value) in linear_indices(self).into_iter().zip(self.values.iter()) {
+            out[linear] = out[linear] + value;
+        }
+
+        Tensor::from_vec(out, &self.dense_shape)
+    }
+}
+
+/// Sum of two sparse tensors with the same dense shape. Entries at the same position are
+/// merged, so the result is ordered and has no repeated positions.
+pub fn sparse_add<T: NumberLike>(
+    a: &SparseTensor<T>,
+    b: &SparseTensor<T>,
+) -> Result<SparseTensor<T>, OpError> {
+    if a.dense_shape != b.dense_shape {
+        return Err(OpError::NotSameShape(
+            a.dense_shape.clone(),
+            b.dense_shape.clone(),
+        ));
+    }
+
+    let mut merged: BTreeMap<usize, T> = BTreeMap::new();
+
+    for sparse in [a, b] {
+        for (linear, &value) in linear_indices(sparse).into_iter().zip(sparse.values.iter()) {
+            let slot = merged.entry(linear).or_default();
+            *slot = *slot + value;
+        }
+    }
+
+    let linear: Vec<usize> = merged.keys().copied().collect();
+    let nnz = linear.len();
+
+    Ok(SparseTensor {
+        indices: coo_indices(&linear, &a.dense_shape),
+        values: Tensor::from_iter(merged.into_values(), &[nnz]),
+        dense_shape: a.dense_shape.clone(),
+    })
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::ops::{Add, Index, Mul, Neg, Sub};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::OpError;
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+/// A matrix whose shape is known at compile time, stored inline as `[[T; C]; R]`.
+///
+/// It does not enter the lazy graph: every operator is computed right away, over loops of
+/// fixed length that the compiler unrolls, and nothing is allocated. It is meant for tight
+/// loops over small matrices; to_dynamic() and Tensor::to_static() convert between both.
+///
+/// Unlike Tensor, `*` is the matrix product.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StaticTensor<T, const R: usize, const C: usize> {
+    data: [[T; C]; R],
+}
+
+impl<T: NumberLike, const R: usize, const C: usize> StaticTensor<T, R, C> {
+    #[inline]
+    pub fn new(data: [[T; C]; R]) -> Self {
+        Self { data }
+    }
+
+    #[inline]
+    pub fn from_scalar(scalar: T) -> Self {
+        Self::new([[scalar; C]; R])
+    }
+
+    #[inline]
+    pub fn zeros() -> Self {
+        Self::from_scalar(T::default())
+    }
+
+    #[inline]
+    pub fn rows(&self) -> &[[T; C]; R] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn transpose(&self) -> StaticTensor<T, C, R> {
+        StaticTensor::new(std::array::from_fn(|c| {
+            std::array::from_fn(|r| self.data[r][c])
+        }))
+    }
+
+    /// Copies the elements into a new Tensor of shape [R, C].
+    pub fn to_dynamic(&self) -> Tensor<T> {
+        Tensor::from_iter(self.data.iter().flatten().copied(), &[R, C])
+    }
+
+    #[inline]
+    fn zip_with(&self, rhs: &Self, f: impl Fn(T, T) -> T) -> Self {
+        Self::new(std::array::from_fn(|r| {
+            std::array::from_fn(|c| f(self.data[r][c], rhs.data[r][c]))
+        }))
+    }
+}
+
+impl<const R: usize, const C: usize> StaticTensor<f64, R, C> {
+    /// `1 / x` for every element, infinite for 0.
+    #[inline]
+    pub fn recip(&self) -> Self {
+        Self::new(self.data.map(|row| row.map(|el| 1.0 / el)))
+    }
+
+    /// `1 / sqrt(x)` for every element, infinite for 0 and NaN for the negative ones.
+    #[inline]
+    pub fn rsqrt(&self) -> Self {
+        Self::new(self.data.map(|row| row.map(|el| 1.0 / el.sqrt())))
+    }
+}
+
+impl<T: NumberLike> Tensor<T> {
+    /// Copies the elements into a StaticTensor, failing with NotSameShape if this tensor
+    /// is not of shape [R, C].
+    pub fn to_static<const R: usize, const C: usize>(
+        &self,
+    ) -> Result<StaticTensor<T, R, C>, OpError> {
+        if self.shape() != [R, C] {
+            return Err(OpError::NotSameShape([R, C].into(), self.shape().into()));
+        }
+
+        let mut out = StaticTensor::zeros();
+        for (el, &value) in out.data.iter_mut().flatten().zip(self.iter()) {
+            *el = value;
+        }
+
+        Ok(out)
+    }
+}
+
+impl<T, const R: usize, const C: usize> Index<(usize, usize)> for StaticTensor<T, R, C> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        &self.data[row][col]
+    }
+}
+
+impl<T: NumberLike, const R: usize, const C: usize> Add for StaticTensor<T, R, C> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        self.zip_with(&rhs, |a, b| a + b)
+    }
+}
+
+impl<T: NumberLike, const R: usize, const C: usize> Sub for StaticTensor<T, R, C> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        self.zip_with(&rhs, |a, b| a - b)
+    }
+}
+
+impl<T: NumberLike, const R: usize, const C: usize, const K: usize> Mul<StaticTensor<T, C, K>>
+    for StaticTensor<T, R, C>
+{
+    type Output = StaticTensor<T, R, K>;
+
+    #[inline]
+    fn mul(self, rhs: StaticTensor<T, C, K>) -> StaticTensor<T, R, K> {
+        StaticTensor::new(std::array::from_fn(|r| {
+            std::array::from_fn(|k| {
+                (0..C).fold(T::default(), |acc, c| {
+                    acc + self.data[r][c] * rhs.data[c][k]
+                })
+            })
+        }))
+    }
+}
+
+impl<T: NumberLike, const R: usize, const C: usize> Neg for StaticTensor<T, R, C> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(self.data.map(|row| row.map(|el| -el)))
+    }
+}
+
+// The layouts are shared by every StaticTensor of the same shape, and leaked, so reading the
+// shape of one allocates at most once per shape.
+fn static_layout(rows: usize, cols: usize) -> &'static Layout {
+    static LAYOUTS: OnceLock<Mutex<HashMap<(usize, usize), &'static Layout>>> = OnceLock::new();
+
+    let mut layouts =
+        (LAYOUTS.get_or_init(Default::default).lock()).unwrap_or_else(PoisonError::into_inner);
+
+    layouts
+        .entry((rows, cols))
+        .or_insert_with(|| Box::leak(Box::new(Layout::from_shape(&[rows, cols], 0))))
+}
+
+impl<T, const R: usize, const C: usize> Dimension for StaticTensor<T, R, C> {
+    #[inline]
+    fn layout(&self) -> &Layout {
+        static_layout(R, C)
+    }
+}
+
+// Formatted like the Tensor of the same shape. It goes through to_dynamic(), so it allocates.
+impl<T: std::fmt::Display + NumberLike, const R: usize, const C: usize> std::fmt::Display
+    for StaticTensor<T, R, C>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_dynamic().fmt(f)
+    }
+}
@@ -1,10 +1,20 @@
+use std::borrow::Cow;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::tensor::device::Device;
+use crate::tensor::errors::{OpError, ParseError};
+use crate::tensor::internals::{TRANSPOSE_BLOCK_SIZE, transpose_copy_2d};
+#[cfg(feature = "parallel")]
+use crate::tensor::iter::ParIter;
 use crate::tensor::iter::{
     ChunkedSliceIter, ContiguousIter, CopiedContiguousIter, CopiedSliceIter, InformedSliceIter,
-    SliceIter,
+    SliceIter, fold_rows,
 };
 use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::parse;
+use crate::tensor::pretty::{self, FLAT_ELEMENT_BUDGET};
 use crate::tensor::traits::Dimension;
 use crate::{debug_assert_positive, impl_display};
 
@@ -15,31 +25,189 @@ pub enum IterImpl<C, N> {
 
 //////////////////////////////////////////////////////////////////////////////////////////////////
 
+pub type ExternalDeleter<T> = Box<dyn FnOnce(*mut T, usize) + Send>;
+
+// Memory owned by someone else, e.g. a C++ application, see Tensor::from_raw_parts().
+pub struct ExternalBuffer<T> {
+    ptr: *mut T,
+    len: usize,
+    deleter: Option<ExternalDeleter<T>>,
+}
+
+// The caller of Tensor::from_raw_parts() guarantees that the memory can be
+// accessed from any thread while the tensor is alive.
+unsafe impl<T: Send> Send for ExternalBuffer<T> {}
+unsafe impl<T: Sync> Sync for ExternalBuffer<T> {}
+
+impl<T> Drop for ExternalBuffer<T> {
+    fn drop(&mut self) {
+        if let Some(deleter) = self.deleter.take() {
+            deleter(self.ptr, self.len);
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ExternalBuffer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternalBuffer")
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .field("has_deleter", &self.deleter.is_some())
+            .finish()
+    }
+}
+
+// Elements of a memory-mapped file, see TensorData::open_npy_mmap(). They are never written,
+// and the file is unmapped when the last tensor or promise reading them is dropped.
+#[cfg(feature = "mmap")]
+pub struct MappedBuffer<T> {
+    map: memmap2::Mmap,
+    // Position of the first element in the map, in bytes, a multiple of the alignment of T.
+    start: usize,
+    len: usize,
+    _elements: std::marker::PhantomData<T>,
+}
+
+#[cfg(feature = "mmap")]
+impl<T> MappedBuffer<T> {
+    // The caller checks that the `len` elements from `start` are inside the map, aligned,
+    // and that any bytes are a valid T.
+    pub(crate) unsafe fn new(map: memmap2::Mmap, start: usize, len: usize) -> Self {
+        debug_assert!(start + len * std::mem::size_of::<T>() <= map.len());
+        debug_assert_eq!(start % std::mem::align_of::<T>(), 0);
+
+        Self {
+            map,
+            start,
+            len,
+            _elements: std::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<T> std::fmt::Debug for MappedBuffer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MappedBuffer")
+            .field("ptr", &self.map.as_ptr())
+            .field("start", &self.start)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub enum Buffer<T> {
+    Owned(Vec<T>),
+    External(ExternalBuffer<T>),
+    #[cfg(feature = "mmap")]
+    Mapped(MappedBuffer<T>),
+}
+
+impl<T> Deref for Buffer<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        match self {
+            Buffer::Owned(v) => v,
+            Buffer::External(ext) => unsafe { std::slice::from_raw_parts(ext.ptr, ext.len) },
+            #[cfg(feature = "mmap")]
+            Buffer::Mapped(mapped) => unsafe {
+                let first = mapped.map.as_ptr().add(mapped.start) as *const T;
+                std::slice::from_raw_parts(first, mapped.len)
+            },
+        }
+    }
+}
+
+impl<T> DerefMut for Buffer<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            Buffer::Owned(v) => v,
+            Buffer::External(ext) => unsafe { std::slice::from_raw_parts_mut(ext.ptr, ext.len) },
+            // Storage::as_mut_slice() never hands them out.
+            #[cfg(feature = "mmap")]
+            Buffer::Mapped(_) => unreachable!("memory-mapped buffers are read-only"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Storage<T: Copy> {
-    pub(crate) buffer: Arc<Vec<T>>,
+    pub(crate) buffer: Arc<Buffer<T>>,
 }
 
 impl<T: Copy> Storage<T> {
     #[inline]
     pub fn from_scalar(scalar: T, len: usize) -> Self {
-        Self {
-            buffer: Arc::new(vec![scalar; len]),
-        }
+        Self::from_vec(vec![scalar; len])
     }
 
     #[inline]
-    pub fn from_arc(buffer: Arc<Vec<T>>) -> Self {
+    pub fn from_arc(buffer: Arc<Buffer<T>>) -> Self {
         Self { buffer }
     }
 
     #[inline]
     pub fn from_vec(vector: Vec<T>) -> Self {
         Self {
-            buffer: Arc::new(vector),
+            buffer: Arc::new(Buffer::Owned(vector)),
         }
     }
 
+    /// Wraps `len` elements starting at `ptr` without copying them.
+    /// `deleter` is called once, when the last reference to the buffer is dropped.
+    ///
+    /// # Safety
+    /// See Tensor::from_raw_parts().
+    #[inline]
+    pub unsafe fn from_raw_parts(
+        ptr: *mut T,
+        len: usize,
+        deleter: Option<ExternalDeleter<T>>,
+    ) -> Self {
+        Self {
+            buffer: Arc::new(Buffer::External(ExternalBuffer { ptr, len, deleter })),
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[inline]
+    pub(crate) fn from_mapped(buffer: MappedBuffer<T>) -> Self {
+        Self {
+            buffer: Arc::new(Buffer::Mapped(buffer)),
+        }
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.buffer
+    }
+
+    // Mutable access to the elements, only if nothing else shares the buffer
+    // and it can be written.
+    #[inline]
+    pub(crate) fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        if self.is_read_only() {
+            return None;
+        }
+
+        Arc::get_mut(&mut self.buffer).map(|buffer| &mut **buffer)
+    }
+
+    // Whether the elements can never be written, like the ones of a mapped file.
+    #[inline]
+    pub(crate) fn is_read_only(&self) -> bool {
+        #[cfg(feature = "mmap")]
+        if let Buffer::Mapped(_) = *self.buffer {
+            return true;
+        }
+
+        false
+    }
+
     #[inline]
     pub fn from_iter<I>(iter: I) -> Self
     where
@@ -53,6 +221,12 @@ impl<T: Copy> Storage<T> {
     pub fn clone_reference(&self) -> Self {
         Storage::from_arc(self.buffer.clone())
     }
+
+    // Whether another storage references the same buffer.
+    #[inline]
+    pub(crate) fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.buffer) > 1
+    }
 }
 
 impl<T: Copy> Clone for Storage<T> {
@@ -69,6 +243,9 @@ pub struct TensorData<T: Copy> {
     pub(crate) storage: Storage<T>,
     layout: Layout,
     pub(crate) reusable: bool,
+    device: Device,
+    // Fraction of nonzero elements, see Tensor::with_density_hint().
+    density: Option<f32>,
 }
 
 impl<T: Copy> TensorData<T> {
@@ -78,6 +255,8 @@ impl<T: Copy> TensorData<T> {
             storage,
             layout,
             reusable: false,
+            device: Device::Cpu,
+            density: None,
         }
     }
 
@@ -91,26 +270,32 @@ impl<T: Copy> TensorData<T> {
             storage: Storage::from_scalar(scalar, len as usize),
             layout: Layout::from_shape(shape, 0),
             reusable: false,
+            device: Device::Cpu,
+            density: None,
         }
     }
 
     #[inline]
-    pub fn from_arc(buffer: Arc<Vec<T>>, shape: &[usize]) -> Self {
+    pub fn from_arc(buffer: Arc<Buffer<T>>, shape: &[usize]) -> Self {
         Self {
             storage: Storage::from_arc(buffer),
             layout: Layout::from_shape(shape, 0),
             reusable: false,
+            device: Device::Cpu,
+            density: None,
         }
     }
 
     #[inline]
     pub fn from_vec(vector: Vec<T>, shape: &[usize], offset: usize) -> Self {
-        debug_assert!(vector.len() <= (shape.iter().product()));
+        debug_assert!(vector.len() <= offset + shape.iter().product::<usize>());
 
         Self {
             storage: Storage::from_vec(vector),
             layout: Layout::from_shape(shape, offset),
             reusable: false,
+            device: Device::Cpu,
+            density: None,
         }
     }
 
@@ -125,26 +310,33 @@ impl<T: Copy> TensorData<T> {
 
     #[inline]
     pub fn as_layout(&self, layout: Layout) -> Self {
+        // Reordering the elements keeps their density, selecting some of them does not.
+        let density = self.density.filter(|_| layout.len() == self.len());
+
         Self {
             storage: self.storage.clone_reference(),
             layout,
             reusable: self.reusable,
+            device: self.device,
+            density,
         }
     }
 
     #[inline]
     pub fn iter(&self) -> SliceIter<'_, T> {
-        SliceIter::new(&self.storage.buffer, self.len(), self.layout())
+        SliceIter::new(self.storage.as_slice(), self.len(), self.layout())
     }
 
+    /// # Safety
+    /// Every position reached by `layout` must be inside the buffer of this tensor.
     #[inline]
     pub unsafe fn iter_as_layout<'a>(&'a self, layout: &'a Layout) -> SliceIter<'a, T> {
-        SliceIter::new(&self.storage.buffer, layout.len(), layout)
+        SliceIter::new(self.storage.as_slice(), layout.len(), layout)
     }
 
     #[inline]
     pub fn fast_iter(&self) -> IterImpl<ContiguousIter<'_, T>, SliceIter<'_, T>> {
-        let buffer = &self.storage.buffer;
+        let buffer = self.storage.as_slice();
 
         if self.is_contiguous() {
             IterImpl::Contiguous(ContiguousIter::new(buffer, self.offset(), self.len()))
@@ -155,14 +347,53 @@ impl<T: Copy> TensorData<T> {
 
     #[inline]
     pub fn copied_iter(&self) -> CopiedSliceIter<'_, T> {
-        CopiedSliceIter::new(&self.storage.buffer, self.len(), self.layout())
+        CopiedSliceIter::new(self.storage.as_slice(), self.len(), self.layout())
+    }
+
+    /// The elements as a slice, in row-major order, if the layout is contiguous and starts
+    /// at the beginning of the buffer. None otherwise, see to_boxed_slice().
+    ///
+    /// No lock is taken: the slice borrows self, so self cannot be written while it lives,
+    /// and writing to another tensor sharing the buffer copies it first, see
+    /// Tensor::make_unique(). The slice never changes under its reader, and borrowing the
+    /// slices of several tensors at once cannot deadlock.
+    #[inline]
+    pub fn try_as_slice(&self) -> Option<&[T]> {
+        if !self.is_contiguous() || self.offset() != 0 {
+            return None;
+        }
+
+        Some(&self.storage.as_slice()[..self.len()])
+    }
+
+    /// Calls `f` with the slice of try_as_slice(), if there is one.
+    #[inline]
+    pub fn with_slice<R>(&self, f: impl FnOnce(&[T]) -> R) -> Option<R> {
+        self.try_as_slice().map(f)
+    }
+
+    /// A copy of the elements in row-major order, whatever the layout.
+    pub fn to_boxed_slice(&self) -> Box<[T]> {
+        match self.try_as_slice() {
+            Some(slice) => slice.into(),
+            None => self.copied_iter().collect(),
+        }
+    }
+
+    // The slice of try_as_slice(), or a copy of the elements in row-major order.
+    #[inline]
+    pub(crate) fn elements(&self) -> Cow<'_, [T]> {
+        match self.try_as_slice() {
+            Some(slice) => Cow::Borrowed(slice),
+            None => Cow::Owned(self.copied_iter().collect()),
+        }
     }
 
     #[inline]
     pub fn copied_fast_iter(
         &self,
     ) -> IterImpl<CopiedContiguousIter<'_, T>, CopiedSliceIter<'_, T>> {
-        let buffer = &self.storage.buffer;
+        let buffer = self.storage.as_slice();
 
         if self.is_contiguous() {
             IterImpl::Contiguous(CopiedContiguousIter::new(buffer, self.offset(), self.len()))
@@ -173,7 +404,86 @@ impl<T: Copy> TensorData<T> {
 
     #[inline]
     pub fn informed_iter(&self) -> InformedSliceIter<'_, T> {
-        InformedSliceIter::new(&self.storage.buffer, &self.layout)
+        InformedSliceIter::new(self.storage.as_slice(), &self.layout)
+    }
+
+    /// Folds the elements in row-major order, like `iter().copied().fold()`, without
+    /// allocating. Rows whose elements are next to each other in memory, the usual case,
+    /// are folded as plain slices.
+    pub fn fold<B>(&self, init: B, mut f: impl FnMut(B, T) -> B) -> B {
+        let data = self.storage.as_slice();
+
+        if self.is_contiguous() {
+            let start = self.offset();
+            return (data[start..start + self.len()].iter()).fold(init, |acc, &el| f(acc, el));
+        }
+
+        let row_len = self.shape().last().copied().unwrap_or(1);
+        let step = self.stride().last().copied().unwrap_or(1) as isize;
+
+        fold_rows(
+            self.shape(),
+            [self.stride()],
+            [self.offset()],
+            init,
+            |acc, [start]| {
+                if step == 1 {
+                    (data[start..start + row_len].iter()).fold(acc, |acc, &el| f(acc, el))
+                } else {
+                    (0..row_len as isize).fold(acc, |acc, i| {
+                        f(acc, data[(start as isize + i * step) as usize])
+                    })
+                }
+            },
+        )
+    }
+
+    /// Calls `f` on every element in row-major order, see fold().
+    #[inline]
+    pub fn for_each(&self, mut f: impl FnMut(T)) {
+        self.fold((), |(), el| f(el))
+    }
+
+    /// Calls `f` on the elements of both tensors at the same position, in row-major order,
+    /// without allocating. Both must have the same shape, but not the same strides.
+    pub fn zip_for_each(
+        &self,
+        other: &TensorData<T>,
+        mut f: impl FnMut(T, T),
+    ) -> Result<(), OpError> {
+        if self.shape() != other.shape() {
+            return Err(OpError::NotSameShape(
+                self.shape().into(),
+                other.shape().into(),
+            ));
+        }
+
+        let (lhs, rhs) = (self.storage.as_slice(), other.storage.as_slice());
+        let row_len = self.shape().last().copied().unwrap_or(1);
+        let steps = [self.stride(), other.stride()]
+            .map(|stride| stride.last().copied().unwrap_or(1) as isize);
+
+        fold_rows(
+            self.shape(),
+            [self.stride(), other.stride()],
+            [self.offset(), other.offset()],
+            (),
+            |(), [l, r]| {
+                if steps == [1, 1] {
+                    (lhs[l..l + row_len].iter())
+                        .zip(&rhs[r..r + row_len])
+                        .for_each(|(&a, &b)| f(a, b));
+                } else {
+                    for i in 0..row_len as isize {
+                        let a = lhs[(l as isize + i * steps[0]) as usize];
+                        let b = rhs[(r as isize + i * steps[1]) as usize];
+                        f(a, b);
+                    }
+                }
+            },
+        );
+
+        Ok(())
     }
 
     #[inline]
@@ -182,15 +492,134 @@ impl<T: Copy> TensorData<T> {
             storage: self.storage.clone(),
             layout: self.layout.clone(),
             reusable: self.reusable,
+            device: self.device,
+            density: self.density,
         }
     }
 
     #[inline]
-    pub fn as_contiguous(&self) -> Self {
-        if !self.is_contiguous() {
-            Self::from_iter(self.copied_iter(), self.shape())
-        } else {
+    pub fn as_contiguous(&self) -> Self
+    where
+        T: Default,
+    {
+        if self.is_contiguous() {
             self.clone_deep()
+        } else if self.is_transposed_matrix() {
+            Self::from_vec(self.transposed_copy(), self.shape(), 0)
+        } else {
+            Self::from_iter(self.copied_iter(), self.shape())
+        }
+    }
+
+    // Whether the last two axes are a transposed matrix, i.e. walking down a column is
+    // contiguous in memory. Those are compacted with a cache-blocked copy instead
+    // of the element-at-a-time strided iterator.
+    // Matrices with a single row or column are left to the iterator as they are already
+    // contiguous enough.
+    #[inline]
+    pub(crate) fn is_transposed_matrix(&self) -> bool {
+        let shape = self.shape();
+        let stride = self.stride();
+        let rank = shape.len();
+
+        rank >= 2
+            && shape[rank - 2] > 1
+            && shape[rank - 1] > 1
+            && stride[rank - 2] == 1
+            && stride[rank - 1] >= shape[rank - 2] as i32
+    }
+
+    // Position in the buffer of the first element of the matrix number `matrix`,
+    // counting the matrices of the outer axes in row-major order.
+    fn matrix_base(&self, matrix: usize) -> usize {
+        let shape = self.shape();
+        let stride = self.stride();
+
+        let mut remaining = matrix;
+        let mut base = self.offset() as isize;
+
+        for dim in (0..shape.len() - 2).rev() {
+            base += (remaining % shape[dim]) as isize * stride[dim] as isize;
+            remaining /= shape[dim];
+        }
+
+        base as usize
+    }
+
+    // Dense copy of a tensor for which is_transposed_matrix() holds.
+    // Every matrix of the outer axes is copied with transpose_copy_2d.
+    pub(crate) fn transposed_copy(&self) -> Vec<T>
+    where
+        T: Default,
+    {
+        debug_assert!(self.is_transposed_matrix());
+
+        let shape = self.shape();
+        let rank = shape.len();
+
+        let rows = shape[rank - 2];
+        let cols = shape[rank - 1];
+        let src_row_stride = self.stride()[rank - 1] as usize;
+
+        let buffer = self.storage.as_slice();
+        let mut out = vec![T::default(); self.len()];
+
+        for (matrix, dst) in out.chunks_exact_mut(rows * cols).enumerate() {
+            let base = self.matrix_base(matrix);
+            transpose_copy_2d(&buffer[base..], dst, rows, cols, src_row_stride);
+        }
+
+        out
+    }
+
+    // Walks a tensor for which is_transposed_matrix() holds in dense strips of up to
+    // TRANSPOSE_BLOCK_SIZE rows, calling `f` with the position of the strip in the dense
+    // tensor and its elements. Only one strip is allocated, so an op reading a transposed
+    // operand does not need a dense copy of the whole of it.
+    pub(crate) fn for_each_transposed_strip(&self, mut f: impl FnMut(usize, &[T]))
+    where
+        T: Default,
+    {
+        debug_assert!(self.is_transposed_matrix());
+
+        let shape = self.shape();
+        let rank = shape.len();
+
+        let rows = shape[rank - 2];
+        let cols = shape[rank - 1];
+        let src_row_stride = self.stride()[rank - 1] as usize;
+
+        let buffer = self.storage.as_slice();
+        let mut strip = vec![T::default(); TRANSPOSE_BLOCK_SIZE.min(rows) * cols];
+
+        for matrix in 0..self.len() / (rows * cols) {
+            let base = self.matrix_base(matrix);
+
+            for first_row in (0..rows).step_by(TRANSPOSE_BLOCK_SIZE) {
+                let strip_rows = TRANSPOSE_BLOCK_SIZE.min(rows - first_row);
+                let strip = &mut strip[..strip_rows * cols];
+
+                transpose_copy_2d(
+                    &buffer[base + first_row..],
+                    strip,
+                    strip_rows,
+                    cols,
+                    src_row_stride,
+                );
+
+                f((matrix * rows + first_row) * cols, strip);
+            }
+        }
+    }
+
+    // The layout and the device, over an empty buffer. Reading an element panics.
+    pub(crate) fn without_elements(&self) -> Self {
+        Self {
+            storage: Storage::from_vec(Vec::new()),
+            layout: self.layout.clone(),
+            reusable: false,
+            device: self.device,
+            density: self.density,
         }
     }
 
@@ -212,6 +641,130 @@ impl<T: Copy> TensorData<T> {
     pub fn layout(&self) -> &Layout {
         &self.layout
     }
+
+    #[inline]
+    pub fn device(&self) -> Device {
+        self.device
+    }
+
+    // Only tags the data, the elements stay where they are. See testing::placed_on().
+    #[inline]
+    pub(crate) fn with_device(mut self, device: Device) -> Self {
+        self.device = device;
+
+        self
+    }
+
+    /// Fraction of the elements that are nonzero, if known, see Tensor::with_density_hint().
+    #[inline]
+    pub fn density_hint(&self) -> Option<f32> {
+        self.density
+    }
+
+    #[inline]
+    pub(crate) fn with_density_hint(mut self, density: Option<f32>) -> Self {
+        self.density = density;
+
+        self
+    }
+
+    // The elements in row-major order, borrowed from the buffer if the layout is contiguous.
+    #[inline]
+    pub(crate) fn contiguous_slice(&self) -> Option<&[T]> {
+        self.is_contiguous()
+            .then(|| &self.storage.as_slice()[self.offset()..self.offset() + self.len()])
+    }
+
+    // Elements of the buffer, the ones outside of the layout included.
+    #[inline]
+    pub(crate) fn buffer_len(&self) -> usize {
+        self.storage.as_slice().len()
+    }
+
+    #[inline]
+    pub(crate) fn owns_buffer(&mut self) -> bool {
+        self.storage.as_mut_slice().is_some()
+    }
+
+    // Moves the elements out of the buffer without copying them, if nothing else shares it,
+    // it is a Vec, and the layout reads it contiguously from its start. Leaves the buffer empty.
+    pub(crate) fn take_vec(&mut self) -> Option<Vec<T>> {
+        if !self.is_contiguous() || self.offset() != 0 {
+            return None;
+        }
+
+        let len = self.len();
+        match Arc::get_mut(&mut self.storage.buffer)? {
+            Buffer::Owned(vector) => {
+                let mut vector = std::mem::take(vector);
+                vector.truncate(len);
+
+                Some(vector)
+            }
+            _ => None,
+        }
+    }
+
+    // Overwrites the elements of this tensor, following its layout, with the ones of `src`.
+    // Both must have the same shape and this tensor must own its buffer.
+    pub(crate) fn write_from(&mut self, src: &TensorData<T>) {
+        debug_assert_eq!(self.shape(), src.shape());
+
+        let buffer = self
+            .storage
+            .as_mut_slice()
+            .expect("write_from requires the buffer to not be shared");
+
+        write_strided(buffer, &self.layout, src.copied_iter());
+    }
+
+    // Overwrites the elements of `layout` over the buffer of this tensor, in row-major order,
+    // with the ones of `values` until either of them ends. This tensor must own its buffer.
+    pub(crate) fn write_iter_as_layout(
+        &mut self,
+        layout: &Layout,
+        values: impl Iterator<Item = T>,
+    ) {
+        let buffer = self
+            .storage
+            .as_mut_slice()
+            .expect("write_iter_as_layout requires the buffer to not be shared");
+
+        write_strided(buffer, layout, values);
+    }
+}
+
+fn write_strided<T: Copy>(buffer: &mut [T], layout: &Layout, values: impl Iterator<Item = T>) {
+    if layout.is_contiguous() {
+        let dst = &mut buffer[layout.offset()..layout.offset() + layout.len()];
+
+        for (slot, value) in dst.iter_mut().zip(values) {
+            *slot = value;
+        }
+
+        return;
+    }
+
+    let shape = layout.shape();
+    let adj_stride = layout.adj_stride();
+
+    let mut pos = layout.offset() as isize;
+    let mut counter = vec![0; shape.len()];
+
+    for value in values.take(layout.len()) {
+        buffer[pos as usize] = value;
+
+        let mut step_dim = shape.len() - 1;
+        counter[step_dim] += 1;
+
+        while step_dim > 0 && counter[step_dim] == shape[step_dim] {
+            counter[step_dim] = 0;
+            counter[step_dim - 1] += 1;
+            step_dim -= 1;
+        }
+
+        pos += adj_stride[step_dim] as isize;
+    }
 }
 
 impl<T: Copy + Default> TensorData<T> {
@@ -221,16 +774,53 @@ impl<T: Copy + Default> TensorData<T> {
     }
 }
 
+#[cfg(feature = "parallel")]
+impl<T: Copy + Send + Sync> TensorData<T> {
+    /// The elements in row-major order as a rayon IndexedParallelIterator. Contiguous
+    /// tensors are split over their buffer, the others are gathered into a copy first.
+    pub fn par_iter(&self) -> ParIter<'_, T> {
+        use rayon::iter::{
+            Either, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+        };
+
+        match self.contiguous_slice() {
+            Some(slice) => Either::Left(slice.par_iter().copied()),
+            None => Either::Right(self.copied_iter().collect::<Vec<T>>().into_par_iter()),
+        }
+    }
+}
+
+/// The elements in row-major order, copied, like copied_iter().
+impl<'a, T: Copy> IntoIterator for &'a TensorData<T> {
+    type Item = T;
+    type IntoIter = CopiedSliceIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.copied_iter()
+    }
+}
+
 impl<T: Copy> Clone for TensorData<T> {
     fn clone(&self) -> Self {
         Self {
             storage: self.storage.clone_reference(),
             layout: self.layout.clone(),
             reusable: self.reusable,
+            device: self.device,
+            density: self.density,
         }
     }
 }
 
+/// Compares the shapes, then the elements in logical order, so two buffers holding the same
+/// values with different strides or offsets are equal.
+impl<T: Copy + PartialEq> PartialEq for TensorData<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape() == other.shape() && self.iter().eq(other.iter())
+    }
+}
+
 impl<T: Copy> Dimension for TensorData<T> {
     #[inline]
     fn layout(&self) -> &Layout {
@@ -239,3 +829,64 @@ impl<T: Copy> Dimension for TensorData<T> {
 }
 
 impl_display!(TensorData<T>);
+
+/// Reads a tensor written as nested lists, like `[[1, 2.5], [3e-7, 4]]`, with one pair of
+/// brackets per axis and whitespace anywhere between the elements. The shape follows the
+/// nesting, and a number outside of any list has no axes.
+///
+/// Every element is read with the FromStr of T, so floats take exponents, `inf` and `NaN`.
+/// The elements of to_parseable_string() are read back exactly, and so are the ones of
+/// to_string_flat() for the tensors it shows entirely. Malformed input, and lists of
+/// different lengths or depths, fail with the byte offset of the first part rejected.
+impl<T: Copy + FromStr> FromStr for TensorData<T> {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (elements, shape) = parse::parse_nested(text)?;
+
+        Ok(Self::from_vec(elements, &shape, 0))
+    }
+}
+
+impl<T: Copy + std::fmt::Debug> TensorData<T> {
+    /// The tensor on a single line, like `[[1.0, 2.5], [3e-7, 4.0]]`, with every element in
+    /// the shortest form that from_str() reads back exactly: -0.0, subnormals and NaN
+    /// included. Unlike to_string_flat(), nothing is elided.
+    ///
+    /// The shape of an empty tensor is only written up to its first empty axis, so `[2, 0, 3]`
+    /// is read back as `[2, 0]`.
+    pub fn to_parseable_string(&self) -> String {
+        pretty::parseable(self)
+    }
+}
+
+impl<T: Copy + std::fmt::Display> TensorData<T> {
+    /// The tensor on a single line, like `[[1, 2], [3, 4]] shape=[2,2]`, showing its first
+    /// FLAT_ELEMENT_BUDGET elements and eliding the rest as `...`.
+    #[inline]
+    pub fn to_string_flat(&self) -> String {
+        pretty::flat(self, FLAT_ELEMENT_BUDGET)
+    }
+
+    /// Same as to_string_flat(), showing exactly the first `max_elements` elements,
+    /// or all of them if there are fewer.
+    #[inline]
+    pub fn to_string_flat_with(&self, max_elements: usize) -> String {
+        pretty::flat(self, max_elements)
+    }
+
+    /// The top-left corner of the last two axes, at most `max_rows` by `max_cols`, under a
+    /// header with the shape and the element type. Elided rows and columns are shown as
+    /// `...`, and only the first matrix is shown of tensors with more than two axes:
+    ///
+    /// ```text
+    /// shape=[2,3,4] dtype=f64 view=[0,:,:]
+    /// [[ 0,  1, ...],
+    ///  [ 4,  5, ...],
+    ///  ...]
+    /// ```
+    #[inline]
+    pub fn preview(&self, max_rows: usize, max_cols: usize) -> String {
+        pretty::preview(self, None, max_rows, max_cols)
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::errors::OpError;
+use crate::tensor::graph::{NodeKind, get_id, numerics_of};
+use crate::tensor::iter::CopiedSliceIter;
+use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::numerics::NumericsScope;
+use crate::tensor::ops::def_op::OpKind;
+use crate::tensor::ops::{ComputeWrapperSpec, cpu_compute};
+use crate::tensor::storage::TensorData;
+use crate::tensor::traits::{Dimension, Promising};
+
+// Everything a streamed graph reads, found by walking it once before computing anything.
+struct StreamPlan<T: Copy> {
+    // Tensors read a chunk at a time through their strided iterators,
+    // including the views built directly on top of them.
+    leaves: HashMap<usize, TensorData<T>>,
+    // Single element inputs of DynScalarOp, computed up front.
+    scalars: HashMap<usize, TensorData<T>>,
+    not_elementwise: Vec<(usize, &'static str)>,
+    visited: HashSet<usize>,
+}
+
+fn node_parts<T: Copy>(node: &NodeKind<T>) -> Option<(&OpKind<T>, &[NodeKind<T>])> {
+    match node {
+        NodeKind::Edge(_) => None,
+        NodeKind::Node(node) => Some((&node.op, &node.inputs)),
+        NodeKind::Cache(cache) => Some((&cache.get_node().op, &cache.get_node().inputs)),
+    }
+}
+
+// The data of a tensor, or of a chain of views over it, without copying anything.
+fn leaf_view<T: Copy>(node: &NodeKind<T>) -> Option<TensorData<T>> {
+    match node {
+        NodeKind::Edge(edge) => return Some(edge.peek()),
+        NodeKind::Cache(cache) => {
+            if let Some(data) = cache.cached() {
+                return Some(data);
+            }
+        }
+        _ => {}
+    }
+
+    let (op, inputs) = node_parts(node)?;
+
+    match op {
+        OpKind::NoOp | OpKind::AsContiguous => leaf_view(&inputs[0]),
+        OpKind::View(layout) | OpKind::Slice(layout) | OpKind::TransposeAxes(layout, _) => {
+            Some(leaf_view(&inputs[0])?.as_layout(layout.clone()))
+        }
+        OpKind::Transpose => {
+            let data = leaf_view(&inputs[0])?;
+            Some(data.as_layout(data.layout().transpose()))
+        }
+        OpKind::Flip(axes) => {
+            let data = leaf_view(&inputs[0])?;
+            Some(data.as_layout(data.layout().flip(axes).ok()?))
+        }
+        _ => None,
+    }
+}
+
+fn compute_node<T: NumberLike + ComputeWrapperSpec>(node: &NodeKind<T>) -> TensorData<T> {
+    match node {
+        NodeKind::Edge(edge) => edge.compute(),
+        NodeKind::Node(node) => node.compute(),
+        NodeKind::Cache(cache) => cache.compute(),
+    }
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> StreamPlan<T> {
+    fn new(root: &NodeKind<T>) -> Self {
+        let mut plan = Self {
+            leaves: HashMap::new(),
+            scalars: HashMap::new(),
+            not_elementwise: Vec::new(),
+            visited: HashSet::new(),
+        };
+
+        plan.visit(root);
+        plan
+    }
+
+    fn visit(&mut self, node: &NodeKind<T>) {
+        let id = get_id(node);
+
+        if !self.visited.insert(id) {
+            return;
+        }
+
+        if let Some(data) = leaf_view(node) {
+            self.leaves.insert(id, data);
+            return;
+        }
+
+        let Some((op, inputs)) = node_parts(node) else {
+            unreachable!("edges are always leaves")
+        };
+
+        if !op.is_elementwise() {
+            self.not_elementwise.push((id, op.as_str()));
+            return;
+        }
+
+        match op {
+            OpKind::DynScalarOp(_, scalar_idx, _) => {
+                self.visit(&inputs[1 - scalar_idx]);
+                self.visit_scalar(&inputs[*scalar_idx]);
+            }
+            _ => inputs.iter().for_each(|input| self.visit(input)),
+        }
+    }
+
+    // The scalar has a single element, so if its own graph is elementwise
+    // it only reads single elements and is cheap to compute whole.
+    fn visit_scalar(&mut self, node: &NodeKind<T>) {
+        let mut scalar_plan = Self::new(node);
+        self.not_elementwise
+            .append(&mut scalar_plan.not_elementwise);
+
+        if self.not_elementwise.is_empty() {
+            self.scalars.insert(get_id(node), compute_node(node));
+        }
+    }
+}
+
+struct ChunkEvaluator<'a, T: Copy> {
+    iters: HashMap<usize, CopiedSliceIter<'a, T>>,
+    scalars: &'a HashMap<usize, TensorData<T>>,
+    // Position of the current chunk in the output, which is where random ops count from.
+    offset: usize,
+    // Results of the current chunk, for the nodes read by more than one op.
+    results: HashMap<usize, TensorData<T>>,
+}
+
+impl<T: NumberLike + ComputeWrapperSpec> ChunkEvaluator<'_, T> {
+    fn eval(&mut self, node: &NodeKind<T>, len: usize) -> TensorData<T> {
+        let id = get_id(node);
+
+        if let Some(result) = self.results.get(&id) {
+            return result.clone().mark_as_not_reusable();
+        }
+
+        let result = if let Some(iter) = self.iters.get_mut(&id) {
+            TensorData::from_iter(iter.by_ref().take(len), &[len])
+        } else {
+            let (op, inputs) = unsafe { node_parts(node).unwrap_unchecked() };
+
+            match op {
+                OpKind::NoOp | OpKind::AsContiguous | OpKind::View(_) => self.eval(&inputs[0], len),
+                OpKind::DynScalarOp(_, scalar_idx, _) => {
+                    let mut chunk_inputs = vec![self.eval(&inputs[1 - scalar_idx], len)];
+                    let scalar = self.scalars[&get_id(&inputs[*scalar_idx])].clone();
+                    chunk_inputs.insert(*scalar_idx, scalar.mark_as_not_reusable());
+
+                    cpu_compute(op, &Layout::from_shape(&[len], 0), chunk_inputs)
+                }
+                OpKind::Random(kind, seed, counter) => {
+                    let chunk_inputs = vec![self.eval(&inputs[0], len)];
+                    let op = OpKind::Random(*kind, *seed, counter + self.offset);
+
+                    cpu_compute(&op, &Layout::from_shape(&[len], 0), chunk_inputs)
+                }
+                _ => {
+                    let chunk_inputs = inputs.iter().map(|input| self.eval(input, len)).collect();
+
+                    cpu_compute(op, &Layout::from_shape(&[len], 0), chunk_inputs)
+                }
+            }
+        };
+
+        self.results.insert(id, result.clone());
+        result.mark_as_not_reusable()
+    }
+}
+
+// Computes the graph of `root` over `chunk_elems` elements of the output at a time,
+// in row-major order, so no intermediate is bigger than a chunk.
+pub(crate) fn materialize_streamed<T: NumberLike + ComputeWrapperSpec>(
+    root: &NodeKind<T>,
+    len: usize,
+    chunk_elems: usize,
+    mut sink: impl FnMut(&[T], usize),
+) -> Result<(), OpError> {
+    if chunk_elems == 0 {
+        return Err(OpError::ZeroChunkSize);
+    }
+
+    let _scope = numerics_of(root).map(NumericsScope::enter);
+    let plan = StreamPlan::new(root);
+
+    if !plan.not_elementwise.is_empty() {
+        return Err(OpError::NotStreamable(plan.not_elementwise.into()));
+    }
+
+    let mut evaluator = ChunkEvaluator {
+        iters: plan
+            .leaves
+            .iter()
+            .map(|(&id, data)| (id, data.copied_iter()))
+            .collect(),
+        scalars: &plan.scalars,
+        offset: 0,
+        results: HashMap::new(),
+    };
+
+    for offset in (0..len).step_by(chunk_elems) {
+        let chunk_len = chunk_elems.min(len - offset);
+
+        evaluator.offset = offset;
+        evaluator.results.clear();
+        let chunk = evaluator.eval(root, chunk_len);
+        let chunk = if chunk.is_contiguous() {
+            chunk
+        } else {
+            chunk.as_contiguous()
+        };
+
+        let start = chunk.offset();
+        sink(&chunk.storage.as_slice()[start..start + chunk_len], offset);
+    }
+
+    Ok(())
+}
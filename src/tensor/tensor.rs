@@ -1,13 +1,53 @@
-use crate::impl_display;
-use crate::tensor::errors::OpError;
-use crate::tensor::graph::{NodeKind, TensorGraphEdge};
-use crate::tensor::iter::{ContiguousIter, InformedSliceIter, SliceIter};
+use crate::branch_fast_iter;
+use crate::tensor::backend::{Backend, ReductionBackend};
+use crate::tensor::definitions::NumberLike;
+use crate::tensor::device::Device;
+use crate::tensor::errors::{OpError, ParseError};
+use crate::tensor::graph::{NodeKind, TensorGraphEdge, TensorId};
+use crate::tensor::internals::total_order;
+#[cfg(feature = "parallel")]
+use crate::tensor::iter::ParIter;
+use crate::tensor::iter::{ContiguousIter, CopiedSliceIter, InformedSliceIter, SliceIter};
 use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::mem_formats::slice::SliceKind;
+use crate::tensor::ops::AxisNameList;
+use crate::tensor::ops::impl_names::validate_names;
+use crate::tensor::ops::kernels::Summer;
 use crate::tensor::promise::TensorPromise;
-use crate::tensor::storage::TensorData;
-use crate::tensor::traits::{Dimension, Promising};
+use crate::tensor::storage::{ExternalDeleter, Storage, TensorData};
+use crate::tensor::traits::{Dimension, Promising, StreamingIterator};
+use rustc_hash::FxHasher;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 use std::sync::Arc;
 
+/// A tensor that lives in the computation graph.
+///
+/// Tensors are compared by identity, with id() or ptr_eq(), and do not implement PartialEq,
+/// since comparing every element is rarely what is wanted of a map key and easy to do by
+/// accident. Compare their data instead to check for equal values:
+///
+/// ```
+/// use simple_tensor::matrix;
+///
+/// let a = matrix![[1, 2], [3, 4]];
+/// let b = a.transpose().transpose().materialize();
+///
+/// assert!(a.data() == b.data());
+/// assert_ne!(a.id(), b.id());
+/// ```
+///
+/// ```compile_fail,E0369
+/// use simple_tensor::matrix;
+///
+/// let a = matrix![[1, 2], [3, 4]];
+/// let equal = a == a.clone();
+/// ```
+///
+/// With the `serde` feature, tensors are serialized as their shape and their elements in
+/// logical order, and read back contiguous, each with a buffer of its own. Promises cannot
+/// be serialized, only the tensors they materialize to.
 pub struct Tensor<T: Copy> {
     pub(crate) graph: Arc<TensorGraphEdge<T>>,
 }
@@ -31,6 +71,18 @@ impl<T: Copy> Tensor<T> {
         }
     }
 
+    /// Same as from_vec(), failing with ElementCount unless `vector` has exactly as many
+    /// elements as `shape` holds, which from_vec() only asserts in debug builds.
+    pub fn try_from_vec(vector: Vec<T>, shape: &[usize]) -> Result<Self, OpError> {
+        let len = shape.iter().product();
+
+        if vector.len() != len {
+            return Err(OpError::ElementCount(len, vector.len()));
+        }
+
+        Ok(Self::from_vec(vector, shape))
+    }
+
     #[inline]
     pub fn from_iter<I>(iter: I, shape: &[usize]) -> Self
     where
@@ -40,6 +92,103 @@ impl<T: Copy> Tensor<T> {
         Self::from_vec(vector, shape)
     }
 
+    /// Same as from_iter(), failing with ElementCount unless `iter` yields exactly as many
+    /// elements as `shape` holds. Collecting into a Tensor instead makes one of rank 1.
+    pub fn collect_with_shape<I>(iter: I, shape: &[usize]) -> Result<Self, OpError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::try_from_vec(iter.into_iter().collect(), shape)
+    }
+
+    /// Reads a leaf written as nested lists, like `[[1, 2.5], [3e-7, 4]]`, the shape
+    /// following the nesting. Same as TensorData::from_str().
+    pub fn from_str_data(text: &str) -> Result<Self, ParseError>
+    where
+        T: FromStr,
+    {
+        text.parse().map(Self::from_data)
+    }
+
+    /// Creates a tensor that promises to never change, which allows the
+    /// computations depending only on constants to be folded by TensorPromise::fold_constants().
+    #[inline]
+    pub fn constant(vector: Vec<T>, shape: &[usize]) -> Self {
+        Self {
+            graph: Arc::new(TensorGraphEdge::constant(TensorData::from_vec(
+                vector, shape, 0,
+            ))),
+        }
+    }
+
+    #[inline]
+    pub fn constant_from_scalar(scalar: T, shape: &[usize]) -> Self {
+        Self {
+            graph: Arc::new(TensorGraphEdge::constant(TensorData::from_scalar(
+                scalar, shape,
+            ))),
+        }
+    }
+
+    #[inline]
+    pub fn is_constant(&self) -> bool {
+        self.graph.is_constant()
+    }
+
+    /// Identity of this tensor in the graph. It is shared by clone(),
+    /// while clone_detached() and clone_deep() produce tensors with new ids.
+    #[inline]
+    pub fn id(&self) -> TensorId {
+        TensorId(self.graph.id)
+    }
+
+    /// Whether both tensors are the same node of the graph, see id().
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.graph, &other.graph)
+    }
+
+    /// Data of this tensor, which compares by value.
+    #[inline]
+    pub fn data(&self) -> &TensorData<T> {
+        self.graph.get()
+    }
+
+    /// Device the elements are placed on, the cpu unless tagged otherwise.
+    #[inline]
+    pub fn device(&self) -> Device {
+        self.data().device()
+    }
+
+    /// This tensor placed on `device`. Only the cpu has a backend for now, so the one move
+    /// supported is from the cpu to itself, which returns a clone sharing the buffer.
+    /// Any other fails with OpError::DeviceNotSupported.
+    pub fn to_device(&self, device: Device) -> Result<Self, OpError> {
+        match (self.device(), device) {
+            (Device::Cpu, Device::Cpu) => Ok(self.clone()),
+            (from, to) => Err(OpError::DeviceNotSupported(from, to)),
+        }
+    }
+
+    /// The elements as a slice if the tensor is contiguous and starts at the beginning of
+    /// its buffer, see TensorData::try_as_slice().
+    #[inline]
+    pub fn try_as_slice(&self) -> Option<&[T]> {
+        self.data().try_as_slice()
+    }
+
+    /// Calls `f` with the slice of try_as_slice(), if there is one.
+    #[inline]
+    pub fn with_slice<R>(&self, f: impl FnOnce(&[T]) -> R) -> Option<R> {
+        self.data().with_slice(f)
+    }
+
+    /// A copy of the elements in row-major order, whatever the layout.
+    #[inline]
+    pub fn to_boxed_slice(&self) -> Box<[T]> {
+        self.data().to_boxed_slice()
+    }
+
     #[inline]
     pub fn from_data(data: TensorData<T>) -> Self {
         Self {
@@ -47,6 +196,123 @@ impl<T: Copy> Tensor<T> {
         }
     }
 
+    pub(crate) fn from_data_named(data: TensorData<T>, names: Option<AxisNameList>) -> Self {
+        Self {
+            graph: Arc::new(TensorGraphEdge::from_tensor_data(data).with_names(names)),
+        }
+    }
+
+    /// Names every axis of this tensor, in order. The names follow the data through
+    /// the ops built on top of it and can be used in place of axis indices, see permute_named().
+    /// Like clone_detached(), the result shares the memory but not the promises of this tensor.
+    pub fn with_axis_names(&self, names: &[&str]) -> Result<Self, OpError> {
+        let names = validate_names(names, self.shape().len())?;
+
+        Ok(Self::from_data_named(self.graph.get().clone(), Some(names)))
+    }
+
+    /// Hints that only `density` of the elements, a fraction within [0, 1], are nonzero.
+    /// Mul then computes only the positions of those elements when the hint is below
+    /// sparse_threshold(), see SparseZeros. The hint is not checked against the elements,
+    /// and a wrong one only costs speed. Like clone_detached(), the result shares the memory
+    /// but not the promises of this tensor.
+    pub fn with_density_hint(&self, density: f32) -> Result<Self, OpError> {
+        if !(0.0..=1.0).contains(&density) {
+            return Err(OpError::InvalidDensity(density));
+        }
+
+        let data = self.graph.get().clone().with_density_hint(Some(density));
+
+        Ok(Self::from_data_named(data, self.graph.names().cloned()))
+    }
+
+    /// Fraction of the elements that are nonzero, if known. Set by with_density_hint(), and
+    /// by the ops that count it as they compute their result, like dropout() and isnan().
+    #[inline]
+    pub fn density_hint(&self) -> Option<f32> {
+        self.data().density_hint()
+    }
+
+    /// Whether anything else references this tensor or its buffer, be it a clone,
+    /// a promise built on top of it or another tensor sharing its memory, like a view.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.graph) > 1
+            || Arc::strong_count(&self.graph.get().storage.buffer) > 1
+    }
+
+    /// Gives this tensor up to the ops built on it, so the first one computed may overwrite
+    /// its buffer in place instead of writing a new one, like it does with the results of
+    /// other ops. Experimental.
+    ///
+    /// This promises that nothing else reads the buffer by the time that op is computed.
+    /// A clone or a view still sharing it then fails a debug assertion, and is left intact
+    /// by copying the elements in release builds. The ops take the consumable tensor by value,
+    /// so a single one reads it, and only once: computing that op a second time, like when
+    /// a promise built on it is materialized twice, panics.
+    ///
+    /// ```
+    /// use simple_tensor::vector;
+    ///
+    /// let x = vector![1.0, 4.0, 9.0];
+    /// let y = x.consume().sqrt().materialize();
+    /// assert_eq!(y.iter().copied().collect::<Vec<_>>(), [1.0, 2.0, 3.0]);
+    /// ```
+    ///
+    /// The tensor is moved, so it cannot be read after it is given up:
+    ///
+    /// ```compile_fail,E0382
+    /// use simple_tensor::vector;
+    ///
+    /// let x = vector![1.0, 4.0, 9.0];
+    /// let y = x.consume().sqrt();
+    /// let z = x + 1.0;
+    /// ```
+    pub fn consume(self) -> ConsumableTensor<T> {
+        let edge = TensorGraphEdge::consumable(self.graph.get().clone());
+
+        ConsumableTensor {
+            graph: Arc::new(edge.with_names(self.graph.names().cloned())),
+        }
+    }
+
+    /// Copies the elements of this tensor into a buffer of its own if it is shared,
+    /// see is_shared(), so writing to it can't be observed by anything else.
+    /// Read-only tensors, like the ones over a mapped file, are always copied.
+    /// The copy is a new tensor, which the existing clones and promises do not read.
+    /// Does nothing if the tensor is not shared.
+    pub fn make_unique(&mut self) {
+        if !self.is_shared() && !self.is_read_only() {
+            return;
+        }
+
+        let data = self.graph.get();
+        let copy = TensorData::from_iter(data.copied_iter(), data.shape());
+
+        let edge = if self.graph.is_constant() {
+            TensorGraphEdge::constant(copy)
+        } else {
+            TensorGraphEdge::from_tensor_data(copy)
+        };
+
+        self.graph = Arc::new(edge.with_names(self.graph.names().cloned()));
+    }
+
+    /// Whether the elements of this tensor can never be written, like the ones of a mapped
+    /// file. Writing into it returns ReadOnlyBuffer, unless it is copied with make_unique().
+    #[inline]
+    pub fn is_read_only(&self) -> bool {
+        self.graph.get().storage.is_read_only()
+    }
+
+    // Data of this tensor if nothing else references it, be it a clone,
+    // a promise or another tensor sharing its buffer.
+    pub(crate) fn unique_data_mut(&mut self) -> Option<&mut TensorData<T>> {
+        let edge = Arc::get_mut(&mut self.graph)?;
+        let data = edge.get_mut();
+
+        if data.owns_buffer() { Some(data) } else { None }
+    }
+
     #[inline]
     pub fn iter(&self) -> SliceIter<'_, T> {
         self.graph.get().iter()
@@ -62,14 +328,114 @@ impl<T: Copy> Tensor<T> {
         self.graph.get().informed_iter()
     }
 
+    /// Same as TensorData::fold().
+    #[inline]
+    pub fn fold<B>(&self, init: B, f: impl FnMut(B, T) -> B) -> B {
+        self.graph.get().fold(init, f)
+    }
+
+    /// Same as TensorData::for_each().
+    #[inline]
+    pub fn for_each(&self, f: impl FnMut(T)) {
+        self.graph.get().for_each(f)
+    }
+
+    /// Same as TensorData::zip_for_each().
+    #[inline]
+    pub fn zip_for_each(&self, other: &Tensor<T>, f: impl FnMut(T, T)) -> Result<(), OpError> {
+        self.graph.get().zip_for_each(other.graph.get(), f)
+    }
+
     #[inline]
     /// Makes a deep copy of this tensor.
     pub fn clone_deep(&self) -> Self {
         let data = self.graph.get();
 
-        Self {
-            graph: Arc::new(TensorGraphEdge::from_tensor_data(data.clone_deep())),
-        }
+        Self::from_data_named(data.clone_deep(), self.graph.names().cloned())
+    }
+
+    #[inline]
+    /// Makes a view of this tensor with the element order reversed along the given axes.
+    /// No data is copied, the view shares the underlying memory and, like clone_detached(),
+    /// is treated as a completely different tensor during materialization.
+    /// Use .flip() for the lazy version that produces a dense output.
+    pub fn flip_view(&self, axes: &[usize]) -> Result<Self, OpError> {
+        let data = self.graph.get();
+        let layout = data.layout().flip(axes)?;
+
+        Ok(Self::from_data_named(
+            data.as_layout(layout),
+            self.graph.names().cloned(),
+        ))
+    }
+
+    #[inline]
+    /// Makes a 1-D view of the main diagonal of this matrix, of length min(rows, columns).
+    /// No data is copied, see flip_view().
+    pub fn diagonal(&self) -> Result<Self, OpError> {
+        self.diagonal_offset(0)
+    }
+
+    /// Makes a 1-D view of the `k`-th diagonal of this matrix, above the main diagonal
+    /// for positive `k` and below it for negative ones. A diagonal outside of the
+    /// matrix is empty. No data is copied, see flip_view().
+    pub fn diagonal_offset(&self, k: i32) -> Result<Self, OpError> {
+        let data = self.graph.get();
+        let layout = data.layout().diagonal(k)?;
+
+        Ok(Self::from_data(data.as_layout(layout)))
+    }
+
+    /// Makes a view splitting the last axis into groups of `k` elements, of shape
+    /// `[..., n / k, k]`, like a `[n, 3]` view of `3n` coordinates.
+    /// The last axis must have a stride of 1, see Layout::group_last_axis().
+    /// No data is copied, see flip_view().
+    pub fn group_last_axis_view(&self, k: usize) -> Result<Self, OpError> {
+        let data = self.graph.get();
+        let layout = data.layout().group_last_axis(k)?;
+
+        Ok(Self::from_data(data.as_layout(layout)))
+    }
+
+    /// Makes a view merging the last `n` axes into one, if their strides allow it,
+    /// see Layout::flatten_last_axes(). No data is copied, see flip_view().
+    pub fn flatten_last_axes_view(&self, n: usize) -> Result<Self, OpError> {
+        let data = self.graph.get();
+        let layout = data.layout().flatten_last_axes(n)?;
+
+        Ok(Self::from_data(data.as_layout(layout)))
+    }
+
+    /// Makes a 1-D view of every element of a tensor whose elements are next to each other
+    /// in its buffer, in order. No data is copied, see flip_view().
+    pub fn flatten_view(&self) -> Result<Self, OpError> {
+        let data = self.graph.get();
+        let layout = data.layout().flatten()?;
+
+        Ok(Self::from_data(data.as_layout(layout)))
+    }
+
+    /// Makes a view of `shape` whose element at `index` is the one `offset + Σ index[i] * stride[i]`
+    /// elements past the first element of this tensor in its buffer, like numpy's as_strided.
+    /// The strides count elements of the buffer, whatever the layout of this tensor.
+    /// Fails with StridedOutOfBounds if any element would be outside of the buffer.
+    /// Elements may repeat, like with a stride of 0, in which case writing into the view
+    /// needs the _aliased variants of the assign methods. No data is copied, see flip_view().
+    pub fn as_strided(
+        &self,
+        shape: &[usize],
+        stride: &[i32],
+        offset: usize,
+    ) -> Result<Self, OpError> {
+        let data = self.graph.get();
+        let layout = Layout::as_strided(
+            shape,
+            stride,
+            data.offset() as i64 + offset as i64,
+            data.storage.as_slice().len(),
+        )?;
+
+        Ok(Self::from_data(data.as_layout(layout)))
     }
 
     #[inline]
@@ -83,9 +449,379 @@ impl<T: Copy> Tensor<T> {
     pub fn clone_detached(&self) -> Self {
         let data = self.graph.get();
 
-        Self {
-            graph: Arc::new(TensorGraphEdge::from_tensor_data(data.clone())),
+        Self::from_data_named(data.clone(), self.graph.names().cloned())
+    }
+
+    /// Wraps `len` elements of external memory starting at `ptr` as a contiguous tensor,
+    /// without copying them. `deleter`, if any, is called exactly once with `ptr` and `len`
+    /// when the last tensor, view or promise reading the memory is dropped.
+    ///
+    /// # Safety
+    /// `ptr` must be non-null, aligned and valid for reads and writes of `len` initialized
+    /// elements until the deleter is called or, without a deleter, for as long as anything
+    /// reading the memory is alive. Nothing else may write to the memory in the meantime,
+    /// other than through with_raw_mut(), and it must be accessible from any thread.
+    ///
+    /// # Panics
+    /// If `len` is not the number of elements of `shape`.
+    pub unsafe fn from_raw_parts(
+        ptr: *mut T,
+        len: usize,
+        shape: &[usize],
+        deleter: Option<ExternalDeleter<T>>,
+    ) -> Self {
+        assert_eq!(
+            len,
+            shape.iter().product::<usize>(),
+            "the shape does not match the number of elements"
+        );
+
+        let storage = unsafe { Storage::from_raw_parts(ptr, len, deleter) };
+
+        Self::from_data(TensorData::new(storage, Layout::from_shape(shape, 0)))
+    }
+
+    /// Calls `f` with a pointer to the first element and the number of elements,
+    /// e.g. to hand the buffer to a C API. The pointer must not be used after `f` returns.
+    /// Only contiguous tensors expose their buffer, others return NonContiguousBuffer.
+    pub fn with_raw<R>(&self, f: impl FnOnce(*const T, usize) -> R) -> Result<R, OpError> {
+        let data = self.graph.get();
+
+        if !data.is_contiguous() {
+            return Err(OpError::NonContiguousBuffer);
         }
+
+        let first = &data.storage.as_slice()[data.offset()..];
+
+        Ok(f(first.as_ptr(), data.len()))
+    }
+
+    /// Same as with_raw(), but the elements may be written through the pointer.
+    /// A shared tensor is copied first, see make_unique(), so the writes are only
+    /// seen through this tensor. Read-only tensors return ReadOnlyBuffer.
+    pub fn with_raw_mut<R>(&mut self, f: impl FnOnce(*mut T, usize) -> R) -> Result<R, OpError> {
+        if self.is_read_only() {
+            return Err(OpError::ReadOnlyBuffer);
+        }
+
+        if !self.graph.get().is_contiguous() {
+            return Err(OpError::NonContiguousBuffer);
+        }
+
+        self.make_unique();
+
+        let Some(data) = self.unique_data_mut() else {
+            return Err(OpError::SharedBuffer);
+        };
+
+        let offset = data.offset();
+        let len = data.len();
+        let buffer = unsafe { data.storage.as_mut_slice().unwrap_unchecked() };
+
+        Ok(f(buffer[offset..].as_mut_ptr(), len))
+    }
+
+    /// Overwrites the elements of this tensor, in row-major order, with the ones of `iter`
+    /// until either of them ends. The elements past a shorter iterator are left unchanged.
+    /// Like with_raw_mut(), a buffer shared with anything else is copied first
+    /// and read-only tensors return ReadOnlyBuffer.
+    /// Fails with AliasedElements if several of its elements are the same in memory, which only
+    /// views made with as_strided() can be once nothing else shares their buffer.
+    pub fn assign_from_iter(&mut self, iter: impl IntoIterator<Item = T>) -> Result<(), OpError> {
+        self.assign_into_layout(None, iter, false)
+    }
+
+    /// Same as assign_from_iter(), writing the elements that are the same in memory as
+    /// many times as they appear, the last value written being the one kept.
+    pub fn assign_from_iter_aliased(
+        &mut self,
+        iter: impl IntoIterator<Item = T>,
+    ) -> Result<(), OpError> {
+        self.assign_into_layout(None, iter, true)
+    }
+
+    /// Same as assign_from_iter(), only writing the elements of the slice `range` of this tensor.
+    pub fn assign_slice_from_iter(
+        &mut self,
+        range: &[SliceKind],
+        iter: impl IntoIterator<Item = T>,
+    ) -> Result<(), OpError> {
+        self.assign_into_layout(Some(range), iter, false)
+    }
+
+    /// Same as assign_slice_from_iter(), see assign_from_iter_aliased().
+    pub fn assign_slice_from_iter_aliased(
+        &mut self,
+        range: &[SliceKind],
+        iter: impl IntoIterator<Item = T>,
+    ) -> Result<(), OpError> {
+        self.assign_into_layout(Some(range), iter, true)
+    }
+
+    // Writes the elements of the slice `range` of this tensor, or all of them.
+    fn assign_into_layout(
+        &mut self,
+        range: Option<&[SliceKind]>,
+        iter: impl IntoIterator<Item = T>,
+        aliased: bool,
+    ) -> Result<(), OpError> {
+        if self.is_read_only() {
+            return Err(OpError::ReadOnlyBuffer);
+        }
+
+        self.make_unique();
+
+        let Some(data) = self.unique_data_mut() else {
+            return Err(OpError::SharedBuffer);
+        };
+
+        let layout = match range {
+            Some(range) => data.layout().slice(range)?,
+            None => data.layout().clone(),
+        };
+
+        // Only checked once unique, as a copy made by make_unique() never aliases.
+        if !aliased && layout.may_alias() {
+            return Err(OpError::AliasedElements);
+        }
+
+        data.write_iter_as_layout(&layout, iter.into_iter());
+
+        Ok(())
+    }
+}
+
+impl<T: Copy + PartialEq + Default> Tensor<T> {
+    /// Whether any element is nonzero, NaN included. False for an empty tensor.
+    /// Stops at the first nonzero element found.
+    pub fn any_scalar(&self) -> bool {
+        let zero = T::default();
+
+        self.iter().any(|el| *el != zero)
+    }
+
+    /// Whether every element is nonzero, NaN included. True for an empty tensor.
+    /// Stops at the first zero found.
+    pub fn all_scalar(&self) -> bool {
+        let zero = T::default();
+
+        self.iter().all(|el| *el != zero)
+    }
+}
+
+impl<T: Copy> Tensor<T> {
+    /// Applies `f` to every element and its position in a row-major walk of the tensor,
+    /// producing a contiguous tensor of the same shape.
+    pub fn map_with_flat_index<U: Copy>(&self, f: impl Fn(usize, T) -> U) -> Tensor<U> {
+        let out: Vec<U> = branch_fast_iter!(self.graph.get().copied_fast_iter() => iter, {
+            iter.enumerate().map(|(i, el)| f(i, el)).collect()
+        });
+
+        Tensor::from_vec(out, self.shape())
+    }
+
+    /// Applies `f` to every element and its index along each axis,
+    /// producing a contiguous tensor of the same shape.
+    pub fn map_with_multi_index<U: Copy>(&self, f: impl Fn(&[i32], T) -> U) -> Tensor<U> {
+        let shape = self.shape();
+        let mut index = vec![0i32; shape.len()];
+        let mut out = Vec::with_capacity(self.len());
+
+        branch_fast_iter!(self.graph.get().copied_fast_iter() => iter, {
+            for el in iter {
+                out.push(f(&index, el));
+
+                // Increments the index like an odometer, the last axis moving first.
+                for dim in (0..shape.len()).rev() {
+                    index[dim] += 1;
+
+                    if (index[dim] as usize) < shape[dim] {
+                        break;
+                    }
+
+                    index[dim] = 0;
+                }
+            }
+        });
+
+        Tensor::from_vec(out, shape)
+    }
+
+    /// Hash of the shape and the strides, which identifies the layout but not the data.
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        self.shape().hash(&mut hasher);
+        self.layout().stride().hash(&mut hasher);
+
+        hasher.finish()
+    }
+}
+
+impl<T: Copy + std::fmt::Display> Tensor<T> {
+    /// Same as TensorData::to_string_flat().
+    #[inline]
+    pub fn to_string_flat(&self) -> String {
+        self.graph.get().to_string_flat()
+    }
+
+    /// Same as TensorData::to_string_flat_with().
+    #[inline]
+    pub fn to_string_flat_with(&self, max_elements: usize) -> String {
+        self.graph.get().to_string_flat_with(max_elements)
+    }
+
+    /// Same as TensorData::preview(), with the names of the axes in the header if any.
+    #[inline]
+    pub fn preview(&self, max_rows: usize, max_cols: usize) -> String {
+        crate::tensor::pretty::preview(self.graph.get(), self.graph.names(), max_rows, max_cols)
+    }
+}
+
+impl<T: Copy + std::fmt::Debug> Tensor<T> {
+    /// Same as TensorData::to_parseable_string().
+    #[inline]
+    pub fn to_parseable_string(&self) -> String {
+        crate::tensor::pretty::parseable(self.graph.get())
+    }
+}
+
+impl<T: Copy + Hash> Tensor<T> {
+    /// Hash of the shape and of the elements in logical order, so the result
+    /// does not depend on the strides or the offset of the underlying buffer.
+    /// Not stable across versions of the crate, do not persist it.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        self.shape().hash(&mut hasher);
+        self.for_each(|el| el.hash(&mut hasher));
+
+        hasher.finish()
+    }
+}
+
+impl Tensor<f64> {
+    /// Same as content_hash(), over the bits of every element.
+    /// Values that compare equal but have different bits, like 0.0 and -0.0, hash differently.
+    pub fn content_hash_f64(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        self.shape().hash(&mut hasher);
+        self.for_each(|el| hasher.write_u64(el.to_bits()));
+
+        hasher.finish()
+    }
+
+    /// Sum of every element, 0.0 for an empty tensor. The elements are added up as set
+    /// with set_summation(), pairwise by default, and give the same sum bit for bit
+    /// whatever the layout of the tensor.
+    pub fn sum_scalar(&self) -> f64 {
+        let mut summer = Summer::new();
+        self.for_each_chunk(|chunk| summer.add_slice(chunk));
+
+        summer.finish()
+    }
+
+    /// sum_scalar() divided by the number of elements, NaN for an empty tensor.
+    pub fn mean_scalar(&self) -> f64 {
+        self.sum_scalar() / self.len() as f64
+    }
+
+    /// Sum of the products of the elements at the same position, added up like
+    /// sum_scalar(). Both tensors must have the same shape.
+    pub fn dot(&self, other: &Tensor<f64>) -> Result<f64, OpError> {
+        let mut summer = Summer::new();
+        self.zip_for_each(other, |a, b| summer.add(a * b))?;
+
+        Ok(summer.finish())
+    }
+
+    /// Sum of the absolute values (L1 norm), 0.0 for an empty tensor, added up like
+    /// sum_scalar(). Unlike reducing a promise, no intermediate tensor is allocated.
+    pub fn abs_sum(&self) -> f64 {
+        let mut summer = Summer::new();
+        self.for_each_chunk(|chunk| summer.extend(chunk.iter().map(|el| el.abs())));
+
+        summer.finish()
+    }
+
+    /// Largest absolute value (L-infinity norm), 0.0 for an empty tensor.
+    /// NaNs are not reliably propagated, as it depends on the BLAS provider.
+    pub fn max_abs(&self) -> f64 {
+        let mut max = 0.0;
+
+        self.for_each_chunk(|chunk| {
+            if let Some(el) = chunk.get(Backend::iamax(chunk))
+                && el.abs() > max
+            {
+                max = el.abs();
+            }
+        });
+
+        max
+    }
+
+    /// Same as max_abs().
+    #[inline]
+    pub fn norm_inf(&self) -> f64 {
+        self.max_abs()
+    }
+
+    // Calls `f` with the elements in logical order, with the buffer itself when it is
+    // contiguous or with packed chunks of it otherwise.
+    fn for_each_chunk(&self, mut f: impl FnMut(&[f64])) {
+        let data = self.graph.get();
+
+        if data.is_contiguous() {
+            let start = data.offset();
+            f(&data.storage.as_slice()[start..start + data.len()]);
+        } else {
+            let mut chunks = data.packed_iter();
+
+            while let Some(chunk) = chunks.next() {
+                f(chunk.packing_buffer);
+            }
+        }
+    }
+}
+
+impl<T: Copy + PartialOrd> Tensor<T> {
+    /// Returns every distinct value paired with the number of times it appears,
+    /// sorted in ascending order.
+    /// All NaNs are grouped as a single value at the end, and since -0.0 and 0.0
+    /// compare equal they are counted as the same value.
+    pub fn value_counts_map(&self) -> Vec<(T, usize)> {
+        let mut values: Vec<T> = self.iter().copied().collect();
+        values.sort_unstable_by(total_order);
+
+        let mut counts: Vec<(T, usize)> = Vec::new();
+
+        for value in values {
+            match counts.last_mut() {
+                Some((last, count)) if total_order(last, &value) == Ordering::Equal => {
+                    *count += 1;
+                }
+                _ => counts.push((value, 1)),
+            }
+        }
+
+        counts
+    }
+
+    /// Returns the sorted distinct values of this tensor as a 1-D tensor.
+    /// See value_counts_map() for how NaNs and signed zeros are handled.
+    pub fn unique(&self) -> Tensor<T> {
+        let counts = self.value_counts_map();
+
+        Tensor::from_iter(counts.iter().map(|(value, _)| *value), &[counts.len()])
+    }
+
+    /// Same as unique(), but also returns how many times each value appears.
+    pub fn unique_with_counts(&self) -> (Tensor<T>, Tensor<i64>) {
+        let counts = self.value_counts_map();
+        let len = counts.len();
+
+        let values = Tensor::from_iter(counts.iter().map(|(value, _)| *value), &[len]);
+        let counts = Tensor::from_iter(counts.iter().map(|(_, count)| *count as i64), &[len]);
+
+        (values, counts)
     }
 }
 
@@ -102,6 +838,93 @@ impl<T: NumberLike> Tensor<T> {
     }
 }
 
+impl<T: Copy + Send + Sync + 'static> Tensor<T> {
+    /// A promise of the elements of this tensor converted to U, which may only be a type
+    /// every T converts to exactly, like f32 or i32 to f64. The elements are those the
+    /// tensor has now, later writes to it are not seen.
+    ///
+    /// The cast is not computed on its own when a single Add, Sub, Mul or Div reads it:
+    /// that op converts the elements as it reads them, so no converted copy is allocated.
+    /// See set_cast_fusion().
+    ///
+    /// ```
+    /// # use simple_tensor::tensor::Tensor;
+    /// let x = Tensor::from_vec(vec![1.5f32, 2.0, -4.0], &[3]);
+    /// let w = Tensor::from_vec(vec![2.0, 0.5, 0.25], &[3]);
+    ///
+    /// let y = (x.cast::<f64>() * &w).materialize();
+    /// assert_eq!(y.iter().copied().collect::<Vec<_>>(), [3.0, 1.0, -1.0]);
+    /// ```
+    pub fn cast<U: NumberLike + From<T> + 'static>(&self) -> TensorPromise<U> {
+        TensorPromise::with_layout(
+            super::ops::def_op::OpKind::Cast(super::ops::CastSource::new(self.data().clone())),
+            Box::new([]),
+            Layout::from_shape(self.shape(), 0),
+        )
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<T: Copy + Send + Sync> Tensor<T> {
+    /// Same as TensorData::par_iter().
+    #[inline]
+    pub fn par_iter(&self) -> ParIter<'_, T> {
+        self.graph.get().par_iter()
+    }
+}
+
+/// The elements in row-major order, copied, so `for x in &tensor` works on any layout.
+impl<'a, T: Copy> IntoIterator for &'a Tensor<T> {
+    type Item = T;
+    type IntoIter = CopiedSliceIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.graph.get().copied_iter()
+    }
+}
+
+/// The elements in row-major order. The buffer is moved into the iterator without copying
+/// it if nothing else references this tensor or its memory and the tensor reads all of it
+/// contiguously, see is_shared(). The elements are copied otherwise.
+impl<T: Copy> IntoIterator for Tensor<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        match self.unique_data_mut().and_then(TensorData::take_vec) {
+            Some(vector) => vector.into_iter(),
+            None => self
+                .graph
+                .get()
+                .copied_iter()
+                .collect::<Vec<T>>()
+                .into_iter(),
+        }
+    }
+}
+
+/// Collects the elements into a contiguous tensor of rank 1, see collect_with_shape()
+/// for other shapes.
+impl<T: Copy> FromIterator<T> for Tensor<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let vector: Vec<T> = iter.into_iter().collect();
+        let len = vector.len();
+
+        Self::from_vec(vector, &[len])
+    }
+}
+
+/// Same as from_str_data().
+impl<T: Copy + FromStr> FromStr for Tensor<T> {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::from_str_data(text)
+    }
+}
+
 impl<T: Copy> Dimension for Tensor<T> {
     #[inline]
     fn layout(&self) -> &super::mem_formats::layout::Layout {
@@ -109,6 +932,28 @@ impl<T: Copy> Dimension for Tensor<T> {
     }
 }
 
+/// A tensor given up with Tensor::consume(), which the ops read like any other tensor.
+/// It is not Clone, and the ops and operators take it by value, so a single op reads it.
+/// The traits borrowing their operands, like ElementwiseMinMax, are not implemented for it.
+///
+/// ```compile_fail,E0382
+/// use simple_tensor::vector;
+///
+/// let c = vector![1.0, 4.0, 9.0].consume();
+/// let p1 = c + 1.0;
+/// let p2 = c * 2.0;
+/// ```
+pub struct ConsumableTensor<T: Copy> {
+    pub(crate) graph: Arc<TensorGraphEdge<T>>,
+}
+
+impl<T: Copy> Dimension for ConsumableTensor<T> {
+    #[inline]
+    fn layout(&self) -> &Layout {
+        self.graph.layout()
+    }
+}
+
 /// Make a shallow copy of this tensor.
 /// That means that the underlying memory is, or may be, shared with other objects.
 /// The shallow copy still maintain connection with all the promises depending on this tensor
@@ -123,4 +968,22 @@ impl<T: Copy> Clone for Tensor<T> {
     }
 }
 
-impl_display!(Tensor<T>);
+impl<T: Copy + std::fmt::Debug> std::fmt::Debug for Tensor<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tensor")
+            .field("shape", &self.shape())
+            .field("names", &self.graph.names())
+            .field("data", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<T: std::fmt::Display + NumberLike> std::fmt::Display for Tensor<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(names) = self.graph.names() {
+            writeln!(f, "names: [{}]", names.join(", "))?;
+        }
+
+        self.graph.get().fmt(f)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::tensor::device::Device;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::traits::Dimension;
+
+/// Approximates the gradient of `f` at `x` with central differences,
+/// `(f(x + eps * e_i) - f(x - eps * e_i)) / (2 * eps)` for every element `i` of `x`.
+/// The result has the shape of `x`, and its elements follow the row-major order of `x`.
+pub fn numerical_jacobian<F: Fn(&Tensor<f64>) -> f64>(
+    f: F,
+    x: &Tensor<f64>,
+    eps: f64,
+) -> Tensor<f64> {
+    let mut values: Vec<f64> = x.iter().copied().collect();
+    let mut grad = Vec::with_capacity(values.len());
+
+    for i in 0..values.len() {
+        let original = values[i];
+
+        values[i] = original + eps;
+        let forward = f(&Tensor::from_vec(values.clone(), x.shape()));
+
+        values[i] = original - eps;
+        let backward = f(&Tensor::from_vec(values.clone(), x.shape()));
+
+        values[i] = original;
+        grad.push((forward - backward) / (2.0 * eps));
+    }
+
+    Tensor::from_vec(grad, x.shape())
+}
+
+/// Checks an analytical gradient of `f` at `x` against numerical_jacobian().
+/// Every element must satisfy `|numerical - analytical| <= atol + rtol * |analytical|`,
+/// and both gradients must have the shape of `x`.
+/// There is no autograd yet, so the analytical gradient has to be provided by the caller.
+pub fn check_grad<F: Fn(&Tensor<f64>) -> f64>(
+    f: F,
+    analytical: &Tensor<f64>,
+    x: &Tensor<f64>,
+    eps: f64,
+    atol: f64,
+    rtol: f64,
+) -> bool {
+    if analytical.shape() != x.shape() {
+        return false;
+    }
+
+    let numerical = numerical_jacobian(f, x, eps);
+
+    numerical
+        .iter()
+        .zip(analytical.iter())
+        .all(|(&n, &a)| (n - a).abs() <= atol + rtol * a.abs())
+}
+
+/// The elements of `t`, in the same buffer, tagged as placed on `device`. No backend other
+/// than the cpu exists yet, so this is only meant to test how graphs validate placement:
+/// ops over the result are built on `device`, and computing them panics unless it is the cpu.
+pub fn placed_on<T: Copy>(t: &Tensor<T>, device: Device) -> Tensor<T> {
+    Tensor::from_data(t.data().clone().with_device(device))
+}
+
+#[cfg(feature = "proptest")]
+pub mod proptest_strategies {
+    use std::fmt::Debug;
+    use std::ops::Range;
+
+    use proptest::prelude::*;
+
+    use crate::tensor::mem_formats::slice::SliceRange;
+    use crate::tensor::tensor::Tensor;
+
+    /// Shapes with 1 to `max_rank` axes, each of them with 1 to `max_dim` elements.
+    /// The product of the shape is always bigger than 0.
+    pub fn arb_shape(max_rank: usize, max_dim: usize) -> impl Strategy<Value = Vec<usize>> {
+        prop::collection::vec(1..=max_dim.max(1), 1..=max_rank.max(1))
+    }
+
+    /// Contiguous tensors with a shape generated by `shape` and arbitrary elements.
+    pub fn arb_tensor<T>(
+        shape: impl Strategy<Value = Vec<usize>>,
+    ) -> impl Strategy<Value = Tensor<T>>
+    where
+        T: Arbitrary + Copy + Debug,
+    {
+        shape.prop_flat_map(|shape| {
+            let len: usize = shape.iter().product();
+
+            prop::collection::vec(any::<T>(), len)
+                .prop_map(move |values| Tensor::from_vec(values, &shape))
+        })
+    }
+
+    /// Non-empty ranges within `0..dim_size`, sometimes expressed with negative indexes.
+    pub fn arb_slice_range(dim_size: usize) -> impl Strategy<Value = SliceRange> {
+        let dim_size = dim_size.max(1) as i32;
+
+        (0..dim_size)
+            .prop_flat_map(move |start| (Just(start), start + 1..=dim_size, any::<bool>()))
+            .prop_map(move |(start, end, reversed)| {
+                if reversed && end < dim_size {
+                    SliceRange::from(Range {
+                        start: start - dim_size,
+                        end: end - dim_size,
+                    })
+                } else {
+                    SliceRange::from(start..end)
+                }
+            })
+    }
+}
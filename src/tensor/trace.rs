@@ -0,0 +1,161 @@
+use std::marker::PhantomData;
+
+use crate::tensor::graph::TensorId;
+
+#[cfg(feature = "execution_trace")]
+use std::cell::RefCell;
+
+/// How a node read the result of one of its inputs, see TraceRecord::inputs().
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputRead {
+    /// The last read of the result, which is moved into the op, so it may reuse its buffer.
+    Consumed,
+    /// A read while other nodes still have to read the result, which stays alive.
+    Cloned,
+}
+
+/// A node computed while a TraceRecorder was recording.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceRecord {
+    id: TensorId,
+    op: &'static str,
+    inputs: Vec<(TensorId, InputRead)>,
+}
+
+impl TraceRecord {
+    /// The node that was computed, the same as the id() of its promise or tensor.
+    pub fn id(&self) -> TensorId {
+        self.id
+    }
+
+    /// Name of the op of the node, like "Add", or "Tensor" for the tensors a graph reads.
+    pub fn op(&self) -> &'static str {
+        self.op
+    }
+
+    /// The results the node read, in the order it read them.
+    /// Tensors and filled caches read none. An op fusing one of its inputs, like a
+    /// division by a square root, lists the inputs of the fused node in its place.
+    pub fn inputs(&self) -> &[(TensorId, InputRead)] {
+        &self.inputs
+    }
+}
+
+// The records of the recorder of this thread, and the reads of the node being computed.
+#[cfg(feature = "execution_trace")]
+#[derive(Default)]
+struct Trace {
+    records: Vec<TraceRecord>,
+    reads: Vec<(TensorId, InputRead)>,
+}
+
+#[cfg(feature = "execution_trace")]
+thread_local! {
+    static TRACE: RefCell<Option<Trace>> = const { RefCell::new(None) };
+}
+
+/// Records the nodes computed on the current thread, in the order they are computed,
+/// along with how each of them read its inputs. Meant for tests checking the order of
+/// materialize(), materialize_min_memory() and materialize_many().
+///
+/// Nodes are only recorded with the `execution_trace` feature, otherwise finish() always
+/// returns no records. A thread records for one recorder at a time: starting another
+/// drops what the previous one recorded.
+pub struct TraceRecorder {
+    // Recording is per thread, so the recorder stays on the thread that started it.
+    _thread: PhantomData<*const ()>,
+}
+
+impl TraceRecorder {
+    /// Starts recording on the current thread.
+    pub fn start() -> Self {
+        #[cfg(feature = "execution_trace")]
+        TRACE.set(Some(Trace::default()));
+
+        Self {
+            _thread: PhantomData,
+        }
+    }
+
+    /// Stops recording and returns the records, in the order the nodes were computed.
+    pub fn finish(self) -> Vec<TraceRecord> {
+        #[cfg(feature = "execution_trace")]
+        {
+            TRACE.take().map(|trace| trace.records).unwrap_or_default()
+        }
+
+        #[cfg(not(feature = "execution_trace"))]
+        Vec::new()
+    }
+}
+
+impl Drop for TraceRecorder {
+    fn drop(&mut self) {
+        #[cfg(feature = "execution_trace")]
+        TRACE.set(None);
+    }
+}
+
+// Records that the node being computed read the result of `id`.
+#[cfg(feature = "execution_trace")]
+pub(crate) fn read(id: usize, read: InputRead) {
+    TRACE.with_borrow_mut(|trace| {
+        if let Some(trace) = trace {
+            trace.reads.push((TensorId(id), read));
+        }
+    });
+}
+
+// Records the node `id` as computed, from the reads since the previous node.
+#[cfg(feature = "execution_trace")]
+pub(crate) fn computed(id: usize, op: &'static str) {
+    TRACE.with_borrow_mut(|trace| {
+        if let Some(trace) = trace {
+            let inputs = std::mem::take(&mut trace.reads);
+            trace.records.push(TraceRecord {
+                id: TensorId(id),
+                op,
+                inputs,
+            });
+        }
+    });
+}
+
+// Drops the reads since the previous node, which were the results of the graph
+// handed to the caller rather than read by a node.
+#[cfg(feature = "execution_trace")]
+pub(crate) fn outputs_read() {
+    TRACE.with_borrow_mut(|trace| {
+        if let Some(trace) = trace {
+            trace.reads.clear();
+        }
+    });
+}
+
+// Runs `f` without recording what it computes.
+#[cfg(feature = "execution_trace")]
+pub(crate) fn untraced<R>(f: impl FnOnce() -> R) -> R {
+    let trace = TRACE.take();
+    let result = f();
+    TRACE.set(trace);
+
+    result
+}
+
+#[cfg(not(feature = "execution_trace"))]
+#[inline(always)]
+pub(crate) fn read(_id: usize, _read: InputRead) {}
+
+#[cfg(not(feature = "execution_trace"))]
+#[inline(always)]
+pub(crate) fn computed(_id: usize, _op: &'static str) {}
+
+#[cfg(not(feature = "execution_trace"))]
+#[inline(always)]
+pub(crate) fn outputs_read() {}
+
+#[cfg(not(feature = "execution_trace"))]
+#[inline(always)]
+pub(crate) fn untraced<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
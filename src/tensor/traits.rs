@@ -1,4 +1,7 @@
+use crate::tensor::device::Device;
+use crate::tensor::errors::OpError;
 use crate::tensor::mem_formats::layout::Layout;
+use crate::tensor::ops::AxisNameList;
 use crate::tensor::storage::TensorData;
 
 pub trait Dimension {
@@ -46,7 +49,24 @@ pub trait Promising {
 
     fn compute(&self) -> TensorData<Self::Output>;
 
+    /// Computes the result into `out`, which has its shape and owns its buffer.
+    /// By default the result is computed on its own and copied into it.
+    fn compute_into(&self, out: &mut TensorData<Self::Output>) {
+        out.write_from(&self.compute());
+    }
+
     fn layout(&self) -> &Layout;
+
+    /// Device the result is placed on.
+    fn device(&self) -> Device;
+
+    fn names(&self) -> Option<&AxisNameList>;
+
+    /// Ops on the longest path from a tensor to this one, 0 for tensors.
+    fn depth(&self) -> usize;
+
+    /// Fails with OpError::CyclicGraph if this is its own input, directly or not.
+    fn validate_acyclic(&self) -> Result<(), OpError>;
 }
 
 pub trait StreamingIterator {
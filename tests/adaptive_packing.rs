@@ -0,0 +1,220 @@
+mod common;
+
+use std::sync::Arc;
+
+use simple_tensor::tensor::complex::Complex;
+use simple_tensor::tensor::{
+    BinaryRoute, InMemoryMetrics, Layout, MetricKey, Tensor, TensorPromise, clear_metrics_sink,
+    set_adaptive_packing, set_metrics_sink,
+};
+
+use common::{bits, lock_globals};
+
+type BinaryFn = fn(&Tensor<f64>, &Tensor<f64>) -> TensorPromise<f64>;
+
+// Finite values of every magnitude, signed zeros, infinities and NaN.
+fn specials(len: usize) -> Tensor<f64> {
+    let pool = [
+        1.5,
+        -0.0,
+        0.0,
+        f64::INFINITY,
+        -2.25,
+        f64::NAN,
+        1e300,
+        -1e-310,
+        f64::NEG_INFINITY,
+        0.1,
+        7.0,
+        -3e-8,
+    ];
+    Tensor::from_iter((0..len).map(|i| pool[(i * 7 + i / 5) % pool.len()]), &[len])
+}
+
+// The result of `f` with and without adaptive packing, and the routes it took.
+fn both_ways(f: impl Fn() -> TensorPromise<f64>) -> (Tensor<f64>, Tensor<f64>, [u64; 4]) {
+    let metrics = Arc::new(InMemoryMetrics::new());
+    set_metrics_sink(metrics.clone());
+    let adaptive = f().materialize();
+    clear_metrics_sink();
+
+    set_adaptive_packing(false);
+    let packed = f().materialize();
+    set_adaptive_packing(true);
+
+    let routes = [
+        BinaryRoute::Contiguous,
+        BinaryRoute::Scalar,
+        BinaryRoute::Strided,
+        BinaryRoute::Packed,
+    ]
+    .map(|route| metrics.get(MetricKey::BinaryRoutes(route)));
+
+    (adaptive, packed, routes)
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn layouts_tell_their_pattern() {
+    let contiguous = Layout::from_shape(&[4, 6], 0);
+    assert_eq!(contiguous.single_stride(), Some(1));
+    assert!(!contiguous.is_constant_broadcast());
+
+    // A column of a [4, 6] matrix, and every other element of a row.
+    assert_eq!(Layout::from_slice(&[4], &[6], 2).single_stride(), Some(6));
+    assert_eq!(
+        Layout::from_slice(&[4, 1], &[6, 1], 2).single_stride(),
+        Some(6)
+    );
+    assert_eq!(Layout::from_slice(&[3], &[2], 0).single_stride(), Some(2));
+    // Every other column of rows 7 apart: the step to the next row is not the same.
+    assert_eq!(
+        Layout::from_slice(&[4, 3], &[7, 2], 0).single_stride(),
+        None
+    );
+    assert_eq!(
+        Layout::from_slice(&[4, 3], &[6, 2], 0).single_stride(),
+        Some(2)
+    );
+    assert_eq!(contiguous.transpose().single_stride(), None);
+
+    let broadcast = Layout::as_strided(&[4, 6], &[0, 0], 3, 4).unwrap();
+    assert_eq!(broadcast.single_stride(), Some(0));
+    assert!(broadcast.is_constant_broadcast());
+    assert!(
+        !Layout::as_strided(&[4, 6], &[1, 0], 0, 4)
+            .unwrap()
+            .is_constant_broadcast()
+    );
+    assert_eq!(Layout::from_shape(&[1, 1], 0).single_stride(), None);
+}
+
+#[test]
+fn every_route_gives_the_values_of_the_packed_one() {
+    let _globals = lock_globals();
+    let lhs = specials(24).as_strided(&[4, 6], &[6, 1], 0).unwrap();
+    let matrix = specials(576);
+
+    // A repeated element, every 24th element, and a transposed view packed either way.
+    let one = specials(12);
+    let repeated = [0, 1, 3, 5, 6, 9].map(|at| one.as_strided(&[4, 6], &[0, 0], at).unwrap());
+    let strided = matrix.as_strided(&[4, 6], &[144, 24], 5).unwrap();
+    let general = specials(24).as_strided(&[4, 6], &[1, 4], 0).unwrap();
+
+    let ops: [(&str, BinaryFn); 4] = [
+        ("Add", |a, b| a + b),
+        ("Sub", |a, b| a - b),
+        ("Mul", |a, b| a * b),
+        ("Div", |a, b| a / b),
+    ];
+
+    for (name, op) in ops {
+        for rhs in repeated.iter().chain([&strided, &general]) {
+            let (adaptive, packed, _) = both_ways(|| op(&lhs, rhs));
+            assert_eq!(bits(&adaptive), bits(&packed), "{} {:?}", name, rhs.data());
+
+            let expected: Vec<u64> = (lhs.iter().zip(rhs.iter()))
+                .map(|(&a, &b)| {
+                    op(
+                        &Tensor::from_vec(vec![a], &[1]),
+                        &Tensor::from_vec(vec![b], &[1]),
+                    )
+                })
+                .map(|promise| promise.materialize().iter().next().unwrap().to_bits())
+                .collect();
+            assert_eq!(bits(&adaptive), expected, "{}", name);
+        }
+    }
+
+    // Complex numbers only read a broadcast unpacked, for Add and Sub.
+    let z = Tensor::from_iter(
+        (0..24).map(|i| Complex::new(i as f64, -0.5 * i as f64)),
+        &[4, 6],
+    );
+    let c = Tensor::from_vec(vec![Complex::new(f64::INFINITY, 2.0)], &[1]);
+    let c = c.as_strided(&[4, 6], &[0, 0], 0).unwrap();
+    for (sum, expected) in [
+        ((&z + &c).materialize(), 1.0),
+        ((&z - &c).materialize(), -1.0),
+    ] {
+        let (sum, z): (Vec<_>, Vec<_>) =
+            (sum.iter().copied().collect(), z.iter().copied().collect());
+        assert!(
+            sum.iter()
+                .zip(&z)
+                .all(|(s, z)| s.re == expected * f64::INFINITY && s.im == z.im + expected * 2.0)
+        );
+    }
+}
+
+#[test]
+fn each_pattern_takes_its_route() {
+    let _globals = lock_globals();
+    let x = Tensor::from_iter((0..24).map(|i| i as f64), &[4, 6]);
+    let one = Tensor::from_vec(vec![2.0], &[1]);
+    let repeated = one.as_strided(&[4, 6], &[0, 0], 0).unwrap();
+
+    // Add, Sub and Mul by a repeated element are scalar ops, Div is packed.
+    let ops: [(&dyn Fn() -> TensorPromise<f64>, BinaryRoute); 4] = [
+        (&|| &x + &repeated, BinaryRoute::Scalar),
+        (&|| &x - &repeated, BinaryRoute::Scalar),
+        (&|| &x * &repeated, BinaryRoute::Scalar),
+        (&|| &x / &repeated, BinaryRoute::Packed),
+    ];
+
+    for (op, route) in ops {
+        let (adaptive, packed, routes) = both_ways(op);
+        assert_eq!(bits(&adaptive), bits(&packed));
+        assert_eq!(routes.iter().sum::<u64>(), 1);
+        assert_eq!(routes[route as usize], 1, "{:?}", routes);
+    }
+
+    // A contiguous operand is read in place, and a lhs of any layout is copied first.
+    let (_, _, routes) = both_ways(|| &repeated + &x);
+    assert_eq!(routes, [1, 0, 0, 0]);
+
+    // Without adaptive packing everything that is not contiguous is packed.
+    set_adaptive_packing(false);
+    let metrics = Arc::new(InMemoryMetrics::new());
+    set_metrics_sink(metrics.clone());
+    (&x + &repeated).materialize();
+    clear_metrics_sink();
+    set_adaptive_packing(true);
+    assert_eq!(metrics.get(MetricKey::BinaryRoutes(BinaryRoute::Packed)), 1);
+    assert_eq!(metrics.get(MetricKey::BinaryRoutes(BinaryRoute::Scalar)), 0);
+}
+
+#[test]
+fn a_column_plus_a_vector_is_not_packed() {
+    let _globals = lock_globals();
+    let (rows, cols) = (1000, 7);
+    let matrix = Tensor::from_iter((0..rows * cols).map(|i| i as f64 * 0.5), &[rows, cols]);
+    let column = matrix.as_strided(&[rows], &[cols as i32], 3).unwrap();
+    let vector = Tensor::from_iter((0..rows).map(|i| 1.0 / (i + 1) as f64), &[rows]);
+
+    let (sum, packed, routes) = both_ways(|| &vector + &column);
+    assert_eq!(routes, [0, 0, 1, 0]);
+    assert_eq!(bits(&sum), bits(&packed));
+    assert_eq!(
+        sum.iter().nth(10),
+        Some(&(1.0 / 11.0 + (10 * cols + 3) as f64 * 0.5))
+    );
+
+    let (difference, packed, routes) = both_ways(|| &vector - &column);
+    assert_eq!(routes, [0, 0, 1, 0]);
+    assert_eq!(bits(&difference), bits(&packed));
+    assert_eq!(difference.iter().next(), Some(&(1.0 - 1.5)));
+
+    // Mul and Div have no strided kernel.
+    for op in [(|v, c| v * c) as BinaryFn, |v, c| v / c] {
+        let (result, packed, routes) = both_ways(|| op(&vector, &column));
+        assert_eq!(routes, [0, 0, 0, 1]);
+        assert_eq!(bits(&result), bits(&packed));
+    }
+
+    // The strided operand is the lhs of Sub: it is copied, and the rhs is contiguous.
+    let (difference, packed, routes) = both_ways(|| &column - &vector);
+    assert_eq!(routes, [1, 0, 0, 0]);
+    assert_eq!(bits(&difference), bits(&packed));
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use std::cell::Cell;
+
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, s, vector};
+
+use common::values;
+
+// An f64 counting how many times it is compared, to see where any_scalar() and
+// all_scalar() stop.
+#[derive(Clone, Copy, Debug, Default)]
+struct Counted(f64);
+
+thread_local! {
+    static COMPARISONS: Cell<usize> = const { Cell::new(0) };
+}
+
+impl PartialEq for Counted {
+    fn eq(&self, other: &Self) -> bool {
+        COMPARISONS.set(COMPARISONS.get() + 1);
+        self.0 == other.0
+    }
+}
+
+fn comparisons_of(f: impl FnOnce() -> bool) -> (bool, usize) {
+    COMPARISONS.set(0);
+    let result = f();
+
+    (result, COMPARISONS.get())
+}
+
+// Any and all of every lane along `axis` of a row-major [2, 3, 4] pattern.
+fn reference(pattern: &[f64], axis: usize) -> (Vec<f64>, Vec<f64>) {
+    let shape = [2, 3, 4];
+    let stride = [12, 4, 1];
+    let kept: Vec<usize> = (0..3).filter(|&dim| dim != axis).collect();
+    let lanes = shape[kept[0]] * shape[kept[1]];
+
+    let (mut any, mut all) = (Vec::new(), Vec::new());
+    for lane in 0..lanes {
+        let start =
+            lane / shape[kept[1]] * stride[kept[0]] + lane % shape[kept[1]] * stride[kept[1]];
+        let mut lane = (0..shape[axis]).map(|k| pattern[start + k * stride[axis]]);
+
+        any.push(f64::from(lane.clone().any(|el| el != 0.0)));
+        all.push(f64::from(lane.all(|el| el != 0.0)));
+    }
+
+    (any, all)
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn every_axis_matches_the_reference() {
+    // Lanes with no nonzero element, some of them and all of them along every axis.
+    let pattern: Vec<f64> = (0..24)
+        .map(|i| match i % 7 {
+            0 | 3 => 0.0,
+            5 => -2.0,
+            _ => 1.0,
+        })
+        .collect();
+    let t = Tensor::from_vec(pattern.clone(), &[2, 3, 4]);
+
+    for axis in 0..3 {
+        let (any, all) = reference(&pattern, axis);
+
+        let got_any = t.any(axis).unwrap().materialize();
+        let got_all = t.all(axis).unwrap().materialize();
+
+        let mut shape = vec![2, 3, 4];
+        shape.remove(axis);
+        assert_eq!(got_any.shape(), shape.as_slice());
+        assert_eq!(got_all.shape(), shape.as_slice());
+
+        assert_eq!(values(&got_any), any, "any along {axis}");
+        assert_eq!(values(&got_all), all, "all along {axis}");
+    }
+}
+
+#[test]
+fn empty_lanes_are_false_for_any_and_true_for_all() {
+    let t = Tensor::from_vec(Vec::<f64>::new(), &[2, 0, 3]);
+
+    let any = t.any(1).unwrap().materialize();
+    assert_eq!(any.shape(), &[2, 3]);
+    assert_eq!(values(&any), [0.0; 6]);
+
+    let all = t.all(1).unwrap().materialize();
+    assert_eq!(all.shape(), &[2, 3]);
+    assert_eq!(values(&all), [1.0; 6]);
+
+    // No lane at all.
+    let any = t.any(0).unwrap().materialize();
+    assert_eq!(any.shape(), &[0, 3]);
+
+    assert!(!Tensor::from_vec(Vec::<f64>::new(), &[0]).any_scalar());
+    assert!(Tensor::from_vec(Vec::<f64>::new(), &[0]).all_scalar());
+}
+
+#[test]
+fn strided_inputs_are_read_in_place() {
+    // Every other element, from the second one.
+    let buffer = Tensor::from_vec(
+        vec![9.0, 0.0, 9.0, 5.0, 9.0, 1.0, 9.0, 2.0, 9.0, 3.0, 9.0, 0.0],
+        &[12],
+    );
+    let strided = buffer.as_strided(&[2, 3], &[6, 2], 1).unwrap();
+    let contiguous = Tensor::from_vec(values(&strided), &[2, 3]);
+    assert_eq!(values(&contiguous), [0.0, 5.0, 1.0, 2.0, 3.0, 0.0]);
+
+    for axis in 0..2 {
+        assert_eq!(
+            values(&strided.any(axis).unwrap().materialize()),
+            values(&contiguous.any(axis).unwrap().materialize()),
+        );
+        assert_eq!(
+            values(&strided.all(axis).unwrap().materialize()),
+            values(&contiguous.all(axis).unwrap().materialize()),
+        );
+    }
+
+    // The lanes of a transposed view run along its buffer.
+    let transposed = strided.transpose();
+    assert_eq!(
+        values(&transposed.any(1).unwrap().materialize()),
+        [1.0, 1.0, 1.0]
+    );
+    assert_eq!(
+        values(&transposed.all(1).unwrap().materialize()),
+        [0.0, 1.0, 0.0]
+    );
+}
+
+#[test]
+fn scalar_reductions_stop_at_the_first_decisive_element() {
+    let t = Tensor::from_vec(
+        vec![
+            Counted(0.0),
+            Counted(0.0),
+            Counted(3.0),
+            Counted(0.0),
+            Counted(5.0),
+        ],
+        &[5],
+    );
+
+    // Any stops at the first nonzero element, the third one.
+    assert_eq!(comparisons_of(|| t.any_scalar()), (true, 3));
+
+    // All stops at the first zero, the first one.
+    assert_eq!(comparisons_of(|| t.all_scalar()), (false, 1));
+
+    // Neither finds its element and reads them all.
+    let zeros = Tensor::from_vec(vec![Counted(0.0); 4], &[4]);
+    assert_eq!(comparisons_of(|| zeros.any_scalar()), (false, 4));
+
+    let ones = Tensor::from_vec(vec![Counted(1.0); 4], &[4]);
+    assert_eq!(comparisons_of(|| ones.all_scalar()), (true, 4));
+}
+
+#[test]
+fn nans_are_found_with_isnan() {
+    let x = vector![1.0, f64::NAN, f64::INFINITY, -f64::INFINITY, 0.0, -2.5];
+
+    assert_eq!(
+        values(&x.isnan().materialize()),
+        [0.0, 1.0, 0.0, 0.0, 0.0, 0.0]
+    );
+    assert_eq!(
+        values(&x.isinf().materialize()),
+        [0.0, 0.0, 1.0, 1.0, 0.0, 0.0]
+    );
+
+    // NaN is nonzero, so any() and all() treat it as true, and finding one needs isnan().
+    let m = Tensor::from_vec(vec![f64::NAN, 1.0, 0.0, 2.0, 3.0, 4.0], &[2, 3]);
+    assert_eq!(values(&m.all(1).unwrap().materialize()), [0.0, 1.0]);
+    assert_eq!(values(&m.isnan().any(1).unwrap().materialize()), [1.0, 0.0]);
+
+    assert!(x.isnan().any_scalar());
+    assert!(!(&x * 0.0).isnan().all_scalar());
+    assert!(!vector![1.0, 2.0].isnan().any_scalar());
+}
+
+#[test]
+fn masks_of_offset_views_only_count_their_elements() {
+    let a = matrix![[f64::NAN, f64::NAN, f64::NAN], [1.0, 2.0, 3.0]];
+    let b = Tensor::from_scalar(0.0, &[2, 3]);
+
+    // The second row of a result, a view past the start of its buffer.
+    let mask = (&a + &b).slice(s![1, ..]).unwrap().isnan().materialize();
+    assert_eq!(values(&mask), [0.0, 0.0, 0.0]);
+    assert_eq!(mask.density_hint(), Some(0.0));
+
+    let mask = (&a * 1.0).slice(s![0, 1..]).unwrap().isnan().materialize();
+    assert_eq!(values(&mask), [1.0, 1.0]);
+    assert_eq!(mask.density_hint(), Some(1.0));
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use simple_tensor::s;
+use simple_tensor::ss;
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::{ApplyOptions, Dimension, PACKING_BUFFER_SIZE, Tensor, TensorPromise};
+
+use common::{arange, values};
+
+fn custom(x: f64) -> f64 {
+    (x * 0.25).sin() * x - 1.0
+}
+
+fn eager(t: &Tensor<f64>) -> Vec<f64> {
+    t.iter().map(|&x| custom(x)).collect()
+}
+
+fn chunked(input: &[f64], output: &mut [f64]) {
+    for (out, &x) in output.iter_mut().zip(input) {
+        *out = custom(x);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn chunked_and_scalar_forms_match_an_eager_map() {
+    let t = arange(&[17, 23]);
+    // Contiguous, strided with negative steps and strided across rows.
+    let inputs = [
+        t.clone(),
+        t.flip_view(&[0, 1]).unwrap(),
+        t.diagonal().unwrap(),
+    ];
+    assert!(!inputs[1].data().is_contiguous() && !inputs[2].data().is_contiguous());
+
+    for input in &inputs {
+        let expected = eager(input);
+
+        let from_chunks = input.apply(chunked).materialize();
+        let from_scalars = input.apply_scalar(custom).materialize();
+
+        assert_eq!(from_chunks.shape(), input.shape());
+        assert_eq!(values(&from_chunks), expected);
+        assert_eq!(values(&from_scalars), expected);
+    }
+
+    // Over the results of other ops.
+    let promises = [
+        &t * 2.0,
+        t.transpose(),
+        t.slice(s![2..15, 3..]).unwrap(),
+        t.step_slice(ss![.., 1..23;3]).unwrap(),
+    ];
+
+    for promise in promises {
+        let expected = eager(&promise.clone().materialize());
+
+        assert_eq!(values(&promise.apply(chunked).materialize()), expected);
+        assert_eq!(
+            values(&promise.apply_scalar(custom).materialize()),
+            expected
+        );
+    }
+
+    assert!(t.iter().copied().eq((0..17 * 23).map(|i| i as f64)));
+}
+
+#[test]
+fn every_element_is_visited_once_across_chunks() {
+    for len in [
+        1,
+        PACKING_BUFFER_SIZE - 1,
+        PACKING_BUFFER_SIZE,
+        3 * PACKING_BUFFER_SIZE + 5,
+    ] {
+        let t = arange(&[len]);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+
+        let recorder = {
+            let (seen, chunks) = (seen.clone(), chunks.clone());
+            move |input: &[f64], output: &mut [f64]| {
+                assert_eq!(input.len(), output.len());
+                seen.lock().unwrap().extend_from_slice(input);
+                chunks.lock().unwrap().push(input.len());
+                output.copy_from_slice(input);
+            }
+        };
+
+        let result = t.apply(recorder).materialize();
+
+        assert_eq!(values(&result), values(&t));
+        assert_eq!(*seen.lock().unwrap(), values(&t));
+
+        let chunks = chunks.lock().unwrap();
+        assert_eq!(chunks.len(), len.div_ceil(PACKING_BUFFER_SIZE));
+        assert!(chunks.iter().all(|&n| n > 0 && n <= PACKING_BUFFER_SIZE));
+    }
+}
+
+#[test]
+fn streamed_graphs_call_the_closure_per_stream_chunk() {
+    let t = arange(&[10, 31]);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let promise = {
+        let calls = calls.clone();
+        (&t + 1.0).apply(move |input, output| {
+            calls.fetch_add(input.len(), Ordering::Relaxed);
+            chunked(input, output);
+        })
+    };
+
+    let expected = eager(&(&t + 1.0).materialize());
+
+    for chunk_elems in [7, 91, 310, 1000] {
+        calls.store(0, Ordering::Relaxed);
+        let mut out = Vec::new();
+
+        promise
+            .materialize_streamed(chunk_elems, |chunk, offset| {
+                assert_eq!(offset, out.len());
+                out.extend_from_slice(chunk);
+            })
+            .unwrap();
+
+        assert_eq!(out, expected);
+        assert_eq!(calls.load(Ordering::Relaxed), 310);
+    }
+}
+
+#[test]
+fn closures_can_run_on_other_threads() {
+    fn assert_send_sync<S: Send + Sync>(_: &S) {}
+
+    let t = arange(&[64, 64]);
+    let promise = (&t * 0.5).apply_scalar(custom);
+    assert_send_sync(&promise);
+
+    let expected = eager(&(&t * 0.5).materialize());
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| scope.spawn(|| values(&promise.clone().materialize())))
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    });
+}
+
+#[test]
+fn only_pure_closures_are_folded() {
+    let t = Tensor::constant(vec![1.0, 2.0, 3.0], &[3]);
+    let counted = |calls: &Arc<AtomicUsize>, pure: bool| -> TensorPromise<f64> {
+        let calls = calls.clone();
+        let f = move |x: f64| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            x * 10.0
+        };
+
+        (&t + 1.0).apply_scalar_with(f, ApplyOptions { pure })
+    };
+
+    let impure_calls = Arc::new(AtomicUsize::new(0));
+    let impure = counted(&impure_calls, false).fold_constants();
+    assert_eq!(impure_calls.load(Ordering::Relaxed), 0);
+
+    assert_eq!(values(&impure.clone().materialize()), [20.0, 30.0, 40.0]);
+    assert_eq!(values(&impure.materialize()), [20.0, 30.0, 40.0]);
+    assert_eq!(impure_calls.load(Ordering::Relaxed), 6);
+
+    let pure_calls = Arc::new(AtomicUsize::new(0));
+    let pure = counted(&pure_calls, true).fold_constants();
+    assert_eq!(pure_calls.load(Ordering::Relaxed), 3);
+
+    assert_eq!(values(&pure.clone().materialize()), [20.0, 30.0, 40.0]);
+    assert_eq!(values(&pure.materialize()), [20.0, 30.0, 40.0]);
+    assert_eq!(pure_calls.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn scalar_ops_do_not_fuse_across_it() {
+    let t = arange(&[4, 5]);
+    let promise = (&t * 2.0).apply_scalar(custom) * 3.0;
+
+    let before = computed_op_count();
+    let result = promise.materialize();
+
+    assert_eq!(computed_op_count() - before, 3);
+    let expected: Vec<f64> = t.iter().map(|&x| custom(x * 2.0) * 3.0).collect();
+    assert_eq!(values(&result), expected);
+}
+
+#[test]
+fn graphs_with_closures_can_be_printed() {
+    let t = arange(&[2, 3]);
+    let promise = t.apply(chunked).apply_scalar(|x| x + 1.0);
+
+    let summary = promise.graph_summary();
+
+    assert!(
+        summary.contains("Apply(ApplyFn(apply::chunked)"),
+        "{}",
+        summary
+    );
+    assert!(summary.contains("{{closure}}"), "{}", summary);
+    assert!(
+        summary.contains("ApplyOptions { pure: false }"),
+        "{}",
+        summary
+    );
+}
+
+#[test]
+fn complex_elements_can_be_applied() {
+    use simple_tensor::tensor::complex::Complex;
+
+    let t = Tensor::from_vec(vec![Complex::new(1.0, 2.0), Complex::new(-3.0, 0.5)], &[2]);
+    let result = t.apply_scalar(|z| z * z).materialize();
+
+    let expected = [Complex::new(-3.0, 4.0), Complex::new(8.75, -3.0)];
+    assert!(result.iter().copied().eq(expected));
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Layout, Tensor};
+use simple_tensor::{matrix, s};
+
+use common::{arange, values};
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn classic_stride_tricks() {
+    let t = arange(&[6]);
+
+    // Overlapping windows of 3.
+    let windows = t.as_strided(&[4, 3], &[1, 1], 0).unwrap();
+    assert_eq!(windows.shape(), &[4, 3]);
+    assert_eq!(
+        values(&windows),
+        [0.0, 1.0, 2.0, 1.0, 2.0, 3.0, 2.0, 3.0, 4.0, 3.0, 4.0, 5.0]
+    );
+
+    // The same row repeated.
+    let rows = t.as_strided(&[3, 4], &[0, 1], 1).unwrap();
+    assert_eq!(values(&rows), [1.0, 2.0, 3.0, 4.0].repeat(3));
+
+    // Both axes of a [2, 3] matrix reversed.
+    let reversed = t.as_strided(&[2, 3], &[-3, -1], 5).unwrap();
+    assert_eq!(values(&reversed), [5.0, 4.0, 3.0, 2.0, 1.0, 0.0]);
+
+    // Every other element.
+    let every_other = t.as_strided(&[3], &[2], 1).unwrap();
+    assert_eq!(values(&every_other), [1.0, 3.0, 5.0]);
+
+    // Views share the buffer.
+    assert!(t.is_shared());
+}
+
+#[test]
+fn elements_outside_of_the_buffer_are_rejected() {
+    let t = arange(&[6]);
+
+    let err = t.as_strided(&[3], &[3], 0).err().unwrap();
+    assert!(
+        matches!(err, OpError::StridedOutOfBounds(0, 6, 6)),
+        "{:?}",
+        err
+    );
+    assert!(t.as_strided(&[2, 3], &[3, 1], 1).is_err());
+    assert!(t.as_strided(&[1], &[1], 6).is_err());
+
+    let err = t.as_strided(&[3], &[-1], 1).err().unwrap();
+    assert!(
+        matches!(err, OpError::StridedOutOfBounds(-1, 1, 6)),
+        "{:?}",
+        err
+    );
+    assert!(t.as_strided(&[2, 2], &[3, -2], 1).is_err());
+
+    // The last element and nothing at all are fine.
+    assert!(t.as_strided(&[2, 2], &[3, 2], 0).is_ok());
+    assert!(t.as_strided(&[0, 4], &[100, 100], 3).is_ok());
+
+    assert!(matches!(
+        t.as_strided(&[2, 2], &[1], 0),
+        Err(OpError::NotEnoughAxes(2, 1))
+    ));
+
+    // Checked against the buffer, not a layout.
+    assert!(Layout::as_strided(&[3], &[2], 0, 5).is_ok());
+    assert!(Layout::as_strided(&[3], &[2], 0, 4).is_err());
+}
+
+#[test]
+fn offsets_compose_with_the_ones_of_slices() {
+    let t = Tensor::from_iter((0..20).map(|i| i as f64), &[4, 5]);
+    let row = t.slice(s![2, ..]).unwrap().materialize();
+    assert_eq!(row.data().offset(), 10);
+
+    // The strides are in elements of the buffer, from the first element of the slice.
+    let pairs = row.as_strided(&[2, 2], &[5, 1], 1).unwrap();
+    assert_eq!(values(&pairs), [11.0, 12.0, 16.0, 17.0]);
+
+    // Back into the rows before it, but not before the buffer.
+    let above = row.as_strided(&[2], &[-5], 0).unwrap();
+    assert_eq!(values(&above), [10.0, 5.0]);
+    assert!(row.as_strided(&[4], &[-5], 0).is_err());
+    assert!(row.as_strided(&[2], &[5], 5).is_err());
+}
+
+#[test]
+fn aliased_views_need_the_aliased_writes() {
+    let base = arange(&[4]);
+    let mut rows = base.as_strided(&[3, 4], &[0, 1], 0).unwrap();
+    drop(base);
+    assert!(!rows.is_shared());
+
+    let err = rows
+        .assign_from_iter((0..12).map(|i| i as f64))
+        .err()
+        .unwrap();
+    assert!(matches!(err, OpError::AliasedElements), "{:?}", err);
+    assert!(rows.assign_slice_from_iter(s![0..2, ..], [0.0; 8]).is_err());
+    assert_eq!(values(&rows), [0.0, 1.0, 2.0, 3.0].repeat(3));
+
+    // The last row written is the one kept.
+    rows.assign_from_iter_aliased((0..12).map(|i| i as f64))
+        .unwrap();
+    assert_eq!(values(&rows), [8.0, 9.0, 10.0, 11.0].repeat(3));
+
+    // A single row of it does not alias.
+    rows.assign_slice_from_iter(s![1, ..], [1.0; 4]).unwrap();
+    assert_eq!(values(&rows), [1.0; 12]);
+
+    // Shared views are copied before the write, so they never alias.
+    let base = arange(&[4]);
+    let mut shared = base.as_strided(&[2, 4], &[0, 1], 0).unwrap();
+    shared.assign_from_iter((0..8).map(|i| i as f64)).unwrap();
+    assert_eq!(values(&shared), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+    assert_eq!(values(&base), [0.0, 1.0, 2.0, 3.0]);
+
+    // Strides that never alias are written as usual.
+    let base = arange(&[12]);
+    let mut strided = base.as_strided(&[3, 2], &[4, 2], 1).unwrap();
+    drop(base);
+    strided.assign_from_iter([0.0; 6]).unwrap();
+    assert_eq!(values(&strided), [0.0; 6]);
+}
+
+#[test]
+fn aliased_views_are_not_materialized_into() {
+    let base = arange(&[3]);
+    let mut rows = base.as_strided(&[2, 3], &[0, 1], 0).unwrap();
+    drop(base);
+
+    let result = matrix![[10, 20, 30], [40, 50, 60]] * 1.0;
+    let err = result.materialize_into(&mut rows).unwrap_err();
+    assert!(matches!(err, OpError::AliasedElements), "{:?}", err);
+    assert_eq!(values(&rows), [0.0, 1.0, 2.0].repeat(2));
+
+    // Shared views are copied first, so they never alias.
+    let base = arange(&[3]);
+    let mut shared = base.as_strided(&[2, 3], &[0, 1], 0).unwrap();
+    (matrix![[10, 20, 30], [40, 50, 60]] * 1.0)
+        .materialize_into(&mut shared)
+        .unwrap();
+    assert_eq!(values(&shared), [10.0, 20.0, 30.0, 40.0, 50.0, 60.0]);
+    assert_eq!(values(&base), [0.0, 1.0, 2.0]);
+}
+
+#[test]
+fn view_ops_match_their_stride_tricks() {
+    let t = Tensor::from_iter((0..12).map(|i| i as f64), &[3, 4]);
+
+    let flipped = t.flip_view(&[0, 1]).unwrap();
+    let tricked = t.as_strided(&[3, 4], &[-4, -1], 11).unwrap();
+    assert_eq!(values(&flipped), values(&tricked));
+    assert_eq!(
+        flipped.data().layout().stride(),
+        tricked.data().layout().stride()
+    );
+
+    for k in [-2, 0, 1, 3, 5] {
+        let diagonal = t.diagonal_offset(k).unwrap();
+        let len = diagonal.shape()[0];
+        let start = if k >= 0 {
+            k as usize
+        } else {
+            (-k) as usize * 4
+        };
+        let tricked = t
+            .as_strided(&[len], &[5], if len == 0 { 0 } else { start })
+            .unwrap();
+
+        assert_eq!(values(&diagonal), values(&tricked), "k = {}", k);
+    }
+
+    // Over an already flipped view.
+    let flipped = t.flip_view(&[1]).unwrap();
+    let diagonal = flipped.diagonal().unwrap();
+    let tricked = t.as_strided(&[3], &[3], 3).unwrap();
+    assert_eq!(values(&diagonal), values(&tricked));
+    assert_eq!(values(&diagonal), [3.0, 6.0, 9.0]);
+}
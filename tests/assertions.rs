@@ -0,0 +1,230 @@
+mod common;
+
+use simple_tensor::tensor::errors::{AssertionFailure, OpError};
+use simple_tensor::tensor::shape_check::ShapePattern;
+use simple_tensor::tensor::{
+    Dimension, Tensor, TensorPromise, graph_assertions, materialize_many, set_graph_assertions,
+};
+use simple_tensor::{matrix, vector};
+
+use common::{lock_globals, values};
+
+fn buffer_ptr(t: &Tensor<f64>) -> *const f64 {
+    t.with_raw(|ptr, _| ptr).unwrap()
+}
+
+fn failure(promise: TensorPromise<f64>) -> AssertionFailure {
+    match promise.try_materialize() {
+        Err(OpError::AssertionFailed(failure)) => *failure,
+        other => panic!("expected AssertionFailed, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn finite_elements_pass_through() {
+    let _globals = lock_globals();
+    let t = matrix![[1.0, -2.0], [0.0, 1e300]];
+
+    let result = t.assert_finite("weights").try_materialize().unwrap();
+
+    assert_eq!(result.shape(), [2, 2]);
+    assert_eq!(values(&result), [1.0, -2.0, 0.0, 1e300]);
+}
+
+#[test]
+fn the_first_element_that_is_not_finite_is_reported() {
+    let _globals = lock_globals();
+    let t = vector![1.0, 2.0, f64::NAN, f64::INFINITY];
+
+    let failure = failure((&t * 2.0).assert_finite("after_softmax"));
+
+    assert_eq!(failure.label(), "after_softmax");
+    assert_eq!(failure.condition(), "every element to be finite");
+    assert_eq!(failure.index(), Some(2));
+    assert_eq!(failure.value(), "NaN");
+}
+
+#[test]
+fn elements_within_the_range_pass_through() {
+    let _globals = lock_globals();
+    let t = vector![0.0, 0.25, 1.0];
+
+    let result = t.assert_in_range(0.0, 1.0, "probabilities").materialize();
+
+    assert_eq!(values(&result), [0.0, 0.25, 1.0]);
+}
+
+#[test]
+fn elements_out_of_the_range_and_nans_fail() {
+    let _globals = lock_globals();
+
+    let failure_above = failure(vector![0.5, 1.5, -1.0].assert_in_range(0.0, 1.0, "probabilities"));
+    assert_eq!(failure_above.label(), "probabilities");
+    assert_eq!(
+        failure_above.condition(),
+        "every element to be within [0.0, 1.0]"
+    );
+    assert_eq!(failure_above.index(), Some(1));
+    assert_eq!(failure_above.value(), "1.5");
+
+    let failure_nan = failure(vector![0.5, f64::NAN].assert_in_range(0.0, 1.0, "probabilities"));
+    assert_eq!(failure_nan.index(), Some(1));
+    assert_eq!(failure_nan.value(), "NaN");
+}
+
+#[test]
+fn matching_shapes_pass_and_others_fail() {
+    let _globals = lock_globals();
+    let t = Tensor::from_scalar(1.0, &[4, 64]);
+
+    let passing = ShapePattern::new().any().exact(64);
+    assert_eq!(
+        t.assert_shape(&passing, "hidden").materialize().shape(),
+        [4, 64]
+    );
+
+    let failing = ShapePattern::new().any().exact(128);
+    let failure = failure(t.assert_shape(&failing, "hidden"));
+
+    assert_eq!(failure.label(), "hidden");
+    assert_eq!(failure.condition(), "the shape to match [_, 128]");
+    assert_eq!(failure.index(), None);
+    assert_eq!(failure.value(), "[4, 64]");
+}
+
+#[test]
+fn failures_read_well_and_make_materialize_panic() {
+    let _globals = lock_globals();
+    let t = vector![1.0, f64::NEG_INFINITY];
+
+    let err = t.assert_finite("logits").try_materialize().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "assertion logits failed: expected every element to be finite, found -inf at index 1"
+    );
+
+    let err = t
+        .assert_shape(&ShapePattern::new().exact(3), "logits")
+        .try_materialize()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "assertion logits failed: expected the shape to match [3], found [2]"
+    );
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        t.assert_finite("logits").materialize()
+    }));
+    assert!(panic.is_err());
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        materialize_many(&[&t.assert_finite("logits")])
+    }));
+    assert!(panic.is_err());
+}
+
+#[test]
+fn a_failed_assertion_leaves_the_destination_untouched() {
+    let _globals = lock_globals();
+    let mut dest = vector![7.0, 7.0];
+
+    let result = (vector![1.0, f64::NAN] + 1.0)
+        .assert_finite("shifted")
+        .materialize_into(&mut dest);
+
+    assert!(matches!(result, Err(OpError::AssertionFailed(_))));
+    assert_eq!(values(&dest), [7.0, 7.0]);
+}
+
+#[test]
+fn passing_through_does_not_copy() {
+    let _globals = lock_globals();
+    let t = vector![1.0, 2.0, 3.0];
+
+    let result = t.assert_finite("input").materialize();
+    assert_eq!(buffer_ptr(&result), buffer_ptr(&t));
+
+    let result = t
+        .assert_finite("input")
+        .assert_in_range(0.0, 5.0, "input")
+        .materialize();
+    assert_eq!(buffer_ptr(&result), buffer_ptr(&t));
+}
+
+#[test]
+fn strided_inputs_are_checked_in_logical_order() {
+    let _globals = lock_globals();
+    // In memory the 5 comes before the 9, read transposed it comes after.
+    let t = matrix![[0.0, 9.0, 1.0], [5.0, 2.0, 3.0]];
+
+    let failure = failure(t.transpose().assert_in_range(0.0, 4.0, "transposed"));
+
+    assert_eq!(failure.index(), Some(1));
+    assert_eq!(failure.value(), "5.0");
+
+    let passing = t.transpose().assert_finite("transposed").materialize();
+    assert_eq!(passing.shape(), [3, 2]);
+    assert_eq!(values(&passing), [0.0, 5.0, 9.0, 2.0, 1.0, 3.0]);
+}
+
+#[test]
+fn the_first_assertion_to_fail_is_reported() {
+    let _globals = lock_globals();
+    let t = vector![0.5, 2.0];
+
+    let checked = t
+        .assert_finite("input")
+        .assert_in_range(0.0, 1.0, "before_scaling");
+    let scaled = (&checked * 4.0).assert_in_range(0.0, 1.0, "after_scaling");
+
+    assert_eq!(failure(scaled).label(), "before_scaling");
+
+    let scaled = (&t * 0.25)
+        .assert_finite("scaled")
+        .assert_in_range(0.0, 1.0, "scaled");
+    let sum = (&scaled + &(&scaled * 3.0)).assert_in_range(0.0, 2.0, "sum");
+
+    // The assertions of the shared node hold, so does the one of the sum.
+    assert_eq!(values(&sum.materialize()), [0.5, 2.0]);
+
+    let failure = failure((&scaled + 1.0).assert_in_range(0.0, 1.0, "shifted"));
+    assert_eq!(failure.label(), "shifted");
+    assert_eq!((failure.index(), failure.value()), (Some(0), "1.125"));
+}
+
+#[test]
+fn disabled_assertions_are_skipped() {
+    let _globals = lock_globals();
+    assert!(graph_assertions());
+
+    let t = vector![1.0, f64::NAN];
+    let built_enabled = (&t * 2.0).assert_finite("doubled");
+
+    set_graph_assertions(false);
+    let result = built_enabled.try_materialize();
+    let built_disabled = t.assert_finite("input").materialize();
+    set_graph_assertions(true);
+
+    assert_eq!(values(&result.unwrap())[0], 2.0);
+    assert_eq!(buffer_ptr(&built_disabled), buffer_ptr(&t));
+}
+
+#[test]
+fn disabled_assertions_do_not_prevent_fusion() {
+    let _globals = lock_globals();
+    let t = vector![1.0, 2.0];
+
+    let checked = (&t * 2.0).assert_finite("doubled") * 3.0;
+    assert!(checked.graph_summary().contains("Assert"));
+    assert_eq!(checked.depth(), 3);
+
+    set_graph_assertions(false);
+    let fused = (&t * 2.0).assert_finite("doubled") * 3.0;
+    set_graph_assertions(true);
+
+    // The assertion is read through, leaving a single scalar op over the tensor.
+    assert!(!fused.graph_summary().contains("Assert"));
+    assert!(fused.graph_summary().contains("ScalarOp(Mul(6.0))"));
+    assert_eq!(fused.depth(), 1);
+    assert_eq!(values(&fused.materialize()), values(&checked.materialize()));
+}
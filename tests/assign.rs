@@ -0,0 +1,76 @@
+mod common;
+
+use simple_tensor::tensor::Tensor;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::{matrix, s, srange};
+
+use common::values;
+
+#[test]
+fn fills_in_row_major_order() {
+    let mut t = Tensor::from_scalar(-1.0, &[2, 3]);
+    t.assign_from_iter((0..6).map(|i| i as f64)).unwrap();
+
+    assert_eq!(t.data(), srange!(6, &[2, 3]).data());
+}
+
+#[test]
+fn shorter_and_longer_iterators() {
+    let mut t = Tensor::from_scalar(-1.0, &[2, 3]);
+
+    t.assign_from_iter([1.0, 2.0, 3.0, 4.0]).unwrap();
+    assert_eq!(values(&t), [1.0, 2.0, 3.0, 4.0, -1.0, -1.0]);
+
+    // The extra elements are ignored.
+    t.assign_from_iter((10..100).map(|i| i as f64)).unwrap();
+    assert_eq!(values(&t), [10.0, 11.0, 12.0, 13.0, 14.0, 15.0]);
+
+    t.assign_from_iter([]).unwrap();
+    assert_eq!(values(&t), [10.0, 11.0, 12.0, 13.0, 14.0, 15.0]);
+}
+
+#[test]
+fn follows_the_logical_order_of_views() {
+    let t = srange!(6, &[2, 3]);
+    let mut flipped = t.flip_view(&[1]).unwrap();
+
+    // The buffer is shared with t, so it is copied first and t is left alone.
+    flipped.assign_from_iter([7.0, 8.0]).unwrap();
+    assert_eq!(values(&flipped), [7.0, 8.0, 0.0, 5.0, 4.0, 3.0]);
+    assert_eq!(values(&t), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+}
+
+#[test]
+fn writes_into_a_slice() {
+    let mut t = matrix![[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+
+    t.assign_slice_from_iter(s![1..3, 1..3], [-1.0, -2.0, -3.0, -4.0])
+        .unwrap();
+    assert_eq!(
+        values(&t),
+        [1.0, 2.0, 3.0, 4.0, -1.0, -2.0, 7.0, -3.0, -4.0]
+    );
+
+    // A short iterator only fills the start of the slice.
+    t.assign_slice_from_iter(s![.., 0..1], [0.0, 0.0]).unwrap();
+    assert_eq!(
+        values(&t),
+        [0.0, 2.0, 3.0, 0.0, -1.0, -2.0, 7.0, -3.0, -4.0]
+    );
+
+    assert!(matches!(
+        t.assign_slice_from_iter(s![0..4, ..], [0.0]),
+        Err(OpError::InvalidSliceShape(3, 4))
+    ));
+}
+
+#[test]
+fn promises_keep_the_old_elements() {
+    let mut t = matrix![[1, 2], [3, 4]];
+    let doubled = &t * 2.0;
+
+    t.assign_from_iter([0.0; 4]).unwrap();
+
+    assert_eq!(values(&doubled.materialize()), [2.0, 4.0, 6.0, 8.0]);
+    assert_eq!(values(&t), [0.0; 4]);
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, s};
+
+use common::values;
+
+fn names(names: Option<&[Box<str>]>) -> Vec<&str> {
+    names.unwrap().iter().map(|name| &**name).collect()
+}
+
+fn batch_channel() -> Tensor<f64> {
+    Tensor::from_vec((0..6).map(|i| i as f64).collect(), &[2, 3])
+        .with_axis_names(&["batch", "channel"])
+        .unwrap()
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn elementwise_keeps_names() {
+    let named = batch_channel();
+    let plain = Tensor::from_scalar(1.0, &[2, 3]);
+
+    assert_eq!(names(named.names()), ["batch", "channel"]);
+    assert_eq!(plain.names(), None);
+
+    // The lhs names win, the rhs ones are used if the lhs has none.
+    let sum = &named + &plain;
+    assert_eq!(names(sum.names()), ["batch", "channel"]);
+    let sum = &plain + &named;
+    assert_eq!(names(sum.names()), ["batch", "channel"]);
+
+    let scaled = (&named * 2.0 - 1.0).isnan();
+    assert_eq!(names(scaled.names()), ["batch", "channel"]);
+
+    // Materializing keeps them too.
+    let result = (&named + &plain).materialize();
+    assert_eq!(names(result.names()), ["batch", "channel"]);
+    assert_eq!(values(&result), [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+}
+
+#[test]
+fn reshaping_drops_names() {
+    let named = batch_channel();
+
+    assert_eq!(named.view(&[3, 2]).unwrap().names(), None);
+    assert_eq!(named.tile(&[2, 1, 1]).names(), None);
+
+    // Ops that keep every axis in place keep the names.
+    assert_eq!(
+        names(named.slice(s![.., 1..]).unwrap().names()),
+        ["batch", "channel"]
+    );
+    assert_eq!(names(named.tile(&[2, 2]).names()), ["batch", "channel"]);
+    assert_eq!(
+        names(named.flip_view(&[0]).unwrap().names()),
+        ["batch", "channel"]
+    );
+    assert_eq!(names(named.clone_detached().names()), ["batch", "channel"]);
+}
+
+#[test]
+fn transpose_and_permute_move_names() {
+    let named = Tensor::from_scalar(0.0, &[2, 3, 4])
+        .with_axis_names(&["batch", "channel", "width"])
+        .unwrap();
+
+    assert_eq!(
+        names(named.transpose().names()),
+        ["width", "channel", "batch"]
+    );
+
+    let permuted = named.permute_named(&["channel", "width", "batch"]).unwrap();
+    assert_eq!(permuted.shape(), &[3, 4, 2]);
+    assert_eq!(names(permuted.names()), ["channel", "width", "batch"]);
+    assert_eq!(
+        names(named.transpose_axes(&[2, 0, 1]).unwrap().names()),
+        ["width", "batch", "channel"]
+    );
+}
+
+#[test]
+fn reductions_remove_the_axis_name() {
+    let t = matrix![[0, 1, 0], [0, 0, 0]]
+        .with_axis_names(&["batch", "channel"])
+        .unwrap();
+
+    let any = t.any_named("channel").unwrap();
+    assert_eq!(names(any.names()), ["batch"]);
+    assert_eq!(values(&any.materialize()), [1.0, 0.0]);
+
+    let all = t.all_named("batch").unwrap();
+    assert_eq!(names(all.names()), ["channel"]);
+    assert_eq!(values(&all.materialize()), [0.0, 0.0, 0.0]);
+
+    // Reducing the only axis leaves nothing to name.
+    let row = Tensor::from_scalar(1.0, &[3])
+        .with_axis_names(&["channel"])
+        .unwrap();
+    assert_eq!(row.any_named("channel").unwrap().names(), None);
+
+    let normalized = t.normalize_named("channel", 1e-5).unwrap();
+    assert_eq!(names(normalized.names()), ["batch", "channel"]);
+}
+
+#[test]
+fn unknown_names_list_the_axes() {
+    let named = batch_channel();
+
+    let Err(err) = named.any_named("time") else {
+        panic!("time is not an axis");
+    };
+    assert!(matches!(err, OpError::UnknownAxisName(_, _)));
+    assert_eq!(
+        err.to_string(),
+        "there is no axis named time. the axes are [batch, channel]"
+    );
+    assert_eq!(named.axis_index("channel").unwrap(), 1);
+
+    assert!(matches!(
+        Tensor::from_scalar(0.0, &[2, 3]).permute_named(&["channel", "batch"]),
+        Err(OpError::UnknownAxisName(_, _))
+    ));
+}
+
+#[test]
+fn names_are_validated() {
+    let t = Tensor::from_scalar(0.0, &[2, 3]);
+
+    assert!(matches!(
+        t.with_axis_names(&["batch", "batch"]),
+        Err(OpError::RepeatedAxisName(_))
+    ));
+    assert!(matches!(
+        t.with_axis_names(&["batch"]),
+        Err(OpError::NotEnoughAxes(2, 1))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "expected [batch=2, channel=3], but got [batch=3, channel=2]")]
+fn shape_mismatch_shows_names() {
+    let a = batch_channel();
+    let b = Tensor::from_scalar(1.0, &[3, 2])
+        .with_axis_names(&["batch", "channel"])
+        .unwrap();
+
+    let _ = &a + &b;
+}
+
+#[test]
+fn display_and_debug_show_names() {
+    let t = matrix![[1, 2], [3, 4]]
+        .with_axis_names(&["row", "col"])
+        .unwrap();
+
+    assert!(format!("{}", t).starts_with("names: [row, col]\n"));
+    assert!(format!("{:?}", t).contains("\"row\", \"col\""));
+    assert!(!format!("{}", matrix![[1, 2], [3, 4]]).contains("names"));
+}
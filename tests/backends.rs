@@ -0,0 +1,148 @@
+mod common;
+
+use simple_tensor::tensor::complex::Complex;
+use simple_tensor::tensor::{BatchProduct, Tensor, TensorPromise};
+
+use common::values;
+
+// Checks that the backend this build runs on, mkl, cblas or pure, stays within what the
+// notes of backend/mod.rs allow from the scalar reference, so that any two backends agree
+// within twice that.
+
+fn random_values(len: usize, low: f64, high: f64, mut seed: u64) -> Vec<f64> {
+    (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            low + (seed >> 11) as f64 / (1u64 << 53) as f64 * (high - low)
+        })
+        .collect()
+}
+
+fn random_tensor(len: usize, low: f64, high: f64, seed: u64) -> Tensor<f64> {
+    Tensor::from_vec(random_values(len, low, high, seed), &[len])
+}
+
+// Number of doubles between `a` and `b`, 0 for equal ones.
+fn ulps(a: f64, b: f64) -> u64 {
+    // Maps the bits to integers in the order of the doubles, -0 and 0 included.
+    let ordered = |x: f64| {
+        let bits = x.to_bits() as i64;
+        if bits < 0 { i64::MIN - bits } else { bits }
+    };
+
+    ordered(a).abs_diff(ordered(b))
+}
+
+fn assert_within_ulps(name: &str, promise: TensorPromise<f64>, expected: &[f64], max: u64) {
+    let got = values(&promise.materialize());
+
+    for (&g, &e) in got.iter().zip(expected) {
+        assert!(ulps(g, e) <= max, "{name}: got {g:e} expected {e:e}");
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn binary_ops_are_within_one_ulp() {
+    let a = random_tensor(1000, -100.0, 100.0, 1);
+    let b = random_tensor(1000, 0.5, 50.0, 2);
+    let (va, vb) = (values(&a), values(&b));
+
+    let expected = |f: fn(f64, f64) -> f64| -> Vec<f64> {
+        va.iter().zip(&vb).map(|(&a, &b)| f(a, b)).collect()
+    };
+
+    assert_within_ulps("add", &a + &b, &expected(|a, b| a + b), 1);
+    assert_within_ulps("sub", &a - &b, &expected(|a, b| a - b), 1);
+    assert_within_ulps("mul", &a * &b, &expected(|a, b| a * b), 1);
+    assert_within_ulps("div", &a / &b, &expected(|a, b| a / b), 1);
+
+    let scaled: Vec<f64> = va.iter().map(|a| a * 0.3).collect();
+    assert_within_ulps("scal", &a * 0.3, &scaled, 1);
+}
+
+#[test]
+fn roots_and_reciprocals_are_within_one_ulp() {
+    let x = random_tensor(1000, 1e-3, 1e3, 3);
+    let vx = values(&x);
+
+    let expected = |f: fn(f64) -> f64| -> Vec<f64> { vx.iter().map(|&x| f(x)).collect() };
+
+    assert_within_ulps("sqrt", x.sqrt(), &expected(f64::sqrt), 1);
+    assert_within_ulps("recip", x.recip(), &expected(f64::recip), 1);
+    // The reference rounds twice, so it is itself up to 1 ulp away.
+    assert_within_ulps("rsqrt", x.rsqrt(), &expected(|x| 1.0 / x.sqrt()), 2);
+}
+
+#[test]
+fn exp_and_log_functions_are_within_one_ulp() {
+    let x = random_tensor(1000, -20.0, 20.0, 4);
+    let positive = random_tensor(1000, 1e-3, 1e3, 5);
+    let small = random_tensor(1000, -0.5, 0.5, 6);
+    let (vx, vp, vs) = (values(&x), values(&positive), values(&small));
+
+    let expected = |v: &[f64], f: fn(f64) -> f64| -> Vec<f64> { v.iter().map(|&x| f(x)).collect() };
+
+    assert_within_ulps("exp", x.exp(), &expected(&vx, f64::exp), 1);
+    assert_within_ulps("exp2", x.exp2(), &expected(&vx, f64::exp2), 1);
+    assert_within_ulps("expm1", small.expm1(), &expected(&vs, f64::exp_m1), 1);
+    assert_within_ulps("ln", positive.ln(), &expected(&vp, f64::ln), 1);
+    assert_within_ulps("log2", positive.log2(), &expected(&vp, f64::log2), 1);
+    assert_within_ulps("log10", positive.log10(), &expected(&vp, f64::log10), 1);
+    assert_within_ulps("log1p", small.log1p(), &expected(&vs, f64::ln_1p), 1);
+}
+
+#[test]
+fn complex_mul_and_div_are_within_a_few_ulps() {
+    let parts = |seed| random_values(200, -10.0, 10.0, seed);
+    let to_complex = |re: Vec<f64>, im: Vec<f64>| -> Vec<Complex<f64>> {
+        re.into_iter()
+            .zip(im)
+            .map(|(re, im)| Complex::new(re, im))
+            .collect()
+    };
+    let va = to_complex(parts(7), parts(8));
+    let vb = to_complex(parts(9), parts(10));
+
+    let a = Tensor::from_vec(va.clone(), &[200]);
+    let b = Tensor::from_vec(vb.clone(), &[200]);
+
+    let products = values(&(&a * &b).materialize());
+    let quotients = values(&(&a / &b).materialize());
+
+    for (i, (&a, &b)) in va.iter().zip(&vb).enumerate() {
+        for (got, expected) in [(products[i], a * b), (quotients[i], a / b)] {
+            let tolerance = 8.0 * f64::EPSILON * expected.abs();
+            assert!((got - expected).abs() <= tolerance, "{got} != {expected}");
+        }
+    }
+}
+
+#[test]
+fn gemm_is_within_the_rounding_of_its_dot_products() {
+    let (m, k, n) = (17, 33, 9);
+    let va = random_values(m * k, -1.0, 1.0, 11);
+    let vb = random_values(k * n, -1.0, 1.0, 12);
+
+    let a = Tensor::from_vec(va.clone(), &[m, k]);
+    let b = Tensor::from_vec(vb.clone(), &[k, n]);
+    let got = values(&a.bmm(&b).unwrap().materialize());
+
+    // Any order of the k additions is within k ulps of the sum of the magnitudes.
+    for (idx, &got) in got.iter().enumerate() {
+        let (i, j) = (idx / n, idx % n);
+        let products = (0..k).map(|p| va[i * k + p] * vb[p * n + j]);
+
+        let expected: f64 = products.clone().sum();
+        let magnitude: f64 = products.map(f64::abs).sum();
+
+        let tolerance = k as f64 * f64::EPSILON * magnitude;
+        assert!(
+            (got - expected).abs() <= tolerance,
+            "[{i}, {j}]: got {got:e} expected {expected:e}"
+        );
+    }
+}
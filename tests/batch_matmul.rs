@@ -0,0 +1,260 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout as AllocLayout, System};
+use std::cell::Cell;
+
+use simple_tensor::ss;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{
+    BatchProduct, Dimension, Tensor, set_strided_batch_gemm, strided_batch_gemm,
+};
+
+use common::{lock_globals, values};
+
+// Counts the bytes allocated by the current thread, as the tests run in parallel.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATED.with(|count| count.set(count.get() + layout.size()));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bytes_allocated_in<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+// Small integers, so every backend computes the products exactly.
+fn arange(shape: &[usize], shift: usize) -> Tensor<f64> {
+    let len = shape.iter().product();
+
+    Tensor::from_iter((0..len).map(|i| ((i * 7 + shift) % 11) as f64 - 5.0), shape)
+}
+
+// The matrices of a 2-D or 3-D tensor, in logical order.
+fn matrices(t: &Tensor<f64>) -> Vec<Vec<f64>> {
+    let shape = t.shape();
+    let len = shape[shape.len() - 2] * shape[shape.len() - 1];
+
+    values(t).chunks(len).map(<[f64]>::to_vec).collect()
+}
+
+// Multiplies every matrix of `lhs` by the one of `rhs` at the same index of the batch,
+// a single matrix on either side being used for the whole batch.
+fn naive_bmm(lhs: &Tensor<f64>, rhs: &Tensor<f64>) -> Vec<f64> {
+    let (lhs_shape, rhs_shape) = (lhs.shape(), rhs.shape());
+    let (m, k) = (
+        lhs_shape[lhs_shape.len() - 2],
+        lhs_shape[lhs_shape.len() - 1],
+    );
+    let n = rhs_shape[rhs_shape.len() - 1];
+    let (a, b) = (matrices(lhs), matrices(rhs));
+
+    let mut out = Vec::new();
+    for i in 0..a.len().max(b.len()) {
+        let (a, b) = (&a[i.min(a.len() - 1)], &b[i.min(b.len() - 1)]);
+
+        for row in 0..m {
+            for col in 0..n {
+                out.push((0..k).map(|p| a[row * k + p] * b[p * n + col]).sum());
+            }
+        }
+    }
+
+    out
+}
+
+fn check_bmm(lhs: &Tensor<f64>, rhs: &Tensor<f64>, shape: &[usize]) {
+    let result = lhs.bmm(rhs).unwrap().materialize();
+
+    assert_eq!(result.shape(), shape);
+    assert_eq!(values(&result), naive_bmm(lhs, rhs));
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn batches_match_a_product_per_matrix() {
+    for batch in [1, 2, 5] {
+        let lhs = arange(&[batch, 3, 4], 0);
+        let rhs = arange(&[batch, 4, 6], 3);
+
+        check_bmm(&lhs, &rhs, &[batch, 3, 6]);
+    }
+
+    // Single matrices, and vectors as matrices.
+    check_bmm(&arange(&[3, 4], 0), &arange(&[4, 2], 1), &[3, 2]);
+    check_bmm(&arange(&[4, 1, 7], 0), &arange(&[4, 7, 1], 1), &[4, 1, 1]);
+}
+
+#[test]
+fn single_matrices_are_broadcast_over_the_batch() {
+    check_bmm(&arange(&[3, 4], 0), &arange(&[5, 4, 2], 1), &[5, 3, 2]);
+    check_bmm(&arange(&[5, 3, 4], 0), &arange(&[4, 2], 1), &[5, 3, 2]);
+    check_bmm(&arange(&[1, 3, 4], 0), &arange(&[5, 4, 2], 1), &[5, 3, 2]);
+    check_bmm(&arange(&[5, 3, 4], 0), &arange(&[1, 4, 2], 1), &[5, 3, 2]);
+}
+
+#[test]
+fn promises_and_cached_promises_can_be_multiplied() {
+    let (lhs, rhs) = (arange(&[2, 3, 4], 0), arange(&[2, 4, 5], 2));
+    let expected = naive_bmm(&(&lhs * 2.0).materialize(), &rhs);
+
+    let promise = (&lhs * 2.0).bmm(&rhs).unwrap();
+    assert_eq!(values(&promise.materialize()), expected);
+
+    let cached = (&lhs * 2.0).cache();
+    let result = cached.bmm(&rhs).unwrap();
+    assert_eq!(values(&result.materialize()), expected);
+}
+
+#[test]
+fn transposed_matrices_are_not_compacted() {
+    let _globals = lock_globals();
+    let (batch, m, k, n) = (4, 24, 32, 16);
+
+    // Stored as [batch, k, m] and [batch, n, k], read as [batch, m, k] and [batch, k, n].
+    let lhs = arange(&[batch, k, m], 0);
+    let rhs = arange(&[batch, n, k], 5);
+    let lhs_t = lhs.transpose_axes(&[0, 2, 1]).unwrap().materialize();
+    let rhs_t = rhs.transpose_axes(&[0, 2, 1]).unwrap().materialize();
+    assert!(!lhs_t.data().is_contiguous() && !rhs_t.data().is_contiguous());
+
+    let expected = naive_bmm(&lhs_t, &rhs_t);
+    let out_bytes = batch * m * n * size_of::<f64>();
+    // Compacting either operand would allocate at least this much more.
+    let smallest_operand_bytes = batch * k * m.min(n) * size_of::<f64>();
+
+    for strided in [true, false] {
+        set_strided_batch_gemm(strided);
+        let promise = lhs_t.bmm(&rhs_t).unwrap();
+        let (result, allocated) = bytes_allocated_in(|| promise.materialize());
+        set_strided_batch_gemm(true);
+
+        assert_eq!(values(&result), expected);
+        assert!(
+            allocated < out_bytes + smallest_operand_bytes / 2,
+            "allocated {} bytes for an output of {} (strided: {})",
+            allocated,
+            out_bytes,
+            strided
+        );
+    }
+
+    // Only one side transposed.
+    check_bmm(&lhs_t, &arange(&[batch, k, n], 1), &[batch, m, n]);
+    check_bmm(&arange(&[batch, m, k], 1), &rhs_t, &[batch, m, n]);
+}
+
+#[test]
+fn non_contiguous_batches_fall_back_to_a_product_per_matrix() {
+    let _globals = lock_globals();
+    let lhs = arange(&[6, 3, 4], 0);
+    let rhs = arange(&[6, 4, 5], 4);
+
+    let every_other = lhs.step_slice(ss![0..6;2, .., ..]).unwrap().materialize();
+    let flipped = rhs.flip_view(&[0]).unwrap();
+    let rhs_half = rhs.step_slice(ss![1..6;2, .., ..]).unwrap().materialize();
+    assert!(!every_other.data().is_contiguous() && !flipped.data().is_contiguous());
+
+    let cases = [
+        (every_other.clone(), rhs_half.clone(), [3, 3, 5]),
+        (lhs.clone(), flipped.clone(), [6, 3, 5]),
+        (every_other.clone(), arange(&[4, 5], 1), [3, 3, 5]),
+    ];
+
+    for (lhs, rhs, shape) in &cases {
+        check_bmm(lhs, rhs, shape);
+
+        set_strided_batch_gemm(false);
+        let looped = lhs.bmm(rhs).unwrap().materialize();
+        set_strided_batch_gemm(true);
+
+        assert_eq!(values(&looped), naive_bmm(lhs, rhs));
+    }
+
+    // Neither axis of the matrices contiguous, which is compacted first.
+    let strided_rows = arange(&[2, 6, 8], 0)
+        .step_slice(ss![.., 0..6;2, 0..8;2])
+        .unwrap()
+        .materialize();
+    check_bmm(&strided_rows, &arange(&[2, 4, 3], 1), &[2, 3, 3]);
+
+    assert!(strided_batch_gemm());
+}
+
+#[test]
+fn empty_matrices_give_zeros() {
+    let result = arange(&[2, 3, 0], 0)
+        .bmm(&arange(&[2, 0, 4], 0))
+        .unwrap()
+        .materialize();
+
+    assert_eq!(result.shape(), &[2, 3, 4]);
+    assert!(result.iter().all(|&x| x == 0.0));
+
+    let result = arange(&[0, 3, 2], 0)
+        .bmm(&arange(&[2, 4], 0))
+        .unwrap()
+        .materialize();
+    assert_eq!(result.shape(), &[0, 3, 4]);
+}
+
+#[test]
+fn mismatched_shapes_are_errors() {
+    let err = arange(&[4, 3, 2], 0)
+        .bmm(&arange(&[3, 2, 5], 0))
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err.cause(), OpError::NotSameBatch(4, 3)),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.context().unwrap().input(), 1);
+    let message = err.to_string();
+    assert!(message.contains("BatchMatMul"), "{}", message);
+    assert!(message.contains("expected 4 found 3"), "{}", message);
+
+    let err = arange(&[2, 3, 4], 0)
+        .bmm(&arange(&[2, 5, 6], 0))
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err.cause(), OpError::CannotMatmul(4, 5)),
+        "{:?}",
+        err
+    );
+
+    let err = arange(&[4], 0).bmm(&arange(&[4, 2], 0)).err().unwrap();
+    assert!(
+        matches!(err.cause(), OpError::NotEnoughAxes(2, 1)),
+        "{:?}",
+        err
+    );
+
+    let err = arange(&[1, 2, 3, 4], 0)
+        .bmm(&arange(&[4, 2], 0))
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err.cause(), OpError::NotEnoughAxes(3, 4)),
+        "{:?}",
+        err
+    );
+}
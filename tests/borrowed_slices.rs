@@ -0,0 +1,152 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout as AllocLayout, System};
+use std::cell::Cell;
+
+use simple_tensor::tensor::{Dimension, InnerProduct, OuterProduct};
+use simple_tensor::{s, ss};
+
+use common::{arange, values};
+
+// Counts the bytes allocated by the current thread, as the tests run in parallel.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATED.with(|count| count.set(count.get() + layout.size()));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bytes_allocated_in<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn contiguous_tensors_are_borrowed_in_place() {
+    let t = arange(&[2, 3]);
+
+    let slice = t.try_as_slice().unwrap();
+    assert_eq!(slice, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(t.data().try_as_slice().unwrap().as_ptr(), slice.as_ptr());
+
+    assert_eq!(t.with_slice(|slice| slice.iter().sum::<f64>()), Some(15.0));
+    assert_eq!(&*t.to_boxed_slice(), slice);
+
+    // A view of the whole buffer, reshaped, is still contiguous from its start.
+    let reshaped = t.view(&[3, 2]).unwrap().materialize();
+    assert_eq!(reshaped.try_as_slice(), Some(slice));
+}
+
+#[test]
+fn strided_or_offset_layouts_are_not_borrowed() {
+    let t = arange(&[4, 6]);
+
+    let views = [
+        t.transpose().materialize(),
+        t.step_slice(ss![.., 0..6;2]).unwrap().materialize(),
+        t.flip_view(&[1]).unwrap(),
+        // Contiguous, but not from the start of the buffer.
+        t.slice(s![1..3, ..]).unwrap().materialize(),
+    ];
+
+    for view in &views {
+        assert!(view.try_as_slice().is_none(), "{:?}", view.stride());
+        assert!(view.data().try_as_slice().is_none());
+        assert!(view.with_slice(|_| unreachable!()).is_none());
+
+        // The copy gathers the elements in row-major order.
+        assert_eq!(&*view.to_boxed_slice(), values(view));
+    }
+
+    let transposed = t.transpose().materialize();
+    assert_eq!(
+        transposed.to_boxed_slice()[..6],
+        [0.0, 6.0, 12.0, 18.0, 1.0, 7.0]
+    );
+}
+
+#[test]
+fn writes_while_borrowed_copy_the_buffer() {
+    let t = arange(&[4]);
+    let mut other = t.clone_detached();
+
+    // `other` shares the buffer borrowed here, so writing to it copies it first.
+    t.with_slice(|slice| {
+        other
+            .with_raw_mut(|ptr, len| unsafe { std::slice::from_raw_parts_mut(ptr, len) }.fill(9.0))
+            .unwrap();
+
+        assert_eq!(slice, [0.0, 1.0, 2.0, 3.0]);
+    })
+    .unwrap();
+
+    assert_eq!(values(&other), [9.0; 4]);
+    assert_eq!(values(&t), [0.0, 1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn slices_of_several_tensors_can_be_borrowed_at_once() {
+    let (a, b) = (arange(&[3]), arange(&[3]) * 2.0);
+    let b = b.materialize();
+
+    // Nested both ways, and over the same tensor.
+    let dot = a.with_slice(|x| b.with_slice(|y| x.iter().zip(y).map(|(x, y)| x * y).sum::<f64>()));
+    assert_eq!(dot, Some(Some(10.0)));
+
+    let again = b.with_slice(|y| a.with_slice(|x| y.len() + x.len()));
+    assert_eq!(again, Some(Some(6)));
+    assert_eq!(a.with_slice(|x| a.with_slice(|y| x == y)), Some(Some(true)));
+}
+
+#[test]
+fn ops_read_contiguous_inputs_without_copying_them() {
+    // 256 KiB for the lhs, 8 KiB for the result.
+    let lhs = arange(&[512, 64]);
+    let rhs = arange(&[2, 64]);
+    let lhs_bytes = 512 * 64 * size_of::<f64>();
+
+    let (contiguous, bytes) = bytes_allocated_in(|| lhs.inner(&rhs).unwrap().materialize());
+    assert!(bytes < lhs_bytes / 4, "{} bytes", bytes);
+
+    // A transposed input is still packed first.
+    let transposed = arange(&[64, 512]).transpose().materialize();
+    let (_, bytes) = bytes_allocated_in(|| transposed.inner(&rhs).unwrap().materialize());
+    assert!(bytes >= lhs_bytes, "{} bytes", bytes);
+
+    let (l, r) = (values(&lhs), values(&rhs));
+    let expected: Vec<f64> = (l.chunks(64))
+        .flat_map(|row| {
+            r.chunks(64)
+                .map(move |col| row.iter().zip(col).map(|(a, b)| a * b).sum())
+        })
+        .collect();
+    assert_eq!(values(&contiguous), expected);
+
+    // The rhs of an outer product and the source of a tile.
+    let (x, y) = (arange(&[3]), arange(&[4]));
+    let outer = x.outer(&y).unwrap().materialize();
+    assert_eq!(values(&outer)[4..8], [0.0, 1.0, 2.0, 3.0]);
+
+    let tiled = arange(&[2, 2]).tile(&[1, 2]).materialize();
+    assert_eq!(values(&tiled), [0.0, 1.0, 0.0, 1.0, 2.0, 3.0, 2.0, 3.0]);
+    let flipped = arange(&[2, 2]).flip_view(&[1]).unwrap();
+    let tiled = flipped.tile(&[2, 1]).materialize();
+    assert_eq!(values(&tiled), [1.0, 0.0, 3.0, 2.0, 1.0, 0.0, 3.0, 2.0]);
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::{CachePool, CachedTensorPromise, Tensor, materialize_many};
+
+use common::values;
+
+// A cache of `len` elements, (i + seed) * 2 for every i.
+fn cached(pool: &CachePool, len: usize, seed: f64) -> CachedTensorPromise<f64> {
+    let t = Tensor::from_iter((0..len).map(|i| i as f64), &[len]);
+    ((&t + seed) * 2.0).cache_in(pool)
+}
+
+fn expected(len: usize, seed: f64) -> Vec<f64> {
+    (0..len).map(|i| (i as f64 + seed) * 2.0).collect()
+}
+
+const A: usize = 100;
+const B: usize = 200;
+const C: usize = 300;
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn evicts_the_least_recently_used() {
+    // Room for B and C, but not for the three of them.
+    let pool = CachePool::new((B + C) * 8);
+    let (a, b, c) = (
+        cached(&pool, A, 0.0),
+        cached(&pool, B, 1.0),
+        cached(&pool, C, 2.0),
+    );
+
+    a.clone().materialize();
+    b.clone().materialize();
+    assert_eq!(pool.used_bytes(), (A + B) * 8);
+
+    c.clone().materialize();
+    assert!(!a.is_cached());
+    assert!(b.is_cached() && c.is_cached());
+    assert_eq!(pool.used_bytes(), (B + C) * 8);
+
+    // Filling A again goes over the budget, B is the oldest.
+    a.clone().materialize();
+    assert!(a.is_cached() && !b.is_cached() && c.is_cached());
+    assert_eq!(pool.used_bytes(), (A + C) * 8);
+}
+
+#[test]
+fn recently_read_entries_survive() {
+    let pool = CachePool::new((B + C) * 8);
+    let (a, b, c) = (
+        cached(&pool, A, 0.0),
+        cached(&pool, B, 1.0),
+        cached(&pool, C, 2.0),
+    );
+
+    a.clone().materialize();
+    b.clone().materialize();
+
+    // Reading A, even through another promise, makes B the least recently used.
+    (&a * 3.0).materialize();
+    c.clone().materialize();
+
+    assert!(a.is_cached() && !b.is_cached() && c.is_cached());
+    assert_eq!(pool.used_bytes(), (A + C) * 8);
+}
+
+#[test]
+fn evicted_caches_are_computed_again() {
+    let pool = CachePool::new(A * 8);
+    let (a, b) = (cached(&pool, A, 0.0), cached(&pool, A, 5.0));
+
+    assert_eq!(values(&a.clone().materialize()), expected(A, 0.0));
+    assert_eq!(values(&b.clone().materialize()), expected(A, 5.0));
+    assert!(!a.is_cached());
+
+    let before = computed_op_count();
+    assert_eq!(values(&(&a - 1.0).materialize()), expected(A, -0.5));
+    assert!(computed_op_count() - before > 1);
+    assert!(a.is_cached() && !b.is_cached());
+
+    // Both in a single pass, each one evicting the other.
+    let [a_many, b_many] = materialize_many(&[&(&a * 1.0), &(&b * 1.0)])
+        .try_into()
+        .unwrap();
+    assert_eq!(values(&a_many), expected(A, 0.0));
+    assert_eq!(values(&b_many), expected(A, 5.0));
+    assert_eq!(pool.used_bytes(), A * 8);
+}
+
+#[test]
+fn hits_and_misses() {
+    let pool = CachePool::new(1 << 20);
+    let (a, b) = (cached(&pool, A, 0.0), cached(&pool, B, 1.0));
+    assert_eq!(pool.hit_rate(), 0.0);
+
+    a.clone().materialize();
+    b.clone().materialize();
+    assert_eq!((pool.hits(), pool.misses()), (0, 2));
+
+    a.clone().materialize();
+    (&a + &a).materialize();
+    assert_eq!((pool.hits(), pool.misses()), (2, 2));
+    assert_eq!(pool.hit_rate(), 0.5);
+
+    pool.clear();
+    assert_eq!(pool.used_bytes(), 0);
+    assert!(!a.is_cached() && !b.is_cached());
+
+    assert_eq!(values(&b.clone().materialize()), expected(B, 1.0));
+    assert_eq!((pool.hits(), pool.misses()), (2, 3));
+    assert_eq!(pool.used_bytes(), B * 8);
+}
+
+#[test]
+fn zero_budget_disables_caching() {
+    let pool = CachePool::new(0);
+    let a = cached(&pool, A, 3.0);
+
+    for _ in 0..3 {
+        assert_eq!(values(&a.clone().materialize()), expected(A, 3.0));
+        assert!(!a.is_cached());
+    }
+
+    assert_eq!(pool.used_bytes(), 0);
+    assert_eq!((pool.hits(), pool.misses()), (0, 3));
+}
+
+#[test]
+fn dropped_caches_free_their_bytes() {
+    let pool = CachePool::new(1 << 20);
+    let a = cached(&pool, A, 0.0);
+    let b = cached(&pool, B, 0.0);
+
+    a.clone().materialize();
+    b.clone().materialize();
+    drop(a);
+
+    assert_eq!(pool.used_bytes(), B * 8);
+}
+
+#[test]
+fn concurrent_materializations() {
+    // Room for about half of the caches, so the threads keep evicting each other's.
+    let pool = CachePool::new(4 * B * 8);
+    let caches: Vec<(CachedTensorPromise<f64>, f64)> = (0..8)
+        .map(|seed| (cached(&pool, B, seed as f64), seed as f64))
+        .collect();
+
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            let caches = &caches;
+
+            scope.spawn(move || {
+                for round in 0..50 {
+                    let (cache, seed) = &caches[(thread * 3 + round) % caches.len()];
+                    let doubled = (cache * 2.0).materialize();
+
+                    let expected: Vec<f64> = expected(B, *seed).iter().map(|x| x * 2.0).collect();
+                    assert_eq!(values(&doubled), expected);
+                }
+            });
+        }
+    });
+
+    assert!(pool.used_bytes() <= pool.budget());
+    assert_eq!(pool.hits() + pool.misses(), 200);
+}
@@ -0,0 +1,225 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout as AllocLayout, System};
+use std::cell::Cell;
+
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::{
+    Dimension, Layout, Tensor, TensorData, TensorPromise, cast_fusion, set_cast_fusion,
+};
+
+use common::{lock_globals, values};
+
+// Counts the bytes allocated by the current thread, as the tests run in parallel.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATED.with(|count| count.set(count.get() + layout.size()));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bytes_allocated_in<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+// Number of ops computed by `f`.
+fn count_ops<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = computed_op_count();
+    let result = f();
+
+    (result, computed_op_count() - before)
+}
+
+// Materializes the promise built by `f` with the casts unfused.
+fn unfused(f: impl FnOnce() -> TensorPromise<f64>) -> Tensor<f64> {
+    set_cast_fusion(false);
+    let result = f().materialize();
+    set_cast_fusion(true);
+
+    result
+}
+
+// Applies an op to the cast and `w`, the cast being the lhs if asked.
+type BinaryOp = fn(TensorPromise<f64>, &Tensor<f64>, bool) -> TensorPromise<f64>;
+
+// Every binary op with the cast of `x` on either side, fused and unfused.
+fn check_binary_ops<U>(x: &Tensor<U>, w: &Tensor<f64>)
+where
+    U: Copy + Send + Sync + 'static,
+    f64: From<U>,
+{
+    let ops: [(&str, BinaryOp); 4] = [
+        ("add", |c, w, lhs| if lhs { c + w } else { w + c }),
+        ("sub", |c, w, lhs| if lhs { c - w } else { w - c }),
+        ("mul", |c, w, lhs| if lhs { c * w } else { w * c }),
+        ("div", |c, w, lhs| if lhs { c / w } else { w / c }),
+    ];
+
+    for (name, op) in ops {
+        for lhs in [true, false] {
+            let expected = unfused(|| op(x.cast::<f64>(), w, lhs));
+            let (fused, computed) = count_ops(|| op(x.cast::<f64>(), w, lhs).materialize());
+
+            assert_eq!(
+                values(&fused),
+                values(&expected),
+                "{} (cast lhs: {})",
+                name,
+                lhs
+            );
+            assert_eq!(fused.shape(), w.shape());
+            assert_eq!(computed, 1, "{} (cast lhs: {})", name, lhs);
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn fused_casts_match_the_unfused_ones() {
+    let _globals = lock_globals();
+    assert!(cast_fusion());
+
+    let w = Tensor::from_vec(vec![0.5, -3.0, 7.25, 1e10, -1e-3, 2.0], &[2, 3]);
+
+    let x = Tensor::from_vec(vec![1.1f32, -2.7, 3.0, f32::MAX, 1e-30, -0.0], &[2, 3]);
+    check_binary_ops(&x, &w);
+
+    let x = Tensor::from_vec(vec![1i32, -2, 3, i32::MAX, i32::MIN, 0], &[2, 3]);
+    check_binary_ops(&x, &w);
+
+    // Exact conversions, so the same values as converting by hand.
+    let y = (x.cast::<f64>() * &w).materialize();
+    let by_hand: Vec<f64> = (x.iter().zip(w.iter()))
+        .map(|(&x, &w)| f64::from(x) * w)
+        .collect();
+    assert_eq!(values(&y), by_hand);
+}
+
+#[test]
+fn strided_sources_are_read_in_logical_order() {
+    let _globals = lock_globals();
+
+    // A 3x4 transpose, and the last three columns of a 4x4 matrix, starting at an offset.
+    let transposed = TensorData::from_vec((0..12).map(|i| i as f32).collect(), &[4, 3], 0)
+        .as_layout(Layout::from_slice(&[3, 4], &[1, 3], 0));
+    let columns = TensorData::from_vec((0..16).map(|i| i as f32 * 0.5).collect(), &[4, 4], 0)
+        .as_layout(Layout::from_slice(&[4, 3], &[4, 1], 1));
+
+    let w = Tensor::from_vec((1..=12).map(|i| i as f64).collect(), &[3, 4]);
+    check_binary_ops(&Tensor::from_data(transposed), &w);
+
+    let w = Tensor::from_vec((1..=12).map(|i| -i as f64).collect(), &[4, 3]);
+    check_binary_ops(&Tensor::from_data(columns), &w);
+
+    // The other operand may be strided as well.
+    let x = Tensor::from_vec(vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], &[3, 2]);
+    let w = Tensor::from_vec(vec![10.0, 20.0, 30.0, 40.0, 50.0, 60.0], &[2, 3]);
+    let y = (&w.transpose() - x.cast::<f64>()).materialize();
+    assert_eq!(values(&y), [9.0, 38.0, 17.0, 46.0, 25.0, 54.0]);
+}
+
+#[test]
+fn fusion_allocates_no_converted_copy() {
+    let _globals = lock_globals();
+
+    let len = 1 << 16;
+    let x = Tensor::from_vec((0..len).map(|i| i as f32).collect(), &[len]);
+    let w = Tensor::from_vec((0..len).map(|i| i as f64 * 0.25).collect(), &[len]);
+    let buffer = len * size_of::<f64>();
+
+    let (fused, fused_bytes) = bytes_allocated_in(|| (&w - x.cast::<f64>()).materialize());
+
+    set_cast_fusion(false);
+    let (expected, unfused_bytes) = bytes_allocated_in(|| (&w - x.cast::<f64>()).materialize());
+    set_cast_fusion(true);
+
+    assert_eq!(values(&fused), values(&expected));
+
+    // The result only, next to the converted copy of x and the result.
+    assert!(
+        fused_bytes < buffer * 3 / 2,
+        "{} bytes allocated",
+        fused_bytes
+    );
+    assert!(
+        unfused_bytes >= buffer * 2,
+        "{} bytes allocated",
+        unfused_bytes
+    );
+}
+
+#[test]
+fn casts_read_twice_are_computed_once() {
+    let _globals = lock_globals();
+
+    let x = Tensor::from_vec(vec![1.0f32, 2.0, 3.0, 4.0], &[2, 2]);
+    let w = Tensor::from_vec(vec![0.5, 0.5, 2.0, 2.0], &[2, 2]);
+
+    let c = x.cast::<f64>();
+    let (y, computed) = count_ops(|| ((&c + &w) * (&c - &w)).materialize());
+
+    // The cast, both ops reading it and their product.
+    assert_eq!(computed, 4);
+    assert_eq!(values(&y), [0.75, 3.75, 5.0, 12.0]);
+
+    // Only one of two casts read by the same op is fused.
+    let i = Tensor::from_vec(vec![2i32, -1, 0, 3], &[2, 2]);
+    let (y, computed) = count_ops(|| (x.cast::<f64>() / i.cast::<f64>()).materialize());
+    assert_eq!(computed, 2);
+    assert_eq!(values(&y), [0.5, -2.0, f64::INFINITY, 4.0 / 3.0]);
+
+    // The cast of a promise materialized alone is computed like any op.
+    let (y, computed) = count_ops(|| x.cast::<f64>().materialize());
+    assert_eq!(computed, 1);
+    assert_eq!(values(&y), [1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn casts_see_the_elements_at_the_time_of_the_cast() {
+    let _globals = lock_globals();
+
+    let mut x = Tensor::from_vec(vec![1i32, 2, 3], &[3]);
+    let w = Tensor::from_vec(vec![1.0, 1.0, 1.0], &[3]);
+
+    let y = x.cast::<f64>() + &w;
+    x.assign_from_iter([10, 20, 30]).unwrap();
+
+    assert_eq!(values(&y.materialize()), [2.0, 3.0, 4.0]);
+    assert_eq!(
+        values(&(x.cast::<f64>() + &w).materialize()),
+        [11.0, 21.0, 31.0]
+    );
+}
+
+#[test]
+fn non_commutative_ops_keep_their_operands_in_order() {
+    let a = Tensor::from_vec(vec![1.0, 2.0, 3.0], &[3]);
+    let b = Tensor::from_vec(vec![4.0, 8.0, 16.0], &[3]);
+
+    // Only the rhs is a computed result, which used to be written over in place of the lhs.
+    assert_eq!(
+        values(&(&a - &(&b * 2.0)).materialize()),
+        [-7.0, -14.0, -29.0]
+    );
+    assert_eq!(
+        values(&(&a / &(&b * 2.0)).materialize()),
+        [0.125, 0.125, 3.0 / 32.0]
+    );
+}
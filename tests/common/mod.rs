@@ -0,0 +1,38 @@
+// Helpers shared by the integration tests. Every file under tests/ is its own crate
+// and uses a few of them, so the others are dead code there.
+#![allow(dead_code)]
+
+use std::sync::{Mutex, MutexGuard};
+
+use simple_tensor::tensor::Tensor;
+
+pub fn values<T: Copy>(t: &Tensor<T>) -> Vec<T> {
+    t.iter().copied().collect()
+}
+
+// Bit patterns, to compare results exactly, NaNs and signed zeros included.
+pub fn bits(t: &Tensor<f64>) -> Vec<u64> {
+    t.iter().map(|el| el.to_bits()).collect()
+}
+
+// 0, 1, 2, ... in row-major order.
+pub fn arange(shape: &[usize]) -> Tensor<f64> {
+    arange_from(shape, 0.0)
+}
+
+// start, start + 1, start + 2, ... in row-major order.
+pub fn arange_from(shape: &[usize], start: f64) -> Tensor<f64> {
+    let len = shape.iter().product();
+
+    Tensor::from_iter((0..len).map(|i| i as f64 + start), shape)
+}
+
+// Serializes the tests of a file that set global toggles or read global counters,
+// as the tests of a file run in parallel.
+static GLOBALS: Mutex<()> = Mutex::new(());
+
+pub fn lock_globals() -> MutexGuard<'static, ()> {
+    GLOBALS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,206 @@
+mod common;
+
+use std::thread;
+
+use simple_tensor::ss;
+use simple_tensor::tensor::errors::{CallError, OpError};
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::{Dimension, Tensor, TensorPromise};
+
+use common::{arange_from, values};
+
+fn fill(t: &mut Tensor<f64>, value: f64) {
+    t.with_raw_mut(|ptr, len| unsafe { std::slice::from_raw_parts_mut(ptr, len) }.fill(value))
+        .unwrap();
+}
+
+// The graph compiled in the tests, over two inputs and a weight that is not one.
+fn graph(x: &Tensor<f64>, y: &Tensor<f64>, w: &Tensor<f64>) -> TensorPromise<f64> {
+    ((x * y) + x) * 2.0 - w
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn calls_match_freshly_built_graphs() {
+    let (x, y, w) = (
+        arange_from(&[2, 3], 0.0),
+        arange_from(&[2, 3], 1.0),
+        arange_from(&[2, 3], 0.5),
+    );
+    let compiled = graph(&x, &y, &w).compile(&[&x, &y]);
+    assert_eq!(compiled.arity(), 2);
+    assert_eq!(compiled.shape(), &[2, 3]);
+
+    for shift in [0.0, -3.0, 10.0, 0.25] {
+        let (a, b) = (
+            arange_from(&[2, 3], shift),
+            arange_from(&[2, 3], 2.0 * shift),
+        );
+
+        let result = compiled.call(&[&a, &b]).unwrap();
+        assert_eq!(result.shape(), &[2, 3]);
+        assert_eq!(values(&result), values(&graph(&a, &b, &w).materialize()));
+    }
+
+    // The arguments are only read.
+    let a = arange_from(&[2, 3], 1.0);
+    compiled.call(&[&a, &a]).unwrap();
+    assert_eq!(values(&a), values(&arange_from(&[2, 3], 1.0)));
+}
+
+#[test]
+fn arguments_are_checked_against_the_inputs() {
+    let (x, y, w) = (
+        arange_from(&[2, 3], 0.0),
+        arange_from(&[2, 3], 1.0),
+        arange_from(&[2, 3], 0.5),
+    );
+    let compiled = graph(&x, &y, &w).compile(&[&x, &y]);
+
+    let err = compiled.call(&[&x]).err().unwrap();
+    assert!(matches!(err, CallError::WrongArity(2, 1)), "{}", err);
+    let err = compiled.call(&[&x, &y, &w]).err().unwrap();
+    assert!(matches!(err, CallError::WrongArity(2, 3)), "{}", err);
+
+    let wrong = arange_from(&[3, 2], 0.0);
+    let err = compiled.call(&[&x, &wrong]).err().unwrap();
+    assert!(
+        matches!(&err, CallError::ArgumentShape(1, expected, found)
+            if **expected == [2, 3] && **found == [3, 2]),
+        "{}",
+        err
+    );
+    assert_eq!(
+        format!("{}", err),
+        "argument 1 should have the shape [2, 3] of its input, found [3, 2]"
+    );
+
+    // A failed assertion of the graph.
+    let checked = (&x * &y).assert_finite("product").compile(&[&x, &y]);
+    let nan = Tensor::from_iter([f64::NAN; 6], &[2, 3]);
+    let err = checked.call(&[&nan, &y]).err().unwrap();
+    assert!(
+        matches!(err, CallError::Op(OpError::AssertionFailed(_))),
+        "{}",
+        err
+    );
+    assert!(checked.call(&[&x, &y]).is_ok());
+}
+
+#[test]
+fn tensors_that_are_not_inputs_are_captured_at_compile_time() {
+    let (x, y) = (arange_from(&[2, 3], 0.0), arange_from(&[2, 3], 1.0));
+    let mut w = arange_from(&[2, 3], 0.5);
+    let compiled = graph(&x, &y, &w).compile(&[&x, &y]);
+    let before = compiled.call(&[&x, &y]).unwrap();
+
+    fill(&mut w, 100.0);
+
+    // A graph built now reads the new weight, the compiled one the old.
+    let after = compiled.call(&[&x, &y]).unwrap();
+    assert_eq!(values(&after), values(&before));
+    assert_ne!(values(&graph(&x, &y, &w).materialize()), values(&before));
+
+    // An input listed twice is bound to its first position.
+    let twice = (&x + &y).compile(&[&x, &y, &x]);
+    let (a, b, c) = (
+        arange_from(&[2, 3], 1.0),
+        arange_from(&[2, 3], 2.0),
+        arange_from(&[2, 3], 5.0),
+    );
+    let result = twice.call(&[&a, &b, &c]).unwrap();
+    assert_eq!(values(&result), values(&(&a + &b).materialize()));
+}
+
+#[test]
+fn caches_are_computed_once_across_calls() {
+    let (x, w) = (arange_from(&[4], 0.0), arange_from(&[4], 1.0));
+
+    // Filled before compiling, which the compiled graph does not see.
+    let weights = (&w * &w).cache();
+    weights.clone().materialize();
+    let compiled = (&weights + &x).compile(&[&x]);
+
+    let before = computed_op_count();
+    let first = compiled.call(&[&arange_from(&[4], 1.0)]).unwrap();
+    // The product, the cache over it and the sum.
+    assert_eq!(computed_op_count() - before, 3);
+
+    let before = computed_op_count();
+    let second = compiled.call(&[&arange_from(&[4], 2.0)]).unwrap();
+    assert_eq!(computed_op_count() - before, 1);
+
+    let squares: Vec<f64> = values(&w).iter().map(|w| w * w).collect();
+    let expected = |shift: f64| -> Vec<f64> {
+        (squares.iter().enumerate())
+            .map(|(i, w)| w + i as f64 + shift)
+            .collect()
+    };
+    assert_eq!(values(&first), expected(1.0));
+    assert_eq!(values(&second), expected(2.0));
+
+    // A cache over an argument keeps the result of the first call.
+    let doubled = (&x * &w).cache();
+    let compiled = (&doubled + &w).compile(&[&x]);
+    let first = compiled.call(&[&arange_from(&[4], 1.0)]).unwrap();
+    let second = compiled.call(&[&arange_from(&[4], 7.0)]).unwrap();
+    assert_eq!(values(&first), values(&second));
+
+    // Compiling again gives new caches.
+    let recompiled = (&doubled + &w).compile(&[&x]);
+    let third = recompiled.call(&[&arange_from(&[4], 7.0)]).unwrap();
+    assert_ne!(values(&third), values(&first));
+}
+
+#[test]
+fn arguments_laid_out_differently_are_copied() {
+    let x = arange_from(&[3, 2], 0.0);
+    let promise = x.transpose() * 2.0;
+    let compiled = promise.compile(&[&x]);
+
+    // Flipped and strided views of the same shape as x.
+    let flipped = arange_from(&[3, 2], 1.0).flip_view(&[0, 1]).unwrap();
+    let strided = arange_from(&[6, 2], 1.0)
+        .step_slice(ss![0..6;2, ..])
+        .unwrap();
+    let strided = strided.materialize();
+
+    for arg in [&flipped, &strided] {
+        let expected = (arg.transpose() * 2.0).materialize();
+        assert_eq!(values(&compiled.call(&[arg]).unwrap()), values(&expected));
+    }
+
+    // Compiled over a view, read with a contiguous argument.
+    let view = arange_from(&[2, 3], 0.0).flip_view(&[1]).unwrap();
+    let compiled = (view.transpose() + 1.0).compile(&[&view]);
+    let arg = arange_from(&[2, 3], 4.0);
+    let expected = (arg.transpose() + 1.0).materialize();
+    assert_eq!(values(&compiled.call(&[&arg]).unwrap()), values(&expected));
+}
+
+#[test]
+fn concurrent_calls_share_a_compiled_graph() {
+    let (x, y, w) = (
+        arange_from(&[8, 8], 0.0),
+        arange_from(&[8, 8], 1.0),
+        arange_from(&[8, 8], 0.5),
+    );
+    let compiled = graph(&x, &y, &w).compile(&[&x, &y]);
+
+    thread::scope(|scope| {
+        for thread in 0..8 {
+            let (compiled, w) = (&compiled, &w);
+
+            scope.spawn(move || {
+                for call in 0..20 {
+                    let shift = (thread * 20 + call) as f64;
+                    let (a, b) = (arange_from(&[8, 8], shift), arange_from(&[8, 8], -shift));
+
+                    let result = compiled.call(&[&a, &b]).unwrap();
+                    assert_eq!(values(&result), values(&graph(&a, &b, w).materialize()));
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use simple_tensor::s;
+use simple_tensor::tensor::complex::Complex;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+
+use common::values;
+
+type C = Complex<f64>;
+
+fn c(re: f64, im: f64) -> C {
+    Complex::new(re, im)
+}
+
+// MKL may round complex mul and div differently from the scalar reference.
+fn assert_close(got: &[C], expected: &[C]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        let tolerance = 1e-12 * (1.0 + e.abs());
+        assert!((*g - *e).abs() <= tolerance, "{} != {}", g, e);
+    }
+}
+
+fn fixture() -> Tensor<C> {
+    Tensor::from_vec(
+        vec![
+            c(1.0, 2.0),
+            c(-3.0, 0.5),
+            c(0.0, -1.0),
+            c(2.5, 0.0),
+            c(-0.25, -4.0),
+            c(3.0, 3.0),
+        ],
+        &[2, 3],
+    )
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn elementwise_arithmetic() {
+    let a = fixture();
+    let b = Tensor::from_iter(
+        values(&a).into_iter().rev().map(|x| x * c(0.5, -1.0)),
+        &[2, 3],
+    );
+    let (xs, ys) = (values(&a), values(&b));
+
+    let reference =
+        |f: fn(C, C) -> C| -> Vec<C> { xs.iter().zip(&ys).map(|(&x, &y)| f(x, y)).collect() };
+
+    assert_close(&values(&(&a + &b).materialize()), &reference(|x, y| x + y));
+    assert_close(&values(&(&a - &b).materialize()), &reference(|x, y| x - y));
+    assert_close(&values(&(&a * &b).materialize()), &reference(|x, y| x * y));
+    assert_close(&values(&(&a / &b).materialize()), &reference(|x, y| x / y));
+}
+
+#[test]
+fn fused_scalar_chains() {
+    let t = fixture();
+    let (s1, s2, s3) = (c(1.0, -2.0), c(0.5, 0.5), c(-2.0, 1.0));
+
+    // Mul and Div fold into a single scalar, Add and Sub into another.
+    let chain = ((&t * s1) / s2 + s3 - s1) * s2 / s3;
+    let expected: Vec<C> = values(&t)
+        .into_iter()
+        .map(|x| ((x * s1) / s2 + s3 - s1) * s2 / s3)
+        .collect();
+
+    assert_close(&values(&chain.materialize()), &expected);
+}
+
+#[test]
+fn conj_abs_real_imag() {
+    let t = fixture();
+
+    assert_eq!(
+        values(&t.conj()),
+        [
+            c(1.0, -2.0),
+            c(-3.0, -0.5),
+            c(0.0, 1.0),
+            c(2.5, -0.0),
+            c(-0.25, 4.0),
+            c(3.0, -3.0)
+        ]
+    );
+
+    let abs = t.abs_complex();
+    assert_eq!(abs.shape(), &[2, 3]);
+    assert_eq!(abs.iter().nth(3), Some(&2.5));
+    assert_eq!(abs.iter().nth(5), Some(&18f64.sqrt()));
+
+    let re: Vec<f64> = t.real().iter().copied().collect();
+    let im: Vec<f64> = t.imag().iter().copied().collect();
+    assert_eq!(re, [1.0, -3.0, 0.0, 2.5, -0.25, 3.0]);
+    assert_eq!(im, [2.0, 0.5, -1.0, 0.0, -4.0, 3.0]);
+}
+
+#[test]
+fn angle_is_the_argument() {
+    let t = Tensor::from_vec(vec![c(1.0, 0.0), c(0.0, 1.0), c(-1.0, 0.0)], &[3]);
+    let angle = t.angle();
+    assert_eq!(angle.shape(), &[3]);
+    assert_eq!(values(&angle), [0.0, FRAC_PI_2, PI]);
+
+    // A negative imaginary part is a negative angle, within (-pi, pi].
+    let t = Tensor::from_vec(
+        vec![c(0.0, -1.0), c(1.0, -1.0), c(-1.0, -1.0), c(-1.0, -0.0)],
+        &[2, 2],
+    );
+    let angle = t.angle();
+    assert_eq!(angle.shape(), &[2, 2]);
+    assert_eq!(
+        values(&angle),
+        [-FRAC_PI_2, -FRAC_PI_4, -3.0 * FRAC_PI_4, -PI]
+    );
+}
+
+#[test]
+fn from_parts() {
+    let t = fixture();
+
+    let rebuilt = Tensor::from_parts_complex(&t.real(), &t.imag()).unwrap();
+    assert_eq!(rebuilt.shape(), &[2, 3]);
+    assert_eq!(values(&rebuilt), values(&t));
+
+    // The parts are read in logical order, whatever their layout.
+    let re = Tensor::from_vec(vec![1.0, 2.0, 3.0, 4.0], &[2, 2])
+        .flip_view(&[1])
+        .unwrap();
+    let im = Tensor::from_scalar(-1.0, &[2, 2]);
+    let flipped = Tensor::from_parts_complex(&re, &im).unwrap();
+    assert_eq!(
+        values(&flipped),
+        [c(2.0, -1.0), c(1.0, -1.0), c(4.0, -1.0), c(3.0, -1.0)]
+    );
+
+    assert!(matches!(
+        Tensor::from_parts_complex(&t.real(), &Tensor::from_scalar(0.0, &[3, 2])),
+        Err(OpError::NotSameShape(..))
+    ));
+}
+
+#[test]
+fn views_and_slices() {
+    let t = fixture();
+
+    let column = t.slice(s![.., 1..2]).unwrap().materialize();
+    assert_eq!(values(&column), [c(-3.0, 0.5), c(-0.25, -4.0)]);
+
+    let transposed = (&t.transpose() * c(0.0, 1.0)).materialize();
+    assert_eq!(transposed.shape(), &[3, 2]);
+    assert_close(
+        &values(&transposed),
+        &[
+            c(-2.0, 1.0),
+            c(-0.0, 2.5),
+            c(-0.5, -3.0),
+            c(4.0, -0.25),
+            c(1.0, 0.0),
+            c(-3.0, 3.0),
+        ],
+    );
+
+    let conj = t.flip_view(&[1]).unwrap().conj();
+    assert_eq!(values(&conj)[0], c(0.0, 1.0));
+}
+
+#[test]
+fn display() {
+    assert_eq!(
+        fixture().to_string(),
+        "[\n  [1+2i, -3+0.5i, 0-1i]\n  [2.5+0i, -0.25-4i, 3+3i]\n]\n"
+    );
+    assert_eq!(c(1.0, -0.0).to_string(), "1-0i");
+}
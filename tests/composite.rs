@@ -0,0 +1,159 @@
+mod common;
+
+use simple_tensor::s;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{CompositeBuilder, Dimension, Tensor, TensorPromise};
+
+use common::{arange, values};
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn blocks_assemble_into_the_eager_result() {
+    let a = arange(&[2, 3]);
+    let b = arange(&[2, 2]);
+
+    let blocks: [TensorPromise<f64>; 4] = [
+        &a + 1.0,
+        &b * 10.0,
+        arange(&[3, 2]).transpose() - 1.0,
+        (&b * &b).view(&[2, 2]).unwrap(),
+    ];
+
+    let mut builder = CompositeBuilder::new(&[4, 5]);
+    builder.place(s![0..2, 0..3], blocks[0].clone()).unwrap();
+    builder.place(s![0..2, 3..5], blocks[1].clone()).unwrap();
+    builder.place(s![2..4, 0..3], blocks[2].clone()).unwrap();
+    builder.place(s![2..4, 3..], blocks[3].clone()).unwrap();
+    let result = builder.build().unwrap().materialize();
+
+    // Writing the materialized blocks one by one.
+    let mut expected = Tensor::from_scalar(0.0, &[4, 5]);
+    let ranges = [
+        s![0..2, 0..3],
+        s![0..2, 3..5],
+        s![2..4, 0..3],
+        s![2..4, 3..],
+    ];
+    for (range, block) in ranges.into_iter().zip(blocks) {
+        let block = block.materialize();
+        expected
+            .assign_slice_from_iter(range, block.iter().copied())
+            .unwrap();
+    }
+
+    assert_eq!(result.shape(), &[4, 5]);
+    assert_eq!(values(&result), values(&expected));
+}
+
+#[test]
+fn overlapping_placements_are_errors() {
+    let mut builder = CompositeBuilder::new(&[3, 3]);
+    builder
+        .place(s![0..2, 0..2], arange(&[2, 2]).as_promise())
+        .unwrap();
+    builder.place(s![2, ..], arange(&[3]).as_promise()).unwrap();
+    builder
+        .place(s![1..3, 1..3], arange(&[2, 2]).as_promise())
+        .unwrap();
+
+    let err = builder.build().err().unwrap();
+    assert!(
+        matches!(err, OpError::OverlappingPlacements(0, 2)),
+        "{:?}",
+        err
+    );
+    assert!(err.to_string().contains("placements 0 and 2 overlap"));
+}
+
+#[test]
+fn uncovered_elements_need_a_fill_value() {
+    let place_diagonal_blocks = |builder: &mut CompositeBuilder<f64>| {
+        builder
+            .place(s![0..2, 0..2], arange(&[2, 2]).as_promise())
+            .unwrap();
+        builder
+            .place(s![2..3, 2..3], arange(&[1, 1]).as_promise())
+            .unwrap();
+    };
+
+    let mut builder = CompositeBuilder::new(&[3, 3]);
+    place_diagonal_blocks(&mut builder);
+    let err = builder.build().err().unwrap();
+    assert!(matches!(err, OpError::UncoveredElements(4)), "{:?}", err);
+
+    let mut builder = CompositeBuilder::new(&[3, 3]).with_fill(-1.0);
+    place_diagonal_blocks(&mut builder);
+    let result = builder.build().unwrap().materialize();
+    assert_eq!(
+        values(&result),
+        [0.0, 1.0, -1.0, 2.0, 3.0, -1.0, -1.0, -1.0, 0.0]
+    );
+
+    // Nothing placed at all.
+    let empty = CompositeBuilder::<f64>::new(&[2]).with_fill(7.0);
+    assert_eq!(values(&empty.build().unwrap().materialize()), [7.0, 7.0]);
+}
+
+#[test]
+fn placements_can_share_their_leaves() {
+    let t = arange(&[2, 2]);
+
+    let mut builder = CompositeBuilder::new(&[2, 6]);
+    builder.place(s![.., 0..2], t.as_promise()).unwrap();
+    builder.place(s![.., 2..4], &t * 2.0).unwrap();
+    builder.place(s![.., 4..6], &t + &t).unwrap();
+    let result = builder.build().unwrap().materialize();
+
+    assert_eq!(
+        values(&result),
+        [0.0, 1.0, 0.0, 2.0, 0.0, 2.0, 2.0, 3.0, 4.0, 6.0, 4.0, 6.0]
+    );
+    assert_eq!(values(&t), [0.0, 1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn composites_can_be_nested_and_strided() {
+    let mut inner = CompositeBuilder::new(&[2, 2]);
+    inner.place(s![0, ..], arange(&[2]).as_promise()).unwrap();
+    inner.place(s![1, ..], arange(&[2]) + 10.0).unwrap();
+    let inner = inner.build().unwrap();
+
+    let mut outer = CompositeBuilder::new(&[2, 4]).with_fill(0.5);
+    outer.place(s![.., 1..3], inner.clone()).unwrap();
+    let result = outer.build().unwrap().materialize();
+
+    assert_eq!(values(&result), [0.5, 0.0, 1.0, 0.5, 0.5, 10.0, 11.0, 0.5]);
+
+    // Every other column, through a step slice.
+    let mut strided = CompositeBuilder::new(&[2, 4]).with_fill(0.0);
+    strided.place(s![.., 0..4;2], inner * 2.0).unwrap();
+    let result = strided.build().unwrap().materialize();
+    assert_eq!(values(&result), [0.0, 0.0, 2.0, 0.0, 20.0, 0.0, 22.0, 0.0]);
+}
+
+#[test]
+fn placement_shapes_are_validated_immediately() {
+    let mut builder = CompositeBuilder::new(&[4, 4]);
+    builder
+        .place(s![0..2, ..], arange(&[2, 4]).as_promise())
+        .unwrap();
+
+    let err = builder
+        .place(s![2..4, 0..3], arange(&[2, 4]).as_promise())
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&err, OpError::PlacementShape(1, expected, got) if **expected == [2, 3] && **got == [2, 4]),
+        "{:?}",
+        err
+    );
+    assert!(err.to_string().contains("placement 1"), "{}", err);
+
+    // Out of bounds windows.
+    assert!(
+        builder
+            .place(s![3..6, ..], arange(&[3, 4]).as_promise())
+            .is_err()
+    );
+}
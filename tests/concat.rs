@@ -0,0 +1,99 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor, TensorPromise};
+use simple_tensor::{matrix, s, vector};
+
+use common::values;
+
+fn filled(value: f64) -> TensorPromise<f64> {
+    &Tensor::from_scalar(0.0, &[4, 3]) + value
+}
+
+#[test]
+fn lazy_concat_along_the_first_axis() {
+    let parts = [filled(1.0), filled(2.0), filled(3.0)];
+
+    let joined = TensorPromise::concat(&parts, 0).unwrap();
+    assert_eq!(joined.shape(), &[12, 3]);
+
+    let joined = joined.materialize();
+    let expected: Vec<f64> = [1.0, 2.0, 3.0]
+        .iter()
+        .flat_map(|&v| std::iter::repeat_n(v, 12))
+        .collect();
+    assert_eq!(values(&joined), expected);
+}
+
+#[test]
+fn concat_along_inner_axes() {
+    let a = matrix![[1, 2], [3, 4]];
+    let b = matrix![[5], [6]];
+    let c = matrix![[7, 8, 9], [10, 11, 12]];
+
+    let joined =
+        TensorPromise::concat(&[a.as_promise(), b.as_promise(), c.as_promise()], 1).unwrap();
+    assert_eq!(joined.shape(), &[2, 6]);
+    assert_eq!(
+        values(&joined.materialize()),
+        [
+            1.0, 2.0, 5.0, 7.0, 8.0, 9.0, 3.0, 4.0, 6.0, 10.0, 11.0, 12.0
+        ]
+    );
+
+    // Strided inputs are read in their logical order.
+    let joined = TensorPromise::concat(&[a.transpose(), (&a * 10.0).transpose()], 0).unwrap();
+    assert_eq!(
+        values(&joined.materialize()),
+        [1.0, 3.0, 2.0, 4.0, 10.0, 30.0, 20.0, 40.0]
+    );
+}
+
+#[test]
+fn adjacent_slices_of_a_tensor_become_one_slice() {
+    let t = Tensor::from_vec((0..12).map(f64::from).collect(), &[4, 3]);
+
+    let top = t.slice(s![0..1, ..]).unwrap();
+    let middle = t.slice(s![1..3, ..]).unwrap();
+    let joined = TensorPromise::concat(&[top, middle], 0).unwrap();
+
+    assert_eq!(joined.shape(), &[3, 3]);
+    // A slice of a tensor can be streamed, a concatenation can't.
+    assert!(joined.materialize_streamed(4, |_, _| {}).is_ok());
+    assert_eq!(
+        values(&joined.materialize()),
+        (0..9).map(f64::from).collect::<Vec<_>>()
+    );
+
+    // Out of order, the slices are copied.
+    let first = t.slice(s![2..4, ..]).unwrap();
+    let second = t.slice(s![0..1, ..]).unwrap();
+    let joined = TensorPromise::concat(&[first, second], 0).unwrap();
+    assert!(matches!(
+        joined.materialize_streamed(4, |_, _| {}),
+        Err(OpError::NotStreamable(_))
+    ));
+    assert_eq!(
+        values(&joined.materialize()),
+        [6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 0.0, 1.0, 2.0]
+    );
+}
+
+#[test]
+fn invalid_concatenations() {
+    let a = vector![1, 2, 3].as_promise();
+    let b = matrix![[1, 2, 3]].as_promise();
+
+    assert!(matches!(
+        TensorPromise::concat(&[a.clone(), b.clone()], 0),
+        Err(err) if matches!(err.cause(), OpError::NotSameShape(_, _))
+    ));
+    assert!(matches!(
+        TensorPromise::concat(&[a.clone(), a.clone()], 1),
+        Err(OpError::OutOfBoundAxes)
+    ));
+    assert!(matches!(
+        TensorPromise::<f64>::concat(&[], 0),
+        Err(OpError::NothingToConcat)
+    ));
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use std::panic::{self, AssertUnwindSafe};
+
+use simple_tensor::s;
+use simple_tensor::tensor::{Dimension, Tensor, materialize_many};
+
+use common::{arange, values};
+
+fn ptr(t: &Tensor<f64>) -> *const f64 {
+    t.try_as_slice().unwrap().as_ptr()
+}
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = panic::catch_unwind(AssertUnwindSafe(f)).err().unwrap();
+
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => payload.downcast_ref::<String>().unwrap().clone(),
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn the_first_op_overwrites_the_consumed_buffer() {
+    let x = arange(&[64, 64]);
+    let expected: Vec<f64> = values(&x).iter().map(|el| el.sqrt() * 2.0).collect();
+    let input = ptr(&x);
+
+    let out = (x.consume().sqrt() * 2.0).materialize();
+    assert_eq!(values(&out), expected);
+    assert_eq!(ptr(&out), input);
+
+    // A tensor that was not consumed is read in place, into a new buffer.
+    let x = arange(&[64, 64]);
+    let input = ptr(&x);
+    let out = (x.sqrt() * 2.0).materialize();
+    assert_eq!(values(&out), expected);
+    assert_ne!(ptr(&out), input);
+
+    // The names and the shape are kept.
+    let x = arange(&[2, 3]).with_axis_names(&["rows", "cols"]).unwrap();
+    let consumed = x.consume();
+    assert_eq!(consumed.shape(), [2, 3]);
+    let out = consumed.sqrt().materialize();
+    assert_eq!(out.names().unwrap().join(","), "rows,cols");
+}
+
+#[test]
+fn values_match_across_op_kinds() {
+    // A clone would share the buffer, so every consumed tensor is a new one.
+    let fresh_x = || arange(&[4, 6]);
+    let fresh_y = || Tensor::from_iter((0..24).map(|i| 1.0 + (i % 5) as f64), &[4, 6]);
+    let (x, y) = (fresh_x(), fresh_y());
+
+    let cases: Vec<(Tensor<f64>, Tensor<f64>)> = vec![
+        (-fresh_x().consume() + 1.0, -&x + 1.0),
+        (fresh_x().consume() / &y, &x / &y),
+        (&y - fresh_x().consume(), &y - &x),
+        (fresh_x().consume().transpose(), x.transpose()),
+        (
+            fresh_x().consume().slice(s![1.., ..;2]).unwrap(),
+            x.slice(s![1.., ..;2]).unwrap(),
+        ),
+        (
+            fresh_x().consume().normalize(1, 1e-5).unwrap(),
+            x.normalize(1, 1e-5).unwrap(),
+        ),
+        (fresh_x().consume().tile(&[2, 1]), x.tile(&[2, 1])),
+        (
+            fresh_x().consume().rolling_sum(2, 1).unwrap(),
+            x.rolling_sum(2, 1).unwrap(),
+        ),
+        (
+            fresh_x().consume().apply_scalar(|el| el * el),
+            x.apply_scalar(|el| el * el),
+        ),
+        (fresh_x().consume() * fresh_y().consume(), &x * &y),
+    ]
+    .into_iter()
+    .map(|(consumed, expected)| (consumed.materialize(), expected.materialize()))
+    .collect();
+
+    for (i, (consumed, expected)) in cases.iter().enumerate() {
+        assert_eq!(consumed.shape(), expected.shape(), "case {}", i);
+        assert_eq!(values(consumed), values(expected), "case {}", i);
+    }
+}
+
+#[test]
+fn fused_and_cached_ops_read_it_once() {
+    // Chains of scalar ops are a single node, which overwrites the buffer.
+    let x = arange(&[32, 32]);
+    let input = ptr(&x);
+    let out = ((x.consume() * 2.0 + 1.0) / 4.0).materialize();
+    assert_eq!(ptr(&out), input);
+    assert_eq!(values(&out)[3], 7.0 / 4.0);
+
+    // A cache computes it once, and is then read by every promise.
+    let cached = arange(&[8]).consume().sqrt().cache();
+    let first = &cached + 1.0;
+    let second = &cached * 2.0;
+    let outs = materialize_many(&[&first, &second]);
+    assert_eq!(values(&outs[0])[4], 3.0);
+    assert_eq!(values(&outs[1])[4], 4.0);
+    assert_eq!(values(&first.materialize())[7], 7f64.sqrt() + 1.0);
+
+    // An op read by two others in the same graph, a view of it outliving the first read.
+    let scaled = arange(&[3, 3]).consume() * 1.0;
+    let out = (scaled.transpose() + scaled.sqrt()).materialize();
+    let x = arange(&[3, 3]);
+    assert_eq!(
+        values(&out),
+        values(&(x.transpose() + x.sqrt()).materialize())
+    );
+
+    // Without a cache, computing it again finds nothing to read.
+    let promise = arange(&[8]).consume().sqrt();
+    promise.clone().materialize();
+    let message = panic_message(|| {
+        promise.materialize();
+    });
+    assert!(
+        message.contains("only read by the first computation"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn shared_buffers_are_not_overwritten() {
+    // A clone reads the buffer, which breaks the promise of consume().
+    let x = arange(&[16]);
+    let kept = x.clone();
+    let promise = x.consume().sqrt();
+
+    if cfg!(debug_assertions) {
+        let message = panic_message(|| {
+            promise.materialize();
+        });
+        assert!(
+            message.contains("shared with another tensor"),
+            "{}",
+            message
+        );
+    } else {
+        assert_eq!(values(&promise.materialize())[4], 2.0);
+    }
+    assert_eq!(values(&kept), values(&arange(&[16])));
+
+    // So does another tensor over the same memory.
+    let x = arange(&[4]);
+    let alias = x.with_axis_names(&["i"]).unwrap();
+    let promise = x.consume() + 1.0;
+
+    if cfg!(debug_assertions) {
+        let message = panic_message(|| {
+            promise.materialize();
+        });
+        assert!(
+            message.contains("shared with another tensor"),
+            "{}",
+            message
+        );
+    } else {
+        assert_eq!(values(&promise.materialize()), [1.0, 2.0, 3.0, 4.0]);
+    }
+    assert_eq!(values(&alias), [0.0, 1.0, 2.0, 3.0]);
+
+    // Once the clone is dropped, the buffer is free to reuse.
+    let x = arange(&[16]);
+    let input = ptr(&x);
+    let kept = x.clone();
+    let promise = x.consume().sqrt();
+    drop(kept);
+    assert_eq!(ptr(&promise.materialize()), input);
+}
+
+#[test]
+fn external_views_copy_only_their_elements() {
+    let mut data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let external = unsafe { Tensor::from_raw_parts(data.as_mut_ptr(), 6, &[6], None) };
+
+    // The first half of the buffer, the only tensor left reading it.
+    let view = external.as_strided(&[3], &[1], 0).unwrap();
+    drop(external);
+
+    let out = (view.consume() + Tensor::from_vec(vec![10.0, 20.0, 30.0], &[3])).materialize();
+    assert_eq!(values(&out), [11.0, 22.0, 33.0]);
+
+    // External memory is copied rather than overwritten.
+    assert_eq!(data, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+}
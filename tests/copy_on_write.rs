@@ -0,0 +1,144 @@
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+fn fill(t: &mut Tensor<f64>, value: f64) {
+    t.with_raw_mut(|ptr, len| unsafe { std::slice::from_raw_parts_mut(ptr, len) }.fill(value))
+        .unwrap();
+}
+
+fn buffer_ptr(t: &Tensor<f64>) -> *const f64 {
+    t.with_raw(|ptr, _| ptr).unwrap()
+}
+
+#[test]
+fn writes_to_a_reshaped_tensor_do_not_reach_the_original() {
+    let a = matrix![[1, 2, 3], [4, 5, 6]];
+    let mut b = a.view(&[3, 2]).unwrap().materialize();
+
+    // The view shares the buffer until it is written to.
+    assert!(a.is_shared() && b.is_shared());
+    assert_eq!(buffer_ptr(&a), buffer_ptr(&b));
+
+    fill(&mut b, 0.0);
+
+    assert_eq!(b.shape(), &[3, 2]);
+    assert_eq!(values(&b), [0.0; 6]);
+    assert_eq!(values(&a), [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    assert!(!a.is_shared() && !b.is_shared());
+}
+
+#[test]
+fn make_unique_is_a_no_op_on_unique_tensors() {
+    let mut t = vector![1, 2, 3];
+    assert!(!t.is_shared());
+
+    let before = buffer_ptr(&t);
+    let id = t.id();
+    t.make_unique();
+
+    assert_eq!(buffer_ptr(&t), before);
+    assert_eq!(t.id(), id);
+}
+
+#[test]
+fn clones_and_promises_keep_the_old_elements() {
+    let mut t = vector![1, 2, 3];
+    let clone = t.clone();
+    let promise = &t * 2.0;
+    assert!(t.is_shared());
+
+    fill(&mut t, 5.0);
+
+    assert_eq!(values(&t), [5.0; 3]);
+    assert_eq!(values(&clone), [1.0, 2.0, 3.0]);
+    assert_eq!(values(&promise.materialize()), [2.0, 4.0, 6.0]);
+    assert_ne!(t.id(), clone.id());
+}
+
+#[test]
+fn only_the_logical_elements_are_copied() {
+    let big = Tensor::from_vec((0..100).map(f64::from).collect(), &[10, 10]);
+    let mut row = big
+        .slice(simple_tensor::s![3..4, ..])
+        .unwrap()
+        .materialize();
+
+    row.make_unique();
+
+    assert_eq!(values(&row), (30..40).map(f64::from).collect::<Vec<_>>());
+    assert!(!row.is_shared());
+    assert!(!big.is_shared());
+}
+
+#[test]
+fn materialize_into_a_shared_tensor() {
+    let mut dest = vector![1, 2, 3];
+    let reader = dest.clone_detached();
+
+    (&dest + 10.0).materialize_into(&mut dest).unwrap();
+
+    assert_eq!(values(&dest), [11.0, 12.0, 13.0]);
+    assert_eq!(values(&reader), [1.0, 2.0, 3.0]);
+
+    // Once unique again, the same buffer is written.
+    let before = buffer_ptr(&dest);
+    (&dest * 2.0).materialize_into(&mut dest).unwrap();
+    assert_eq!(values(&dest), [22.0, 24.0, 26.0]);
+    assert_eq!(buffer_ptr(&dest), before);
+}
+
+// Buffers are not behind locks, so a live iterator over a view never blocks a write
+// to the tensor it shares the buffer with, the write going to a copy instead.
+#[test]
+fn writing_while_iterating_a_view_does_not_block() {
+    let mut t = matrix![[1, 2, 3], [4, 5, 6]];
+    let view = t.view(&[6]).unwrap().materialize();
+
+    let mut reader = view.iter();
+    assert_eq!(reader.next(), Some(&1.0));
+
+    fill(&mut t, 0.0);
+    t.assign_from_iter([7.0, 8.0]).unwrap();
+
+    assert!(reader.copied().eq([2.0, 3.0, 4.0, 5.0, 6.0]));
+    assert_eq!(values(&t), [7.0, 8.0, 0.0, 0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn readers_never_see_a_partial_write() {
+    let original: Vec<f64> = (0..4096).map(f64::from).collect();
+    let mut t = Tensor::from_vec(original.clone(), &[4096]);
+    let done = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let reader = t.clone_detached();
+                let (done, original) = (&done, &original);
+
+                scope.spawn(move || {
+                    while !done.load(Ordering::Acquire) {
+                        assert!(reader.iter().eq(original.iter()));
+                    }
+                })
+            })
+            .collect();
+
+        for value in 0..50 {
+            fill(&mut t, value as f64);
+            assert!(t.iter().all(|&v| v == value as f64));
+        }
+
+        done.store(true, Ordering::Release);
+        readers
+            .into_iter()
+            .for_each(|reader| reader.join().unwrap());
+    });
+}
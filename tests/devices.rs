@@ -0,0 +1,188 @@
+mod common;
+
+use std::panic::{self, AssertUnwindSafe};
+
+use simple_tensor::s;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::testing::placed_on;
+use simple_tensor::tensor::{BatchProduct, Device, Tensor, TensorPromise, TryArithmetic};
+
+use common::{arange, values};
+
+const GPU: Device = Device::Gpu(0);
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn tensors_are_placed_on_the_cpu() {
+    let x = arange(&[2, 3]);
+
+    assert_eq!(Device::default(), Device::Cpu);
+    assert_eq!(x.device(), Device::Cpu);
+    assert_eq!(x.data().device(), Device::Cpu);
+    assert_eq!((&x + 1.0).device(), Device::Cpu);
+    assert_eq!(x.sqrt().cache().device(), Device::Cpu);
+    assert_eq!(x.transpose().materialize().device(), Device::Cpu);
+
+    assert_eq!(Device::Cpu.to_string(), "cpu");
+    assert_eq!(Device::Gpu(1).to_string(), "gpu:1");
+}
+
+#[test]
+fn every_kind_of_op_keeps_the_device_of_its_inputs() {
+    let a = placed_on(&arange(&[2, 3]), GPU);
+    let b = placed_on(&arange(&[3, 2]), GPU);
+    assert_eq!(a.device(), GPU);
+    assert_eq!(values(&a), values(&arange(&[2, 3])));
+
+    let promises: Vec<(&str, TensorPromise<f64>)> = vec![
+        ("unary", a.sqrt()),
+        ("scalar", &a * 2.0),
+        ("binary", &a + &a),
+        ("view", a.transpose()),
+        ("slice", a.slice(s![.., 1..]).unwrap()),
+        ("reduction", a.any(1).unwrap()),
+        ("lanes", a.normalize(1, 1e-5).unwrap()),
+        ("matmul", a.bmm(&b).unwrap()),
+        ("apply", a.apply_scalar(|x| x + 1.0)),
+        ("random", a.rand_like(7)),
+        (
+            "concat",
+            TensorPromise::concat(&[a.as_promise(), a.as_promise()], 0).unwrap(),
+        ),
+        ("chain", ((&a + 1.0).sqrt() * &a).transpose()),
+    ];
+
+    for (kind, promise) in promises {
+        assert_eq!(promise.device(), GPU, "{}", kind);
+    }
+
+    assert_eq!((&a + &a).cache().device(), GPU);
+
+    // A cast has no input, it is placed where the tensor it converts is.
+    let halves = placed_on(&Tensor::from_vec(vec![0.5f32; 6], &[2, 3]), GPU);
+    assert_eq!(halves.cast::<f64>().device(), GPU);
+    assert_eq!((halves.cast::<f64>() + &a).device(), GPU);
+}
+
+#[test]
+fn mixing_devices_fails_when_building_the_graph() {
+    let cpu = arange(&[2, 3]);
+    let gpu = placed_on(&cpu, GPU);
+
+    let err = cpu.try_add(&gpu).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::MixedDevices(Device::Cpu, Device::Gpu(0))
+    ));
+    assert_eq!(err.context().unwrap().op(), "Add");
+    assert_eq!(err.context().unwrap().input(), 1);
+    assert!(
+        err.to_string()
+            .starts_with("the inputs are placed on different devices, cpu and gpu:0"),
+        "{}",
+        err
+    );
+
+    let other = placed_on(&cpu, Device::Gpu(1));
+    let err = other.try_mul(&(&gpu * 2.0)).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::MixedDevices(Device::Gpu(1), Device::Gpu(0))
+    ));
+
+    let err = gpu.bmm(&cpu.transpose()).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::MixedDevices(Device::Gpu(0), Device::Cpu)
+    ));
+
+    let parts = [gpu.as_promise(), gpu.as_promise(), cpu.as_promise()];
+    let err = TensorPromise::concat(&parts, 0).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::MixedDevices(Device::Gpu(0), Device::Cpu)
+    ));
+    assert_eq!(err.context().unwrap().input(), 2);
+
+    let halves = Tensor::from_vec(vec![0.5f32; 6], &[2, 3]);
+    let err = halves.cast::<f64>().try_sub(&gpu).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::MixedDevices(Device::Cpu, Device::Gpu(0))
+    ));
+
+    // The operators panic with the same error.
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| &cpu + &gpu));
+    assert!(panicked.is_err());
+}
+
+#[test]
+fn to_device_only_moves_from_the_cpu_to_itself() {
+    let x = arange(&[2, 3]);
+
+    let moved = x.to_device(Device::Cpu).unwrap();
+    assert!(moved.ptr_eq(&x));
+    assert_eq!(moved.device(), Device::Cpu);
+
+    let err = x.to_device(GPU).err().unwrap();
+    assert!(matches!(
+        err,
+        OpError::DeviceNotSupported(Device::Cpu, Device::Gpu(0))
+    ));
+    assert_eq!(
+        err.to_string(),
+        "cannot move a tensor from cpu to gpu:0, only the cpu has a backend for now"
+    );
+
+    let gpu = placed_on(&x, GPU);
+    let err = gpu.to_device(Device::Cpu).err().unwrap();
+    assert!(matches!(
+        err,
+        OpError::DeviceNotSupported(Device::Gpu(0), Device::Cpu)
+    ));
+    assert!(gpu.to_device(GPU).is_err());
+}
+
+#[test]
+#[should_panic(expected = "read data placed on gpu:0, but only the cpu has a backend")]
+fn computing_off_the_cpu_panics() {
+    let _ = (placed_on(&arange(&[4]), GPU).sqrt() + 1.0).materialize();
+}
+
+#[test]
+#[should_panic(expected = "read data placed on gpu:1, but only the cpu has a backend")]
+fn casting_off_the_cpu_panics() {
+    let halves = placed_on(&Tensor::from_vec(vec![0.5f32; 4], &[4]), Device::Gpu(1));
+    let _ = halves.cast::<f64>().materialize();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialized_tensors_keep_their_device() {
+    use simple_tensor::tensor::TENSOR_FORMAT_VERSION;
+
+    let gpu = placed_on(&Tensor::from_vec(vec![1.0, 2.0], &[2]), Device::Gpu(3));
+
+    let json = serde_json::to_string(&gpu).unwrap();
+    assert_eq!(
+        json,
+        format!(
+            r#"{{"format":{},"shape":[2],"device":{{"gpu":3}},"data":[1.0,2.0]}}"#,
+            TENSOR_FORMAT_VERSION
+        )
+    );
+    let read: Tensor<f64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.device(), Device::Gpu(3));
+    assert_eq!(values(&read), [1.0, 2.0]);
+
+    let bytes = bincode::serialize(&(&gpu, &arange(&[2]))).unwrap();
+    let (read, cpu): (Tensor<f64>, Tensor<f64>) = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(read.device(), Device::Gpu(3));
+    assert_eq!(cpu.device(), Device::Cpu);
+
+    // Tensors written without a device are read as placed on the cpu.
+    let read: Tensor<f64> =
+        serde_json::from_str(r#"{"format":1,"shape":[2],"data":[1.0,2.0]}"#).unwrap();
+    assert_eq!(read.device(), Device::Cpu);
+}
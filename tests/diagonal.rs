@@ -0,0 +1,77 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, srange, vector};
+
+use common::values;
+
+#[test]
+fn main_and_offset_diagonals() {
+    let t = srange!(9, &[3, 3]);
+
+    let diagonal = t.diagonal().unwrap();
+    assert_eq!(diagonal.shape(), &[3]);
+    assert_eq!(diagonal.stride(), &[4]);
+    assert_eq!(values(&diagonal), [0.0, 4.0, 8.0]);
+
+    assert_eq!(values(&t.diagonal_offset(1).unwrap()), [1.0, 5.0]);
+    assert_eq!(values(&t.diagonal_offset(2).unwrap()), [2.0]);
+    assert_eq!(values(&t.diagonal_offset(-1).unwrap()), [3.0, 7.0]);
+    assert_eq!(values(&t.diagonal_offset(-2).unwrap()), [6.0]);
+
+    // Outside of the matrix.
+    assert!(t.diagonal_offset(3).unwrap().iter().next().is_none());
+    assert_eq!(t.diagonal_offset(-7).unwrap().shape(), &[0]);
+}
+
+#[test]
+fn rectangular_matrices() {
+    let wide = matrix![[1, 2, 3, 4], [5, 6, 7, 8]];
+    assert_eq!(values(&wide.diagonal().unwrap()), [1.0, 6.0]);
+    assert_eq!(values(&wide.diagonal_offset(2).unwrap()), [3.0, 8.0]);
+    assert_eq!(values(&wide.diagonal_offset(3).unwrap()), [4.0]);
+    assert_eq!(values(&wide.diagonal_offset(-1).unwrap()), [5.0]);
+
+    let tall = matrix![[1, 2], [3, 4], [5, 6]];
+    assert_eq!(values(&tall.diagonal().unwrap()), [1.0, 4.0]);
+    assert_eq!(values(&tall.diagonal_offset(-1).unwrap()), [3.0, 6.0]);
+}
+
+#[test]
+fn views_of_strided_tensors() {
+    let t = srange!(12, &[3, 4]);
+
+    // [[0, 4, 8], [1, 5, 9], [2, 6, 10], [3, 7, 11]], reading the buffer of t.
+    let view = Tensor::from_data(t.data().as_layout(t.layout().transpose()));
+    assert_eq!(values(&view.diagonal().unwrap()), [0.0, 5.0, 10.0]);
+    assert_eq!(values(&view.diagonal_offset(-1).unwrap()), [1.0, 6.0, 11.0]);
+
+    let flipped = t.flip_view(&[1]).unwrap();
+    assert_eq!(values(&flipped.diagonal().unwrap()), [3.0, 6.0, 9.0]);
+    assert_eq!(
+        values(&flipped.diagonal_offset(1).unwrap()),
+        [2.0, 5.0, 8.0]
+    );
+}
+
+#[test]
+fn shares_the_buffer() {
+    let t = srange!(9, &[3, 3]);
+    let diagonal = t.diagonal().unwrap();
+
+    assert!(t.is_shared());
+    assert_eq!(values(&(&diagonal * 2.0).materialize()), [0.0, 8.0, 16.0]);
+}
+
+#[test]
+fn only_matrices_have_diagonals() {
+    assert!(matches!(
+        vector![1, 2, 3].diagonal(),
+        Err(OpError::NotEnoughAxes(2, 1))
+    ));
+    assert!(matches!(
+        Tensor::from_scalar(0.0, &[2, 2, 2]).diagonal_offset(1),
+        Err(OpError::NotEnoughAxes(2, 3))
+    ));
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, vector};
+
+use common::{arange, values};
+
+fn assert_close(got: &[f64], expected: &[f64], tol: f64) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert!((g - e).abs() <= tol, "{:?} != {:?}", got, expected);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn diff_of_arange_is_ones() {
+    let d = arange(&[6]).diff(1, 0).unwrap().materialize();
+
+    assert_eq!(d.shape(), &[5]);
+    assert_eq!(values(&d), [1.0; 5]);
+}
+
+#[test]
+fn higher_order_differences() {
+    let squares = vector![0, 1, 4, 9, 16, 25];
+
+    let second = squares.diff(2, 0).unwrap().materialize();
+    assert_eq!(values(&second), [2.0; 4]);
+
+    let third = squares.diff(3, 0).unwrap().materialize();
+    assert_eq!(values(&third), [0.0; 3]);
+
+    // Same as chaining first differences.
+    let chained = squares.diff(1, 0).unwrap().diff(1, 0).unwrap();
+    assert_eq!(values(&chained.materialize()), values(&second));
+}
+
+#[test]
+fn diff_along_an_axis_of_a_transposed_view() {
+    let t = matrix![[1, 4, 9], [2, 3, 7]].transpose();
+    // [[1, 2], [4, 3], [9, 7]]
+
+    let rows = t.diff(1, 0).unwrap().materialize();
+    assert_eq!(rows.shape(), &[2, 2]);
+    assert_eq!(values(&rows), [3.0, 1.0, 5.0, 4.0]);
+
+    let cols = t.diff(1, 1).unwrap().materialize();
+    assert_eq!(cols.shape(), &[3, 1]);
+    assert_eq!(values(&cols), [1.0, -1.0, -2.0]);
+}
+
+#[test]
+fn trapz_of_a_polynomial() {
+    // x^3 on [0, 2] sampled every 0.002, integral 4.
+    let n = 1001;
+    let dx = 2.0 / (n - 1) as f64;
+    let y = Tensor::from_vec((0..n).map(|i| (i as f64 * dx).powi(3)).collect(), &[n]);
+
+    let area = y.trapz(dx, 0).unwrap().materialize();
+    assert_eq!(area.shape(), &[1]);
+    // The error of the rule is h^2 / 12 * (f'(2) - f'(0)) = 4e-6.
+    assert_close(&values(&area), &[4.0], 1e-5);
+
+    // Linear functions are integrated exactly, along either axis.
+    let lines = matrix![[0, 1, 2, 3], [1, 1, 1, 1]];
+    let along_rows = lines.trapz(0.5, 1).unwrap().materialize();
+    assert_eq!(along_rows.shape(), &[2]);
+    assert_close(&values(&along_rows), &[2.25, 1.5], 1e-12);
+
+    let along_cols = lines.trapz(1.0, 0).unwrap().materialize();
+    assert_close(&values(&along_cols), &[0.5, 1.0, 1.5, 2.0], 1e-12);
+}
+
+#[test]
+fn trapz_with_sample_positions() {
+    // Expected values from numpy.trapz(y, x).
+    let x = vector![0, 1, 3, 6, 10];
+    let y = vector![1, 2, 4, 7, 11];
+    assert_close(
+        &values(&y.trapz_x(&x, 0).unwrap().materialize()),
+        &[60.0],
+        1e-12,
+    );
+
+    let x = vector![0.0, 0.5, 2.0, 3.0];
+    let y = matrix![[0.0, 0.25, 4.0, 9.0], [1.0, 1.0, 1.0, 1.0]];
+    let area = y.trapz_x(&x, 1).unwrap().materialize();
+    assert_close(&values(&area), &[9.75, 3.0], 1e-12);
+
+    // Positions read through a strided view.
+    let x = vector![3.0, 2.0, 0.5, 0.0].flip_view(&[0]).unwrap();
+    let area = y.trapz_x(&x, 1).unwrap().materialize();
+    assert_close(&values(&area), &[9.75, 3.0], 1e-12);
+}
+
+#[test]
+fn validation_errors() {
+    let t = matrix![[1, 2, 3], [4, 5, 6]];
+
+    assert!(matches!(t.diff(0, 1), Err(OpError::InvalidDiffOrder(0, 3))));
+    assert!(matches!(t.diff(3, 1), Err(OpError::InvalidDiffOrder(3, 3))));
+    assert!(matches!(t.diff(2, 0), Err(OpError::InvalidDiffOrder(2, 2))));
+    assert!(matches!(t.diff(1, 2), Err(OpError::OutOfBoundAxes)));
+    assert!(matches!(t.trapz(1.0, 2), Err(OpError::OutOfBoundAxes)));
+
+    let Err(err) = t.trapz_x(&vector![0, 1], 1) else {
+        panic!("the positions do not match the axis");
+    };
+    assert!(matches!(err.cause(), OpError::NotSameShape(_, _)));
+    assert!(t.trapz_x(&matrix![[0, 1, 2]], 1).is_err());
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use simple_tensor::tensor::graph::NodeKind;
+use simple_tensor::tensor::ops::def_op::OpKind;
+use simple_tensor::tensor::ops::def_op::OpKindScalar::{self, Div, Mul, Sum};
+use simple_tensor::tensor::{Dimension, Tensor, TensorPromise};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+// Integers have no scalar operators, as they cannot be computed, but their graphs can be built.
+fn int_scalar_op(input: impl Into<NodeKind<i64>>, op: OpKindScalar<i64>) -> TensorPromise<i64> {
+    TensorPromise::new(OpKind::ScalarOp(op), Box::new([input.into()])).unwrap()
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn single_element_operands_are_lowered_on_either_side() {
+    let m = matrix![[1.0, 2.0], [4.0, 8.0]];
+    let s = vector![2.0];
+
+    let cases: [(TensorPromise<f64>, [f64; 4]); 8] = [
+        (&m + &s, [3.0, 4.0, 6.0, 10.0]),
+        (&m - &s, [-1.0, 0.0, 2.0, 6.0]),
+        (&m * &s, [2.0, 4.0, 8.0, 16.0]),
+        (&m / &s, [0.5, 1.0, 2.0, 4.0]),
+        (&s + &m, [3.0, 4.0, 6.0, 10.0]),
+        (&s - &m, [1.0, 0.0, -2.0, -6.0]),
+        (&s * &m, [2.0, 4.0, 8.0, 16.0]),
+        (&s / &m, [2.0, 1.0, 0.5, 0.25]),
+    ];
+
+    for (promise, expected) in cases {
+        let summary = promise.graph_summary();
+        assert!(summary.contains("DynScalarOp"), "{}", summary);
+
+        let result = promise.materialize();
+        assert_eq!(result.shape(), &[2, 2]);
+        assert_eq!(values(&result), expected, "{}", summary);
+    }
+}
+
+#[test]
+fn scalar_ops_after_a_lowered_op_are_fused_into_it() {
+    let m = matrix![[1.0, 2.0], [4.0, 8.0]];
+    let s = vector![3.0] * 2.0;
+
+    let promise = (&s - &m) * 2.0 + 1.0;
+    let summary = promise.graph_summary();
+    let nodes: Vec<&str> = (summary.lines())
+        .filter(|line| line.starts_with('#') && !line.contains("Tensor"))
+        .collect();
+    assert!(
+        nodes.iter().any(|line| line.contains("DynScalarOp")),
+        "{}",
+        summary
+    );
+    assert!(!summary.contains("ScalarOp(Sum"), "{}", summary);
+
+    assert_eq!(values(&promise.materialize()), [11.0, 9.0, 5.0, -3.0]);
+}
+
+#[test]
+fn lowered_ops_keep_the_rank_of_the_broadcast() {
+    let one = vector![2.0];
+    let column = Tensor::from_scalar(3.0, &[1, 1]);
+
+    // Both have a single element, the one with fewer axes is the scalar.
+    let result = (&one + &column).materialize();
+    assert_eq!(result.shape(), &[1, 1]);
+    assert_eq!(values(&result), [5.0]);
+
+    let result = (&column - &one).materialize();
+    assert_eq!(result.shape(), &[1, 1]);
+    assert_eq!(values(&result), [1.0]);
+
+    // A scalar with more axes than the other operand adds leading axes to it.
+    let m = matrix![[1.0, 2.0], [3.0, 4.0]];
+    let cube = Tensor::from_scalar(10.0, &[1, 1, 1]);
+
+    let result = (&cube * &m).materialize();
+    assert_eq!(result.shape(), &[1, 2, 2]);
+    assert_eq!(values(&result), [10.0, 20.0, 30.0, 40.0]);
+
+    let result = (&m / &cube).materialize();
+    assert_eq!(result.shape(), &[1, 2, 2]);
+    assert_eq!(values(&result), [0.1, 0.2, 0.3, 0.4]);
+
+    // Operands of the same shape are not lowered.
+    let promise = &one + &one;
+    assert!(!promise.graph_summary().contains("DynScalarOp"));
+    assert_eq!(values(&promise.materialize()), [4.0]);
+}
+
+#[test]
+fn appending_to_a_fused_list_keeps_its_last_op() {
+    let t = Tensor::from_iter(0..6i64, &[6]);
+
+    // Mul(4) composes with neither Div(2) before it nor Div(3) after it.
+    let promise = int_scalar_op(&int_scalar_op(&int_scalar_op(&t, Div(2)), Mul(4)), Div(3));
+    let summary = promise.graph_summary();
+    assert!(
+        summary.contains("FusedScalar([Div(2), Mul(4), Div(3)])"),
+        "{}",
+        summary
+    );
+
+    let promise = int_scalar_op(&promise, Sum(1));
+    let summary = promise.graph_summary();
+    assert!(
+        summary.contains("FusedScalar([Div(2), Mul(4), Div(3), Sum(1)])"),
+        "{}",
+        summary
+    );
+}
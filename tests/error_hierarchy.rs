@@ -0,0 +1,205 @@
+mod common;
+
+use std::error::Error as _;
+
+use simple_tensor::tensor::errors::{CallError, OpError, ParseError};
+use simple_tensor::tensor::shape_check::ShapePattern;
+use simple_tensor::tensor::{
+    Device, Error, ErrorContext, Tensor, TryArithmetic, try_materialize_many,
+};
+
+use common::{arange, values};
+
+// The messages of the error and of every source under it.
+fn chain(err: &dyn std::error::Error) -> Vec<String> {
+    let mut messages = vec![err.to_string()];
+    let mut source = err.source();
+
+    while let Some(err) = source {
+        messages.push(err.to_string());
+        source = err.source();
+    }
+
+    messages
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn every_result_converts_with_the_question_mark() {
+    let x = arange(&[2, 3]);
+    let y = arange(&[3, 2]);
+
+    let op = || -> Result<(), Error> {
+        x.try_add(&y)?;
+        Ok(())
+    };
+    assert!(matches!(op(), Err(Error::Op(OpError::InOp(..)))));
+
+    let count =
+        || -> Result<Tensor<f64>, Error> { Ok(Tensor::try_from_vec(vec![1.0; 5], &[2, 3])?) };
+    assert!(matches!(
+        count(),
+        Err(Error::Op(OpError::ElementCount(6, 5)))
+    ));
+
+    let compute = || -> Result<Tensor<f64>, Error> {
+        let nan = Tensor::from_iter([f64::NAN; 6], &[2, 3]);
+        Ok((&nan + 1.0).assert_finite("shifted").try_materialize()?)
+    };
+    assert!(matches!(
+        compute(),
+        Err(Error::Compute(OpError::AssertionFailed(_)))
+    ));
+
+    let device = || -> Result<Tensor<f64>, Error> { Ok(x.to_device(Device::Gpu(0))?) };
+    assert!(matches!(device(), Err(Error::NotSupported(_))));
+
+    let shape = || -> Result<(), Error> {
+        ShapePattern::new().any().exact(4).matches(&x)?;
+        Ok(())
+    };
+    assert!(matches!(shape(), Err(Error::Shape(_))));
+
+    let parse = || -> Result<Tensor<f64>, Error> { Ok(Tensor::from_str_data("[1, 2")?) };
+    assert!(matches!(
+        parse(),
+        Err(Error::Parse(ParseError::Unexpected(5, ..)))
+    ));
+
+    let call = || -> Result<Tensor<f64>, Error> {
+        let compiled = (&x + &x).compile(&[&x]);
+        Ok(compiled.call(&[&x, &x])?)
+    };
+    assert!(matches!(
+        call(),
+        Err(Error::Call(CallError::WrongArity(1, 2)))
+    ));
+
+    let io = || -> Result<String, Error> { Ok(std::fs::read_to_string("/no/such/tensor")?) };
+    let err = io().err().unwrap();
+    assert!(matches!(err, Error::Io(_)));
+    assert!(err.source().is_some());
+
+    #[cfg(feature = "mmap")]
+    {
+        let npz = || -> Result<(), Error> {
+            simple_tensor::tensor::load_npz("/no/such/archive.npz")?;
+            Ok(())
+        };
+        assert!(matches!(npz(), Err(Error::Io(_))));
+
+        let npy = || -> Result<(), Error> {
+            simple_tensor::tensor::TensorData::<f64>::open_npy_mmap("/no/such/tensor.npy")?;
+            Ok(())
+        };
+        assert!(matches!(npy(), Err(Error::Io(_))));
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        use simple_tensor::tensor::{ParallelismConfig, materialize_all};
+
+        let all = || -> Result<Vec<Tensor<f64>>, Error> {
+            let promises = [&x + 1.0, &x * 2.0];
+            materialize_all(&promises, ParallelismConfig::CurrentThread)
+                .into_iter()
+                .map(|result| Ok(result?))
+                .collect()
+        };
+        assert_eq!(all().unwrap().len(), 2);
+    }
+}
+
+#[test]
+fn display_and_source_follow_the_wrapped_error() {
+    let x = arange(&[2, 3]);
+    let op = x.try_add(&arange(&[3, 2])).err().unwrap();
+    let message = op.to_string();
+    let err = Error::from(op);
+
+    assert_eq!(err.to_string(), message);
+    assert!(err.source().is_none());
+    assert_eq!(err.op_error().unwrap().context().unwrap().op(), "Add");
+
+    let parse = Tensor::<f64>::from_str_data("[1 2]").err().unwrap();
+    let err = Error::from(parse.clone());
+    assert_eq!(err.to_string(), parse.to_string());
+    assert!(err.op_error().is_none());
+
+    // IoError::Io shows the message of the io::Error it is the source of.
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    let err = Error::from(io);
+    assert_eq!(chain(&err), ["no such file", "no such file"]);
+}
+
+#[test]
+fn context_wraps_the_error_with_a_message() {
+    let load = |path: &str| -> Result<Tensor<f64>, Error> {
+        let text = std::fs::read_to_string(path).context("reading the file")?;
+        Tensor::from_str_data(&text).with_context(|| format!("loading checkpoint '{}'", path))
+    };
+    let err = load("/no/such/checkpoint").err().unwrap();
+
+    assert_eq!(err.to_string(), "reading the file");
+    assert_eq!(chain(&err)[1], chain(err.cause())[0]);
+    assert!(matches!(err.cause(), Error::Io(_)));
+
+    // Contexts stack, from the outermost to the innermost.
+    let err = Tensor::<f64>::from_str_data("[[1, 2], [3]]")
+        .context("parsing the weights")
+        .context("loading checkpoint 'foo'")
+        .err()
+        .unwrap();
+    assert_eq!(
+        chain(&err),
+        [
+            "loading checkpoint 'foo'",
+            "parsing the weights",
+            "the list [1] at byte 9 has 1 elements, but the lists before it have 2",
+        ]
+    );
+    assert!(matches!(
+        err.cause(),
+        Error::Parse(ParseError::RaggedLength(9, ..))
+    ));
+
+    // The message of with_context() is only built for errors.
+    let mut built = false;
+    let x = Tensor::<f64>::from_str_data("[1, 2]").with_context(|| {
+        built = true;
+        "never shown"
+    });
+    assert_eq!(values(&x.unwrap()), [1.0, 2.0]);
+    assert!(!built);
+}
+
+#[test]
+fn panicking_paths_have_fallible_versions() {
+    // from_vec() does not count the elements in release builds.
+    let x = Tensor::try_from_vec(vec![1.0, 2.0, 3.0, 4.0], &[2, 2]).unwrap();
+    assert_eq!(values(&x), [1.0, 2.0, 3.0, 4.0]);
+    let err = Tensor::try_from_vec(vec![1.0; 5], &[2, 2]).err().unwrap();
+    assert!(matches!(err, OpError::ElementCount(4, 5)));
+    assert!(Tensor::<f64>::try_from_vec(vec![], &[0, 3]).is_ok());
+
+    // materialize_many() and materialize_min_memory() panic on a failed assertion.
+    let nan = Tensor::from_iter([f64::NAN, 1.0], &[2]);
+    let checked = (&nan * 2.0).assert_finite("doubled");
+    let fine = &x + 1.0;
+
+    let err = try_materialize_many(&[&fine, &checked]).err().unwrap();
+    assert!(matches!(err, OpError::AssertionFailed(_)));
+    let outs = try_materialize_many(&[&fine, &(&x * 2.0)]).unwrap();
+    assert_eq!(values(&outs[0]), [2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(values(&outs[1]), [2.0, 4.0, 6.0, 8.0]);
+
+    let err = checked.clone().try_materialize_min_memory().err().unwrap();
+    assert!(matches!(err, OpError::AssertionFailed(_)));
+    let err = checked.cache().try_materialize_min_memory().err().unwrap();
+    assert!(matches!(Error::from(err), Error::Compute(_)));
+    assert_eq!(
+        values(&fine.clone().cache().try_materialize_min_memory().unwrap()),
+        [2.0, 3.0, 4.0, 5.0]
+    );
+}
@@ -0,0 +1,111 @@
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use simple_tensor::matrix;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Tensor, TryArithmetic};
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = catch_unwind(AssertUnwindSafe(f)).expect_err("the closure should panic");
+
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast::<&str>().unwrap().to_string(),
+    }
+}
+
+fn fixture() -> (Tensor<f64>, Tensor<f64>) {
+    (
+        matrix![[1, 2, 3], [4, 5, 6]],
+        matrix![[1, 2], [3, 4], [5, 6]],
+    )
+}
+
+#[test]
+fn mismatches_print_both_shapes() {
+    let (a, b) = fixture();
+
+    let message = panic_message(|| {
+        let _ = &a + &b;
+    });
+
+    assert!(
+        message.starts_with("expected [2, 3], but got [3, 2]"),
+        "{}",
+        message
+    );
+    assert!(message.contains("in Add with inputs of shapes [2, 3] and [3, 2], rejecting input 1"));
+}
+
+#[test]
+fn labels_are_shown_in_panics() {
+    let (a, b) = fixture();
+    let scores = (&b * 2.0).label("attention_scores");
+
+    let message = panic_message(|| {
+        let _ = &a * &scores;
+    });
+
+    assert!(
+        message.ends_with("while computing Mul ← ScalarOp(label=attention_scores)"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn breadcrumb_follows_the_rejected_input() {
+    let (a, b) = fixture();
+
+    let product = (&b * &b).label("product");
+    // Both transposes fuse into a NoOp.
+    let transposed = product.transpose().transpose().label("back");
+    let residual = (&transposed + &b).label("residual");
+
+    let message = panic_message(|| {
+        let _ = &a - &residual;
+    });
+
+    assert!(
+        message.contains(
+            "while computing Sub ← Add(label=residual) ← NoOp(label=back) ← Mul(label=product)"
+        ),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn try_variants_return_the_context() {
+    let (a, b) = fixture();
+    let doubled = (&b + &b).label("doubled");
+
+    let Err(err) = a.try_div(&doubled) else {
+        panic!("the shapes do not match");
+    };
+
+    assert!(matches!(err.cause(), OpError::NotSameShape(_, _)));
+
+    let context = err.context().unwrap();
+    assert_eq!(context.op(), "Div");
+    assert_eq!(context.input(), 1);
+    assert_eq!(context.shapes(), [[2, 3].into(), [3, 2].into()]);
+    assert_eq!(
+        context.breadcrumb(),
+        ["Div".into(), "Add(label=doubled)".into()]
+    );
+
+    // Shapes that match build the op as usual.
+    let sum = a.try_add(&a).unwrap().materialize();
+    assert_eq!(
+        sum.iter().copied().collect::<Vec<_>>(),
+        [2.0, 4.0, 6.0, 8.0, 10.0, 12.0]
+    );
+}
+
+#[test]
+fn errors_that_are_not_mismatches_are_left_alone() {
+    let (a, _) = fixture();
+
+    assert!(matches!(a.flip(&[2]), Err(OpError::OutOfBoundAxes)));
+    assert!(a.flip(&[2]).err().unwrap().context().is_none());
+}
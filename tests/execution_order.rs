@@ -0,0 +1,498 @@
+mod common;
+
+use simple_tensor::tensor::{TraceRecorder, materialize_many};
+
+use common::arange_from;
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn ops_reading_a_result_twice_do_not_reuse_it() {
+    let x = arange_from(&[2, 3], 1.0);
+    let recip = x.recip();
+    let expected: Vec<f64> = (1..=6).map(|i| 2.0 / i as f64).collect();
+
+    // The second read is the last one, but the op still holds the first.
+    let sum = &recip + &recip;
+    let results = [
+        sum.clone().materialize(),
+        sum.clone().materialize_min_memory(),
+        materialize_many(&[&sum]).remove(0),
+        sum.compile(&[&x]).call(&[&x]).unwrap(),
+    ];
+
+    for result in &results {
+        assert_eq!(result.iter().copied().collect::<Vec<_>>(), expected);
+    }
+}
+
+#[cfg(not(feature = "execution_trace"))]
+#[test]
+fn nothing_is_recorded_without_the_feature() {
+    let (x, y) = (arange_from(&[4], 1.0), arange_from(&[4], 2.0));
+
+    let recorder = TraceRecorder::start();
+    (&(&x + &y) * &x).materialize();
+    assert!(recorder.finish().is_empty());
+}
+
+// The order nodes are computed in, checked on the records of a TraceRecorder.
+#[cfg(feature = "execution_trace")]
+mod traced {
+    use std::collections::{HashMap, HashSet};
+
+    use simple_tensor::tensor::{CachedTensorPromise, InputRead, Tensor, TensorId, TraceRecord};
+
+    use super::*;
+
+    // The inputs of every node of a graph built by a test, as it built it. Tensors read none.
+    type Dag = HashMap<TensorId, Vec<TensorId>>;
+
+    fn ids(records: &[TraceRecord]) -> Vec<TensorId> {
+        records.iter().map(|record| record.id()).collect()
+    }
+
+    // The nodes feeding `roots` in depth-first post-order, the inputs of every node and the
+    // roots in the order they are listed, each node the first time it is reached.
+    fn depth_first<'a>(dag: &'a Dag, roots: &'a [TensorId]) -> Vec<TensorId> {
+        fn visit(dag: &Dag, id: TensorId, seen: &mut HashSet<TensorId>, order: &mut Vec<TensorId>) {
+            if !seen.insert(id) {
+                return;
+            }
+            for &input in &dag[&id] {
+                visit(dag, input, seen, order);
+            }
+            order.push(id);
+        }
+
+        let (mut seen, mut order) = (HashSet::new(), Vec::new());
+        for &root in roots {
+            visit(dag, root, &mut seen, &mut order);
+        }
+
+        order
+    }
+
+    // Checks that `records` computed every node feeding `roots` once, after its inputs, reading
+    // them in the order the node lists them. Whether each read should move the result out is
+    // found on its own from the order of the records: only the last read of a result does,
+    // unless it is one of the roots, which are handed to the caller afterwards.
+    fn check_trace(dag: &Dag, roots: &[TensorId], records: &[TraceRecord]) {
+        let mut expected = depth_first(dag, roots);
+        let mut computed = ids(records);
+        expected.sort();
+        computed.sort();
+        assert_eq!(computed, expected, "every node is computed once");
+
+        let position: HashMap<TensorId, usize> = (records.iter().enumerate())
+            .map(|(at, record)| (record.id(), at))
+            .collect();
+
+        let mut last_read: HashMap<TensorId, (usize, usize)> = HashMap::new();
+        for (at, record) in records.iter().enumerate() {
+            let inputs: Vec<TensorId> = record.inputs().iter().map(|(id, _)| *id).collect();
+            assert_eq!(inputs, dag[&record.id()], "inputs of {:?}", record);
+
+            for (nth, &input) in inputs.iter().enumerate() {
+                assert!(
+                    position[&input] < at,
+                    "{:?} read before it is computed",
+                    input
+                );
+                last_read.insert(input, (at, nth));
+            }
+        }
+
+        for (at, record) in records.iter().enumerate() {
+            for (nth, &(input, read)) in record.inputs().iter().enumerate() {
+                let last = !roots.contains(&input) && last_read[&input] == (at, nth);
+                let expected = if last {
+                    InputRead::Consumed
+                } else {
+                    InputRead::Cloned
+                };
+
+                assert_eq!(read, expected, "read of {:?} by {:?}", input, record);
+            }
+        }
+    }
+
+    #[test]
+    fn nodes_are_computed_depth_first_in_the_order_inputs_are_listed() {
+        let (x, y) = (arange_from(&[2, 3], 1.0), arange_from(&[2, 3], 2.0));
+        let a = x.recip();
+        let b = &y * &a;
+        let root = &a + &b;
+
+        let dag = Dag::from([
+            (x.id(), vec![]),
+            (y.id(), vec![]),
+            (a.id(), vec![x.id()]),
+            (b.id(), vec![y.id(), a.id()]),
+            (root.id(), vec![a.id(), b.id()]),
+        ]);
+
+        let recorder = TraceRecorder::start();
+        root.clone().materialize();
+        let records = recorder.finish();
+
+        // `a` first, as it is the first input of the root, and only computed once.
+        assert_eq!(ids(&records), [x.id(), a.id(), y.id(), b.id(), root.id()]);
+        check_trace(&dag, &[root.id()], &records);
+
+        let ops: Vec<&str> = records.iter().map(|record| record.op()).collect();
+        assert_eq!(ops, ["Tensor", "Recip", "Tensor", "Mul", "Add"]);
+        // Read by `b` while the root still has to read it, then moved into the root.
+        assert_eq!(records[3].inputs()[1], (a.id(), InputRead::Cloned));
+        assert_eq!(
+            records[4].inputs(),
+            [(a.id(), InputRead::Consumed), (b.id(), InputRead::Consumed)]
+        );
+
+        // Several roots are walked in the order they are given as well.
+        let recorder = TraceRecorder::start();
+        materialize_many(&[&b, &a]);
+        let records = recorder.finish();
+
+        assert_eq!(ids(&records), [y.id(), x.id(), a.id(), b.id()]);
+        check_trace(&dag, &[b.id(), a.id()], &records);
+    }
+
+    #[test]
+    fn linear_chains_move_every_result_into_the_next_op() {
+        let x = arange_from(&[8], 1.0);
+        let first = x.recip();
+        let second = first.sqrt();
+        let root = second.rsqrt();
+        assert!(root.is_linear_chain());
+
+        let dag = Dag::from([
+            (x.id(), vec![]),
+            (first.id(), vec![x.id()]),
+            (second.id(), vec![first.id()]),
+            (root.id(), vec![second.id()]),
+        ]);
+
+        let root_id = root.id();
+        let recorder = TraceRecorder::start();
+        root.materialize();
+        let records = recorder.finish();
+
+        check_trace(&dag, &[root_id], &records);
+        assert_eq!(depth_first(&dag, &[root_id]), ids(&records));
+    }
+
+    #[test]
+    fn filled_caches_and_fused_inputs_read_nothing_of_their_own() {
+        use simple_tensor::tensor::NumericsMode;
+
+        let (x, y) = (arange_from(&[4], 1.0), arange_from(&[4], 2.0));
+        let product = &x * &y;
+        let product_id = product.id();
+        let cache = product.cache();
+        cache.clone().materialize();
+
+        let recorder = TraceRecorder::start();
+        (&cache + &x).materialize();
+        let records = recorder.finish();
+
+        // The product is not computed again, the cache is read like a tensor.
+        assert_eq!(records[0].id(), cache.id());
+        assert!(records[0].inputs().is_empty());
+        assert!(!ids(&records).contains(&product_id));
+
+        // The square root is computed as part of the division reading it.
+        let sqrt = y.sqrt();
+        let divided = (&x / &sqrt).with_numerics(NumericsMode::LowAccuracy);
+        let divided_id = divided.id();
+
+        let recorder = TraceRecorder::start();
+        divided.materialize();
+        let records = recorder.finish();
+
+        assert_eq!(ids(&records), [x.id(), y.id(), divided_id]);
+        assert_eq!(
+            records[2].inputs(),
+            [(x.id(), InputRead::Consumed), (y.id(), InputRead::Consumed)]
+        );
+    }
+
+    #[test]
+    fn caches_filled_after_sorting_still_read_their_inputs() {
+        use simple_tensor::tensor::CachePool;
+
+        let (x, y) = (arange_from(&[4], 1.0), arange_from(&[4], 2.0));
+        let pool = CachePool::new(1 << 20);
+
+        // The pooled cache is computed on its own before the rest of the graph,
+        // which fills `cache` after the graph was sorted with it empty.
+        let recip = x.recip();
+        let recip_id = recip.id();
+        let cache: CachedTensorPromise<f64> = recip.cache();
+        let pooled = (&cache + &y).cache_in(&pool);
+        let root = &pooled + &cache;
+        let root_id = root.id();
+
+        let recorder = TraceRecorder::start();
+        let result = root.materialize();
+        let records = recorder.finish();
+
+        assert_eq!(
+            ids(&records),
+            [pooled.id(), x.id(), recip_id, cache.id(), root_id]
+        );
+        assert!(records[0].inputs().is_empty());
+        // The reciprocal was computed for the cache, so it is moved into it, not left behind.
+        assert_eq!(records[3].inputs(), [(recip_id, InputRead::Consumed)]);
+
+        let expected: Vec<f64> = (1..=4)
+            .map(|i| (1.0 / i as f64 + (i + 1) as f64) + 1.0 / i as f64)
+            .collect();
+        assert_eq!(result.iter().copied().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn racing_threads_read_the_inputs_of_caches_they_found_empty() {
+        use std::sync::Barrier;
+        use std::thread;
+
+        let x = arange_from(&[64], 1.0);
+        let recip = x.recip();
+        let recip_id = recip.id();
+        let cache = recip.cache();
+        let barrier = Barrier::new(8);
+
+        let results: Vec<Vec<u64>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    let (x, cache, barrier) = (&x, &cache, &barrier);
+
+                    scope.spawn(move || {
+                        let root = cache + x;
+                        barrier.wait();
+
+                        let recorder = TraceRecorder::start();
+                        let result = root.materialize();
+                        let records = recorder.finish();
+
+                        // Either the cache was empty when sorted, and the reciprocal computed
+                        // for it is moved into it, or it was filled and nothing was computed.
+                        let cached = records.iter().find(|r| r.id() == cache.id()).unwrap();
+                        match cached.inputs() {
+                            [] => assert!(!ids(&records).contains(&recip_id)),
+                            inputs => assert_eq!(inputs, [(recip_id, InputRead::Consumed)]),
+                        }
+
+                        result.iter().map(|el| el.to_bits()).collect()
+                    })
+                })
+                .collect();
+
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    // Random graphs of elementwise ops over a few tensors, computed by every engine.
+    #[cfg(feature = "proptest")]
+    mod random_graphs {
+        use super::*;
+
+        use proptest::prelude::*;
+        use proptest::sample::Index;
+        use simple_tensor::tensor::TensorPromise;
+        use simple_tensor::tensor::testing::proptest_strategies::arb_tensor;
+
+        use crate::common::bits;
+
+        #[derive(Clone, Debug)]
+        enum Step {
+            // One of the tensors of the graph.
+            Tensor(Index),
+            Add(Index, Index),
+            Mul(Index, Index),
+            Sub(Index, Index),
+            Recip(Index),
+            // A cache over the reciprocal of an earlier step.
+            Cache(Index),
+        }
+
+        enum Value {
+            Tensor(Tensor<f64>),
+            Promise(TensorPromise<f64>),
+            Cache(CachedTensorPromise<f64>),
+        }
+
+        macro_rules! binary {
+            ($lhs: expr, $rhs: expr, $op: tt) => {
+                match ($lhs, $rhs) {
+                    (Value::Tensor(a), Value::Tensor(b)) => a $op b,
+                    (Value::Tensor(a), Value::Promise(b)) => a $op b,
+                    (Value::Tensor(a), Value::Cache(b)) => a $op b,
+                    (Value::Promise(a), Value::Tensor(b)) => a $op b,
+                    (Value::Promise(a), Value::Promise(b)) => a $op b,
+                    (Value::Promise(a), Value::Cache(b)) => a $op b,
+                    (Value::Cache(a), Value::Tensor(b)) => a $op b,
+                    (Value::Cache(a), Value::Promise(b)) => a $op b,
+                    (Value::Cache(a), Value::Cache(b)) => a $op b,
+                }
+            };
+        }
+
+        impl Value {
+            fn id(&self) -> TensorId {
+                match self {
+                    Value::Tensor(t) => t.id(),
+                    Value::Promise(p) => p.id(),
+                    Value::Cache(c) => c.id(),
+                }
+            }
+
+            fn recip(&self) -> TensorPromise<f64> {
+                match self {
+                    Value::Tensor(t) => t.recip(),
+                    Value::Promise(p) => p.recip(),
+                    Value::Cache(c) => c.recip(),
+                }
+            }
+        }
+
+        // The graph of `steps` over `tensors`, built anew on every call so that its caches are
+        // empty, with the promises `materialize_many()` is tested with. Its root adds the last
+        // step to the first, so it is always an op.
+        fn build(tensors: &[Tensor<f64>], steps: &[Step]) -> (Dag, Vec<TensorPromise<f64>>) {
+            let mut dag = Dag::new();
+            let mut values: Vec<Value> = vec![Value::Tensor(tensors[0].clone())];
+            dag.insert(tensors[0].id(), vec![]);
+
+            for step in steps {
+                let pick = |index: &Index| &values[index.index(values.len())];
+
+                let (value, inputs) = match step {
+                    Step::Tensor(index) => {
+                        let tensor = index.get(tensors).clone();
+                        (Value::Tensor(tensor), vec![])
+                    }
+                    Step::Add(a, b) => {
+                        let (a, b) = (pick(a), pick(b));
+                        (Value::Promise(binary!(a, b, +)), vec![a.id(), b.id()])
+                    }
+                    Step::Mul(a, b) => {
+                        let (a, b) = (pick(a), pick(b));
+                        (Value::Promise(binary!(a, b, *)), vec![a.id(), b.id()])
+                    }
+                    Step::Sub(a, b) => {
+                        let (a, b) = (pick(a), pick(b));
+                        (Value::Promise(binary!(a, b, -)), vec![a.id(), b.id()])
+                    }
+                    Step::Recip(a) => {
+                        let a = pick(a);
+                        (Value::Promise(a.recip()), vec![a.id()])
+                    }
+                    Step::Cache(a) => {
+                        let a = pick(a);
+                        let recip = a.recip();
+                        dag.insert(recip.id(), vec![a.id()]);
+                        let inputs = vec![recip.id()];
+
+                        (Value::Cache(recip.cache()), inputs)
+                    }
+                };
+
+                dag.insert(value.id(), inputs);
+                values.push(value);
+            }
+
+            let (first, last) = (&values[0], &values[values.len() - 1]);
+            let root = binary!(last, first, +);
+            dag.insert(root.id(), vec![last.id(), first.id()]);
+
+            let mut sinks: Vec<TensorPromise<f64>> = (values.into_iter().rev())
+                .filter_map(|value| match value {
+                    Value::Promise(promise) => Some(promise),
+                    _ => None,
+                })
+                .take(2)
+                .collect();
+            sinks.insert(0, root);
+
+            (dag, sinks)
+        }
+
+        // The position of every node in the order it was built, which does not depend on the
+        // ids the other tests took meanwhile.
+        fn ranks(dag: &Dag, records: &[TraceRecord]) -> Vec<usize> {
+            let mut built: Vec<TensorId> = dag.keys().copied().collect();
+            built.sort();
+
+            (records.iter())
+                .map(|record| built.binary_search(&record.id()).unwrap())
+                .collect()
+        }
+
+        fn arb_step() -> impl Strategy<Value = Step> {
+            prop_oneof![
+                any::<Index>().prop_map(Step::Tensor),
+                (any::<Index>(), any::<Index>()).prop_map(|(a, b)| Step::Add(a, b)),
+                (any::<Index>(), any::<Index>()).prop_map(|(a, b)| Step::Mul(a, b)),
+                (any::<Index>(), any::<Index>()).prop_map(|(a, b)| Step::Sub(a, b)),
+                any::<Index>().prop_map(Step::Recip),
+                any::<Index>().prop_map(Step::Cache),
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn every_engine_reads_results_until_their_last_use(
+                tensors in prop::collection::vec(arb_tensor::<f64>(Just(vec![2, 3])), 1..4),
+                steps in prop::collection::vec(arb_step(), 1..24),
+            ) {
+                // materialize() follows the depth-first order exactly.
+                let (dag, sinks) = build(&tensors, &steps);
+                let root = sinks[0].id();
+
+                let recorder = TraceRecorder::start();
+                let sequential = sinks[0].clone().materialize();
+                let records = recorder.finish();
+
+                check_trace(&dag, &[root], &records);
+                prop_assert_eq!(ids(&records), depth_first(&dag, &[root]));
+
+                // Building the graph again gives the same order.
+                let (again, sinks) = build(&tensors, &steps);
+                let recorder = TraceRecorder::start();
+                sinks[0].clone().materialize();
+                prop_assert_eq!(ranks(&again, &recorder.finish()), ranks(&dag, &records));
+
+                // materialize_min_memory() may reorder the inputs, within a topological order.
+                let (dag, sinks) = build(&tensors, &steps);
+                let root = sinks[0].id();
+
+                let recorder = TraceRecorder::start();
+                let min_memory = sinks[0].clone().materialize_min_memory();
+                let records = recorder.finish();
+
+                check_trace(&dag, &[root], &records);
+                prop_assert_eq!(bits(&min_memory), bits(&sequential));
+
+                let (again, sinks) = build(&tensors, &steps);
+                let recorder = TraceRecorder::start();
+                sinks[0].clone().materialize_min_memory();
+                prop_assert_eq!(ranks(&again, &recorder.finish()), ranks(&dag, &records));
+
+                // materialize_many() hands every root to the caller, even the ones others read.
+                let (dag, sinks) = build(&tensors, &steps);
+                let roots: Vec<TensorId> = sinks.iter().map(|sink| sink.id()).collect();
+
+                let recorder = TraceRecorder::start();
+                let many = materialize_many(&sinks.iter().collect::<Vec<_>>());
+                let records = recorder.finish();
+
+                check_trace(&dag, &roots, &records);
+                prop_assert_eq!(ids(&records), depth_first(&dag, &roots));
+                prop_assert_eq!(bits(&many[0]), bits(&sequential));
+            }
+        }
+    }
+}
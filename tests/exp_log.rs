@@ -0,0 +1,280 @@
+mod common;
+
+use std::sync::MutexGuard;
+
+use simple_tensor::tensor::{
+    ConsumableTensor, Dimension, Tensor, TensorPromise, set_exp_log_fusion,
+};
+
+use common::{bits, values};
+
+// The fusion is global, so the tests building `exp(x) - 1` or `ln(x + 1)` run one at a
+// time, each starting with it disabled.
+fn lock_globals() -> MutexGuard<'static, ()> {
+    let guard = common::lock_globals();
+    set_exp_log_fusion(false);
+
+    guard
+}
+
+type Graph<'a> = dyn Fn() -> TensorPromise<f64> + 'a;
+type Reference = fn(f64) -> f64;
+
+// Whether `a` is within `max` ulps of `b`, NaNs matching NaNs and infinities themselves.
+fn within_ulps(a: f64, b: f64, max: u64) -> bool {
+    if a.is_nan() || b.is_nan() {
+        return a.is_nan() && b.is_nan();
+    }
+
+    a == b
+        || a.is_finite()
+            && b.is_finite()
+            && a.is_sign_negative() == b.is_sign_negative()
+            && a.to_bits().abs_diff(b.to_bits()) <= max
+}
+
+fn tiny() -> Tensor<f64> {
+    Tensor::from_vec(vec![1e-12, -1e-12, 3e-9, 1e-300, -2.5e-16], &[5])
+}
+
+// Values of every sign and magnitude, denormals, zeros, infinities and NaN.
+fn grid() -> Vec<f64> {
+    let mut grid = vec![
+        0.0,
+        -0.0,
+        f64::from_bits(1),
+        -f64::from_bits(12345),
+        f64::MIN_POSITIVE,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NAN,
+        1.0,
+        -1.0,
+        2.0,
+        10.0,
+        1024.0,
+        -1075.0,
+    ];
+    for exponent in -20..=3 {
+        let x = 1.2345 * 10f64.powi(exponent);
+        grid.extend([x, -x, 0.5 + x]);
+    }
+
+    grid
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn expm1_and_log1p_keep_the_bits_of_tiny_arguments() {
+    let _globals = lock_globals();
+    let x = tiny();
+
+    // The first terms of the series are exact to an ulp this close to 0.
+    let expm1: Vec<f64> = x.iter().map(|&x| x + x * x / 2.0).collect();
+    let log1p: Vec<f64> = x.iter().map(|&x| x - x * x / 2.0).collect();
+
+    let direct = values(&x.expm1().materialize());
+    let naive = values(&(x.exp() - 1.0).materialize());
+    for ((&got, &naive), &expected) in direct.iter().zip(&naive).zip(&expm1) {
+        assert!(within_ulps(got, expected, 1), "{:e} {:e}", got, expected);
+        assert!(!within_ulps(naive, expected, 1000), "{:e}", naive);
+    }
+
+    let direct = values(&x.log1p().materialize());
+    let naive = values(&(&x + 1.0).ln().materialize());
+    for ((&got, &naive), &expected) in direct.iter().zip(&naive).zip(&log1p) {
+        assert!(within_ulps(got, expected, 1), "{:e} {:e}", got, expected);
+        assert!(!within_ulps(naive, expected, 1000), "{:e}", naive);
+    }
+
+    // Rewritten, the compositions are as exact.
+    set_exp_log_fusion(true);
+    let fused_expm1 = (x.exp() - 1.0).materialize();
+    let fused_log1p = (&x + 1.0).ln().materialize();
+    set_exp_log_fusion(false);
+
+    assert_eq!(bits(&fused_expm1), bits(&x.expm1().materialize()));
+    assert_eq!(bits(&fused_log1p), bits(&x.log1p().materialize()));
+}
+
+#[test]
+fn the_rewrite_needs_a_single_reader_and_the_fusion() {
+    let _globals = lock_globals();
+    let x = tiny();
+    let ones = Tensor::from_vec(vec![1.0; 5], &[5]);
+
+    let e = x.exp().materialize();
+    let shifted = (&x + 1.0).materialize();
+    let expm1 = bits(&x.expm1().materialize());
+    let log1p = bits(&x.log1p().materialize());
+    let naive_expm1 = bits(&(&e - 1.0).materialize());
+    let naive_log1p = bits(&shifted.ln().materialize());
+    assert_ne!(expm1, naive_expm1);
+    assert_ne!(log1p, naive_log1p);
+
+    // Linear chains, and graphs computed node by node, either way of writing the constant.
+    let rewritten: [(&Graph, &[u64]); 6] = [
+        (&|| x.exp() - 1.0, &expm1),
+        (&|| x.exp() + -1.0, &expm1),
+        (&|| &(x.exp() - 1.0) * &ones, &expm1),
+        (&|| (&x + 1.0).ln(), &log1p),
+        (&|| (&x - -1.0).ln(), &log1p),
+        (&|| &(&x + 1.0).ln() * &ones, &log1p),
+    ];
+
+    for (graph, expected) in rewritten {
+        set_exp_log_fusion(true);
+        assert_eq!(bits(&graph().materialize()), expected);
+        set_exp_log_fusion(false);
+
+        let naive = if expected == expm1 {
+            &naive_expm1
+        } else {
+            &naive_log1p
+        };
+        assert_eq!(&bits(&graph().materialize()), naive);
+    }
+
+    set_exp_log_fusion(true);
+
+    // The exponential and the sum are read by the Mul as well.
+    let exp = x.exp();
+    let both = &(&exp - 1.0) * &exp;
+    let expected = (&(&e - 1.0).materialize() * &e).materialize();
+    assert_eq!(bits(&both.materialize()), bits(&expected));
+
+    let sum = &x + 1.0;
+    let both = &sum.ln() * &sum;
+    let expected = (&shifted.ln().materialize() * &shifted).materialize();
+    assert_eq!(bits(&both.materialize()), bits(&expected));
+
+    // Other constants, and an exponential scaled before the subtraction.
+    assert_eq!(
+        bits(&(x.exp() - 2.0).materialize()),
+        bits(&(&e - 2.0).materialize())
+    );
+    assert_eq!(
+        bits(&(&x + 2.0).ln().materialize()),
+        bits(&(&x + 2.0).materialize().ln().materialize())
+    );
+    assert_eq!(
+        bits(&(x.exp() * 3.0 - 1.0).materialize()),
+        bits(&(&e * 3.0 - 1.0).materialize())
+    );
+
+    set_exp_log_fusion(false);
+}
+
+#[test]
+fn every_function_matches_the_standard_library() {
+    let grid = grid();
+    let x = Tensor::from_vec(grid.clone(), &[grid.len()]);
+
+    let functions: [(&str, TensorPromise<f64>, Reference); 7] = [
+        ("exp", x.exp(), f64::exp),
+        ("ln", x.ln(), f64::ln),
+        ("expm1", x.expm1(), f64::exp_m1),
+        ("log1p", x.log1p(), f64::ln_1p),
+        ("log2", x.log2(), f64::log2),
+        ("log10", x.log10(), f64::log10),
+        ("exp2", x.exp2(), f64::exp2),
+    ];
+
+    for (name, promise, reference) in functions {
+        let result = promise.materialize();
+        for (&x, &got) in grid.iter().zip(result.iter()) {
+            assert!(
+                within_ulps(got, reference(x), 1),
+                "{}({:e}) = {:e}, expected {:e}",
+                name,
+                x,
+                got,
+                reference(x)
+            );
+        }
+    }
+
+    // Exact results at the edges of every domain.
+    let edges = Tensor::from_vec(vec![0.0, -0.0, 1.0, -1.0, f64::INFINITY], &[5]);
+    let ln = values(&edges.ln().materialize());
+    assert_eq!(ln[..3], [f64::NEG_INFINITY, f64::NEG_INFINITY, 0.0]);
+    assert!(ln[3].is_nan());
+    assert_eq!(values(&edges.log1p().materialize())[3], f64::NEG_INFINITY);
+    let expm1 = values(&edges.expm1().materialize());
+    assert_eq!(
+        (expm1[0].to_bits(), expm1[1].to_bits()),
+        (0, (-0f64).to_bits())
+    );
+    assert_eq!(expm1[4], f64::INFINITY);
+
+    let powers = Tensor::from_vec(vec![8.0, 0.125, 10.0, -3.0], &[4]);
+    assert_eq!(values(&powers.log2().materialize())[..2], [3.0, -3.0]);
+    assert_eq!(values(&powers.exp2().materialize())[2..], [1024.0, 0.125]);
+}
+
+#[test]
+fn strided_inputs_give_the_values_of_dense_ones() {
+    let _globals = lock_globals();
+    let x = Tensor::from_iter((0..48).map(|i| (i as f64 - 20.0) * 0.37), &[6, 8]);
+
+    // Transposed, a window, reversed columns and a broadcast row.
+    let views = [
+        x.as_strided(&[8, 6], &[1, 8], 0).unwrap(),
+        x.as_strided(&[4, 5], &[8, 1], 10).unwrap(),
+        x.as_strided(&[4, 3], &[9, -2], 10).unwrap(),
+        x.as_strided(&[3, 8], &[0, 1], 16).unwrap(),
+    ];
+
+    type Op = fn(&Tensor<f64>) -> TensorPromise<f64>;
+    let ops: [Op; 7] = [
+        |t| t.exp(),
+        |t| t.ln(),
+        |t| t.expm1(),
+        |t| t.log1p(),
+        |t| t.log2(),
+        |t| t.log10(),
+        |t| t.exp2(),
+    ];
+
+    for view in &views {
+        let dense = Tensor::from_iter(view.iter().copied(), view.shape());
+
+        for op in ops {
+            assert_eq!(
+                bits(&op(view).materialize()),
+                bits(&op(&dense).materialize())
+            );
+        }
+
+        set_exp_log_fusion(true);
+        let fused = (view.exp() - 1.0).materialize();
+        set_exp_log_fusion(false);
+        assert_eq!(bits(&fused), bits(&dense.expm1().materialize()));
+    }
+}
+
+#[test]
+fn consumed_tensors_have_every_function() {
+    let x = tiny();
+    let fresh = || Tensor::from_iter(x.iter().copied(), x.shape());
+
+    type Op = fn(&Tensor<f64>) -> TensorPromise<f64>;
+    type ConsumedOp = fn(ConsumableTensor<f64>) -> TensorPromise<f64>;
+    let ops: [(Op, ConsumedOp); 7] = [
+        (|t| t.exp(), |t| t.exp()),
+        (|t| t.ln(), |t| t.ln()),
+        (|t| t.expm1(), |t| t.expm1()),
+        (|t| t.log1p(), |t| t.log1p()),
+        (|t| t.log2(), |t| t.log2()),
+        (|t| t.log10(), |t| t.log10()),
+        (|t| t.exp2(), |t| t.exp2()),
+    ];
+
+    for (op, consumed_op) in ops {
+        assert_eq!(
+            bits(&consumed_op(fresh().consume()).materialize()),
+            bits(&op(&x).materialize())
+        );
+    }
+}
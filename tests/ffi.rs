@@ -0,0 +1,139 @@
+mod common;
+
+use std::mem::ManuallyDrop;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use simple_tensor::s;
+use simple_tensor::tensor::Tensor;
+use simple_tensor::tensor::errors::OpError;
+
+use common::values;
+
+// Hands a Vec over as if it were memory owned by another language. The returned
+// counter is increased every time the deleter frees it.
+fn wrap_vec(data: Vec<f64>, shape: &[usize]) -> (Tensor<f64>, Arc<AtomicUsize>) {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let counter = drops.clone();
+
+    let mut data = ManuallyDrop::new(data);
+    let (ptr, len, capacity) = (data.as_mut_ptr(), data.len(), data.capacity());
+
+    let deleter = Box::new(move |ptr: *mut f64, len: usize| {
+        drop(unsafe { Vec::from_raw_parts(ptr, len, capacity) });
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+
+    let tensor = unsafe { Tensor::from_raw_parts(ptr, len, shape, Some(deleter)) };
+
+    (tensor, drops)
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn graph_ops_over_external_memory() {
+    let (t, drops) = wrap_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3]);
+
+    assert_eq!(values(&t), [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let other = Tensor::from_scalar(10.0, &[2, 3]);
+    let sum = ((&t * 2.0) + &other).materialize();
+    assert_eq!(values(&sum), [12.0, 14.0, 16.0, 18.0, 20.0, 22.0]);
+
+    let transposed = t.transpose().as_contiguous().materialize();
+    assert_eq!(values(&transposed), [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+
+    let column = t.slice(s![.., 1..2]).unwrap().materialize();
+    assert_eq!(values(&column), [2.0, 5.0]);
+
+    drop(t);
+    assert_eq!(
+        drops.load(Ordering::SeqCst),
+        0,
+        "the slice still reads the memory"
+    );
+
+    drop(column);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn deleter_runs_once_after_the_last_reference() {
+    let (t, drops) = wrap_vec(vec![1.0; 8], &[8]);
+
+    let clone = t.clone();
+    let detached = t.clone_detached();
+    let promise = &t + 1.0;
+
+    drop(t);
+    drop(clone);
+    drop(detached);
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+    assert_eq!(values(&promise.materialize()), [2.0; 8]);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn with_raw_exposes_contiguous_buffers() {
+    let t = Tensor::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3]);
+
+    let sum = t
+        .with_raw(|ptr, len| {
+            unsafe { std::slice::from_raw_parts(ptr, len) }
+                .iter()
+                .sum::<f64>()
+        })
+        .unwrap();
+    assert_eq!(sum, 21.0);
+
+    // A contiguous slice starts at its offset.
+    let row = t.slice(s![1..2, ..]).unwrap().materialize();
+    let first = row.with_raw(|ptr, len| (unsafe { *ptr }, len)).unwrap();
+    assert_eq!(first, (4.0, 3));
+
+    let view = t.transpose().materialize();
+    assert!(matches!(
+        view.with_raw(|_, _| ()),
+        Err(OpError::NonContiguousBuffer)
+    ));
+}
+
+#[test]
+fn with_raw_mut_writes_are_visible() {
+    let (mut t, drops) = wrap_vec(vec![0.0; 4], &[2, 2]);
+
+    t.with_raw_mut(|ptr, len| {
+        let buffer = unsafe { std::slice::from_raw_parts_mut(ptr, len) };
+
+        for (i, el) in buffer.iter_mut().enumerate() {
+            *el = i as f64;
+        }
+    })
+    .unwrap();
+
+    assert_eq!(values(&t), [0.0, 1.0, 2.0, 3.0]);
+    assert_eq!(values(&(&t + 1.0).materialize()), [1.0, 2.0, 3.0, 4.0]);
+
+    // Nothing may observe the buffer changing under it, so a shared tensor is copied.
+    let shared = t.clone_detached();
+    t.with_raw_mut(|ptr, len| unsafe { std::slice::from_raw_parts_mut(ptr, len) }.fill(9.0))
+        .unwrap();
+    assert_eq!(values(&t), [9.0; 4]);
+    assert_eq!(values(&shared), [0.0, 1.0, 2.0, 3.0]);
+
+    // The copy owns its memory, the external buffer goes away with the last reader.
+    drop(t);
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+    drop(shared);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[should_panic(expected = "the shape does not match the number of elements")]
+fn from_raw_parts_checks_the_shape() {
+    let mut data = vec![0.0; 4];
+
+    let _ = unsafe { Tensor::from_raw_parts(data.as_mut_ptr(), data.len(), &[3], None) };
+}
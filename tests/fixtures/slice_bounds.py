@@ -0,0 +1,88 @@
+"""Generates slice_bounds.txt, the expected result of slicing an axis of length n with
+every kind of bound, read by tests/negative_indexes.rs.
+
+    python3 tests/fixtures/slice_bounds.py > tests/fixtures/slice_bounds.txt
+
+The positions come from slicing list(range(n)), which follows the same rules as basic
+slicing of numpy.arange(n). Where they differ from s!, the bounds are resolved first:
+a bound outside of -n..=n is an error instead of being clamped, an empty range is an
+error, and a negative step walks the positions of the range backwards instead of
+swapping the meaning of its bounds, like numpy.arange(n)[lo:hi][::step].
+
+Each line is `n kind start end step` followed by the outcome: `=` and the positions,
+`past B` or `before B` for the first bound out of the axis, or `empty`.
+"""
+
+I32_MIN, I32_MAX = -(2**31), 2**31 - 1
+LENGTHS = [1, 3, 5]
+STEPS = [1, 2, -1, -2]
+
+
+def bounds(n):
+    # In range, on the boundaries, one past them and the extremes of an i32.
+    return sorted({0, 1, n - 1, n, n + 1, -1, -n, -n - 1, I32_MIN, I32_MAX})
+
+
+def out_of_axis(bound, n):
+    if bound > n:
+        return f"past {bound}"
+    if bound < -n:
+        return f"before {bound}"
+    return None
+
+
+def position(bound, n):
+    return bound if bound >= 0 else n + bound
+
+
+def select(n, start, end, step):
+    for bound in (start, end):
+        if bound is not None and (error := out_of_axis(bound, n)):
+            return error
+
+    lo = 0 if start is None else position(start, n)
+    hi = n if end is None else position(end, n)
+    if step < 0 and lo > hi:
+        lo, hi = hi, lo
+    if hi <= lo:
+        return "empty"
+
+    positions = list(range(n))[lo:hi][::step]
+    if step > 0:
+        # Python clamps nothing here, as the bounds are within the axis.
+        assert positions == list(range(n))[slice(start, end, step)]
+
+    return "= " + " ".join(map(str, positions))
+
+
+def index(n, i):
+    if i >= n:
+        return f"past {i + 1}"
+    if i < -n:
+        return f"before {i}"
+
+    return f"= {list(range(n))[i]}"
+
+
+def fmt(bound):
+    return "_" if bound is None else str(bound)
+
+
+def main():
+    for n in LENGTHS:
+        cases = [("full", None, None)]
+        cases += [("from", start, None) for start in bounds(n)]
+        cases += [("to", None, end) for end in bounds(n)]
+        cases += [("range", start, end) for start in bounds(n) for end in bounds(n)]
+
+        for kind, start, end in cases:
+            for step in STEPS:
+                outcome = select(n, start, end, step)
+                print(f"{n} {kind} {fmt(start)} {fmt(end)} {step} {outcome}")
+
+        for i in bounds(n):
+            print(f"{n} index {i} _ 1 {index(n, i)}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,128 @@
+mod common;
+
+use simple_tensor::s;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+
+use common::{arange, values};
+
+// The row-major elements of arange([2, 3, 4]) reversed along `axes`.
+fn flipped_arange(axes: &[usize]) -> Vec<f64> {
+    let shape = [2, 3, 4];
+
+    (0..24)
+        .map(|i| {
+            let mut index = [i / 12, i / 4 % 3, i % 4];
+            for &axis in axes {
+                index[axis] = shape[axis] - 1 - index[axis];
+            }
+
+            (index[0] * 12 + index[1] * 4 + index[2]) as f64
+        })
+        .collect()
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn a_vector_is_reversed() {
+    let x = arange(&[5]);
+
+    assert_eq!(
+        values(&x.flip_view(&[0]).unwrap()),
+        [4.0, 3.0, 2.0, 1.0, 0.0]
+    );
+    assert_eq!(
+        values(&x.flip(&[0]).unwrap().materialize()),
+        [4.0, 3.0, 2.0, 1.0, 0.0]
+    );
+
+    // Nothing to reverse along no axis, or in an empty tensor.
+    assert_eq!(values(&x.flip_view(&[]).unwrap()), values(&x));
+    assert!(values(&arange(&[0]).flip_view(&[0]).unwrap()).is_empty());
+}
+
+#[test]
+fn every_combination_of_axes_matches_the_oracle() {
+    let x = arange(&[2, 3, 4]);
+
+    let combinations: [&[usize]; 7] = [&[0], &[1], &[2], &[0, 1], &[0, 2], &[2, 1], &[0, 1, 2]];
+    for axes in combinations {
+        let expected = flipped_arange(axes);
+
+        let view = x.flip_view(axes).unwrap();
+        assert_eq!(view.shape(), &[2, 3, 4]);
+        assert_eq!(values(&view), expected, "view along {axes:?}");
+
+        let lazy = x.flip(axes).unwrap().materialize();
+        assert_eq!(lazy.shape(), &[2, 3, 4]);
+        assert_eq!(values(&lazy), expected, "op along {axes:?}");
+    }
+}
+
+#[test]
+fn flipping_twice_gives_the_original() {
+    let x = arange(&[2, 3, 4]);
+
+    for axes in [&[1][..], &[0, 2], &[0, 1, 2]] {
+        let back = x.flip_view(axes).unwrap().flip_view(axes).unwrap();
+        assert_eq!(values(&back), values(&x));
+
+        let back = x.flip(axes).unwrap().flip(axes).unwrap().materialize();
+        assert_eq!(values(&back), values(&x));
+    }
+
+    // Flipping the axes one at a time is flipping them all at once.
+    let one_by_one = x.flip_view(&[0]).unwrap().flip_view(&[2]).unwrap();
+    assert_eq!(values(&one_by_one), flipped_arange(&[0, 2]));
+}
+
+#[test]
+fn sliced_views_are_flipped_within_their_elements() {
+    // Rows 1 and 2, every other column from the second one: [[5, 7], [9, 11]].
+    let x = arange(&[3, 4]);
+    let sliced = x.slice(s![1..3, 1..;2]).unwrap().materialize();
+    let view = x.as_strided(&[2, 2], &[4, 2], 5).unwrap();
+    assert_eq!(values(&view), values(&sliced));
+
+    assert_eq!(
+        values(&view.flip_view(&[0]).unwrap()),
+        [9.0, 11.0, 5.0, 7.0]
+    );
+    assert_eq!(
+        values(&view.flip_view(&[1]).unwrap()),
+        [7.0, 5.0, 11.0, 9.0]
+    );
+    assert_eq!(
+        values(&view.flip(&[0, 1]).unwrap().materialize()),
+        [11.0, 9.0, 7.0, 5.0]
+    );
+
+    // The original is left as it was.
+    assert_eq!(values(&x), values(&arange(&[3, 4])));
+}
+
+#[test]
+fn flipped_views_are_displayed_in_their_order() {
+    let x = arange(&[2, 3]);
+    let view = x.flip_view(&[0, 1]).unwrap();
+    let dense = Tensor::from_vec(vec![5.0, 4.0, 3.0, 2.0, 1.0, 0.0], &[2, 3]);
+
+    assert_eq!(format!("{}", view), format!("{}", dense));
+    assert_ne!(format!("{}", view), format!("{}", x));
+}
+
+#[test]
+fn axes_must_exist_and_be_distinct() {
+    let x = arange(&[2, 3]);
+
+    let err = x.flip_view(&[2]).unwrap_err();
+    assert!(matches!(err, OpError::OutOfBoundAxes), "{err:?}");
+    let err = x.flip(&[0, 5]).err().unwrap();
+    assert!(matches!(err, OpError::OutOfBoundAxes), "{err:?}");
+
+    let err = x.flip_view(&[1, 1]).unwrap_err();
+    assert!(matches!(err, OpError::RepeatedAxis(1)), "{err:?}");
+    let err = x.flip(&[0, 1, 0]).err().unwrap();
+    assert!(matches!(err, OpError::RepeatedAxis(0)), "{err:?}");
+}
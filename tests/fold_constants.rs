@@ -0,0 +1,159 @@
+mod common;
+
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::promise::DEFAULT_FOLD_LIMIT_BYTES;
+use simple_tensor::tensor::{Schedule, Tensor, TensorPromise};
+
+use common::{arange, bits};
+
+fn nodes(promise: &TensorPromise<f64>) -> usize {
+    promise.graph_stats(Schedule::Topological).nodes()
+}
+
+// Number of ops computed while running `f`.
+fn ops_of<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = computed_op_count();
+    let result = f();
+
+    (result, computed_op_count() - before)
+}
+
+// `exp(a * b + a) + x`, the first term only reading `a` and `b`.
+fn graph(a: &Tensor<f64>, b: &Tensor<f64>, x: &Tensor<f64>) -> TensorPromise<f64> {
+    ((a * b) + a).exp() + x
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn constant_subexpressions_are_computed_once() {
+    let a = Tensor::constant(vec![1.0, 2.0, 3.0, 4.0], &[4]);
+    let b = Tensor::constant_from_scalar(0.5, &[4]);
+    let x = arange(&[4]);
+
+    let promise = graph(&a, &b, &x);
+    let (folded, folding_ops) = ops_of(|| promise.fold_constants());
+
+    // The constant term is computed while folding and replaced by a tensor.
+    assert!(folding_ops > 0);
+    assert!(nodes(&folded) < nodes(&promise));
+
+    let (expected, ops) = ops_of(|| promise.clone().materialize());
+    let (got, folded_ops) = ops_of(|| folded.clone().materialize());
+    assert_eq!(bits(&got), bits(&expected));
+    assert_eq!(folded_ops, 1);
+    assert!(ops > folded_ops);
+
+    // Materializing again only computes the op reading `x`.
+    let (again, again_ops) = ops_of(|| folded.materialize());
+    assert_eq!(bits(&again), bits(&expected));
+    assert_eq!(again_ops, 1);
+}
+
+#[test]
+fn tensors_not_marked_constant_are_never_folded() {
+    let a = Tensor::from_vec(vec![1.0, 2.0, 3.0, 4.0], &[4]);
+    let b = Tensor::constant_from_scalar(0.5, &[4]);
+    let x = arange(&[4]);
+
+    let promise = graph(&a, &b, &x);
+    let (folded, folding_ops) = ops_of(|| promise.fold_constants());
+
+    assert_eq!(folding_ops, 0);
+    assert_eq!(nodes(&folded), nodes(&promise));
+
+    let (expected, ops) = ops_of(|| promise.materialize());
+    let (got, folded_ops) = ops_of(|| folded.materialize());
+    assert_eq!(bits(&got), bits(&expected));
+    assert_eq!(folded_ops, ops);
+}
+
+#[test]
+fn results_over_the_limit_are_not_folded() {
+    // 1024 elements, 8 KiB of results.
+    let c = Tensor::constant_from_scalar(1.0, &[1024]);
+    let promise = (&c + 1.0) * &arange(&[1024]);
+
+    let (folded, folding_ops) = ops_of(|| promise.fold_constants_with_limit(8 * 1024 - 1));
+    assert_eq!(folding_ops, 0);
+    assert_eq!(nodes(&folded), nodes(&promise));
+
+    let (folded, folding_ops) = ops_of(|| promise.fold_constants_with_limit(8 * 1024));
+    assert_eq!(folding_ops, 1);
+    assert!(nodes(&folded) < nodes(&promise));
+    assert_eq!(bits(&folded.materialize()), bits(&promise.materialize()));
+
+    // A small constant tiled past the default limit is left to materialize(),
+    // without computing it.
+    let small = Tensor::constant_from_scalar(1.0, &[8]);
+    let repeats = DEFAULT_FOLD_LIMIT_BYTES / (8 * size_of::<f64>()) + 1;
+    let huge = small.tile(&[repeats]);
+
+    let (folded, folding_ops) = ops_of(|| huge.fold_constants());
+    assert_eq!(folding_ops, 0);
+    assert_eq!(nodes(&folded), nodes(&huge));
+
+    // While the same one below it is.
+    let tiled = small.tile(&[4]);
+    let (folded, folding_ops) = ops_of(|| tiled.fold_constants());
+    assert_eq!(folding_ops, 1);
+    assert_eq!(bits(&folded.materialize()), bits(&tiled.materialize()));
+}
+
+#[test]
+fn caches_inside_the_folded_subgraph_are_replaced() {
+    let a = Tensor::constant(vec![1.0, 2.0, 3.0], &[3]);
+    let b = Tensor::constant_from_scalar(2.0, &[3]);
+
+    // An empty cache is computed into the folded result, without being filled.
+    let inner = (&a * &b).cache();
+    let promise = &inner + 1.0;
+    let (folded, empty_ops) = ops_of(|| promise.fold_constants());
+    assert!(!inner.is_cached());
+
+    let (got, ops) = ops_of(|| folded.materialize());
+    assert_eq!(
+        bits(&got),
+        bits(&Tensor::from_vec(vec![3.0, 5.0, 7.0], &[3]))
+    );
+    // Only the copy of the folded result is left.
+    assert_eq!(ops, 1);
+
+    // A filled one is read instead of computed again.
+    (&inner * 1.0).materialize();
+    assert!(inner.is_cached());
+
+    let (folded, filled_ops) = ops_of(|| (&inner + 1.0).fold_constants());
+    assert!(filled_ops < empty_ops, "{filled_ops} {empty_ops}");
+    assert_eq!(bits(&folded.materialize()), bits(&got));
+}
+
+#[test]
+fn caches_above_the_folded_subgraph_are_kept() {
+    let a = Tensor::constant(vec![1.0, 2.0, 3.0], &[3]);
+    let b = Tensor::constant_from_scalar(2.0, &[3]);
+    let x = arange(&[3]);
+
+    // The folded copy of an empty cache computes its node once, like the original.
+    // Its ops are the addition of `x` and the NoOp of the cache.
+    let above = ((&a * &b) + &x).cache();
+    let promise = &above * 2.0;
+    let folded = promise.fold_constants();
+
+    let (first, first_ops) = ops_of(|| folded.clone().materialize());
+    let (second, second_ops) = ops_of(|| folded.materialize());
+    assert_eq!(bits(&first), bits(&second));
+    assert_eq!(bits(&first), bits(&promise.materialize()));
+    assert_eq!((first_ops, second_ops), (3, 1));
+
+    // A filled one is kept as it is, and its subgraph is never read again.
+    let above = ((&a * &b) + &x).cache();
+    (&above * 1.0).materialize();
+
+    let folded = (&above * 2.0).fold_constants();
+    assert!(above.is_cached());
+
+    let (got, ops) = ops_of(|| folded.materialize());
+    assert_eq!(bits(&got), bits(&first));
+    assert_eq!(ops, 1);
+}
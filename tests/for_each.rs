@@ -0,0 +1,115 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{s, ss};
+
+use common::arange;
+
+// Counts the allocations of the current thread, as the tests run in parallel.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations_in(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+// Contiguous, transposed, sliced with an offset, and reversed views of the same tensor.
+// Only the first one is contiguous, and only the sliced one has contiguous rows.
+fn views() -> Vec<Tensor<f64>> {
+    let x = arange(&[4, 5, 6]);
+
+    vec![
+        x.clone(),
+        x.transpose_axes(&[2, 0, 1]).unwrap().materialize(),
+        x.slice(s![1..3, 1..4, 2..5]).unwrap().materialize(),
+        x.step_slice(ss![.., .., 5..0;-2]).unwrap().materialize(),
+    ]
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn fold_matches_the_iterator() {
+    for view in views() {
+        let sum = view.fold(0.0, |acc, el| acc + el);
+        assert_eq!(sum, view.iter().sum::<f64>());
+
+        let mut seen = Vec::new();
+        view.for_each(|el| seen.push(el));
+        assert_eq!(seen, view.iter().copied().collect::<Vec<_>>());
+        assert_eq!(seen.len(), view.len());
+    }
+}
+
+#[test]
+fn zip_over_different_strides() {
+    let x = arange(&[6, 4]);
+    let y = arange(&[4, 6])
+        .transpose_axes(&[1, 0])
+        .unwrap()
+        .materialize();
+    let z = arange(&[6, 8]).slice(s![.., 2..6]).unwrap().materialize();
+
+    for (lhs, rhs) in [(&x, &y), (&y, &z), (&z, &x)] {
+        let mut pairs = Vec::new();
+        lhs.zip_for_each(rhs, |a, b| pairs.push((a, b))).unwrap();
+
+        let expected: Vec<(f64, f64)> = lhs.iter().copied().zip(rhs.iter().copied()).collect();
+        assert_eq!(pairs, expected);
+    }
+}
+
+#[test]
+fn zip_checks_the_shape() {
+    let (x, y) = (arange(&[2, 3]), arange(&[3, 2]));
+
+    let mut calls = 0;
+    let err = x.zip_for_each(&y, |_, _| calls += 1).err().unwrap();
+
+    assert!(matches!(err, OpError::NotSameShape(_, _)));
+    assert_eq!(calls, 0);
+}
+
+#[test]
+fn nothing_is_allocated() {
+    for view in views() {
+        let mut count = 0;
+
+        let allocations = allocations_in(|| {
+            let sum = view.fold(0.0, |acc, el| acc + el);
+            view.for_each(|_| count += 1);
+            view.zip_for_each(&view, |a, b| assert_eq!(a, b)).unwrap();
+            assert!(sum >= 0.0);
+        });
+
+        assert_eq!(allocations, 0);
+        assert_eq!(count, view.len());
+    }
+
+    // The strided iterator does allocate its counter.
+    let x = &views()[1];
+    assert!(allocations_in(|| assert!(x.iter().count() > 0)) > 0);
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::geometry::{angle_between, cross, normalize, project_onto};
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+fn assert_close(got: f64, expected: f64) {
+    assert!(
+        (got - expected).abs() <= 1e-12 * expected.abs().max(1.0),
+        "got {} expected {}",
+        got,
+        expected
+    );
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn cross_products_match_hand_values() {
+    let x = vector![1, 0, 0];
+    let y = vector![0, 1, 0];
+    assert_eq!(values(&cross(&x, &y).unwrap()), [0.0, 0.0, 1.0]);
+
+    let a = vector![1, 2, 3];
+    let b = vector![4, 5, 6];
+    let ab = cross(&a, &b).unwrap();
+    assert_eq!(ab.shape(), &[3]);
+    assert_eq!(values(&ab), [-3.0, 6.0, -3.0]);
+
+    // Anti-commutative, orthogonal to both and zero with itself.
+    let ba = cross(&b, &a).unwrap();
+    assert!(values(&ab).iter().zip(values(&ba)).all(|(x, y)| *x == -y));
+    assert_eq!(ab.dot(&a).unwrap(), 0.0);
+    assert_eq!(ab.dot(&b).unwrap(), 0.0);
+    assert_eq!(values(&cross(&a, &a).unwrap()), [0.0; 3]);
+}
+
+#[test]
+fn batches_are_computed_along_the_last_axis() {
+    let a = matrix![[1, 0, 0], [1, 2, 3], [0, 0, 2]];
+    let b = matrix![[0, 1, 0], [4, 5, 6], [0, 0, 5]];
+
+    let result = cross(&a, &b).unwrap();
+    assert_eq!(result.shape(), &[3, 3]);
+    assert_eq!(
+        values(&result),
+        [0.0, 0.0, 1.0, -3.0, 6.0, -3.0, 0.0, 0.0, 0.0]
+    );
+
+    // The same vectors read through a transposed and a flipped layout.
+    let a_t = matrix![[1, 1, 0], [0, 2, 0], [0, 3, 2]]
+        .transpose()
+        .materialize();
+    let b_flipped = matrix![[0, 0, 5], [4, 5, 6], [0, 1, 0]]
+        .flip_view(&[0])
+        .unwrap();
+    assert!(!a_t.data().is_contiguous() && !b_flipped.data().is_contiguous());
+    assert_eq!(values(&cross(&a_t, &b_flipped).unwrap()), values(&result));
+
+    // Leading axes of any rank.
+    let a = Tensor::from_iter((0..24).map(f64::from), &[2, 4, 3]);
+    let b = Tensor::from_iter((0..24).map(|i| f64::from(i * i % 7)), &[2, 4, 3]);
+    let result = cross(&a, &b).unwrap();
+    assert_eq!(result.shape(), &[2, 4, 3]);
+
+    let (a, b, result) = (values(&a), values(&b), values(&result));
+    for i in 0..8 {
+        let vector = |v: &[f64]| Tensor::from_vec(v[i * 3..i * 3 + 3].to_vec(), &[3]);
+        let single = cross(&vector(&a), &vector(&b)).unwrap();
+
+        assert_eq!(values(&single), result[i * 3..i * 3 + 3]);
+    }
+}
+
+#[test]
+fn angles_keep_their_precision_near_0_and_pi() {
+    assert_close(
+        angle_between(&vector![1, 0, 0], &vector![0, 2, 0]).unwrap(),
+        std::f64::consts::FRAC_PI_2,
+    );
+    assert_close(
+        angle_between(&vector![1, 1], &vector![1, 0]).unwrap(),
+        std::f64::consts::FRAC_PI_4,
+    );
+
+    let naive = |a: &Tensor<f64>, b: &Tensor<f64>| {
+        let norms = a.dot(a).unwrap().sqrt() * b.dot(b).unwrap().sqrt();
+        (a.dot(b).unwrap() / norms).clamp(-1.0, 1.0).acos()
+    };
+
+    let x = vector![1, 0, 0];
+    let tiny = 1e-10;
+
+    // The arccosine loses every digit, the cosine rounding to 1 or -1.
+    let nearly_parallel = Tensor::from_vec(vec![1.0, tiny, 0.0], &[3]);
+    assert_eq!(naive(&x, &nearly_parallel), 0.0);
+    assert_close(angle_between(&x, &nearly_parallel).unwrap(), tiny);
+
+    let nearly_opposite = Tensor::from_vec(vec![-1.0, tiny, 0.0], &[3]);
+    let opposite_angle = angle_between(&x, &nearly_opposite).unwrap();
+    assert_eq!(naive(&x, &nearly_opposite), std::f64::consts::PI);
+    assert!(opposite_angle < std::f64::consts::PI);
+    assert_close(std::f64::consts::PI - opposite_angle, tiny);
+
+    assert_eq!(angle_between(&x, &vector![0, 0, 0]).unwrap(), 0.0);
+}
+
+#[test]
+fn normalized_vectors_have_unit_norm() {
+    let t = matrix![[3, 4, 0], [0, 0, 0], [1, 1, 1]];
+    let result = normalize(&t, 1e-12).unwrap();
+
+    assert_eq!(result.shape(), &[3, 3]);
+    let result = values(&result);
+    assert_eq!(result[..3], [0.6, 0.8, 0.0]);
+    // Zero vectors are written as zeros.
+    assert_eq!(result[3..6], [0.0; 3]);
+    result[6..]
+        .iter()
+        .for_each(|&x| assert_close(x, 1.0 / 3f64.sqrt()));
+
+    // Below the threshold too.
+    let tiny = vector![1e-9, 0, 0];
+    assert_eq!(values(&normalize(&tiny, 1e-6).unwrap()), [0.0; 3]);
+    assert_eq!(values(&normalize(&tiny, 1e-12).unwrap()), [1.0, 0.0, 0.0]);
+
+    assert!(matches!(
+        normalize(&tiny, 0.0),
+        Err(OpError::NonPositiveEpsilon)
+    ));
+}
+
+#[test]
+fn projections_keep_the_component_along_the_other_vector() {
+    let t = matrix![[2, 3, 4], [1, 1, 0], [5, 5, 5]];
+    let onto = matrix![[0, 2, 0], [1, -1, 0], [0, 0, 0]];
+
+    let result = project_onto(&t, &onto).unwrap();
+    assert_eq!(
+        values(&result),
+        [0.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+    );
+
+    let a = vector![3, 4, 0];
+    let b = vector![1, 0, 0];
+    assert_eq!(values(&project_onto(&a, &b).unwrap()), [3.0, 0.0, 0.0]);
+}
+
+#[test]
+fn shapes_are_validated() {
+    let err = cross(&vector![1, 2], &vector![3, 4]).err().unwrap();
+    assert!(
+        matches!(&err, OpError::NotSameShape(expected, got) if **expected == [3] && **got == [2]),
+        "{:?}",
+        err
+    );
+    assert!(err.to_string().contains("expected [3]"), "{}", err);
+
+    let err = cross(&matrix![[1, 2, 3]], &vector![1, 2, 3]).err().unwrap();
+    assert!(matches!(err, OpError::NotSameShape(_, _)), "{:?}", err);
+
+    let err = angle_between(&matrix![[1, 2]], &matrix![[1, 2]])
+        .err()
+        .unwrap();
+    assert!(matches!(err, OpError::NotEnoughAxes(1, 2)), "{:?}", err);
+
+    let err = angle_between(&vector![1, 2], &vector![1, 2, 3])
+        .err()
+        .unwrap();
+    assert!(matches!(err, OpError::NotSameShape(_, _)), "{:?}", err);
+
+    let err = project_onto(&vector![1, 2], &vector![1, 2, 3])
+        .err()
+        .unwrap();
+    assert!(matches!(err, OpError::NotSameShape(_, _)), "{:?}", err);
+
+    let scalar = Tensor::from_vec(vec![1.0], &[]);
+    let err = normalize(&scalar, 1e-12).err().unwrap();
+    assert!(matches!(err, OpError::NotEnoughAxes(1, 0)), "{:?}", err);
+}
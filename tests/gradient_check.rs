@@ -0,0 +1,80 @@
+mod common;
+
+use simple_tensor::tensor::testing::{check_grad, numerical_jacobian};
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+fn sum_of_squares(t: &Tensor<f64>) -> f64 {
+    t.iter().map(|v| v * v).sum()
+}
+
+fn dot(a: &Tensor<f64>, b: &Tensor<f64>) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+fn assert_close(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert!((g - e).abs() < 1e-6, "{:?} != {:?}", got, expected);
+    }
+}
+
+#[test]
+fn gradient_of_the_sum_of_squares() {
+    let x = matrix![[1.0, -2.0, 0.5], [3.0, 0.0, -0.25]];
+
+    let grad = numerical_jacobian(sum_of_squares, &x, 1e-5);
+    assert_eq!(grad.shape(), x.shape());
+    assert_close(&values(&grad), &[2.0, -4.0, 1.0, 6.0, 0.0, -0.5]);
+
+    let analytical = (&x * 2.0).materialize();
+    assert!(check_grad(
+        sum_of_squares,
+        &analytical,
+        &x,
+        1e-5,
+        1e-8,
+        1e-6
+    ));
+}
+
+#[test]
+fn gradient_of_a_dot_product() {
+    let x = vector![0.5, -1.5, 2.0, 4.0];
+
+    let grad = numerical_jacobian(|t| dot(t, t), &x, 1e-5);
+    assert_close(&values(&grad), &[1.0, -3.0, 4.0, 8.0]);
+
+    // A strided input is perturbed in its logical order.
+    let x = matrix![[1, 2], [3, 4]].transpose().materialize();
+    let grad = numerical_jacobian(|t| dot(t, t), &x, 1e-5);
+    assert_close(&values(&grad), &[2.0, 6.0, 4.0, 8.0]);
+}
+
+#[test]
+fn wrong_gradients_are_rejected() {
+    let x = vector![1.0, 2.0, 3.0];
+
+    let off_by_one = vector![2.0, 4.0, 7.0];
+    assert!(!check_grad(
+        sum_of_squares,
+        &off_by_one,
+        &x,
+        1e-5,
+        1e-6,
+        1e-6
+    ));
+
+    let wrong_shape = vector![2.0, 4.0];
+    assert!(!check_grad(
+        sum_of_squares,
+        &wrong_shape,
+        &x,
+        1e-5,
+        1e-6,
+        1e-6
+    ));
+}
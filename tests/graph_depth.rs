@@ -0,0 +1,79 @@
+use simple_tensor::tensor::{Tensor, TensorPromise};
+
+const LONG_CHAIN: usize = 500_000;
+
+fn chain(t: &Tensor<f64>, len: usize) -> TensorPromise<f64> {
+    let mut promise = t + t;
+    for _ in 1..len {
+        promise = &promise + t;
+    }
+
+    promise
+}
+
+#[test]
+fn dropping_a_long_chain() {
+    let t = Tensor::from_scalar(1.0, &[2]);
+    let promise = chain(&t, LONG_CHAIN);
+
+    assert_eq!(promise.depth(), LONG_CHAIN);
+    drop(promise);
+
+    // Shared by a cache in the middle, which keeps its half of the chain alive.
+    let half = chain(&t, LONG_CHAIN / 2).cache();
+    let mut promise = &half + &t;
+    for _ in 1..LONG_CHAIN / 2 {
+        promise = &promise + &t;
+    }
+
+    assert_eq!(promise.depth(), LONG_CHAIN + 1);
+    drop(promise);
+    assert_eq!(half.depth(), LONG_CHAIN / 2 + 1);
+}
+
+#[test]
+fn materializing_a_long_chain() {
+    let t = Tensor::from_scalar(1.0, &[2]);
+    let result = chain(&t, LONG_CHAIN).materialize();
+
+    assert_eq!(
+        result.iter().copied().collect::<Vec<_>>(),
+        [LONG_CHAIN as f64 + 1.0; 2]
+    );
+}
+
+#[test]
+fn depth_is_the_longest_path() {
+    let a = Tensor::from_scalar(1.0, &[3]);
+    let b = Tensor::from_scalar(2.0, &[3]);
+
+    let sum = &a + &b;
+    assert_eq!(sum.depth(), 1);
+
+    let rolled = sum.rolling_sum(2, 0).unwrap();
+    let diff = rolled.diff(1, 0).unwrap();
+    assert_eq!(diff.depth(), 3);
+
+    // The shorter branch does not count.
+    let product = &sum * &(&rolled.diff(1, 0).unwrap() + 1.0).materialize();
+    assert_eq!(product.depth(), 2);
+    assert_eq!(TensorPromise::concat(&[sum, diff], 0).unwrap().depth(), 4);
+}
+
+#[test]
+fn graphs_are_acyclic() {
+    let a = Tensor::from_scalar(1.0, &[4]);
+
+    // Diamonds share nodes without being cycles.
+    let shared = &a * 2.0;
+    let diamond = &(&shared + &a) * &(&shared - &a);
+    assert!(diamond.validate_acyclic().is_ok());
+
+    let cached = diamond.clone().cache();
+    let on_top = &(&cached + &shared) * &cached;
+    assert!(on_top.validate_acyclic().is_ok());
+
+    cached.clone().materialize();
+    assert!(on_top.validate_acyclic().is_ok());
+    assert!(chain(&a, 1000).validate_acyclic().is_ok());
+}
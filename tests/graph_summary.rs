@@ -0,0 +1,64 @@
+use simple_tensor::vector;
+
+// Position of the line describing the node `id`.
+fn line_of(summary: &str, id: impl std::fmt::Debug) -> usize {
+    let id = format!("{:?}", id);
+    let id = id.trim_start_matches("TensorId(").trim_end_matches(')');
+    let prefix = format!("#{} ", id);
+
+    summary
+        .lines()
+        .position(|line| line.starts_with(&prefix))
+        .unwrap_or_else(|| panic!("no line for node #{} in\n{}", id, summary))
+}
+
+#[test]
+fn chain_in_topological_order() {
+    let a = vector![1, 2, 3, 4];
+    let b = vector![4, 3, 2, 1];
+
+    let sum = &a + &b;
+    let rolled = sum.rolling_sum(2, 0).unwrap();
+    let diff = rolled.diff(1, 0).unwrap();
+
+    let summary = diff.graph_summary();
+
+    let lines = [
+        line_of(&summary, a.id()),
+        line_of(&summary, b.id()),
+        line_of(&summary, sum.id()),
+        line_of(&summary, rolled.id()),
+        line_of(&summary, diff.id()),
+    ];
+    assert!(lines[0] < lines[2] && lines[1] < lines[2]);
+    assert!(lines[2] < lines[3] && lines[3] < lines[4]);
+
+    let ops: Vec<&str> = summary
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .map(|line| line.split(' ').nth(1).unwrap())
+        .collect();
+    assert_eq!(ops, ["Tensor", "Tensor", "Add", "Rolling(Sum,", "Diff(1,"]);
+
+    // The inputs of the sum are listed below it, the last one closing the branch.
+    let below_sum: Vec<&str> = summary.lines().skip(lines[2] + 1).take(2).collect();
+    assert!(below_sum[0].starts_with("├── #"));
+    assert!(below_sum[1].starts_with("└── #"));
+    assert!(summary.lines().nth(lines[4]).unwrap().ends_with("[2]"));
+}
+
+#[test]
+fn filled_caches_are_marked() {
+    let a = vector![1, 2, 3];
+    let cached = (&a + &a).cache();
+
+    assert!(!cached.graph_summary().contains("[cached]"));
+    assert!(cached.graph_summary().contains("Add"));
+
+    cached.clone().materialize();
+
+    let summary = cached.graph_summary();
+    assert!(summary.lines().next().unwrap().ends_with("[cached]"));
+    // The graph behind a filled cache is never computed again.
+    assert_eq!(summary.lines().count(), 1);
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{s, ss};
+
+use common::{arange, values};
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn grouping_and_flattening_round_trip() {
+    // Four xyz points.
+    let coords = arange(&[2, 12]);
+
+    let points = coords.group_last_axis_view(3).unwrap();
+    assert_eq!(points.shape(), &[2, 4, 3]);
+    assert_eq!(points.stride(), &[12, 3, 1]);
+    assert_eq!(values(&points), values(&coords));
+    assert!(coords.is_shared() && points.is_shared());
+
+    let back = points.flatten_last_axes_view(2).unwrap();
+    assert_eq!(back.shape(), &[2, 12]);
+    assert_eq!(values(&back), values(&coords));
+
+    let flat = points.flatten_view().unwrap();
+    assert_eq!(flat.shape(), &[24]);
+    assert_eq!(values(&flat), values(&coords));
+
+    // The same through the graph.
+    let grouped = coords.group_last_axis(3).unwrap();
+    assert_eq!(grouped.shape(), &[2, 4, 3]);
+    let round_trip = grouped.flatten_last_axes(2).unwrap().materialize();
+    assert_eq!(round_trip.shape(), &[2, 12]);
+    assert_eq!(values(&round_trip), values(&coords));
+
+    let flat = (&coords * 2.0)
+        .group_last_axis(4)
+        .unwrap()
+        .flatten()
+        .unwrap();
+    let expected: Vec<f64> = values(&coords).iter().map(|x| x * 2.0).collect();
+    assert_eq!(values(&flat.materialize()), expected);
+}
+
+#[test]
+fn indivisible_or_strided_last_axes_are_rejected() {
+    let t = arange(&[2, 10]);
+
+    for k in [0, 3, 4, 20] {
+        let err = t.group_last_axis_view(k).err().unwrap();
+        assert!(
+            matches!(err, OpError::IndivisibleAxis(10, got) if got == k),
+            "{:?}",
+            err
+        );
+    }
+    assert!(matches!(
+        arange(&[]).group_last_axis_view(1).err().unwrap(),
+        OpError::NotEnoughAxes(1, 0)
+    ));
+
+    // Every other element, or the columns of a matrix.
+    let strided = t.step_slice(ss![.., 0..10;2]).unwrap().materialize();
+    assert!(matches!(
+        strided.group_last_axis_view(5).err().unwrap(),
+        OpError::NonContiguousView
+    ));
+    let transposed = t.transpose().materialize();
+    assert!(matches!(
+        transposed.group_last_axis_view(2).err().unwrap(),
+        OpError::NonContiguousView
+    ));
+    assert!(matches!(
+        t.transpose().group_last_axis(2).err().unwrap(),
+        OpError::NonContiguousView
+    ));
+
+    // A last axis of length 1 has no stride to speak of.
+    let column = t.slice(s![.., 3..4]).unwrap().materialize();
+    let grouped = column.group_last_axis_view(1).unwrap();
+    assert_eq!(grouped.shape(), &[2, 1, 1]);
+    assert_eq!(values(&grouped), [3.0, 13.0]);
+}
+
+#[test]
+fn only_axes_stepping_over_each_other_merge() {
+    let t = arange(&[4, 3, 5]);
+
+    // Rows cut short: the last two axes no longer merge, the first two still do.
+    let cut = t.slice(s![.., .., 0..2]).unwrap().materialize();
+    assert!(matches!(
+        cut.flatten_last_axes_view(2).err().unwrap(),
+        OpError::NonContiguousView
+    ));
+    assert!(matches!(
+        cut.flatten_view().err().unwrap(),
+        OpError::NonContiguousView
+    ));
+    let permuted = cut.transpose_axes(&[2, 0, 1]).unwrap().materialize();
+    let merged = permuted.flatten_last_axes_view(2).unwrap();
+    assert_eq!(merged.shape(), &[2, 12]);
+    assert_eq!(values(&merged), values(&permuted));
+
+    // Every other matrix: the last two axes merge, all three do not.
+    let every_other = t.step_slice(ss![0..4;2, .., ..]).unwrap().materialize();
+    let rows = every_other.flatten_last_axes_view(2).unwrap();
+    assert_eq!(rows.shape(), &[2, 15]);
+    assert_eq!(rows.stride(), &[30, 1]);
+    assert_eq!(values(&rows), values(&every_other));
+    assert!(every_other.flatten_last_axes_view(3).is_err());
+
+    // Every other element merges into an axis of stride 2, which is not a flat view.
+    let strided = t.step_slice(ss![.., .., 0..5;2]).unwrap().materialize();
+    assert!(strided.flatten_last_axes_view(1).is_ok());
+    assert!(strided.flatten_last_axes_view(2).is_err());
+
+    // Flipped axes merge into one of negative stride.
+    let flipped = t.flip_view(&[1, 2]).unwrap();
+    let merged = flipped.flatten_last_axes_view(2).unwrap();
+    assert_eq!(merged.stride(), &[15, -1]);
+    assert_eq!(values(&merged), values(&flipped));
+    assert!(flipped.flatten_view().is_err());
+
+    // Axes of length 1 and empty axes never get in the way.
+    let single = t.slice(s![1..2, 1..2, ..]).unwrap().materialize();
+    assert_eq!(single.flatten_view().unwrap().shape(), &[5]);
+    let column = t.slice(s![1..2, .., 2..3]).unwrap().materialize();
+    assert_eq!(column.flatten_last_axes_view(3).unwrap().stride(), &[5]);
+    assert!(column.flatten_view().is_err());
+    let empty = arange(&[4, 0, 5])
+        .transpose_axes(&[2, 1, 0])
+        .unwrap()
+        .materialize();
+    assert_eq!(empty.flatten_last_axes_view(3).unwrap().shape(), &[0]);
+
+    assert!(matches!(
+        t.flatten_last_axes_view(4).err().unwrap(),
+        OpError::NotEnoughAxes(4, 3)
+    ));
+    assert!(matches!(
+        t.flatten_last_axes_view(0).err().unwrap(),
+        OpError::NotEnoughAxes(1, 3)
+    ));
+}
+
+#[test]
+fn grouped_views_can_be_sliced() {
+    let coords = arange(&[18]);
+    let points = coords.group_last_axis_view(3).unwrap();
+
+    // The y of every point, and the points 1 to 3.
+    let ys = points.slice(s![.., 1]).unwrap().materialize();
+    assert_eq!(values(&ys), [1.0, 4.0, 7.0, 10.0, 13.0, 16.0]);
+
+    let some = points.slice(s![1..4, ..]).unwrap().materialize();
+    assert_eq!(values(&some), (3..12).map(|i| i as f64).collect::<Vec<_>>());
+    assert_eq!(some.flatten_view().unwrap().shape(), &[9]);
+
+    // Grouping a slice of the points again.
+    let pairs = some
+        .flatten_last_axes_view(2)
+        .unwrap()
+        .group_last_axis_view(9);
+    assert_eq!(pairs.unwrap().shape(), &[1, 9]);
+}
+
+#[test]
+fn reshaped_views_iterate_and_print_like_dense_tensors() {
+    let coords = arange(&[2, 6]);
+    let points = coords.group_last_axis_view(3).unwrap();
+    let dense = arange(&[2, 2, 3]);
+
+    assert_eq!(values(&points), values(&dense));
+    assert_eq!(format!("{}", points), format!("{}", dense));
+
+    let flipped = arange(&[2, 3]).flip_view(&[0, 1]).unwrap();
+    let flat = flipped.flatten_last_axes_view(2).unwrap();
+    let expected = Tensor::from_iter((0..6).rev().map(|i| i as f64), &[6]);
+    assert_eq!(format!("{}", flat), format!("{}", expected));
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use simple_tensor::tensor::Tensor;
+use simple_tensor::{matrix, vector};
+
+#[test]
+fn clones_hash_the_same() {
+    let t = matrix![[1, 2, 3], [4, 5, 6]];
+
+    assert_eq!(t.content_hash_f64(), t.clone().content_hash_f64());
+    assert_eq!(t.content_hash_f64(), t.clone_deep().content_hash_f64());
+    assert_eq!(t.content_hash_f64(), t.clone_detached().content_hash_f64());
+
+    let ints = Tensor::from_vec(vec![1i64, 2, 3, 4], &[2, 2]);
+    assert_eq!(ints.content_hash(), ints.clone_deep().content_hash());
+}
+
+#[test]
+fn order_and_shape_change_the_content_hash() {
+    assert_ne!(
+        vector![1.0, 2.0].content_hash_f64(),
+        vector![2.0, 1.0].content_hash_f64()
+    );
+
+    let flat = Tensor::from_vec(vec![1i64, 2, 3, 4], &[4]);
+    let square = Tensor::from_vec(vec![1i64, 2, 3, 4], &[2, 2]);
+    assert_ne!(flat.content_hash(), square.content_hash());
+}
+
+#[test]
+fn content_hash_ignores_the_layout() {
+    let t = matrix![[1, 2], [3, 4]];
+
+    // A transposed view and a dense copy of it hold the same logical elements.
+    let transposed = t.transpose().materialize();
+    let view = t.flip_view(&[0]).unwrap();
+    let flipped = t.flip(&[0]).unwrap().materialize();
+
+    assert_eq!(
+        transposed.content_hash_f64(),
+        matrix![[1, 3], [2, 4]].content_hash_f64()
+    );
+    assert_eq!(view.content_hash_f64(), flipped.content_hash_f64());
+    assert_ne!(view.structural_hash(), flipped.structural_hash());
+}
+
+#[test]
+fn structural_hash_follows_shape_and_strides() {
+    let a = matrix![[1, 2], [3, 4]];
+    let b = matrix![[5, 6], [7, 8]];
+
+    assert_eq!(a.structural_hash(), b.structural_hash());
+    assert_ne!(a.structural_hash(), vector![1, 2, 3, 4].structural_hash());
+    assert_ne!(
+        a.structural_hash(),
+        a.flip_view(&[1]).unwrap().structural_hash()
+    );
+}
+
+#[test]
+fn deduplicates_through_a_map() {
+    let tensors = [
+        vector![1, 2, 3],
+        vector![3, 2, 1],
+        vector![1, 2, 3].clone_deep(),
+    ];
+
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+    for (i, t) in tensors.iter().enumerate() {
+        seen.entry(t.content_hash_f64()).or_insert(i);
+    }
+
+    assert_eq!(seen.len(), 2);
+}
@@ -0,0 +1,62 @@
+use std::collections::{HashMap, HashSet};
+
+use simple_tensor::tensor::{Tensor, TensorId};
+use simple_tensor::{matrix, vector};
+
+#[test]
+fn ids_as_map_keys() {
+    let a = matrix![[1, 2], [3, 4]];
+    let b = matrix![[1, 2], [3, 4]];
+
+    let mut grads: HashMap<TensorId, &str> = HashMap::new();
+    grads.insert(a.id(), "a");
+    grads.insert(b.id(), "b");
+
+    // clone() shares the node, the other copies are new leaves.
+    assert_eq!(grads.get(&a.clone().id()), Some(&"a"));
+    assert_eq!(grads.get(&a.clone_detached().id()), None);
+    assert_eq!(grads.get(&a.clone_deep().id()), None);
+    assert_eq!(grads.len(), 2);
+}
+
+#[test]
+fn ptr_eq_follows_the_node() {
+    let a = vector![1, 2, 3];
+
+    assert!(a.ptr_eq(&a.clone()));
+    assert!(!a.ptr_eq(&a.clone_detached()));
+    assert!(!a.ptr_eq(&vector![1, 2, 3]));
+}
+
+#[test]
+fn promise_ids() {
+    let a = vector![1, 2, 3];
+    let sum = &a + 1.0;
+    let product = &a * 2.0;
+
+    let ids: HashSet<TensorId> = [a.id(), sum.id(), product.id()].into();
+    assert_eq!(ids.len(), 3);
+
+    let cached = product.cache();
+    assert_eq!(cached.id(), cached.id());
+    assert!(!ids.contains(&cached.id()));
+}
+
+#[test]
+fn data_compares_by_value() {
+    let a = matrix![[1, 2, 3], [4, 5, 6]];
+
+    // Same logical elements through a transposed view of a transposed copy.
+    let transposed = a.transpose().materialize();
+    let back = transposed.transpose().materialize();
+    assert!(a.data() == back.data());
+    assert!(a.data() == a.clone_deep().data());
+
+    let flipped = a.flip_view(&[0, 1]).unwrap();
+    let reversed = Tensor::from_vec(vec![6.0, 5.0, 4.0, 3.0, 2.0, 1.0], &[2, 3]);
+    assert!(flipped.data() == reversed.data());
+
+    assert!(a.data() != transposed.data());
+    assert!(a.data() != a.view(&[3, 2]).unwrap().materialize().data());
+    assert!(a.data() != matrix![[1, 2, 3], [4, 5, 7]].data());
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout as AllocLayout, System};
+use std::cell::Cell;
+
+use simple_tensor::tensor::{Dimension, Layout};
+use simple_tensor::{s, ss};
+
+use common::{arange, values};
+
+// Counts the allocations made by the current thread, as the tests run in parallel.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations_in<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+// Element of a row-major tensor of `shape` at `index`, reading `arange(shape)`.
+fn flat_index(shape: &[usize], index: &[usize]) -> f64 {
+    let mut flat = 0;
+    for (&len, &i) in shape.iter().zip(index) {
+        flat = flat * len + i;
+    }
+
+    flat as f64
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn layouts_stay_small() {
+    assert!(size_of::<Layout>() <= 144, "{} bytes", size_of::<Layout>());
+}
+
+#[test]
+fn layouts_up_to_rank_6_do_not_allocate() {
+    let layout = Layout::from_shape(&[2, 3, 4, 5, 6, 7], 0);
+
+    let (clone, allocations) = allocations_in(|| layout.clone());
+    assert_eq!(allocations, 0);
+    assert_eq!(clone.shape(), layout.shape());
+    assert_eq!(clone.stride(), layout.stride());
+    assert_eq!(clone.adj_stride(), layout.adj_stride());
+
+    let (_, allocations) = allocations_in(|| {
+        let layout = Layout::from_shape(&[4, 6, 8], 0);
+        let _ = layout.transpose();
+        let _ = layout.transpose_axes(&[2, 0, 1]).unwrap();
+        let _ = layout.step_slice(ss![1..4, ..;-2]).unwrap();
+        let _ = layout.group_last_axis(4).unwrap();
+        let _ = layout.flatten_last_axes(2).unwrap();
+        let _ = layout.flip(&[0, 2]).unwrap();
+        let _ = Layout::from_slice(&[6, 4], &[1, 6], 0);
+    });
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn layouts_above_rank_6_are_boxed() {
+    let shape = [2, 1, 3, 1, 2, 2, 3, 2];
+    let t = arange(&shape);
+    let layout = t.layout().clone();
+
+    assert_eq!(layout.shape(), shape);
+    assert_eq!(layout.stride(), [72, 72, 24, 24, 12, 6, 2, 1]);
+    assert!(layout.is_contiguous());
+
+    let (clone, allocations) = allocations_in(|| layout.clone());
+    assert!(allocations > 0);
+    assert_eq!(clone.stride(), layout.stride());
+
+    // Reversing the axes reads each element at the reversed index.
+    let transposed = t.transpose().materialize();
+    let reversed: Vec<usize> = shape.iter().rev().copied().collect();
+    assert_eq!(transposed.shape(), reversed);
+    let read_reversed = transposed.map_with_multi_index(|index, el| {
+        let index: Vec<usize> = index.iter().rev().map(|&i| i as usize).collect();
+        el == flat_index(&shape, &index)
+    });
+    assert!(read_reversed.iter().all(|&ok| ok));
+
+    // Slicing, stepping and flipping with every axis spilled.
+    let sliced = t.slice(s![1, .., 1..3, .., ..]).unwrap().materialize();
+    assert_eq!(sliced.shape(), [1, 2, 1, 2, 2, 3, 2]);
+    assert_eq!(
+        values(&sliced)[0],
+        flat_index(&shape, &[1, 0, 1, 0, 0, 0, 0, 0])
+    );
+
+    let stepped = t
+        .step_slice(ss![.., .., ..;-2, .., .., .., 0..3;2, ..])
+        .unwrap()
+        .materialize();
+    assert_eq!(stepped.shape(), [2, 1, 2, 1, 2, 2, 2, 2]);
+    assert_eq!(
+        values(&stepped)[0],
+        flat_index(&shape, &[0, 0, 2, 0, 0, 0, 0, 0])
+    );
+
+    let flipped = t.flip_view(&[7]).unwrap();
+    assert_eq!(values(&flipped)[..2], [1.0, 0.0]);
+}
+
+#[test]
+fn ranks_crossing_6_match_the_lower_ranks() {
+    let t = arange(&[2, 2, 2, 2, 2, 4]);
+
+    // Rank 6 to 7 and back.
+    let grouped = t.group_last_axis_view(2).unwrap();
+    assert_eq!(grouped.shape(), [2, 2, 2, 2, 2, 2, 2]);
+    assert_eq!(grouped.stride(), [64, 32, 16, 8, 4, 2, 1]);
+    assert_eq!(values(&grouped), values(&t));
+
+    let flattened = grouped.flatten_last_axes_view(2).unwrap();
+    assert_eq!(flattened.shape(), t.shape());
+    assert_eq!(flattened.layout().adj_stride(), t.layout().adj_stride());
+
+    let flat = grouped.flatten_view().unwrap();
+    assert_eq!(flat.shape(), [128]);
+    assert_eq!(values(&flat), values(&t));
+
+    // New axes past rank 6.
+    let expanded = t.slice(s![.., NewAxis, .., .., .., .., ..]).unwrap();
+    assert_eq!(expanded.shape(), [2, 1, 2, 2, 2, 2, 4]);
+    assert_eq!(values(&expanded.materialize()), values(&t));
+
+    let permuted = (grouped.transpose_axes(&[6, 5, 4, 3, 2, 1, 0]).unwrap()).materialize();
+    let transposed = grouped.transpose().materialize();
+    assert_eq!(permuted.stride(), transposed.stride());
+    assert_eq!(values(&permuted), values(&transposed));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, InnerProduct};
+use simple_tensor::{matrix, srange, vector};
+
+use common::values;
+
+#[test]
+fn dot_product_of_vectors() {
+    let dot = vector![1, 2, 3].inner(&vector![4, 5, 6]).unwrap();
+
+    assert_eq!(dot.shape(), &[1]);
+    assert_eq!(values(&dot.materialize()), [32.0]);
+}
+
+#[test]
+fn rows_with_a_vector() {
+    let out = matrix![[1, 2], [3, 4]].inner(&vector![1, 1]).unwrap();
+
+    assert_eq!(out.shape(), &[2]);
+    assert_eq!(values(&out.materialize()), [3.0, 7.0]);
+
+    let out = vector![1, 1].inner(&matrix![[1, 2], [3, 4]]).unwrap();
+    assert_eq!(values(&out.materialize()), [3.0, 7.0]);
+}
+
+#[test]
+fn leading_axes_of_both_inputs() {
+    let a = srange!(12, &[2, 3, 2]);
+    let b = matrix![[1, 0], [0, 1], [1, -1], [2, 2]];
+
+    let out = a.inner(&b).unwrap().materialize();
+    assert_eq!(out.shape(), &[2, 3, 4]);
+
+    // Compared with every lane of a against every row of b.
+    let a_lanes: Vec<f64> = values(&a);
+    let b_rows: Vec<f64> = values(&b);
+    let expected: Vec<f64> = (a_lanes.chunks(2))
+        .flat_map(|lane| {
+            b_rows
+                .chunks(2)
+                .map(move |row| lane[0] * row[0] + lane[1] * row[1])
+        })
+        .collect();
+
+    assert_eq!(values(&out), expected);
+}
+
+#[test]
+fn strided_inputs_and_promises() {
+    let t = srange!(6, &[2, 3]);
+
+    // [[0, 3], [1, 4], [2, 5]] with [[2, 5], [1, 4]]
+    let transposed = t.transpose();
+    let flipped = (&t.slice(simple_tensor::s![.., 1..3]).unwrap() + 0.0)
+        .transpose()
+        .flip(&[0])
+        .unwrap();
+
+    let out = transposed.inner(&flipped).unwrap().materialize();
+    assert_eq!(out.shape(), &[3, 2]);
+    assert_eq!(values(&out), [15.0, 12.0, 22.0, 17.0, 29.0, 22.0]);
+}
+
+#[test]
+fn last_axes_must_match() {
+    let err = vector![1, 2, 3].inner(&vector![1, 2]).err().unwrap();
+    assert!(matches!(err.cause(), OpError::NotSameShape(..)));
+    assert_eq!(err.context().unwrap().op(), "Inner");
+
+    let err = matrix![[1, 2, 3]]
+        .inner(&matrix![[1, 2], [3, 4]])
+        .err()
+        .unwrap();
+    assert!(matches!(err.cause(), OpError::NotSameShape(..)));
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use simple_tensor::s;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+
+use common::{arange, values};
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn for_loops_yield_the_logical_order() {
+    let x = arange(&[3, 4]);
+
+    let mut read = Vec::new();
+    for el in &x {
+        read.push(el);
+    }
+    assert_eq!(read, values(&x));
+
+    // Strided views are read in their own order, not the one of their buffer.
+    let transposed = x.transpose().materialize();
+    let sliced = x.slice(s![1.., ..;2]).unwrap().materialize();
+    for view in [&transposed, &sliced] {
+        let read: Vec<f64> = view.into_iter().collect();
+        assert_eq!(read, values(view));
+    }
+    assert_eq!(
+        (&sliced).into_iter().collect::<Vec<_>>(),
+        [4.0, 6.0, 8.0, 10.0]
+    );
+
+    let read: Vec<f64> = transposed.data().into_iter().collect();
+    assert_eq!(
+        read,
+        [0.0, 4.0, 8.0, 1.0, 5.0, 9.0, 2.0, 6.0, 10.0, 3.0, 7.0, 11.0]
+    );
+    assert_eq!(transposed.clone().into_iter().collect::<Vec<_>>(), read);
+}
+
+#[test]
+fn collecting_round_trips() {
+    let x: Tensor<f64> = (0..6).map(|i| i as f64).collect();
+    assert_eq!(x.shape(), [6]);
+    assert_eq!(values(&x), values(&arange(&[6])));
+
+    let empty: Tensor<f64> = std::iter::empty().collect();
+    assert_eq!(empty.shape(), [0]);
+    assert_eq!(empty.into_iter().count(), 0);
+
+    let x = arange(&[2, 3, 2]);
+    let again = Tensor::collect_with_shape(&x, x.shape()).unwrap();
+    assert!(again.data() == x.data());
+    let back: Tensor<f64> = again.into_iter().collect();
+    assert_eq!(values(&back), values(&x));
+
+    let err = Tensor::collect_with_shape(&x, &[5, 2]).err().unwrap();
+    assert!(matches!(err, OpError::ElementCount(10, 12)));
+    assert_eq!(
+        err.to_string(),
+        "the shape holds 10 elements, but 12 were given"
+    );
+    let err = Tensor::collect_with_shape(x.iter().copied().take(4), &[5])
+        .err()
+        .unwrap();
+    assert!(matches!(err, OpError::ElementCount(5, 4)));
+}
+
+#[test]
+fn consuming_a_unique_tensor_moves_its_buffer() {
+    let x = arange(&[64, 64]);
+    let ptr = x.try_as_slice().unwrap().as_ptr();
+
+    let iter = x.into_iter();
+    assert_eq!(iter.as_slice().as_ptr(), ptr);
+    assert_eq!(iter.len(), 64 * 64);
+
+    // Anything else reading the buffer keeps it, the elements are copied instead.
+    let x = arange(&[64, 64]);
+    let ptr = x.try_as_slice().unwrap().as_ptr();
+    let clone = x.clone();
+    let iter = x.into_iter();
+    assert_ne!(iter.as_slice().as_ptr(), ptr);
+    assert_eq!(iter.collect::<Vec<_>>(), values(&clone));
+
+    let x = arange(&[64, 64]);
+    let promise = &x + 1.0;
+    assert_ne!(x.into_iter().as_slice().as_ptr(), ptr);
+    drop(promise);
+
+    // A view of part of its buffer only moves the elements it reads.
+    let view = arange(&[4, 4]).slice(s![1..3]).unwrap().materialize();
+    assert_eq!(
+        view.into_iter().collect::<Vec<_>>(),
+        values(&arange(&[16]))[4..12]
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_sums_match_the_sequential_ones() {
+    use rayon::prelude::*;
+
+    let x = Tensor::from_iter((0..1 << 20).map(|i| (i % 1000) as f64), &[1024, 1024]);
+    let sequential: f64 = x.iter().sum();
+
+    assert_eq!(x.par_iter().sum::<f64>(), sequential);
+    assert_eq!(x.par_iter().len(), 1 << 20);
+
+    // Strided tensors are gathered first, and keep their order.
+    let transposed = x.transpose().materialize();
+    assert_eq!(transposed.par_iter().sum::<f64>(), sequential);
+    let read: Vec<f64> = transposed.par_iter().collect();
+    assert_eq!(read, values(&transposed));
+
+    let evens: Vec<f64> = x.par_iter().step_by(2).take(3).collect();
+    assert_eq!(evens, [0.0, 2.0, 4.0]);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_iterators_are_shared_across_threads() {
+    use rayon::prelude::*;
+
+    let x = Tensor::from_iter((0..100_000).map(|i| i as f64), &[100_000]);
+    let threads = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+
+    let main = std::thread::current().id();
+    let seen = std::sync::Mutex::new(std::collections::HashSet::new());
+    let max = threads.install(|| {
+        x.par_iter()
+            .inspect(|_| {
+                seen.lock().unwrap().insert(std::thread::current().id());
+            })
+            .reduce(|| f64::MIN, f64::max)
+    });
+    assert_eq!(max, 99_999.0);
+    let seen = seen.into_inner().unwrap();
+    assert!(!seen.is_empty() && !seen.contains(&main));
+
+    // Tensors are Send and Sync, so a scope can read one from every thread.
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| scope.spawn(|| x.par_iter().map(|el| el * 2.0).sum::<f64>()))
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 99_999.0 * 100_000.0);
+        }
+    });
+}
@@ -0,0 +1,293 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout as AllocLayout, System};
+use std::cell::Cell;
+use std::sync::MutexGuard;
+
+use simple_tensor::tensor::{
+    Dimension, InterningScope, Layout, LayoutInterner, Tensor, TensorPromise, layout_interning,
+    set_layout_interning,
+};
+use simple_tensor::{s, ss};
+
+use common::{arange, values};
+
+// Counts the bytes the current thread holds on to, as the tests run in parallel.
+struct CountingAlloc;
+
+thread_local! {
+    static RETAINED: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        RETAINED.with(|count| count.set(count.get() + layout.size() as isize));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        RETAINED.with(|count| count.set(count.get() - layout.size() as isize));
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bytes_retained_by<R>(f: impl FnOnce() -> R) -> (R, isize) {
+    let before = RETAINED.with(Cell::get);
+    let result = f();
+
+    (result, RETAINED.with(Cell::get) - before)
+}
+
+fn lock_globals() -> MutexGuard<'static, ()> {
+    let guard = common::lock_globals();
+    set_layout_interning(false);
+    LayoutInterner::global().clear();
+
+    guard
+}
+
+// A graph of views and elementwise ops over a [3, 4] tensor, giving a [3, 3] one.
+fn graph(x: &Tensor<f64>) -> TensorPromise<f64> {
+    let t = x.transpose() * 2.0;
+    let sliced = x.slice(s![.., 1..]).unwrap() + 1.0;
+
+    (&t + &t).diff(1, 0).unwrap() - sliced
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn repeated_shapes_share_one_layout() {
+    let _globals = lock_globals();
+    let x = arange(&[3, 4]);
+    let interner = LayoutInterner::global();
+
+    set_layout_interning(true);
+    let (a, b, c) = (&x + 1.0, &x * 2.0, &x - 3.0);
+    let t = x.transpose() * 2.0;
+    set_layout_interning(false);
+
+    for p in [&a, &b, &c, &t] {
+        assert!(p.layout().is_interned());
+    }
+    assert!(Layout::ptr_eq(a.layout(), b.layout()));
+    assert!(Layout::ptr_eq(a.layout(), c.layout()));
+    assert!(!Layout::ptr_eq(a.layout(), t.layout()));
+    assert_ne!(a.layout(), t.layout());
+    // One for [3, 4], one for the [4, 3] product of the transpose.
+    assert_eq!(interner.len(), 2);
+
+    // Layouts not interned are compared by their values.
+    let d = &x + 1.0;
+    assert!(!d.layout().is_interned());
+    assert_eq!(d.layout(), a.layout());
+    assert!(!Layout::ptr_eq(d.layout(), a.layout()));
+    assert_eq!(values(&a.materialize()), values(&d.materialize()));
+
+    // Interning an equal layout by hand gives the shared one.
+    let by_hand = interner.intern(&Layout::from_shape(&[3, 4], 0));
+    assert!(Layout::ptr_eq(&by_hand, b.layout()));
+    assert_eq!(interner.len(), 2);
+}
+
+#[test]
+fn interned_layouts_behave_like_inline_ones() {
+    let _globals = lock_globals();
+    let interner = LayoutInterner::global();
+
+    let layouts = [
+        Layout::from_shape(&[2, 3, 4], 0),
+        Layout::from_shape(&[4, 6], 0).transpose(),
+        Layout::as_strided(&[3, 4], &[1, 3], 2, 16).unwrap(),
+        Layout::from_shape(&[1, 2, 1, 2, 1, 2, 1, 2], 3),
+    ];
+
+    for inline in layouts {
+        let interned = interner.intern(&inline);
+        assert!(interned.is_interned() && !inline.is_interned());
+        assert_eq!(interned, inline);
+        assert_eq!(format!("{:?}", interned), format!("{:?}", inline));
+        assert_eq!(interned.to_string(), inline.to_string());
+
+        let rank = inline.shape().len();
+        let axes: Vec<usize> = (0..rank).rev().collect();
+        assert_eq!(interned.shape(), inline.shape());
+        assert_eq!(interned.stride(), inline.stride());
+        assert_eq!(interned.adj_stride(), inline.adj_stride());
+        assert_eq!(
+            (interned.offset(), interned.len(), interned.is_contiguous()),
+            (inline.offset(), inline.len(), inline.is_contiguous())
+        );
+        assert_eq!(interned.single_stride(), inline.single_stride());
+        assert_eq!(interned.transpose(), inline.transpose());
+        assert_eq!(
+            interned.transpose_axes(&axes).ok(),
+            inline.transpose_axes(&axes).ok()
+        );
+        assert_eq!(interned.flip(&[0]).ok(), inline.flip(&[0]).ok());
+        assert_eq!(interned.flatten().ok(), inline.flatten().ok());
+        assert_eq!(
+            interned.flatten_last_axes(2).ok(),
+            inline.flatten_last_axes(2).ok()
+        );
+        assert_eq!(
+            interned.view(&[inline.len()]).ok(),
+            inline.view(&[inline.len()]).ok()
+        );
+        assert_eq!(interned.to_dim_stride(0).ok(), inline.to_dim_stride(0).ok());
+        assert_eq!(interned.slice(s![1..]).ok(), inline.slice(s![1..]).ok());
+        assert_eq!(
+            interned.step_slice(ss![..;-1]).ok(),
+            inline.step_slice(ss![..;-1]).ok()
+        );
+
+        // What is derived from an interned layout is not interned itself.
+        assert!(!interned.transpose().is_interned());
+    }
+
+    // The same graph gives the same values either way.
+    let x = arange(&[3, 4]);
+    let inline = graph(&x).materialize();
+    set_layout_interning(true);
+    let interned = graph(&x).materialize();
+    set_layout_interning(false);
+
+    assert_eq!(inline.shape(), interned.shape());
+    assert_eq!(values(&inline), values(&interned));
+}
+
+#[test]
+fn a_large_graph_keeps_one_copy_of_every_shape() {
+    let _globals = lock_globals();
+
+    // Layouts of more than six axes are allocated, so every node not interning its layout
+    // holds its own shape and strides.
+    let leaves: Vec<Tensor<f64>> = (1..=6)
+        .map(|len| arange(&[1, 2, 1, 2, 1, 1, 2, len]))
+        .collect();
+    let build = || -> Vec<TensorPromise<f64>> {
+        (0..50_000)
+            .map(|i| &leaves[i % leaves.len()] + i as f64)
+            .collect()
+    };
+
+    let (inline, inline_bytes) = bytes_retained_by(build);
+    set_layout_interning(true);
+    let (interned, interned_bytes) = bytes_retained_by(build);
+    set_layout_interning(false);
+
+    assert_eq!(LayoutInterner::global().len(), 6);
+    assert!(interned.iter().all(|p| p.layout().is_interned()));
+    for (i, p) in interned.iter().enumerate().skip(6) {
+        assert!(Layout::ptr_eq(p.layout(), interned[i % 6].layout()));
+    }
+
+    // A shape, a stride and an adjacent stride of 8 axes each, 128 bytes, for every node
+    // but the six the interner keeps.
+    assert!(
+        inline_bytes - interned_bytes >= 50_000 * 120,
+        "{} bytes retained inline, {} interned",
+        inline_bytes,
+        interned_bytes
+    );
+
+    assert_eq!(inline[49_999].layout(), interned[49_999].layout());
+    assert_eq!(
+        values(&interned.into_iter().nth(6).unwrap().materialize()),
+        values(&(&leaves[0] + 6.0).materialize())
+    );
+}
+
+#[test]
+fn the_outermost_scope_clears_the_interner() {
+    let _globals = lock_globals();
+    let interner = LayoutInterner::global();
+    let x = arange(&[2, 5]);
+
+    assert!(!layout_interning());
+    let kept = {
+        let _outer = InterningScope::enter();
+        assert!(layout_interning());
+
+        let a = &x + 1.0;
+        {
+            let _inner = InterningScope::enter();
+            let b = x.transpose() * 3.0;
+            assert!(b.layout().is_interned());
+        }
+
+        // Leaving the inner scope keeps interning and the interned layouts.
+        assert!(layout_interning());
+        assert!(interner.len() >= 2);
+        assert!(Layout::ptr_eq(a.layout(), (&x * 2.0).layout()));
+
+        a
+    };
+
+    assert!(!layout_interning());
+    assert!(interner.is_empty());
+
+    // Layouts interned before the interner was cleared stay valid, but are no longer shared.
+    assert!(kept.layout().is_interned());
+    assert_eq!(kept.shape(), &[2, 5]);
+    set_layout_interning(true);
+    let again = &x + 1.0;
+    set_layout_interning(false);
+    assert_eq!(again.layout(), kept.layout());
+    assert!(!Layout::ptr_eq(again.layout(), kept.layout()));
+    assert_eq!(values(&kept.materialize())[..3], [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn threads_intern_into_the_same_shards() {
+    let _globals = lock_globals();
+    let interner = LayoutInterner::global();
+    let shapes: Vec<Vec<usize>> = (1..=6).map(|len| vec![len, 2, 3]).collect();
+
+    set_layout_interning(true);
+    let per_thread: Vec<Vec<Layout>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|thread| {
+                let shapes = &shapes;
+                scope.spawn(move || {
+                    let mut layouts = Vec::new();
+
+                    for round in 0..200 {
+                        let shape = &shapes[(thread + round) % shapes.len()];
+                        let p = arange(shape) + 1.0;
+                        layouts.push(p.layout().clone());
+                    }
+
+                    layouts
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    set_layout_interning(false);
+
+    assert_eq!(interner.len(), 6);
+    for layout in per_thread.iter().flatten() {
+        let shared = interner.intern(layout);
+        assert!(Layout::ptr_eq(layout, &shared));
+    }
+}
+
+#[test]
+fn nothing_is_interned_by_default() {
+    let _globals = lock_globals();
+    let x = arange(&[3, 4]);
+
+    assert!(!layout_interning());
+    let p = graph(&x);
+    assert!(!p.layout().is_interned());
+    assert!(LayoutInterner::global().is_empty());
+    assert_eq!(
+        values(&p.materialize()),
+        [2.0, 1.0, 0.0, -2.0, -3.0, -4.0, -6.0, -7.0, -8.0]
+    );
+}
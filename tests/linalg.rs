@@ -0,0 +1,237 @@
+mod common;
+
+use simple_tensor::matrix;
+use simple_tensor::tensor::{Dimension, Error, LinAlgError, MatrixNorm, Tensor};
+
+use common::values;
+
+fn assert_close(got: &[f64], expected: &[f64], tol: f64) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert!(
+            (g - e).abs() <= tol * e.abs().max(1.0),
+            "got {:?} expected {:?}",
+            got,
+            expected
+        );
+    }
+}
+
+// The product of an [m, k] and a [k, n] matrix, row-major.
+fn matmul(a: &Tensor<f64>, b: &Tensor<f64>) -> Vec<f64> {
+    let (m, k, n) = (a.shape()[0], a.shape()[1], b.shape()[1]);
+    let (a, b) = (values(a), values(b));
+
+    (0..m * n)
+        .map(|idx| {
+            (0..k)
+                .map(|p| a[idx / n * k + p] * b[p * n + idx % n])
+                .sum()
+        })
+        .collect()
+}
+
+fn transposed(t: &Tensor<f64>) -> Tensor<f64> {
+    let (m, n) = (t.shape()[0], t.shape()[1]);
+    let v = values(t);
+
+    Tensor::from_iter((0..m * n).map(|idx| v[idx % m * n + idx / m]), &[n, m])
+}
+
+fn identity(n: usize) -> Vec<f64> {
+    (0..n * n)
+        .map(|idx| (idx / n == idx % n) as u8 as f64)
+        .collect()
+}
+
+fn hilbert(n: usize) -> Tensor<f64> {
+    Tensor::from_iter(
+        (0..n * n).map(|idx| 1.0 / (idx / n + idx % n + 1) as f64),
+        &[n, n],
+    )
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn lu_gives_back_the_matrix_with_its_row_swaps() {
+    let cases = [
+        matrix![[1, 2, 3], [4, 5, 6], [7, 8, 10]],
+        hilbert(5),
+        // Tall and wide matrices.
+        Tensor::from_iter((0..12).map(|i| ((i * 7) % 5) as f64 - 2.0), &[4, 3]),
+        Tensor::from_iter((0..15).map(|i| ((i * 3) % 7) as f64 + 0.5), &[3, 5]),
+    ];
+
+    for a in cases {
+        let (m, n) = (a.shape()[0], a.shape()[1]);
+        let k = m.min(n);
+        let (l, u, pivots) = a.lu().unwrap();
+        assert_eq!(
+            (l.shape(), u.shape(), pivots.len()),
+            (&[m, k][..], &[k, n][..], k)
+        );
+
+        // L is unit lower triangular and U upper triangular.
+        let (lv, uv) = (values(&l), values(&u));
+        for i in 0..m {
+            for j in i..k {
+                assert_eq!(lv[i * k + j], (i == j) as u8 as f64);
+            }
+        }
+        for i in 0..k {
+            assert!(uv[i * n..i * n + i].iter().all(|&el| el == 0.0));
+        }
+
+        // Partial pivoting keeps every multiplier at most 1.
+        assert!(lv.iter().all(|el| el.abs() <= 1.0));
+
+        let mut swapped = values(&a);
+        for (i, &p) in pivots.iter().enumerate() {
+            for j in 0..n {
+                swapped.swap(i * n + j, p as usize * n + j);
+            }
+        }
+        assert_close(&matmul(&l, &u), &swapped, 1e-12);
+    }
+}
+
+#[test]
+fn every_backend_gives_the_same_small_factors() {
+    // Factors with no rounding, the same bit for bit on LAPACK and the pure fallback.
+    let (l, u, pivots) = matrix![[0, 1], [2, 3]].lu().unwrap();
+    assert_eq!(values(&l), [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(values(&u), [2.0, 3.0, 0.0, 1.0]);
+    assert_eq!(pivots, [1, 1]);
+
+    let a = matrix![[1, 1, 1], [2, 3, 5], [4, 6, 8]];
+    let (l, u, pivots) = a.lu().unwrap();
+    assert_eq!(values(&l), [1.0, 0.0, 0.0, 0.25, 1.0, 0.0, 0.5, 0.0, 1.0]);
+    assert_eq!(values(&u), [4.0, 6.0, 8.0, 0.0, -0.5, -1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(pivots, [2, 2, 2]);
+    assert_eq!(a.det().unwrap(), -2.0);
+
+    let (q, r) = matrix![[3, 0], [4, 5]].qr().unwrap();
+    assert_close(&values(&q), &[-0.6, -0.8, -0.8, 0.6], 1e-15);
+    assert_close(&values(&r), &[-5.0, -4.0, 0.0, 3.0], 1e-15);
+}
+
+#[test]
+fn qr_has_orthonormal_columns_and_gives_back_the_matrix() {
+    let cases = [
+        hilbert(4),
+        Tensor::from_iter((0..12).map(|i| ((i * 7) % 5) as f64 - 2.0), &[4, 3]),
+        Tensor::from_iter((0..15).map(|i| ((i * 3) % 7) as f64 + 0.5), &[3, 5]),
+        // A zero column needs no reflection.
+        matrix![[0, 1], [0, 2], [0, 3]],
+    ];
+
+    for a in cases {
+        let (m, n) = (a.shape()[0], a.shape()[1]);
+        let k = m.min(n);
+        let (q, r) = a.qr().unwrap();
+        assert_eq!((q.shape(), r.shape()), (&[m, k][..], &[k, n][..]));
+
+        assert_close(&matmul(&transposed(&q), &q), &identity(k), 1e-14);
+        assert_close(&matmul(&q, &r), &values(&a), 1e-13);
+
+        let rv = values(&r);
+        for i in 0..k {
+            assert!(rv[i * n..i * n + i].iter().all(|&el| el == 0.0));
+        }
+    }
+}
+
+#[test]
+fn det_of_known_matrices() {
+    assert_close(&[matrix![[1, 2], [3, 4]].det().unwrap()], &[-2.0], 1e-15);
+    assert_eq!(
+        matrix![[2, 0, 0], [0, 3, 0], [0, 0, 4]].det().unwrap(),
+        24.0
+    );
+
+    // The sign of the permutation: one swap, then a cycle of three rows.
+    assert_eq!(matrix![[0, 1], [1, 0]].det().unwrap(), -1.0);
+    assert_eq!(matrix![[0, 1, 0], [0, 0, 1], [1, 0, 0]].det().unwrap(), 1.0);
+    assert_eq!(
+        matrix![[0, 0, 1], [0, 1, 0], [1, 0, 0]].det().unwrap(),
+        -1.0
+    );
+
+    assert_close(&[hilbert(4).det().unwrap()], &[1.0 / 6048000.0], 1e-10);
+    assert_eq!(matrix![[7.5]].det().unwrap(), 7.5);
+
+    // A singular matrix has a zero determinant rather than an error.
+    assert_eq!(matrix![[1, 2], [2, 4]].det().unwrap(), 0.0);
+    assert_eq!(matrix![[0, 0], [0, 0]].det().unwrap(), 0.0);
+}
+
+#[test]
+fn cond_of_well_and_ill_conditioned_matrices() {
+    for norm in [MatrixNorm::One, MatrixNorm::Inf] {
+        assert_close(
+            &[Tensor::from_vec(identity(4), &[4, 4]).cond(norm).unwrap()],
+            &[1.0],
+            1e-15,
+        );
+        assert_close(
+            &[matrix![[1, 0], [0, 1e-10]].cond(norm).unwrap()],
+            &[1e10],
+            1e-12,
+        );
+        // Symmetric, so both norms give the same condition number.
+        assert_close(&[hilbert(4).cond(norm).unwrap()], &[28375.0], 1e-8);
+        assert_eq!(matrix![[1, 2], [2, 4]].cond(norm).unwrap(), f64::INFINITY);
+    }
+
+    // The columns of the matrix sum to 1, 3 and 4, its rows to 3, 4 and 1. The columns
+    // of its inverse [[1, -2, 6], [0, 1, -3], [0, 0, 1]] sum to 1, 3 and 10, its rows to
+    // 9, 4 and 1.
+    let a = matrix![[1, 2, 0], [0, 1, 3], [0, 0, 1]];
+    assert_close(&[a.cond(MatrixNorm::One).unwrap()], &[4.0 * 10.0], 1e-12);
+    assert_close(&[a.cond(MatrixNorm::Inf).unwrap()], &[4.0 * 9.0], 1e-12);
+
+    // Badly conditioned matrices have large condition numbers.
+    assert!(hilbert(8).cond(MatrixNorm::One).unwrap() > 1e10);
+}
+
+#[test]
+fn shapes_and_singular_matrices_fail() {
+    let wide = Tensor::from_iter((0..6).map(|i| i as f64), &[2, 3]);
+    assert!(wide.qr().is_ok());
+    assert!(wide.lu().is_ok());
+    assert_eq!(wide.det().err().unwrap(), LinAlgError::NotSquare(2, 3));
+    assert_eq!(
+        wide.cond(MatrixNorm::One).err().unwrap(),
+        LinAlgError::NotSquare(2, 3)
+    );
+
+    let vector = Tensor::from_vec(vec![1.0, 2.0], &[2]);
+    let empty = Tensor::from_vec(vec![], &[0, 3]);
+    for t in [&vector, &empty] {
+        let err = t.lu().err().unwrap();
+        assert_eq!(err, LinAlgError::NotAMatrix(t.shape().into()));
+        assert!(matches!(t.qr(), Err(LinAlgError::NotAMatrix(_))));
+        assert!(matches!(t.det(), Err(LinAlgError::NotAMatrix(_))));
+    }
+
+    // The second pivot is zero once the first column is eliminated.
+    let err = matrix![[1, 2], [2, 4]].lu().err().unwrap();
+    assert_eq!(err, LinAlgError::Singular(2));
+    assert_eq!(
+        err.to_string(),
+        "the matrix is singular: U has a zero at position 2 of its diagonal"
+    );
+    assert!(matches!(
+        Error::from(err),
+        Error::LinAlg(LinAlgError::Singular(2))
+    ));
+
+    let err = matrix![[0, 1], [0, 2]].lu().err().unwrap();
+    assert_eq!(err, LinAlgError::Singular(1));
+    assert_eq!(
+        LinAlgError::NotSquare(2, 3).to_string(),
+        "expected a square matrix, found 2 x 3"
+    );
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::{Tensor, TensorPromise, materialize_many};
+use simple_tensor::vector;
+
+use common::values;
+
+fn assert_bitwise_eq(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert_eq!(g.to_bits(), e.to_bits(), "{} != {}", g, e);
+    }
+}
+
+// Materializes the promise through the fast path and through the sorted graph,
+// which materialize_many() always uses, and checks they run the same ops.
+// With the check_fast_paths feature, the fast path runs the sorted graph too.
+fn materialize_both(promise: &TensorPromise<f64>) -> Vec<f64> {
+    let before = computed_op_count();
+    let fast = values(&promise.clone().materialize());
+    let fast_ops = computed_op_count() - before;
+
+    let before = computed_op_count();
+    let sorted = values(&materialize_many(&[promise])[0]);
+    let sorted_ops = computed_op_count() - before;
+
+    if !cfg!(feature = "check_fast_paths") {
+        assert_eq!(fast_ops, sorted_ops);
+    }
+
+    assert_bitwise_eq(&fast, &sorted);
+    fast
+}
+
+fn fixture() -> Tensor<f64> {
+    Tensor::from_vec(
+        vec![
+            -3.5,
+            -1.0,
+            -0.0,
+            0.0,
+            0.25,
+            2.0,
+            7.5,
+            f64::NAN,
+            f64::INFINITY,
+        ],
+        &[3, 3],
+    )
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn scalar_ops() {
+    let t = fixture();
+
+    let fused = ((&t + 1.0) * 2.0 - 0.5) / 4.0;
+    assert!(fused.is_linear_chain());
+    materialize_both(&fused);
+
+    let chain = (&t * 3.0).min_scalar(5.0).max_scalar(-2.0).clamp(-1.0, 4.0) + 1.0;
+    assert!(chain.is_linear_chain());
+    assert_bitwise_eq(
+        &materialize_both(&chain),
+        &[0.0, 0.0, 1.0, 1.0, 1.75, 5.0, 5.0, f64::NAN, 5.0],
+    );
+}
+
+#[test]
+fn unary_and_layout_ops() {
+    let t = fixture();
+
+    let unary = (&t * 2.0).isnan() + t.isinf().transpose().transpose();
+    assert!(!unary.is_linear_chain());
+    materialize_both(&unary);
+
+    let nan = (&t - 1.0).isnan();
+    assert!(nan.is_linear_chain());
+    assert_eq!(
+        materialize_both(&nan),
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+    );
+
+    let inf = t.isinf().min_scalar(0.5);
+    assert!(inf.is_linear_chain());
+    materialize_both(&inf);
+
+    let clean = t.clamp(-10.0, 10.0).max_scalar(-10.0).min_scalar(10.0);
+    let layouts = clean
+        .transpose()
+        .flip(&[0])
+        .unwrap()
+        .tile(&[2, 1])
+        .rolling_sum(2, 0)
+        .unwrap()
+        .diff(1, 1)
+        .unwrap()
+        .view(&[10])
+        .unwrap();
+    assert!(layouts.is_linear_chain());
+    materialize_both(&layouts);
+}
+
+#[test]
+fn caches_are_not_followed() {
+    let t = fixture();
+
+    // The node inside the cache is a chain, and is computed once.
+    let cached = ((&t + 1.0).isnan() * 2.0).cache();
+    let on_top = &cached - 1.0;
+    assert!(!on_top.is_linear_chain());
+
+    let expected = [-1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, 1.0, -1.0];
+    assert_bitwise_eq(&values(&on_top.clone().materialize()), &expected);
+    assert!(cached.is_cached());
+
+    let before = computed_op_count();
+    assert_bitwise_eq(&values(&on_top.materialize()), &expected);
+    assert_eq!(computed_op_count() - before, 1);
+}
+
+#[test]
+fn diamonds_and_binary_ops_take_the_general_path() {
+    let t = fixture();
+    let u = vector![1, 2, 3, 4, 5, 6, 7, 8, 9].view(&[3, 3]).unwrap();
+
+    let base = &t * 2.0;
+    let diamond = &base.min_scalar(0.0) + &base.max_scalar(1.0);
+    assert!(base.is_linear_chain());
+    assert!(!diamond.is_linear_chain());
+    materialize_both(&diamond);
+
+    let binary = (&t + &u) * 3.0;
+    assert!(!binary.is_linear_chain());
+    materialize_both(&binary);
+
+    // A chain shared by several graphs is still a chain for each of them.
+    let first = base.isnan();
+    let second = base.clamp(0.0, 1.0);
+    assert!(first.is_linear_chain() && second.is_linear_chain());
+    materialize_both(&first);
+    materialize_both(&second);
+}
+
+#[test]
+fn folded_chains() {
+    let t = Tensor::constant(fixture().iter().copied().collect(), &[3, 3]);
+    let chain = ((&t + 1.0).isnan() * 2.0).fold_constants();
+
+    // The whole chain is folded into a copy of its result.
+    assert!(chain.is_linear_chain());
+    materialize_both(&chain);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use simple_tensor::tensor::Dimension;
+use simple_tensor::{arange, matrix, vector};
+
+use common::values;
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn matrix_is_row_major() {
+    let m = matrix![[1, 2], [3, 4]];
+    let reshaped = arange!(1, 5).view(&[2, 2]).unwrap().materialize();
+
+    assert_eq!(m.shape(), &[2, 2]);
+    assert_eq!(values(&m), values(&reshaped));
+}
+
+#[test]
+fn matrix_takes_trailing_commas_and_single_rows() {
+    let m = matrix![[1.5, -2.0, 3,], [4, 5, 6],];
+    assert_eq!(m.shape(), &[2, 3]);
+    assert_eq!(values(&m), [1.5, -2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let row = matrix![[7, 8, 9]];
+    assert_eq!(row.shape(), &[1, 3]);
+    assert_eq!(values(&row), [7.0, 8.0, 9.0]);
+}
+
+#[test]
+fn vector_is_one_dimensional() {
+    let v = vector![1, 2.5, -3,];
+    assert_eq!(v.shape(), &[3]);
+    assert_eq!(values(&v), [1.0, 2.5, -3.0]);
+
+    let empty = vector![];
+    assert_eq!(empty.shape(), &[0]);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{arange, s, zeros};
+
+use common::values;
+
+#[test]
+fn flat_index_of_zeros_is_arange() {
+    let t: Tensor<f64> = zeros!(&[3, 4]);
+
+    let mapped = t.map_with_flat_index(|i, v| i as f64 + v);
+    assert_eq!(mapped.shape(), &[3, 4]);
+    assert_eq!(values(&mapped), values(&arange!(12)));
+}
+
+#[test]
+fn flat_index_follows_the_logical_order() {
+    let t = Tensor::from_vec((0..6).map(|i| i as f64).collect(), &[2, 3]);
+    let transposed = t.transpose().materialize();
+
+    // The index is the position in the transposed order, not in the buffer.
+    let mapped = transposed.map_with_flat_index(|i, v| (i, v as usize));
+    let pairs: Vec<_> = mapped.iter().copied().collect();
+    assert_eq!(pairs, [(0, 0), (1, 3), (2, 1), (3, 4), (4, 2), (5, 5)]);
+
+    let sliced = t.slice(s![.., 1..]).unwrap().materialize();
+    let mapped = sliced.map_with_flat_index(|i, v| i as f64 * 10.0 + v);
+    assert_eq!(values(&mapped), [1.0, 12.0, 24.0, 35.0]);
+}
+
+#[test]
+fn multi_index_matches_nested_loops() {
+    let shape = [2, 3, 4];
+    let t: Tensor<f64> = zeros!(&shape);
+
+    let mapped = t.map_with_multi_index(|index, v| index.iter().sum::<i32>() as f64 + v);
+
+    let mut expected = Vec::new();
+    for i in 0..shape[0] {
+        for j in 0..shape[1] {
+            for k in 0..shape[2] {
+                expected.push((i + j + k) as f64);
+            }
+        }
+    }
+
+    assert_eq!(mapped.shape(), &shape);
+    assert_eq!(values(&mapped), expected);
+}
+
+#[test]
+fn multi_index_on_a_strided_tensor() {
+    let t = Tensor::from_vec((0..6).map(|i| i as f64).collect(), &[2, 3]);
+    let flipped = t.flip_view(&[1]).unwrap();
+
+    let mapped = flipped.map_with_multi_index(|index, v| (index[0], index[1], v));
+    let triples: Vec<_> = mapped.iter().copied().collect();
+    assert_eq!(
+        triples,
+        [
+            (0, 0, 2.0),
+            (0, 1, 1.0),
+            (0, 2, 0.0),
+            (1, 0, 5.0),
+            (1, 1, 4.0),
+            (1, 2, 3.0)
+        ]
+    );
+}
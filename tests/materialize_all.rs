@@ -0,0 +1,193 @@
+#![cfg(feature = "parallel")]
+
+mod common;
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{
+    BatchProduct, ParallelismConfig, Tensor, TensorPromise, materialize_all,
+};
+
+use common::{arange_from, values};
+
+const CONFIGS: [ParallelismConfig; 4] = [
+    ParallelismConfig::CurrentThread,
+    ParallelismConfig::Rayon,
+    ParallelismConfig::Threads(1),
+    ParallelismConfig::Threads(4),
+];
+
+// Counts the elements the op computes, so Apply of an n-element tensor counts n per run.
+// Slow enough for every thread to reach the op before the first one is done with it.
+fn counted(x: &Tensor<f64>, calls: &Arc<AtomicUsize>) -> TensorPromise<f64> {
+    let calls = calls.clone();
+    x.apply_scalar(move |el| {
+        calls.fetch_add(1, Ordering::Relaxed);
+        thread::sleep(Duration::from_micros(200));
+        el * 2.0
+    })
+}
+
+// The ids of the threads computing the op.
+fn recorded(x: &Tensor<f64>, threads: &Arc<Mutex<HashSet<ThreadId>>>) -> TensorPromise<f64> {
+    let threads = threads.clone();
+    x.apply_scalar(move |el| {
+        threads.lock().unwrap().insert(thread::current().id());
+        el + 1.0
+    })
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn results_are_the_ones_of_materialize() {
+    let shared = arange_from(&[8, 8], 0.0);
+    let weights = (shared.sqrt() * 2.0).cache();
+
+    let promises: Vec<TensorPromise<f64>> = (0..100)
+        .map(|i| {
+            let own = arange_from(&[8, 8], i as f64);
+            match i % 3 {
+                0 => (&own * &shared).sqrt().normalize(1, 1e-5).unwrap(),
+                1 => ((&weights + &own) / 3.0).transpose(),
+                _ => own.bmm(&shared).unwrap() - &weights,
+            }
+        })
+        .collect();
+    let expected: Vec<Vec<f64>> = (promises.iter())
+        .map(|promise| values(&promise.clone().materialize()))
+        .collect();
+
+    for config in CONFIGS {
+        let results = materialize_all(&promises, config);
+
+        assert_eq!(results.len(), promises.len());
+        for (result, expected) in results.iter().zip(&expected) {
+            assert_eq!(values(result.as_ref().unwrap()), *expected, "{:?}", config);
+        }
+    }
+
+    assert!(materialize_all::<f64>(&[], ParallelismConfig::Threads(2)).is_empty());
+}
+
+#[test]
+fn shared_caches_are_computed_once() {
+    for config in CONFIGS {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let x = arange_from(&[16], 0.0);
+
+        // A cache over another, both read by every promise, computed from an op of their own.
+        let inner = (counted(&x, &calls) * &x).cache();
+        let outer = (counted(&x, &calls) * &inner).cache();
+        let promises: Vec<TensorPromise<f64>> =
+            (0..200).map(|i| &outer + &inner + i as f64).collect();
+
+        let results = materialize_all(&promises, config);
+        assert_eq!(calls.load(Ordering::Relaxed), 2 * 16, "{:?}", config);
+
+        let first = values(results[0].as_ref().unwrap());
+        assert_eq!(first[3], 6.0 * 18.0 + 18.0);
+        assert_eq!(values(results[199].as_ref().unwrap())[3], first[3] + 199.0);
+
+        // Filled, they are read in place from then on.
+        materialize_all(&promises, config);
+        assert_eq!(calls.load(Ordering::Relaxed), 2 * 16);
+    }
+
+    // A cache read by a single promise is left to it.
+    let calls = Arc::new(AtomicUsize::new(0));
+    let x = arange_from(&[16], 0.0);
+    let promises: Vec<TensorPromise<f64>> = (0..8)
+        .map(|i| (counted(&x, &calls) * &x + i as f64).cache() * 2.0)
+        .collect();
+    let results = materialize_all(&promises, ParallelismConfig::Threads(4));
+    assert_eq!(calls.load(Ordering::Relaxed), 8 * 16);
+    assert_eq!(values(results[7].as_ref().unwrap())[1], (2.0 + 7.0) * 2.0);
+}
+
+#[test]
+fn threads_are_bounded_by_the_config() {
+    let caller = thread::current().id();
+    let run = |config: ParallelismConfig| {
+        let threads = Arc::new(Mutex::new(HashSet::new()));
+        let promises: Vec<TensorPromise<f64>> = (0..200)
+            .map(|i| recorded(&arange_from(&[64], i as f64), &threads).sqrt())
+            .collect();
+
+        assert!(materialize_all(&promises, config).iter().all(Result::is_ok));
+        threads.lock().unwrap().clone()
+    };
+
+    let threads = run(ParallelismConfig::CurrentThread);
+    assert_eq!(threads, HashSet::from([caller]));
+
+    for n in [1, 2, 3] {
+        let threads = run(ParallelismConfig::Threads(n));
+        assert!(!threads.is_empty() && threads.len() <= n, "{:?}", threads);
+        assert!(!threads.contains(&caller));
+    }
+
+    // Rayon runs in the pool of the caller.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let threads = pool.install(|| run(ParallelismConfig::Rayon));
+    assert!(!threads.is_empty() && threads.len() <= 2, "{:?}", threads);
+    assert!(!threads.contains(&caller));
+}
+
+#[test]
+fn panics_are_returned_with_the_index_of_their_promise() {
+    let x = arange_from(&[4], 1.0);
+    let mut promises: Vec<TensorPromise<f64>> = (0..8).map(|i| &x * i as f64).collect();
+    promises[3] = x.apply_scalar(|el| {
+        if el > 2.0 {
+            panic!("{} is too big", el)
+        } else {
+            el
+        }
+    });
+    promises[5] = (&x - 10.0).sqrt().assert_finite("logs");
+
+    for config in CONFIGS {
+        let results = materialize_all(&promises, config);
+
+        for (i, result) in results.iter().enumerate() {
+            match i {
+                3 => {
+                    let err = result.as_ref().err().unwrap();
+                    assert!(
+                        matches!(err, OpError::PromisePanicked(3, message) if &**message == "3 is too big")
+                    );
+                    assert_eq!(
+                        err.to_string(),
+                        "materializing promise 3 panicked: 3 is too big"
+                    );
+                }
+                5 => assert!(matches!(result, Err(OpError::AssertionFailed(_)))),
+                _ => {
+                    let expected: Vec<f64> = (1..5).map(|el| (el * i) as f64).collect();
+                    assert_eq!(values(result.as_ref().unwrap()), expected, "{:?}", config);
+                }
+            }
+        }
+    }
+
+    // A shared cache that panics is reported by every promise reading it.
+    let failing = x
+        .apply_scalar(|_| -> f64 { panic!("no cache for you") })
+        .cache();
+    let promises = [&x + 1.0, &failing + 1.0, &x * 2.0, &failing * 2.0];
+    let results = materialize_all(&promises, ParallelismConfig::Threads(2));
+
+    assert!(matches!(&results[1], Err(OpError::PromisePanicked(1, _))));
+    assert!(matches!(&results[3], Err(OpError::PromisePanicked(3, _))));
+    assert_eq!(values(results[0].as_ref().unwrap()), [2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(values(results[2].as_ref().unwrap()), [2.0, 4.0, 6.0, 8.0]);
+}
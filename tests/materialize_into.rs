@@ -0,0 +1,136 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use simple_tensor::tensor::Tensor;
+
+use common::{arange, lock_globals, values};
+
+// Counts the bytes currently allocated by the whole test binary, and the allocations of at
+// least BIG bytes, the size of a result below.
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static BIG_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+const BIG: usize = 64 * 64 * std::mem::size_of::<f64>();
+
+// The value expected from each element of the input.
+#[cfg(not(feature = "check_fast_paths"))]
+type Expected = fn(f64) -> f64;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE.fetch_add(layout.size(), Ordering::SeqCst);
+        if layout.size() >= BIG {
+            BIG_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn buffer_ptr(t: &Tensor<f64>) -> *const f64 {
+    t.try_as_slice().unwrap().as_ptr()
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+// The profiler keeps a record of every node computed.
+#[cfg(not(feature = "profiling"))]
+#[test]
+fn repeated_calls_keep_the_memory_flat() {
+    // Counted for the whole binary, so the tests of this file run one at a time.
+    let _globals = lock_globals();
+    let x = arange(&[64, 64]);
+    let y = Tensor::from_scalar(0.5, &[64, 64]);
+    let mut dest = Tensor::from_scalar(0.0, &[64, 64]);
+    let dest_ptr = buffer_ptr(&dest);
+
+    // The first call fills whatever the crate keeps around, like thread pools.
+    (&(&x * 2.0) + &y).materialize_into(&mut dest).unwrap();
+    let live = LIVE.load(Ordering::SeqCst);
+
+    for i in 0..1000 {
+        let shift = i as f64;
+        (&(&x * 2.0) + &y + shift)
+            .materialize_into(&mut dest)
+            .unwrap();
+
+        assert_eq!(buffer_ptr(&dest), dest_ptr);
+        assert_eq!(values(&dest)[3], 3.0 * 2.0 + 0.5 + shift);
+    }
+
+    assert_eq!(LIVE.load(Ordering::SeqCst), live);
+}
+
+// The cross-check of check_fast_paths computes the graphs again, in buffers of their own.
+#[cfg(not(feature = "check_fast_paths"))]
+#[test]
+fn the_last_op_writes_into_the_destination() {
+    let _globals = lock_globals();
+    let x = arange(&[64, 64]);
+    let y = Tensor::from_scalar(3.0, &[64, 64]);
+    let s = Tensor::from_scalar(4.0, &[1]);
+    let mut dest = Tensor::from_scalar(0.0, &[64, 64]);
+    let dest_ptr = buffer_ptr(&dest);
+
+    // Reading tensors that cannot be overwritten, materialize() allocates the result.
+    let before = BIG_ALLOCATIONS.load(Ordering::SeqCst);
+    let result = (&x - &y).materialize();
+    assert_eq!(BIG_ALLOCATIONS.load(Ordering::SeqCst), before + 1);
+
+    let cases: [(_, Expected); 5] = [
+        ((&x - &y), |x| x - 3.0),
+        ((&x * 0.5 + 1.0), |x| x * 0.5 + 1.0),
+        (x.sqrt(), f64::sqrt),
+        (-&x, |x| -x),
+        ((&s / &x), |x| 4.0 / x),
+    ];
+
+    for (promise, expected) in cases {
+        let before = BIG_ALLOCATIONS.load(Ordering::SeqCst);
+        promise.materialize_into(&mut dest).unwrap();
+        assert_eq!(BIG_ALLOCATIONS.load(Ordering::SeqCst), before);
+
+        assert_eq!(buffer_ptr(&dest), dest_ptr);
+        let expected: Vec<f64> = values(&x).into_iter().map(expected).collect();
+        assert_eq!(values(&dest), expected);
+    }
+
+    drop(result);
+}
+
+#[test]
+fn other_ops_are_copied_into_the_destination() {
+    let _globals = lock_globals();
+    let x = arange(&[8, 8]);
+    let mut dest = Tensor::from_scalar(0.0, &[8, 8]);
+    let dest_ptr = buffer_ptr(&dest);
+
+    // Written through a transposed layout, and by an op that cannot reuse its input.
+    x.transpose().materialize_into(&mut dest).unwrap();
+    assert_eq!(values(&dest)[1], 8.0);
+    assert_eq!(buffer_ptr(&dest), dest_ptr);
+
+    x.rolling_sum(1, 1)
+        .unwrap()
+        .materialize_into(&mut dest)
+        .unwrap();
+    assert_eq!(values(&dest), values(&x));
+    assert_eq!(buffer_ptr(&dest), dest_ptr);
+
+    // A destination read by its own promise is computed first.
+    (&dest * 2.0 + &dest).materialize_into(&mut dest).unwrap();
+    let expected: Vec<f64> = values(&x).iter().map(|x| x * 3.0).collect();
+    assert_eq!(values(&dest), expected);
+}
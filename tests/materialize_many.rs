@@ -0,0 +1,123 @@
+mod common;
+
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::{TensorPromise, materialize_many};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+// Number of ops computed by `f`.
+fn count_ops<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = computed_op_count();
+    let result = f();
+
+    (result, computed_op_count() - before)
+}
+
+fn materialize_each(promises: &[&TensorPromise<f64>]) -> (Vec<Vec<f64>>, usize) {
+    count_ops(|| {
+        promises
+            .iter()
+            .map(|promise| values(&(*promise).clone().materialize()))
+            .collect()
+    })
+}
+
+fn materialize_batch(promises: &[&TensorPromise<f64>]) -> (Vec<Vec<f64>>, usize) {
+    count_ops(|| materialize_many(promises).iter().map(values).collect())
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn shared_subexpressions_are_computed_once() {
+    let a = matrix![[1, 2], [3, 4]];
+    let b = matrix![[5, 6], [7, 8]];
+    let c = matrix![[1, 1], [2, 2]];
+
+    let product = &(&a * &b) - &c;
+    let mean = product.rolling_mean(2, 1).unwrap();
+    let squares = &product * &c;
+
+    let (shared, shared_ops) = materialize_each(&[&product]);
+    let (expected, each_ops) = materialize_each(&[&mean, &squares]);
+    let (got, batch_ops) = materialize_batch(&[&mean, &squares]);
+
+    assert_eq!(got, expected);
+    assert_eq!(shared[0], [4.0, 11.0, 19.0, 30.0]);
+    assert_eq!(batch_ops, each_ops - shared_ops);
+}
+
+#[test]
+fn diamond_dependencies() {
+    let a = vector![1, 2, 3, 4];
+    let b = vector![4, 3, 2, 1];
+
+    let top = &a * &b;
+    let left = &top + &a;
+    let right = &top - &b;
+    let bottom = &left * &right;
+
+    let (expected, each_ops) = materialize_each(&[&left, &right, &bottom]);
+    let (got, batch_ops) = materialize_batch(&[&left, &right, &bottom]);
+
+    assert_eq!(got, expected);
+    assert_eq!(got[2], [0.0, 24.0, 36.0, 24.0]);
+    // Each of the four nodes once.
+    assert_eq!(batch_ops, 4);
+    assert!(each_ops > batch_ops);
+}
+
+#[test]
+fn sinks_used_by_other_sinks() {
+    let a = vector![1, 2, 3];
+    let b = vector![2, 2, 2];
+
+    let inner = &a * &b;
+    let outer = &inner + &inner;
+
+    // The order of the sinks does not change the results.
+    let (got, batch_ops) = materialize_batch(&[&outer, &inner]);
+    assert_eq!(got, [vec![4.0, 8.0, 12.0], vec![2.0, 4.0, 6.0]]);
+    assert_eq!(batch_ops, 2);
+
+    let (got, _) = materialize_batch(&[&inner, &outer, &inner]);
+    assert_eq!(
+        got,
+        [
+            vec![2.0, 4.0, 6.0],
+            vec![4.0, 8.0, 12.0],
+            vec![2.0, 4.0, 6.0]
+        ]
+    );
+
+    // The inputs of the sinks are left untouched.
+    assert_eq!(values(&a), [1.0, 2.0, 3.0]);
+    assert_eq!(values(&inner.materialize()), [2.0, 4.0, 6.0]);
+}
+
+#[test]
+fn cache_nodes_in_a_batch() {
+    let a = vector![1, 2, 3];
+    let b = vector![3, 2, 1];
+
+    let cached = (&a * &b).cache();
+    let left = &cached + &a;
+    let right = &cached - &b;
+
+    let (got, batch_ops) = materialize_batch(&[&left, &right]);
+    assert_eq!(got, [vec![4.0, 6.0, 6.0], vec![0.0, 2.0, 2.0]]);
+    // The cache is a node of its own.
+    assert_eq!(batch_ops, 4);
+    assert!(cached.is_cached());
+
+    // Once filled, the cache is read instead of computed.
+    let (got, batch_ops) = materialize_batch(&[&left, &right]);
+    assert_eq!(got, [vec![4.0, 6.0, 6.0], vec![0.0, 2.0, 2.0]]);
+    assert_eq!(batch_ops, 2);
+}
+
+#[test]
+fn empty_batch() {
+    assert!(materialize_many::<f64>(&[]).is_empty());
+}
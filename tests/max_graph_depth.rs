@@ -0,0 +1,32 @@
+// On its own, as the limit is shared by every test of the binary.
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Tensor, max_graph_depth, set_max_graph_depth};
+
+#[test]
+fn deeper_graphs_than_the_limit_fail() {
+    assert_eq!(max_graph_depth(), usize::MAX);
+
+    let t = Tensor::from_scalar(1.0, &[2]);
+    let mut promise = &t + &t;
+    for _ in 1..20 {
+        promise = &promise + &t;
+    }
+
+    set_max_graph_depth(19);
+    match promise.clone().try_materialize() {
+        Err(OpError::GraphTooDeep(depth, max)) => assert_eq!((depth, max), (20, 19)),
+        other => panic!("expected GraphTooDeep, got {:?}", other.map(|_| ())),
+    }
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        promise.clone().materialize()
+    }));
+    assert!(panic.is_err());
+
+    set_max_graph_depth(20);
+    let result = promise.try_materialize().unwrap();
+    assert_eq!(result.iter().copied().collect::<Vec<_>>(), [21.0, 21.0]);
+
+    set_max_graph_depth(usize::MAX);
+}
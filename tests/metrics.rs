@@ -0,0 +1,184 @@
+mod common;
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::{
+    BinaryRoute, InMemoryMetrics, MetricKey, MetricsSink, Tensor, clear_metrics_sink,
+    materialize_many, set_metrics_sink,
+};
+
+use common::{arange, lock_globals};
+
+// Registers a new InMemoryMetrics for the duration of a test.
+fn in_memory() -> Arc<InMemoryMetrics> {
+    let metrics = Arc::new(InMemoryMetrics::new());
+    set_metrics_sink(metrics.clone());
+
+    metrics
+}
+
+// Every call the sink received, in order.
+#[derive(Default)]
+struct CallLog {
+    calls: Mutex<Vec<(MetricKey, u64)>>,
+}
+
+impl MetricsSink for CallLog {
+    fn incr(&self, key: MetricKey, by: u64) {
+        self.calls.lock().unwrap().push((key, by));
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn a_known_graph_gives_exact_counts() {
+    let _globals = lock_globals();
+    let (a, b, c) = (arange(&[2, 3]), arange(&[2, 3]), arange(&[2, 3]));
+    let promise = (&a + &b) * &c;
+
+    let metrics = in_memory();
+    promise.materialize();
+    clear_metrics_sink();
+
+    assert_eq!(metrics.get(MetricKey::OpsComputed("Add")), 1);
+    assert_eq!(metrics.get(MetricKey::OpsComputed("Mul")), 1);
+    assert_eq!(metrics.get(MetricKey::ElementsProcessed), 12);
+    assert_eq!(metrics.get(MetricKey::Materializations), 1);
+    assert_eq!(metrics.get(MetricKey::CacheHits), 0);
+    assert_eq!(metrics.get(MetricKey::CacheMisses), 0);
+    assert_eq!(
+        metrics.get(MetricKey::BinaryRoutes(BinaryRoute::Contiguous)),
+        2
+    );
+    assert_eq!(metrics.snapshot().len(), 5);
+}
+
+#[test]
+fn caches_miss_once_then_hit() {
+    let _globals = lock_globals();
+    let (a, b) = (arange(&[4]), arange(&[4]));
+    let cached = (&a + &b).cache();
+
+    let metrics = in_memory();
+    cached.clone().materialize();
+    assert_eq!(metrics.get(MetricKey::CacheMisses), 1);
+    assert_eq!(metrics.get(MetricKey::CacheHits), 0);
+    assert_eq!(metrics.get(MetricKey::OpsComputed("Add")), 1);
+
+    cached.clone().materialize();
+    clear_metrics_sink();
+
+    assert_eq!(metrics.get(MetricKey::CacheMisses), 1);
+    assert_eq!(metrics.get(MetricKey::CacheHits), 1);
+    assert_eq!(metrics.get(MetricKey::OpsComputed("Add")), 1);
+    assert_eq!(metrics.get(MetricKey::Materializations), 2);
+}
+
+#[test]
+fn fusing_chains_are_counted() {
+    let _globals = lock_globals();
+    let t = arange(&[8]);
+
+    let metrics = in_memory();
+    let promise = ((&t + 1.0) + 2.0) * 3.0;
+    let before = computed_op_count();
+    promise.materialize();
+    let computed = computed_op_count() - before;
+    clear_metrics_sink();
+
+    // The three scalar ops are a single node.
+    assert_eq!(computed, 1);
+    assert_eq!(metrics.get(MetricKey::FusionRewrites), 2);
+
+    // Built without a sink, nothing is counted.
+    let metrics = Arc::new(InMemoryMetrics::new());
+    let promise = ((&t + 1.0) + 2.0) * 3.0;
+    set_metrics_sink(metrics.clone());
+    promise.materialize();
+    clear_metrics_sink();
+
+    assert_eq!(metrics.get(MetricKey::FusionRewrites), 0);
+    assert_eq!(metrics.get(MetricKey::Materializations), 1);
+}
+
+#[test]
+fn the_sink_gets_a_single_call_per_key() {
+    let _globals = lock_globals();
+    let tensors: Vec<Tensor<f64>> = (0..6).map(|_| arange(&[5])).collect();
+    let first = &tensors[0] + &tensors[1];
+    let sum = (tensors[2..].iter()).fold(first, |acc, t| acc + t);
+    let other = &tensors[0] - &tensors[1];
+
+    let log = Arc::new(CallLog::default());
+    set_metrics_sink(log.clone());
+    materialize_many(&[&sum, &other]);
+    clear_metrics_sink();
+
+    let calls = log.calls.lock().unwrap().clone();
+    let keys: HashSet<MetricKey> = calls.iter().map(|&(key, _)| key).collect();
+    assert_eq!(keys.len(), calls.len(), "{:?}", calls);
+
+    assert!(
+        calls.contains(&(MetricKey::OpsComputed("Add"), 5)),
+        "{:?}",
+        calls
+    );
+    assert!(
+        calls.contains(&(MetricKey::OpsComputed("Sub"), 1)),
+        "{:?}",
+        calls
+    );
+    assert!(
+        calls.contains(&(MetricKey::ElementsProcessed, 30)),
+        "{:?}",
+        calls
+    );
+    assert!(
+        calls.contains(&(MetricKey::Materializations, 1)),
+        "{:?}",
+        calls
+    );
+}
+
+#[test]
+fn materializations_on_several_threads_add_up() {
+    let _globals = lock_globals();
+    let (a, b) = (Arc::new(arange(&[16])), Arc::new(arange(&[16])));
+
+    let metrics = in_memory();
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..25 {
+                    (&*a + &*b).materialize();
+                }
+            });
+        }
+    });
+    clear_metrics_sink();
+
+    assert_eq!(metrics.get(MetricKey::Materializations), 100);
+    assert_eq!(metrics.get(MetricKey::OpsComputed("Add")), 100);
+    assert_eq!(metrics.get(MetricKey::ElementsProcessed), 1600);
+}
+
+#[test]
+fn nothing_is_counted_without_a_sink() {
+    let _globals = lock_globals();
+    let metrics = in_memory();
+    clear_metrics_sink();
+
+    let t = arange(&[4]);
+    ((&t + 1.0) * &t).materialize();
+
+    assert!(metrics.snapshot().is_empty());
+
+    metrics.incr(MetricKey::CacheHits, 3);
+    assert_eq!(metrics.get(MetricKey::CacheHits), 3);
+    metrics.reset();
+    assert!(metrics.snapshot().is_empty());
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{ElementwiseMinMax, Tensor};
+use simple_tensor::{matrix, s, vector};
+
+use common::values;
+
+// Bitwise equality, so the sign of the zeros is checked too. Any NaN matches any NaN.
+fn assert_same(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert!(
+            (g.is_nan() && e.is_nan()) || g.to_bits() == e.to_bits(),
+            "{:?} != {:?}",
+            got,
+            expected
+        );
+    }
+}
+
+const NAN: f64 = f64::NAN;
+const INF: f64 = f64::INFINITY;
+
+fn fixture() -> (Tensor<f64>, Tensor<f64>) {
+    (
+        vector![1.0, NAN, -0.0, 0.0, INF, -INF, NAN, 3.0],
+        vector![2.0, 5.0, 0.0, -0.0, -INF, 7.0, NAN, NAN],
+    )
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn nan_propagating_variants() {
+    let (a, b) = fixture();
+
+    let min = a.minimum(&b).unwrap().materialize();
+    assert_same(&values(&min), &[1.0, NAN, -0.0, -0.0, -INF, -INF, NAN, NAN]);
+
+    let max = a.maximum(&b).unwrap().materialize();
+    assert_same(&values(&max), &[2.0, NAN, 0.0, 0.0, INF, 7.0, NAN, NAN]);
+}
+
+#[test]
+fn nan_ignoring_variants() {
+    let (a, b) = fixture();
+
+    let min = a.minimum_num(&b).unwrap().materialize();
+    assert_same(&values(&min), &[1.0, 5.0, -0.0, -0.0, -INF, -INF, NAN, 3.0]);
+
+    let max = a.maximum_num(&b).unwrap().materialize();
+    assert_same(&values(&max), &[2.0, 5.0, 0.0, 0.0, INF, 7.0, NAN, 3.0]);
+
+    // The order of the operands does not matter, signed zeros included.
+    let swapped = b.minimum_num(&a).unwrap().materialize();
+    assert_same(&values(&swapped), &values(&min));
+}
+
+#[test]
+fn scalar_forms() {
+    let t = vector![-1.0, NAN, -0.0, 2.0, INF];
+
+    let min = t.min_scalar(0.0).materialize();
+    assert_same(&values(&min), &[-1.0, NAN, -0.0, 0.0, 0.0]);
+
+    let max = t.max_scalar(0.0).materialize();
+    assert_same(&values(&max), &[0.0, NAN, 0.0, 2.0, INF]);
+
+    let nan_scalar = t.max_scalar(NAN).materialize();
+    assert!(values(&nan_scalar).iter().all(|v| v.is_nan()));
+}
+
+#[test]
+fn clamp_matches_the_composed_ops() {
+    let t = vector![-5.0, -1.0, -0.0, 0.5, 3.0, NAN, INF];
+    let (lo, hi) = (-1.0, 2.0);
+
+    let clamped = values(&t.clamp(lo, hi).materialize());
+    assert_same(&clamped, &[-1.0, -1.0, -0.0, 0.5, 2.0, NAN, 2.0]);
+
+    let lo_t = Tensor::from_scalar(lo, &[7]);
+    let hi_t = Tensor::from_scalar(hi, &[7]);
+    let composed = t
+        .maximum(&lo_t)
+        .unwrap()
+        .minimum(&hi_t)
+        .unwrap()
+        .materialize();
+    assert_same(&clamped, &values(&composed));
+}
+
+#[test]
+fn scalar_ops_around_min_max_are_not_fused_into_them() {
+    let a = vector![1.0, 4.0, -2.0];
+    let b = vector![3.0, 0.0, -1.0];
+
+    let result = ((&a + 1.0).maximum(&(&b * 2.0)).unwrap() * 2.0 - 1.0).materialize();
+    assert_eq!(values(&result), [11.0, 9.0, -3.0]);
+
+    let result = (a.min_scalar(2.0) + 1.0).min_scalar(2.5) * 2.0;
+    assert_eq!(values(&result.materialize()), [4.0, 5.0, -2.0]);
+}
+
+#[test]
+fn strided_and_offset_inputs() {
+    let a = matrix![[1, 5, 2], [8, 0, 6]];
+    let b = matrix![[4, 3], [2, 9], [7, 1]];
+
+    let min = a.minimum(&b.transpose()).unwrap().materialize();
+    assert_eq!(values(&min), [1.0, 2.0, 2.0, 3.0, 0.0, 1.0]);
+
+    // Slices that keep an offset into their buffer.
+    let row = a.slice(s![1..2, ..]).unwrap().materialize();
+    let col = b.slice(s![.., 1..2]).unwrap().transpose().materialize();
+    let max = row.maximum(&col).unwrap().materialize();
+    assert_eq!(values(&max), [8.0, 9.0, 6.0]);
+
+    let flipped = a.flip_view(&[1]).unwrap();
+    let max = flipped.maximum_num(&a).unwrap().materialize();
+    assert_eq!(values(&max), [2.0, 5.0, 2.0, 8.0, 0.0, 8.0]);
+}
+
+#[test]
+fn shapes_must_match() {
+    let a = vector![1, 2, 3];
+    let b = vector![1, 2];
+
+    let Err(err) = a.minimum(&b) else {
+        panic!("the shapes do not match");
+    };
+    assert!(matches!(err.cause(), OpError::NotSameShape(_, _)));
+    assert_eq!(err.context().unwrap().op(), "Minimum");
+}
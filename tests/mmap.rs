@@ -0,0 +1,318 @@
+#![cfg(feature = "mmap")]
+
+mod common;
+
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use simple_tensor::s;
+use simple_tensor::ss;
+use simple_tensor::tensor::errors::{IoError, OpError};
+use simple_tensor::tensor::{Dimension, MappedTensor, Tensor, TensorData};
+
+use common::values;
+
+// A path in the temporary directory, removed when dropped.
+struct TempPath(PathBuf);
+
+impl TempPath {
+    fn new(name: &str) -> Self {
+        let name = format!("simple_tensor_{}_{}.npy", std::process::id(), name);
+
+        Self(std::env::temp_dir().join(name))
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+// The header of an NPY file of version 1.0, padded with spaces so the elements start
+// `shift` bytes after a multiple of 64. NumPy always starts them at a multiple of 64.
+fn npy_header(descr: &str, fortran_order: bool, shape: &[usize], shift: usize) -> Vec<u8> {
+    let shape: Vec<String> = shape.iter().map(|dim| format!("{},", dim)).collect();
+    let order = if fortran_order { "True" } else { "False" };
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': {}, 'shape': ({}), }}",
+        descr,
+        order,
+        shape.join(" ")
+    );
+
+    while (10 + dict.len() + 1) % 64 != shift {
+        dict.push(' ');
+    }
+    dict.push('\n');
+
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend((dict.len() as u16).to_le_bytes());
+    header.extend(dict.as_bytes());
+
+    header
+}
+
+fn write_npy(
+    name: &str,
+    descr: &str,
+    fortran_order: bool,
+    shape: &[usize],
+    values: &[f64],
+) -> TempPath {
+    let path = TempPath::new(name);
+    let mut file = File::create(&path.0).unwrap();
+
+    file.write_all(&npy_header(descr, fortran_order, shape, 0))
+        .unwrap();
+    for value in values {
+        file.write_all(&value.to_le_bytes()).unwrap();
+    }
+
+    path
+}
+
+fn arange(len: usize) -> Vec<f64> {
+    (0..len).map(|i| i as f64).collect()
+}
+
+fn open(path: &TempPath) -> Result<MappedTensor<f64>, IoError> {
+    TensorData::<f64>::open_npy_mmap(&path.0)
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn reads_the_elements_of_the_file() {
+    let path = write_npy("reads", "<f8", false, &[3, 4], &arange(12));
+
+    let mapped = open(&path).unwrap();
+
+    assert_eq!(mapped.shape(), [3, 4]);
+    assert_eq!(mapped.len(), 12);
+    assert!(mapped.iter().copied().eq(arange(12)));
+    assert_eq!(mapped.fold(0.0, |acc, el| acc + el), 66.0);
+    assert_eq!(mapped.data(), Tensor::from_vec(arange(12), &[3, 4]).data());
+    assert_eq!(mapped.to_string(), mapped.data().to_string());
+}
+
+#[test]
+fn headers_that_do_not_match_are_rejected() {
+    let path = write_npy("wrong_dtype", "<f4", false, &[4], &arange(2));
+    assert!(matches!(
+        open(&path),
+        Err(IoError::DtypeMismatch(expected, found)) if &*expected == "<f8" && &*found == "<f4"
+    ));
+
+    let path = write_npy("big_endian", ">f8", false, &[4], &arange(4));
+    assert!(matches!(open(&path), Err(IoError::DtypeMismatch(_, _))));
+
+    let path = write_npy("fortran", "<f8", true, &[2, 2], &arange(4));
+    assert!(matches!(open(&path), Err(IoError::FortranOrder)));
+
+    let path = write_npy("truncated", "<f8", false, &[3, 4], &arange(11));
+    assert!(matches!(
+        open(&path),
+        Err(IoError::Truncated(expected, found)) if expected == 128 + 96 && found == 128 + 88
+    ));
+
+    let path = write_npy("no_axes", "<f8", false, &[], &arange(1));
+    assert!(matches!(open(&path), Err(IoError::InvalidHeader(_))));
+
+    let path = TempPath::new("not_npy");
+    std::fs::write(&path.0, b"PK\x03\x04 definitely a zip file").unwrap();
+    assert!(matches!(open(&path), Err(IoError::NotNpy)));
+
+    let missing = TempPath::new("missing");
+    let err = open(&missing).unwrap_err();
+    assert!(matches!(&err, IoError::Io(io) if io.kind() == std::io::ErrorKind::NotFound));
+}
+
+#[test]
+fn misaligned_elements_are_rejected() {
+    let path = TempPath::new("misaligned");
+    let mut bytes = npy_header("<f8", false, &[2], 3);
+    bytes.extend(arange(2).iter().flat_map(|x| x.to_le_bytes()));
+    std::fs::write(&path.0, &bytes).unwrap();
+
+    let err = open(&path).unwrap_err();
+
+    assert!(matches!(err, IoError::Misaligned(131, 8)));
+    assert_eq!(
+        err.to_string(),
+        "the elements start at byte 131, which is not a multiple of their alignment of 8"
+    );
+
+    // Single bytes are never misaligned.
+    let path = TempPath::new("bytes");
+    let mut bytes = npy_header("|u1", false, &[3], 3);
+    bytes.extend([7, 8, 9]);
+    std::fs::write(&path.0, &bytes).unwrap();
+
+    let mapped = TensorData::<u8>::open_npy_mmap(&path.0).unwrap();
+    assert!(mapped.iter().copied().eq([7, 8, 9]));
+}
+
+#[test]
+fn slices_stay_mapped_and_iterate_strided() {
+    let path = write_npy("slices", "<f8", false, &[4, 5], &arange(20));
+    let mapped = open(&path).unwrap();
+
+    let rows = mapped.slice(s![1..3, ..]).unwrap();
+    assert_eq!(rows.shape(), [2, 5]);
+    assert!(rows.copied_iter().eq((5..15).map(|i| i as f64)));
+
+    // The row starts 5 elements into the map, nothing was copied.
+    let base = Tensor::from_mapped(&mapped).with_raw(|ptr, _| ptr).unwrap();
+    let first = Tensor::from_mapped(&rows).with_raw(|ptr, _| ptr).unwrap();
+    assert_eq!(first, base.wrapping_add(5));
+
+    let columns = mapped.step_slice(ss![.., 0..5;2]).unwrap();
+    assert!(!columns.is_contiguous());
+    assert!(
+        columns
+            .copied_iter()
+            .eq([0, 2, 4, 5, 7, 9, 10, 12, 14, 15, 17, 19].map(f64::from))
+    );
+
+    let transposed = mapped.transpose().slice(s![1, ..]).unwrap();
+    assert!(transposed.copied_iter().eq([1.0, 6.0, 11.0, 16.0]));
+    assert_eq!(columns.fold(0.0, |acc, el| acc + el), 114.0);
+}
+
+#[test]
+fn mapped_tensors_cannot_be_written() {
+    let path = write_npy("read_only", "<f8", false, &[2, 2], &arange(4));
+    let mapped = open(&path).unwrap();
+    let mut t = Tensor::from_mapped(&mapped);
+
+    assert!(t.is_read_only());
+    assert!(matches!(
+        t.with_raw_mut(|ptr, _| unsafe { *ptr = 9.0 }),
+        Err(OpError::ReadOnlyBuffer)
+    ));
+    assert!(matches!(
+        t.assign_from_iter([9.0]),
+        Err(OpError::ReadOnlyBuffer)
+    ));
+    assert!(matches!(
+        t.assign_slice_from_iter(s![0, ..], [9.0]),
+        Err(OpError::ReadOnlyBuffer)
+    ));
+    assert!(matches!(
+        (&t + 1.0).materialize_into(&mut t),
+        Err(OpError::ReadOnlyBuffer)
+    ));
+
+    // A copy can be written, the file is left as it was.
+    t.make_unique();
+    assert!(!t.is_read_only());
+    t.assign_from_iter([9.0]).unwrap();
+
+    assert_eq!(values(&t), [9.0, 1.0, 2.0, 3.0]);
+    assert!(mapped.iter().copied().eq(arange(4)));
+    assert!(open(&path).unwrap().iter().copied().eq(arange(4)));
+}
+
+#[test]
+fn graph_ops_over_a_mapped_leaf_match_an_in_memory_copy() {
+    let path = write_npy("graph", "<f8", false, &[6, 7], &arange(42));
+    let mapped = open(&path).unwrap();
+
+    let t = Tensor::from_mapped(&mapped);
+    let copy = Tensor::from_vec(arange(42), &[6, 7]);
+
+    let compute = |t: &Tensor<f64>| {
+        let scaled = (t * 0.5 + 1.0).transpose();
+        (&scaled * &t.transpose()).min_scalar(100.0).materialize()
+    };
+
+    assert_eq!(values(&compute(&t)), values(&compute(&copy)));
+    assert_eq!(t.sum_scalar(), copy.sum_scalar());
+
+    let tail = Tensor::from_mapped(&mapped.slice(s![4.., 2..]).unwrap());
+    let expected = copy.slice(s![4.., 2..]).unwrap().materialize();
+    assert_eq!(
+        values(&(&tail - 1.0).materialize()),
+        values(&(&expected - 1.0).materialize())
+    );
+
+    // The mapped leaf is only read, never reused as the output of an op.
+    drop(t);
+    assert!(mapped.iter().copied().eq(arange(42)));
+}
+
+#[test]
+fn large_sparse_files_are_read_lazily() {
+    // 2 GiB of elements, but only the pages written here exist on disk.
+    let shape = [1 << 14, 1 << 14];
+    let path = TempPath::new("sparse");
+    let mut file = File::create(&path.0).unwrap();
+    let header = npy_header("<f8", false, &shape, 0);
+    file.write_all(&header).unwrap();
+    file.set_len(header.len() as u64 + 8 * (1 << 28)).unwrap();
+
+    for (row, col, value) in [
+        (0, 3, 1.5),
+        (9000, 100, -2.0),
+        ((1 << 14) - 1, (1 << 14) - 1, 7.0),
+    ] {
+        let position = (header.len() + 8 * (row * (1 << 14) + col)) as u64;
+        file.seek(SeekFrom::Start(position)).unwrap();
+        file.write_all(&f64::to_le_bytes(value)).unwrap();
+    }
+    drop(file);
+
+    let mapped = open(&path).unwrap();
+    assert_eq!(mapped.shape(), shape);
+    assert_eq!(mapped.len(), 1 << 28);
+
+    let first = mapped.slice(s![0, 0..5]).unwrap();
+    assert!(first.copied_iter().eq([0.0, 0.0, 0.0, 1.5, 0.0]));
+
+    let column = mapped.slice(s![8999..9001, 100]).unwrap();
+    assert!(column.copied_iter().eq([0.0, -2.0]));
+
+    let corner = Tensor::from_mapped(&mapped.slice(s![-2.., -2..]).unwrap());
+    assert_eq!(
+        values(&(&corner * 2.0).materialize()),
+        [0.0, 0.0, 0.0, 14.0]
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn files_are_unmapped_and_closed_on_drop() {
+    let path = write_npy("unmapped", "<f8", false, &[8], &arange(8));
+    let is_mapped = || {
+        std::fs::read_to_string("/proc/self/maps")
+            .unwrap()
+            .contains(path.0.to_str().unwrap())
+    };
+    let open_fds = || std::fs::read_dir("/proc/self/fd").unwrap().count();
+
+    let mapped = open(&path).unwrap();
+    let slice = mapped.slice(s![2..]).unwrap();
+    let promise = Tensor::from_mapped(&slice) * 2.0;
+    assert!(is_mapped());
+
+    drop(mapped);
+    drop(slice);
+    assert!(is_mapped());
+    assert_eq!(
+        values(&promise.materialize()),
+        [4.0, 6.0, 8.0, 10.0, 12.0, 14.0]
+    );
+    assert!(!is_mapped());
+
+    // Other tests open files at the same time, but not a thousand of them.
+    let before = open_fds();
+    for _ in 0..1000 {
+        let mapped = open(&path).unwrap();
+        assert_eq!(mapped.len(), 8);
+    }
+
+    assert!(open_fds() < before + 100);
+    assert!(!is_mapped());
+}
@@ -0,0 +1,73 @@
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{axes, matrix, named_tensor};
+
+#[test]
+fn same_names_can_be_combined() {
+    let a = named_tensor!(matrix![[1, 2], [3, 4]], ["batch", "channel"]).unwrap();
+    let b = named_tensor!(matrix![[10, 20], [30, 40]], ["batch", "channel"]).unwrap();
+
+    let sum = (&a + &b).materialize();
+    assert_eq!(sum.names(), &["batch", "channel"]);
+    assert_eq!(sum.shape(), &[2, 2]);
+    assert_eq!(
+        sum.inner().iter().copied().collect::<Vec<_>>(),
+        [11.0, 22.0, 33.0, 44.0]
+    );
+
+    // Promises keep their names too.
+    let scaled = named_tensor!(a.inner() * 2.0, ["batch", "channel"]).unwrap();
+    let product = (scaled * b).materialize();
+    assert_eq!(
+        product.into_inner().iter().copied().collect::<Vec<_>>(),
+        [20.0, 80.0, 180.0, 320.0]
+    );
+}
+
+#[test]
+fn axis_lookup() {
+    let t = named_tensor!(
+        Tensor::from_scalar(0.0, &[2, 3, 4, 5]),
+        ["batch", "channel", "height", "width"]
+    )
+    .unwrap();
+
+    assert_eq!(t.axis("channel"), Some(1));
+    assert_eq!(t.axis("width"), Some(3));
+    assert_eq!(t.axis("depth"), None);
+    assert_eq!(t.shape()[t.axis("height").unwrap()], 4);
+}
+
+#[test]
+fn rename() {
+    let t = named_tensor!(Tensor::from_scalar(1.0, &[2, 3]), ["batch", "channel"]).unwrap();
+
+    let renamed = t
+        .clone()
+        .rename::<axes!["batch", "features"]>("channel", "features")
+        .unwrap();
+    assert_eq!(renamed.names(), &["batch", "features"]);
+
+    let other = named_tensor!(Tensor::from_scalar(2.0, &[2, 3]), ["batch", "features"]).unwrap();
+    let sum = (&renamed + &other).materialize();
+    assert_eq!(sum.names(), &["batch", "features"]);
+
+    assert!(matches!(
+        t.clone()
+            .rename::<axes!["batch", "features"]>("depth", "features"),
+        Err(OpError::UnknownAxisName(_, _))
+    ));
+
+    // The new names must be the ones of the annotated type.
+    assert!(matches!(
+        t.rename::<axes!["batch", "features"]>("channel", "width"),
+        Err(OpError::AxisNamesMismatch)
+    ));
+}
+
+#[test]
+fn names_must_cover_every_axis() {
+    let result = named_tensor!(Tensor::from_scalar(1.0, &[2, 3, 4]), ["batch", "channel"]);
+
+    assert!(matches!(result, Err(OpError::NotEnoughAxes(2, 3))));
+}
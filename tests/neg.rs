@@ -0,0 +1,62 @@
+mod common;
+
+use simple_tensor::tensor::complex::Complex;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{arange, matrix, s};
+
+use common::values;
+
+#[test]
+fn negates_every_element() {
+    let t = arange![5];
+
+    assert_eq!(values(&(-&t).materialize()), [0.0, -1.0, -2.0, -3.0, -4.0]);
+    assert!(values(&(-&t).materialize())[0].is_sign_negative());
+    assert_eq!(
+        values(&(-t.clone()).materialize()),
+        values(&(&t * -1.0).materialize())
+    );
+}
+
+#[test]
+fn double_negation_is_the_identity() {
+    let t = matrix![[1.5, -2.0], [0.0, f64::INFINITY]];
+
+    assert_eq!(values(&(-(-&t)).materialize()), values(&t));
+    assert_eq!((-(-&t)).materialize().shape(), [2, 2]);
+}
+
+#[test]
+fn negates_promises_and_caches() {
+    let t = arange![4];
+    let cached = (&t + 1.0).cache();
+
+    assert_eq!(
+        values(&(-(&t * 2.0)).materialize()),
+        [0.0, -2.0, -4.0, -6.0]
+    );
+    assert_eq!(values(&(-&cached).materialize()), [-1.0, -2.0, -3.0, -4.0]);
+    assert_eq!(values(&(-cached + &t).materialize()), [-1.0; 4]);
+}
+
+#[test]
+fn negates_views() {
+    let t = Tensor::from_iter((0..12).map(|i| i as f64), &[3, 4]);
+
+    let sliced = t.slice(s![1..3, 1..3]).unwrap();
+    assert_eq!(values(&(-&sliced).materialize()), [-5.0, -6.0, -9.0, -10.0]);
+
+    let transposed = t.transpose_axes(&[1, 0]).unwrap().materialize();
+    let negated = (-&transposed).materialize();
+    assert_eq!(negated.shape(), [4, 3]);
+    assert_eq!(&values(&negated)[..3], [-0.0, -4.0, -8.0]);
+}
+
+#[test]
+fn negates_complex_tensors() {
+    let t = Tensor::from_vec(vec![Complex::new(1.0, -2.0), Complex::new(-3.0, 0.5)], &[2]);
+    let negated = (-&t).materialize();
+
+    let expected = [Complex::new(-1.0, 2.0), Complex::new(3.0, -0.5)];
+    assert_eq!(negated.iter().copied().collect::<Vec<_>>(), expected);
+}
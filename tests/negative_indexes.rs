@@ -0,0 +1,218 @@
+mod common;
+
+use std::num::NonZeroI32;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, SliceKind, SliceRange, StepSliceRange, Tensor};
+use simple_tensor::{s, ss};
+
+use common::{arange, values};
+
+// Expected outcome of a line of tests/fixtures/slice_bounds.txt, see slice_bounds.py.
+#[derive(Debug)]
+enum Outcome {
+    Positions(Vec<usize>),
+    Past(usize),
+    Before(i64),
+    Empty,
+}
+
+struct Case {
+    len: usize,
+    kind: String,
+    start: Option<i32>,
+    end: Option<i32>,
+    step: i32,
+    outcome: Outcome,
+}
+
+impl Case {
+    fn parse(line: &str) -> Self {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let bound = |field: &str| (field != "_").then(|| field.parse().unwrap());
+
+        let outcome = match fields[5] {
+            "=" => Outcome::Positions(fields[6..].iter().map(|p| p.parse().unwrap()).collect()),
+            "past" => Outcome::Past(fields[6].parse().unwrap()),
+            "before" => Outcome::Before(fields[6].parse().unwrap()),
+            "empty" => Outcome::Empty,
+            other => panic!("unknown outcome {}", other),
+        };
+
+        Self {
+            len: fields[0].parse().unwrap(),
+            kind: fields[1].into(),
+            start: bound(fields[2]),
+            end: bound(fields[3]),
+            step: fields[4].parse().unwrap(),
+            outcome,
+        }
+    }
+
+    fn slice_kind(&self) -> SliceKind {
+        let step = NonZeroI32::new(self.step).unwrap();
+        let range = |range: SliceRange| SliceKind::Range(StepSliceRange::new(range, step));
+
+        match self.kind.as_str() {
+            "full" => range((..).into()),
+            "from" => range((self.start.unwrap()..).into()),
+            "to" => range((..self.end.unwrap()).into()),
+            "range" => range((self.start.unwrap()..self.end.unwrap()).into()),
+            "index" => SliceKind::Index(self.start.unwrap()),
+            other => panic!("unknown kind {}", other),
+        }
+    }
+}
+
+fn cases() -> Vec<Case> {
+    let fixture = include_str!("fixtures/slice_bounds.txt");
+    fixture.lines().map(Case::parse).collect()
+}
+
+// Slices `axis` of `t` with the bound of the case and checks the layout against it.
+// `t` is read through its stride on that axis, from its own offset.
+fn check(case: &Case, t: &Tensor<f64>, axis: usize) {
+    let mut kinds: Vec<SliceKind> = (0..axis).map(|_| SliceKind::from(..)).collect();
+    kinds.push(case.slice_kind());
+
+    let name = format!(
+        "{} {:?}..{:?};{} of {:?} on axis {}",
+        case.kind,
+        case.start,
+        case.end,
+        case.step,
+        t.shape(),
+        axis
+    );
+    let result = t.slice(&kinds);
+
+    let (positions, sliced) = match (&case.outcome, result) {
+        (Outcome::Positions(positions), Ok(sliced)) => (positions, sliced),
+        (Outcome::Past(bound), Err(err)) => {
+            let expected = OpError::InvalidSliceShape(case.len, *bound);
+            assert_eq!(
+                format!("{:?}", err.cause()),
+                format!("{:?}", expected),
+                "{}",
+                name
+            );
+            return;
+        }
+        (Outcome::Before(bound), Err(err)) => {
+            let expected = OpError::NegativeBoundOutOfRange(axis, *bound, case.len);
+            assert_eq!(
+                format!("{:?}", err.cause()),
+                format!("{:?}", expected),
+                "{}",
+                name
+            );
+            return;
+        }
+        (Outcome::Empty, Err(err)) => {
+            assert!(matches!(err.cause(), OpError::OutOfBoundSlice), "{}", name);
+            return;
+        }
+        (outcome, result) => panic!("{}: expected {:?}, got {:?}", name, outcome, result.err()),
+    };
+
+    let stride = t.stride()[axis];
+    let offset = t.offset() as i64 + positions[0] as i64 * stride as i64;
+    assert_eq!(sliced.offset() as i64, offset, "{}", name);
+
+    let mut shape = t.shape().to_vec();
+    if case.kind == "index" {
+        shape.remove(axis);
+        if shape.is_empty() {
+            shape.push(1);
+        }
+    } else {
+        shape[axis] = positions.len();
+        assert_eq!(sliced.stride()[axis], stride * case.step, "{}", name);
+    }
+    assert_eq!(sliced.shape(), shape, "{}", name);
+
+    // The elements of a single axis, read from the buffer of `t`.
+    if t.shape().len() == 1 {
+        let first = values(t)[0];
+        let expected: Vec<f64> = positions.iter().map(|&p| first + p as f64).collect();
+        assert_eq!(values(&sliced.materialize()), expected, "{}", name);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn bounds_match_the_fixture_table() {
+    let cases = cases();
+    assert!(cases.len() > 1000);
+
+    for case in &cases {
+        let n = case.len;
+
+        check(case, &arange(&[n]), 0);
+        check(case, &arange(&[n, 4]), 0);
+        check(case, &arange(&[3, n]), 1);
+
+        // A view starting inside of its buffer, whose offset the slice adds to.
+        let inner = arange(&[2, n + 2]).slice(s![1, 1..n as i32 + 1]).unwrap();
+        check(case, &inner.materialize(), 0);
+    }
+}
+
+#[test]
+fn extreme_bounds_fail_instead_of_overflowing() {
+    let t = arange(&[3, 4]);
+
+    let err = t.slice(s![i32::MIN..]).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::NegativeBoundOutOfRange(0, -2147483648, 3)
+    ));
+
+    let err = t.slice(s![.., ..i32::MIN]).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::NegativeBoundOutOfRange(1, -2147483648, 4)
+    ));
+
+    let err = t.slice(s![.., i32::MIN]).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::NegativeBoundOutOfRange(1, -2147483648, 4)
+    ));
+
+    let err = t.slice(s![i32::MAX]).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::InvalidSliceShape(3, 2147483648)
+    ));
+
+    // Step slices resolve their bounds the same way.
+    let err = t.step_slice(ss![.., i32::MIN..0;-1]).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::NegativeBoundOutOfRange(1, -2147483648, 4)
+    ));
+}
+
+#[test]
+fn errors_name_the_axis_the_bound_and_the_length() {
+    let t = arange(&[3, 4]);
+
+    let err = t.slice(s![.., -5..]).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "the bound -5 of axis 1 is before the start of an axis of length 4. negative bounds go from -4 to -1"
+    );
+
+    // New axes do not count as axes of the tensor.
+    let err = t.slice(s![NewAxis, .., -7]).err().unwrap();
+    assert!(matches!(
+        err.cause(),
+        OpError::NegativeBoundOutOfRange(1, -7, 4)
+    ));
+
+    // The whole axis counted from the end is still within it.
+    let whole = t.slice(s![-3.., -4..]).unwrap().materialize();
+    assert_eq!(values(&whole), values(&t));
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::nn::{
+    InterpMode, Reduction, interpolate_1d, interpolate_2d, mae_loss, mse_loss,
+};
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn mse_loss_squares_the_errors() {
+    let predictions = matrix![[1.0, 2.0], [3.0, 4.0]];
+    let targets = matrix![[1.0, 0.0], [4.0, 1.5]];
+
+    let none = mse_loss(&predictions, &targets, Reduction::None)
+        .unwrap()
+        .materialize();
+    assert_eq!(none.shape(), &[2, 2]);
+    assert_eq!(values(&none), [0.0, 4.0, 1.0, 6.25]);
+
+    let sum = mse_loss(&predictions, &targets, Reduction::Sum)
+        .unwrap()
+        .materialize();
+    assert_eq!(sum.shape(), &[1]);
+    assert_eq!(values(&sum), [11.25]);
+
+    let mean = mse_loss(&predictions, &targets, Reduction::Mean)
+        .unwrap()
+        .materialize();
+    assert_eq!(mean.shape(), &[1]);
+    assert_eq!(values(&mean), [11.25 / 4.0]);
+}
+
+#[test]
+fn mae_loss_takes_the_absolute_errors() {
+    let predictions = vector![1.0, -2.0, 3.0, 0.5];
+    let targets = vector![1.5, 2.0, -1.0, 0.5];
+
+    let none = mae_loss(&predictions, &targets, Reduction::None)
+        .unwrap()
+        .materialize();
+    assert_eq!(values(&none), [0.5, 4.0, 4.0, 0.0]);
+
+    let sum = mae_loss(&predictions, &targets, Reduction::Sum)
+        .unwrap()
+        .materialize();
+    assert_eq!(values(&sum), [8.5]);
+
+    let mean = mae_loss(&predictions, &targets, Reduction::Mean)
+        .unwrap()
+        .materialize();
+    assert_eq!(values(&mean), [8.5 / 4.0]);
+}
+
+#[test]
+fn losses_read_strided_operands() {
+    let base = Tensor::from_iter((0..6).map(|i| i as f64), &[2, 3]);
+    let predictions = base.as_strided(&[3, 2], &[1, 3], 0).unwrap();
+    let targets = Tensor::from_scalar(1.0, &[3, 2]);
+
+    let loss = mse_loss(&predictions, &targets, Reduction::None)
+        .unwrap()
+        .materialize();
+
+    // [[0, 3], [1, 4], [2, 5]] minus 1, squared.
+    assert_eq!(values(&loss), [1.0, 4.0, 0.0, 9.0, 1.0, 16.0]);
+}
+
+#[test]
+fn losses_reject_operands_of_different_shapes() {
+    let predictions = matrix![[1.0, 2.0], [3.0, 4.0]];
+    let targets = vector![1.0, 2.0, 3.0, 4.0];
+
+    for reduction in [Reduction::None, Reduction::Sum, Reduction::Mean] {
+        let err = mse_loss(&predictions, &targets, reduction).err().unwrap();
+        assert!(
+            matches!(err.cause(), OpError::NotSameShape(a, b) if **a == [2, 2] && **b == [4]),
+            "{err:?}"
+        );
+
+        let err = mae_loss(&predictions, &targets, reduction).err().unwrap();
+        assert!(
+            matches!(err.cause(), OpError::NotSameShape(_, _)),
+            "{err:?}"
+        );
+    }
+
+    // Broadcastable shapes are not accepted either.
+    let row = matrix![[1.0, 2.0]];
+    assert!(mse_loss(&predictions, &row, Reduction::Mean).is_err());
+}
+
+#[test]
+fn interpolate_1d_resizes_the_last_axis() {
+    let x = Tensor::from_iter([1.0, 2.0, 3.0, 4.0, 0.0, 1.0, 0.0, 1.0], &[2, 1, 4]);
+
+    let nearest = interpolate_1d(&x, 2, InterpMode::Nearest).unwrap();
+    assert_eq!(nearest.shape(), &[2, 1, 2]);
+    assert_eq!(values(&nearest), [1.0, 3.0, 0.0, 0.0]);
+
+    let linear = interpolate_1d(&x, 2, InterpMode::Linear).unwrap();
+    assert_eq!(values(&linear), [1.5, 3.5, 0.5, 0.5]);
+
+    // Upsampling clamps the positions before the first and after the last element.
+    let x = Tensor::from_iter([0.0, 1.0], &[1, 1, 2]);
+    let linear = interpolate_1d(&x, 4, InterpMode::Linear).unwrap();
+    assert_eq!(values(&linear), [0.0, 0.25, 0.75, 1.0]);
+}
+
+#[test]
+fn interpolate_2d_resizes_the_last_two_axes() {
+    let x = Tensor::from_iter([0.0, 1.0, 2.0, 3.0], &[1, 1, 2, 2]);
+
+    let nearest = interpolate_2d(&x, (4, 4), InterpMode::Nearest).unwrap();
+    assert_eq!(nearest.shape(), &[1, 1, 4, 4]);
+    let expected: Vec<f64> = (0..16)
+        .map(|i| ((i / 4) / 2 * 2 + (i % 4) / 2) as f64)
+        .collect();
+    assert_eq!(values(&nearest), expected);
+
+    let linear = interpolate_2d(&x, (1, 1), InterpMode::Linear).unwrap();
+    assert_eq!(values(&linear), [1.5]);
+}
+
+#[test]
+fn interpolate_1d_only_blends_the_elements_it_reads() {
+    let x = Tensor::from_iter([1.0, f64::INFINITY, 3.0, 4.0], &[1, 1, 4]);
+
+    // Nothing is blended at the same size, and nearest only replicates.
+    let nearest = interpolate_1d(&x, 4, InterpMode::Nearest).unwrap();
+    assert_eq!(values(&nearest), [1.0, f64::INFINITY, 3.0, 4.0]);
+    let linear = interpolate_1d(&x, 4, InterpMode::Linear).unwrap();
+    assert_eq!(values(&linear), [1.0, f64::INFINITY, 3.0, 4.0]);
+
+    let nearest = interpolate_1d(&x, 8, InterpMode::Nearest).unwrap();
+    assert_eq!(
+        values(&nearest),
+        [1.0, 1.0, f64::INFINITY, f64::INFINITY, 3.0, 3.0, 4.0, 4.0]
+    );
+
+    // The clamped ends read a single element, and only the blends with it are infinite.
+    let linear = interpolate_1d(&x, 8, InterpMode::Linear).unwrap();
+    let got = values(&linear);
+    assert_eq!(got[0], 1.0);
+    assert_eq!(got[1..5], [f64::INFINITY; 4]);
+    assert_eq!(got[5..], [3.25, 3.75, 4.0]);
+
+    let x = Tensor::from_iter([f64::NAN, 2.0, 4.0], &[1, 1, 3]);
+    let linear = interpolate_1d(&x, 3, InterpMode::Linear).unwrap();
+    assert!(values(&linear)[0].is_nan());
+    assert_eq!(values(&linear)[1..], [2.0, 4.0]);
+}
+
+#[test]
+fn interpolate_2d_only_blends_the_elements_it_reads() {
+    let x = Tensor::from_iter([1.0, f64::INFINITY, 3.0, 4.0], &[1, 1, 2, 2]);
+
+    let nearest = interpolate_2d(&x, (4, 4), InterpMode::Nearest).unwrap();
+    let expected: Vec<f64> = (0..16)
+        .map(|i| [1.0, f64::INFINITY, 3.0, 4.0][(i / 4) / 2 * 2 + (i % 4) / 2])
+        .collect();
+    assert_eq!(values(&nearest), expected);
+
+    let linear = interpolate_2d(&x, (2, 2), InterpMode::Linear).unwrap();
+    assert_eq!(values(&linear), [1.0, f64::INFINITY, 3.0, 4.0]);
+
+    // The corners read a single element, and no NaN comes out of the infinite one.
+    let got = values(&interpolate_2d(&x, (4, 4), InterpMode::Linear).unwrap());
+    assert_eq!([got[0], got[12], got[15]], [1.0, 3.0, 4.0]);
+    assert_eq!(got[1], f64::INFINITY);
+    assert!(got.iter().all(|el| !el.is_nan()), "{got:?}");
+}
+
+#[test]
+fn interpolate_rejects_other_ranks() {
+    let x = Tensor::from_scalar(1.0, &[2, 2]);
+    let err = interpolate_1d(&x, 4, InterpMode::Nearest).unwrap_err();
+    assert!(matches!(err, OpError::NotEnoughAxes(3, 2)), "{err:?}");
+    let err = interpolate_2d(&x, (4, 4), InterpMode::Nearest).unwrap_err();
+    assert!(matches!(err, OpError::NotEnoughAxes(4, 2)), "{err:?}");
+
+    let x = Tensor::from_scalar(1.0, &[1, 1, 2, 2]);
+    let err = interpolate_1d(&x, 4, InterpMode::Linear).unwrap_err();
+    assert!(matches!(err, OpError::RankMismatch(3, 4)), "{err:?}");
+
+    let x = Tensor::from_scalar(1.0, &[1, 1, 1, 2, 2]);
+    let err = interpolate_2d(&x, (4, 4), InterpMode::Linear).unwrap_err();
+    assert!(matches!(err, OpError::RankMismatch(4, 5)), "{err:?}");
+
+    let x = Tensor::from_scalar(1.0, &[1, 1, 0]);
+    let err = interpolate_1d(&x, 4, InterpMode::Linear).unwrap_err();
+    assert!(matches!(err, OpError::EmptyAxis(2)), "{err:?}");
+}
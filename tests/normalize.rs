@@ -0,0 +1,103 @@
+mod common;
+
+use simple_tensor::matrix;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+
+use common::values;
+
+// Normalizes every lane along `axis` of a 2d tensor, one element at a time.
+fn reference(t: &Tensor<f64>, axis: usize, eps: f64) -> Vec<f64> {
+    let shape = t.shape().to_vec();
+    let v = values(t);
+    let mut out = vec![0.0; v.len()];
+
+    for lane in 0..shape[1 - axis] {
+        let index = |k: usize| {
+            if axis == 0 {
+                k * shape[1] + lane
+            } else {
+                lane * shape[1] + k
+            }
+        };
+
+        let n = shape[axis] as f64;
+        let mean = (0..shape[axis]).map(|k| v[index(k)]).sum::<f64>() / n;
+        let var = (0..shape[axis])
+            .map(|k| (v[index(k)] - mean).powi(2))
+            .sum::<f64>()
+            / n;
+
+        for k in 0..shape[axis] {
+            out[index(k)] = (v[index(k)] - mean) / (var + eps).sqrt();
+        }
+    }
+
+    out
+}
+
+fn assert_all_close(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (&g, &e) in got.iter().zip(expected.iter()) {
+        assert!(
+            (g - e).abs() <= 1e-12 * e.abs().max(1.0),
+            "got {:?} expected {:?}",
+            got,
+            expected
+        );
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn normalize_centers_and_scales_every_lane() {
+    let t = matrix![[1.0, 2.0, 3.0], [4.0, 4.0, 4.0]];
+
+    let rows = t.normalize(1, 1e-5).unwrap().materialize();
+    assert_eq!(rows.shape(), &[2, 3]);
+    assert_all_close(&values(&rows), &reference(&t, 1, 1e-5));
+
+    // A constant lane has no variance, eps keeps it at zero instead of NaN.
+    assert_eq!(values(&rows)[3..], [0.0; 3]);
+
+    let columns = t.normalize(0, 1e-5).unwrap().materialize();
+    assert_all_close(&values(&columns), &reference(&t, 0, 1e-5));
+}
+
+#[test]
+fn normalize_reads_strided_inputs() {
+    let base = Tensor::from_iter((0..12).map(|i| (i * i) as f64), &[3, 4]);
+    let strided = base.as_strided(&[4, 3], &[1, 4], 0).unwrap();
+    let dense = Tensor::from_iter(values(&strided), &[4, 3]);
+
+    for axis in [0, 1] {
+        let got = strided.normalize(axis, 1e-3).unwrap().materialize();
+        assert_all_close(&values(&got), &reference(&dense, axis, 1e-3));
+    }
+}
+
+#[test]
+fn normalize_of_lanes_of_one_element_is_zero() {
+    let t = matrix![[1.0], [-3.0]];
+
+    let got = t.normalize(1, 1e-5).unwrap().materialize();
+    assert_eq!(values(&got), [0.0, 0.0]);
+}
+
+#[test]
+fn normalize_rejects_invalid_epsilons() {
+    let t = matrix![[1.0, 2.0]];
+
+    for eps in [0.0, -0.0, -1e-5, f64::NAN, f64::NEG_INFINITY] {
+        let err = t.normalize(1, eps).err().unwrap();
+        assert!(
+            matches!(err.cause(), OpError::NonPositiveEpsilon),
+            "{eps}: {err:?}"
+        );
+    }
+
+    let err = t.normalize(2, 1e-5).err().unwrap();
+    assert!(matches!(err.cause(), OpError::OutOfBoundAxes), "{err:?}");
+}
@@ -0,0 +1,56 @@
+use simple_tensor::tensor::Tensor;
+use simple_tensor::{matrix, srange, vector};
+
+#[test]
+fn abs_sum_and_max_abs() {
+    let t = vector![-1.0, 2.0, -3.0];
+
+    assert_eq!(t.abs_sum(), 6.0);
+    assert_eq!(t.max_abs(), 3.0);
+    assert_eq!(t.norm_inf(), 3.0);
+
+    // The first of several equal magnitudes, with either sign.
+    assert_eq!(vector![4.0, -4.0, 1.0].max_abs(), 4.0);
+    assert_eq!(vector![-0.0, -0.0].max_abs(), 0.0);
+}
+
+#[test]
+fn empty_tensors() {
+    let t = Tensor::from_vec(vec![], &[0]);
+
+    assert_eq!(t.abs_sum(), 0.0);
+    assert_eq!(t.max_abs(), 0.0);
+}
+
+#[test]
+fn strided_tensors() {
+    // [[-0, 3, -6], [-1, 4, -7], [-2, 5, -8]] read through the transposed layout.
+    let t = (&srange!(9, &[3, 3]) * matrix![[-1, -1, -1], [1, 1, 1], [-1, -1, -1]])
+        .materialize()
+        .transpose()
+        .materialize();
+    let view = t.flip_view(&[0]).unwrap().diagonal().unwrap();
+
+    assert_eq!(t.abs_sum(), 36.0);
+    assert_eq!(t.max_abs(), 8.0);
+
+    // [-2, 4, -6]
+    assert_eq!(view.abs_sum(), 12.0);
+    assert_eq!(view.max_abs(), 6.0);
+}
+
+#[test]
+fn matches_a_plain_loop() {
+    let t = Tensor::from_iter(
+        (0..10_000).map(|i| (i as f64 * 0.37).sin() * 5.0),
+        &[100, 100],
+    );
+    let abs: Vec<f64> = t.iter().map(|el| el.abs()).collect();
+
+    let sum: f64 = abs.iter().sum();
+    assert!((t.abs_sum() - sum).abs() <= 1e-9 * sum);
+
+    let max = abs.iter().copied().fold(0.0, f64::max);
+    assert_eq!(t.max_abs(), max);
+    assert_eq!(t.transpose().materialize().max_abs(), max);
+}
@@ -0,0 +1,318 @@
+#![cfg(feature = "mmap")]
+
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout as AllocLayout, System};
+use std::cell::Cell;
+use std::path::PathBuf;
+
+use simple_tensor::tensor::complex::Complex;
+use simple_tensor::tensor::errors::IoError;
+use simple_tensor::tensor::{Dimension, NpzCompression, NpzWriter, Tensor, load_npz, save_npz};
+
+use common::values;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATED.with(|count| count.set(count.get() + layout.size()));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bytes_allocated_in<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+// A path in the temporary directory, removed when dropped.
+struct TempPath(PathBuf);
+
+impl TempPath {
+    fn new(name: &str) -> Self {
+        let name = format!("simple_tensor_{}_{}.npz", std::process::id(), name);
+
+        Self(std::env::temp_dir().join(name))
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn arange(shape: &[usize]) -> Tensor<f64> {
+    let len = shape.iter().product();
+
+    Tensor::from_iter((0..len).map(|i| i as f64 * 0.5), shape)
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn tensors_of_several_types_round_trip() {
+    let path = TempPath::new("mixed");
+    let weights = arange(&[2, 3]);
+    let ids = Tensor::from_iter([10i64, 20, 30, -40], &[4]);
+    let mask = Tensor::from_iter([1u8, 0, 1], &[3]);
+    let freqs = Tensor::from_iter([Complex::new(1.0, 2.0), Complex::new(3.0, -4.0)], &[2]);
+    let small = Tensor::from_iter((0..6).map(|i| i as f32), &[3, 2]);
+    // Written in logical order, like any other tensor.
+    let flipped = small.flip_view(&[0, 1]).unwrap();
+
+    let mut writer = NpzWriter::create(&path.0).unwrap();
+    writer.add("weights", &weights).unwrap();
+    writer.add("ids", &ids).unwrap();
+    writer.add("mask", &mask).unwrap();
+    writer.add("freqs", &freqs).unwrap();
+    writer.add("flipped", &flipped).unwrap();
+    writer.add("empty", &arange(&[3, 0])).unwrap();
+    writer.finish().unwrap();
+
+    let (tensors, skipped) = load_npz(&path.0).unwrap();
+    assert!(skipped.is_empty(), "{:?}", skipped);
+    assert_eq!(tensors.len(), 6);
+
+    let loaded = tensors["weights"].as_f64().unwrap();
+    assert_eq!(loaded.shape(), &[2, 3]);
+    assert_eq!(values(loaded), values(&weights));
+
+    assert_eq!(tensors["ids"].dtype(), "i8");
+    assert_eq!(values(tensors["ids"].as_i64().unwrap()), values(&ids));
+    assert_eq!(values(tensors["mask"].as_u8().unwrap()), [1, 0, 1]);
+    assert_eq!(values(tensors["freqs"].as_c64().unwrap()), values(&freqs));
+
+    let loaded = tensors["flipped"].as_f32().unwrap();
+    assert_eq!(loaded.shape(), &[3, 2]);
+    assert_eq!(values(loaded), [5.0, 4.0, 3.0, 2.0, 1.0, 0.0]);
+
+    assert_eq!(tensors["empty"].shape(), &[3, 0]);
+    assert!(tensors["empty"].as_i32().is_none());
+
+    // A single type, with save_npz().
+    let path = TempPath::new("single");
+    save_npz(&path.0, &[("a", &weights), ("b", &arange(&[5]))]).unwrap();
+    let (tensors, _) = load_npz(&path.0).unwrap();
+    assert_eq!(values(tensors["a"].as_f64().unwrap()), values(&weights));
+    assert_eq!(
+        values(tensors["b"].as_f64().unwrap()),
+        values(&arange(&[5]))
+    );
+}
+
+#[test]
+fn names_round_trip_whatever_they_end_with() {
+    let path = TempPath::new("names");
+    let names = ["x", "x.npy", "x.npy.npy", "layer/0/bias", "with spaces", ""];
+
+    let tensors: Vec<Tensor<f64>> = (1..=names.len()).map(|len| arange(&[len])).collect();
+    let entries: Vec<(&str, &Tensor<f64>)> = names.iter().copied().zip(&tensors).collect();
+    save_npz(&path.0, &entries).unwrap();
+
+    let (loaded, _) = load_npz(&path.0).unwrap();
+    assert_eq!(loaded.len(), names.len());
+
+    for (name, tensor) in entries {
+        assert_eq!(
+            values(loaded[name].as_f64().unwrap()),
+            values(tensor),
+            "{}",
+            name
+        );
+    }
+}
+
+// Written the way numpy.savez() writes, with zip64 local headers and NPY 1.0 headers padded
+// to 64 bytes, for the arrays of
+// np.savez(path, weights=np.arange(6.0).reshape(2, 3) / 2, ids=np.array([10, 20, 30, -40]),
+//          mask=np.array([1, 0, 1], np.uint8), freqs=np.array([1 + 2j, 3 - 4j]),
+//          fortran=np.asfortranarray(np.arange(6, dtype=np.float32).reshape(2, 3)),
+//          scalar=np.float64(3.5), labels=np.array(["abc", "de"]))
+// and a README.txt member added afterwards.
+#[test]
+fn archives_written_by_numpy_are_read() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/numpy.npz");
+    let (tensors, skipped) = load_npz(path).unwrap();
+
+    let weights = tensors["weights"].as_f64().unwrap();
+    assert_eq!(weights.shape(), &[2, 3]);
+    assert_eq!(values(weights), values(&arange(&[2, 3])));
+    assert_eq!(values(tensors["ids"].as_i64().unwrap()), [10, 20, 30, -40]);
+    assert_eq!(values(tensors["mask"].as_u8().unwrap()), [1, 0, 1]);
+    assert_eq!(
+        values(tensors["freqs"].as_c64().unwrap()),
+        [Complex::new(1.0, 2.0), Complex::new(3.0, -4.0)]
+    );
+
+    // Read as a transposed view of the columns.
+    let fortran = tensors["fortran"].as_f32().unwrap();
+    assert_eq!(fortran.shape(), &[2, 3]);
+    assert_eq!(fortran.stride(), &[1, 2]);
+    assert_eq!(values(fortran), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    // The strings, the scalar and the text file.
+    assert_eq!(tensors.len(), 5);
+    assert_eq!(skipped.len(), 3);
+    assert!(matches!(
+        &skipped[0],
+        skipped if skipped.name == "scalar.npy" && matches!(skipped.reason, IoError::InvalidHeader(_))
+    ));
+    assert!(matches!(
+        &skipped[1],
+        skipped if skipped.name == "labels.npy"
+            && matches!(&skipped.reason, IoError::UnsupportedDtype(dtype) if &**dtype == "<U3")
+    ));
+    assert!(matches!(
+        &skipped[2],
+        skipped if skipped.name == "README.txt" && matches!(skipped.reason, IoError::NotNpy)
+    ));
+}
+
+#[test]
+fn duplicate_names_are_rejected() {
+    let path = TempPath::new("duplicates");
+    let (a, b) = (arange(&[2]), arange(&[3]));
+
+    let err = save_npz(&path.0, &[("a", &a), ("b", &b), ("a", &b)]).unwrap_err();
+    assert!(matches!(err, IoError::DuplicateMember(name) if &*name == "a"));
+    assert!(!path.0.exists());
+
+    let mut writer = NpzWriter::create(&path.0).unwrap();
+    writer.add("a", &a).unwrap();
+    let err = writer
+        .add("a", &Tensor::from_iter([1i32], &[1]))
+        .unwrap_err();
+    assert!(matches!(err, IoError::DuplicateMember(name) if &*name == "a"));
+    writer.finish().unwrap();
+
+    let (tensors, _) = load_npz(&path.0).unwrap();
+    assert_eq!(values(tensors["a"].as_f64().unwrap()), values(&a));
+}
+
+#[test]
+fn compressed_and_stored_members_read_the_same() {
+    let (stored, deflated) = (TempPath::new("stored"), TempPath::new("deflated"));
+    let zeros = Tensor::from_scalar(0.0, &[100, 100]);
+    let ids = Tensor::from_iter(0..1000i32, &[1000]);
+
+    for (path, compression) in [
+        (&stored, NpzCompression::Stored),
+        (&deflated, NpzCompression::Deflated),
+    ] {
+        let mut writer = NpzWriter::create(&path.0)
+            .unwrap()
+            .with_compression(compression);
+        writer.add("zeros", &zeros).unwrap();
+        writer.add("ids", &ids).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let stored_len = std::fs::metadata(&stored.0).unwrap().len();
+    let deflated_len = std::fs::metadata(&deflated.0).unwrap().len();
+    assert!(stored_len > 80_000 + 4000, "{}", stored_len);
+    assert!(deflated_len < 10_000, "{}", deflated_len);
+
+    for path in [&stored, &deflated] {
+        let (tensors, _) = load_npz(&path.0).unwrap();
+        assert_eq!(values(tensors["zeros"].as_f64().unwrap()), values(&zeros));
+        assert_eq!(values(tensors["ids"].as_i32().unwrap()), values(&ids));
+    }
+}
+
+#[test]
+fn large_members_are_streamed() {
+    let path = TempPath::new("large");
+    let len = 1 << 20;
+    let large = Tensor::from_iter((0..len).map(|i| i as f64), &[len]);
+    let bytes = len * size_of::<f64>();
+
+    for compression in [NpzCompression::Stored, NpzCompression::Deflated] {
+        let (_, written) = bytes_allocated_in(|| {
+            let mut writer = NpzWriter::create(&path.0)
+                .unwrap()
+                .with_compression(compression);
+            writer.add("large", &large).unwrap();
+            writer.finish().unwrap();
+        });
+        assert!(written < bytes / 8, "{:?}: {}", compression, written);
+
+        // The tensor and a few buffers, not a copy of the member.
+        let (tensors, read) = bytes_allocated_in(|| load_npz(&path.0).unwrap().0);
+        assert!(read < bytes + bytes / 8, "{:?}: {}", compression, read);
+        assert_eq!(values(tensors["large"].as_f64().unwrap()), values(&large));
+    }
+}
+
+#[test]
+fn corrupted_members_are_named() {
+    let path = TempPath::new("corrupted");
+    save_npz(
+        &path.0,
+        &[("first", &arange(&[4])), ("second", &arange(&[4]))],
+    )
+    .unwrap();
+    let archive = std::fs::read(&path.0).unwrap();
+
+    // The NPY headers, at the start of each member.
+    let headers: Vec<usize> = (archive.windows(6).enumerate())
+        .filter(|(_, window)| window == b"\x93NUMPY")
+        .map(|(at, _)| at)
+        .collect();
+    assert_eq!(headers.len(), 2);
+
+    let load = |bytes: &[u8]| {
+        std::fs::write(&path.0, bytes).unwrap();
+        load_npz(&path.0).unwrap_err()
+    };
+    let in_member = |bytes: &[u8]| match load(bytes) {
+        IoError::InMember(name, reason) => (name.to_string(), *reason),
+        err => panic!("{}", err),
+    };
+
+    // An element of the second member, caught by its checksum.
+    let mut bytes = archive.clone();
+    bytes[headers[1] + 128 + 8] ^= 0xff;
+    let err = load(&bytes);
+    assert!(format!("{}", err).starts_with("in the member second.npy: "));
+    assert!(
+        matches!(&err, IoError::InMember(_, reason) if matches!(**reason, IoError::Io(_))),
+        "{:?}",
+        err
+    );
+
+    // The magic string of the first one.
+    let mut bytes = archive.clone();
+    bytes[headers[0] + 1] = b'X';
+    let (name, reason) = in_member(&bytes);
+    assert_eq!(name, "first.npy");
+    assert!(matches!(reason, IoError::NotNpy));
+
+    // A shape larger than the member.
+    let mut bytes = archive.clone();
+    let shape = (bytes.windows(4).position(|window| window == b"(4,)")).unwrap();
+    bytes[shape + 1] = b'9';
+    let (name, reason) = in_member(&bytes);
+    assert_eq!(name, "first.npy");
+    assert!(matches!(
+        reason,
+        IoError::Truncated(expected, found) if expected == 128 + 72 && found == 128 + 32
+    ));
+
+    let err = load(b"PK\x03\x04 not really a zip file");
+    assert!(matches!(err, IoError::InvalidArchive(_)), "{}", err);
+}
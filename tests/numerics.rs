@@ -0,0 +1,157 @@
+mod common;
+
+use std::panic::{self, AssertUnwindSafe};
+
+use simple_tensor::tensor::{
+    FlushDenormals, NumericsMode, NumericsScope, Tensor, denormals_are_flushed, numerics_mode,
+    set_flush_denormals, set_numerics_mode,
+};
+
+use common::{bits, lock_globals, values};
+
+fn random_tensor(shape: &[usize], mut seed: u64) -> Tensor<f64> {
+    let len = shape.iter().product();
+    let data = (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 20.0 - 10.0
+        })
+        .collect();
+
+    Tensor::from_vec(data, shape)
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn scopes_restore_the_mode_on_panic() {
+    let _globals = lock_globals();
+    assert_eq!(numerics_mode(), NumericsMode::HighAccuracy);
+
+    let outer = NumericsScope::enter(NumericsMode::LowAccuracy);
+
+    let result = panic::catch_unwind(|| {
+        let _inner = NumericsScope::enter(NumericsMode::EnhancedPerformance);
+        assert_eq!(numerics_mode(), NumericsMode::EnhancedPerformance);
+        panic!("kernel failed");
+    });
+
+    assert!(result.is_err());
+    assert_eq!(numerics_mode(), NumericsMode::LowAccuracy);
+
+    drop(outer);
+    assert_eq!(numerics_mode(), NumericsMode::HighAccuracy);
+}
+
+#[test]
+fn flush_guard_restores_the_register_on_panic() {
+    let _globals = lock_globals();
+    assert!(!denormals_are_flushed());
+
+    let result = panic::catch_unwind(|| {
+        let _flush = FlushDenormals::new();
+        assert!(denormals_are_flushed());
+        panic!("kernel failed");
+    });
+
+    assert!(result.is_err());
+    assert!(!denormals_are_flushed());
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[test]
+fn denormals_are_flushed_inside_the_kernels_only() {
+    let _globals = lock_globals();
+
+    let x = Tensor::from_vec(vec![1e-310, 1.0, 1e-300, -5e-324], &[4]);
+    let y = Tensor::from_vec(vec![1.0, 1e-310, 1e-10, 1.0], &[4]);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        set_flush_denormals(true);
+        let flushed = (&x * &y).materialize();
+        assert!(!denormals_are_flushed());
+        flushed
+    }));
+    set_flush_denormals(false);
+
+    assert_eq!(values(&result.unwrap()), [0.0, 0.0, 0.0, -0.0]);
+
+    // Back to IEEE results once disabled.
+    let kept = (&x * &y).materialize();
+    assert_eq!(values(&kept), [1e-310, 1e-310, 1e-300 * 1e-10, -5e-324]);
+}
+
+#[test]
+fn the_pure_kernels_ignore_the_mode() {
+    let _globals = lock_globals();
+
+    let a = random_tensor(&[1000], 1);
+    let b = random_tensor(&[1000], 2);
+    let compute = || ((&a / &b) * &a + &b).materialize();
+
+    let expected = bits(&compute());
+
+    for mode in [
+        NumericsMode::LowAccuracy,
+        NumericsMode::EnhancedPerformance,
+        NumericsMode::HighAccuracy,
+    ] {
+        set_numerics_mode(mode);
+        let global = bits(&compute());
+        set_numerics_mode(NumericsMode::HighAccuracy);
+
+        let scoped = bits(&((&a / &b) * &a + &b).with_numerics(mode).materialize());
+
+        #[cfg(not(feature = "mkl"))]
+        {
+            assert_eq!(global, expected);
+            assert_eq!(scoped, expected);
+        }
+        #[cfg(feature = "mkl")]
+        assert_eq!(global, scoped);
+    }
+
+    // Same bits from run to run once back in the default mode.
+    assert_eq!(bits(&compute()), expected);
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn the_promise_mode_overrides_the_global_one() {
+    use simple_tensor::tensor::flush_profile;
+
+    let _globals = lock_globals();
+
+    let a = random_tensor(&[100], 1);
+    let b = random_tensor(&[100], 2);
+
+    set_numerics_mode(NumericsMode::LowAccuracy);
+    flush_profile();
+
+    (&(&a + &b) * &a).materialize();
+    let global = flush_profile();
+
+    (&(&a + &b) * &a)
+        .with_numerics(NumericsMode::EnhancedPerformance)
+        .materialize();
+    let own = flush_profile();
+
+    set_numerics_mode(NumericsMode::HighAccuracy);
+
+    assert_eq!(global.len(), 2);
+    assert!(
+        global
+            .iter()
+            .all(|record| record.numerics() == NumericsMode::LowAccuracy)
+    );
+
+    // Every node of the graph is computed in the mode of the promise materialized.
+    assert_eq!(own.len(), 2);
+    assert!(
+        own.iter()
+            .all(|record| record.numerics() == NumericsMode::EnhancedPerformance)
+    );
+    assert_eq!(numerics_mode(), NumericsMode::HighAccuracy);
+}
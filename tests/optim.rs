@@ -0,0 +1,159 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::optim::{Adam, Sgd};
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+fn assert_all_close(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (&g, &e) in got.iter().zip(expected.iter()) {
+        assert!(
+            (g - e).abs() <= 1e-12 * e.abs().max(1.0),
+            "got {:?} expected {:?}",
+            got,
+            expected
+        );
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn sgd_steps_against_the_gradient() {
+    let mut p = vector![1.0, -2.0];
+    let g = vector![2.0, 0.5];
+
+    let mut sgd = Sgd::new(0.1, 0.0, 0.0);
+    sgd.step(&mut [&mut p], &[&g]).unwrap();
+    assert_all_close(&values(&p), &[0.8, -2.05]);
+
+    // The weight decay adds weight_decay * p to the gradient.
+    let mut p = vector![1.0, -2.0];
+    let mut sgd = Sgd::new(0.1, 0.0, 0.5);
+    sgd.step(&mut [&mut p], &[&g]).unwrap();
+    assert_all_close(&values(&p), &[0.75, -1.95]);
+}
+
+#[test]
+fn sgd_momentum_accumulates_the_velocity() {
+    let mut p = vector![1.0];
+    let g = vector![2.0];
+
+    let mut sgd = Sgd::new(0.1, 0.9, 0.0);
+    sgd.step(&mut [&mut p], &[&g]).unwrap();
+    assert_all_close(&values(&p), &[0.8]);
+
+    // v = 0.9 * 2 + 2 = 3.8
+    sgd.step(&mut [&mut p], &[&g]).unwrap();
+    assert_all_close(&values(&p), &[0.8 - 0.38]);
+}
+
+#[test]
+fn adam_first_step_moves_by_the_learning_rate() {
+    // With bias correction, the first update is lr * g / (|g| + eps).
+    let mut w = matrix![[1.0, -1.0], [0.0, 3.0]];
+    let mut b = vector![0.5];
+    let gw = matrix![[2.0, -4.0], [1.0, 0.5]];
+    let gb = vector![-1.0];
+
+    let mut adam = Adam::new(0.1);
+    adam.step(&mut [&mut w, &mut b], &[&gw, &gb]).unwrap();
+
+    assert_eq!(adam.steps(), 1);
+    assert_eq!(w.shape(), &[2, 2]);
+    for (got, expected) in values(&w).iter().zip([0.9, -0.9, -0.1, 2.9]) {
+        assert!((got - expected).abs() < 1e-7, "{got} {expected}");
+    }
+    assert!((values(&b)[0] - 0.6).abs() < 1e-7);
+}
+
+#[test]
+fn adam_matches_a_scalar_reference() {
+    let (lr, beta1, beta2, eps, decay) = (0.01, 0.8, 0.99, 1e-6, 0.1);
+
+    let mut p = vector![1.5, -0.5];
+    let mut reference = [1.5, -0.5];
+    let (mut m, mut v) = ([0.0; 2], [0.0; 2]);
+
+    let mut adam = Adam::with_params(lr, beta1, beta2, eps, decay);
+
+    for step in 1..=5 {
+        let grads = [reference[0] * 2.0, reference[1] - 1.0];
+        let g = Tensor::from_iter(grads.iter().copied(), &[2]);
+        adam.step(&mut [&mut p], &[&g]).unwrap();
+
+        for i in 0..2 {
+            let g = grads[i] + decay * reference[i];
+            m[i] = beta1 * m[i] + (1.0 - beta1) * g;
+            v[i] = beta2 * v[i] + (1.0 - beta2) * g * g;
+            let m_hat = m[i] / (1.0 - beta1.powi(step));
+            let v_hat = v[i] / (1.0 - beta2.powi(step));
+            reference[i] -= lr * m_hat / (v_hat.sqrt() + eps);
+        }
+
+        assert_all_close(&values(&p), &reference);
+    }
+
+    assert_eq!(adam.steps(), 5);
+}
+
+#[test]
+fn optimizers_reject_a_gradient_count_mismatch() {
+    let mut p = vector![1.0];
+    let g = vector![1.0];
+
+    let err = Sgd::new(0.1, 0.0, 0.0)
+        .step(&mut [&mut p], &[&g, &g])
+        .unwrap_err();
+    assert!(matches!(err, OpError::ParamCount(1, 2)), "{err:?}");
+
+    let mut adam = Adam::new(0.1);
+    let err = adam.step(&mut [&mut p], &[]).unwrap_err();
+    assert!(matches!(err, OpError::ParamCount(1, 0)), "{err:?}");
+    assert_eq!(adam.steps(), 0);
+
+    let err = adam.step(&mut [&mut p], &[&vector![1.0, 2.0]]).unwrap_err();
+    assert!(
+        matches!(&err, OpError::NotSameShape(a, b) if **a == [1] && **b == [2]),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn optimizers_reject_other_params_after_the_first_step() {
+    let mut p = vector![1.0, 2.0];
+    let mut q = vector![3.0];
+    let g = vector![1.0, 1.0];
+
+    let mut sgd = Sgd::new(0.1, 0.9, 0.0);
+    sgd.step(&mut [&mut p], &[&g]).unwrap();
+
+    let err = sgd
+        .step(&mut [&mut p, &mut q], &[&g, &vector![1.0]])
+        .unwrap_err();
+    assert!(matches!(err, OpError::ParamCount(2, 1)), "{err:?}");
+
+    let mut r = vector![1.0, 2.0, 3.0];
+    let err = sgd
+        .step(&mut [&mut r], &[&vector![1.0, 1.0, 1.0]])
+        .unwrap_err();
+    assert!(
+        matches!(&err, OpError::NotSameShape(a, b) if **a == [2] && **b == [3]),
+        "{err:?}"
+    );
+
+    let mut adam = Adam::new(0.1);
+    adam.step(&mut [&mut p], &[&g]).unwrap();
+
+    let before = values(&q);
+    let err = adam.step(&mut [&mut q], &[&vector![1.0]]).unwrap_err();
+    assert!(matches!(err, OpError::NotSameShape(_, _)), "{err:?}");
+
+    // A rejected step leaves the parameters and the step count untouched.
+    assert_eq!(values(&q), before);
+    assert_eq!(adam.steps(), 1);
+}
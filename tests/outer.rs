@@ -0,0 +1,60 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, OuterProduct, Tensor};
+use simple_tensor::{matrix, s, srange, vector};
+
+use common::values;
+
+#[test]
+fn outer_product() {
+    let out = vector![1, 2, 3]
+        .outer(&vector![4, 5])
+        .unwrap()
+        .materialize();
+
+    assert_eq!(out.shape(), &[3, 2]);
+    assert_eq!(out.data(), matrix![[4, 5], [8, 10], [12, 15]].data());
+}
+
+#[test]
+fn strided_inputs_and_promises() {
+    let t = srange!(12, &[3, 4]);
+    let diagonal = t.diagonal().unwrap();
+    let row = (&t.slice(s![2..3, ..]).unwrap().view(&[4]).unwrap() - 10.0)
+        .flip(&[0])
+        .unwrap();
+
+    // [0, 5, 10] and [1, 0, -1, -2]
+    let out = diagonal.outer(&row).unwrap();
+    assert_eq!(out.shape(), &[3, 4]);
+    assert_eq!(
+        values(&out.materialize()),
+        [
+            0.0, 0.0, -0.0, -0.0, 5.0, 0.0, -5.0, -10.0, 10.0, 0.0, -10.0, -20.0
+        ]
+    );
+
+    let cached = (&vector![2, -1] * 1.0).cache();
+    let out = cached.outer(&vector![-0.0, 3.0]).unwrap().materialize();
+    assert_eq!(values(&out), [-0.0, 6.0, 0.0, -3.0]);
+    assert!(values(&out)[0].is_sign_negative());
+
+    let empty = vector![1, 2]
+        .outer(&Tensor::from_vec(vec![], &[0]))
+        .unwrap();
+    assert_eq!(empty.materialize().shape(), &[2, 0]);
+}
+
+#[test]
+fn only_vectors() {
+    let m = matrix![[1, 2], [3, 4]];
+    let v = vector![1, 2];
+
+    assert!(matches!(m.outer(&v), Err(OpError::NotEnoughAxes(1, 2))));
+    assert!(matches!(v.outer(&m), Err(OpError::NotEnoughAxes(1, 2))));
+    assert!(matches!(
+        v.outer(&Tensor::from_scalar(1.0, &[1, 1, 2])),
+        Err(OpError::NotEnoughAxes(1, 3))
+    ));
+}
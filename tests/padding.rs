@@ -0,0 +1,195 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, PaddingMode, Tensor, pad_sequence};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+#[test]
+fn pad_constant_on_every_side() {
+    let t = matrix![[1, 2], [3, 4]];
+
+    let padded = t.pad_constant(1, 0.0);
+    assert_eq!(padded.shape(), &[4, 4]);
+    assert_eq!(
+        values(&padded),
+        [
+            0.0, 0.0, 0.0, 0.0, //
+            0.0, 1.0, 2.0, 0.0, //
+            0.0, 3.0, 4.0, 0.0, //
+            0.0, 0.0, 0.0, 0.0,
+        ]
+    );
+
+    let unchanged = t.pad_constant(0, 9.0);
+    assert_eq!(values(&unchanged), values(&t));
+}
+
+#[test]
+fn symmetric_mode_repeats_the_edges() {
+    let t = vector![1, 2, 3];
+
+    let symmetric = t.pad(&[(2, 3)], PaddingMode::Symmetric).unwrap();
+    assert_eq!(values(&symmetric), [2.0, 1.0, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0]);
+
+    let reflect = t.pad(&[(2, 2)], PaddingMode::Reflect).unwrap();
+    assert_eq!(values(&reflect), [3.0, 2.0, 1.0, 2.0, 3.0, 2.0, 1.0]);
+
+    assert!(matches!(
+        t.pad(&[(4, 0)], PaddingMode::Symmetric),
+        Err(OpError::InvalidPadding(0))
+    ));
+}
+
+#[test]
+fn pad_symmetric_limits_both_sides() {
+    let t = matrix![[1, 2, 3], [4, 5, 6]];
+
+    let padded = t
+        .pad_symmetric(&[(1, 1), (2, 1)], PaddingMode::Symmetric)
+        .unwrap();
+    assert_eq!(padded.shape(), &[4, 6]);
+    assert_eq!(
+        values(&padded),
+        [
+            2.0, 1.0, 1.0, 2.0, 3.0, 3.0, //
+            2.0, 1.0, 1.0, 2.0, 3.0, 3.0, //
+            5.0, 4.0, 4.0, 5.0, 6.0, 6.0, //
+            5.0, 4.0, 4.0, 5.0, 6.0, 6.0,
+        ]
+    );
+
+    // Each side alone fits in the rows, but not both together.
+    assert!(matches!(
+        t.pad_symmetric(&[(2, 1), (0, 0)], PaddingMode::Edge),
+        Err(OpError::InvalidPadding(0))
+    ));
+    assert!(matches!(
+        t.pad_symmetric(&[(0, 0), (2, 2)], PaddingMode::Reflect),
+        Err(OpError::InvalidPadding(1))
+    ));
+}
+
+#[test]
+fn pad_to_shape_right_pads() {
+    let t = matrix![[1, 2], [3, 4]];
+
+    let padded = t.pad_to_shape(&[3, 4], -1.0).unwrap();
+    assert_eq!(padded.shape(), &[3, 4]);
+    assert_eq!(
+        values(&padded),
+        [
+            1.0, 2.0, -1.0, -1.0, //
+            3.0, 4.0, -1.0, -1.0, //
+            -1.0, -1.0, -1.0, -1.0,
+        ]
+    );
+
+    assert!(matches!(
+        t.pad_to_shape(&[3, 1], 0.0),
+        Err(OpError::InvalidPadding(1))
+    ));
+    assert!(matches!(
+        t.pad_to_shape(&[3], 0.0),
+        Err(OpError::NotEnoughAxes(2, 1))
+    ));
+}
+
+#[test]
+fn padding_a_strided_tensor() {
+    let t = matrix![[1, 2], [3, 4]].transpose().materialize();
+
+    let padded = t.pad_to_shape(&[2, 3], 0.0).unwrap();
+    assert_eq!(values(&padded), [1.0, 3.0, 0.0, 2.0, 4.0, 0.0]);
+}
+
+#[test]
+fn pad_to_multiple_in_every_mode() {
+    let t = matrix![[1, 2, 3], [4, 5, 6]];
+
+    let cases = [
+        (
+            PaddingMode::Constant(0.0),
+            [1.0, 2.0, 3.0, 0.0, 0.0, 4.0, 5.0, 6.0, 0.0, 0.0],
+        ),
+        (
+            PaddingMode::Edge,
+            [1.0, 2.0, 3.0, 3.0, 3.0, 4.0, 5.0, 6.0, 6.0, 6.0],
+        ),
+        (
+            PaddingMode::Reflect,
+            [1.0, 2.0, 3.0, 2.0, 1.0, 4.0, 5.0, 6.0, 5.0, 4.0],
+        ),
+        (
+            PaddingMode::Symmetric,
+            [1.0, 2.0, 3.0, 3.0, 2.0, 4.0, 5.0, 6.0, 6.0, 5.0],
+        ),
+    ];
+
+    for (mode, expected) in cases {
+        let padded = t.pad_to_multiple(1, 5, mode).unwrap();
+        assert_eq!(padded.shape(), &[2, 5], "{mode:?}");
+        assert_eq!(values(&padded), expected, "{mode:?}");
+
+        // An axis that is already a multiple is left as it is.
+        let unchanged = t.pad_to_multiple(1, 3, mode).unwrap();
+        assert_eq!(values(&unchanged), values(&t), "{mode:?}");
+    }
+
+    let rows = t.pad_to_multiple(0, 4, PaddingMode::Edge).unwrap();
+    assert_eq!(rows.shape(), &[4, 3]);
+    assert_eq!(
+        values(&rows),
+        [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 4.0, 5.0, 6.0, 4.0, 5.0, 6.0]
+    );
+}
+
+#[test]
+fn pad_to_multiple_rejects_invalid_requests() {
+    let t = matrix![[1, 2, 3], [4, 5, 6]];
+
+    assert!(matches!(
+        t.pad_to_multiple(1, 0, PaddingMode::Edge),
+        Err(OpError::InvalidPadding(1))
+    ));
+    assert!(matches!(
+        t.pad_to_multiple(2, 4, PaddingMode::Edge),
+        Err(OpError::OutOfBoundAxes)
+    ));
+
+    // Reflect pads less than the axis, Symmetric up to the axis.
+    assert!(matches!(
+        t.pad_to_multiple(1, 6, PaddingMode::Reflect),
+        Err(OpError::InvalidPadding(1))
+    ));
+    assert!(t.pad_to_multiple(1, 6, PaddingMode::Symmetric).is_ok());
+    assert!(matches!(
+        t.pad_to_multiple(1, 7, PaddingMode::Symmetric),
+        Err(OpError::InvalidPadding(1))
+    ));
+}
+
+#[test]
+fn pad_sequence_stacks_along_the_second_axis() {
+    let sequences = [vector![1, 2, 3], vector![4], vector![5, 6]];
+
+    let padded = pad_sequence(&sequences, -1.0).unwrap();
+    assert_eq!(padded.shape(), &[3, 3]);
+    assert_eq!(
+        values(&padded),
+        [
+            1.0, 4.0, 5.0, //
+            2.0, -1.0, 6.0, //
+            3.0, -1.0, -1.0,
+        ]
+    );
+
+    let empty: [Tensor<f64>; 0] = [];
+    assert_eq!(pad_sequence(&empty, 0.0).unwrap().shape(), &[0, 0]);
+
+    assert!(matches!(
+        pad_sequence(&[vector![1], matrix![[1, 2]]], 0.0),
+        Err(OpError::NotEnoughAxes(1, 2))
+    ));
+}
@@ -0,0 +1,272 @@
+mod common;
+
+use simple_tensor::parse_tensor;
+use simple_tensor::tensor::errors::ParseError;
+use simple_tensor::tensor::{Dimension, Tensor, TensorData};
+
+use common::{bits, values};
+
+fn unexpected(offset: usize, found: &str, expected: &'static str) -> ParseError {
+    ParseError::Unexpected(offset, found.into(), expected)
+}
+
+fn random_words(len: usize, mut seed: u64) -> Vec<u64> {
+    (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            seed ^ (seed >> 29)
+        })
+        .collect()
+}
+
+// NaN is read back without its payload.
+fn same_f64(a: f64, b: f64) -> bool {
+    a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn values_round_trip_exactly() {
+    let specials = vec![
+        -0.0,
+        0.0,
+        5e-324,
+        -2.2250738585072e-308,
+        f64::MIN_POSITIVE,
+        f64::MAX,
+        f64::MIN,
+        f64::EPSILON,
+        1e300,
+        3e-7,
+        0.1,
+        1.0 / 3.0,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NAN,
+        123456789012345680.0,
+    ];
+    let x = Tensor::from_vec(specials.clone(), &[4, 4]);
+    let text = x.to_parseable_string();
+    let back = Tensor::<f64>::from_str_data(&text).unwrap();
+
+    assert_eq!(back.shape(), [4, 4]);
+    assert!(back.iter().zip(&specials).all(|(&a, &b)| same_f64(a, b)));
+    assert_eq!(bits(&back)[..14], bits(&x)[..14]);
+    assert!(text.starts_with("[[-0.0, 0.0, 5e-324, -2.2250738585072e-308], ["));
+
+    let x = Tensor::from_vec(vec![1.0, 2.5, 3e-7, 4.0], &[2, 2]);
+    assert_eq!(x.to_parseable_string(), "[[1.0, 2.5], [3e-7, 4.0]]");
+
+    let singles = vec![-0.0f32, 1e-45, f32::MAX, f32::MIN_POSITIVE, 0.1, -7.25];
+    let x = TensorData::from_vec(singles.clone(), &[6], 0);
+    let back: TensorData<f32> = x.to_parseable_string().parse().unwrap();
+    let back: Vec<u32> = back.iter().map(|el| el.to_bits()).collect();
+    assert_eq!(
+        back,
+        singles.iter().map(|el| el.to_bits()).collect::<Vec<_>>()
+    );
+
+    let integers = Tensor::from_vec(vec![i64::MIN, -1, 0, 42, i64::MAX], &[5]);
+    let text = integers.to_parseable_string();
+    assert_eq!(text, format!("[{}, -1, 0, 42, {}]", i64::MIN, i64::MAX));
+    let back: Tensor<i64> = text.parse().unwrap();
+    assert_eq!(
+        back.iter().copied().collect::<Vec<_>>(),
+        [i64::MIN, -1, 0, 42, i64::MAX]
+    );
+}
+
+#[test]
+fn shapes_follow_the_nesting() {
+    let cases: [(&str, &[usize]); 9] = [
+        ("7.5", &[]),
+        ("[1, 2, 3]", &[3]),
+        ("[[1, 2.5], [3e-7, 4]]", &[2, 2]),
+        ("[[[1], [2]], [[3], [4]], [[5], [6]]]", &[3, 2, 1]),
+        ("[[[[1, 2, 3]]], [[[4, 5, 6]]]]", &[2, 1, 1, 3]),
+        ("[]", &[0]),
+        ("[[], []]", &[2, 0]),
+        ("[[[]]]", &[1, 1, 0]),
+        (" \n\t[ [ 1 ,2 ]\n ,\r\n[3,\t4]\t] \n", &[2, 2]),
+    ];
+    for (text, shape) in cases {
+        let x = Tensor::<f64>::from_str_data(text).unwrap();
+        assert_eq!(x.shape(), shape, "{}", text);
+        assert_eq!(x.len(), shape.iter().product::<usize>());
+    }
+
+    let x = Tensor::<f64>::from_str_data("[[1, 2.5], [3e-7, 4]]").unwrap();
+    assert_eq!(values(&x), [1.0, 2.5, 3e-7, 4.0]);
+    assert!(x.is_contiguous());
+
+    // Strided views are written in logical order.
+    let transposed = x.transpose().materialize();
+    assert_eq!(
+        transposed.to_parseable_string(),
+        "[[1.0, 3e-7], [2.5, 4.0]]"
+    );
+
+    // Empty tensors keep their axes up to the first empty one.
+    let empty = Tensor::<f64>::from_vec(vec![], &[2, 0, 3]);
+    assert_eq!(empty.to_parseable_string(), "[[], []]");
+    let scalar = Tensor::from_vec(vec![-0.5], &[]);
+    assert_eq!(scalar.to_parseable_string(), "-0.5");
+    assert_eq!(Tensor::<f64>::from_str_data("-0.5").unwrap().shape(), []);
+}
+
+#[test]
+fn malformed_input_fails_at_its_offset() {
+    let cases = [
+        ("", unexpected(0, "the end of the input", "a number or '['")),
+        (
+            "   ",
+            unexpected(3, "the end of the input", "a number or '['"),
+        ),
+        ("]", unexpected(0, "']'", "a number or '['")),
+        ("[1, 2", unexpected(5, "the end of the input", "',' or ']'")),
+        ("[1,, 2]", unexpected(3, "','", "a number or '['")),
+        ("[1, 2,]", unexpected(6, "']'", "a number or '['")),
+        ("[1 2]", unexpected(3, "'2'", "',' or ']'")),
+        ("[1]]", unexpected(3, "']'", "the end of the input")),
+        ("[1] [2]", unexpected(4, "'['", "the end of the input")),
+        ("[1[2]]", unexpected(2, "'['", "',' or ']'")),
+        (
+            "[1, 2.5.1]",
+            ParseError::InvalidNumber(4, "2.5.1".into(), "f64"),
+        ),
+        ("[é]", ParseError::InvalidNumber(1, "é".into(), "f64")),
+        // What to_string_flat() elides cannot be read back.
+        (
+            "[1, 2, ...]",
+            ParseError::InvalidNumber(7, "...".into(), "f64"),
+        ),
+    ];
+    for (text, expected) in cases {
+        let err = text.parse::<TensorData<f64>>().err().unwrap();
+        assert_eq!(err, expected, "{:?}", text);
+        assert!(err.offset() <= text.len());
+    }
+
+    let err = Tensor::<i64>::from_str_data("[1, 2.0]").err().unwrap();
+    assert_eq!(err, ParseError::InvalidNumber(4, "2.0".into(), "i64"));
+    assert_eq!(err.to_string(), "2.0 at byte 4 is not a valid i64");
+    let err = Tensor::<f64>::from_str_data("[1 2]").err().unwrap();
+    assert_eq!(err.to_string(), "expected ',' or ']' at byte 3, found '2'");
+
+    // Nesting is not bounded by the stack.
+    let deep = "[".repeat(1_000_000);
+    let err = Tensor::<f64>::from_str_data(&deep).err().unwrap();
+    assert_eq!(err.offset(), deep.len());
+    let deep = format!("{}1{}", deep, "]".repeat(1_000_000));
+    assert_eq!(Tensor::<f64>::from_str_data(&deep).unwrap().len(), 1);
+}
+
+#[test]
+fn ragged_lists_fail_with_their_index() {
+    let cases = [
+        (
+            "[[1, 2], [3]]",
+            ParseError::RaggedLength(9, Box::new([1]), 2, 1),
+        ),
+        (
+            "[[[1], [2]], [[3], [4, 5]]]",
+            ParseError::RaggedLength(19, Box::new([1, 1]), 1, 2),
+        ),
+        (
+            "[[1], []]",
+            ParseError::RaggedLength(6, Box::new([1]), 1, 0),
+        ),
+        (
+            "[[], [1]]",
+            ParseError::RaggedLength(5, Box::new([1]), 0, 1),
+        ),
+        ("[[1, 2], 3]", ParseError::RaggedDepth(9, Box::new([1]))),
+        ("[1, [2]]", ParseError::RaggedDepth(4, Box::new([1]))),
+        ("[[], 1]", ParseError::RaggedDepth(5, Box::new([1]))),
+        ("[[1], [[2]]]", ParseError::RaggedDepth(7, Box::new([1, 0]))),
+    ];
+    for (text, expected) in cases {
+        let err = Tensor::<f64>::from_str_data(text).err().unwrap();
+        assert_eq!(err, expected, "{:?}", text);
+    }
+
+    let err = Tensor::<f64>::from_str_data("[[1, 2],\n [3]]")
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "the list [1] at byte 10 has 1 elements, but the lists before it have 2"
+    );
+    let err = Tensor::<f64>::from_str_data("[[1, 2], 3]").err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "the element [1] at byte 9 is not nested like the elements before it"
+    );
+}
+
+#[test]
+fn the_macro_reads_fixtures() {
+    let x = parse_tensor!("[[1,2],[3,4]]");
+    assert_eq!(x.shape(), [2, 2]);
+    assert_eq!(values(&x), [1.0, 2.0, 3.0, 4.0]);
+
+    let x = parse_tensor!("[[1, -2]]", i64);
+    assert_eq!(x.shape(), [1, 2]);
+    assert_eq!(x.iter().copied().collect::<Vec<_>>(), [1, -2]);
+
+    let result = std::panic::catch_unwind(|| parse_tensor!("[[1, 2], [3]]"));
+    let message = result.err().unwrap();
+    assert_eq!(
+        message.downcast_ref::<String>().unwrap(),
+        "parse_tensor!: the list [1] at byte 9 has 1 elements, but the lists before it have 2"
+    );
+}
+
+#[test]
+fn formatting_then_parsing_is_the_identity() {
+    for seed in 0..200 {
+        let words = random_words(5, seed);
+        let shape: Vec<usize> = words[..1 + (words[4] % 4) as usize]
+            .iter()
+            .map(|word| 1 + (word % 4) as usize)
+            .collect();
+        let len = shape.iter().product();
+
+        // Random bits reach subnormals, NaN and every exponent. iter() needs an axis.
+        let words = random_words(len, seed + 1000);
+        let doubles = Tensor::from_iter(words.iter().map(|&w| f64::from_bits(w)), &shape);
+        let back = Tensor::<f64>::from_str_data(&doubles.to_parseable_string()).unwrap();
+        assert_eq!(back.shape(), doubles.shape());
+        assert!(
+            back.iter()
+                .zip(doubles.iter())
+                .all(|(&a, &b)| same_f64(a, b))
+        );
+
+        let singles = Tensor::from_iter(words.iter().map(|&w| f32::from_bits(w as u32)), &shape);
+        let back = Tensor::<f32>::from_str_data(&singles.to_parseable_string()).unwrap();
+        assert!(
+            (back.iter().zip(singles.iter()))
+                .all(|(a, b)| a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan()))
+        );
+
+        let integers = Tensor::from_iter(words.iter().map(|&w| w as i64), &shape);
+        let back = Tensor::<i64>::from_str_data(&integers.to_parseable_string()).unwrap();
+        assert!(back.data() == integers.data());
+
+        // The flat rendering of a small tensor shows every element, and Display is exact too.
+        if len <= 32 {
+            let flat = doubles.to_string_flat();
+            let (elements, _) = flat.rsplit_once(" shape=").unwrap();
+            let back = Tensor::<f64>::from_str_data(elements).unwrap();
+            assert!(
+                back.iter()
+                    .zip(doubles.iter())
+                    .all(|(&a, &b)| same_f64(a, b))
+            );
+        }
+    }
+}
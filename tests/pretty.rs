@@ -0,0 +1,230 @@
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+use std::rc::Rc;
+
+use simple_tensor::s;
+use simple_tensor::tensor::complex::Complex;
+use simple_tensor::tensor::{Dimension, FLAT_ELEMENT_BUDGET, Layout, Tensor, TensorData};
+
+fn range(len: usize, shape: &[usize]) -> Tensor<f64> {
+    Tensor::from_vec((0..len).map(|i| i as f64).collect(), shape)
+}
+
+// Numbers written before the shape, and whether the rest was elided.
+fn shown_elements(flat: &str) -> (usize, bool) {
+    let (elements, _) = flat.rsplit_once(" shape=").unwrap();
+    let tokens: Vec<&str> = elements
+        .split(['[', ']', ',', ' '])
+        .filter(|token| !token.is_empty())
+        .collect();
+
+    let numbers = tokens.iter().filter(|token| token.parse::<i64>().is_ok());
+
+    (numbers.count(), tokens.contains(&"..."))
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn flat_renderings() {
+    let vector = Tensor::from_vec(vec![1.0, 2.5, -3.0], &[3]);
+    assert_eq!(vector.to_string_flat(), "[1, 2.5, -3] shape=[3]");
+
+    let matrix = range(4, &[2, 2]);
+    assert_eq!(matrix.to_string_flat(), "[[0, 1], [2, 3]] shape=[2,2]");
+    assert_eq!(
+        matrix.to_string_flat_with(3),
+        "[[0, 1], [2, ...]] shape=[2,2]"
+    );
+    assert_eq!(matrix.to_string_flat_with(2), "[[0, 1], ...] shape=[2,2]");
+    assert_eq!(matrix.to_string_flat_with(0), "[...] shape=[2,2]");
+
+    let batch = range(8, &[2, 1, 2, 2]);
+    assert_eq!(
+        batch.to_string_flat(),
+        "[[[[0, 1], [2, 3]]], [[[4, 5], [6, 7]]]] shape=[2,1,2,2]"
+    );
+    assert_eq!(
+        batch.to_string_flat_with(5),
+        "[[[[0, 1], [2, 3]]], [[[4, ...]]]] shape=[2,1,2,2]"
+    );
+
+    let empty = Tensor::<f64>::from_vec(vec![], &[2, 0]);
+    assert_eq!(empty.to_string_flat(), "[] shape=[2,0]");
+
+    let scalar = Tensor::from_vec(vec![7.5], &[]);
+    assert_eq!(scalar.to_string_flat(), "7.5 shape=[]");
+
+    let data = TensorData::from_vec(vec![1i32, -2], &[1, 2], 0);
+    assert_eq!(data.to_string_flat(), "[[1, -2]] shape=[1,2]");
+}
+
+#[test]
+fn flat_budget_is_exact() {
+    for len in [1, 2, 7, 31, 32, 33, 100, 1000] {
+        for shape in [vec![len], vec![len, 1], vec![1, len], vec![1, 1, len]] {
+            let x = Tensor::from_vec((0..len as i64).collect(), &shape);
+
+            for budget in [0, 1, 2, len - 1, len, len + 1, FLAT_ELEMENT_BUDGET] {
+                let flat = x.to_string_flat_with(budget);
+
+                assert_eq!(
+                    shown_elements(&flat),
+                    (budget.min(len), budget < len),
+                    "{:?} with {} elements: {}",
+                    shape,
+                    budget,
+                    flat
+                );
+                assert!(!flat.contains('\n') && flat.is_ascii());
+            }
+        }
+    }
+
+    // The length of the string depends on the budget only.
+    let x = range(1 << 20, &[1024, 1024]);
+    let flat = x.to_string_flat();
+    assert_eq!(shown_elements(&flat), (FLAT_ELEMENT_BUDGET, true));
+    assert!(flat.len() < 200, "{}", flat);
+}
+
+#[test]
+fn previews() {
+    let matrix = range(12, &[3, 4]);
+    assert_eq!(
+        matrix.preview(2, 2),
+        "shape=[3,4] dtype=f64\n[[0, 1, ...],\n [4, 5, ...],\n ...]"
+    );
+    assert_eq!(
+        matrix.preview(3, 3),
+        "shape=[3,4] dtype=f64\n[[ 0,  1,  2, ...],\n [ 4,  5,  6, ...],\n [ 8,  9, 10, ...]]"
+    );
+    assert_eq!(
+        matrix.preview(10, 10),
+        "shape=[3,4] dtype=f64\n[[ 0,  1,  2,  3],\n [ 4,  5,  6,  7],\n [ 8,  9, 10, 11]]"
+    );
+    assert_eq!(
+        matrix.preview(1, 0),
+        "shape=[3,4] dtype=f64\n[[...],\n ...]"
+    );
+
+    let vector = Tensor::from_vec(vec![1.5, -2.0, 3.0, 4.0], &[4]);
+    assert_eq!(
+        vector.preview(5, 3),
+        "shape=[4] dtype=f64\n[1.5,  -2,   3, ...]"
+    );
+
+    let batch = range(24, &[2, 3, 2, 2]);
+    assert_eq!(
+        batch.preview(5, 5),
+        "shape=[2,3,2,2] dtype=f64 view=[0,0,:,:]\n[[0, 1],\n [2, 3]]"
+    );
+    assert_eq!(
+        batch.preview(1, 1),
+        "shape=[2,3,2,2] dtype=f64 view=[0,0,:,:]\n[[0, ...],\n ...]"
+    );
+
+    let scalar = Tensor::from_vec(vec![7.5], &[]);
+    assert_eq!(scalar.preview(3, 3), "shape=[] dtype=f64\n7.5");
+}
+
+#[test]
+fn preview_headers() {
+    let named = range(6, &[2, 3])
+        .with_axis_names(&["batch", "feature"])
+        .unwrap();
+    assert!(
+        named
+            .preview(1, 1)
+            .starts_with("shape=[2,3] names=[batch,feature] dtype=f64\n")
+    );
+
+    let complex = Tensor::from_vec(vec![Complex::new(1.0, -2.0)], &[1, 1]);
+    assert_eq!(
+        complex.preview(1, 1),
+        "shape=[1,1] dtype=Complex<f64>\n[[1-2i]]"
+    );
+
+    let data = TensorData::from_vec(vec![1i32, 2, 3, 4], &[2, 2], 0);
+    assert_eq!(data.preview(1, 2), "shape=[2,2] dtype=i32\n[[1, 2],\n ...]");
+
+    let empty = Tensor::<f64>::from_vec(vec![], &[0, 3]);
+    assert_eq!(empty.preview(2, 2), "shape=[0,3] dtype=f64\n[]");
+}
+
+#[test]
+fn views_render_in_logical_order() {
+    let x = range(6, &[2, 3]);
+
+    let transposed = x.transpose().materialize();
+    assert!(!transposed.data().is_contiguous());
+    assert_eq!(
+        transposed.to_string_flat(),
+        "[[0, 3], [1, 4], [2, 5]] shape=[3,2]"
+    );
+    assert_eq!(
+        transposed.preview(2, 2),
+        "shape=[3,2] dtype=f64\n[[0, 3],\n [1, 4],\n ...]"
+    );
+
+    let sliced = x.slice(s![.., 1..]).unwrap().materialize();
+    assert_eq!(sliced.to_string_flat(), "[[1, 2], [4, 5]] shape=[2,2]");
+    assert_eq!(
+        sliced.preview(1, 1),
+        "shape=[2,2] dtype=f64\n[[1, ...],\n ...]"
+    );
+
+    // The last two columns of a 3x3 matrix, read backwards from the end of the buffer.
+    let data = TensorData::from_vec((0..9).collect::<Vec<i32>>(), &[3, 3], 0)
+        .as_layout(Layout::from_slice(&[3, 2], &[-3, -1], 8));
+    assert_eq!(
+        data.to_string_flat(),
+        "[[8, 7], [5, 4], [2, 1]] shape=[3,2]"
+    );
+    assert_eq!(
+        data.preview(2, 1),
+        "shape=[3,2] dtype=i32\n[[8, ...],\n [5, ...],\n ...]"
+    );
+}
+
+// An element calling the hook of its thread whenever it is formatted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Hooked(f64);
+
+thread_local! {
+    static ON_FORMAT: RefCell<Option<Box<dyn FnMut()>>> = const { RefCell::new(None) };
+}
+
+impl Display for Hooked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        ON_FORMAT.with_borrow_mut(|hook| hook.as_mut().map(|hook| hook()));
+        write!(f, "{}", self.0)
+    }
+}
+
+#[test]
+fn writers_proceed_while_elements_are_formatted() {
+    let x = Tensor::from_vec(vec![Hooked(1.0), Hooked(2.0), Hooked(3.0)], &[3]);
+
+    // Shares the buffer of x, and writes to it while every element of x is formatted.
+    let writer = Rc::new(RefCell::new(x.clone_detached()));
+    let writes = Rc::new(RefCell::new(0.0));
+
+    let (w, n) = (writer.clone(), writes.clone());
+    ON_FORMAT.set(Some(Box::new(move || {
+        *n.borrow_mut() += 1.0;
+        let value = *n.borrow();
+        w.borrow_mut().assign_from_iter([Hooked(value); 3]).unwrap();
+    })));
+
+    let flat = x.to_string_flat();
+    let preview = x.preview(1, 3);
+    ON_FORMAT.set(None);
+
+    assert_eq!(flat, "[1, 2, 3] shape=[3]");
+    assert_eq!(preview, "shape=[3] dtype=Hooked\n[1, 2, 3]");
+
+    // Once per element formatted, each write having gone through.
+    assert_eq!(*writes.borrow(), 6.0);
+    assert_eq!(writer.borrow().to_string_flat(), "[6, 6, 6] shape=[3]");
+}
@@ -0,0 +1,67 @@
+use simple_tensor::tensor::{Tensor, flush_profile};
+
+fn random_tensor(shape: &[usize], mut seed: u64) -> Tensor<f64> {
+    let len = shape.iter().product();
+    let data = (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 20.0 - 10.0
+        })
+        .collect();
+
+    Tensor::from_vec(data, shape)
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn one_record_per_computed_node() {
+    use std::time::{Duration, Instant};
+
+    let a = random_tensor(&[400_000], 1);
+    let b = random_tensor(&[400_000], 2);
+    let c = random_tensor(&[400_000], 3);
+
+    let sum = &a + &b;
+    let product = &sum * &c;
+    let rolled = product.rolling_sum(16, 0).unwrap();
+    let diff = rolled.diff(1, 0).unwrap();
+    let area = diff.trapz(0.5, 0).unwrap();
+    let ids = [sum.id(), product.id(), rolled.id(), diff.id(), area.id()];
+
+    flush_profile();
+    let start = Instant::now();
+    area.materialize();
+    let wall = start.elapsed();
+
+    let records = flush_profile();
+    assert_eq!(records.len(), 5);
+
+    let ops: Vec<&str> = records.iter().map(|record| record.op()).collect();
+    assert_eq!(ops, ["Add", "Mul", "RollingSum", "Diff", "Trapz"]);
+    assert!(records.iter().map(|record| record.id()).eq(ids));
+    assert!(
+        records
+            .iter()
+            .all(|record| record.duration() > Duration::ZERO)
+    );
+
+    // The kernels are most of the work, the rest is sorting and moving the results.
+    let total: Duration = records.iter().map(|record| record.duration()).sum();
+    assert!(total <= wall);
+    assert!(total * 2 >= wall, "{:?} of {:?}", total, wall);
+
+    // The buffer is drained.
+    assert!(flush_profile().is_empty());
+}
+
+#[cfg(not(feature = "profiling"))]
+#[test]
+fn nothing_is_recorded_without_the_feature() {
+    let a = random_tensor(&[100], 1);
+    let b = random_tensor(&[100], 2);
+
+    (&(&a + &b) * &a).materialize();
+    assert!(flush_profile().is_empty());
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "indicatif")]
+
+mod common;
+
+use simple_tensor::tensor::{ProgressOutput, Tensor, last_progress, set_progress_output};
+
+use common::{arange, lock_globals, values};
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn the_bar_reaches_every_node_of_the_graph() {
+    let _globals = lock_globals();
+    set_progress_output(ProgressOutput::None);
+
+    let a = arange(&[4]);
+    let b = Tensor::from_scalar(2.0, &[4]);
+
+    // The two tensors and 8 ops.
+    let left = (&a + 1.0).sqrt();
+    let right = (&b * &a) - 3.0;
+    let promise = (((&left + &right) * &left - &right) * 0.5).with_progress("graph");
+
+    let expected: Vec<f64> = (0..4)
+        .map(|i| {
+            let (l, r) = ((i as f64 + 1.0).sqrt(), 2.0 * i as f64 - 3.0);
+            ((l + r) * l - r) * 0.5
+        })
+        .collect();
+
+    assert_eq!(values(&promise.materialize()), expected);
+    assert_eq!(last_progress(), Some((10, 10)));
+
+    // A linear chain goes through a faster path, which advances the bar all the same.
+    let chain = ((((&a + 1.0) * 2.0).sqrt() - 1.0).exp() * 3.0).with_progress("chain");
+    chain.materialize();
+
+    let (position, length) = last_progress().unwrap();
+    assert_eq!(position, length);
+
+    set_progress_output(ProgressOutput::Stderr);
+}
@@ -0,0 +1,313 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{CachedTensorPromise, Dimension, Tensor, TensorPromise};
+use simple_tensor::{arange, matrix, ones, s, srange, vector, zeros};
+
+use common::values;
+
+fn lhs() -> Tensor<f64> {
+    Tensor::from_vec(vec![1.0, 2.0, 3.0, 4.0], &[2, 2])
+}
+
+fn rhs() -> Tensor<f64> {
+    Tensor::from_vec(vec![10.0, 20.0, 40.0, 80.0], &[2, 2])
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn constructors() {
+    let t = Tensor::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3]);
+    assert_eq!(t.shape(), &[2, 3]);
+    assert_eq!(values(&t), [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let t = Tensor::from_scalar(7.0, &[3]);
+    assert_eq!(values(&t), [7.0; 3]);
+
+    let t = Tensor::from_iter((0..4).map(|i| i as f64 * 0.5), &[4]);
+    assert_eq!(values(&t), [0.0, 0.5, 1.0, 1.5]);
+}
+
+#[test]
+fn convenience_macros() {
+    assert_eq!(values(&zeros!(&[2, 2])), [0.0; 4]);
+    assert_eq!(values(&ones!(&[3])), [1.0; 3]);
+
+    let t = arange![4];
+    assert_eq!(t.shape(), &[4]);
+    assert_eq!(values(&t), [0.0, 1.0, 2.0, 3.0]);
+
+    let t = arange![2, 5];
+    assert_eq!(t.shape(), &[1, 3]);
+    assert_eq!(values(&t), [2.0, 3.0, 4.0]);
+
+    let t = arange![0, 10, 3];
+    assert_eq!(values(&t), [0.0, 3.0, 6.0, 9.0]);
+
+    let t = srange![6, &[2, 3]];
+    assert_eq!(t.shape(), &[2, 3]);
+    assert_eq!(values(&t), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    let t = vector![1.0, 2.0, 3];
+    assert_eq!(t.shape(), &[3]);
+    assert_eq!(values(&t), [1.0, 2.0, 3.0]);
+
+    let m = matrix![[1, 2], [3, 4]];
+    let reference = arange![1, 5].view(&[2, 2]).unwrap().materialize();
+    assert_eq!(m.shape(), reference.shape());
+    assert_eq!(values(&m), values(&reference));
+}
+
+#[test]
+fn slicing() {
+    let t = srange![12, &[3, 4]];
+
+    let block = t.slice(s![0..2, 1..3]).unwrap().materialize();
+    assert_eq!(block.shape(), &[2, 2]);
+    assert_eq!(values(&block), [1.0, 2.0, 5.0, 6.0]);
+
+    let last_column = t.slice(s![.., -1..]).unwrap().materialize();
+    assert_eq!(last_column.shape(), &[3, 1]);
+    assert_eq!(values(&last_column), [3.0, 7.0, 11.0]);
+
+    let tail = t.slice(s![1.., ..2]).unwrap().materialize();
+    assert_eq!(values(&tail), [4.0, 5.0, 8.0, 9.0]);
+
+    // Slicing a promise composes with the previous slice
+    let nested = t
+        .slice(s![1.., ..])
+        .unwrap()
+        .slice(s![.., 2..])
+        .unwrap()
+        .materialize();
+    assert_eq!(values(&nested), [6.0, 7.0, 10.0, 11.0]);
+
+    assert!(matches!(
+        t.slice(s![0..4, ..]),
+        Err(OpError::InvalidSliceShape(3, 4))
+    ));
+    assert!(matches!(
+        t.slice(s![2..2, ..]),
+        Err(OpError::OutOfBoundSlice)
+    ));
+}
+
+#[test]
+fn views_and_transposes() {
+    let t = arange![12];
+
+    let m = t.view(&[3, 4]).unwrap().materialize();
+    assert_eq!(m.shape(), &[3, 4]);
+
+    let back = m.view(&[12]).unwrap().materialize();
+    assert_eq!(values(&back), values(&t));
+
+    let transposed = m.transpose().materialize();
+    assert_eq!(transposed.shape(), &[4, 3]);
+    assert_eq!(
+        values(&transposed),
+        [0.0, 4.0, 8.0, 1.0, 5.0, 9.0, 2.0, 6.0, 10.0, 3.0, 7.0, 11.0]
+    );
+    assert_eq!(values(&transposed.transpose().materialize()), values(&m));
+
+    let permuted = t
+        .view(&[2, 3, 2])
+        .unwrap()
+        .transpose_axes(&[2, 0, 1])
+        .unwrap()
+        .materialize();
+    assert_eq!(permuted.shape(), &[2, 2, 3]);
+    assert_eq!(
+        values(&permuted),
+        [0.0, 2.0, 4.0, 6.0, 8.0, 10.0, 1.0, 3.0, 5.0, 7.0, 9.0, 11.0]
+    );
+
+    assert!(matches!(t.view(&[5, 2]), Err(OpError::InvalidViewShape)));
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+// Every operator is checked with owned and borrowed operands, for each pairing of
+// Tensor, TensorPromise and CachedTensorPromise the crate implements.
+macro_rules! check_binary_ops {
+    ($name:ident, $make_lhs:expr, $make_rhs:expr) => {
+        #[test]
+        fn $name() {
+            let make_lhs = $make_lhs;
+            let make_rhs = $make_rhs;
+
+            let cases: [(fn(f64, f64) -> f64, &str); 4] = [
+                (|a, b| a + b, "add"),
+                (|a, b| a - b, "sub"),
+                (|a, b| a * b, "mul"),
+                (|a, b| a / b, "div"),
+            ];
+
+            for (f, op) in cases {
+                let expected: Vec<f64> = values(&lhs())
+                    .into_iter()
+                    .zip(values(&rhs()))
+                    .map(|(a, b)| f(a, b))
+                    .collect();
+
+                let (l, r) = (make_lhs(), make_rhs());
+                let results = match op {
+                    "add" => [
+                        &l + &r,
+                        &l + r.clone(),
+                        l.clone() + &r,
+                        l.clone() + r.clone(),
+                    ],
+                    "sub" => [
+                        &l - &r,
+                        &l - r.clone(),
+                        l.clone() - &r,
+                        l.clone() - r.clone(),
+                    ],
+                    "mul" => [
+                        &l * &r,
+                        &l * r.clone(),
+                        l.clone() * &r,
+                        l.clone() * r.clone(),
+                    ],
+                    _ => [
+                        &l / &r,
+                        &l / r.clone(),
+                        l.clone() / &r,
+                        l.clone() / r.clone(),
+                    ],
+                };
+
+                for result in results {
+                    let result = result.materialize();
+                    assert_eq!(result.shape(), &[2, 2], "{op}");
+                    assert_eq!(values(&result), expected, "{op}");
+                }
+            }
+        }
+    };
+}
+
+fn tensor(t: Tensor<f64>) -> Tensor<f64> {
+    t
+}
+
+fn promise(t: Tensor<f64>) -> TensorPromise<f64> {
+    t.as_promise()
+}
+
+fn cached(t: Tensor<f64>) -> CachedTensorPromise<f64> {
+    t.as_promise().cache()
+}
+
+check_binary_ops!(tensor_tensor_ops, || tensor(lhs()), || tensor(rhs()));
+check_binary_ops!(tensor_promise_ops, || tensor(lhs()), || promise(rhs()));
+check_binary_ops!(tensor_cached_ops, || tensor(lhs()), || cached(rhs()));
+check_binary_ops!(promise_tensor_ops, || promise(lhs()), || tensor(rhs()));
+check_binary_ops!(promise_promise_ops, || promise(lhs()), || promise(rhs()));
+check_binary_ops!(promise_cached_ops, || promise(lhs()), || cached(rhs()));
+check_binary_ops!(cached_tensor_ops, || cached(lhs()), || tensor(rhs()));
+check_binary_ops!(cached_promise_ops, || cached(lhs()), || promise(rhs()));
+check_binary_ops!(cached_cached_ops, || cached(lhs()), || cached(rhs()));
+
+macro_rules! check_scalar_ops {
+    ($name:ident, $make:expr) => {
+        #[test]
+        fn $name() {
+            let make = $make;
+            let base = values(&lhs());
+            let expect = |f: fn(f64) -> f64| base.iter().map(|&x| f(x)).collect::<Vec<f64>>();
+
+            let t = make();
+            let cases = [
+                (&t + 2.0, expect(|x| x + 2.0)),
+                (t.clone() + 2.0, expect(|x| x + 2.0)),
+                (&t - 2.0, expect(|x| x - 2.0)),
+                (t.clone() - 2.0, expect(|x| x - 2.0)),
+                (&t * 2.0, expect(|x| x * 2.0)),
+                (t.clone() * 2.0, expect(|x| x * 2.0)),
+                (&t / 2.0, expect(|x| x / 2.0)),
+                (t.clone() / 2.0, expect(|x| x / 2.0)),
+                // A chain of scalar ops is fused into a single node
+                (
+                    ((&t + 1.0) * 3.0 - 1.0) / 2.0,
+                    expect(|x| ((x + 1.0) * 3.0 - 1.0) / 2.0),
+                ),
+            ];
+
+            for (result, expected) in cases {
+                assert_eq!(values(&result.materialize()), expected);
+            }
+        }
+    };
+}
+
+check_scalar_ops!(tensor_scalar_ops, || tensor(lhs()));
+check_scalar_ops!(promise_scalar_ops, || promise(lhs()));
+check_scalar_ops!(cached_scalar_ops, || cached(lhs()));
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn cached_promise_is_computed_once() {
+    let shared = (lhs() + rhs()).cache();
+    assert!(!shared.is_cached());
+
+    let first = (&shared * 2.0).materialize();
+    assert!(shared.is_cached());
+
+    let second = (&shared * 2.0).materialize();
+    assert_eq!(values(&first), values(&second));
+    assert_eq!(values(&first), [22.0, 44.0, 86.0, 168.0]);
+
+    // Materializing the cached promise itself returns the stored result
+    assert_eq!(
+        values(&shared.clone().materialize()),
+        [11.0, 22.0, 43.0, 84.0]
+    );
+    assert!(shared.is_cached());
+}
+
+#[test]
+fn uncached_promise_can_be_materialized_repeatedly() {
+    let p = lhs() * rhs();
+
+    assert_eq!(values(&p.clone().materialize()), values(&p.materialize()));
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn display_vector() {
+    let t = vector![1, 2, 3];
+
+    assert_eq!(t.to_string(), "[   1,    2,    3]\n");
+}
+
+#[test]
+fn display_matrix() {
+    let t = matrix![[1, 2], [3, 4.5]];
+
+    assert_eq!(t.to_string(), "[\n  [   1,    2]\n  [   3,  4.5]\n]\n");
+}
+
+#[test]
+fn display_transposed_view() {
+    let t = srange![6, &[2, 3]].transpose().materialize();
+
+    assert_eq!(
+        t.to_string(),
+        "[\n  [   0,    3]\n  [   1,    4]\n  [   2,    5]\n]\n"
+    );
+}
+
+#[test]
+fn display_3d() {
+    let t = srange![8, &[2, 2, 2]];
+
+    assert_eq!(
+        t.to_string(),
+        "[\n  [\n    [   0,    1]\n    [   2,    3]\n  ]\n  [\n    [   4,    5]\n    [   6,    7]\n  ]\n]\n"
+    );
+}
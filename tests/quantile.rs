@@ -0,0 +1,178 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, NanPolicy, QuantileMethod, Tensor};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+fn assert_close(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert!((g - e).abs() <= 1e-12, "{:?} != {:?}", got, expected);
+    }
+}
+
+// The example of the numpy.quantile documentation.
+fn numpy_example() -> Tensor<f64> {
+    matrix![[10, 7, 4], [3, 2, 1]]
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn median_of_odd_and_even_lanes() {
+    let odd = vector![3, 1, 2].median(None).unwrap().materialize();
+    assert_eq!(odd.shape(), [1]);
+    assert_eq!(values(&odd), [2.0]);
+
+    let even = vector![4, 1, 3, 2].median(None).unwrap().materialize();
+    assert_eq!(values(&even), [2.5]);
+
+    assert_eq!(
+        values(&vector![7].median(Some(0)).unwrap().materialize()),
+        [7.0]
+    );
+}
+
+#[test]
+fn whole_tensor_and_per_axis() {
+    let a = numpy_example();
+
+    assert_eq!(
+        values(&a.quantile(&[0.5], None).unwrap().materialize()),
+        [3.5]
+    );
+
+    let columns = a.quantile(&[0.5], Some(0)).unwrap().materialize();
+    assert_eq!(columns.shape(), [3]);
+    assert_eq!(values(&columns), [6.5, 4.5, 2.5]);
+
+    let rows = a.median(Some(1)).unwrap().materialize();
+    assert_eq!(rows.shape(), [2]);
+    assert_eq!(values(&rows), [7.0, 2.0]);
+
+    assert_eq!(
+        values(&a.percentile(&[50.0], Some(1)).unwrap().materialize()),
+        [7.0, 2.0]
+    );
+}
+
+#[test]
+fn several_quantiles_lead_the_shape() {
+    let a = numpy_example();
+
+    let rows = a
+        .quantile(&[0.25, 0.5, 1.0], Some(1))
+        .unwrap()
+        .materialize();
+    assert_eq!(rows.shape(), [3, 2]);
+    assert_eq!(values(&rows), [5.5, 1.5, 7.0, 2.0, 10.0, 3.0]);
+
+    let whole = a.quantile(&[0.0, 0.5, 1.0], None).unwrap().materialize();
+    assert_eq!(whole.shape(), [3]);
+    assert_eq!(values(&whole), [1.0, 3.5, 10.0]);
+
+    let cube = Tensor::from_iter((0..24).map(|i| i as f64), &[2, 3, 4]);
+    let middle = cube.quantile(&[0.0, 1.0], Some(1)).unwrap().materialize();
+    assert_eq!(middle.shape(), [2, 2, 4]);
+    assert_eq!(&values(&middle)[..4], [0.0, 1.0, 2.0, 3.0]);
+    assert_eq!(&values(&middle)[8..12], [8.0, 9.0, 10.0, 11.0]);
+}
+
+#[test]
+fn interpolation_methods() {
+    // Sorted: [1, 1, 2, 3, 4, 5, 6, 9], numpy.quantile(x, [0.25, 0.5, 0.75], method=...)
+    let x = vector![3, 1, 4, 1, 5, 9, 2, 6];
+    let qs = [0.25, 0.5, 0.75];
+
+    let fixtures = [
+        (QuantileMethod::Linear, [1.75, 3.5, 5.25]),
+        (QuantileMethod::Lower, [1.0, 3.0, 5.0]),
+        (QuantileMethod::Higher, [2.0, 4.0, 6.0]),
+        (QuantileMethod::Nearest, [2.0, 4.0, 5.0]),
+        (QuantileMethod::Midpoint, [1.5, 3.5, 5.5]),
+    ];
+
+    for (method, expected) in fixtures {
+        let got = x
+            .quantile_with(&qs, None, method, NanPolicy::Propagate)
+            .unwrap()
+            .materialize();
+        assert_close(&values(&got), &expected);
+    }
+
+    // Quantiles landing on an element are the element for every method.
+    for (method, _) in fixtures {
+        let got = (x.quantile_with(&[0.0, 1.0], None, method, NanPolicy::Propagate))
+            .unwrap()
+            .materialize();
+        assert_eq!(values(&got), [1.0, 9.0]);
+    }
+}
+
+#[test]
+fn nan_policies() {
+    let x = matrix![[1.0, f64::NAN, 3.0], [4.0, 5.0, 6.0]];
+
+    let propagated = x.median(Some(1)).unwrap().materialize();
+    assert!(values(&propagated)[0].is_nan());
+    assert_eq!(values(&propagated)[1], 5.0);
+
+    let omitted = (x.quantile_with(&[0.5], Some(1), QuantileMethod::Linear, NanPolicy::Omit))
+        .unwrap()
+        .materialize();
+    assert_eq!(values(&omitted), [2.0, 5.0]);
+
+    let all_nan = vector![f64::NAN, f64::NAN];
+    let omitted = (all_nan.quantile_with(&[0.5], None, QuantileMethod::Linear, NanPolicy::Omit))
+        .unwrap()
+        .materialize();
+    assert!(values(&omitted)[0].is_nan());
+}
+
+#[test]
+fn transposed_inputs() {
+    let a = numpy_example();
+    let transposed = a.transpose_axes(&[1, 0]).unwrap().materialize();
+
+    let rows = transposed.median(Some(0)).unwrap().materialize();
+    assert_eq!(values(&rows), [7.0, 2.0]);
+
+    let columns = (a.transpose().quantile(&[0.5, 1.0], Some(1)))
+        .unwrap()
+        .materialize();
+    assert_eq!(columns.shape(), [2, 3]);
+    assert_eq!(values(&columns), [6.5, 4.5, 2.5, 10.0, 7.0, 4.0]);
+}
+
+#[test]
+fn quantiles_are_validated() {
+    let a = numpy_example();
+
+    assert!(matches!(
+        a.quantile(&[0.5, 1.5], None).err().unwrap(),
+        OpError::InvalidQuantile(q) if q == 1.5
+    ));
+    assert!(matches!(
+        a.quantile(&[-0.1], Some(0)).err().unwrap(),
+        OpError::InvalidQuantile(_)
+    ));
+    assert!(matches!(
+        a.quantile(&[f64::NAN], None).err().unwrap(),
+        OpError::InvalidQuantile(_)
+    ));
+    assert!(matches!(
+        a.percentile(&[101.0], None).err().unwrap(),
+        OpError::InvalidQuantile(_)
+    ));
+    assert!(matches!(
+        a.quantile(&[], None).err().unwrap(),
+        OpError::NoQuantiles
+    ));
+    assert!(matches!(
+        a.median(Some(2)).err().unwrap(),
+        OpError::OutOfBoundAxes
+    ));
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::{Dimension, Tensor, TensorPromise};
+
+use common::{arange_from, values};
+
+fn stream(promise: &TensorPromise<f64>, chunk_elems: usize) -> Vec<f64> {
+    let mut out = Vec::new();
+
+    promise
+        .materialize_streamed(chunk_elems, |chunk, _| out.extend_from_slice(chunk))
+        .unwrap();
+
+    out
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn same_seed_same_draw() {
+    let x = arange_from(&[10, 37], 1.0);
+
+    for promise in [
+        x.dropout(0.3, 7).unwrap(),
+        x.rand_like(7),
+        (&x + &x).randn_like(7),
+    ] {
+        let whole = values(&promise.clone().materialize());
+
+        assert_eq!(values(&promise.clone().materialize()), whole);
+        for chunk_elems in [1, 3, 64, 1000] {
+            assert_eq!(stream(&promise, chunk_elems), whole);
+        }
+    }
+}
+
+#[test]
+fn different_seeds_differ() {
+    let x = arange_from(&[1000], 1.0);
+
+    for (a, b) in [
+        (x.rand_like(1), x.rand_like(2)),
+        (x.randn_like(1), x.randn_like(2)),
+        (x.dropout(0.5, 1).unwrap(), x.dropout(0.5, 2).unwrap()),
+    ] {
+        assert_ne!(values(&a.materialize()), values(&b.materialize()));
+    }
+}
+
+#[test]
+fn dropout_keeps_one_minus_p() {
+    let n = 100_000;
+    let x = Tensor::from_scalar(2.0, &[n]);
+
+    for p in [0.1, 0.5, 0.9] {
+        let out = values(&x.dropout(p, 42).unwrap().materialize());
+        let kept: Vec<f64> = out.iter().copied().filter(|&el| el != 0.0).collect();
+
+        // Survivors are scaled so the mean stays the same.
+        assert!(kept.iter().all(|&el| (el - 2.0 / (1.0 - p)).abs() < 1e-12));
+
+        // Within 5 standard deviations of the binomial.
+        let expected = n as f64 * (1.0 - p);
+        let sigma = (n as f64 * p * (1.0 - p)).sqrt();
+        assert!((kept.len() as f64 - expected).abs() < 5.0 * sigma);
+    }
+}
+
+#[test]
+fn dropout_checks_p() {
+    let x = arange_from(&[4], 1.0);
+
+    for p in [-0.1, 1.0, 1.5, f64::NAN] {
+        let err = x.dropout(p, 0).err().unwrap();
+        assert!(matches!(err.cause(), OpError::InvalidProbability(_)));
+    }
+
+    let same = x.dropout(0.0, 0).unwrap().materialize();
+    assert_eq!(values(&same), values(&x));
+}
+
+#[test]
+fn random_like_keeps_the_shape() {
+    let x = arange_from(&[3, 5], 1.0);
+    let transposed = x.transpose();
+    let drawn = transposed.rand_like(3).materialize();
+
+    assert_eq!(drawn.shape(), [5, 3]);
+    assert!(values(&drawn).iter().all(|el| (0.0..1.0).contains(el)));
+
+    let normal = values(
+        &Tensor::from_scalar(0.0, &[50_000])
+            .randn_like(3)
+            .materialize(),
+    );
+    let mean = normal.iter().sum::<f64>() / normal.len() as f64;
+    let var = normal.iter().map(|el| (el - mean).powi(2)).sum::<f64>() / normal.len() as f64;
+    assert!(mean.abs() < 0.02);
+    assert!((var - 1.0).abs() < 0.03);
+}
+
+#[test]
+fn caching_freezes_the_draw() {
+    let x = arange_from(&[64], 1.0);
+    let cached = (&x + &x).dropout(0.5, 9).unwrap().cache();
+    let first = values(&cached.clone().materialize());
+
+    // Reading the cache does not draw again, and neither does an op on top of it.
+    let before = computed_op_count();
+    assert_eq!(values(&cached.clone().materialize()), first);
+    assert_eq!(computed_op_count(), before);
+
+    let doubled = values(&(&cached + &cached).materialize());
+    assert!(doubled.iter().zip(&first).all(|(d, f)| *d == 2.0 * f));
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use simple_tensor::tensor::{NumericsMode, Tensor};
+use simple_tensor::{s, ss};
+
+use common::{bits, values};
+
+fn random_tensor(shape: &[usize], mut seed: u64) -> Tensor<f64> {
+    let len = shape.iter().product();
+    let data = (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 20.0 + 0.5
+        })
+        .collect();
+
+    Tensor::from_vec(data, shape)
+}
+
+// Distance in ulps between two finite floats of the same sign, 0 for equal infinities or NaNs.
+fn ulps(a: f64, b: f64) -> u64 {
+    if a.is_nan() && b.is_nan() || a == b {
+        return 0;
+    }
+    assert!(a.is_finite() && b.is_finite(), "{} and {}", a, b);
+    assert_eq!(
+        a.is_sign_negative(),
+        b.is_sign_negative(),
+        "{} and {}",
+        a,
+        b
+    );
+
+    a.to_bits().abs_diff(b.to_bits())
+}
+
+// Positive values from denormals to the largest floats.
+fn magnitudes() -> Vec<f64> {
+    let mut magnitudes = vec![
+        f64::from_bits(1),
+        f64::from_bits(12345),
+        1e-310,
+        f64::MIN_POSITIVE,
+        f64::MAX,
+    ];
+    for exponent in -300..=300 {
+        magnitudes.push(1.2345 * 10f64.powi(exponent));
+    }
+
+    magnitudes
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn reciprocals_are_within_an_ulp_across_magnitudes() {
+    let magnitudes = magnitudes();
+    let negated: Vec<f64> = magnitudes.iter().map(|x| -x).collect();
+    let x = Tensor::from_vec(
+        [magnitudes.clone(), negated].concat(),
+        &[2, magnitudes.len()],
+    );
+
+    let recip = x.recip().materialize();
+    for (&x, &r) in x.iter().zip(recip.iter()) {
+        assert!(ulps(r, 1.0 / x) <= 1, "recip({:e}) = {:e}", x, r);
+    }
+
+    // Both are within an ulp of the exact result.
+    let positive = Tensor::from_vec(magnitudes.clone(), &[magnitudes.len()]);
+    let rsqrt = positive.rsqrt().materialize();
+    for (&x, &r) in magnitudes.iter().zip(rsqrt.iter()) {
+        assert!(ulps(r, x.powf(-0.5)) <= 2, "rsqrt({:e}) = {:e}", x, r);
+    }
+
+    let sqrt = positive.sqrt().materialize();
+    for (&x, &r) in magnitudes.iter().zip(sqrt.iter()) {
+        assert!(ulps(r, x.sqrt()) <= 1, "sqrt({:e}) = {:e}", x, r);
+    }
+}
+
+#[test]
+fn zeros_and_negatives_follow_ieee() {
+    let x = Tensor::from_vec(vec![0.0, -0.0, -4.0, f64::INFINITY, f64::NAN], &[5]);
+
+    let recip = values(&x.recip().materialize());
+    assert_eq!(recip[..4], [f64::INFINITY, f64::NEG_INFINITY, -0.25, 0.0]);
+    assert!(recip[4].is_nan());
+
+    let rsqrt = values(&x.rsqrt().materialize());
+    assert_eq!(rsqrt[0], f64::INFINITY);
+    assert_eq!(rsqrt[1], f64::NEG_INFINITY);
+    assert!(rsqrt[2].is_nan() && rsqrt[4].is_nan());
+    assert_eq!(rsqrt[3], 0.0);
+
+    let sqrt = values(&x.sqrt().materialize());
+    assert!(sqrt[2].is_nan());
+    assert_eq!(sqrt[3], f64::INFINITY);
+}
+
+#[test]
+fn division_by_a_square_root_is_rewritten_outside_high_accuracy() {
+    let (a, b) = (random_tensor(&[4096], 1), random_tensor(&[4096], 2));
+
+    // Computed on their own, so nothing is fused.
+    let divided = bits(&(&a / b.sqrt().materialize()).materialize());
+    let multiplied = bits(&(&a * b.rsqrt().materialize()).materialize());
+    assert_ne!(divided, multiplied, "the rewrite rounds differently");
+
+    for mode in [NumericsMode::LowAccuracy, NumericsMode::EnhancedPerformance] {
+        let fast = (&a / b.sqrt()).with_numerics(mode).materialize();
+        let expected = (&a * b.rsqrt().materialize()).with_numerics(mode);
+        assert_eq!(bits(&fast), bits(&expected.materialize()), "{:?}", mode);
+    }
+
+    let strict = (&a / b.sqrt()).materialize();
+    assert_eq!(bits(&strict), divided);
+    let strict = (&a / b.sqrt()).with_numerics(NumericsMode::HighAccuracy);
+    assert_eq!(bits(&strict.materialize()), divided);
+
+    // A square root read by something else is still computed, and divided by.
+    let sqrt = b.sqrt();
+    let shared = ((&a / &sqrt) + &sqrt).with_numerics(NumericsMode::LowAccuracy);
+    let expected = (&a / b.sqrt().materialize()) + b.sqrt().materialize();
+    assert_eq!(bits(&shared.materialize()), bits(&expected.materialize()));
+
+    // Only the divisor is rewritten.
+    let numerator = (b.sqrt() / &a).with_numerics(NumericsMode::LowAccuracy);
+    let expected = b.sqrt().materialize() / &a;
+    assert_eq!(
+        bits(&numerator.materialize()),
+        bits(&expected.materialize())
+    );
+}
+
+#[test]
+fn scalars_over_tensors_are_reciprocals() {
+    let x = Tensor::from_vec(vec![1.0, 2.0, 4.0, 0.0, -8.0], &[5]);
+
+    let ones = 1.0 / &x;
+    assert!(ones.trace_back(0)[0].contains("Recip"));
+    assert_eq!(bits(&ones.materialize()), bits(&x.recip().materialize()));
+
+    let threes = (3.0 / &x).materialize();
+    assert_eq!(bits(&threes), bits(&(x.recip() * 3.0).materialize()));
+    assert_eq!(values(&threes), [3.0, 1.5, 0.75, f64::INFINITY, -0.375]);
+
+    // From promises and caches, owned or not.
+    let promise = &x + 1.0;
+    assert_eq!(
+        values(&(2.0 / &promise).materialize())[..3],
+        [1.0, 2.0 / 3.0, 0.4]
+    );
+    let cached = (&x * 2.0).cache();
+    assert_eq!(
+        values(&(1.0 / cached).materialize())[..3],
+        [0.5, 0.25, 0.125]
+    );
+    assert_eq!(values(&(-1.0 / x.clone()).materialize())[..2], [-1.0, -0.5]);
+}
+
+#[test]
+fn strided_inputs() {
+    let t = random_tensor(&[6, 8], 3);
+    let views = [
+        t.transpose().materialize(),
+        t.step_slice(ss![0..6;2, 1..8;3]).unwrap().materialize(),
+        t.slice(s![1..5, 2..6]).unwrap().materialize(),
+        t.flip_view(&[0, 1]).unwrap(),
+    ];
+
+    for view in &views {
+        let dense = view.as_contiguous().materialize();
+        assert_eq!(
+            bits(&view.recip().materialize()),
+            bits(&dense.recip().materialize())
+        );
+        assert_eq!(
+            bits(&view.rsqrt().materialize()),
+            bits(&dense.rsqrt().materialize())
+        );
+        assert_eq!(
+            bits(&view.sqrt().materialize()),
+            bits(&dense.sqrt().materialize())
+        );
+
+        let fused = (view / view.sqrt()).with_numerics(NumericsMode::LowAccuracy);
+        let expected = (&dense * dense.rsqrt().materialize()).materialize();
+        assert_eq!(bits(&fused.materialize()), bits(&expected));
+    }
+}
+
+#[cfg(feature = "static_tensor")]
+#[test]
+fn static_tensors_compute_them_eagerly() {
+    use simple_tensor::tensor::StaticTensor;
+
+    let m = StaticTensor::new([[1.0, 4.0, 0.25], [0.0, 16.0, 1e-310]]);
+    let dynamic = m.to_dynamic();
+
+    let pairs = [
+        (m.recip().to_dynamic(), dynamic.recip().materialize()),
+        (m.rsqrt().to_dynamic(), dynamic.rsqrt().materialize()),
+    ];
+    for (eager, lazy) in &pairs {
+        for (&a, &b) in eager.iter().zip(lazy.iter()) {
+            assert!(ulps(a, b) <= 1, "{:e} and {:e}", a, b);
+        }
+    }
+}
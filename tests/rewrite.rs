@@ -0,0 +1,174 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::ops::def_op::{OpKind, OpKindScalar};
+use simple_tensor::tensor::{GraphRewriter, Replacement, Tensor, TensorId, TensorPromise};
+
+use common::{arange, values};
+
+// Ids and ops of every node of the graph, from the tensors up.
+fn nodes(promise: &TensorPromise<f64>) -> Vec<(TensorId, &'static str)> {
+    let mut nodes = Vec::new();
+
+    GraphRewriter::new(|view| {
+        nodes.push((view.id(), view.op().map_or("Tensor", |op| op.as_str())));
+        None
+    })
+    .rewrite(promise)
+    .unwrap();
+
+    nodes
+}
+
+fn is_times_two(op: Option<&OpKind<f64>>) -> bool {
+    matches!(op, Some(OpKind::ScalarOp(OpKindScalar::Mul(s))) if *s == 2.0)
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn times_two_becomes_a_sum() {
+    let x = arange(&[2, 3]);
+    let y = Tensor::from_scalar(1.0, &[2, 3]);
+    let promise = &(&x * 2.0) - &y;
+
+    let rewritten = GraphRewriter::new(|view| {
+        is_times_two(view.op()).then(|| Replacement::Op(OpKind::Add, vec![0, 0]))
+    })
+    .rewrite(&promise)
+    .unwrap();
+
+    let ops: Vec<&str> = nodes(&rewritten).into_iter().map(|(_, op)| op).collect();
+    assert_eq!(ops, ["Tensor", "Add", "Tensor", "Sub"]);
+
+    assert_eq!(
+        values(&rewritten.materialize()),
+        values(&promise.materialize())
+    );
+}
+
+#[test]
+fn only_ancestors_are_rebuilt() {
+    let (x, y, z) = (arange(&[4]), arange(&[4]), Tensor::from_scalar(3.0, &[4]));
+
+    let doubled = &x * 2.0;
+    let untouched = &y + &z;
+    let promise = &(&doubled * &untouched) / &z;
+
+    let rewritten = GraphRewriter::new(|view| {
+        is_times_two(view.op()).then(|| Replacement::Op(OpKind::Add, vec![0, 0]))
+    })
+    .rewrite(&promise)
+    .unwrap();
+
+    let ids: Vec<TensorId> = nodes(&rewritten).into_iter().map(|(id, _)| id).collect();
+
+    for kept in [x.id(), y.id(), z.id(), untouched.id()] {
+        assert!(ids.contains(&kept));
+    }
+    for rebuilt in [doubled.id(), promise.id()] {
+        assert!(!ids.contains(&rebuilt));
+    }
+    assert_eq!(ids.len(), nodes(&promise).len());
+
+    assert_eq!(
+        values(&rewritten.materialize()),
+        values(&promise.materialize())
+    );
+}
+
+#[test]
+fn replacements_that_do_not_fit_fail() {
+    let x = arange(&[2, 3]);
+    // Not followed by a scalar op, which would be fused with it.
+    let promise = &(&x * 2.0) + &x;
+
+    let rewrite = |replacement: fn() -> Replacement<f64>| {
+        GraphRewriter::new(|view| is_times_two(view.op()).then(replacement)).rewrite(&promise)
+    };
+
+    // Outer needs 1-D inputs.
+    let err = rewrite(|| Replacement::Op(OpKind::Outer, vec![0, 0]))
+        .err()
+        .unwrap();
+    assert!(matches!(err, OpError::NotEnoughAxes(1, 2)));
+
+    let err = rewrite(|| Replacement::Op(OpKind::Add, vec![0, 1]))
+        .err()
+        .unwrap();
+    assert!(matches!(err, OpError::IndexOutOfBounds(1, 1)));
+
+    let err = rewrite(|| Replacement::Node((&Tensor::from_scalar(1.0, &[3, 2])).into()))
+        .err()
+        .unwrap();
+    assert!(matches!(err, OpError::NotSameShape(_, _)));
+}
+
+#[test]
+fn caches_are_protected() {
+    let (x, y) = (arange(&[3]), arange(&[3]));
+    let cached = (&x * 3.0).cache();
+    let promise = &cached + &y;
+
+    // Reads what the cache reads, without caching it.
+    let uncache = |allow: bool| {
+        GraphRewriter::new(|view| {
+            (view.is_cache()).then(|| Replacement::Node(view.inputs()[0].node()))
+        })
+        .replace_caches(allow)
+        .rewrite(&promise)
+    };
+
+    let err = uncache(false).err().unwrap();
+    assert!(matches!(err, OpError::ProtectedCache(_)));
+
+    let rewritten = uncache(true).unwrap();
+    assert!(nodes(&rewritten).iter().all(|(id, _)| *id != cached.id()));
+    assert_eq!(values(&rewritten.materialize()), [0.0, 4.0, 8.0]);
+    assert!(!cached.is_cached());
+}
+
+#[test]
+fn caches_above_a_replacement_are_rebuilt() {
+    let x = arange(&[3]);
+    let cached = (&x * 2.0).cache();
+    let promise = &cached - 1.0;
+    cached.clone().materialize();
+
+    let rewritten = GraphRewriter::new(|view| {
+        is_times_two(view.op()).then(|| Replacement::Op(OpKind::Add, vec![0, 0]))
+    })
+    .rewrite(&promise)
+    .unwrap();
+
+    let ops: Vec<&str> = nodes(&rewritten).into_iter().map(|(_, op)| op).collect();
+    assert_eq!(ops, ["Tensor", "Add", "NoOp", "ScalarOp"]);
+    assert_eq!(values(&rewritten.materialize()), [-1.0, 1.0, 3.0]);
+}
+
+#[test]
+fn identity_rewrites_share_every_node() {
+    let (x, y) = (arange(&[2, 2]), arange(&[2, 2]));
+    let shared = &x * &y;
+    let promise = &(&shared + &x) * &(&shared - 2.0).cache();
+
+    let mut calls = 0;
+    let same = GraphRewriter::new(|_| {
+        calls += 1;
+        None
+    })
+    .rewrite(&promise)
+    .unwrap();
+
+    // Shared nodes are only shown once.
+    assert_eq!(calls, nodes(&promise).len());
+    assert_eq!(same.id(), promise.id());
+
+    // Splicing every node in its own place changes nothing either.
+    let same = GraphRewriter::new(|view| Some(Replacement::Node(view.node())))
+        .replace_caches(true)
+        .rewrite(&promise)
+        .unwrap();
+    assert_eq!(same.id(), promise.id());
+    assert_eq!(nodes(&same), nodes(&promise));
+}
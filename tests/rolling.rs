@@ -0,0 +1,228 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, s, vector};
+
+use common::values;
+
+// Small deterministic generator, so the tests do not need a rand dependency.
+fn random_values(len: usize, mut seed: u64) -> Vec<f64> {
+    (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 200.0 - 100.0
+        })
+        .collect()
+}
+
+// Reduces every window of a row-major [rows, cols] buffer along the given axis,
+// one window at a time.
+fn naive_rolling(
+    data: &[f64],
+    shape: [usize; 2],
+    window: usize,
+    axis: usize,
+    reduce: fn(&[f64]) -> f64,
+) -> Vec<f64> {
+    let cols = shape[1];
+    let mut out_shape = shape;
+    out_shape[axis] -= window - 1;
+
+    let mut out = Vec::new();
+    for i in 0..out_shape[0] {
+        for j in 0..out_shape[1] {
+            let lane: Vec<f64> = (0..window)
+                .map(|k| match axis {
+                    0 => data[(i + k) * cols + j],
+                    _ => data[i * cols + j + k],
+                })
+                .collect();
+            out.push(reduce(&lane));
+        }
+    }
+
+    out
+}
+
+fn sum(lane: &[f64]) -> f64 {
+    lane.iter().sum()
+}
+
+fn mean(lane: &[f64]) -> f64 {
+    sum(lane) / lane.len() as f64
+}
+
+fn max(lane: &[f64]) -> f64 {
+    lane.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+}
+
+fn assert_close(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert!((g - e).abs() <= 1e-9 * e.abs().max(1.0), "{} != {}", g, e);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn hand_computed() {
+    let t = vector![1, 3, 2, 5, 4];
+
+    let s = t.rolling_sum(2, 0).unwrap().materialize();
+    assert_eq!(s.shape(), &[4]);
+    assert_eq!(values(&s), [4.0, 5.0, 7.0, 9.0]);
+
+    let m = t.rolling_mean(3, 0).unwrap().materialize();
+    assert_eq!(values(&m), [2.0, 10.0 / 3.0, 11.0 / 3.0]);
+
+    let m = t.rolling_max(3, 0).unwrap().materialize();
+    assert_eq!(values(&m), [3.0, 5.0, 5.0]);
+}
+
+#[test]
+fn along_each_axis() {
+    let t = matrix![[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+
+    let rows = t.rolling_sum(2, 0).unwrap().materialize();
+    assert_eq!(rows.shape(), &[2, 3]);
+    assert_eq!(values(&rows), [5.0, 7.0, 9.0, 11.0, 13.0, 15.0]);
+
+    let cols = t.rolling_max(2, 1).unwrap().materialize();
+    assert_eq!(cols.shape(), &[3, 2]);
+    assert_eq!(values(&cols), [2.0, 3.0, 5.0, 6.0, 8.0, 9.0]);
+}
+
+#[test]
+fn window_edge_cases() {
+    let t = matrix![[1, 2, 3], [4, 5, 6]];
+
+    // A window of 1 is the identity.
+    for result in [
+        t.rolling_sum(1, 1).unwrap(),
+        t.rolling_mean(1, 1).unwrap(),
+        t.rolling_max(1, 0).unwrap(),
+    ] {
+        let result = result.materialize();
+        assert_eq!(result.shape(), &[2, 3]);
+        assert_eq!(values(&result), values(&t));
+    }
+
+    // A window as long as the axis reduces it to a single element.
+    let full = t.rolling_sum(3, 1).unwrap().materialize();
+    assert_eq!(full.shape(), &[2, 1]);
+    assert_eq!(values(&full), [6.0, 15.0]);
+
+    assert!(matches!(
+        t.rolling_sum(3, 0),
+        Err(OpError::InvalidWindow(3, 2))
+    ));
+    assert!(matches!(
+        t.rolling_max(0, 1),
+        Err(OpError::InvalidWindow(0, 3))
+    ));
+    assert!(matches!(t.rolling_mean(2, 2), Err(OpError::OutOfBoundAxes)));
+}
+
+#[test]
+fn nan_poisons_its_windows() {
+    let t = vector![1.0, f64::NAN, 3.0, 4.0, 5.0];
+
+    for result in [
+        t.rolling_sum(2, 0).unwrap(),
+        t.rolling_mean(2, 0).unwrap(),
+        t.rolling_max(2, 0).unwrap(),
+    ] {
+        let result = values(&result.materialize());
+
+        assert!(result[0].is_nan());
+        assert!(result[1].is_nan());
+        assert!(!result[2].is_nan());
+        assert!(!result[3].is_nan());
+    }
+}
+
+#[test]
+fn infinity_leaving_the_window() {
+    let t = vector![f64::INFINITY, 1.0, 2.0, f64::NEG_INFINITY];
+
+    let s = values(&t.rolling_sum(2, 0).unwrap().materialize());
+    assert_eq!(s, [f64::INFINITY, 3.0, f64::NEG_INFINITY]);
+
+    let m = values(&t.rolling_max(2, 0).unwrap().materialize());
+    assert_eq!(m, [f64::INFINITY, 2.0, 2.0]);
+}
+
+#[test]
+fn matches_naive_windows_on_random_data() {
+    let shape = [7, 41];
+    let data = random_values(shape[0] * shape[1], 42);
+    let t = Tensor::from_vec(data.clone(), &shape);
+
+    for axis in 0..2 {
+        for window in [1, 2, 5, shape[axis]] {
+            let got = t.rolling_sum(window, axis).unwrap().materialize();
+            assert_close(
+                &values(&got),
+                &naive_rolling(&data, shape, window, axis, sum),
+            );
+
+            let got = t.rolling_mean(window, axis).unwrap().materialize();
+            assert_close(
+                &values(&got),
+                &naive_rolling(&data, shape, window, axis, mean),
+            );
+
+            let got = t.rolling_max(window, axis).unwrap().materialize();
+            assert_eq!(values(&got), naive_rolling(&data, shape, window, axis, max));
+        }
+    }
+}
+
+#[test]
+fn max_on_monotonic_input() {
+    let len = 1000;
+    let window = 37;
+
+    // Decreasing input keeps every element in the deque, increasing input evicts all of them.
+    for data in [
+        (0..len).rev().map(|i| i as f64).collect::<Vec<_>>(),
+        (0..len).map(|i| i as f64).collect::<Vec<_>>(),
+    ] {
+        let t = Tensor::from_vec(data.clone(), &[1, len]);
+        let got = t.rolling_max(window, 1).unwrap().materialize();
+
+        assert_eq!(values(&got), naive_rolling(&data, [1, len], window, 1, max));
+    }
+}
+
+#[test]
+fn strided_and_transposed_inputs() {
+    let shape = [6, 5];
+    let data = random_values(shape[0] * shape[1], 7);
+    let t = Tensor::from_vec(data.clone(), &shape);
+
+    // Rolling over the columns of the transpose is rolling over the rows of the original.
+    let transposed = t.transpose().materialize();
+    let got = transposed.rolling_max(3, 1).unwrap().materialize();
+    let expected = t.rolling_max(3, 0).unwrap().transpose().materialize();
+    assert_eq!(got.shape(), &[5, 4]);
+    assert_eq!(values(&got), values(&expected));
+
+    let got = transposed.rolling_sum(2, 0).unwrap().materialize();
+    let expected = t.rolling_sum(2, 1).unwrap().transpose().materialize();
+    assert_close(&values(&got), &values(&expected));
+
+    // A slice keeps the offset and the row stride of the original buffer.
+    let sliced = t.slice(s![1..5, 1..4]).unwrap().materialize();
+    let sliced_data = values(&sliced);
+    let got = sliced.rolling_mean(2, 0).unwrap().materialize();
+    assert_close(
+        &values(&got),
+        &naive_rolling(&sliced_data, [4, 3], 2, 0, mean),
+    );
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use simple_tensor::tensor::graph::NodeKind;
+use simple_tensor::tensor::ops::computed_op_count;
+use simple_tensor::tensor::ops::def_op::OpKindScalar::{Div, Mul, Sum};
+use simple_tensor::tensor::ops::def_op::{OpKind, OpKindScalar};
+use simple_tensor::tensor::{
+    DEFAULT_MAX_FUSED_SCALAR_OPS, GraphRewriter, Tensor, TensorPromise, max_fused_scalar_ops,
+    set_max_fused_scalar_ops,
+};
+
+use common::{arange_from, lock_globals};
+
+// Ops of every node of the graph, from the tensors up.
+fn ops(promise: &TensorPromise<f64>) -> Vec<OpKind<f64>> {
+    let mut ops = Vec::new();
+
+    GraphRewriter::new(|view| {
+        ops.extend(view.op().cloned());
+        None
+    })
+    .rewrite(promise)
+    .unwrap();
+
+    ops
+}
+
+fn affine(promise: &TensorPromise<f64>) -> (f64, f64) {
+    match &ops(promise)[..] {
+        [OpKind::ScalarOp(OpKindScalar::Affine(scale, shift))] => (*scale, *shift),
+        ops => panic!("not a single affine op: {:?}", ops),
+    }
+}
+
+// Integers have no scalar operators, as they cannot be computed, but their graphs can be built.
+fn int_scalar_op(input: impl Into<NodeKind<i64>>, op: OpKindScalar<i64>) -> TensorPromise<i64> {
+    TensorPromise::new(OpKind::ScalarOp(op), Box::new([input.into()])).unwrap()
+}
+
+fn assert_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+    assert_eq!(actual.len(), expected.len());
+
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(
+            (a - e).abs() <= tolerance * e.abs().max(1.0),
+            "{} is not {}",
+            a,
+            e
+        );
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn long_append_loops_collapse_into_a_single_op() {
+    let t = arange_from(&[10], -5.0);
+
+    let mut promise = &t * 1.0;
+    let mut expected: Vec<f64> = t.iter().copied().collect();
+
+    for i in 0..1000 {
+        let shift = 0.1 * (i % 3) as f64;
+        promise = (promise * 0.999) + shift;
+        expected.iter_mut().for_each(|x| *x = *x * 0.999 + shift);
+    }
+
+    affine(&promise);
+
+    let before = computed_op_count();
+    let result = promise.materialize();
+    assert_eq!(computed_op_count() - before, 1);
+
+    // The scale and shift are products of a thousand roundings.
+    let values: Vec<f64> = result.iter().copied().collect();
+    assert_close(&values, &expected, 1e-10);
+}
+
+#[test]
+fn mixed_ops_collapse_whatever_their_order() {
+    let t = arange_from(&[6], -5.0);
+
+    assert_eq!(affine(&((&t * 2.0) + 1.0)), (2.0, 1.0));
+    assert_eq!(affine(&((&t + 0.5) * 2.0)), (2.0, 1.0));
+    assert_eq!(affine(&(((&t - 1.0) / 4.0) + 3.0)), (0.25, 2.75));
+    assert_eq!(affine(&(((&t * 4.0) + 3.0) * 0.5)), (2.0, 1.5));
+
+    // A scale back to 1 is a sum, a shift back to 0 a mul.
+    assert!(matches!(
+        &ops(&((((&t * 2.0) + 1.0) / 2.0) - 0.5))[..],
+        [OpKind::ScalarOp(OpKindScalar::Sum(s))] if *s == 0.0
+    ));
+    assert!(matches!(
+        &ops(&(((&t + 1.0) * 3.0) - 3.0))[..],
+        [OpKind::ScalarOp(OpKindScalar::Mul(s))] if *s == 3.0
+    ));
+
+    let result = ((((&t - 1.0) / 4.0) + 3.0) * 2.0).materialize();
+    let expected: Vec<f64> = t.iter().map(|x| ((x - 1.0) / 4.0 + 3.0) * 2.0).collect();
+    assert_close(
+        &result.iter().copied().collect::<Vec<_>>(),
+        &expected,
+        1e-15,
+    );
+}
+
+#[test]
+fn inexact_integer_divisions_are_not_collapsed() {
+    let t = Tensor::from_iter(0..6i64, &[6]);
+    let chain = |ops: &[OpKindScalar<i64>]| {
+        (ops.iter()).fold(t.as_promise(), |acc, op| int_scalar_op(&acc, op.clone()))
+    };
+
+    // 3 / 2 is 1 for integers, but x * 3 / 2 is not x.
+    let summary = chain(&[Mul(3), Div(2)]).graph_summary();
+    assert!(
+        summary.contains("FusedScalar([Mul(3), Div(2)])"),
+        "{}",
+        summary
+    );
+
+    let summary = chain(&[Div(2), Mul(4)]).graph_summary();
+    assert!(
+        summary.contains("FusedScalar([Div(2), Mul(4)])"),
+        "{}",
+        summary
+    );
+
+    // Exact divisions still collapse.
+    let summary = chain(&[Mul(4), Sum(2), Div(2)]).graph_summary();
+    assert!(summary.contains("ScalarOp(Affine(2, 1))"), "{}", summary);
+
+    let summary = chain(&[Div(2), Div(3)]).graph_summary();
+    assert!(summary.contains("ScalarOp(Div(6))"), "{}", summary);
+}
+
+#[test]
+fn lists_longer_than_the_limit_are_split() {
+    let _globals = lock_globals();
+    let t = Tensor::from_iter(0..6i64, &[6]);
+
+    set_max_fused_scalar_ops(3);
+    let mut promise = int_scalar_op(&t, Sum(1));
+    for _ in 0..4 {
+        promise = int_scalar_op(&int_scalar_op(&promise, Div(2)), Sum(1));
+    }
+    set_max_fused_scalar_ops(DEFAULT_MAX_FUSED_SCALAR_OPS);
+
+    // Sum, then Div and Sum four times, three at most per node.
+    let summary = promise.graph_summary();
+    let nodes: Vec<&str> = (summary.lines())
+        .filter(|line| line.starts_with('#') && !line.contains("Tensor"))
+        .collect();
+    assert_eq!(nodes.len(), 3, "{}", summary);
+    assert!(
+        nodes.iter().all(|line| line.contains("FusedScalar")),
+        "{}",
+        summary
+    );
+
+    set_max_fused_scalar_ops(0);
+    assert_eq!(max_fused_scalar_ops(), 1);
+    set_max_fused_scalar_ops(DEFAULT_MAX_FUSED_SCALAR_OPS);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use simple_tensor::tensor::{Schedule, Tensor, TensorPromise, materialize_many};
+
+use common::values;
+
+fn random_tensor(shape: &[usize], mut seed: u64) -> Tensor<f64> {
+    let len = shape.iter().product();
+    let data = (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 20.0 - 10.0
+        })
+        .collect();
+
+    Tensor::from_vec(data, shape)
+}
+
+fn assert_bitwise_eq(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert_eq!(g.to_bits(), e.to_bits(), "{} != {}", g, e);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+const N: usize = 1000;
+// Bytes of every intermediate result.
+const U: usize = N * 8;
+
+#[test]
+fn computes_the_hungriest_input_first() {
+    let a = random_tensor(&[N], 1);
+    let b = random_tensor(&[N], 2);
+    let c = random_tensor(&[N], 3);
+
+    // The left input only needs its result, the right one needs three alive at once.
+    let left = &a + 1.0;
+    let right = &(&b * &c) + &(&b - &c);
+    let root = &left + &right;
+
+    let default = root.graph_stats(Schedule::Topological);
+    let scheduled = root.graph_stats(Schedule::MinMemory);
+
+    // Topological: left, b * c, b - c and right are alive at once.
+    assert_eq!(default.peak_bytes(), 4 * U);
+    // MinMemory: right first, then left and the output.
+    assert_eq!(scheduled.peak_bytes(), 3 * U);
+    assert_eq!(default.nodes(), scheduled.nodes());
+
+    let expected = values(&root.clone().materialize());
+    assert_bitwise_eq(&values(&root.materialize_min_memory()), &expected);
+}
+
+#[test]
+fn shared_subexpressions() {
+    let a = random_tensor(&[4, 5], 4);
+    let b = random_tensor(&[4, 5], 5);
+    let c = random_tensor(&[4, 5], 6);
+
+    let shared = &a * &b;
+    let root = &(&shared + 1.0) + &(&(&shared * &c) - &(&c / 2.0));
+
+    let expected = values(&root.clone().materialize());
+    assert_bitwise_eq(&values(&root.clone().materialize_min_memory()), &expected);
+
+    // The shared node keeps being read after the root is computed.
+    let other = &shared - &a;
+    let [root_many, other_many] = materialize_many(&[&root, &other]).try_into().unwrap();
+    assert_bitwise_eq(&values(&root_many), &expected);
+    assert_bitwise_eq(
+        &values(&other_many),
+        &values(&other.clone().materialize_min_memory()),
+    );
+
+    // Stats count every node once, whatever the order.
+    assert_eq!(
+        root.graph_stats(Schedule::Topological).nodes(),
+        root.graph_stats(Schedule::MinMemory).nodes()
+    );
+}
+
+#[test]
+fn cache_nodes() {
+    let a = random_tensor(&[N], 7);
+    let b = random_tensor(&[N], 8);
+
+    let cached = (&a * &b).cache();
+    let root = &(&a - 3.0) + &(&(&cached * 2.0) + &(&cached / &b));
+
+    let unfilled = root.graph_stats(Schedule::MinMemory);
+    let result = values(&root.clone().materialize_min_memory());
+
+    // Filled by the scheduled run, and read in place from then on.
+    assert!(cached.is_cached());
+    let filled = root.graph_stats(Schedule::MinMemory);
+    assert!(filled.nodes() < unfilled.nodes());
+    assert!(filled.peak_bytes() <= unfilled.peak_bytes());
+
+    assert_bitwise_eq(&values(&root.clone().materialize()), &result);
+    assert_bitwise_eq(&values(&root.materialize_min_memory()), &result);
+
+    // A cache can be the root too.
+    let root = (&a + &b).cache();
+    let expected = values(&(&a + &b).materialize());
+    assert_bitwise_eq(&values(&root.clone().materialize_min_memory()), &expected);
+    assert!(root.is_cached());
+    assert_eq!(root.graph_stats(Schedule::Topological).peak_bytes(), 0);
+}
+
+#[test]
+fn memory_heavy_comb() {
+    const LEN: usize = 100_000;
+    const DEPTH: usize = 12;
+
+    let leaves: Vec<Tensor<f64>> = (0..=DEPTH as u64)
+        .map(|seed| random_tensor(&[LEN], 10 + seed))
+        .collect();
+
+    // Every level adds a fresh result on the left of the deeper levels, which the
+    // default order keeps alive all the way down.
+    let mut acc: TensorPromise<f64> = &leaves[0] + 1.0;
+    for leaf in &leaves[1..] {
+        acc = &(leaf * 2.0) + &acc;
+    }
+
+    let bytes = LEN * 8;
+    let default = acc.graph_stats(Schedule::Topological);
+    let scheduled = acc.graph_stats(Schedule::MinMemory);
+    println!(
+        "simulated peak: {} MB by default, {} MB scheduled",
+        default.peak_bytes() / (1 << 20),
+        scheduled.peak_bytes() / (1 << 20)
+    );
+
+    assert!(default.peak_bytes() >= DEPTH * bytes);
+    assert!(scheduled.peak_bytes() <= 3 * bytes);
+
+    let expected = values(&acc.clone().materialize());
+    assert_bitwise_eq(&values(&acc.materialize_min_memory()), &expected);
+}
@@ -0,0 +1,185 @@
+#![cfg(feature = "serde")]
+
+use std::alloc::{GlobalAlloc, Layout as AllocLayout, System};
+use std::cell::Cell;
+use std::fmt::Debug;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use simple_tensor::tensor::{Dimension, Layout, TENSOR_FORMAT_VERSION, Tensor, TensorData};
+
+// Counts the bytes allocated by the current thread, as the tests run in parallel.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATED.with(|count| count.set(count.get() + layout.size()));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bytes_allocated_in(f: impl FnOnce()) -> usize {
+    let before = ALLOCATED.with(Cell::get);
+    f();
+    ALLOCATED.with(Cell::get) - before
+}
+
+// A contiguous 2x3 matrix, its transpose, and its last two columns, which start at an offset.
+fn views<T: Copy>(values: [T; 6]) -> Vec<(TensorData<T>, Vec<T>)> {
+    let v = values;
+    let x = TensorData::from_vec(v.to_vec(), &[2, 3], 0);
+
+    vec![
+        (x.clone(), v.to_vec()),
+        (
+            x.as_layout(Layout::from_slice(&[3, 2], &[1, 3], 0)),
+            vec![v[0], v[3], v[1], v[4], v[2], v[5]],
+        ),
+        (
+            x.as_layout(Layout::from_slice(&[2, 2], &[3, 1], 1)),
+            vec![v[1], v[2], v[4], v[5]],
+        ),
+    ]
+}
+
+fn check_round_trips<T>(values: [T; 6])
+where
+    T: Copy + PartialEq + Debug + Serialize + DeserializeOwned,
+{
+    for (view, expected) in views(values) {
+        let bytes = bincode::serialize(&view).unwrap();
+        let from_bincode: TensorData<T> = bincode::deserialize(&bytes).unwrap();
+
+        let json = serde_json::to_string(&view).unwrap();
+        let from_json: TensorData<T> = serde_json::from_str(&json).unwrap();
+
+        for read in [from_bincode, from_json] {
+            assert_eq!(read.shape(), view.shape());
+            assert!(read.layout().is_contiguous());
+            assert_eq!(read.offset(), 0);
+            assert_eq!(read.copied_iter().collect::<Vec<_>>(), expected);
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn views_round_trip_dense() {
+    check_round_trips([0.5f64, -1.0, 2.25, f64::MAX, 1e-300, -0.0]);
+    check_round_trips([0.5f32, -1.0, 2.25, f32::MAX, 1e-30, 3.0]);
+    check_round_trips([1i64, -2, 3, i64::MAX, i64::MIN, 0]);
+
+    let view = &views([1.0, 2.0, 3.0, 4.0, 5.0, 6.0])[1].0;
+    let json = serde_json::to_string(view).unwrap();
+    assert_eq!(
+        json,
+        format!(
+            r#"{{"format":{},"shape":[3,2],"device":"cpu","data":[1.0,4.0,2.0,5.0,3.0,6.0]}}"#,
+            TENSOR_FORMAT_VERSION
+        )
+    );
+}
+
+#[test]
+fn tensors_and_layouts_round_trip() {
+    let x = Tensor::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[3, 2]);
+    let transposed = x.transpose().materialize();
+
+    let bytes = bincode::serialize(&transposed).unwrap();
+    let read: Tensor<f64> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(read.shape(), [2, 3]);
+    assert!(read.data() == transposed.data());
+
+    let layout = Layout::from_slice(&[2, 2], &[3, 1], 1);
+    let json = serde_json::to_string(&layout).unwrap();
+    assert_eq!(json, r#"{"shape":[2,2],"stride":[3,1],"offset":1}"#);
+
+    let read: Layout = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.shape(), layout.shape());
+    assert_eq!(read.stride(), layout.stride());
+    assert_eq!(read.offset(), layout.offset());
+
+    let err = serde_json::from_str::<Layout>(r#"{"shape":[2,2],"stride":[1],"offset":0}"#);
+    assert!(err.is_err());
+}
+
+#[test]
+fn large_views_are_written_without_copies() {
+    let len = 1 << 20;
+    let x = TensorData::from_iter((0..len).map(|i| i as f64), &[1024, 1024]);
+    let transposed = x.as_layout(Layout::from_slice(&[1024, 1024], &[1, 1024], 0));
+
+    // A tenth of the data, far below the 8 MiB a dense copy would take.
+    let budget = len * 8 / 10;
+
+    let allocated = bytes_allocated_in(|| {
+        bincode::serialize_into(std::io::sink(), &transposed).unwrap();
+    });
+    assert!(allocated < budget, "{} bytes allocated", allocated);
+
+    let allocated = bytes_allocated_in(|| {
+        serde_json::to_writer(std::io::sink(), &transposed).unwrap();
+    });
+    assert!(allocated < budget, "{} bytes allocated", allocated);
+
+    let bytes = bincode::serialize(&transposed).unwrap();
+    let read: TensorData<f64> = bincode::deserialize(&bytes).unwrap();
+    assert!(read.copied_iter().eq(transposed.copied_iter()));
+}
+
+#[test]
+fn tagged_format_is_checked() {
+    // Unknown fields are skipped, whatever the order of the known ones.
+    let read: TensorData<f64> =
+        serde_json::from_str(r#"{"data":[1.0,2.0],"dtype":"f64","shape":[2,1],"format":1}"#)
+            .unwrap();
+    assert_eq!(read.shape(), [2, 1]);
+    assert_eq!(read.copied_iter().collect::<Vec<_>>(), [1.0, 2.0]);
+
+    let newer = format!(
+        r#"{{"format":{},"shape":[2],"data":[1.0,2.0]}}"#,
+        TENSOR_FORMAT_VERSION + 1
+    );
+    let err = serde_json::from_str::<TensorData<f64>>(&newer)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("newer"), "{}", err);
+
+    // Format 1 had no device, the tensors written with it are read as placed on the cpu.
+    let v1 = bincode::serialize(&(1u32, vec![2usize, 1], vec![1.0f64, 2.0])).unwrap();
+    let read: TensorData<f64> = bincode::deserialize(&v1).unwrap();
+    assert_eq!(read.shape(), [2, 1]);
+    assert_eq!(read.copied_iter().collect::<Vec<_>>(), [1.0, 2.0]);
+
+    let too_short = r#"{"format":1,"shape":[2,2],"data":[1.0,2.0]}"#;
+    assert!(serde_json::from_str::<TensorData<f64>>(too_short).is_err());
+
+    let untagged = r#"{"shape":[2],"data":[1.0,2.0]}"#;
+    assert!(serde_json::from_str::<TensorData<f64>>(untagged).is_err());
+}
+
+#[test]
+fn shared_buffers_are_read_back_independent() {
+    let a = Tensor::from_vec(vec![1.0, 2.0, 3.0, 4.0], &[2, 2]);
+    let b = a.clone_detached();
+    assert!(a.is_shared() && b.is_shared());
+
+    let bytes = bincode::serialize(&(&a, &b)).unwrap();
+    let (mut a, b): (Tensor<f64>, Tensor<f64>) = bincode::deserialize(&bytes).unwrap();
+    assert!(!a.is_shared() && !b.is_shared());
+
+    a.assign_from_iter([0.0; 4]).unwrap();
+    assert_eq!(b.iter().copied().collect::<Vec<_>>(), [1.0, 2.0, 3.0, 4.0]);
+}
@@ -0,0 +1,207 @@
+use simple_tensor::tensor::shape_check::ShapePattern;
+use simple_tensor::tensor::{Tensor, TensorData};
+use simple_tensor::{expect_shape, named_tensor};
+
+fn zeros(shape: &[usize]) -> Tensor<f64> {
+    Tensor::from_scalar(0.0, shape)
+}
+
+#[test]
+fn wildcards_literals_and_bindings() {
+    let t = zeros(&[4, 128, 8]);
+
+    let bindings = ShapePattern::new()
+        .any()
+        .exact(128)
+        .bind("h")
+        .matches(&t)
+        .unwrap();
+    assert_eq!(bindings["h"], 8);
+    assert_eq!(bindings.get("batch"), None);
+
+    let bindings = ShapePattern::new()
+        .bind("b")
+        .any()
+        .bind("h")
+        .matches(&t)
+        .unwrap();
+    let bound: Vec<(&str, usize)> = bindings.iter().collect();
+    assert_eq!(bound, [("b", 4), ("h", 8)]);
+
+    assert!(ShapePattern::new().any().any().any().matches(&t).is_ok());
+    assert!(
+        ShapePattern::new()
+            .exact(4)
+            .exact(128)
+            .exact(8)
+            .matches(&t)
+            .is_ok()
+    );
+    assert!(
+        ShapePattern::new()
+            .exact(4)
+            .exact(127)
+            .any()
+            .matches(&t)
+            .is_err()
+    );
+}
+
+#[test]
+fn repeated_names_must_agree() {
+    let square = zeros(&[3, 3]);
+    let bindings = ShapePattern::new()
+        .bind("n")
+        .bind("n")
+        .matches(&square)
+        .unwrap();
+    assert_eq!(bindings["n"], 3);
+
+    let err = ShapePattern::new()
+        .bind("n")
+        .bind("n")
+        .matches(&zeros(&[3, 5]))
+        .unwrap_err();
+    assert_eq!(err.axis(), Some(1));
+    assert_eq!(
+        err.to_string(),
+        "shape [3, 5] does not match [n, n]\n          ^ expected n = 3 at axis 1"
+    );
+}
+
+#[test]
+fn rank_mismatch() {
+    let t = zeros(&[4, 128]);
+
+    let err = ShapePattern::new()
+        .any()
+        .exact(128)
+        .bind("h")
+        .matches(&t)
+        .unwrap_err();
+    assert_eq!(err.axis(), None);
+    assert_eq!(err.shape(), &[4, 128]);
+    assert_eq!(
+        err.to_string(),
+        "shape [4, 128] does not match [_, 128, h]\nexpected 3 axes, found 2"
+    );
+
+    let err = ShapePattern::new()
+        .rest()
+        .any()
+        .any()
+        .any()
+        .matches(&t)
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .ends_with("expected at least 3 axes, found 2")
+    );
+}
+
+#[test]
+fn caret_under_the_first_mismatch() {
+    let t = zeros(&[16, 1024, 3, 7]);
+
+    let err = ShapePattern::new()
+        .any()
+        .exact(512)
+        .exact(4)
+        .any()
+        .matches(&t)
+        .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "shape [16, 1024, 3, 7] does not match [_, 512, 4, _]\n\
+         \x20          ^^^^ expected 512 at axis 1"
+    );
+
+    let err = ShapePattern::new().rest().exact(3).matches(&t).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "shape [16, 1024, 3, 7] does not match [.., 3]\n\
+         \x20                   ^ expected 3 at axis 3"
+    );
+}
+
+#[test]
+fn trailing_and_leading_rest() {
+    let t = zeros(&[2, 5, 6, 3]);
+
+    let bindings = ShapePattern::new()
+        .rest()
+        .bind("w")
+        .exact(3)
+        .matches(&t)
+        .unwrap();
+    assert_eq!(bindings["w"], 6);
+
+    let bindings = ShapePattern::new()
+        .bind("b")
+        .rest()
+        .bind("c")
+        .matches(&t)
+        .unwrap();
+    assert_eq!((bindings["b"], bindings["c"]), (2, 3));
+
+    // The rest may be empty.
+    let bindings = ShapePattern::new()
+        .bind("n")
+        .rest()
+        .matches(&zeros(&[7]))
+        .unwrap();
+    assert_eq!(bindings["n"], 7);
+}
+
+#[test]
+fn macro_binds_locals() {
+    let t = zeros(&[4, 128, 8]);
+
+    expect_shape!(t, [_, 128, h]);
+    expect_shape!(t, [batch, ..]);
+    expect_shape!(t, [.., 8]);
+
+    // The bindings are plain locals.
+    let heads: usize = h;
+    assert_eq!(heads * batch, 32);
+
+    let square = zeros(&[5, 5]);
+    expect_shape!(square, [n, n,]);
+    assert_eq!(n, 5);
+}
+
+#[test]
+#[should_panic(expected = "expected 128 at axis 1")]
+fn macro_panics_on_mismatch() {
+    let t = zeros(&[4, 64, 8]);
+
+    expect_shape!(t, [_, 128, _]);
+}
+
+#[test]
+fn every_dimension_type() {
+    let pattern = ShapePattern::new().bind("rows").exact(3);
+    let t = zeros(&[2, 3]);
+
+    let promise = &t + 1.0;
+    let cached = (&t * 2.0).cache();
+    let data: TensorData<f64> = t.data().clone();
+    let named = named_tensor!(t.clone(), ["batch", "feature"]).unwrap();
+
+    assert_eq!(pattern.matches(&t).unwrap()["rows"], 2);
+    assert_eq!(pattern.matches(&promise).unwrap()["rows"], 2);
+    assert_eq!(pattern.matches(&cached).unwrap()["rows"], 2);
+    assert_eq!(pattern.matches(&data).unwrap()["rows"], 2);
+    assert_eq!(pattern.matches(&named).unwrap()["rows"], 2);
+    assert_eq!(pattern.matches_shape(&[9, 3]).unwrap()["rows"], 9);
+
+    expect_shape!(promise, [_, cols]);
+    assert_eq!(cols, 3);
+}
+
+#[test]
+#[should_panic(expected = "only have one `..`")]
+fn a_single_rest() {
+    let _ = ShapePattern::new().rest().any().rest();
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, ones, vector};
+
+use common::values;
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn tile_matches_ones() {
+    let tiled = Tensor::from_scalar(1.0, &[3]).tile(&[4]).materialize();
+    let expected = ones!(&[12]);
+
+    assert_eq!(tiled.shape(), expected.shape());
+    assert_eq!(values(&tiled), values(&expected));
+}
+
+#[test]
+fn tile_pads_shape_and_repeats() {
+    let t = matrix![[1, 2], [3, 4]];
+
+    let tiled = t.tile(&[2, 3]).materialize();
+    assert_eq!(tiled.shape(), &[4, 6]);
+    assert_eq!(
+        values(&tiled),
+        [
+            1.0, 2.0, 1.0, 2.0, 1.0, 2.0, //
+            3.0, 4.0, 3.0, 4.0, 3.0, 4.0, //
+            1.0, 2.0, 1.0, 2.0, 1.0, 2.0, //
+            3.0, 4.0, 3.0, 4.0, 3.0, 4.0,
+        ]
+    );
+
+    // Fewer repeats than axes tiles the trailing axes.
+    let tiled = t.tile(&[2]).materialize();
+    assert_eq!(tiled.shape(), &[2, 4]);
+    assert_eq!(values(&tiled), [1.0, 2.0, 1.0, 2.0, 3.0, 4.0, 3.0, 4.0]);
+
+    // More repeats than axes adds leading axes.
+    let tiled = vector![1, 2].tile(&[2, 1]).materialize();
+    assert_eq!(tiled.shape(), &[2, 2]);
+    assert_eq!(values(&tiled), [1.0, 2.0, 1.0, 2.0]);
+}
+
+#[test]
+fn tile_of_a_transposed_view() {
+    let t = matrix![[1, 2], [3, 4]];
+
+    let tiled = t.transpose().tile(&[1, 2]).materialize();
+    assert_eq!(values(&tiled), [1.0, 3.0, 1.0, 3.0, 2.0, 4.0, 2.0, 4.0]);
+}
+
+#[test]
+fn scalar_ops_after_tile() {
+    let t = vector![1, 2, 3];
+
+    let result = ((t.tile(&[2]) * 2.0) + 1.0).materialize();
+    assert_eq!(values(&result), [3.0, 5.0, 7.0, 3.0, 5.0, 7.0]);
+
+    let result = (t.repeat(2, 0).unwrap() - 1.0).materialize();
+    assert_eq!(values(&result), [0.0, 0.0, 1.0, 1.0, 2.0, 2.0]);
+}
+
+#[test]
+fn repeat_along_axis() {
+    let t = matrix![[1, 2], [3, 4]];
+
+    let rows = t.repeat(2, 0).unwrap().materialize();
+    assert_eq!(rows.shape(), &[4, 2]);
+    assert_eq!(values(&rows), [1.0, 2.0, 1.0, 2.0, 3.0, 4.0, 3.0, 4.0]);
+
+    let cols = t.repeat(3, 1).unwrap().materialize();
+    assert_eq!(cols.shape(), &[2, 6]);
+    assert_eq!(
+        values(&cols),
+        [1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0]
+    );
+
+    assert!(matches!(t.repeat(2, 2), Err(OpError::OutOfBoundAxes)));
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use simple_tensor::s;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, OuterProduct, Tensor};
+
+use common::{arange, values};
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn index_removes_the_axis() {
+    let x = arange(&[3, 4]);
+
+    let row = x.slice(s![1, ..]).unwrap().materialize();
+    assert_eq!(row.shape(), [4]);
+    assert_eq!(values(&row), [4.0, 5.0, 6.0, 7.0]);
+
+    let col = x.slice(s![.., -1]).unwrap().materialize();
+    assert_eq!(col.shape(), [3]);
+    assert_eq!(values(&col), [3.0, 7.0, 11.0]);
+
+    // The axes left out are kept whole.
+    let y = arange(&[2, 3, 4]);
+    let plane = y.slice(s![1]).unwrap().materialize();
+    assert_eq!(plane.shape(), [3, 4]);
+    assert_eq!(
+        values(&plane),
+        (12..24).map(|i| i as f64).collect::<Vec<_>>()
+    );
+
+    let element = y.slice(s![1, -2, 3]).unwrap().materialize();
+    assert_eq!(element.shape(), [1]);
+    assert_eq!(values(&element), [19.0]);
+}
+
+#[test]
+fn new_axis_adds_an_axis() {
+    let x = arange(&[3, 4]);
+
+    let middle = x.slice(s![.., NewAxis, ..]).unwrap();
+    assert_eq!(middle.shape(), [3, 1, 4]);
+    assert_eq!(values(&middle.materialize()), values(&x));
+
+    let around = x.slice(s![NewAxis, 0..2, NewAxis]).unwrap().materialize();
+    assert_eq!(around.shape(), [1, 2, 1, 4]);
+    assert_eq!(values(&around), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+
+    // It stays contiguous, so adding the axis to a whole tensor is free.
+    assert!(
+        x.slice(s![NewAxis])
+            .unwrap()
+            .materialize()
+            .layout()
+            .is_contiguous()
+    );
+}
+
+#[test]
+fn new_axes_of_a_vector() {
+    let v = arange(&[3]);
+
+    let column = v.slice(s![.., NewAxis]).unwrap();
+    assert_eq!(column.shape(), [3, 1]);
+    assert_eq!(column.layout().stride(), [1, 1]);
+    assert_eq!(column.layout().adj_stride(), [1, 1]);
+
+    let row = v.slice(s![NewAxis]).unwrap();
+    assert_eq!(row.shape(), [1, 3]);
+    assert_eq!(row.layout().stride(), [3, 1]);
+
+    // More entries than axes, as long as the extra ones are new axes.
+    let padded = v.slice(s![NewAxis, NewAxis, .., NewAxis]).unwrap();
+    assert_eq!(padded.shape(), [1, 1, 3, 1]);
+    assert_eq!(padded.layout().stride(), [3, 3, 1, 1]);
+    assert_eq!(padded.layout().adj_stride(), [1, 1, 1, 1]);
+
+    for view in [column, row, padded] {
+        assert_eq!(values(&view.materialize()), [0.0, 1.0, 2.0]);
+    }
+}
+
+#[test]
+fn new_axes_keep_the_strides_of_the_view() {
+    let x = arange(&[3, 4]);
+
+    let front = x.slice(s![NewAxis]).unwrap();
+    assert_eq!(front.shape(), [1, 3, 4]);
+    assert_eq!(front.layout().stride(), [12, 4, 1]);
+
+    let end = x.slice(s![.., .., NewAxis]).unwrap();
+    assert_eq!(end.shape(), [3, 4, 1]);
+    assert_eq!(end.layout().stride(), [4, 1, 1]);
+    assert_eq!(end.layout().adj_stride(), [1, 1, 1]);
+
+    // Between two stepped axes, the new one is given the stride of the axis after it.
+    let stepped = x.slice(s![..;2, NewAxis, ..;2]).unwrap();
+    assert_eq!(stepped.shape(), [2, 1, 2]);
+    assert_eq!(stepped.layout().stride(), [8, 4, 2]);
+    assert_eq!(values(&stepped.materialize()), [0.0, 2.0, 8.0, 10.0]);
+
+    // Printed like a tensor of that shape.
+    let expected = Tensor::from_iter((0..12).map(|i| i as f64), &[1, 3, 4]);
+    assert_eq!(format!("{}", front.materialize()), format!("{}", expected));
+}
+
+#[test]
+fn new_axes_do_not_take_an_axis() {
+    let x = arange(&[3, 4]);
+
+    // The index still checks the second axis.
+    let err = x.slice(s![NewAxis, .., 4]).err().unwrap();
+    assert!(matches!(err.cause(), OpError::InvalidSliceShape(4, 5)));
+
+    let err = arange(&[3]).slice(s![.., NewAxis, ..]).err().unwrap();
+    assert!(matches!(err.cause(), OpError::NotEnoughAxes(1, 2)));
+
+    let element = arange(&[3]).slice(s![-1, NewAxis]).unwrap();
+    assert_eq!(element.shape(), [1]);
+    assert_eq!(values(&element.materialize()), [2.0]);
+}
+
+#[test]
+fn outer_product_through_new_axes() {
+    let a = Tensor::from_vec(vec![1.0, 2.0, 3.0], &[3]);
+    let b = Tensor::from_vec(vec![10.0, 20.0, 30.0, 40.0], &[4]);
+
+    // a[:, None] * b[None, :], tiling in place of broadcasting.
+    let lhs = a.slice(s![.., NewAxis]).unwrap().tile(&[1, 4]);
+    let rhs = b.slice(s![NewAxis, ..]).unwrap().tile(&[3, 1]);
+    let product = (&lhs * &rhs).materialize();
+
+    let expected = a.outer(&b).unwrap().materialize();
+    assert_eq!(product.shape(), [3, 4]);
+    assert_eq!(values(&product), values(&expected));
+}
+
+#[test]
+fn steps_indexes_and_new_axes_together() {
+    let x = arange(&[4, 6]);
+
+    let picked = x.slice(s![3..0;-2, NewAxis, 2]).unwrap().materialize();
+    assert_eq!(picked.shape(), [2, 1]);
+    // Rows 2 and 0, as the range covers rows 0 to 2 walked backwards.
+    assert_eq!(values(&picked), [14.0, 2.0]);
+
+    let stepped = x.slice(s![-1, ..;3]).unwrap().materialize();
+    assert_eq!(values(&stepped), [18.0, 21.0]);
+}
+
+#[test]
+fn invalid_slices_fail() {
+    let x = arange(&[3, 4]);
+
+    let err = x.slice(s![3, ..]).err().unwrap();
+    assert!(matches!(err.cause(), OpError::InvalidSliceShape(3, 4)));
+
+    let err = x.slice(s![0, 0, 0]).err().unwrap();
+    assert!(matches!(err.cause(), OpError::NotEnoughAxes(2, 3)));
+}
+
+#[cfg(feature = "proptest")]
+mod random_slices {
+    use proptest::prelude::*;
+    use simple_tensor::tensor::SliceKind;
+    use simple_tensor::tensor::testing::proptest_strategies::{
+        arb_shape, arb_slice_range, arb_tensor,
+    };
+
+    use super::*;
+
+    // A tensor and one valid range per axis of it.
+    fn arb_sliced() -> impl Strategy<Value = (Tensor<f64>, Vec<SliceKind>)> {
+        arb_tensor::<f64>(arb_shape(4, 6)).prop_flat_map(|t| {
+            let ranges: Vec<_> = (t.shape().iter())
+                .map(|&len| arb_slice_range(len).prop_map(SliceKind::from))
+                .collect();
+
+            (Just(t), ranges)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn slices_iterate_over_as_many_elements_as_their_len((t, ranges) in arb_sliced()) {
+            let slice = t.slice(&ranges).unwrap().materialize();
+
+            prop_assert_eq!(slice.shape().len(), t.shape().len());
+            prop_assert!(slice.len() > 0);
+            prop_assert_eq!(slice.iter().count(), slice.len());
+            prop_assert_eq!(slice.len(), slice.shape().iter().product::<usize>());
+        }
+    }
+}
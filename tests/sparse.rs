@@ -0,0 +1,91 @@
+mod common;
+
+use simple_tensor::matrix;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, SparseTensor, Tensor, sparse_add};
+
+use common::values;
+
+fn sparse_matrix() -> Tensor<f64> {
+    matrix![[0, 0, 3, 0], [1, 0, 0, 0], [0, 0, 0, -2]]
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn from_dense_round_trip() {
+    let dense = sparse_matrix();
+    let sparse = SparseTensor::from_dense(&dense, 0.0);
+
+    assert_eq!(sparse.nnz(), 3);
+    assert_eq!(sparse.dense_shape(), &[3, 4]);
+    assert_eq!(sparse.indices().shape(), &[2, 3]);
+    assert_eq!(values(sparse.indices()), [0, 1, 2, 2, 0, 3]);
+    assert_eq!(values(sparse.values()), [3.0, 1.0, -2.0]);
+
+    let back = sparse.to_dense();
+    assert_eq!(back.shape(), dense.shape());
+    assert_eq!(values(&back), values(&dense));
+}
+
+#[test]
+fn from_dense_threshold() {
+    let dense = matrix![[0.5, -0.1], [0.05, -2.0]];
+    let sparse = SparseTensor::from_dense(&dense, 0.1);
+
+    assert_eq!(values(sparse.values()), [0.5, -2.0]);
+    assert_eq!(values(&sparse.to_dense()), [0.5, 0.0, 0.0, -2.0]);
+}
+
+#[test]
+fn repeated_positions_are_summed() {
+    let indices = Tensor::from_vec(vec![1, 1, 0, 2, 2, 0], &[2, 3]);
+    let sparse = SparseTensor::new(
+        indices,
+        Tensor::from_vec(vec![1.0, 2.0, 5.0], &[3]),
+        &[2, 3],
+    )
+    .unwrap();
+
+    assert_eq!(values(&sparse.to_dense()), [5.0, 0.0, 0.0, 0.0, 0.0, 3.0]);
+}
+
+#[test]
+fn sparse_add_matches_dense_sum() {
+    let a_dense = sparse_matrix();
+    let b_dense = matrix![[0, 1, -3, 0], [0, 0, 0, 0], [4, 0, 0, 2]];
+
+    let a = SparseTensor::from_dense(&a_dense, 0.0);
+    let b = SparseTensor::from_dense(&b_dense, 0.0);
+
+    let sum = sparse_add(&a, &b).unwrap();
+    let expected = (&a_dense + &b_dense).materialize();
+    assert_eq!(values(&sum.to_dense()), values(&expected));
+
+    // Positions present in both are merged, even if they cancel out.
+    assert_eq!(sum.nnz(), 5);
+    assert_eq!(values(sum.indices()), [0, 0, 1, 2, 2, 1, 2, 0, 0, 3]);
+
+    let other = SparseTensor::from_dense(&Tensor::from_scalar(1.0, &[4, 3]), 0.0);
+    assert!(matches!(
+        sparse_add(&a, &other),
+        Err(OpError::NotSameShape(_, _))
+    ));
+}
+
+#[test]
+fn new_validates_the_indices() {
+    let values = Tensor::from_vec(vec![1.0, 2.0], &[2]);
+
+    let indices = Tensor::from_vec(vec![0, 1, 0, 4], &[2, 2]);
+    assert!(matches!(
+        SparseTensor::new(indices, values.clone(), &[2, 3]),
+        Err(OpError::IndexOutOfBounds(4, 3))
+    ));
+
+    let indices = Tensor::from_vec(vec![0, 1], &[1, 2]);
+    assert!(matches!(
+        SparseTensor::new(indices, values, &[2, 3]),
+        Err(OpError::NotSameShape(_, _))
+    ));
+}
@@ -0,0 +1,203 @@
+mod common;
+
+use simple_tensor::s;
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{
+    DEFAULT_SPARSE_THRESHOLD, SparseZeros, Tensor, set_sparse_threshold, set_sparse_zeros,
+    sparse_threshold, sparse_zeros,
+};
+
+use common::{bits, lock_globals, values};
+
+// Result of `f` with the given settings, restoring the defaults afterwards.
+fn with_sparse<R>(zeros: SparseZeros, threshold: f32, f: impl FnOnce() -> R) -> R {
+    set_sparse_zeros(zeros);
+    set_sparse_threshold(threshold);
+    let result = f();
+    set_sparse_zeros(SparseZeros::Ieee);
+    set_sparse_threshold(DEFAULT_SPARSE_THRESHOLD);
+
+    result
+}
+
+fn random_values(len: usize, mut seed: u64) -> Vec<f64> {
+    (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        })
+        .collect()
+}
+
+// About `density` of ones, with a few of the zeros negative.
+fn random_mask(len: usize, density: f64, seed: u64) -> Tensor<f64> {
+    let mask = random_values(len, seed)
+        .into_iter()
+        .enumerate()
+        .map(|(i, u)| {
+            if u < density {
+                1.5
+            } else if i % 7 == 0 {
+                -0.0
+            } else {
+                0.0
+            }
+        });
+
+    Tensor::from_iter(mask, &[len])
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn hints_are_set_and_follow_the_elements() {
+    let _globals = lock_globals();
+    assert_eq!(sparse_zeros(), SparseZeros::Ieee);
+    assert_eq!(sparse_threshold(), DEFAULT_SPARSE_THRESHOLD);
+
+    let x = Tensor::from_vec(vec![0.0, 2.0, 0.0, 0.0, 0.0, 3.0], &[2, 3]);
+    assert_eq!(x.density_hint(), None);
+
+    let hinted = x.with_density_hint(1.0 / 3.0).unwrap();
+    assert_eq!(hinted.density_hint(), Some(1.0 / 3.0));
+    assert_eq!(values(&hinted), values(&x));
+
+    for density in [-0.1, 1.5, f32::NAN] {
+        let err = x.with_density_hint(density).err().unwrap();
+        assert!(matches!(err, OpError::InvalidDensity(_)));
+    }
+
+    // Reordering keeps the hint, selecting some of the elements drops it.
+    let transposed = hinted.transpose().materialize();
+    assert_eq!(transposed.density_hint(), Some(1.0 / 3.0));
+    let sliced = hinted.slice(s![.., 1..]).unwrap().materialize();
+    assert_eq!(sliced.density_hint(), None);
+
+    // A product is at most as dense as its sparsest operand.
+    let ones = Tensor::from_scalar(1.0, &[2, 3]);
+    let denser = ones.with_density_hint(0.9).unwrap();
+    assert_eq!(
+        (&hinted * &denser).materialize().density_hint(),
+        Some(1.0 / 3.0)
+    );
+    assert_eq!((&denser * &ones).materialize().density_hint(), Some(0.9));
+    assert_eq!((&ones * &ones).materialize().density_hint(), None);
+    assert_eq!((&hinted + &ones).materialize().density_hint(), None);
+}
+
+#[test]
+fn masks_and_dropout_count_their_density() {
+    let _globals = lock_globals();
+
+    let x = Tensor::from_vec(vec![1.0, f64::NAN, 3.0, f64::INFINITY], &[4]);
+    assert_eq!(x.isnan().materialize().density_hint(), Some(0.25));
+    assert_eq!(x.isinf().materialize().density_hint(), Some(0.25));
+
+    let x = Tensor::from_iter(
+        random_values(10_000, 3).into_iter().map(|u| u + 1.0),
+        &[10_000],
+    );
+    let dropped = x.dropout(0.95, 7).unwrap().materialize();
+    let nonzeros = dropped.iter().filter(|&&el| el != 0.0).count();
+    assert_eq!(dropped.density_hint(), Some(nonzeros as f32 / 10_000.0));
+    assert!(dropped.density_hint().unwrap() < 0.1);
+
+    // The hint reaches the Mul reading the mask, which then absorbs the zeros.
+    let inf = Tensor::from_scalar(f64::INFINITY, &[10_000]);
+    let product = with_sparse(SparseZeros::Absorbing, DEFAULT_SPARSE_THRESHOLD, || {
+        (x.dropout(0.95, 7).unwrap() * &inf).materialize()
+    });
+    assert!(product.iter().all(|el| !el.is_nan()));
+}
+
+#[test]
+fn sparse_products_are_the_dense_ones_bit_for_bit() {
+    let _globals = lock_globals();
+    let len = 4096;
+
+    let positive = Tensor::from_vec(random_values(len, 1), &[len]);
+    let signed = Tensor::from_iter(random_values(len, 2).into_iter().map(|u| u - 0.5), &[len]);
+    let mut nonfinite = random_values(len, 3);
+    nonfinite[10] = f64::INFINITY;
+    nonfinite[len - 1] = f64::NAN;
+    let nonfinite = Tensor::from_vec(nonfinite, &[len]);
+
+    for (seed, density) in [(4, 0.0), (5, 0.01), (6, 0.05)] {
+        let mask = random_mask(len, density, seed);
+        let hinted = mask.with_density_hint(density as f32).unwrap();
+
+        for dense in [&positive, &signed, &nonfinite] {
+            let expected = with_sparse(SparseZeros::Ieee, 0.0, || (&mask * dense).materialize());
+
+            let lhs = (&hinted * dense).materialize();
+            let rhs = (dense * &hinted).materialize();
+            assert_eq!(bits(&lhs), bits(&expected), "density {}", density);
+            assert_eq!(bits(&rhs), bits(&expected), "density {}", density);
+        }
+    }
+
+    // 0 * inf and 0 * NaN are NaN, and zeros take the sign of the product.
+    let mask = Tensor::from_vec(vec![0.0, -0.0, 0.0, 2.0, 0.0], &[5]);
+    let dense = Tensor::from_vec(vec![f64::INFINITY, 3.0, f64::NAN, 4.0, -1.0], &[5]);
+    let product = (&mask.with_density_hint(0.0).unwrap() * &dense).materialize();
+    let product = values(&product);
+    assert!(product[0].is_nan() && product[2].is_nan());
+    assert_eq!(product[1].to_bits(), (-0.0f64).to_bits());
+    assert_eq!(product[3], 8.0);
+    assert_eq!(product[4].to_bits(), (-0.0f64).to_bits());
+}
+
+#[test]
+fn absorbing_zeros_ignore_inf_and_nan() {
+    let _globals = lock_globals();
+
+    let mask = Tensor::from_vec(vec![0.0, -0.0, 0.0, 2.0, 0.0, 0.5], &[6]);
+    let dense = Tensor::from_vec(
+        vec![f64::INFINITY, f64::NAN, -1.0, 4.0, 7.0, f64::INFINITY],
+        &[6],
+    );
+    let hinted = mask.with_density_hint(0.05).unwrap();
+
+    let product = with_sparse(SparseZeros::Absorbing, DEFAULT_SPARSE_THRESHOLD, || {
+        (&dense * &hinted).materialize()
+    });
+    assert_eq!(
+        bits(&product),
+        bits(&Tensor::from_vec(
+            vec![0.0, 0.0, 0.0, 8.0, 0.0, f64::INFINITY],
+            &[6]
+        ))
+    );
+
+    // Operands that are not contiguous are multiplied densely.
+    let strided = Tensor::from_vec(vec![f64::INFINITY; 12], &[6, 2])
+        .slice(s![.., 0])
+        .unwrap();
+    let product = with_sparse(SparseZeros::Absorbing, DEFAULT_SPARSE_THRESHOLD, || {
+        (strided * &hinted).materialize()
+    });
+    assert!(product.iter().take(3).all(|el| el.is_nan()));
+}
+
+#[test]
+fn hints_above_the_threshold_are_multiplied_densely() {
+    let _globals = lock_globals();
+
+    let mask = Tensor::from_vec(vec![0.0, 1.0], &[2]);
+    let inf = Tensor::from_scalar(f64::INFINITY, &[2]);
+    let product = |density: f32, threshold: f32| {
+        let hinted = mask.with_density_hint(density).unwrap();
+        with_sparse(SparseZeros::Absorbing, threshold, || {
+            values(&(&hinted * &inf).materialize())[0]
+        })
+    };
+
+    // Only the absorbing sparse path makes 0 * inf a zero.
+    assert_eq!(product(0.05, DEFAULT_SPARSE_THRESHOLD), 0.0);
+    assert!(product(0.5, DEFAULT_SPARSE_THRESHOLD).is_nan());
+    assert_eq!(product(0.5, 0.6), 0.0);
+    assert!(product(0.0, 0.0).is_nan());
+    assert!(product(DEFAULT_SPARSE_THRESHOLD, DEFAULT_SPARSE_THRESHOLD).is_nan());
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "static_tensor")]
+
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, InnerProduct, StaticTensor, Tensor};
+
+use common::values;
+
+fn counting<const R: usize, const C: usize>(start: f64) -> StaticTensor<f64, R, C> {
+    StaticTensor::new(std::array::from_fn(|r| {
+        std::array::from_fn(|c| start + (r * C + c) as f64)
+    }))
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn elementwise_ops() {
+    let a = counting::<2, 3>(0.0);
+    let b = StaticTensor::from_scalar(2.0);
+
+    assert_eq!((a + b).rows(), &[[2.0, 3.0, 4.0], [5.0, 6.0, 7.0]]);
+    assert_eq!((a - b).rows(), &[[-2.0, -1.0, 0.0], [1.0, 2.0, 3.0]]);
+    assert_eq!((-a).rows(), &[[-0.0, -1.0, -2.0], [-3.0, -4.0, -5.0]]);
+    assert_eq!(a[(1, 2)], 5.0);
+}
+
+#[test]
+fn matrix_product() {
+    let a = counting::<2, 3>(1.0);
+    let b = counting::<3, 4>(-2.0);
+    let product: StaticTensor<f64, 2, 4> = a * b;
+
+    // Row i, column j of the product is the inner product of row i of a and column j of b.
+    let expected = a
+        .to_dynamic()
+        .inner(&b.transpose().to_dynamic())
+        .ok()
+        .unwrap()
+        .materialize();
+    assert_eq!(product.to_dynamic().shape(), [2, 4]);
+    assert_eq!(values(&product.to_dynamic()), values(&expected));
+
+    let identity = StaticTensor::new([[1.0, 0.0], [0.0, 1.0]]);
+    let square = counting::<2, 2>(3.0);
+    assert_eq!(square * identity, square);
+    assert_eq!(identity * square, square);
+}
+
+#[test]
+fn transpose() {
+    let a = counting::<2, 3>(0.0);
+    let t: StaticTensor<f64, 3, 2> = a.transpose();
+
+    assert_eq!(t.rows(), &[[0.0, 3.0], [1.0, 4.0], [2.0, 5.0]]);
+    assert_eq!(t.transpose(), a);
+}
+
+#[test]
+fn round_trip() {
+    let a = counting::<3, 2>(0.5);
+    let dynamic = a.to_dynamic();
+
+    assert_eq!(dynamic.shape(), [3, 2]);
+    assert_eq!(values(&dynamic), vec![0.5, 1.5, 2.5, 3.5, 4.5, 5.5]);
+    assert_eq!(dynamic.to_static::<3, 2>().unwrap(), a);
+
+    // Views are read in logical order.
+    let flipped = dynamic.transpose_axes(&[1, 0]).unwrap().materialize();
+    assert_eq!(flipped.to_static::<2, 3>().unwrap(), a.transpose());
+}
+
+#[test]
+fn to_static_checks_the_shape() {
+    let t = Tensor::from_iter((0..6).map(|i| i as f64), &[2, 3]);
+
+    match t.to_static::<3, 2>() {
+        Err(OpError::NotSameShape(expected, found)) => {
+            assert_eq!(&*expected, [3, 2]);
+            assert_eq!(&*found, [2, 3]);
+        }
+        other => panic!("expected NotSameShape, got {:?}", other),
+    }
+
+    let flat = Tensor::from_iter((0..6).map(|i| i as f64), &[6]);
+    assert!(flat.to_static::<1, 6>().is_err());
+}
+
+#[test]
+fn dimension_and_display() {
+    let a = counting::<2, 3>(0.0);
+
+    assert_eq!(a.shape(), [2, 3]);
+    assert_eq!(a.len(), 6);
+    assert_eq!(a.shape(), a.to_dynamic().shape());
+    assert_eq!(format!("{}", a), format!("{}", a.to_dynamic()));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, StepRange, StepSliceRange};
+use simple_tensor::{matrix, ss, vector};
+
+use common::values;
+
+#[test]
+fn forward_steps() {
+    let t = vector![0, 1, 2, 3, 4, 5, 6, 7, 8];
+
+    let stepped = t.step_slice(ss![0..9;3]).unwrap().materialize();
+    assert_eq!(stepped.shape(), &[3]);
+    assert_eq!(values(&stepped), [0.0, 3.0, 6.0]);
+
+    // The last step may not fit whole in the range.
+    let stepped = t.step_slice(ss![1..8;2]).unwrap().materialize();
+    assert_eq!(values(&stepped), [1.0, 3.0, 5.0, 7.0]);
+
+    let from_struct = StepSliceRange::from(StepRange {
+        start: 1,
+        end: 8,
+        step: 2,
+    });
+    let stepped = t.step_slice(&[from_struct]).unwrap().materialize();
+    assert_eq!(values(&stepped), [1.0, 3.0, 5.0, 7.0]);
+}
+
+#[test]
+fn negative_steps_reverse() {
+    let t = vector![0, 1, 2, 3, 4, 5, 6, 7, 8];
+
+    let reversed = t.step_slice(ss![9..0;-1]).unwrap().materialize();
+    assert_eq!(
+        values(&reversed),
+        [8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0, 0.0]
+    );
+
+    // The bounds select the same positions in either order.
+    let reversed = t.step_slice(ss![0..9;-1]).unwrap().materialize();
+    assert_eq!(
+        values(&reversed),
+        [8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0, 0.0]
+    );
+
+    let stepped = t.step_slice(ss![..;-2]).unwrap().materialize();
+    assert_eq!(values(&stepped), [8.0, 6.0, 4.0, 2.0, 0.0]);
+
+    let stepped = t.step_slice(ss![-3..;-1]).unwrap().materialize();
+    assert_eq!(values(&stepped), [8.0, 7.0, 6.0]);
+}
+
+#[test]
+fn steps_per_axis() {
+    let t = matrix![[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12]];
+
+    let sliced = t.step_slice(ss![..;-1, 0..4;2]).unwrap().materialize();
+    assert_eq!(sliced.shape(), &[3, 2]);
+    assert_eq!(values(&sliced), [9.0, 11.0, 5.0, 7.0, 1.0, 3.0]);
+
+    // Ranges without a step behave like `s!`.
+    let sliced = t.step_slice(ss![1..3, 3..0;-2]).unwrap().materialize();
+    assert_eq!(values(&sliced), [7.0, 5.0, 11.0, 9.0]);
+}
+
+#[test]
+fn stepping_a_view_and_its_results() {
+    let t = matrix![[1, 2, 3], [4, 5, 6]].transpose();
+
+    let sliced = t.step_slice(ss![..;2, ..;-1]).unwrap();
+    assert_eq!(values(&sliced.clone().materialize()), [4.0, 1.0, 6.0, 3.0]);
+
+    let doubled = (&sliced * 2.0).materialize();
+    assert_eq!(values(&doubled), [8.0, 2.0, 12.0, 6.0]);
+
+    let again = doubled.step_slice(ss![..;-1]).unwrap().materialize();
+    assert_eq!(values(&again), [12.0, 6.0, 8.0, 2.0]);
+}
+
+#[test]
+fn invalid_ranges() {
+    let t = vector![0, 1, 2, 3];
+
+    assert!(matches!(
+        t.step_slice(ss![3..1;2]),
+        Err(OpError::OutOfBoundSlice)
+    ));
+    assert!(matches!(
+        t.step_slice(ss![2..2;-1]),
+        Err(OpError::OutOfBoundSlice)
+    ));
+    assert!(matches!(
+        t.step_slice(ss![6..0;-1]),
+        Err(OpError::InvalidSliceShape(4, 6))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "the step of a slice can't be zero")]
+fn zero_step_panics() {
+    let t = vector![0, 1, 2, 3];
+    let _ = t.step_slice(ss![0..4;0]);
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Tensor, TensorPromise};
+use simple_tensor::{s, vector};
+
+use common::values;
+
+fn random_tensor(shape: &[usize], mut seed: u64) -> Tensor<f64> {
+    let len = shape.iter().product();
+    let data = (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 20.0 - 10.0
+        })
+        .collect();
+
+    Tensor::from_vec(data, shape)
+}
+
+// Streams the promise and checks the chunks cover the output in order.
+fn stream(promise: &TensorPromise<f64>, chunk_elems: usize) -> Vec<f64> {
+    let mut out = Vec::new();
+
+    promise
+        .materialize_streamed(chunk_elems, |chunk, offset| {
+            assert_eq!(offset, out.len());
+            assert!(!chunk.is_empty() && chunk.len() <= chunk_elems);
+            out.extend_from_slice(chunk);
+        })
+        .unwrap();
+
+    out
+}
+
+fn assert_bitwise_eq(got: &[f64], expected: &[f64]) {
+    assert_eq!(got.len(), expected.len());
+
+    for (g, e) in got.iter().zip(expected) {
+        assert_eq!(g.to_bits(), e.to_bits(), "{} != {}", g, e);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn matches_materialize_for_any_chunk_size() {
+    let a = random_tensor(&[13, 7], 1);
+    let b = random_tensor(&[13, 7], 2);
+
+    let graph = ((&a * 3.0 + &b) / (&b * &b + 1.0) - &a).isnan() + &a * 0.5;
+    let expected = values(&graph.clone().materialize());
+
+    // Sizes that divide the output evenly or not, and bigger than the output.
+    for chunk_elems in [1, 2, 7, 10, 64, 91, 1000] {
+        assert_bitwise_eq(&stream(&graph, chunk_elems), &expected);
+    }
+}
+
+#[test]
+fn strided_leaves() {
+    let a = random_tensor(&[6, 9], 3);
+    let b = random_tensor(&[9, 6], 4);
+    let c = random_tensor(&[8, 12], 5);
+
+    let transposed = a.transpose();
+    let sliced = c.slice(s![1..7, 2..11]).unwrap().transpose();
+    let flipped = b.flip_view(&[0, 1]).unwrap();
+
+    let graph = &(&transposed * 2.0) + &sliced;
+    let graph = &graph - &flipped;
+    let expected = values(&graph.clone().materialize());
+
+    for chunk_elems in [1, 5, 16, 54] {
+        assert_bitwise_eq(&stream(&graph, chunk_elems), &expected);
+    }
+
+    // A view of a computed node keeps its row-major order.
+    let reshaped = graph.as_contiguous().view(&[6, 9]).unwrap();
+    assert_bitwise_eq(&stream(&reshaped, 4), &expected);
+}
+
+#[test]
+fn views_of_a_single_tensor() {
+    let a = random_tensor(&[4, 5], 6);
+
+    let graph = a.transpose_axes(&[1, 0]).unwrap();
+    let expected = values(&graph.clone().materialize());
+
+    assert_bitwise_eq(&stream(&graph, 3), &expected);
+}
+
+#[test]
+fn scalar_tensors_are_broadcast() {
+    let a = random_tensor(&[5, 5], 7);
+    let scale = vector![2.5];
+
+    let graph = &(&a * &scale) - &(&scale + 1.0);
+    let expected = values(&graph.clone().materialize());
+
+    assert_bitwise_eq(&stream(&graph, 4), &expected);
+}
+
+#[test]
+fn cached_promises_can_be_streamed() {
+    let a = random_tensor(&[3, 4], 8);
+    let cached = (&a + 1.0).cache();
+    let expected = values(&cached.clone().materialize());
+
+    // Once filled, the cache is read like a tensor.
+    let mut out = Vec::new();
+    cached
+        .materialize_streamed(5, |chunk, _| out.extend_from_slice(chunk))
+        .unwrap();
+    assert_bitwise_eq(&out, &expected);
+}
+
+#[test]
+fn rejects_graphs_that_are_not_elementwise() {
+    let a = random_tensor(&[4, 6], 9);
+
+    let reduced = (&a * 2.0).any(1).unwrap() + 1.0;
+    let Err(err) = reduced.materialize_streamed(4, |_, _| {}) else {
+        panic!("reductions cannot be streamed");
+    };
+    assert!(matches!(&err, OpError::NotStreamable(nodes) if nodes.len() == 1));
+    assert!(err.to_string().contains("Any (node "));
+
+    let rolled = a.rolling_sum(2, 0).unwrap();
+    let tiled = a.tile(&[2, 1]);
+    let both = rolled.view(&[18]).unwrap() - tiled.view(&[48]).unwrap().slice(s![..18]).unwrap();
+
+    match both.materialize_streamed(4, |_, _| {}) {
+        Err(OpError::NotStreamable(nodes)) => {
+            let ops: Vec<&str> = nodes.iter().map(|(_, op)| *op).collect();
+            // The slice reads a computed node, so it is listed too.
+            assert_eq!(nodes.len(), 2);
+            assert!(ops.contains(&"RollingSum") && ops.contains(&"Slice"));
+        }
+        _ => panic!("rolling windows cannot be streamed"),
+    }
+
+    assert!(matches!(
+        (&a + 1.0).materialize_streamed(0, |_, _| {}),
+        Err(OpError::ZeroChunkSize)
+    ));
+}
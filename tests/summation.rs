@@ -0,0 +1,247 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::nn::{Reduction, mae_loss};
+use simple_tensor::tensor::{
+    DEFAULT_PAIRWISE_BLOCK, InnerProduct, Summation, Tensor, pairwise_block, set_pairwise_block,
+    set_summation, summation,
+};
+use simple_tensor::vector;
+
+use common::lock_globals;
+
+// Sum of `f` with the given settings, restoring the defaults afterwards.
+fn with_summation<R>(mode: Summation, block: usize, f: impl FnOnce() -> R) -> R {
+    set_summation(mode);
+    set_pairwise_block(block);
+    let result = f();
+    set_summation(Summation::Pairwise);
+    set_pairwise_block(DEFAULT_PAIRWISE_BLOCK);
+
+    result
+}
+
+fn naive(values: &[f64]) -> f64 {
+    values.iter().fold(0.0, |acc, x| acc + x)
+}
+
+// Exact sum rounded once, with Shewchuk's algorithm as in Python's math.fsum.
+fn exact(values: &[f64]) -> f64 {
+    let mut partials: Vec<f64> = Vec::new();
+
+    for &x in values {
+        let mut x = x;
+        let mut kept = 0;
+
+        for i in 0..partials.len() {
+            let y = partials[i];
+            let (big, small) = if x.abs() < y.abs() { (y, x) } else { (x, y) };
+            let high = big + small;
+            let low = small - (high - big);
+
+            if low != 0.0 {
+                partials[kept] = low;
+                kept += 1;
+            }
+            x = high;
+        }
+
+        partials.truncate(kept);
+        partials.push(x);
+    }
+
+    partials.iter().rev().fold(0.0, |acc, x| acc + x)
+}
+
+fn random_values(len: usize, mut seed: u64) -> Vec<f64> {
+    (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+        })
+        .collect()
+}
+
+#[test]
+fn pairwise_is_the_default() {
+    let _globals = lock_globals();
+
+    assert_eq!(summation(), Summation::Pairwise);
+    assert_eq!(pairwise_block(), DEFAULT_PAIRWISE_BLOCK);
+}
+
+#[test]
+fn kahan_keeps_cancelling_terms() {
+    let _globals = lock_globals();
+    let values: Vec<f64> = [1e16, 1.0, -1e16].repeat(1000);
+    let t = Tensor::from_vec(values.clone(), &[values.len()]);
+
+    assert_eq!(exact(&values), 1000.0);
+    assert_eq!(naive(&values), 0.0);
+    assert_eq!(t.sum_scalar(), 0.0);
+    assert_eq!(
+        with_summation(Summation::Kahan, 1, || t.sum_scalar()),
+        1000.0
+    );
+}
+
+#[test]
+fn pairwise_and_kahan_are_closer_to_the_exact_sum() {
+    let _globals = lock_globals();
+    let values = vec![0.1; 1_000_000];
+    let t = Tensor::from_vec(values.clone(), &[values.len()]);
+    let reference = exact(&values);
+
+    let naive_error = (naive(&values) - reference).abs();
+    let pairwise_error = (t.sum_scalar() - reference).abs();
+    let kahan = with_summation(Summation::Kahan, 1, || t.sum_scalar());
+
+    assert!(naive_error > 1e-7, "{}", naive_error);
+    assert!(pairwise_error < naive_error / 100.0, "{}", pairwise_error);
+    assert_eq!(kahan, reference);
+}
+
+#[test]
+fn large_terms_with_a_tiny_sum() {
+    let _globals = lock_globals();
+    // Every value comes with its negation a long way after it, and 1e-3 breaks the tie.
+    let half = random_values(500_000, 7);
+    let mut values: Vec<f64> = half.iter().map(|x| x * 1e8).collect();
+    values.extend(half.iter().map(|x| -x * 1e8));
+    values.push(1e-3);
+
+    let t = Tensor::from_vec(values.clone(), &[values.len()]);
+    let reference = exact(&values);
+    assert_eq!(reference, 1e-3);
+
+    let naive_error = (naive(&values) - reference).abs();
+    let pairwise_error = (t.sum_scalar() - reference).abs();
+    let kahan_error = (with_summation(Summation::Kahan, 1, || t.sum_scalar()) - reference).abs();
+
+    assert!(
+        pairwise_error < naive_error,
+        "{} {}",
+        pairwise_error,
+        naive_error
+    );
+    assert!(kahan_error < 1e-12, "{}", kahan_error);
+}
+
+#[test]
+fn blocks_as_long_as_the_tensor_are_the_naive_sum() {
+    let _globals = lock_globals();
+    let values = random_values(10_000, 3);
+    let t = Tensor::from_vec(values.clone(), &[values.len()]);
+
+    let sum = with_summation(Summation::Pairwise, usize::MAX, || t.sum_scalar());
+
+    assert_eq!(sum.to_bits(), naive(&values).to_bits());
+}
+
+#[test]
+fn sums_are_reproducible_whatever_the_layout() {
+    let _globals = lock_globals();
+    let values = random_values(37 * 1001, 11);
+    let t = Tensor::from_vec(values, &[37, 1001]);
+
+    // The same elements in the same logical order, once strided and once contiguous.
+    let strided = t.transpose().materialize();
+    let contiguous = Tensor::from_vec(strided.iter().copied().collect(), &[1001, 37]);
+    assert!(strided.with_raw(|_, _| ()).is_err());
+
+    for block in [1, 7, 100, DEFAULT_PAIRWISE_BLOCK, 1000] {
+        let (a, b) = with_summation(Summation::Pairwise, block, || {
+            (strided.sum_scalar(), contiguous.sum_scalar())
+        });
+
+        assert_eq!(a.to_bits(), b.to_bits(), "block of {}", block);
+    }
+
+    let (a, b) = with_summation(Summation::Kahan, 1, || {
+        (strided.sum_scalar(), contiguous.sum_scalar())
+    });
+    assert_eq!(a.to_bits(), b.to_bits());
+}
+
+#[test]
+fn lane_reductions_sum_like_the_scalar_ones() {
+    let _globals = lock_globals();
+    let values = random_values(5000, 5);
+    let t = Tensor::from_vec(values, &[50, 100]);
+    let strided = t.transpose().materialize();
+    let zeros = Tensor::from_scalar(0.0, &[100, 50]);
+
+    let loss = mae_loss(&strided, &zeros, Reduction::Sum)
+        .unwrap()
+        .materialize();
+
+    assert_eq!(
+        loss.iter().next().unwrap().to_bits(),
+        strided.abs_sum().to_bits()
+    );
+}
+
+#[test]
+fn mean_divides_the_sum() {
+    let _globals = lock_globals();
+    let t = Tensor::from_vec(vec![0.1; 1_000_000], &[1000, 1000]);
+
+    assert_eq!(t.mean_scalar(), t.sum_scalar() / 1e6);
+    assert!((t.mean_scalar() - 0.1).abs() < 1e-15);
+    assert_eq!(vector![1.0, 2.0, 4.0].mean_scalar(), 7.0 / 3.0);
+    assert!(Tensor::<f64>::from_vec(vec![], &[0]).mean_scalar().is_nan());
+    assert_eq!(Tensor::<f64>::from_vec(vec![], &[0]).sum_scalar(), 0.0);
+}
+
+#[test]
+fn dot_sums_the_products() {
+    let _globals = lock_globals();
+    let values = random_values(3000, 13);
+    let x = Tensor::from_vec(values.clone(), &[3000]);
+    let ones = Tensor::from_scalar(1.0, &[3000]);
+
+    assert_eq!(x.dot(&ones).unwrap().to_bits(), x.sum_scalar().to_bits());
+    assert_eq!(
+        vector![1.0, 2.0, 3.0].dot(&vector![4.0, 5.0, 6.0]).unwrap(),
+        32.0
+    );
+
+    let inner = x.inner(&ones).unwrap().materialize();
+    assert_eq!(
+        inner.iter().next().unwrap().to_bits(),
+        x.sum_scalar().to_bits()
+    );
+
+    assert!(matches!(
+        x.dot(&vector![1.0]),
+        Err(OpError::NotSameShape(_, _))
+    ));
+
+    let values: Vec<f64> = [1e16, 1.0, -1e16].repeat(10);
+    let x = Tensor::from_vec(values, &[30]);
+    let ones = Tensor::from_scalar(1.0, &[30]);
+    assert_eq!(
+        with_summation(Summation::Kahan, 1, || x.dot(&ones).unwrap()),
+        10.0
+    );
+}
+
+#[test]
+fn kahan_keeps_infinities_and_nans() {
+    let _globals = lock_globals();
+
+    let sums = with_summation(Summation::Kahan, 1, || {
+        [
+            vector![f64::INFINITY, 1.0].sum_scalar(),
+            vector![1.0, f64::NEG_INFINITY].sum_scalar(),
+            vector![f64::INFINITY, f64::NEG_INFINITY].sum_scalar(),
+            vector![1.0, f64::NAN].sum_scalar(),
+        ]
+    });
+
+    assert_eq!(sums[0], f64::INFINITY);
+    assert_eq!(sums[1], f64::NEG_INFINITY);
+    assert!(sums[2].is_nan() && sums[3].is_nan());
+}
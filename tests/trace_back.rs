@@ -0,0 +1,79 @@
+use simple_tensor::tensor::{Tensor, detect_nan_in_graph};
+use simple_tensor::vector;
+
+// The id printed by trace_back() for a TensorId.
+fn raw_id(id: impl std::fmt::Debug) -> String {
+    let id = format!("{:?}", id);
+    id.trim_start_matches("TensorId(")
+        .trim_end_matches(')')
+        .to_string()
+}
+
+#[test]
+fn lists_the_nodes_in_topological_order() {
+    let a = vector![1.0, 2.0, 3.0];
+    let b = vector![3.0, 2.0, 1.0];
+
+    let sum = &a + &b;
+    let product = &sum * &a;
+
+    let trace = product.trace_back(usize::MAX);
+    assert_eq!(
+        trace,
+        [
+            format!("Node {}: Tensor (layout: [3])", raw_id(a.id())),
+            format!("Node {}: Tensor (layout: [3])", raw_id(b.id())),
+            format!("Node {}: Add (layout: [3])", raw_id(sum.id())),
+            format!("Node {}: Mul (layout: [3])", raw_id(product.id())),
+        ]
+    );
+}
+
+#[test]
+fn stops_at_max_depth() {
+    let a = vector![1.0, 2.0, 3.0, 4.0];
+    let sum = &a + &a;
+    let rolled = sum.rolling_sum(2, 0).unwrap();
+    let diff = rolled.diff(1, 0).unwrap();
+
+    assert_eq!(diff.trace_back(0).len(), 1);
+    assert!(diff.trace_back(0)[0].starts_with(&format!("Node {}: Diff", raw_id(diff.id()))));
+
+    let trace = diff.trace_back(2);
+    assert_eq!(trace.len(), 3);
+    assert!(trace[0].starts_with(&format!("Node {}: Add", raw_id(sum.id()))));
+    assert!(trace.iter().all(|line| !line.contains("Tensor")));
+
+    assert_eq!(diff.trace_back(3).len(), 4);
+}
+
+#[test]
+fn finds_the_division_making_a_nan() {
+    let zeros = Tensor::from_scalar(0.0, &[4]);
+    let ones = Tensor::from_scalar(1.0, &[4]);
+
+    let sum = &zeros + &ones;
+    let nan = &zeros / &zeros;
+    let result = &sum * &nan;
+
+    assert_eq!(
+        detect_nan_in_graph(result.clone(), usize::MAX),
+        Some(nan.id())
+    );
+    assert!(
+        (result.trace_back(1).iter())
+            .any(|line| *line == format!("Node {}: Div (layout: [4])", raw_id(nan.id())))
+    );
+
+    // Only the tensors and the sum are computed.
+    assert_eq!(detect_nan_in_graph(result.clone(), 3), None);
+    assert_eq!(detect_nan_in_graph(sum, usize::MAX), None);
+}
+
+#[test]
+fn finds_nans_in_tensors() {
+    let a = vector![1.0, f64::NAN];
+    let b = vector![1.0, 2.0];
+
+    assert_eq!(detect_nan_in_graph(&b + &a, usize::MAX), Some(a.id()));
+}
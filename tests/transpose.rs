@@ -0,0 +1,72 @@
+mod common;
+
+use simple_tensor::tensor::errors::OpError;
+use simple_tensor::tensor::{Dimension, GraphRewriter, TensorPromise};
+
+use common::{arange, values};
+
+// Ops of every node of the graph, from the tensors up.
+fn ops(promise: &TensorPromise<f64>) -> Vec<&'static str> {
+    let mut ops = Vec::new();
+
+    GraphRewriter::new(|view| {
+        ops.push(view.op().map_or("Tensor", |op| op.as_str()));
+        None
+    })
+    .rewrite(promise)
+    .unwrap();
+
+    ops
+}
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn swap_axes_of_a_matrix() {
+    let x = arange(&[2, 3]);
+
+    let swapped = x.swap_axes(0, 1).unwrap().materialize();
+    assert_eq!(swapped.shape(), [3, 2]);
+    assert_eq!(values(&swapped), [0.0, 3.0, 1.0, 4.0, 2.0, 5.0]);
+
+    let same = x.swap_axes(1, 1).unwrap().materialize();
+    assert_eq!(values(&same), values(&x));
+}
+
+#[test]
+fn swap_axes_leaves_the_others() {
+    let x = arange(&[2, 3, 4]);
+    let swapped = x.swap_axes(2, 0).unwrap().materialize();
+
+    assert_eq!(swapped.shape(), [4, 3, 2]);
+    let expected = x.transpose_axes(&[2, 1, 0]).unwrap().materialize();
+    assert_eq!(values(&swapped), values(&expected));
+
+    let err = x.swap_axes(0, 3).err().unwrap();
+    assert!(matches!(err, OpError::OutOfBoundAxes));
+}
+
+#[test]
+fn transposes_that_cancel_out_fuse_away() {
+    let x = arange(&[2, 3, 4]);
+
+    let back = x.transpose().transpose();
+    assert_eq!(ops(&back), ["Tensor", "NoOp"]);
+    assert_eq!(values(&back.materialize()), values(&x));
+
+    let back = x.swap_axes(0, 2).unwrap().swap_axes(0, 2).unwrap();
+    assert_eq!(ops(&back), ["Tensor", "NoOp"]);
+    assert_eq!(values(&back.materialize()), values(&x));
+}
+
+#[test]
+fn permutations_fuse_into_one() {
+    let x = arange(&[2, 3, 4]);
+
+    let twice = x.swap_axes(0, 1).unwrap().swap_axes(1, 2).unwrap();
+    assert_eq!(ops(&twice), ["Tensor", "TransposeAxes"]);
+
+    let expected = x.transpose_axes(&[1, 2, 0]).unwrap().materialize();
+    assert_eq!(twice.shape(), expected.shape());
+    assert_eq!(values(&twice.materialize()), values(&expected));
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use simple_tensor::tensor::{Dimension, Tensor};
+
+use common::{arange, bits, values};
+
+// A view of the last two axes of `base` swapped, `[.., rows, cols]` over a buffer laid out
+// as `[.., cols, rows]` starting at `offset`, and the same elements gathered one by one.
+fn transposed(outer: usize, rows: usize, cols: usize, offset: usize) -> (Tensor<f64>, Vec<f64>) {
+    let matrix = rows * cols;
+    let base = arange(&[offset + outer * matrix]);
+    let view = base
+        .as_strided(
+            &[outer, rows, cols],
+            &[matrix as i32, 1, rows as i32],
+            offset,
+        )
+        .unwrap();
+
+    let mut expected = Vec::with_capacity(outer * matrix);
+    for m in 0..outer {
+        for r in 0..rows {
+            for c in 0..cols {
+                expected.push((offset + m * matrix + c * rows + r) as f64);
+            }
+        }
+    }
+
+    (view, expected)
+}
+
+// Single rows and columns, sizes around the block of 32 and several matrices.
+const SIZES: [(usize, usize, usize); 9] = [
+    (1, 1, 40),
+    (1, 40, 1),
+    (1, 2, 2),
+    (1, 31, 33),
+    (1, 32, 32),
+    (1, 33, 31),
+    (1, 65, 40),
+    (3, 33, 70),
+    (2, 100, 2),
+];
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn compacting_a_transposed_view_is_exact() {
+    for (outer, rows, cols) in SIZES {
+        for offset in [0, 5] {
+            let (view, expected) = transposed(outer, rows, cols, offset);
+            let dense = view.as_contiguous().materialize();
+
+            assert_eq!(dense.shape(), &[outer, rows, cols]);
+            assert_eq!(
+                bits(&dense),
+                bits(&Tensor::from_vec(expected, &[outer, rows, cols])),
+                "{outer}x{rows}x{cols} at {offset}"
+            );
+        }
+    }
+}
+
+#[test]
+fn transposed_operands_are_read_in_either_position() {
+    for (outer, rows, cols) in SIZES {
+        let (view, expected) = transposed(outer, rows, cols, 3);
+        let other = Tensor::from_iter(
+            (0..outer * rows * cols).map(|i| (i as f64).sqrt()),
+            &[outer, rows, cols],
+        );
+        let others = values(&other);
+
+        let lhs = values(&(&view - &other).materialize());
+        let rhs = values(&(&other - &view).materialize());
+        let both = values(&(&view * &view).materialize());
+
+        for i in 0..expected.len() {
+            assert_eq!(lhs[i].to_bits(), (expected[i] - others[i]).to_bits());
+            assert_eq!(rhs[i].to_bits(), (others[i] - expected[i]).to_bits());
+            assert_eq!(both[i].to_bits(), (expected[i] * expected[i]).to_bits());
+        }
+    }
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use simple_tensor::tensor::{Dimension, Tensor};
+use simple_tensor::{matrix, vector};
+
+use common::values;
+
+//////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn duplicates_are_counted_in_ascending_order() {
+    let x = vector![3.5, -1.0, 2.0, 3.5, 2.0, 3.5, 10.0];
+
+    assert_eq!(
+        x.value_counts_map(),
+        [(-1.0, 1), (2.0, 2), (3.5, 3), (10.0, 1)]
+    );
+
+    let unique = x.unique();
+    assert_eq!(unique.shape(), &[4]);
+    assert_eq!(values(&unique), [-1.0, 2.0, 3.5, 10.0]);
+
+    // The counts are aligned with the sorted values.
+    let (unique, counts) = x.unique_with_counts();
+    assert_eq!(values(&unique), [-1.0, 2.0, 3.5, 10.0]);
+    assert_eq!(counts.shape(), &[4]);
+    assert_eq!(values(&counts), [1, 2, 3, 1]);
+
+    // Any shape is read as its elements.
+    let m = matrix![[2, 1], [1, 2], [2, 2]];
+    assert_eq!(m.value_counts_map(), [(1.0, 2), (2.0, 4)]);
+}
+
+#[test]
+fn signed_zeros_are_the_same_value() {
+    let x = vector![0.0, -0.0, 1.0, -0.0];
+
+    let counts = x.value_counts_map();
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[0].0, 0.0);
+    assert_eq!(counts[0].1, 3);
+    assert_eq!(counts[1], (1.0, 1));
+
+    let (unique, counts) = x.unique_with_counts();
+    assert_eq!(unique.shape(), &[2]);
+    assert_eq!(values(&counts), [3, 1]);
+}
+
+#[test]
+fn nans_are_grouped_at_the_end() {
+    let x = vector![
+        f64::NAN,
+        2.0,
+        f64::NAN,
+        f64::INFINITY,
+        -f64::INFINITY,
+        f64::NAN
+    ];
+
+    let (unique, counts) = x.unique_with_counts();
+    let unique = values(&unique);
+
+    assert_eq!(unique.len(), 4);
+    assert_eq!(unique[..3], [-f64::INFINITY, 2.0, f64::INFINITY]);
+    assert!(unique[3].is_nan());
+    assert_eq!(values(&counts), [1, 1, 1, 3]);
+}
+
+#[test]
+fn integer_elements_are_counted() {
+    let x = Tensor::from_vec(vec![5i64, -3, 5, 0, -3, 5], &[2, 3]);
+
+    assert_eq!(x.value_counts_map(), [(-3, 2), (0, 1), (5, 3)]);
+    assert_eq!(values(&x.unique()), [-3, 0, 5]);
+
+    let x = Tensor::from_vec(vec![7u8, 7, 1], &[3]);
+    let (unique, counts) = x.unique_with_counts();
+    assert_eq!(values(&unique), [1, 7]);
+    assert_eq!(values(&counts), [1, 2]);
+}
+
+#[test]
+fn a_single_repeated_value_is_one_group() {
+    let x = Tensor::from_scalar(4.25, &[3, 5]);
+
+    assert_eq!(x.value_counts_map(), [(4.25, 15)]);
+
+    let (unique, counts) = x.unique_with_counts();
+    assert_eq!(unique.shape(), &[1]);
+    assert_eq!(values(&unique), [4.25]);
+    assert_eq!(values(&counts), [15]);
+}
+
+#[test]
+fn empty_tensors_have_no_values() {
+    let x = Tensor::from_vec(Vec::<f64>::new(), &[0, 3]);
+
+    assert!(x.value_counts_map().is_empty());
+
+    let (unique, counts) = x.unique_with_counts();
+    assert_eq!(unique.shape(), &[0]);
+    assert_eq!(counts.shape(), &[0]);
+    assert!(values(&x.unique()).is_empty());
+}